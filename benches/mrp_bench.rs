@@ -1,13 +1,36 @@
 //! MRP 性能基準測試
+//!
+//! 以 `mrp-examples` 產生的範例資料集量測完整計算（BOM 展開、淨需求、批量、追溯）
 
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use mrp_examples::{SampleDataset, SampleSpec};
 
 fn benchmark_mrp_calculation(c: &mut Criterion) {
-    c.bench_function("mrp_calculation", |b| {
-        b.iter(|| {
-            // TODO: 實現 MRP 計算基準測試
-        })
-    });
+    let mut group = c.benchmark_group("mrp_calculation");
+    group.sample_size(10);
+    for (name, spec) in [
+        ("small", SampleSpec::small()),
+        ("medium", SampleSpec::medium()),
+    ] {
+        let dataset = SampleDataset::generate(&spec);
+        let calculator = dataset.calculator();
+        group.bench_with_input(
+            BenchmarkId::new(name, dataset.item_count()),
+            &dataset,
+            |b, dataset| {
+                b.iter(|| {
+                    calculator
+                        .calculate(
+                            dataset.demands.clone(),
+                            dataset.supplies.clone(),
+                            dataset.inventories.clone(),
+                        )
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
 }
 
 criterion_group!(benches, benchmark_mrp_calculation);
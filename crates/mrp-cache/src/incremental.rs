@@ -1,11 +1,290 @@
 //! 增量計算
 
+use mrp_calc::coverage::CoverageCalculator;
+use mrp_calc::{MrpCalculator, MrpResult};
+use mrp_core::{Demand, Inventory, ProcurementType, Result, Supply};
+use std::collections::{BTreeSet, HashSet};
+
 /// 增量計算器
-pub struct IncrementalCalculator;
+///
+/// 只重算有變更的物料及其 BOM 下階物料，其餘物料沿用上次結果。
+/// 未受影響的父件以上次的生產計劃訂單（視為確認計劃供應）展開受影響子件的相依需求
+pub struct IncrementalCalculator<'a> {
+    calculator: &'a MrpCalculator,
+}
+
+impl<'a> IncrementalCalculator<'a> {
+    /// 創建增量計算器
+    pub fn new(calculator: &'a MrpCalculator) -> Self {
+        Self { calculator }
+    }
+
+    /// 受影響物料：有變更的物料及其所有 BOM 下階物料（含計劃 BOM 的變體）
+    pub fn affected_components(&self, dirty: &[String]) -> BTreeSet<String> {
+        let mut affected = BTreeSet::new();
+        let mut stack: Vec<String> = dirty.to_vec();
+        while let Some(component_id) = stack.pop() {
+            if !affected.insert(component_id.clone()) {
+                continue;
+            }
+            stack.extend(self.children(&component_id));
+        }
+        affected
+    }
 
-impl IncrementalCalculator {
     /// 增量 MRP 計算
-    pub fn calculate_incremental() {
-        // TODO: 實現增量計算
+    ///
+    /// 以完整的輸入呼叫，`previous` 為上次（完整或增量）的計算結果；
+    /// 只有 `dirty` 物料及其下階物料重新計算，結果合併回 `previous` 的副本
+    pub fn calculate_incremental(
+        &self,
+        previous: &MrpResult,
+        dirty: &[String],
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+    ) -> Result<MrpResult> {
+        let affected = self.affected_components(dirty);
+
+        // 未受影響、但有受影響子件的父件：保留上次訂單，只展開受影響子件
+        let mut anchor_parents: HashSet<String> = HashSet::new();
+        let mut bom_overrides = Vec::new();
+        for parent_id in self.parents_of(&affected, previous, &demands, &supplies) {
+            let Some(config) = self.calculator.config(&parent_id) else {
+                continue;
+            };
+            if config
+                .planning_percentages
+                .keys()
+                .any(|variant_id| affected.contains(variant_id))
+            {
+                // 計劃 BOM 的產品族以原需求重新分配到變體（產品族本身不產生訂單）
+                anchor_parents.insert(parent_id);
+                continue;
+            }
+            if config.procurement_type != ProcurementType::Make {
+                continue;
+            }
+            let children: Vec<_> = self
+                .calculator
+                .bom_children(&parent_id)
+                .into_iter()
+                .filter(|item| affected.contains(item.child_id.as_str()))
+                .collect();
+            anchor_parents.insert(parent_id.clone());
+            bom_overrides.push((parent_id, children));
+        }
+
+        let in_scope = |component_id: &str| affected.contains(component_id);
+        let mut partial_supplies: Vec<Supply> = supplies
+            .into_iter()
+            .filter(|s| {
+                in_scope(&s.component_id)
+                    || (anchor_parents.contains(&s.component_id) && s.is_firm && s.is_planned())
+            })
+            .collect();
+        partial_supplies.extend(
+            previous
+                .planned_orders
+                .iter()
+                .filter(|o| anchor_parents.contains(&o.component_id))
+                .map(|o| o.to_firm_supply()),
+        );
+        let partial_demands: Vec<Demand> = demands
+            .iter()
+            .filter(|d| {
+                in_scope(&d.component_id)
+                    || (anchor_parents.contains(&d.component_id)
+                        && self
+                            .calculator
+                            .config(&d.component_id)
+                            .is_some_and(|c| !c.planning_percentages.is_empty()))
+            })
+            .cloned()
+            .collect();
+        let partial_inventories: Vec<Inventory> = inventories
+            .into_iter()
+            .filter(|i| in_scope(&i.component_id))
+            .collect();
+
+        let partial = self
+            .calculator
+            .with_bom_overrides(bom_overrides)
+            .calculate(partial_demands, partial_supplies, partial_inventories)?;
+
+        let mut result = previous.clone();
+        result.replace_components(partial, &affected.into_iter().collect());
+        result.tier_fill_rates = CoverageCalculator::fill_rate_by_tier(&demands, &result.coverage);
+        Ok(result)
+    }
+
+    /// 物料的直接下階物料（BOM 子件與計劃 BOM 變體）
+    fn children(&self, component_id: &str) -> Vec<String> {
+        let mut children: Vec<String> = self
+            .calculator
+            .bom_children(component_id)
+            .iter()
+            .map(|item| item.child_id.as_str().to_string())
+            .collect();
+        if let Some(config) = self.calculator.config(component_id) {
+            children.extend(config.planning_percentages.keys().cloned());
+        }
+        children
+    }
+
+    /// 受影響物料未受影響的直接上階物料
+    ///
+    /// 只有上次有計劃訂單、有確認計劃供應或有需求的物料才可能產生子件需求
+    fn parents_of(
+        &self,
+        affected: &BTreeSet<String>,
+        previous: &MrpResult,
+        demands: &[Demand],
+        supplies: &[Supply],
+    ) -> BTreeSet<String> {
+        previous
+            .planned_orders
+            .iter()
+            .map(|o| &o.component_id)
+            .chain(demands.iter().map(|d| &d.component_id))
+            .chain(
+                supplies
+                    .iter()
+                    .filter(|s| s.is_firm && s.is_planned())
+                    .map(|s| &s.component_id),
+            )
+            .filter(|id| !affected.contains(*id))
+            .filter(|id| self.children(id).iter().any(|c| affected.contains(c)))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bom_core::{BomItem, ComponentId};
+    use bom_graph::BomGraph;
+    use chrono::NaiveDate;
+    use mrp_core::{DemandType, MrpConfig, WorkCalendar};
+    use rust_decimal::Decimal;
+    use std::collections::HashMap;
+
+    fn bom_item(parent: &str, child: &str, quantity: i64) -> BomItem {
+        BomItem {
+            id: uuid::Uuid::new_v4(),
+            parent_id: ComponentId::new(parent),
+            child_id: ComponentId::new(child),
+            quantity: Decimal::from(quantity),
+            scrap_factor: Decimal::ZERO,
+            sequence: 10,
+            operation_sequence: None,
+            is_phantom: false,
+            effective_from: None,
+            effective_to: None,
+            alternative_group: None,
+            alternative_priority: None,
+            reference_designator: None,
+            position: None,
+            notes: None,
+            version: 1,
+        }
+    }
+
+    fn demand(component_id: &str, quantity: i64, day: u32) -> Demand {
+        Demand::new(
+            component_id.to_string(),
+            Decimal::from(quantity),
+            NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+            DemandType::SalesOrder,
+        )
+    }
+
+    #[test]
+    fn test_incremental_matches_full_replan_for_shared_child() {
+        // BIKE、TRIKE 共用 WHEEL；只改 BIKE 需求時 TRIKE 不重算，但其訂單仍展開 WHEEL 需求，
+        // 結果與完整重算相同
+        let mut bom_graph = BomGraph::new();
+        bom_graph
+            .add_bom_item(bom_item("BIKE", "WHEEL", 2))
+            .unwrap();
+        bom_graph
+            .add_bom_item(bom_item("TRIKE", "WHEEL", 3))
+            .unwrap();
+        let configs: HashMap<String, MrpConfig> = [
+            ("BIKE", 2, ProcurementType::Make),
+            ("TRIKE", 3, ProcurementType::Make),
+            ("WHEEL", 1, ProcurementType::Buy),
+        ]
+        .into_iter()
+        .map(|(id, lead_time, procurement)| {
+            (
+                id.to_string(),
+                MrpConfig::new(id.to_string(), lead_time, procurement),
+            )
+        })
+        .collect();
+        let calculator = MrpCalculator::new(
+            bom_graph,
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+
+        let trike = demand("TRIKE", 4, 20);
+        let previous = calculator
+            .calculate(vec![demand("BIKE", 10, 20), trike.clone()], vec![], vec![])
+            .unwrap();
+        let demands = vec![demand("BIKE", 15, 20), trike];
+
+        let incremental = IncrementalCalculator::new(&calculator);
+        assert_eq!(
+            incremental.affected_components(&["BIKE".to_string()]),
+            BTreeSet::from(["BIKE".to_string(), "WHEEL".to_string()])
+        );
+        let result = incremental
+            .calculate_incremental(
+                &previous,
+                &["BIKE".to_string()],
+                demands.clone(),
+                vec![],
+                vec![],
+            )
+            .unwrap();
+        let full = calculator.calculate(demands, vec![], vec![]).unwrap();
+
+        let key = |result: &MrpResult| {
+            let mut orders: Vec<_> = result
+                .planned_orders
+                .iter()
+                .map(|o| {
+                    (
+                        o.component_id.clone(),
+                        o.quantity,
+                        o.order_date,
+                        o.required_date,
+                    )
+                })
+                .collect();
+            orders.sort();
+            orders
+        };
+        assert_eq!(key(&result), key(&full));
+        // TRIKE 訂單（12 個 WHEEL）仍計入 WHEEL 的相依需求
+        assert!(result
+            .planned_orders
+            .iter()
+            .any(|o| o.component_id == "WHEEL" && o.quantity == Decimal::from(12)));
+
+        // 未受影響的 TRIKE 訂單原樣保留
+        let trike_order = |result: &MrpResult| {
+            result
+                .planned_orders
+                .iter()
+                .find(|o| o.component_id == "TRIKE")
+                .unwrap()
+                .id
+        };
+        assert_eq!(trike_order(&result), trike_order(&previous));
+        assert_eq!(result.coverage.len(), 2);
     }
 }
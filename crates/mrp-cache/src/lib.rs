@@ -4,6 +4,12 @@
 
 pub mod dirty_tracking;
 pub mod incremental;
+pub mod session;
+pub mod watch;
 
 // Re-export 主要類型
 pub use incremental::IncrementalCalculator;
+pub use session::{JournalEntry, JournalSource, MrpSession};
+pub use watch::{
+    AutoReplanner, ChangeKind, CycleReport, FileChange, InputWatcher, PollReport, WatchDaemon,
+};
@@ -0,0 +1,392 @@
+//! 常駐計劃會話：保存目前的輸入與計劃結果，並逐物料記錄每次重新計劃之間的變更
+//!
+//! 變更日誌記錄需求與供應的數量／日期變更，以及重新計劃後計劃訂單的變化，
+//! 介面可直接查詢「這張訂單為何移動」，不需自行比對前後兩次結果。
+
+use chrono::NaiveDate;
+use mrp_calc::{MrpCalculator, MrpResult};
+use mrp_core::{Demand, Inventory, PlannedOrder, Result, Supply};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use uuid::Uuid;
+
+/// 變更的記錄類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JournalSource {
+    /// 需求
+    Demand,
+    /// 供應
+    Supply,
+    /// 計劃訂單（重新計劃的結果）
+    PlannedOrder,
+}
+
+/// 變更日誌項目
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct JournalEntry {
+    /// 發生於第幾次重新計劃之前（計劃訂單則為該次重新計劃的結果）
+    pub replan: u64,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 記錄類型
+    pub source: JournalSource,
+
+    /// 需求／供應ID，或計劃訂單ID（訂單取消時為原訂單ID）
+    pub record_id: Uuid,
+
+    /// 變更前的（日期, 數量）；新增時為 None
+    pub before: Option<(NaiveDate, Decimal)>,
+
+    /// 變更後的（日期, 數量）；刪除時為 None
+    pub after: Option<(NaiveDate, Decimal)>,
+}
+
+impl JournalEntry {
+    /// 是否為輸入（需求或供應）變更
+    pub fn is_input(&self) -> bool {
+        self.source != JournalSource::PlannedOrder
+    }
+}
+
+impl fmt::Display for JournalEntry {
+    /// 格式：`#2 BIKE 需求 {id}：2025-11-10 × 10 → 2025-11-10 × 15`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match self.source {
+            JournalSource::Demand => "需求",
+            JournalSource::Supply => "供應",
+            JournalSource::PlannedOrder => "計劃訂單",
+        };
+        let side = |value: Option<(NaiveDate, Decimal)>| {
+            value.map_or_else(
+                || "（無）".to_string(),
+                |(date, qty)| format!("{} × {}", date, qty),
+            )
+        };
+        write!(
+            f,
+            "#{} {} {} {}：{} → {}",
+            self.replan,
+            self.component_id,
+            source,
+            self.record_id,
+            side(self.before),
+            side(self.after)
+        )
+    }
+}
+
+/// 常駐計劃會話
+pub struct MrpSession {
+    calculator: MrpCalculator,
+    demands: BTreeMap<Uuid, Demand>,
+    supplies: BTreeMap<Uuid, Supply>,
+    inventories: Vec<Inventory>,
+    result: Option<MrpResult>,
+    replans: u64,
+    journal: Vec<JournalEntry>,
+}
+
+impl MrpSession {
+    /// 創建會話（初始輸入不記入變更日誌）
+    pub fn new(
+        calculator: MrpCalculator,
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+    ) -> Self {
+        Self {
+            calculator,
+            demands: demands.into_iter().map(|d| (d.id, d)).collect(),
+            supplies: supplies.into_iter().map(|s| (s.id, s)).collect(),
+            inventories,
+            result: None,
+            replans: 0,
+            journal: Vec::new(),
+        }
+    }
+
+    /// 新增或修改需求（以需求ID辨識）
+    pub fn set_demand(&mut self, demand: Demand) {
+        let after = Some((demand.required_date, demand.quantity));
+        let before = self
+            .demands
+            .insert(demand.id, demand.clone())
+            .map(|old| (old.required_date, old.quantity));
+        self.record_input(
+            JournalSource::Demand,
+            &demand.component_id,
+            demand.id,
+            before,
+            after,
+        );
+    }
+
+    /// 刪除需求
+    pub fn remove_demand(&mut self, demand_id: Uuid) -> Option<Demand> {
+        let removed = self.demands.remove(&demand_id)?;
+        self.record_input(
+            JournalSource::Demand,
+            &removed.component_id,
+            demand_id,
+            Some((removed.required_date, removed.quantity)),
+            None,
+        );
+        Some(removed)
+    }
+
+    /// 新增或修改供應（以供應ID辨識）
+    pub fn set_supply(&mut self, supply: Supply) {
+        let after = Some((supply.available_date, supply.quantity));
+        let before = self
+            .supplies
+            .insert(supply.id, supply.clone())
+            .map(|old| (old.available_date, old.quantity));
+        self.record_input(
+            JournalSource::Supply,
+            &supply.component_id,
+            supply.id,
+            before,
+            after,
+        );
+    }
+
+    /// 刪除供應
+    pub fn remove_supply(&mut self, supply_id: Uuid) -> Option<Supply> {
+        let removed = self.supplies.remove(&supply_id)?;
+        self.record_input(
+            JournalSource::Supply,
+            &removed.component_id,
+            supply_id,
+            Some((removed.available_date, removed.quantity)),
+            None,
+        );
+        Some(removed)
+    }
+
+    /// 替換庫存（不記入變更日誌）
+    pub fn set_inventories(&mut self, inventories: Vec<Inventory>) {
+        self.inventories = inventories;
+    }
+
+    /// 以目前輸入重新計劃，並記錄各物料計劃訂單的變化
+    ///
+    /// 計劃訂單每次重新產生（ID 不延續），故按物料比對（日期, 數量）：
+    /// 消失與新出現的訂單按日期依序配對為「移動」，其餘為新增或取消。
+    pub fn replan(&mut self) -> Result<&MrpResult> {
+        let result = self.calculator.calculate(
+            self.demands.values().cloned().collect(),
+            self.supplies.values().cloned().collect(),
+            self.inventories.clone(),
+        )?;
+        self.replans += 1;
+
+        let previous = self
+            .result
+            .as_ref()
+            .map_or(&[][..], |r| r.planned_orders.as_slice());
+        let mut before: BTreeMap<&str, Vec<&PlannedOrder>> = BTreeMap::new();
+        for order in previous {
+            before.entry(&order.component_id).or_default().push(order);
+        }
+        let mut after: BTreeMap<&str, Vec<&PlannedOrder>> = BTreeMap::new();
+        for order in &result.planned_orders {
+            after.entry(&order.component_id).or_default().push(order);
+        }
+
+        let components: BTreeSet<&str> = before.keys().chain(after.keys()).copied().collect();
+        let mut entries = Vec::new();
+        for component_id in components {
+            let (removed, added) = Self::diff(
+                before.get(component_id).map_or(&[][..], Vec::as_slice),
+                after.get(component_id).map_or(&[][..], Vec::as_slice),
+            );
+            let pairs = removed.len().max(added.len());
+            for index in 0..pairs {
+                let old = removed.get(index);
+                let new = added.get(index);
+                let Some(record_id) = new.or(old).map(|o| o.id) else {
+                    continue;
+                };
+                entries.push(JournalEntry {
+                    replan: self.replans,
+                    component_id: component_id.to_string(),
+                    source: JournalSource::PlannedOrder,
+                    record_id,
+                    before: old.map(|o| (o.required_date, o.quantity)),
+                    after: new.map(|o| (o.required_date, o.quantity)),
+                });
+            }
+        }
+        self.journal.extend(entries);
+
+        Ok(self.result.insert(result))
+    }
+
+    /// 最近一次重新計劃的結果
+    pub fn result(&self) -> Option<&MrpResult> {
+        self.result.as_ref()
+    }
+
+    /// 已完成的重新計劃次數
+    pub fn replan_count(&self) -> u64 {
+        self.replans
+    }
+
+    /// 物料的變更日誌（依發生順序）
+    pub fn journal(&self, component_id: &str) -> Vec<&JournalEntry> {
+        self.journal
+            .iter()
+            .filter(|entry| entry.component_id == component_id)
+            .collect()
+    }
+
+    /// 指定重新計劃的所有變更（輸入變更與計劃訂單變化）
+    pub fn changes_in(&self, replan: u64) -> Vec<&JournalEntry> {
+        self.journal
+            .iter()
+            .filter(|entry| entry.replan == replan)
+            .collect()
+    }
+
+    /// 觸發計劃訂單變化的輸入變更
+    ///
+    /// 取訂單最近一次變化所在的重新計劃，回傳同物料的需求／供應變更；
+    /// 同物料沒有輸入變更時（例如由父件需求帶動的子件），回傳該次的所有輸入變更。
+    pub fn triggers(&self, order_id: Uuid) -> Vec<&JournalEntry> {
+        let Some(moved) = self.journal.iter().rev().find(|entry| {
+            entry.source == JournalSource::PlannedOrder && entry.record_id == order_id
+        }) else {
+            return Vec::new();
+        };
+        let inputs: Vec<&JournalEntry> = self
+            .changes_in(moved.replan)
+            .into_iter()
+            .filter(|entry| entry.is_input())
+            .collect();
+        let own: Vec<&JournalEntry> = inputs
+            .iter()
+            .copied()
+            .filter(|entry| entry.component_id == moved.component_id)
+            .collect();
+        if own.is_empty() {
+            inputs
+        } else {
+            own
+        }
+    }
+
+    /// 記錄輸入變更；同一筆記錄在下次重新計劃前多次修改時合併為一筆
+    fn record_input(
+        &mut self,
+        source: JournalSource,
+        component_id: &str,
+        record_id: Uuid,
+        before: Option<(NaiveDate, Decimal)>,
+        after: Option<(NaiveDate, Decimal)>,
+    ) {
+        let replan = self.replans + 1;
+        if let Some(index) = self.journal.iter().position(|entry| {
+            entry.replan == replan && entry.source == source && entry.record_id == record_id
+        }) {
+            let entry = &mut self.journal[index];
+            entry.after = after;
+            if entry.before == entry.after {
+                self.journal.remove(index);
+            }
+            return;
+        }
+        if before == after {
+            return;
+        }
+        self.journal.push(JournalEntry {
+            replan,
+            component_id: component_id.to_string(),
+            source,
+            record_id,
+            before,
+            after,
+        });
+    }
+
+    /// 比對物料前後的計劃訂單，回傳（消失的, 新出現的），各按日期排序
+    fn diff<'a>(
+        before: &[&'a PlannedOrder],
+        after: &[&'a PlannedOrder],
+    ) -> (Vec<&'a PlannedOrder>, Vec<&'a PlannedOrder>) {
+        let key = |o: &PlannedOrder| (o.required_date, o.quantity);
+        let mut unmatched: HashMap<(NaiveDate, Decimal), usize> = HashMap::new();
+        for order in before {
+            *unmatched.entry(key(order)).or_default() += 1;
+        }
+        let mut added = Vec::new();
+        for &order in after {
+            match unmatched.get_mut(&key(order)) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => added.push(order),
+            }
+        }
+        let mut removed = Vec::new();
+        for &order in before.iter().rev() {
+            if let Some(count) = unmatched.get_mut(&key(order)).filter(|count| **count > 0) {
+                *count -= 1;
+                removed.push(order);
+            }
+        }
+        removed.sort_by_key(|o| key(o));
+        added.sort_by_key(|o| key(o));
+        (removed, added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bom_graph::BomGraph;
+    use mrp_core::{DemandType, MrpConfig, ProcurementType, WorkCalendar};
+
+    #[test]
+    fn test_journal_explains_order_change() {
+        let configs = HashMap::from([(
+            "BIKE".to_string(),
+            MrpConfig::new("BIKE".to_string(), 2, ProcurementType::Make),
+        )]);
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+        let demand = Demand::new(
+            "BIKE".to_string(),
+            Decimal::from(10),
+            NaiveDate::from_ymd_opt(2025, 11, 20).unwrap(),
+            DemandType::SalesOrder,
+        );
+        let mut session = MrpSession::new(calculator, vec![demand.clone()], vec![], vec![]);
+        session.replan().unwrap();
+
+        // 兩次修改合併為一筆：10 → 15
+        let mut changed = demand.clone();
+        changed.quantity = Decimal::from(12);
+        session.set_demand(changed.clone());
+        changed.quantity = Decimal::from(15);
+        session.set_demand(changed);
+        let order_id = session.replan().unwrap().planned_orders[0].id;
+
+        let journal = session.journal("BIKE");
+        assert_eq!(journal.len(), 3);
+        assert_eq!(journal[1].source, JournalSource::Demand);
+        assert_eq!(journal[1].before.unwrap().1, Decimal::from(10));
+        assert_eq!(journal[1].after.unwrap().1, Decimal::from(15));
+        assert_eq!(journal[2].record_id, order_id);
+        assert_eq!(journal[2].after.unwrap().1, Decimal::from(15));
+
+        let triggers = session.triggers(order_id);
+        assert_eq!(triggers, vec![journal[1]]);
+        assert_eq!(triggers[0].record_id, demand.id);
+    }
+}
@@ -0,0 +1,584 @@
+//! 輸入目錄監看：偵測 CSV 檔案變更、重新匯入並標記受影響物料
+//!
+//! 以輪詢比對檔案修改時間與大小（不依賴作業系統通知）。`WatchDaemon` 定期呼叫
+//! `AutoReplanner::poll`，有變更時以 [`IncrementalCalculator`] 只重算受影響物料、
+//! 延續未變訂單的識別並寫出結果檔。單一檔案匯入失敗不影響其他檔案的變更。
+
+use mrp_calc::{CarryoverTolerance, CsvImporter, MrpCalculator, PlanArchive, PlanStore};
+use mrp_core::{Demand, Inventory, MrpError, PlannedOrder, Result, Supply};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::dirty_tracking::DirtyTracker;
+use crate::incremental::IncrementalCalculator;
+
+/// 檔案變更類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// 新增
+    Created,
+    /// 修改
+    Modified,
+    /// 刪除
+    Removed,
+}
+
+/// 檔案變更
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileChange {
+    /// 檔案路徑
+    pub path: PathBuf,
+
+    /// 變更類型
+    pub kind: ChangeKind,
+}
+
+/// 目錄監看器（只監看指定副檔名的檔案，不遞迴子目錄）
+pub struct InputWatcher {
+    dirs: Vec<PathBuf>,
+    extension: String,
+    seen: BTreeMap<PathBuf, (SystemTime, u64)>,
+}
+
+impl InputWatcher {
+    /// 創建監看器（預設監看 `.csv` 檔）
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        Self {
+            dirs,
+            extension: "csv".to_string(),
+            seen: BTreeMap::new(),
+        }
+    }
+
+    /// 建構器模式：設置監看的副檔名（不含點）
+    pub fn with_extension(mut self, extension: &str) -> Self {
+        self.extension = extension.to_string();
+        self
+    }
+
+    /// 掃描目錄並回傳自上次掃描後的變更（首次掃描時所有檔案皆為新增）
+    pub fn poll(&mut self) -> Result<Vec<FileChange>> {
+        let mut current = BTreeMap::new();
+        for dir in &self.dirs {
+            let entries = fs::read_dir(dir)
+                .map_err(|e| MrpError::ImportError(format!("{}: {}", dir.display(), e)))?;
+            for entry in entries {
+                let entry = entry
+                    .map_err(|e| MrpError::ImportError(format!("{}: {}", dir.display(), e)))?;
+                let path = entry.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some(self.extension.as_str()) {
+                    continue;
+                }
+                // 檔案在掃描途中被刪除時視為不存在
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_file() {
+                    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    current.insert(path, (modified, metadata.len()));
+                }
+            }
+        }
+
+        let mut changes = Vec::new();
+        for (path, stamp) in &current {
+            match self.seen.get(path) {
+                None => changes.push(FileChange {
+                    path: path.clone(),
+                    kind: ChangeKind::Created,
+                }),
+                Some(previous) if previous != stamp => changes.push(FileChange {
+                    path: path.clone(),
+                    kind: ChangeKind::Modified,
+                }),
+                Some(_) => {}
+            }
+        }
+        for path in self.seen.keys().filter(|path| !current.contains_key(*path)) {
+            changes.push(FileChange {
+                path: path.clone(),
+                kind: ChangeKind::Removed,
+            });
+        }
+
+        self.seen = current;
+        Ok(changes)
+    }
+
+    /// 忘記檔案的掃描紀錄（下次掃描時視為新增）
+    pub fn forget(&mut self, path: &Path) {
+        self.seen.remove(path);
+    }
+}
+
+/// 一次掃描的結果
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct PollReport {
+    /// 受影響的物料（按物料ID排序，無變更時為空）
+    pub dirty: Vec<String>,
+
+    /// 匯入失敗的檔案與錯誤（保留上一次成功匯入的資料，下次掃描時重試）
+    pub failures: Vec<(PathBuf, MrpError)>,
+}
+
+/// 自動重新計劃的輸入狀態
+///
+/// 需求檔與供應檔分別放在不同目錄；每個檔案的匯入結果分開保存，
+/// 檔案變更時只替換該檔案的資料，並將新舊資料涉及的物料標記為髒。
+pub struct AutoReplanner {
+    demand_watcher: InputWatcher,
+    supply_watcher: InputWatcher,
+    importer: CsvImporter,
+    demands: BTreeMap<PathBuf, Vec<Demand>>,
+    supplies: BTreeMap<PathBuf, Vec<Supply>>,
+    tracker: DirtyTracker,
+}
+
+impl AutoReplanner {
+    /// 創建自動重新計劃器
+    pub fn new(demand_dir: PathBuf, supply_dir: PathBuf) -> Self {
+        Self {
+            demand_watcher: InputWatcher::new(vec![demand_dir]),
+            supply_watcher: InputWatcher::new(vec![supply_dir]),
+            importer: CsvImporter::new(),
+            demands: BTreeMap::new(),
+            supplies: BTreeMap::new(),
+            tracker: DirtyTracker::new(),
+        }
+    }
+
+    /// 建構器模式：設置 CSV 匯入器（編碼、分隔符號、地區格式）
+    pub fn with_importer(mut self, importer: CsvImporter) -> Self {
+        self.importer = importer;
+        self
+    }
+
+    /// 掃描輸入目錄並重新匯入變更的檔案，回傳受影響的物料與匯入失敗的檔案
+    ///
+    /// 匯入失敗的檔案保留上一次成功匯入的資料，下次掃描時重試（檔案可能仍在寫入中）；
+    /// 其餘檔案照常處理並標記受影響物料。只有目錄無法讀取時回傳錯誤。
+    pub fn poll(&mut self) -> Result<PollReport> {
+        let mut failures = Vec::new();
+
+        for change in self.demand_watcher.poll()? {
+            let new = match change.kind {
+                ChangeKind::Removed => Vec::new(),
+                _ => match Self::read(&change.path)
+                    .and_then(|bytes| self.importer.import_demands(&bytes))
+                {
+                    Ok(new) => new,
+                    Err(e) => {
+                        self.demand_watcher.forget(&change.path);
+                        failures.push((change.path, e));
+                        continue;
+                    }
+                },
+            };
+            let old = self.demands.remove(&change.path).unwrap_or_default();
+            for demand in old.iter().chain(&new) {
+                self.tracker.mark_dirty(demand.component_id.clone());
+            }
+            if change.kind != ChangeKind::Removed {
+                self.demands.insert(change.path, new);
+            }
+        }
+
+        for change in self.supply_watcher.poll()? {
+            let new = match change.kind {
+                ChangeKind::Removed => Vec::new(),
+                _ => match Self::read(&change.path)
+                    .and_then(|bytes| self.importer.import_supplies(&bytes))
+                {
+                    Ok(new) => new,
+                    Err(e) => {
+                        self.supply_watcher.forget(&change.path);
+                        failures.push((change.path, e));
+                        continue;
+                    }
+                },
+            };
+            let old = self.supplies.remove(&change.path).unwrap_or_default();
+            for supply in old.iter().chain(&new) {
+                self.tracker.mark_dirty(supply.component_id.clone());
+            }
+            if change.kind != ChangeKind::Removed {
+                self.supplies.insert(change.path, new);
+            }
+        }
+
+        let dirty: BTreeSet<String> = self.tracker.get_dirty_components().into_iter().collect();
+        self.tracker.clear();
+        Ok(PollReport {
+            dirty: dirty.into_iter().collect(),
+            failures,
+        })
+    }
+
+    /// 目前所有需求檔的需求
+    pub fn demands(&self) -> Vec<Demand> {
+        self.demands.values().flatten().cloned().collect()
+    }
+
+    /// 目前所有供應檔的供應
+    pub fn supplies(&self) -> Vec<Supply> {
+        self.supplies.values().flatten().cloned().collect()
+    }
+
+    fn read(path: &Path) -> Result<Vec<u8>> {
+        fs::read(path).map_err(|e| MrpError::ImportError(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// 計劃快照檔名（[`PlanArchive`] 格式）
+pub const PLAN_FILE: &str = "plan.json";
+
+/// 計劃訂單清單檔名（CSV）
+pub const ORDERS_FILE: &str = "planned_orders.csv";
+
+/// 一次常駐循環的結果
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct CycleReport {
+    /// 重新計劃時受影響的物料；沒有重新計劃時為 None
+    pub replanned: Option<Vec<String>>,
+
+    /// 本次掃描匯入失敗的檔案與錯誤
+    pub failures: Vec<(PathBuf, MrpError)>,
+}
+
+/// 常駐自動重新計劃：監看輸入目錄，有變更時重新計劃並寫出結果
+///
+/// 首次以全部輸入計算；之後只重算有變更的物料及其 BOM 下階物料，其餘物料沿用
+/// 上一版計劃，再從上一版延續容差內訂單的ID，使下游系統能辨識哪些訂單未變。
+/// 各次循環須使用同一個計算器。結果先寫入暫存檔再改名，讀取者不會讀到寫到一半的檔案。
+pub struct WatchDaemon {
+    replanner: AutoReplanner,
+    inventories: Vec<Inventory>,
+    output_dir: PathBuf,
+    tolerance: CarryoverTolerance,
+    store: PlanStore,
+}
+
+impl WatchDaemon {
+    /// 創建常駐程序
+    pub fn new(replanner: AutoReplanner, output_dir: PathBuf) -> Self {
+        Self {
+            replanner,
+            inventories: Vec::new(),
+            output_dir,
+            tolerance: CarryoverTolerance::default(),
+            store: PlanStore::new(),
+        }
+    }
+
+    /// 建構器模式：設置庫存
+    pub fn with_inventories(mut self, inventories: Vec<Inventory>) -> Self {
+        self.inventories = inventories;
+        self
+    }
+
+    /// 建構器模式：設置訂單延續容差
+    pub fn with_carryover_tolerance(mut self, tolerance: CarryoverTolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// 執行一次循環：掃描輸入，有變更（或尚未輸出過）時重新計劃並寫出結果
+    ///
+    /// 回傳受影響的物料（沒有重新計劃時為 None）與匯入失敗的檔案；
+    /// 失敗的檔案沿用上一次成功匯入的資料，不妨礙其他檔案的變更重新計劃。
+    pub fn run_once(&mut self, calculator: &MrpCalculator) -> Result<CycleReport> {
+        let PollReport { dirty, failures } = self.replanner.poll()?;
+        let previous = self.store.current();
+        if dirty.is_empty() && previous.is_some() {
+            return Ok(CycleReport {
+                replanned: None,
+                failures,
+            });
+        }
+
+        let demands = self.replanner.demands();
+        let supplies = self.replanner.supplies();
+        let inventories = self.inventories.clone();
+        let result = match &previous {
+            Some(previous) => IncrementalCalculator::new(calculator).calculate_incremental(
+                previous,
+                &dirty,
+                demands,
+                supplies,
+                inventories,
+            )?,
+            None => calculator.calculate(demands, supplies, inventories)?,
+        };
+        let snapshot = self.store.publish_with_carryover(result, self.tolerance);
+
+        fs::create_dir_all(&self.output_dir).map_err(|e| self.output_error(e))?;
+        self.write(PLAN_FILE, &PlanArchive::save(&snapshot)?)?;
+        self.write(ORDERS_FILE, &Self::orders_csv(&snapshot.planned_orders))?;
+        Ok(CycleReport {
+            replanned: Some(dirty),
+            failures,
+        })
+    }
+
+    /// 常駐執行：每隔 `interval` 執行一次循環，重新計劃、有檔案匯入失敗或發生錯誤時呼叫 `report`
+    ///
+    /// 錯誤不會中止監看（匯入失敗的檔案下次掃描時重試）。
+    pub fn run<F>(&mut self, calculator: &MrpCalculator, interval: Duration, mut report: F) -> !
+    where
+        F: FnMut(Result<CycleReport>),
+    {
+        loop {
+            match self.run_once(calculator) {
+                Ok(cycle) if cycle.replanned.is_none() && cycle.failures.is_empty() => {}
+                outcome => report(outcome),
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    fn orders_csv(orders: &[PlannedOrder]) -> String {
+        let mut csv =
+            String::from("id,component_id,order_type,quantity,order_date,required_date\n");
+        for order in orders {
+            let _ = writeln!(
+                csv,
+                "{},{},{:?},{},{},{}",
+                order.id,
+                order.component_id,
+                order.order_type,
+                order.quantity,
+                order.order_date,
+                order.required_date
+            );
+        }
+        csv
+    }
+
+    fn write(&self, name: &str, contents: &str) -> Result<()> {
+        let path = self.output_dir.join(name);
+        let temp = self.output_dir.join(format!(".{}.tmp", name));
+        fs::write(&temp, contents)
+            .and_then(|()| fs::rename(&temp, &path))
+            .map_err(|e| self.output_error(e))
+    }
+
+    fn output_error(&self, e: std::io::Error) -> MrpError {
+        MrpError::ExportError(format!("{}: {}", self.output_dir.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_reimports_changed_files() {
+        let root = std::env::temp_dir().join(format!("mrp-watch-{}", uuid::Uuid::new_v4()));
+        let demand_dir = root.join("demands");
+        let supply_dir = root.join("supplies");
+        fs::create_dir_all(&demand_dir).unwrap();
+        fs::create_dir_all(&supply_dir).unwrap();
+
+        fs::write(
+            demand_dir.join("orders.csv"),
+            "component_id,quantity,required_date\nBIKE,10,2025-11-10\nTRIKE,5,2025-11-12\n",
+        )
+        .unwrap();
+        fs::write(demand_dir.join("notes.txt"), "ignored").unwrap();
+
+        let mut replanner = AutoReplanner::new(demand_dir.clone(), supply_dir.clone());
+        assert_eq!(replanner.poll().unwrap().dirty, vec!["BIKE", "TRIKE"]);
+        assert_eq!(replanner.demands().len(), 2);
+        assert!(replanner.poll().unwrap().dirty.is_empty());
+
+        // 修改需求檔：TRIKE 移除、SCOOTER 新增，兩者與 BIKE 皆需重新計劃
+        fs::write(
+            demand_dir.join("orders.csv"),
+            "component_id,quantity,required_date\nBIKE,120,2025-11-10\nSCOOTER,3,2025-11-15\n",
+        )
+        .unwrap();
+        fs::write(
+            supply_dir.join("po.csv"),
+            "component_id,quantity,available_date\nWHEEL,100,2025-11-05\n",
+        )
+        .unwrap();
+        assert_eq!(
+            replanner.poll().unwrap().dirty,
+            vec!["BIKE", "SCOOTER", "TRIKE", "WHEEL"]
+        );
+        assert_eq!(replanner.supplies().len(), 1);
+
+        fs::remove_file(supply_dir.join("po.csv")).unwrap();
+        assert_eq!(replanner.poll().unwrap().dirty, vec!["WHEEL"]);
+        assert!(replanner.supplies().is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_daemon_replans_and_writes_outputs() {
+        use bom_graph::BomGraph;
+        use mrp_core::{MrpConfig, ProcurementType, WorkCalendar};
+        use std::collections::HashMap;
+
+        let root = std::env::temp_dir().join(format!("mrp-daemon-{}", uuid::Uuid::new_v4()));
+        let demand_dir = root.join("demands");
+        let supply_dir = root.join("supplies");
+        let output_dir = root.join("out");
+        fs::create_dir_all(&demand_dir).unwrap();
+        fs::create_dir_all(&supply_dir).unwrap();
+
+        let mut configs = HashMap::new();
+        for id in ["BIKE", "TRIKE"] {
+            configs.insert(
+                id.to_string(),
+                MrpConfig::new(id.to_string(), 2, ProcurementType::Buy),
+            );
+        }
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+
+        fs::write(
+            demand_dir.join("bike.csv"),
+            "component_id,quantity,required_date\nBIKE,10,2025-11-10\n",
+        )
+        .unwrap();
+        fs::write(
+            demand_dir.join("trike.csv"),
+            "component_id,quantity,required_date\nTRIKE,5,2025-11-10\n",
+        )
+        .unwrap();
+        let mut daemon = WatchDaemon::new(
+            AutoReplanner::new(demand_dir.clone(), supply_dir.clone()),
+            output_dir.clone(),
+        );
+        assert_eq!(
+            daemon.run_once(&calculator).unwrap().replanned,
+            Some(vec!["BIKE".to_string(), "TRIKE".to_string()])
+        );
+        let first =
+            PlanArchive::load(&fs::read_to_string(output_dir.join(PLAN_FILE)).unwrap()).unwrap();
+        assert_eq!(first.planned_orders.len(), 2);
+        let trike_id = first
+            .planned_orders
+            .iter()
+            .find(|o| o.component_id == "TRIKE")
+            .unwrap()
+            .id;
+
+        // 無變更不重新計劃
+        assert_eq!(daemon.run_once(&calculator).unwrap().replanned, None);
+
+        // 只改 BIKE（檔案大小也改變）：重新計劃後 TRIKE 訂單沿用原ID
+        fs::write(
+            demand_dir.join("bike.csv"),
+            "component_id,quantity,required_date\nBIKE,120,2025-11-10\n",
+        )
+        .unwrap();
+        assert_eq!(
+            daemon.run_once(&calculator).unwrap().replanned,
+            Some(vec!["BIKE".to_string()])
+        );
+        let csv = fs::read_to_string(output_dir.join(ORDERS_FILE)).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().any(|l| l.contains("BIKE,Purchase,120,")));
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with(&format!("{},TRIKE,Purchase,5,", trike_id))));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_daemon_replans_other_inputs_while_one_file_is_broken() {
+        use bom_graph::BomGraph;
+        use mrp_core::{MrpConfig, ProcurementType, WorkCalendar};
+        use std::collections::HashMap;
+
+        let root = std::env::temp_dir().join(format!("mrp-daemon-{}", uuid::Uuid::new_v4()));
+        let demand_dir = root.join("demands");
+        let supply_dir = root.join("supplies");
+        let output_dir = root.join("out");
+        fs::create_dir_all(&demand_dir).unwrap();
+        fs::create_dir_all(&supply_dir).unwrap();
+
+        let mut configs = HashMap::new();
+        for id in ["BIKE", "TRIKE"] {
+            configs.insert(
+                id.to_string(),
+                MrpConfig::new(id.to_string(), 2, ProcurementType::Buy),
+            );
+        }
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+
+        fs::write(
+            demand_dir.join("bike.csv"),
+            "component_id,quantity,required_date\nBIKE,10,2025-11-10\n",
+        )
+        .unwrap();
+        fs::write(
+            demand_dir.join("trike.csv"),
+            "component_id,quantity,required_date\nTRIKE,5,2025-11-10\n",
+        )
+        .unwrap();
+        let mut daemon = WatchDaemon::new(
+            AutoReplanner::new(demand_dir.clone(), supply_dir.clone()),
+            output_dir.clone(),
+        );
+        daemon.run_once(&calculator).unwrap();
+
+        // TRIKE 檔寫壞、BIKE 檔修改：BIKE 照常重新計劃，TRIKE 沿用上次匯入的資料
+        fs::write(
+            demand_dir.join("trike.csv"),
+            "component_id,quantity,required_date\nTRIKE,abc,2025-11-10\n",
+        )
+        .unwrap();
+        fs::write(
+            demand_dir.join("bike.csv"),
+            "component_id,quantity,required_date\nBIKE,120,2025-11-10\n",
+        )
+        .unwrap();
+        let cycle = daemon.run_once(&calculator).unwrap();
+        assert_eq!(cycle.replanned, Some(vec!["BIKE".to_string()]));
+        assert_eq!(cycle.failures.len(), 1);
+        assert_eq!(cycle.failures[0].0, demand_dir.join("trike.csv"));
+        let csv = fs::read_to_string(output_dir.join(ORDERS_FILE)).unwrap();
+        assert!(csv.lines().any(|l| l.contains("BIKE,Purchase,120,")));
+        assert!(csv.lines().any(|l| l.contains("TRIKE,Purchase,5,")));
+
+        // 失敗持續時每次掃描都回報，但沒有其他變更就不重新計劃
+        let cycle = daemon.run_once(&calculator).unwrap();
+        assert_eq!(cycle.replanned, None);
+        assert_eq!(cycle.failures.len(), 1);
+
+        // 修好後只重算 TRIKE
+        fs::write(
+            demand_dir.join("trike.csv"),
+            "component_id,quantity,required_date\nTRIKE,7,2025-11-10\n",
+        )
+        .unwrap();
+        let cycle = daemon.run_once(&calculator).unwrap();
+        assert_eq!(cycle.replanned, Some(vec!["TRIKE".to_string()]));
+        assert!(cycle.failures.is_empty());
+        let csv = fs::read_to_string(output_dir.join(ORDERS_FILE)).unwrap();
+        assert!(csv.lines().any(|l| l.contains("TRIKE,Purchase,7,")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
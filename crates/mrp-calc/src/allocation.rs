@@ -0,0 +1,260 @@
+//! 共用子件的分時分配（多父件競爭同一子件）
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, Supply};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// 分配策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum AllocationStrategy {
+    /// 按客戶等級與需求優先級分配（高等級、高優先級先滿足）
+    Priority,
+    /// 按需求數量比例公平分配
+    FairShare,
+    /// 按單位金額分配（營收或毛利高者先滿足，未設金額視為零；同金額再按等級與優先級）
+    Value,
+}
+
+/// 單筆需求的分配結果
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ComponentAllocation {
+    /// 子件物料ID
+    pub component_id: String,
+
+    /// 需求ID
+    pub demand_id: Uuid,
+
+    /// 父件物料ID（獨立需求為 None）
+    pub parent_id: Option<String>,
+
+    /// 父件計劃訂單ID（獨立需求為 None）
+    pub parent_order_id: Option<Uuid>,
+
+    /// 需求日期
+    pub required_date: NaiveDate,
+
+    /// 需求數量
+    pub requested_qty: Decimal,
+
+    /// 已分配數量
+    pub allocated_qty: Decimal,
+
+    /// 短缺數量
+    pub shortage_qty: Decimal,
+}
+
+impl ComponentAllocation {
+    /// 檢查是否短缺（父件訂單將延遲）
+    pub fn is_short(&self) -> bool {
+        self.shortage_qty > Decimal::ZERO
+    }
+}
+
+/// 共用子件分配計算器
+pub struct AllocationCalculator;
+
+impl AllocationCalculator {
+    /// 分配子件的已承諾供應（現有庫存 + 現有供應）給各需求
+    ///
+    /// 逐日期處理：每個日期可用量 = 初始庫存 + 截至該日到貨的供應 - 先前已分配量。
+    /// 同一日期需求總量超過可用量時，按策略決定哪些需求短缺。
+    pub fn allocate(
+        component_id: &str,
+        demands: &[Demand],
+        initial_inventory: Decimal,
+        supplies: &[Supply],
+        strategy: AllocationStrategy,
+    ) -> Vec<ComponentAllocation> {
+        let mut demands_by_date: BTreeMap<NaiveDate, Vec<&Demand>> = BTreeMap::new();
+        for demand in demands {
+            demands_by_date
+                .entry(demand.required_date)
+                .or_default()
+                .push(demand);
+        }
+
+        let mut allocations = Vec::with_capacity(demands.len());
+        let mut allocated_total = Decimal::ZERO;
+
+        for (date, bucket_demands) in demands_by_date {
+            let received: Decimal = supplies
+                .iter()
+                .filter(|s| s.available_date <= date)
+                .map(|s| s.quantity)
+                .sum();
+            let available = (initial_inventory + received - allocated_total).max(Decimal::ZERO);
+
+            let requested: Decimal = bucket_demands.iter().map(|d| d.quantity).sum();
+
+            let bucket_allocated = if requested <= available {
+                bucket_demands
+                    .iter()
+                    .map(|d| d.quantity)
+                    .collect::<Vec<_>>()
+            } else {
+                match strategy {
+                    AllocationStrategy::FairShare => {
+                        Self::fair_share(&bucket_demands, available, requested)
+                    }
+                    AllocationStrategy::Priority => Self::by_priority(&bucket_demands, available),
+                    AllocationStrategy::Value => Self::by_value(&bucket_demands, available),
+                }
+            };
+
+            for (demand, allocated_qty) in bucket_demands.iter().zip(bucket_allocated) {
+                allocated_total += allocated_qty;
+
+                let (parent_id, parent_order_id) = Self::parse_parent_ref(demand);
+
+                allocations.push(ComponentAllocation {
+                    component_id: component_id.to_string(),
+                    demand_id: demand.id,
+                    parent_id,
+                    parent_order_id,
+                    required_date: date,
+                    requested_qty: demand.quantity,
+                    allocated_qty,
+                    shortage_qty: demand.quantity - allocated_qty,
+                });
+            }
+        }
+
+        allocations
+    }
+
+    /// 按比例分配
+    fn fair_share(demands: &[&Demand], available: Decimal, requested: Decimal) -> Vec<Decimal> {
+        if requested <= Decimal::ZERO {
+            return vec![Decimal::ZERO; demands.len()];
+        }
+
+        let mut shares: Vec<Decimal> = demands
+            .iter()
+            .map(|d| (d.quantity * available / requested).min(d.quantity))
+            .collect();
+
+        // 捨入誤差補給最後一筆，確保分配總量等於可用量
+        let distributed: Decimal = shares.iter().sum();
+        if let Some(last) = shares.last_mut() {
+            *last += available - distributed;
+        }
+
+        shares
+    }
+
+    /// 按客戶等級與優先級分配（相同者按輸入順序）
+    fn by_priority(demands: &[&Demand], available: Decimal) -> Vec<Decimal> {
+        let mut order: Vec<usize> = (0..demands.len()).collect();
+        order.sort_by_key(|&idx| std::cmp::Reverse(demands[idx].allocation_rank()));
+        Self::in_order(demands, &order, available)
+    }
+
+    /// 按單位金額分配（相同者按客戶等級與優先級，再按輸入順序）
+    fn by_value(demands: &[&Demand], available: Decimal) -> Vec<Decimal> {
+        let mut order: Vec<usize> = (0..demands.len()).collect();
+        order.sort_by_key(|&idx| {
+            let demand = demands[idx];
+            std::cmp::Reverse((
+                demand.unit_value.unwrap_or(Decimal::ZERO),
+                demand.allocation_rank(),
+            ))
+        });
+        Self::in_order(demands, &order, available)
+    }
+
+    /// 依指定順序逐筆滿足需求直到可用量用完
+    fn in_order(demands: &[&Demand], order: &[usize], available: Decimal) -> Vec<Decimal> {
+        let mut shares = vec![Decimal::ZERO; demands.len()];
+        let mut remaining = available;
+        for &idx in order {
+            let allocated = demands[idx].quantity.min(remaining);
+            shares[idx] = allocated;
+            remaining -= allocated;
+        }
+
+        shares
+    }
+
+    /// 從相依需求的來源單據（格式 `父件ID:計劃訂單ID`）解析父件
+    fn parse_parent_ref(demand: &Demand) -> (Option<String>, Option<Uuid>) {
+        if !demand.is_dependent() {
+            return (None, None);
+        }
+
+        match demand
+            .source_ref
+            .as_deref()
+            .and_then(|r| r.rsplit_once(':'))
+        {
+            Some((parent_id, order_id)) => {
+                (Some(parent_id.to_string()), Uuid::parse_str(order_id).ok())
+            }
+            None => (demand.source_ref.clone(), None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::DemandType;
+
+    fn dependent(parent: &str, qty: i64, priority: u8) -> Demand {
+        Demand::new(
+            "CHILD-001".to_string(),
+            Decimal::from(qty),
+            NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
+            DemandType::Dependent,
+        )
+        .with_source_ref(format!("{}:{}", parent, Uuid::new_v4()))
+        .with_priority(priority)
+    }
+
+    #[test]
+    fn test_priority_allocation() {
+        let demands = vec![dependent("PARENT-A", 60, 3), dependent("PARENT-B", 60, 8)];
+
+        let result = AllocationCalculator::allocate(
+            "CHILD-001",
+            &demands,
+            Decimal::from(80),
+            &[],
+            AllocationStrategy::Priority,
+        );
+
+        assert_eq!(result.len(), 2);
+        // 高優先級的 PARENT-B 先滿足
+        assert_eq!(result[1].parent_id.as_deref(), Some("PARENT-B"));
+        assert_eq!(result[1].allocated_qty, Decimal::from(60));
+        assert!(!result[1].is_short());
+
+        // PARENT-A 短缺 40，其訂單將延遲
+        assert_eq!(result[0].allocated_qty, Decimal::from(20));
+        assert_eq!(result[0].shortage_qty, Decimal::from(40));
+        assert!(result[0].parent_order_id.is_some());
+    }
+
+    #[test]
+    fn test_fair_share_allocation() {
+        let demands = vec![dependent("PARENT-A", 100, 5), dependent("PARENT-B", 50, 5)];
+
+        let result = AllocationCalculator::allocate(
+            "CHILD-001",
+            &demands,
+            Decimal::from(90),
+            &[],
+            AllocationStrategy::FairShare,
+        );
+
+        // 按 2:1 比例分配 90
+        assert_eq!(result[0].allocated_qty, Decimal::from(60));
+        assert_eq!(result[1].allocated_qty, Decimal::from(30));
+        assert_eq!(result[0].shortage_qty, Decimal::from(40));
+        assert_eq!(result[1].shortage_qty, Decimal::from(20));
+    }
+}
@@ -0,0 +1,190 @@
+//! 計劃快照的持久化格式：帶 schema 版本標記，讀取舊版資料時逐版遷移
+//!
+//! 保存內容為計劃訂單、計劃員註記、警告、未計算物料與執行清單；追溯、覆蓋等衍生資料不保存，
+//! 需要時重新計算。格式變更時遞增 [`RESULT_SCHEMA_VERSION`]，並在 `MIGRATIONS`
+//! 末尾加入由上一版轉換到新版的遷移函數。
+
+use mrp_core::{MrpError, OrderAnnotation, PlannedOrder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{MrpResult, MrpWarning, PlanSnapshot, RunManifest};
+
+/// 目前的結果 schema 版本
+pub const RESULT_SCHEMA_VERSION: u32 = 1;
+
+/// 遷移函數：`MIGRATIONS[n]` 將版本 n 的文件轉為版本 n + 1
+const MIGRATIONS: [fn(Value) -> mrp_core::Result<Value>; RESULT_SCHEMA_VERSION as usize] =
+    [migrate_v0_to_v1];
+
+/// 保存的結果內容（目前版本）
+#[derive(Serialize, Deserialize)]
+struct ArchivedResult {
+    planned_orders: Vec<PlannedOrder>,
+    #[serde(default)]
+    annotations: HashMap<Uuid, OrderAnnotation>,
+    #[serde(default)]
+    warnings: Vec<MrpWarning>,
+    #[serde(default)]
+    unprocessed_components: Vec<String>,
+    #[serde(default)]
+    calculation_time_ms: Option<u128>,
+    #[serde(default)]
+    manifest: RunManifest,
+}
+
+/// 版本化文件
+#[derive(Serialize, Deserialize)]
+struct ArchiveDocument {
+    schema_version: u32,
+    plan_version: u64,
+    result: ArchivedResult,
+}
+
+/// 計劃快照存檔
+pub struct PlanArchive;
+
+impl PlanArchive {
+    /// 將快照序列化為目前版本的 JSON 文件
+    pub fn save(snapshot: &PlanSnapshot) -> mrp_core::Result<String> {
+        let document = ArchiveDocument {
+            schema_version: RESULT_SCHEMA_VERSION,
+            plan_version: snapshot.version(),
+            result: ArchivedResult {
+                planned_orders: snapshot.planned_orders.clone(),
+                annotations: snapshot.annotations.clone(),
+                warnings: snapshot.warnings.clone(),
+                unprocessed_components: snapshot.unprocessed_components.clone(),
+                calculation_time_ms: snapshot.calculation_time_ms,
+                manifest: snapshot.manifest.clone(),
+            },
+        };
+        serde_json::to_string(&document)
+            .map_err(|e| MrpError::ExportError(format!("快照序列化失敗: {}", e)))
+    }
+
+    /// 讀取任一已知版本的文件並遷移到目前版本
+    ///
+    /// 比目前版本更新的文件無法讀取（須升級引擎）。
+    pub fn load(json: &str) -> mrp_core::Result<PlanSnapshot> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| MrpError::ImportError(format!("快照格式錯誤: {}", e)))?;
+        let document: ArchiveDocument = serde_json::from_value(Self::migrate(value)?)
+            .map_err(|e| MrpError::ImportError(format!("快照內容錯誤: {}", e)))?;
+
+        let archived = document.result;
+        let mut result = MrpResult::empty();
+        result.planned_orders = archived.planned_orders;
+        result.annotations = archived.annotations;
+        result.warnings = archived.warnings;
+        result.unprocessed_components = archived.unprocessed_components;
+        result.calculation_time_ms = archived.calculation_time_ms;
+        result.manifest = archived.manifest;
+
+        Ok(PlanSnapshot::with_version(result, document.plan_version))
+    }
+
+    /// 文件的 schema 版本（未標記版本的舊文件為 0）
+    pub fn schema_version(value: &Value) -> mrp_core::Result<u32> {
+        match value.get("schema_version") {
+            None => Ok(0),
+            Some(version) => version
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| MrpError::ImportError(format!("無效的 schema 版本: {}", version))),
+        }
+    }
+
+    /// 將文件逐版遷移到目前版本
+    pub fn migrate(mut value: Value) -> mrp_core::Result<Value> {
+        let version = Self::schema_version(&value)?;
+        if version > RESULT_SCHEMA_VERSION {
+            return Err(MrpError::ImportError(format!(
+                "快照 schema 版本 {} 比引擎支援的版本 {} 新",
+                version, RESULT_SCHEMA_VERSION
+            )));
+        }
+        for migration in &MIGRATIONS[version as usize..] {
+            value = migration(value)?;
+        }
+        Ok(value)
+    }
+}
+
+/// 版本 0 → 1：版本標記前直接保存 `MrpResult` 欄位（或只保存計劃訂單陣列），
+/// 且計劃訂單可能缺少 `pegging`
+fn migrate_v0_to_v1(value: Value) -> mrp_core::Result<Value> {
+    let mut result = match value {
+        Value::Array(orders) => json!({ "planned_orders": orders }),
+        Value::Object(_) => value,
+        other => {
+            return Err(MrpError::ImportError(format!(
+                "無法辨識的舊版快照: {}",
+                other
+            )))
+        }
+    };
+    if let Some(orders) = result
+        .get_mut("planned_orders")
+        .and_then(Value::as_array_mut)
+    {
+        for order in orders.iter_mut().filter_map(Value::as_object_mut) {
+            order.entry("pegging").or_insert_with(|| json!([]));
+        }
+    }
+    Ok(json!({
+        "schema_version": 1,
+        "plan_version": 1,
+        "result": result,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::PlannedOrderType;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_round_trip_and_legacy_migration() {
+        let order = PlannedOrder::new(
+            "BIKE".to_string(),
+            Decimal::from(10),
+            NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 11, 5).unwrap(),
+            PlannedOrderType::Production,
+        );
+        let mut result = MrpResult::empty();
+        result.planned_orders.push(order.clone());
+        result.add_warning(MrpWarning::warning(
+            "BIKE".to_string(),
+            "庫存不足".to_string(),
+        ));
+        let snapshot = PlanSnapshot::new(result);
+
+        let json = PlanArchive::save(&snapshot).unwrap();
+        let loaded = PlanArchive::load(&json).unwrap();
+        assert_eq!(loaded.version(), 1);
+        assert_eq!(loaded.planned_orders[0].id, order.id);
+        assert_eq!(loaded.warnings[0].message, "庫存不足");
+
+        // 版本標記前的格式：計劃訂單陣列，且沒有 pegging 欄位
+        let legacy = format!(
+            r#"[{{"id":"{}","component_id":"BIKE","quantity":"10","required_date":"2025-11-10",
+                "order_date":"2025-11-05","order_type":"Production","source_id":null}}]"#,
+            order.id
+        );
+        let migrated = PlanArchive::load(&legacy).unwrap();
+        assert_eq!(migrated.planned_orders.len(), 1);
+        assert!(migrated.planned_orders[0].pegging.is_empty());
+
+        let future = r#"{"schema_version":99,"plan_version":1,"result":{"planned_orders":[]}}"#;
+        assert!(matches!(
+            PlanArchive::load(future),
+            Err(MrpError::ImportError(_))
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+//! 可承諾量（ATP）：依現有庫存、已排定供應與已承諾需求回答「某日能否出貨」
+//!
+//! ATP 期間以供應到貨日切分（第一期另含現有庫存），各期的分離 ATP = 本期收貨 − 本期已承諾需求。
+//! 後期不足時向前期預留（前瞻消耗），因此後期的剩餘不能補前期的承諾，前期的剩餘可滾到後期；
+//! 累計 ATP 為分離 ATP 的累加，是各日實際可再承諾的數量。
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, Supply};
+use rust_decimal::Decimal;
+
+/// ATP 檢查結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AtpCheck {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 要求數量
+    pub requested_quantity: Decimal,
+
+    /// 要求日期
+    pub requested_date: NaiveDate,
+
+    /// 要求日期的可承諾量（可能為負）
+    pub available_quantity: Decimal,
+
+    /// 要求日期可承諾的數量（不超過要求數量）
+    pub promised_quantity: Decimal,
+
+    /// 可全數承諾的最早日期（不早於要求日期；已排定供應都不足時為 None）
+    pub promise_date: Option<NaiveDate>,
+}
+
+impl AtpCheck {
+    /// 要求日期可全數承諾
+    pub fn is_promisable(&self) -> bool {
+        self.promised_quantity >= self.requested_quantity
+    }
+}
+
+/// ATP 期間
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AtpPeriod {
+    /// 期間開始日（第一期為最早的供應或需求日期，其後為供應到貨日）
+    pub start: NaiveDate,
+
+    /// 下一期開始日（最後一期為 None）
+    pub end: Option<NaiveDate>,
+
+    /// 本期收貨（第一期含現有庫存）
+    pub receipts: Decimal,
+
+    /// 本期已承諾需求
+    pub committed: Decimal,
+
+    /// 分離 ATP（已扣除後期前瞻消耗；只有第一期可能為負，表示已承諾需求無法滿足）
+    pub discrete: Decimal,
+
+    /// 累計 ATP
+    pub cumulative: Decimal,
+}
+
+/// ATP 計算器
+pub struct AtpCalculator;
+
+impl AtpCalculator {
+    /// 指定日期的預計可用量：現有庫存 + 當日以前到貨 − 當日以前已承諾（只計入該物料）
+    ///
+    /// 不為後期的已承諾需求預留；回答訂單承諾請用 [`Self::check`] 的累計 ATP。
+    pub fn available_on(
+        component_id: &str,
+        on_hand: Decimal,
+        supplies: &[Supply],
+        demands: &[Demand],
+        date: NaiveDate,
+    ) -> Decimal {
+        let receipts: Decimal = supplies
+            .iter()
+            .filter(|s| s.component_id == component_id && s.available_date <= date)
+            .map(|s| s.quantity)
+            .sum();
+        let committed: Decimal = demands
+            .iter()
+            .filter(|d| d.component_id == component_id && d.required_date <= date)
+            .map(|d| d.quantity)
+            .sum();
+        on_hand + receipts - committed
+    }
+
+    /// 依供應到貨日切分的 ATP 期間（含前瞻消耗的分離 ATP 與累計 ATP）
+    ///
+    /// 沒有該物料的供應與需求時回傳空清單。
+    pub fn periods(
+        component_id: &str,
+        on_hand: Decimal,
+        supplies: &[Supply],
+        demands: &[Demand],
+    ) -> Vec<AtpPeriod> {
+        let supplies: Vec<&Supply> = supplies
+            .iter()
+            .filter(|s| s.component_id == component_id)
+            .collect();
+        let demands: Vec<&Demand> = demands
+            .iter()
+            .filter(|d| d.component_id == component_id)
+            .collect();
+        let Some(first) = supplies
+            .iter()
+            .map(|s| s.available_date)
+            .chain(demands.iter().map(|d| d.required_date))
+            .min()
+        else {
+            return Vec::new();
+        };
+
+        let mut starts: Vec<NaiveDate> = std::iter::once(first)
+            .chain(supplies.iter().map(|s| s.available_date))
+            .collect();
+        starts.sort_unstable();
+        starts.dedup();
+        let period_of = |date: NaiveDate| starts.partition_point(|&start| start <= date).max(1) - 1;
+
+        let mut receipts = vec![Decimal::ZERO; starts.len()];
+        let mut committed = vec![Decimal::ZERO; starts.len()];
+        receipts[0] = on_hand;
+        for supply in &supplies {
+            receipts[period_of(supply.available_date)] += supply.quantity;
+        }
+        for demand in &demands {
+            committed[period_of(demand.required_date)] += demand.quantity;
+        }
+
+        // 前瞻消耗：後期不足的部分由前期預留
+        let mut discrete: Vec<Decimal> = receipts
+            .iter()
+            .zip(&committed)
+            .map(|(receipt, commitment)| receipt - commitment)
+            .collect();
+        for index in (1..discrete.len()).rev() {
+            if discrete[index] < Decimal::ZERO {
+                let shortfall = discrete[index];
+                discrete[index - 1] += shortfall;
+                discrete[index] = Decimal::ZERO;
+            }
+        }
+
+        let mut cumulative = Decimal::ZERO;
+        (0..starts.len())
+            .map(|index| {
+                cumulative += discrete[index];
+                AtpPeriod {
+                    start: starts[index],
+                    end: starts.get(index + 1).copied(),
+                    receipts: receipts[index],
+                    committed: committed[index],
+                    discrete: discrete[index],
+                    cumulative,
+                }
+            })
+            .collect()
+    }
+
+    /// 檢查要求日期能否承諾指定數量，並找出可全數承諾的最早日期
+    ///
+    /// 可承諾量取要求日期所在期間的累計 ATP（已為後期的已承諾需求預留），
+    /// 累計 ATP 只在供應到貨日增加，因此只需檢查要求日期與其後的到貨日。
+    pub fn check(
+        component_id: &str,
+        quantity: Decimal,
+        requested_date: NaiveDate,
+        on_hand: Decimal,
+        supplies: &[Supply],
+        demands: &[Demand],
+    ) -> AtpCheck {
+        let periods = Self::periods(component_id, on_hand, supplies, demands);
+        let current = periods
+            .iter()
+            .rposition(|p| p.start <= requested_date)
+            .unwrap_or(0);
+        let available_quantity = periods.get(current).map_or(on_hand, |p| p.cumulative);
+
+        let promise_date = if available_quantity >= quantity {
+            Some(requested_date)
+        } else {
+            periods
+                .iter()
+                .skip(current + 1)
+                .find(|p| p.cumulative >= quantity)
+                .map(|p| p.start)
+        };
+
+        AtpCheck {
+            component_id: component_id.to_string(),
+            requested_quantity: quantity,
+            requested_date,
+            available_quantity,
+            promised_quantity: available_quantity.max(Decimal::ZERO).min(quantity),
+            promise_date,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{DemandType, SupplyType};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_check_finds_promise_date() {
+        let supplies = vec![
+            Supply::new(
+                "PUMP".to_string(),
+                Decimal::from(50),
+                date(10),
+                SupplyType::PurchaseOrder,
+            ),
+            Supply::new(
+                "VALVE".to_string(),
+                Decimal::from(999),
+                date(5),
+                SupplyType::PurchaseOrder,
+            ),
+        ];
+        let demands = vec![Demand::new(
+            "PUMP".to_string(),
+            Decimal::from(30),
+            date(4),
+            DemandType::SalesOrder,
+        )];
+
+        // 11/5：40 − 30 = 10；11/10：10 + 50 = 60
+        let check = AtpCalculator::check(
+            "PUMP",
+            Decimal::from(25),
+            date(5),
+            Decimal::from(40),
+            &supplies,
+            &demands,
+        );
+        assert_eq!(check.available_quantity, Decimal::from(10));
+        assert_eq!(check.promised_quantity, Decimal::from(10));
+        assert!(!check.is_promisable());
+        assert_eq!(check.promise_date, Some(date(10)));
+
+        let too_much = AtpCalculator::check(
+            "PUMP",
+            Decimal::from(100),
+            date(5),
+            Decimal::from(40),
+            &supplies,
+            &demands,
+        );
+        assert_eq!(too_much.promise_date, None);
+    }
+
+    #[test]
+    fn test_cumulative_atp_with_lookahead() {
+        let supply = |quantity: i64, day: u32| {
+            Supply::new(
+                "PUMP".to_string(),
+                Decimal::from(quantity),
+                date(day),
+                SupplyType::PurchaseOrder,
+            )
+        };
+        let demand = |quantity: i64, day: u32| {
+            Demand::new(
+                "PUMP".to_string(),
+                Decimal::from(quantity),
+                date(day),
+                DemandType::SalesOrder,
+            )
+        };
+        let supplies = vec![supply(20, 10), supply(30, 20)];
+        let demands = vec![demand(5, 3), demand(25, 12), demand(10, 21)];
+
+        // 分離 ATP：10−5=5、20−25=−5（向第一期預留）、30−10=20
+        let periods = AtpCalculator::periods("PUMP", Decimal::from(10), &supplies, &demands);
+        let atp: Vec<(NaiveDate, Decimal, Decimal)> = periods
+            .iter()
+            .map(|p| (p.start, p.discrete, p.cumulative))
+            .collect();
+        assert_eq!(
+            atp,
+            vec![
+                (date(3), Decimal::ZERO, Decimal::ZERO),
+                (date(10), Decimal::ZERO, Decimal::ZERO),
+                (date(20), Decimal::from(20), Decimal::from(20)),
+            ]
+        );
+
+        // 現有 5 件須留給 11/12 的承諾，11/5 無可承諾量；後期剩餘也不能提前承諾
+        let check = AtpCalculator::check(
+            "PUMP",
+            Decimal::from(5),
+            date(5),
+            Decimal::from(10),
+            &supplies,
+            &demands,
+        );
+        assert_eq!(check.available_quantity, Decimal::ZERO);
+        assert_eq!(check.promise_date, Some(date(20)));
+    }
+}
@@ -0,0 +1,355 @@
+//! BOM 計劃圖：將某物料的 BOM 子樹標上提前期、累計提前期、計劃量與短缺，匯出為 DOT 或 Mermaid
+//!
+//! 共用件只出現一次，每筆 BOM 行為一條邊。累計提前期 = 自身提前期 + 子件累計提前期的最大值。
+
+use bom_core::BomItem;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::{ConfigSet, MrpResult};
+
+/// 匯出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiagramFormat {
+    /// Graphviz DOT
+    Dot,
+
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+/// 圖中的物料節點
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DiagramNode {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 提前期（天；沒有配置時為 0）
+    pub lead_time_days: u32,
+
+    /// 累計提前期（天）
+    pub cumulative_lead_time_days: u32,
+
+    /// 計劃訂單數量合計
+    pub planned_quantity: Decimal,
+
+    /// 短缺數量：有庫存預測的物料為預計庫存的最大缺口，其餘為未被滿足的輸入需求數量合計
+    pub shortage: Decimal,
+}
+
+/// 圖中的 BOM 行
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DiagramEdge {
+    /// 父件ID
+    pub parent_id: String,
+
+    /// 子件ID
+    pub child_id: String,
+
+    /// 用量
+    pub quantity: Decimal,
+}
+
+/// BOM 計劃圖
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BomDiagram {
+    /// 根物料ID
+    pub root_id: String,
+
+    /// 節點（根物料在前，其餘依展開順序）
+    pub nodes: Vec<DiagramNode>,
+
+    /// BOM 行
+    pub edges: Vec<DiagramEdge>,
+}
+
+impl BomDiagram {
+    /// 展開根物料的 BOM 子樹並標上計劃資訊（已展開過的物料不重複展開，避免循環）
+    pub(crate) fn build<F>(
+        root_id: &str,
+        children: F,
+        configs: &ConfigSet,
+        result: &MrpResult,
+    ) -> Self
+    where
+        F: Fn(&str) -> Vec<BomItem>,
+    {
+        let mut order: Vec<String> = vec![root_id.to_string()];
+        let mut edges = Vec::new();
+        let mut child_ids: HashMap<String, Vec<String>> = HashMap::new();
+        let mut index = 0;
+        while index < order.len() {
+            let parent_id = order[index].clone();
+            for item in children(&parent_id) {
+                let child_id = item.child_id.as_str().to_string();
+                edges.push(DiagramEdge {
+                    parent_id: parent_id.clone(),
+                    child_id: child_id.clone(),
+                    quantity: item.quantity,
+                });
+                child_ids
+                    .entry(parent_id.clone())
+                    .or_default()
+                    .push(child_id.clone());
+                if !order.contains(&child_id) {
+                    order.push(child_id);
+                }
+            }
+            index += 1;
+        }
+
+        let lead_time = |id: &str| configs.get(id).map_or(0, |c| c.lead_time_days);
+        let mut cumulative: HashMap<String, u32> = HashMap::new();
+        for id in &order {
+            cumulative_lead_time(id, &child_ids, &lead_time, &mut cumulative);
+        }
+
+        let nodes = order
+            .iter()
+            .map(|id| DiagramNode {
+                component_id: id.clone(),
+                lead_time_days: lead_time(id),
+                cumulative_lead_time_days: cumulative[id.as_str()],
+                planned_quantity: result
+                    .planned_orders
+                    .iter()
+                    .filter(|o| o.component_id == *id)
+                    .map(|o| o.quantity)
+                    .sum(),
+                shortage: match result.projections.get(id) {
+                    Some(projection) => projection
+                        .iter()
+                        .map(|r| -r.projected_on_hand)
+                        .fold(Decimal::ZERO, Decimal::max),
+                    None => result
+                        .coverage
+                        .iter()
+                        .filter(|c| c.component_id == *id)
+                        .map(|c| c.uncovered_quantity)
+                        .sum(),
+                },
+            })
+            .collect();
+
+        Self {
+            root_id: root_id.to_string(),
+            nodes,
+            edges,
+        }
+    }
+
+    /// 以指定格式輸出
+    pub fn render(&self, format: DiagramFormat) -> String {
+        match format {
+            DiagramFormat::Dot => self.to_dot(),
+            DiagramFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    /// Graphviz DOT（有短缺的節點以紅框標示）
+    pub fn to_dot(&self) -> String {
+        let mut text = format!("digraph \"{}\" {{\n", escape(&self.root_id));
+        text.push_str("  rankdir=TB;\n  node [shape=box];\n");
+        for node in &self.nodes {
+            let color = if node.shortage > Decimal::ZERO {
+                ", color=red"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                text,
+                "  \"{}\" [label=\"{}\"{}];",
+                escape(&node.component_id),
+                escape(&Self::label(node)).replace('\n', "\\n"),
+                color
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                text,
+                "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape(&edge.parent_id),
+                escape(&edge.child_id),
+                edge.quantity
+            );
+        }
+        text.push_str("}\n");
+        text
+    }
+
+    /// Mermaid flowchart（節點ID為 n0、n1…，有短缺的節點套用 shortage 樣式）
+    pub fn to_mermaid(&self) -> String {
+        let ids: HashMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.component_id.as_str(), format!("n{}", index)))
+            .collect();
+
+        let mut text = String::from("flowchart TD\n");
+        for node in &self.nodes {
+            let _ = writeln!(
+                text,
+                "  {}[\"{}\"]",
+                ids[node.component_id.as_str()],
+                Self::label(node)
+                    .replace('"', "#quot;")
+                    .replace('\n', "<br/>")
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                text,
+                "  {} -->|{}| {}",
+                ids[edge.parent_id.as_str()],
+                edge.quantity,
+                ids[edge.child_id.as_str()]
+            );
+        }
+        let short: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter(|n| n.shortage > Decimal::ZERO)
+            .map(|n| ids[n.component_id.as_str()].as_str())
+            .collect();
+        if !short.is_empty() {
+            text.push_str("  classDef shortage stroke:#d00,stroke-width:2px\n");
+            let _ = writeln!(text, "  class {} shortage", short.join(","));
+        }
+        text
+    }
+
+    /// 節點標籤（多行）
+    fn label(node: &DiagramNode) -> String {
+        let mut label = format!(
+            "{}\nLT {} / 累計 {}\n計劃 {}",
+            node.component_id,
+            node.lead_time_days,
+            node.cumulative_lead_time_days,
+            node.planned_quantity
+        );
+        if node.shortage > Decimal::ZERO {
+            let _ = write!(label, "\n短缺 {}", node.shortage);
+        }
+        label
+    }
+}
+
+/// 累計提前期（遞迴計算並記錄；循環中的物料先記為自身提前期，避免無窮遞迴）
+fn cumulative_lead_time(
+    id: &str,
+    child_ids: &HashMap<String, Vec<String>>,
+    lead_time: &dyn Fn(&str) -> u32,
+    memo: &mut HashMap<String, u32>,
+) -> u32 {
+    if let Some(&days) = memo.get(id) {
+        return days;
+    }
+    memo.insert(id.to_string(), lead_time(id));
+    let longest_child = child_ids
+        .get(id)
+        .into_iter()
+        .flatten()
+        .map(|child| cumulative_lead_time(child, child_ids, lead_time, memo))
+        .max()
+        .unwrap_or(0);
+    let days = lead_time(id) + longest_child;
+    memo.insert(id.to_string(), days);
+    days
+}
+
+/// DOT 字串跳脫
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::bom_item;
+    use mrp_core::{MrpConfig, PlannedOrder, PlannedOrderType, ProcurementType};
+
+    #[test]
+    fn test_build_and_render_planning_overlay() {
+        let bom: HashMap<&str, Vec<BomItem>> = HashMap::from([
+            (
+                "BIKE",
+                vec![bom_item("BIKE", "FRAME", 1), bom_item("BIKE", "WHEEL", 2)],
+            ),
+            ("WHEEL", vec![bom_item("WHEEL", "SPOKE", 32)]),
+            ("FRAME", vec![bom_item("FRAME", "SPOKE", 4)]),
+        ]);
+        let mut configs = ConfigSet::default();
+        for (id, lead_time) in [("BIKE", 2), ("FRAME", 5), ("WHEEL", 3), ("SPOKE", 4)] {
+            configs.set(MrpConfig::new(
+                id.to_string(),
+                lead_time,
+                ProcurementType::Make,
+            ));
+        }
+        let mut result = MrpResult::empty();
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        result.planned_orders.push(PlannedOrder::new(
+            "WHEEL".to_string(),
+            Decimal::from(20),
+            date,
+            date,
+            PlannedOrderType::Production,
+        ));
+
+        let diagram = BomDiagram::build(
+            "BIKE",
+            |id| bom.get(id).cloned().unwrap_or_default(),
+            &configs,
+            &result,
+        );
+
+        // SPOKE 為共用件只出現一次；BIKE 累計 = 2 + max(5, 3) + 4
+        assert_eq!(diagram.nodes.len(), 4);
+        assert_eq!(diagram.edges.len(), 4);
+        assert_eq!(diagram.nodes[0].cumulative_lead_time_days, 11);
+        let dot = diagram.render(DiagramFormat::Dot);
+        assert!(dot.contains("\"WHEEL\" -> \"SPOKE\" [label=\"32\"];"));
+        assert!(dot.contains("計劃 20"));
+        let mermaid = diagram.render(DiagramFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart TD\n  n0[\"BIKE<br/>LT 2 / 累計 11"));
+        assert!(!mermaid.contains("classDef"));
+    }
+
+    #[test]
+    fn test_child_shortage_from_projection() {
+        let bom: HashMap<&str, Vec<BomItem>> =
+            HashMap::from([("BIKE", vec![bom_item("BIKE", "SPOKE", 32)])]);
+        let mut configs = ConfigSet::default();
+        for id in ["BIKE", "SPOKE"] {
+            configs.set(MrpConfig::new(id.to_string(), 1, ProcurementType::Buy));
+        }
+        let mut result = MrpResult::empty();
+        let date = |day| chrono::NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        // SPOKE 為人工計劃物料：11/10 預計庫存 -40、11/12 -15
+        let mut first = crate::NetRequirement::new(date(10));
+        first.projected_on_hand = Decimal::from(-40);
+        let mut second = crate::NetRequirement::new(date(12));
+        second.projected_on_hand = Decimal::from(-15);
+        result
+            .projections
+            .insert("SPOKE".to_string(), vec![first, second]);
+
+        let diagram = BomDiagram::build(
+            "BIKE",
+            |id| bom.get(id).cloned().unwrap_or_default(),
+            &configs,
+            &result,
+        );
+
+        assert_eq!(diagram.nodes[0].shortage, Decimal::ZERO);
+        assert_eq!(diagram.nodes[1].shortage, Decimal::from(40));
+        let mermaid = diagram.render(DiagramFormat::Mermaid);
+        assert!(mermaid.contains("class n1 shortage"));
+    }
+}
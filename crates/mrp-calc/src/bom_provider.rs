@@ -0,0 +1,133 @@
+//! BOM 提供者：計算器按需讀取父件的子件，不必預先載入完整 BOM 圖
+//!
+//! 十萬料號以上的 BOM 可由資料庫或快取實作 `BomProvider`，只讀取實際展開到的父件；
+//! 記憶體中的 `BomGraph` 為預設實作。
+
+use bom_core::{BomItem, ComponentId};
+use bom_graph::BomGraph;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// BOM 提供者
+pub trait BomProvider: Send + Sync {
+    /// 父件的 BOM 行（物料沒有 BOM 時為空）
+    fn children(&self, parent_id: &str) -> Vec<BomItem>;
+}
+
+impl BomProvider for BomGraph {
+    fn children(&self, parent_id: &str) -> Vec<BomItem> {
+        let arena = self.arena();
+        let Some(parent_node) = arena.find_node(&ComponentId::new(parent_id)) else {
+            return Vec::new();
+        };
+        arena
+            .children(parent_node)
+            .map(|(_, edge)| edge.bom_item.clone())
+            .collect()
+    }
+}
+
+/// 快取包裝：每個父件只向底層提供者讀取一次
+///
+/// 一次計算中同一父件會被排序、檢查與展開多次讀取，
+/// 以資料庫為後端時建議包裝後再交給計算器。
+pub struct CachedBomProvider<P> {
+    inner: P,
+    cache: RwLock<HashMap<String, Vec<BomItem>>>,
+}
+
+impl<P: BomProvider> CachedBomProvider<P> {
+    /// 創建快取包裝
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 清除快取（BOM 變更後呼叫）
+    pub fn invalidate(&self) {
+        self.cache.write().expect("BOM 快取鎖").clear();
+    }
+
+    /// 已快取的父件數
+    pub fn cached_len(&self) -> usize {
+        self.cache.read().expect("BOM 快取鎖").len()
+    }
+}
+
+impl<P: BomProvider> BomProvider for CachedBomProvider<P> {
+    fn children(&self, parent_id: &str) -> Vec<BomItem> {
+        if let Some(items) = self.cache.read().expect("BOM 快取鎖").get(parent_id) {
+            return items.clone();
+        }
+        let items = self.inner.children(parent_id);
+        self.cache
+            .write()
+            .expect("BOM 快取鎖")
+            .insert(parent_id.to_string(), items.clone());
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{bom_item, demand};
+    use crate::MrpCalculator;
+    use mrp_core::{MrpConfig, ProcurementType, WorkCalendar};
+    use rust_decimal::Decimal;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// 模擬資料庫：記錄查詢次數
+    struct CountingProvider {
+        lookups: AtomicUsize,
+    }
+
+    impl BomProvider for CountingProvider {
+        fn children(&self, parent_id: &str) -> Vec<BomItem> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            if parent_id != "BIKE" {
+                return Vec::new();
+            }
+            vec![bom_item("BIKE", "WHEEL", 2)]
+        }
+    }
+
+    #[test]
+    fn test_calculator_fetches_children_on_demand() {
+        let provider = Arc::new(CachedBomProvider::new(CountingProvider {
+            lookups: AtomicUsize::new(0),
+        }));
+        let configs = HashMap::from([
+            (
+                "BIKE".to_string(),
+                MrpConfig::new("BIKE".to_string(), 2, ProcurementType::Make),
+            ),
+            (
+                "WHEEL".to_string(),
+                MrpConfig::new("WHEEL".to_string(), 3, ProcurementType::Buy),
+            ),
+        ]);
+        let calculator = MrpCalculator::with_bom_provider(
+            Arc::clone(&provider) as Arc<dyn BomProvider>,
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+
+        let result = calculator
+            .calculate(vec![demand("BIKE", 10, 20)], vec![], vec![])
+            .unwrap();
+
+        let wheel = result
+            .planned_orders
+            .iter()
+            .find(|o| o.component_id == "WHEEL")
+            .unwrap();
+        assert_eq!(wheel.quantity, Decimal::from(20));
+        // 只查詢實際展開到的兩個父件，各一次
+        assert_eq!(provider.cached_len(), 2);
+        assert_eq!(provider.inner.lookups.load(Ordering::SeqCst), 2);
+    }
+}
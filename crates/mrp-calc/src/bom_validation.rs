@@ -0,0 +1,148 @@
+//! BOM 用量異常檢查：展開前找出可疑的 BOM 行，避免產生荒謬的相依需求
+
+use bom_core::BomItem;
+use rust_decimal::Decimal;
+
+/// BOM 異常類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BomAnomalyKind {
+    /// 用量為零或負數（不展開）
+    NonPositiveQuantity,
+    /// 損耗率 ≥ 100%
+    ScrapAtLeastFull,
+    /// 用量超過合理上限
+    ExcessiveQuantity,
+    /// 子件即父件本身（不展開）
+    SelfReference,
+}
+
+/// 單筆 BOM 異常
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BomAnomaly {
+    /// 父件ID
+    pub parent_id: String,
+
+    /// 子件ID
+    pub child_id: String,
+
+    /// 異常類型
+    pub kind: BomAnomalyKind,
+
+    /// BOM 行用量
+    pub quantity: Decimal,
+
+    /// BOM 行損耗率
+    pub scrap_factor: Decimal,
+}
+
+impl BomAnomaly {
+    /// 此異常是否應跳過該 BOM 行的展開
+    pub fn skips_explosion(&self) -> bool {
+        matches!(
+            self.kind,
+            BomAnomalyKind::NonPositiveQuantity | BomAnomalyKind::SelfReference
+        )
+    }
+
+    /// 警告訊息
+    pub fn message(&self) -> String {
+        let detail = match self.kind {
+            BomAnomalyKind::NonPositiveQuantity => {
+                format!("用量 {} 不大於 0，不展開", self.quantity)
+            }
+            BomAnomalyKind::ScrapAtLeastFull => {
+                format!("損耗率 {} 達 100% 以上", self.scrap_factor)
+            }
+            BomAnomalyKind::ExcessiveQuantity => format!("用量 {} 超過合理上限", self.quantity),
+            BomAnomalyKind::SelfReference => "子件引用父件本身，不展開".to_string(),
+        };
+        format!("BOM {} → {}: {}", self.parent_id, self.child_id, detail)
+    }
+}
+
+/// BOM 異常檢查器
+pub struct BomAnomalyDetector;
+
+impl BomAnomalyDetector {
+    /// 檢查單筆 BOM 行（`quantity_threshold` 為用量合理上限，None 表示不檢查）
+    ///
+    /// 損耗率以比例表示（0.05 = 5%）。
+    pub fn check(
+        parent_id: &str,
+        child_id: &str,
+        item: &BomItem,
+        quantity_threshold: Option<Decimal>,
+    ) -> Vec<BomAnomaly> {
+        let mut kinds = Vec::new();
+        if parent_id == child_id {
+            kinds.push(BomAnomalyKind::SelfReference);
+        }
+        if item.quantity <= Decimal::ZERO {
+            kinds.push(BomAnomalyKind::NonPositiveQuantity);
+        }
+        if item.scrap_factor >= Decimal::ONE {
+            kinds.push(BomAnomalyKind::ScrapAtLeastFull);
+        }
+        if quantity_threshold.is_some_and(|threshold| item.quantity > threshold) {
+            kinds.push(BomAnomalyKind::ExcessiveQuantity);
+        }
+
+        kinds
+            .into_iter()
+            .map(|kind| BomAnomaly {
+                parent_id: parent_id.to_string(),
+                child_id: child_id.to_string(),
+                kind,
+                quantity: item.quantity,
+                scrap_factor: item.scrap_factor,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screw(quantity: Decimal, scrap_factor: Decimal) -> BomItem {
+        BomItem {
+            quantity,
+            scrap_factor,
+            ..crate::fixtures::bom_item("ASSY", "SCREW", 0)
+        }
+    }
+
+    #[test]
+    fn test_detect_anomalies() {
+        let threshold = Some(Decimal::from(1000));
+
+        let normal = screw(Decimal::from(4), Decimal::new(5, 2));
+        assert!(BomAnomalyDetector::check("ASSY", "SCREW", &normal, threshold).is_empty());
+
+        let zero = screw(Decimal::ZERO, Decimal::ZERO);
+        let anomalies = BomAnomalyDetector::check("ASSY", "SCREW", &zero, threshold);
+        assert_eq!(anomalies[0].kind, BomAnomalyKind::NonPositiveQuantity);
+        assert!(anomalies[0].skips_explosion());
+
+        let absurd = screw(Decimal::from(5000), Decimal::ONE);
+        let kinds: Vec<_> = BomAnomalyDetector::check("ASSY", "SCREW", &absurd, threshold)
+            .into_iter()
+            .map(|a| a.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BomAnomalyKind::ScrapAtLeastFull,
+                BomAnomalyKind::ExcessiveQuantity
+            ]
+        );
+
+        let looped = screw(Decimal::ONE, Decimal::ZERO);
+        assert_eq!(
+            BomAnomalyDetector::check("ASSY", "ASSY", &looped, None)[0].kind,
+            BomAnomalyKind::SelfReference
+        );
+    }
+}
@@ -2,9 +2,11 @@
 
 use chrono::NaiveDate;
 use mrp_core::{Demand, Supply};
+use serde::{Deserialize, Serialize};
 
 /// 時間分桶策略
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum BucketingStrategy {
     /// 每日分桶
     Daily,
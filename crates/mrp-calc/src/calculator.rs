@@ -1,21 +1,70 @@
 //! MRP 主計算器
 
 use bom_graph::BomGraph;
-use mrp_core::{Demand, Inventory, MrpConfig, Supply, WorkCalendar};
-use std::collections::HashMap;
-
-use crate::{ComponentMrpResult, MrpResult};
+use mrp_core::{BlanketOrder, Demand, Inventory, MrpConfig, Supply, SupplyContract, WorkCalendar};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use crate::allocation::{AllocationCalculator, AllocationStrategy};
+use crate::bom_provider::BomProvider;
+use crate::bom_validation::{BomAnomaly, BomAnomalyDetector};
+use crate::capacity_constrained::{
+    CapacityConstrainedPlanner, CapacityConstrainedResult, CapacityDiagnostic,
+};
+use crate::commitment::CommitmentTracker;
+use crate::demand_batching::DependentDemandBatcher;
+use crate::matched_set::MatchedSet;
+use crate::metrics::ItemTiming;
+use crate::normalization::{DateNormalizer, DateSnapPolicy};
+use crate::ordering_days::OrderingDays;
+use crate::policy::PlanningPolicy;
+use crate::preview::{PlanPreview, PreviewOptions, RunBudget};
+use crate::rebalancing::{RebalancingAnalyzer, TransferLane};
+use crate::receiving::ReceiptCalendars;
+use crate::scenario::ConfigSet;
+use crate::substitution::{AlternativeSelector, ComponentSubstitution};
+use crate::{ComponentMrpResult, MrpResult, MrpWarning, RunManifest};
 
 /// MRP 計算器
 pub struct MrpCalculator {
-    /// BOM 圖（來自 BOM 引擎）
-    bom_graph: BomGraph,
+    /// BOM 圖（來自 BOM 引擎；唯讀共享，多個計算器可共用同一份）
+    bom_graph: Arc<BomGraph>,
+
+    /// 子件讀取來源（預設為 BOM 圖本身）
+    bom_provider: Arc<dyn BomProvider>,
 
-    /// MRP 配置
-    configs: HashMap<String, MrpConfig>,
+    /// MRP 配置（共享基準 + 情境覆寫）
+    configs: ConfigSet,
+
+    /// 情境 BOM 覆寫（父件ID → 取代 BOM 圖的子件清單）
+    bom_overrides: HashMap<String, Vec<bom_core::BomItem>>,
 
     /// 工作日曆
     calendar: WorkCalendar,
+
+    /// 框架採購訂單（優先以叫貨滿足採購需求）
+    blanket_orders: Vec<BlanketOrder>,
+
+    /// 供應商最低承諾量合約
+    supply_contracts: Vec<SupplyContract>,
+
+    /// 收貨日曆（採購／調撥的收貨日對齊）
+    receipt_calendars: ReceiptCalendars,
+
+    /// 供應商固定下單星期
+    ordering_days: OrderingDays,
+
+    /// 配套物料組
+    matched_sets: Vec<MatchedSet>,
+
+    /// 跨倉調撥路線（空表示不做調撥分析）
+    transfer_lanes: Vec<TransferLane>,
+
+    /// 調撥分析的需求窗口（天）
+    transfer_horizon_days: u32,
+
+    /// 計劃政策（全域行為設定）
+    policy: PlanningPolicy,
 }
 
 impl MrpCalculator {
@@ -24,20 +73,317 @@ impl MrpCalculator {
         bom_graph: BomGraph,
         configs: HashMap<String, MrpConfig>,
         calendar: WorkCalendar,
+    ) -> Self {
+        Self::with_shared_bom(Arc::new(bom_graph), configs, calendar)
+    }
+
+    /// 以共享的 BOM 圖創建計算器
+    ///
+    /// 計算器只讀取 BOM 圖，多個並行的模擬（what-if）計算可共用同一份已載入的 BOM，
+    /// 不必每次複製
+    pub fn with_shared_bom(
+        bom_graph: Arc<BomGraph>,
+        configs: HashMap<String, MrpConfig>,
+        calendar: WorkCalendar,
+    ) -> Self {
+        Self::from_parts(
+            bom_graph,
+            ConfigSet::from(configs),
+            HashMap::new(),
+            calendar,
+        )
+    }
+
+    /// 以 BOM 提供者創建計算器（按需讀取子件，不必預先建立完整 BOM 圖）
+    ///
+    /// 此時 `bom_graph()` 回傳空圖，模擬情境的 BOM 覆寫仍以 BOM 圖為基準。
+    pub fn with_bom_provider(
+        bom_provider: Arc<dyn BomProvider>,
+        configs: HashMap<String, MrpConfig>,
+        calendar: WorkCalendar,
+    ) -> Self {
+        let mut calculator = Self::with_shared_bom(Arc::new(BomGraph::new()), configs, calendar);
+        calculator.bom_provider = bom_provider;
+        calculator
+    }
+
+    /// 以分層配置與 BOM 覆寫創建計算器（供模擬情境使用）
+    pub(crate) fn from_parts(
+        bom_graph: Arc<BomGraph>,
+        configs: ConfigSet,
+        bom_overrides: HashMap<String, Vec<bom_core::BomItem>>,
+        calendar: WorkCalendar,
     ) -> Self {
         Self {
+            bom_provider: Arc::clone(&bom_graph) as Arc<dyn BomProvider>,
             bom_graph,
             configs,
+            bom_overrides,
             calendar,
+            blanket_orders: Vec::new(),
+            supply_contracts: Vec::new(),
+            receipt_calendars: ReceiptCalendars::default(),
+            ordering_days: OrderingDays::default(),
+            matched_sets: Vec::new(),
+            transfer_lanes: Vec::new(),
+            transfer_horizon_days: 0,
+            policy: PlanningPolicy::default(),
         }
     }
 
+    /// 建構器模式：設置計劃政策
+    ///
+    /// 政策的物料預設值（負庫存、取整）即時套用到配置；個別的 `with_*` 設定
+    /// 與政策共用同一份狀態，後設置者生效
+    pub fn with_policy(mut self, policy: PlanningPolicy) -> Self {
+        policy.apply_item_defaults(&mut self.configs);
+        self.policy = policy;
+        self
+    }
+
+    /// 目前的計劃政策
+    pub fn policy(&self) -> &PlanningPolicy {
+        &self.policy
+    }
+
+    /// 建構器模式：啟用共用子件分配檢查
+    ///
+    /// 多個父件在同一日期競爭同一子件時，按策略分配子件的現有庫存與供應，
+    /// 並對分配不足（將延遲）的父件訂單發出警告
+    pub fn with_allocation_strategy(mut self, strategy: AllocationStrategy) -> Self {
+        self.policy.allocation_strategy = Some(strategy);
+        self
+    }
+
+    /// 建構器模式：按（子件, 日期）批次彙總相依需求
+    ///
+    /// 大型 BOM 逐單展開會產生大量相依需求；啟用後同一子件同一日期只保留一筆，
+    /// 來源父件訂單記錄在 `MrpResult::dependent_sources`
+    pub fn with_dependent_demand_batching(mut self, enabled: bool) -> Self {
+        self.policy.batch_dependent_demands = enabled;
+        self
+    }
+
+    /// 建構器模式：設置框架採購訂單
+    ///
+    /// 採購件的計劃訂單會先轉為對有效框架訂單的叫貨建議，框架訂單用盡後才產生一般採購
+    pub fn with_blanket_orders(mut self, blanket_orders: Vec<BlanketOrder>) -> Self {
+        self.blanket_orders = blanket_orders;
+        self
+    }
+
+    /// 建構器模式：設置供應商最低承諾量合約
+    ///
+    /// 計算完成後對未消耗完承諾量的合約發出警告（照付不議合約為 Warning，其餘為 Info）
+    pub fn with_supply_contracts(mut self, contracts: Vec<SupplyContract>) -> Self {
+        self.supply_contracts = contracts;
+        self
+    }
+
+    /// 建構器模式：設置收貨日曆
+    ///
+    /// 採購／調撥計劃訂單的收貨日不是收貨日時提前到前一個收貨日（下單日按生產日曆
+    /// 維持原提前期）；物料的 `receipt_calendar_id` 優先於供應商的收貨日曆
+    pub fn with_receipt_calendars(mut self, receipt_calendars: ReceiptCalendars) -> Self {
+        self.receipt_calendars = receipt_calendars;
+        self
+    }
+
+    /// 建構器模式：設置供應商固定下單星期
+    ///
+    /// 採購計劃訂單的下單日提前到最近的下單日；提前後早於計劃起始日時改在起始日後
+    /// 最早的下單日下單並順延到貨日，延遲的訂單發出警告
+    pub fn with_ordering_days(mut self, ordering_days: OrderingDays) -> Self {
+        self.ordering_days = ordering_days;
+        self
+    }
+
+    /// 建構器模式：加入配套物料組
+    ///
+    /// 成員物料的最小訂購量與訂購倍數即時換算為同一套數；計算後期末不成套的剩餘
+    /// 記錄於 `MrpResult::matched_sets`
+    pub fn with_matched_set(mut self, set: MatchedSet) -> Self {
+        set.harmonize(&mut self.configs);
+        self.matched_sets.push(set);
+        self
+    }
+
+    /// 建構器模式：設置跨倉調撥路線
+    ///
+    /// 計算後依各倉庫存、在途供應與 `horizon_days` 內的獨立需求找出過剩與短缺倉庫，
+    /// 調撥建議記錄於 `MrpResult::transfers`（淨需求仍以全部倉庫合計計算）
+    pub fn with_transfer_lanes(mut self, lanes: Vec<TransferLane>, horizon_days: u32) -> Self {
+        self.transfer_lanes = lanes;
+        self.transfer_horizon_days = horizon_days;
+        self
+    }
+
+    /// 建構器模式：將採購改派給承諾量未達成的合約供應商
+    pub fn with_commitment_sourcing(mut self, enabled: bool) -> Self {
+        self.policy.commitment_sourcing = enabled;
+        self
+    }
+
+    /// 建構器模式：啟用呆滯／慢動物料分析
+    ///
+    /// 現有庫存在計劃起始日後 `window_days` 天內未被（獨立與相依）需求消耗完的物料
+    /// 記錄於 `MrpResult::slow_movers`
+    pub fn with_slow_mover_analysis(mut self, window_days: u32) -> Self {
+        self.policy.slow_mover_window_days = Some(window_days);
+        self
+    }
+
+    /// 建構器模式：將落在非工作日的需求／供應日期移到工作日
+    ///
+    /// 每筆調整記錄於 `MrpResult::date_adjustments`
+    pub fn with_date_normalization(mut self, policy: DateSnapPolicy) -> Self {
+        self.policy.date_snap_policy = Some(policy);
+        self
+    }
+
+    /// 建構器模式：設置 BOM 用量合理上限
+    ///
+    /// 用量超過上限的 BOM 行照常展開並發出警告；零／負用量、損耗率 ≥ 100%
+    /// 與自我引用的 BOM 行無論是否設置都會檢查
+    pub fn with_bom_quantity_threshold(mut self, threshold: rust_decimal::Decimal) -> Self {
+        self.policy.bom_quantity_threshold = Some(threshold);
+        self
+    }
+
+    /// 建構器模式：設置計算時間預算
+    ///
+    /// 頂層物料按需求最高優先級依序計算，子件隨後逐層處理；超過預算時停止，
+    /// 回傳已完成物料的部分結果，未計算的物料列於 `MrpResult::unprocessed_components`
+    pub fn with_time_budget(mut self, budget: std::time::Duration) -> Self {
+        self.policy.time_budget = Some(budget);
+        self
+    }
+
+    /// 建構器模式：啟用逐層追蹤（除錯用）
+    ///
+    /// 每算完一個 BOM 低階碼，將累計的相依需求與計劃訂單寫入 `path`（JSON Lines，
+    /// 以 [`crate::LevelTrace::read`] 讀回）；檔案已存在時覆寫
+    pub fn with_level_trace(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.policy.level_trace_path = Some(path.into());
+        self
+    }
+
     /// 主 MRP 計算入口
     pub fn calculate(
         &self,
         demands: Vec<Demand>,
         supplies: Vec<Supply>,
         inventories: Vec<Inventory>,
+    ) -> mrp_core::Result<MrpResult> {
+        self.run(demands, supplies, inventories, None, None)
+    }
+
+    /// 以指定計劃政策計算（取代計算器目前的政策，只影響本次計算）
+    pub fn calculate_with_policy(
+        &self,
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+        policy: &PlanningPolicy,
+    ) -> mrp_core::Result<MrpResult> {
+        let mut configs = self.configs.clone();
+        policy.apply_item_defaults(&mut configs);
+        self.derive(configs, policy.clone())
+            .run(demands, supplies, inventories, None, None)
+    }
+
+    /// 複製計算器並覆寫部分物料配置（BOM 圖共享，其餘設定與政策不變）
+    ///
+    /// 優化器逐物料調整配置（如改用批對批取得淨需求）後重新計算時使用
+    pub fn with_config_overrides(&self, overrides: impl IntoIterator<Item = MrpConfig>) -> Self {
+        let mut configs = self.configs.clone();
+        for config in overrides {
+            configs.set(config);
+        }
+        self.derive(configs, self.policy.clone())
+    }
+
+    /// 複製計算器並覆寫部分父件的 BOM 行（優先於 BOM 提供者，其餘設定與政策不變）
+    ///
+    /// 增量重算只展開受影響子件時使用
+    pub fn with_bom_overrides(
+        &self,
+        overrides: impl IntoIterator<Item = (String, Vec<bom_core::BomItem>)>,
+    ) -> Self {
+        let mut calculator = self.derive(self.configs.clone(), self.policy.clone());
+        calculator.bom_overrides.extend(overrides);
+        calculator
+    }
+
+    /// 以指定配置與政策複製計算器
+    fn derive(&self, configs: ConfigSet, policy: PlanningPolicy) -> Self {
+        Self {
+            bom_graph: Arc::clone(&self.bom_graph),
+            bom_provider: Arc::clone(&self.bom_provider),
+            configs,
+            bom_overrides: self.bom_overrides.clone(),
+            calendar: self.calendar.clone(),
+            blanket_orders: self.blanket_orders.clone(),
+            supply_contracts: self.supply_contracts.clone(),
+            receipt_calendars: self.receipt_calendars.clone(),
+            ordering_days: self.ordering_days.clone(),
+            matched_sets: self.matched_sets.clone(),
+            transfer_lanes: self.transfer_lanes.clone(),
+            transfer_horizon_days: self.transfer_horizon_days,
+            policy,
+        }
+    }
+
+    /// 產能約束 MRP 計算入口（CC-MRP）
+    ///
+    /// 對設置了 `capacity_per_day` 的物料，在 BOM 展開前即把計劃訂單排入每日產能
+    /// （先提前生產、再延後完成），子件的相依需求因此基於可行的生產訂單產生
+    pub fn calculate_capacity_constrained(
+        &self,
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+    ) -> mrp_core::Result<CapacityConstrainedResult> {
+        let mut diagnostics = Vec::new();
+        let result = self.run(demands, supplies, inventories, Some(&mut diagnostics), None)?;
+
+        Ok(CapacityConstrainedResult {
+            result,
+            diagnostics,
+        })
+    }
+
+    /// 預覽計算入口：在時間預算內計算近似計劃
+    ///
+    /// 可只計算頂層物料（不展開 BOM）或前 K 週；超過時間預算時停止逐物料計算，
+    /// 已完成物料的計劃訂單仍照常回傳，未計算的物料列於 `pending_components`
+    pub fn calculate_preview(
+        &self,
+        mut demands: Vec<Demand>,
+        mut supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+        options: PreviewOptions,
+    ) -> mrp_core::Result<PlanPreview> {
+        let horizon_end = options.restrict_horizon(&mut demands, &mut supplies);
+        let mut state = RunBudget::start(options.time_budget, options.top_level_only);
+        let result = self.run(demands, supplies, inventories, None, Some(&mut state))?;
+
+        Ok(PlanPreview {
+            result,
+            truncated: !state.pending_components.is_empty(),
+            pending_components: state.pending_components,
+            horizon_end,
+        })
+    }
+
+    /// MRP 計算主流程
+    fn run(
+        &self,
+        mut demands: Vec<Demand>,
+        mut supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+        mut capacity_diagnostics: Option<&mut Vec<CapacityDiagnostic>>,
+        budget: Option<&mut RunBudget>,
     ) -> mrp_core::Result<MrpResult> {
         tracing::info!(
             "開始 MRP 計算：需求 {} 筆，供應 {} 筆，庫存 {} 筆",
@@ -48,14 +394,123 @@ impl MrpCalculator {
 
         let start_time = std::time::Instant::now();
 
+        // 前次計算匯出後回傳的未確認計劃供應：由本次計劃取代，避免重複計入
+        let mut replaced_supplies: BTreeMap<String, usize> = BTreeMap::new();
+        if self.policy.planned_supply_policy == crate::policy::PlannedSupplyPolicy::Replace {
+            supplies.retain(|supply| {
+                if supply.is_engine_planned() {
+                    *replaced_supplies
+                        .entry(supply.component_id.clone())
+                        .or_default() += 1;
+                    return false;
+                }
+                true
+            });
+        }
+
+        // Step 0: 輸入日期正規化
+        let date_adjustments = match self.policy.date_snap_policy {
+            Some(policy) => {
+                DateNormalizer::normalize(&mut demands, &mut supplies, &self.calendar, policy)
+            }
+            None => Vec::new(),
+        };
+        if !date_adjustments.is_empty() {
+            tracing::debug!("非工作日日期調整: {} 筆", date_adjustments.len());
+        }
+
+        // 計劃起始日：逾期的需求與供應移到起始日
+        let mut past_due_demands: BTreeMap<String, (usize, rust_decimal::Decimal)> =
+            BTreeMap::new();
+        let mut past_due_supplies: BTreeMap<String, (usize, rust_decimal::Decimal)> =
+            BTreeMap::new();
+        if let Some(plan_start) = self.policy.plan_start_date {
+            for demand in demands.iter_mut().filter(|d| d.required_date < plan_start) {
+                let entry = past_due_demands
+                    .entry(demand.component_id.clone())
+                    .or_default();
+                entry.0 += 1;
+                entry.1 += demand.quantity;
+                demand.required_date = plan_start;
+            }
+            for supply in supplies
+                .iter_mut()
+                .filter(|s| s.available_date < plan_start)
+            {
+                let entry = past_due_supplies
+                    .entry(supply.component_id.clone())
+                    .or_default();
+                entry.0 += 1;
+                entry.1 += supply.quantity;
+                supply.available_date = plan_start;
+            }
+        }
+
+        // 新品爬坡：預測需求按爬坡曲線調整為穩定需求的百分比
+        for demand in demands
+            .iter_mut()
+            .filter(|d| d.demand_type == mrp_core::DemandType::Forecast)
+        {
+            if let Some(profile) = self
+                .configs
+                .get(&demand.component_id)
+                .and_then(|c| c.ramp_profile.as_ref())
+            {
+                demand.quantity = profile.apply(demand.quantity, demand.required_date);
+            }
+        }
+
+        // 需求時界：時界內只以銷售訂單計算，預測需求不計入
+        let mut fenced_forecasts: BTreeMap<String, (usize, rust_decimal::Decimal)> =
+            BTreeMap::new();
+        let plan_start = self.policy.plan_start_date.or_else(|| {
+            demands
+                .iter()
+                .map(|d| d.required_date)
+                .chain(supplies.iter().map(|s| s.available_date))
+                .min()
+        });
+        if let Some(plan_start) = plan_start {
+            demands.retain(|demand| {
+                let fence_end = self
+                    .configs
+                    .get(&demand.component_id)
+                    .and_then(|c| c.demand_time_fence_days)
+                    .map(|days| self.calendar.add_working_days(plan_start, days));
+                let fenced = demand.demand_type == mrp_core::DemandType::Forecast
+                    && fence_end.is_some_and(|end| demand.required_date < end);
+                if fenced {
+                    let entry = fenced_forecasts
+                        .entry(demand.component_id.clone())
+                        .or_default();
+                    entry.0 += 1;
+                    entry.1 += demand.quantity;
+                }
+                !fenced
+            });
+        }
+
         // Step 1: 按時間分桶（Time Bucketing）
         tracing::debug!("Step 1: 時間分桶");
         let planning_horizon = self.get_max_planning_horizon();
-        let time_buckets = crate::bucketing::BucketingCalculator::create_time_buckets(
+        let mut time_buckets = crate::bucketing::BucketingCalculator::create_time_buckets(
             &demands,
             &supplies,
             planning_horizon,
         );
+        // 固定週期分桶：在需求／供應日期之外補上週期桶
+        if let (Some(strategy), Some(&first), Some(&last)) = (
+            self.policy.bucketing,
+            time_buckets.first(),
+            time_buckets.last(),
+        ) {
+            time_buckets.extend(
+                crate::bucketing::BucketingCalculator::create_buckets_by_strategy(
+                    first, last, strategy,
+                ),
+            );
+            crate::bucketing::BucketingCalculator::merge_buckets(&mut time_buckets);
+        }
         tracing::debug!("時間桶數量: {}", time_buckets.len());
 
         // Step 2: 按物料分組需求/供應/庫存
@@ -65,23 +520,142 @@ impl MrpCalculator {
         let inventory_map = self.create_inventory_map(&inventories);
         tracing::debug!("物料數量: {}", grouped_demands.len());
 
-        // Step 3: 拓撲排序（依 BOM 層級，從下到上計算）
+        // Step 3: 拓撲排序（依 BOM 低階碼，父件先於子件計算）
         tracing::debug!("Step 3: 拓撲排序");
-        let sorted_components = self.topological_sort(&grouped_demands)?;
+        let firm_orders = self.firm_planned_orders(&supplies);
+        let sorted_components = if firm_orders.is_empty() {
+            self.topological_sort(&grouped_demands)?
+        } else {
+            // 沒有需求的物料也可能有確認計劃訂單要展開，一併納入排序
+            let mut roots = grouped_demands.clone();
+            for parent_id in firm_orders.keys() {
+                roots.entry(parent_id.clone()).or_default();
+            }
+            self.topological_sort(&roots)?
+        };
         tracing::debug!("排序後物料: {:?}", sorted_components);
+        let low_level_codes: HashMap<&str, u32> = sorted_components
+            .iter()
+            .map(|(id, code)| (id.as_str(), *code))
+            .collect();
 
         // Step 4: 逐物料計算 MRP（按拓撲順序）
         tracing::debug!("Step 4: 逐物料計算 MRP");
+        let mut result = MrpResult::empty();
+        result.manifest = RunManifest::new(&self.policy);
+        result.date_adjustments = date_adjustments;
+        for (component_id, count) in replaced_supplies {
+            result.add_warning(MrpWarning::info(
+                component_id,
+                format!(
+                    "忽略 {} 筆前次計算產生的未確認計劃供應，由本次計劃取代",
+                    count
+                ),
+            ));
+        }
+        for (component_id, (count, quantity)) in past_due_demands {
+            result.add_warning(MrpWarning::warning(
+                component_id,
+                format!(
+                    "{} 筆需求（合計 {}）早於計劃起始日，已移到計劃起始日計算",
+                    count, quantity
+                ),
+            ));
+        }
+        for (component_id, (count, quantity)) in past_due_supplies {
+            result.add_warning(MrpWarning::warning(
+                component_id,
+                format!(
+                    "{} 筆供應（合計 {}）逾期未到，已視為計劃起始日可用",
+                    count, quantity
+                ),
+            ));
+        }
+        for (component_id, (count, quantity)) in fenced_forecasts {
+            result.add_warning(MrpWarning::info(
+                component_id,
+                format!(
+                    "需求時界內 {} 筆預測需求（合計 {}）不計入淨需求",
+                    count, quantity
+                ),
+            ));
+        }
         let mut all_planned_orders = Vec::new();
         let mut dependent_demands: HashMap<String, Vec<Demand>> = HashMap::new();
+
+        // 確認計劃訂單為固定供應，不重新產生；其子件需求在計算前先展開
+        for (parent_id, orders) in &firm_orders {
+            for (child_id, child_demands) in self.explode_bom(parent_id, orders, None)? {
+                dependent_demands
+                    .entry(child_id)
+                    .or_default()
+                    .extend(child_demands);
+            }
+            result.add_warning(MrpWarning::info(
+                parent_id.clone(),
+                format!(
+                    "{} 張確認計劃訂單視為固定供應，已展開子件需求",
+                    orders.len()
+                ),
+            ));
+        }
+        let mut batcher = self
+            .policy
+            .batch_dependent_demands
+            .then(DependentDemandBatcher::default);
         let mut processed_components: std::collections::HashSet<String> =
             std::collections::HashSet::new();
+        let mut alternatives =
+            AlternativeSelector::new(&inventory_map, &grouped_supplies, &grouped_demands);
 
         // 先處理有獨立需求的物料
-        let mut components_to_process = sorted_components.clone();
+        let mut components_to_process: Vec<String> =
+            sorted_components.iter().map(|(id, _)| id.clone()).collect();
+
+        // 設置時間預算時，同一低階碼內按需求最高客戶等級與優先級排序，確保高優先物料先完成
+        let mut own_budget = match budget {
+            Some(_) => None,
+            None => self.policy.time_budget.map(|b| RunBudget::start(b, false)),
+        };
+        let mut budget = budget.or(own_budget.as_mut());
+        if budget.is_some() {
+            let top_priority = |id: &String| {
+                grouped_demands
+                    .get(id)
+                    .and_then(|list| list.iter().map(|d| d.allocation_rank()).max())
+                    .unwrap_or_default()
+            };
+            components_to_process.sort_by_key(|id| {
+                (
+                    low_level_codes
+                        .get(id.as_str())
+                        .copied()
+                        .unwrap_or(u32::MAX),
+                    std::cmp::Reverse(top_priority(id)),
+                )
+            });
+        }
+
+        let mut tracer = self
+            .policy
+            .level_trace_path
+            .as_deref()
+            .map(crate::trace::LevelTracer::create)
+            .transpose()?;
 
         // 迭代處理，直到沒有新的相依需求
         while !components_to_process.is_empty() {
+            // 超過時間預算即停止，其餘物料列為未計算
+            if let Some(state) = budget.as_deref_mut() {
+                if state.expired() {
+                    state.pending_components = components_to_process
+                        .drain(..)
+                        .filter(|id| !processed_components.contains(id))
+                        .collect();
+                    break;
+                }
+            }
+
             let component_id = components_to_process.remove(0);
 
             // 避免重複處理
@@ -91,6 +665,24 @@ impl MrpCalculator {
 
             tracing::debug!("計算物料 MRP: {}", component_id);
 
+            // 多個需求來源競爭子件時分配現有供應，分不到的父件訂單延後（父件訂單尚未定案）
+            if let Some(strategy) = self.policy.allocation_strategy {
+                self.allocate_shared_component(
+                    &component_id,
+                    grouped_demands
+                        .get(&component_id)
+                        .map_or(&[][..], |list| list.as_slice()),
+                    &mut dependent_demands,
+                    &mut all_planned_orders,
+                    &processed_components,
+                    &grouped_supplies,
+                    &inventory_map,
+                    strategy,
+                    self.plan_start(&time_buckets),
+                    &mut result,
+                )?;
+            }
+
             // 合併獨立需求和相依需求
             let mut component_demands = grouped_demands
                 .get(&component_id)
@@ -107,8 +699,21 @@ impl MrpCalculator {
                 continue;
             }
 
+            if let Some(tracer) = tracer.as_mut() {
+                tracer.enter(
+                    low_level_codes
+                        .get(component_id.as_str())
+                        .copied()
+                        .unwrap_or(u32::MAX),
+                    &component_id,
+                    &dependent_demands,
+                    &all_planned_orders,
+                )?;
+            }
+
             // 計算該物料的 MRP
-            let component_result = self.calculate_component_mrp(
+            let item_start = std::time::Instant::now();
+            let mut component_result = self.calculate_component_mrp(
                 &component_id,
                 &component_demands,
                 &grouped_supplies,
@@ -116,12 +721,189 @@ impl MrpCalculator {
                 &time_buckets,
             )?;
 
+            for warning in std::mem::take(&mut component_result.warnings) {
+                result.add_warning(warning);
+            }
+            result
+                .reschedule_messages
+                .append(&mut component_result.reschedules);
+
+            // 停產／已淘汰物料：取消停產日後的訂單，回報剩餘庫存與耗盡日
+            if let Some(config) = self.configs.get(&component_id) {
+                let component_supplies = grouped_supplies
+                    .get(&component_id)
+                    .map_or(&[][..], |list| list.as_slice());
+                let initial_inventory = inventory_map
+                    .get(&component_id)
+                    .map_or(rust_decimal::Decimal::ZERO, |inv| inv.available_qty);
+                if let Some(report) = crate::lifecycle::PhaseOutPlanner::apply(
+                    &component_id,
+                    config,
+                    &mut component_result.planned_orders,
+                    &component_demands,
+                    component_supplies,
+                    initial_inventory,
+                ) {
+                    if report.suppressed_orders > 0 {
+                        result.add_warning(MrpWarning::info(
+                            component_id.clone(),
+                            format!(
+                                "物料生命週期 {:?}，已取消 {} 張計劃訂單",
+                                report.status, report.suppressed_orders
+                            ),
+                        ));
+                    }
+                    if report.shortfall > rust_decimal::Decimal::ZERO {
+                        result.add_warning(MrpWarning::warning(
+                            component_id.clone(),
+                            format!(
+                                "停產物料需求 {} 超過剩餘庫存 {}（短缺 {}），預計 {} 耗盡",
+                                report.total_demand,
+                                report.remaining_stock,
+                                report.shortfall,
+                                report
+                                    .run_out_date
+                                    .map_or_else(|| "-".to_string(), |d| d.to_string())
+                            ),
+                        ));
+                    }
+                    result.phase_outs.push(report);
+                }
+            }
+            if let Some(projection) = component_result.projection.take() {
+                result
+                    .projections
+                    .insert(component_result.component_id.clone(), projection);
+            }
+            for order in component_result
+                .planned_orders
+                .iter()
+                .filter(|o| o.is_late())
+            {
+                result.add_warning(MrpWarning::warning(
+                    component_id.clone(),
+                    format!(
+                        "計劃訂單 {} 預計完工 {}，晚於需要日期 {}（延遲 {} 天）",
+                        order.id,
+                        order.required_date,
+                        order.need_date.unwrap_or(order.required_date),
+                        order.days_late()
+                    ),
+                ));
+            }
+
+            // 產能約束模式：展開前先讓計劃訂單符合產能
+            if let Some(diagnostics) = capacity_diagnostics.as_deref_mut() {
+                let phase_start = std::time::Instant::now();
+                self.apply_capacity_constraint(
+                    &mut component_result,
+                    &time_buckets,
+                    diagnostics,
+                    &mut result,
+                )?;
+                component_result.timing.capacity = phase_start.elapsed();
+            }
+
             // 收集計劃訂單
             all_planned_orders.extend(component_result.planned_orders.clone());
 
             // BOM 展開：為子件生成相依需求
-            let child_demands = self.explode_bom(&component_id, &component_result.planned_orders)?;
-            for (child_id, child_demand_list) in child_demands {
+            let phase_start = std::time::Instant::now();
+            if !component_result.planned_orders.is_empty() {
+                for anomaly in self.bom_anomalies(&component_result.component_id) {
+                    result.add_warning(MrpWarning::warning(
+                        component_result.component_id.clone(),
+                        anomaly.message(),
+                    ));
+                }
+            }
+            let substitutions_before = alternatives.substitution_count();
+            let planning_config = self
+                .configs
+                .get(&component_id)
+                .filter(|c| c.is_planning_bom());
+            let child_demands = if budget.as_deref().is_some_and(|s| s.top_level_only) {
+                HashMap::new()
+            } else if let Some(config) = planning_config {
+                let total: rust_decimal::Decimal = config.planning_percentages.values().sum();
+                if total != rust_decimal::Decimal::ONE_HUNDRED {
+                    result.add_warning(MrpWarning::info(
+                        component_id.clone(),
+                        format!("計劃 BOM 百分比合計 {}%，不等於 100%", total),
+                    ));
+                }
+                self.explode_planning_bom(config, &component_demands)
+            } else {
+                self.explode_bom(
+                    &component_result.component_id,
+                    &component_result.planned_orders,
+                    Some(&mut alternatives),
+                )?
+            };
+            for substitution in &alternatives.substitutions()[substitutions_before..] {
+                result.add_warning(MrpWarning::warning(
+                    substitution.primary_id.clone(),
+                    format!(
+                        "{} 預計可用量不足，{} 的計劃訂單 {} 改用替代料 {}（{}，數量 {}）",
+                        substitution.primary_id,
+                        substitution.parent_id,
+                        substitution.parent_order_id,
+                        substitution.substitute_id,
+                        substitution.required_date,
+                        substitution.quantity
+                    ),
+                ));
+            }
+            let mut timing = component_result.timing;
+            timing.explosion = phase_start.elapsed();
+            timing.planned_order_count = component_result.planned_orders.len();
+            timing.total = item_start.elapsed();
+            result.metrics.items.push(timing);
+            // 父件計劃訂單所滿足的需求（相依需求的客戶等級與金額依其來源訂單決定）
+            let order_sources = self.order_demand_sources(
+                &component_id,
+                &component_demands,
+                &component_result.planned_orders,
+                &grouped_supplies,
+                &inventory_map,
+            );
+            let source_demands = |demand: &Demand| -> Vec<(&Demand, rust_decimal::Decimal)> {
+                let source_id = demand
+                    .source_ref
+                    .as_deref()
+                    .and_then(|r| r.rsplit(':').next())
+                    .and_then(|id| uuid::Uuid::parse_str(id).ok());
+                let Some(source_id) = source_id else {
+                    return Vec::new();
+                };
+                match order_sources.get(&source_id) {
+                    Some(sources) => sources.clone(),
+                    None => component_demands
+                        .iter()
+                        .filter(|d| d.id == source_id)
+                        .map(|d| (d, d.quantity))
+                        .collect(),
+                }
+            };
+            for (child_id, mut child_demand_list) in child_demands {
+                for demand in &mut child_demand_list {
+                    let sources = source_demands(demand);
+                    // 相依需求沿用來源訂單所滿足需求的最高客戶等級（供共用子件分配）
+                    demand.customer_tier = sources
+                        .iter()
+                        .map(|(d, _)| d.customer_tier)
+                        .max_by_key(|tier| tier.rank())
+                        .unwrap_or_default();
+                    // 相依需求金額 = 來源訂單追溯到的需求金額（分配數量 × 單位金額），按子件數量換算單位金額
+                    let values: Vec<rust_decimal::Decimal> = sources
+                        .iter()
+                        .filter_map(|(d, quantity)| d.unit_value.map(|value| value * quantity))
+                        .collect();
+                    if !values.is_empty() && demand.quantity > rust_decimal::Decimal::ZERO {
+                        demand.unit_value =
+                            Some(values.iter().sum::<rust_decimal::Decimal>() / demand.quantity);
+                    }
+                }
                 // 將新的子件加入待處理列表
                 if !processed_components.contains(&child_id)
                     && !components_to_process.contains(&child_id)
@@ -129,26 +911,305 @@ impl MrpCalculator {
                     components_to_process.push(child_id.clone());
                 }
 
-                dependent_demands
+                let child_entry = dependent_demands
                     .entry(child_id.clone())
-                    .or_insert_with(Vec::new)
-                    .extend(child_demand_list);
+                    .or_insert_with(Vec::new);
+                match batcher.as_mut() {
+                    Some(batcher) => batcher.merge(&child_id, child_entry, child_demand_list),
+                    None => child_entry.extend(child_demand_list),
+                }
             }
 
             processed_components.insert(component_id);
         }
 
+        if let Some(tracer) = tracer.as_mut() {
+            tracer.finish(&dependent_demands, &all_planned_orders)?;
+        }
+
+        // 時間預算內未完成：標記為部分結果
+        if let Some(state) = budget.as_deref() {
+            for component_id in &state.pending_components {
+                result.add_warning(MrpWarning::warning(
+                    component_id.clone(),
+                    "超過計算時間預算，物料未計算".to_string(),
+                ));
+            }
+            result.unprocessed_components = state.pending_components.clone();
+        }
+
+        // 合約承諾量檢查
+        if !self.supply_contracts.is_empty() {
+            if self.policy.commitment_sourcing {
+                CommitmentTracker::bias_sourcing(&mut all_planned_orders, &self.supply_contracts);
+            }
+            self.check_commitments(&all_planned_orders, &mut result);
+        }
+
+        // 配套物料成套檢查
+        for set in &self.matched_sets {
+            let report = set.evaluate(
+                &inventory_map,
+                &grouped_supplies,
+                &[&grouped_demands, &dependent_demands],
+                &all_planned_orders,
+            );
+            if report.is_mismatched() {
+                result.add_warning(MrpWarning::warning(
+                    report.set_id.clone(),
+                    format!(
+                        "配套 {} 計劃期末可組 {} 套，不成套剩餘：{}",
+                        report.set_id,
+                        report.complete_sets,
+                        report
+                            .surplus
+                            .iter()
+                            .map(|(id, quantity)| format!("{} {}", id, quantity))
+                            .collect::<Vec<_>>()
+                            .join("、")
+                    ),
+                ));
+            }
+            result.matched_sets.push(report);
+        }
+
+        // 跨倉調撥建議
+        if let Some(as_of) = self
+            .plan_start(&time_buckets)
+            .filter(|_| !self.transfer_lanes.is_empty())
+        {
+            for proposal in RebalancingAnalyzer::propose(
+                &inventories,
+                &supplies,
+                &demands,
+                &self.transfer_lanes,
+                as_of,
+                self.transfer_horizon_days,
+            ) {
+                result.add_warning(MrpWarning::info(
+                    proposal.component_id.clone(),
+                    format!(
+                        "建議自 {} 調撥 {} 到 {}（{} 到貨，{} 起短缺），以減少新採購",
+                        proposal.from_warehouse,
+                        proposal.quantity,
+                        proposal.to_warehouse,
+                        proposal.arrival_date,
+                        proposal.shortage_date
+                    ),
+                ));
+                result.transfers.push(proposal);
+            }
+        }
+
+        // 呆滯／慢動物料分析
+        if let (Some(window_days), Some(as_of)) = (
+            self.policy.slow_mover_window_days,
+            self.plan_start(&time_buckets),
+        ) {
+            let mut demands_by_component: HashMap<String, Vec<&Demand>> = HashMap::new();
+            for (component_id, list) in grouped_demands.iter().chain(dependent_demands.iter()) {
+                demands_by_component
+                    .entry(component_id.clone())
+                    .or_default()
+                    .extend(list.iter());
+            }
+            result.slow_movers = crate::slow_movers::SlowMoverAnalyzer::analyze(
+                &inventory_map,
+                &demands_by_component,
+                &self.configs,
+                as_of,
+                window_days,
+            );
+        }
+
+        // 齊套檢查：生產計劃訂單開工日子件未齊時順延或標記
+        if let (Some(mode), Some(plan_start)) =
+            (self.policy.material_check, self.plan_start(&time_buckets))
+        {
+            let checker = crate::material_check::MaterialChecker::new(
+                &inventory_map,
+                &grouped_supplies,
+                &grouped_demands,
+                &dependent_demands,
+                plan_start,
+                &self.calendar,
+            );
+            for constraint in checker.check(&mut all_planned_orders, mode) {
+                let available_date = constraint
+                    .available_date
+                    .map_or_else(|| "-".to_string(), |d| d.to_string());
+                let message = if constraint.shifted {
+                    format!(
+                        "生產計劃訂單 {} 開工日 {} 子件 {} 不足，順延至 {} 開工",
+                        constraint.order_id,
+                        constraint.order_date,
+                        constraint.short_components.join("、"),
+                        available_date
+                    )
+                } else {
+                    format!(
+                        "生產計劃訂單 {} 開工日 {} 子件 {} 不足（最早齊套日 {}），物料受限",
+                        constraint.order_id,
+                        constraint.order_date,
+                        constraint.short_components.join("、"),
+                        available_date
+                    )
+                };
+                result.add_warning(MrpWarning::warning(
+                    constraint.component_id.clone(),
+                    message,
+                ));
+                result.material_constraints.push(constraint);
+            }
+        }
+
+        // 含計劃訂單入庫的時間分段記錄
+        result.time_phased = self.time_phase(
+            &inventory_map,
+            &grouped_supplies,
+            &[&grouped_demands, &dependent_demands],
+            &all_planned_orders,
+        );
+
         // Step 5: 需求追溯（Pegging）
         tracing::debug!("Step 5: 需求追溯");
-        let pegging = crate::pegging::PeggingCalculator::perform(
+        let mut pegging = crate::pegging::PeggingCalculator::perform(
             &all_planned_orders,
             &demands,
-            crate::pegging::PeggingType::MultiLevel,
+            self.policy.pegging_type,
         )?;
 
-        let mut result = MrpResult::empty();
+        // Step 6: 需求覆蓋分析
+        tracing::debug!("Step 6: 需求覆蓋分析");
+        let (coverage, dependent_coverage, supply_cleanups) = self.calculate_coverage(
+            &demands,
+            &dependent_demands,
+            &grouped_supplies,
+            &inventory_map,
+            &all_planned_orders,
+        );
+
+        // 替代料追溯：掛到實際分配給替代料相依需求的計劃訂單（由庫存或現有供應滿足的部分不追溯）
+        let substitutions = alternatives.into_substitutions();
+        for substitution in &substitutions {
+            let allocations = dependent_coverage
+                .get(&substitution.demand_id)
+                .into_iter()
+                .flat_map(|c| c.allocations.iter())
+                .filter(|a| a.source_type == crate::CoverageSourceType::PlannedOrder);
+            for allocation in allocations {
+                let Some(order_id) = allocation.source_id else {
+                    continue;
+                };
+                pegging.entry(order_id).or_default().push(
+                    mrp_core::PeggingRecord::new(substitution.demand_id, allocation.quantity)
+                        .with_path(vec![
+                            substitution.parent_id.clone(),
+                            substitution.substitute_id.clone(),
+                        ])
+                        .with_substitute_for(substitution.primary_id.clone()),
+                );
+            }
+        }
+        result.substitutions = substitutions;
+
+        // 延遲的計劃訂單：沿相依需求的來源訂單向上追溯到獨立需求，
+        // 回報該需求將延遲幾天（同一需求取最長延遲）
+        let demand_index: HashMap<uuid::Uuid, &Demand> = demands
+            .iter()
+            .chain(dependent_demands.values().flatten())
+            .map(|d| (d.id, d))
+            .collect();
+        let mut order_demands: HashMap<uuid::Uuid, Vec<&Demand>> = HashMap::new();
+        for covered in coverage.iter().chain(dependent_coverage.values()) {
+            let Some(&demand) = demand_index.get(&covered.demand_id) else {
+                continue;
+            };
+            for allocation in covered
+                .allocations
+                .iter()
+                .filter(|a| a.source_type == crate::CoverageSourceType::PlannedOrder)
+            {
+                if let Some(order_id) = allocation.source_id {
+                    order_demands.entry(order_id).or_default().push(demand);
+                }
+            }
+        }
+        let mut late_demands: BTreeMap<uuid::Uuid, (i64, &mrp_core::PlannedOrder)> =
+            BTreeMap::new();
+        for order in all_planned_orders.iter().filter(|o| o.is_late()) {
+            let mut roots = Self::root_demands(order.id, &order_demands, &demand_index);
+            roots.extend(
+                pegging
+                    .get(&order.id)
+                    .into_iter()
+                    .flatten()
+                    .map(|record| record.demand_id),
+            );
+            for demand_id in roots {
+                let entry = late_demands
+                    .entry(demand_id)
+                    .or_insert((order.days_late(), order));
+                if order.days_late() > entry.0 {
+                    *entry = (order.days_late(), order);
+                }
+            }
+        }
+        for demand in &demands {
+            if let Some(&(days, order)) = late_demands.get(&demand.id) {
+                result.add_warning(MrpWarning::warning(
+                    demand.component_id.clone(),
+                    format!(
+                        "需求 {}（需要日期 {}）將延遲 {} 天：{} 的計劃訂單 {} 預計完工 {}",
+                        demand
+                            .source_document
+                            .as_ref()
+                            .map_or_else(|| demand.id.to_string(), ToString::to_string),
+                        demand.required_date,
+                        days,
+                        order.component_id,
+                        order.id,
+                        order.required_date
+                    ),
+                ));
+            }
+        }
+
+        for cleanup in &supply_cleanups {
+            let supply = grouped_supplies
+                .get(&cleanup.component_id)
+                .into_iter()
+                .flatten()
+                .find(|s| s.id == cleanup.supply_id);
+            let label = supply
+                .and_then(|s| s.source_document.as_ref())
+                .map_or_else(|| cleanup.supply_id.to_string(), ToString::to_string);
+            let message = match cleanup.action {
+                crate::CleanupAction::Cancel => format!(
+                    "供應 {}（{}，數量 {}）已無需求追溯，建議取消",
+                    label, cleanup.available_date, cleanup.quantity
+                ),
+                _ => format!(
+                    "供應 {}（{}，數量 {}）只需 {}，建議減量 {}",
+                    label,
+                    cleanup.available_date,
+                    cleanup.quantity,
+                    cleanup.needed_quantity,
+                    cleanup.unneeded_quantity()
+                ),
+            };
+            result.add_warning(MrpWarning::warning(cleanup.component_id.clone(), message));
+        }
+        result.supply_cleanups = supply_cleanups;
+
+        if let Some(batcher) = batcher {
+            result.dependent_sources = batcher.into_sources();
+        }
         result.planned_orders = all_planned_orders;
         result.pegging = pegging;
+        result.tier_fill_rates =
+            crate::coverage::CoverageCalculator::fill_rate_by_tier(&demands, &coverage);
+        result.coverage = coverage;
         result.calculation_time_ms = Some(start_time.elapsed().as_millis());
 
         tracing::info!("MRP 計算完成，耗時 {:?}", start_time.elapsed());
@@ -177,11 +1238,27 @@ impl MrpCalculator {
             return Ok(ComponentMrpResult {
                 component_id: component_id.to_string(),
                 planned_orders: Vec::new(),
+                timing: ItemTiming::new(component_id.to_string()),
+                warnings: Vec::new(),
+                projection: None,
+                reschedules: Vec::new(),
+            });
+        }
+
+        // 計劃 BOM 產品族：不產生計劃訂單，需求於展開時按百分比轉給變體
+        if config.is_planning_bom() {
+            return Ok(ComponentMrpResult {
+                component_id: component_id.to_string(),
+                planned_orders: Vec::new(),
+                timing: ItemTiming::new(component_id.to_string()),
+                warnings: Vec::new(),
+                projection: None,
+                reschedules: Vec::new(),
             });
         }
 
         // 獲取該物料的供應和庫存
-        let component_supplies = grouped_supplies
+        let mut component_supplies = grouped_supplies
             .get(component_id)
             .cloned()
             .unwrap_or_default();
@@ -191,6 +1268,48 @@ impl MrpCalculator {
             .map(|inv| inv.available_qty)
             .unwrap_or_else(|| rust_decimal::Decimal::ZERO);
 
+        // 現有供應日期不符需求時發重排建議；確認供應不由 MRP 移動，建議提前的以需要日期計算淨需求
+        let reserve = if config.allow_negative_inventory {
+            rust_decimal::Decimal::ZERO
+        } else {
+            config.safety_stock
+        };
+        let reschedules = crate::supply_reschedule::SupplyRescheduler::reschedule(
+            component_demands,
+            &mut component_supplies,
+            initial_inventory,
+            reserve,
+            self.policy.reschedule_tolerance_days,
+        );
+        let mut warnings = Vec::new();
+        for message in &reschedules {
+            let action = match message.direction {
+                crate::RescheduleDirection::In => "提前",
+                crate::RescheduleDirection::Out => "延後",
+            };
+            let supply = if message.is_firm {
+                "確認供應"
+            } else {
+                "供應"
+            };
+            let reference = message
+                .source_ref
+                .clone()
+                .unwrap_or_else(|| message.supply_id.to_string());
+            warnings.push(MrpWarning::warning(
+                component_id.to_string(),
+                format!(
+                    "{} {} 建議{}：{} → {}（數量 {}）",
+                    supply,
+                    reference,
+                    action,
+                    message.current_date,
+                    message.suggested_date,
+                    message.quantity
+                ),
+            ));
+        }
+
         // 動態創建時間桶：合併基礎時間桶和該物料的實際需求/供應日期
         let component_time_buckets = self.create_component_time_buckets(
             time_buckets,
@@ -205,8 +1324,12 @@ impl MrpCalculator {
             component_time_buckets.len()
         );
 
+        let mut timing = ItemTiming::new(component_id.to_string());
+        timing.bucket_count = component_time_buckets.len();
+
         // 計算淨需求
-        let net_requirements = crate::netting::NettingCalculator::calculate(
+        let phase_start = std::time::Instant::now();
+        let mut net_requirements = crate::netting::NettingCalculator::calculate(
             component_demands,
             &component_supplies,
             initial_inventory,
@@ -214,14 +1337,379 @@ impl MrpCalculator {
             &component_time_buckets, // 使用動態時間桶
             config.allow_negative_inventory, // 從配置中讀取是否允許負庫存
         )?;
+        #[cfg(feature = "netting-trace")]
+        crate::netting::NettingCalculator::trace(component_id, &net_requirements);
+
+        timing.netting = phase_start.elapsed();
+
+        // 非 MRP 計劃的物料：再訂購點物料依庫存位置下單；
+        // 人工、消耗導向物料只預測庫存並回報短缺
+        if !config.is_mrp_planned() {
+            let planned_orders = if config.planning_method == mrp_core::PlanningMethod::ReorderPoint
+            {
+                crate::reorder_point::ReorderPointPlanner::plan(
+                    component_id,
+                    &net_requirements,
+                    initial_inventory,
+                    config,
+                    &self.calendar,
+                )?
+            } else {
+                if let Some(r) = net_requirements
+                    .iter()
+                    .find(|r| r.net_requirement > rust_decimal::Decimal::ZERO)
+                {
+                    warnings.push(MrpWarning::warning(
+                        component_id.to_string(),
+                        format!(
+                            "計劃方式 {:?} 不產生計劃訂單，預計 {} 起短缺 {}",
+                            config.planning_method, r.date, r.net_requirement
+                        ),
+                    ));
+                }
+                Vec::new()
+            };
+            return Ok(ComponentMrpResult {
+                component_id: component_id.to_string(),
+                planned_orders,
+                timing,
+                warnings,
+                projection: Some(net_requirements),
+                reschedules,
+            });
+        }
 
-        // 應用批量規則，生成計劃訂單
-        let planned_orders = crate::lot_sizing::LotSizingCalculator::apply(
+        // 短缺容差內的微量淨需求不產生計劃訂單；豁免的短缺逐期累計（各期新增的短缺量，
+        // 百分比以累計期間的總需求為基準），累計超過容差的那一期照常下單並重新累計
+        let mut tolerated = Vec::new();
+        if let Some(tolerance) = config.shortage_tolerance {
+            let mut waived = rust_decimal::Decimal::ZERO;
+            let mut waived_gross = rust_decimal::Decimal::ZERO;
+            let mut previous_net = rust_decimal::Decimal::ZERO;
+            for r in net_requirements.iter_mut() {
+                if r.net_requirement <= rust_decimal::Decimal::ZERO {
+                    waived = rust_decimal::Decimal::ZERO;
+                    waived_gross = rust_decimal::Decimal::ZERO;
+                    previous_net = rust_decimal::Decimal::ZERO;
+                    continue;
+                }
+                let added = (r.net_requirement - previous_net).max(rust_decimal::Decimal::ZERO);
+                previous_net = r.net_requirement;
+                if tolerance.tolerates(waived + added, waived_gross + r.gross_requirement) {
+                    waived += added;
+                    waived_gross += r.gross_requirement;
+                    tolerated.push(r.net_requirement);
+                    r.net_requirement = rust_decimal::Decimal::ZERO;
+                } else {
+                    waived = rust_decimal::Decimal::ZERO;
+                    waived_gross = rust_decimal::Decimal::ZERO;
+                }
+            }
+        }
+
+        // 應用批量規則，生成計劃訂單
+        let phase_start = std::time::Instant::now();
+        let mut planned_orders = crate::lot_sizing::LotSizingCalculator::apply_with_plan_start(
+            component_id,
+            &net_requirements,
+            config,
+            &self.calendar,
+            self.plan_start(time_buckets),
+        )?;
+
+        if !tolerated.is_empty() {
+            warnings.push(MrpWarning::info(
+                component_id.to_string(),
+                format!(
+                    "{} 期淨需求在短缺容差內（合計 {}），不產生計劃訂單",
+                    tolerated.len(),
+                    tolerated.iter().sum::<rust_decimal::Decimal>()
+                ),
+            ));
+        }
+
+        // 保存期限限制迫使增加的訂單
+        let extra_orders = crate::lot_sizing::LotSizingCalculator::shelf_life_extra_orders(
             component_id,
             &net_requirements,
             config,
             &self.calendar,
+            self.plan_start(time_buckets),
+            &planned_orders,
         )?;
+        if extra_orders > 0 {
+            warnings.push(MrpWarning::warning(
+                component_id.to_string(),
+                format!(
+                    "保存期限 {} 天限制單張訂單涵蓋範圍，多產生 {} 張計劃訂單",
+                    config.max_coverage_days.unwrap_or_default(),
+                    extra_orders
+                ),
+            ));
+        }
+
+        // 超過最大訂購量而拆單的需求日（批量規則每個需求日最多產生一張訂單）
+        if let Some(max_qty) = config.maximum_order_qty {
+            let mut orders_per_date: BTreeMap<chrono::NaiveDate, usize> = BTreeMap::new();
+            for order in &planned_orders {
+                *orders_per_date.entry(order.required_date).or_insert(0) += 1;
+            }
+            for (date, count) in orders_per_date.into_iter().filter(|(_, c)| *c > 1) {
+                warnings.push(MrpWarning::warning(
+                    component_id.to_string(),
+                    format!(
+                        "{} 的訂購量超過最大訂購量 {}，已拆成 {} 張計劃訂單",
+                        date, max_qty, count
+                    ),
+                ));
+            }
+        }
+
+        // 合併窗口內的計劃訂單
+        if let Some(window_days) = config.consolidation_window_days.filter(|d| *d > 0) {
+            let outcome = crate::consolidation::OrderConsolidator::consolidate(
+                planned_orders,
+                window_days,
+                config,
+                &self.calendar,
+            );
+            if !outcome.merged.is_empty() {
+                warnings.push(MrpWarning::info(
+                    component_id.to_string(),
+                    format!(
+                        "合併窗口 {} 天內的 {} 張計劃訂單已合併",
+                        window_days,
+                        outcome.merged.len()
+                    ),
+                ));
+            }
+            planned_orders = outcome.orders;
+        }
+
+        // 運輸方式選擇，再順排／倒排不可行時順排
+        if let Some(plan_start) = self.plan_start(time_buckets) {
+            crate::transport::TransportModeSelector::assign(
+                &mut planned_orders,
+                config,
+                plan_start,
+                &self.calendar,
+            );
+            // 指定計劃起始日時，開工日不早於起始日（設置逾期處理方式時由其決定）
+            let start_gated = self
+                .policy
+                .plan_start_date
+                .filter(|_| self.policy.past_due.is_none())
+                .filter(|start| {
+                    config
+                        .earliest_order_date
+                        .map_or(true, |date| date < *start)
+                })
+                .map(|start| config.clone().with_earliest_order_date(start));
+            let scheduling_config = start_gated.as_ref().unwrap_or(config);
+            if scheduling_config.scheduling_mode != mrp_core::SchedulingMode::Backward
+                || scheduling_config.earliest_order_date.is_some()
+            {
+                crate::scheduling::OrderScheduler::schedule(
+                    &mut planned_orders,
+                    scheduling_config,
+                    plan_start,
+                    &self.calendar,
+                );
+            }
+            // 逾期訂單：依政策壓縮提前期、自起始日順排或讓物料計算失敗
+            // （失敗只影響該物料：記錄錯誤、不產生計劃訂單也不展開子件，其他物料照常計算）
+            if let Some(past_due) = self.policy.past_due {
+                if !self.apply_past_due(
+                    component_id,
+                    &mut planned_orders,
+                    config,
+                    plan_start,
+                    past_due,
+                    &mut warnings,
+                ) {
+                    planned_orders.clear();
+                }
+            } else if config.scheduling_mode == mrp_core::SchedulingMode::Backward {
+                // 純倒排不改日期：開工日已過的訂單須提醒計劃員
+                for order in planned_orders.iter().filter(|o| o.order_date < plan_start) {
+                    warnings.push(MrpWarning::warning(
+                        component_id.to_string(),
+                        format!(
+                            "計劃訂單 {} 倒排開工日 {} 早於計劃起始日 {}（可改用倒排不可行時順排）",
+                            order.id, order.order_date, plan_start
+                        ),
+                    ));
+                }
+            }
+            // 計劃時界：時界內不產生計劃訂單，需求改發催交警告
+            if let Some(days) = config.planning_time_fence_days.filter(|d| *d > 0) {
+                let fence_end = self.calendar.add_working_days(plan_start, days);
+                let fenced = crate::planning_fence::PlanningTimeFence::apply(
+                    &mut planned_orders,
+                    fence_end,
+                    config,
+                    &self.calendar,
+                );
+                // 提前期長於時界時，邊界訂單的倒排開工日早於計劃起始日：
+                // 依逾期政策處理，未設定時開工日改為起始日（壓縮提前期）
+                let boundary = planned_orders
+                    .iter()
+                    .take_while(|o| o.required_date == fence_end)
+                    .count();
+                if !self.apply_past_due(
+                    component_id,
+                    &mut planned_orders[..boundary],
+                    config,
+                    plan_start,
+                    self.policy
+                        .past_due
+                        .unwrap_or(crate::PastDuePolicy::CompressLeadTime),
+                    &mut warnings,
+                ) {
+                    planned_orders.clear();
+                }
+                for requirement in &fenced {
+                    warnings.push(MrpWarning::warning(
+                        component_id.to_string(),
+                        format!(
+                            "計劃時界內 {} 需要 {}，不產生計劃訂單，請催交現有供應",
+                            requirement.need_date, requirement.quantity
+                        ),
+                    ));
+                }
+                if !fenced.is_empty() {
+                    warnings.push(MrpWarning::info(
+                        component_id.to_string(),
+                        format!("時界內的計劃訂單已順延到計劃時界邊界 {}", fence_end),
+                    ));
+                }
+            }
+        }
+
+        // 框架訂單叫貨
+        let blankets: Vec<BlanketOrder> = self
+            .blanket_orders
+            .iter()
+            .filter(|b| b.component_id == component_id)
+            .cloned()
+            .collect();
+        if !blankets.is_empty() {
+            planned_orders =
+                crate::call_off::CallOffPlanner::apply(planned_orders, &blankets, &self.calendar);
+        }
+
+        timing.lot_sizing = phase_start.elapsed();
+
+        // 寫入預設供應商
+        if let Some(supplier_id) = &config.supplier_id {
+            for order in planned_orders.iter_mut().filter(|o| o.source_id.is_none()) {
+                order.source_id = Some(supplier_id.clone());
+            }
+        }
+
+        // 收貨日對齊到收貨日曆
+        let before_alignment: HashMap<uuid::Uuid, (chrono::NaiveDate, chrono::NaiveDate)> =
+            planned_orders
+                .iter()
+                .map(|o| (o.id, (o.order_date, o.required_date)))
+                .collect();
+        let aligned = self
+            .receipt_calendars
+            .align(&mut planned_orders, config, &self.calendar)?;
+        if aligned > 0 {
+            warnings.push(MrpWarning::info(
+                component_id.to_string(),
+                format!(
+                    "{} 張計劃訂單的收貨日不是收貨日，已提前到前一個收貨日",
+                    aligned
+                ),
+            ));
+        }
+        // 收貨日提前後開工日可能早於計劃起始日、收貨日可能落入計劃時界：重新檢查
+        if let Some(plan_start) = self.plan_start(time_buckets).filter(|_| aligned > 0) {
+            // 純倒排且未以逾期政策、起始日或時界限制開工日時只提醒，其餘依逾期政策處理
+            let fence_days = config.planning_time_fence_days.filter(|d| *d > 0);
+            let policy = self.policy.past_due.or_else(|| {
+                (config.scheduling_mode != mrp_core::SchedulingMode::Backward
+                    || self.policy.plan_start_date.is_some()
+                    || fence_days.is_some())
+                .then_some(crate::PastDuePolicy::CompressLeadTime)
+            });
+            let fence_end = fence_days.map(|days| self.calendar.add_working_days(plan_start, days));
+            let mut failed = false;
+            for order in planned_orders.iter_mut() {
+                let Some(&(order_date, required_date)) = before_alignment.get(&order.id) else {
+                    continue;
+                };
+                if order.required_date == required_date {
+                    continue;
+                }
+                if let Some(fence_end) = fence_end.filter(|end| order.required_date < *end) {
+                    warnings.push(MrpWarning::warning(
+                        component_id.to_string(),
+                        format!(
+                            "計劃訂單 {} 的收貨日提前到 {}，落在計劃時界 {} 內，請確認供應商可提前交貨",
+                            order.id, order.required_date, fence_end
+                        ),
+                    ));
+                }
+                // 對齊前已逾期的訂單在前面已處理或提醒過
+                if order.order_date >= plan_start || order_date < plan_start {
+                    continue;
+                }
+                match policy {
+                    Some(policy) => {
+                        failed |= !self.apply_past_due(
+                            component_id,
+                            std::slice::from_mut(order),
+                            config,
+                            plan_start,
+                            policy,
+                            &mut warnings,
+                        );
+                    }
+                    None => warnings.push(MrpWarning::warning(
+                        component_id.to_string(),
+                        format!(
+                            "計劃訂單 {} 倒排開工日 {} 早於計劃起始日 {}（可改用倒排不可行時順排）",
+                            order.id, order.order_date, plan_start
+                        ),
+                    )),
+                }
+                if failed {
+                    break;
+                }
+            }
+            if failed {
+                planned_orders.clear();
+            }
+        }
+
+        // 下單日對齊到供應商固定下單星期
+        if let Some(plan_start) = self.plan_start(time_buckets) {
+            let (aligned, late) =
+                self.ordering_days
+                    .align(&mut planned_orders, plan_start, &self.calendar);
+            if aligned > 0 {
+                warnings.push(MrpWarning::info(
+                    component_id.to_string(),
+                    format!("{} 張採購計劃訂單的下單日已對齊到供應商的下單日", aligned),
+                ));
+            }
+            for release in late {
+                warnings.push(MrpWarning::warning(
+                    component_id.to_string(),
+                    format!(
+                        "計劃訂單 {} 最早可於 {} 下單，預計 {} 到貨，晚於需要日期 {}（延遲 {} 天）",
+                        release.order_id,
+                        release.order_date,
+                        release.required_date,
+                        release.need_date,
+                        (release.required_date - release.need_date).num_days()
+                    ),
+                ));
+            }
+        }
 
         tracing::debug!(
             "物料 {} 計劃訂單: {} 筆",
@@ -232,9 +1720,372 @@ impl MrpCalculator {
         Ok(ComponentMrpResult {
             component_id: component_id.to_string(),
             planned_orders,
+            timing,
+            warnings,
+            projection: None,
+            reschedules,
         })
     }
 
+    /// 將單物料的計劃訂單排入每日產能
+    fn apply_capacity_constraint(
+        &self,
+        component_result: &mut ComponentMrpResult,
+        time_buckets: &[chrono::NaiveDate],
+        diagnostics: &mut Vec<CapacityDiagnostic>,
+        result: &mut MrpResult,
+    ) -> mrp_core::Result<()> {
+        let Some(config) = self.configs.get(&component_result.component_id) else {
+            return Ok(());
+        };
+        let Some(capacity_per_day) = config.capacity_per_day else {
+            return Ok(());
+        };
+        if component_result.planned_orders.is_empty() {
+            return Ok(());
+        }
+
+        // 提前生產不早於計劃起點（最早的時間桶）
+        let earliest_date = time_buckets
+            .first()
+            .copied()
+            .or_else(|| {
+                component_result
+                    .planned_orders
+                    .iter()
+                    .map(|o| o.order_date)
+                    .min()
+            })
+            .unwrap_or_default();
+
+        let (orders, component_diagnostics) = CapacityConstrainedPlanner::constrain(
+            &component_result.component_id,
+            std::mem::take(&mut component_result.planned_orders),
+            capacity_per_day,
+            config,
+            earliest_date,
+            &self.calendar,
+        )?;
+
+        for diagnostic in &component_diagnostics {
+            if diagnostic.adjustment == crate::capacity_constrained::CapacityAdjustment::Late {
+                result.add_warning(MrpWarning::warning(
+                    diagnostic.component_id.clone(),
+                    format!(
+                        "產能不足：{} 件原定 {} 完成，延至 {}（延遲 {} 天）",
+                        diagnostic.quantity,
+                        diagnostic.original_required_date,
+                        diagnostic.planned_date,
+                        diagnostic.days_late()
+                    ),
+                ));
+            }
+        }
+
+        component_result.planned_orders = orders;
+        diagnostics.extend(component_diagnostics);
+
+        Ok(())
+    }
+
+    /// 共用子件分配：多個父件（及子件本身的獨立需求）競爭同一子件時決定哪些父件訂單延後
+    ///
+    /// 子件新訂單最早於計劃起始日加子件提前期到貨，此前的需求只能由現有庫存與供應滿足，
+    /// 按策略分配。分不到的父件計劃訂單延到子件補貨到位日開工；部分分到時拆成兩張，
+    /// 分到的部分保留原日期。變更的父件訂單重新展開，尚未計算的子件改用新的相依需求
+    /// （已計算的其他子件保留原需求日期）。獨立需求短缺只記錄警告。
+    #[allow(clippy::too_many_arguments)]
+    fn allocate_shared_component(
+        &self,
+        component_id: &str,
+        independent_demands: &[Demand],
+        dependent_demands: &mut HashMap<String, Vec<Demand>>,
+        planned_orders: &mut Vec<mrp_core::PlannedOrder>,
+        processed_components: &std::collections::HashSet<String>,
+        grouped_supplies: &HashMap<String, Vec<Supply>>,
+        inventory_map: &HashMap<String, Inventory>,
+        strategy: AllocationStrategy,
+        plan_start: Option<chrono::NaiveDate>,
+        result: &mut MrpResult,
+    ) -> mrp_core::Result<()> {
+        let (Some(dep_demands), Some(config), Some(plan_start)) = (
+            dependent_demands.get(component_id),
+            self.configs.get(component_id),
+            plan_start,
+        ) else {
+            return Ok(());
+        };
+        let replenished = self
+            .calendar
+            .add_working_days(plan_start, config.lead_time_days);
+        let pool: Vec<Demand> = independent_demands
+            .iter()
+            .chain(dep_demands)
+            .filter(|d| d.required_date < replenished)
+            .cloned()
+            .collect();
+
+        // 只有多個需求來源（父件或獨立需求）競爭時才分配
+        let claimants: std::collections::HashSet<Option<&str>> = pool
+            .iter()
+            .map(|d| {
+                d.is_dependent()
+                    .then(|| d.source_ref.as_deref().and_then(|r| r.rsplit_once(':')))
+                    .flatten()
+                    .map(|(parent_id, _)| parent_id)
+            })
+            .collect();
+        if claimants.len() < 2 {
+            return Ok(());
+        }
+
+        let allocations = AllocationCalculator::allocate(
+            component_id,
+            &pool,
+            inventory_map
+                .get(component_id)
+                .map(|inv| inv.available_qty)
+                .unwrap_or(rust_decimal::Decimal::ZERO),
+            grouped_supplies
+                .get(component_id)
+                .map(|s| s.as_slice())
+                .unwrap_or_default(),
+            strategy,
+        );
+
+        // 父件ID → 變更的訂單；新拆出的訂單ID → 原訂單ID（沿用原訂單相依需求的等級與金額）
+        let mut changed: BTreeMap<String, Vec<uuid::Uuid>> = BTreeMap::new();
+        let mut split_from: HashMap<uuid::Uuid, uuid::Uuid> = HashMap::new();
+        for allocation in allocations.iter().filter(|a| a.is_short()) {
+            let slipped = allocation.parent_id.as_deref().zip(
+                allocation
+                    .parent_order_id
+                    .and_then(|order_id| planned_orders.iter().position(|o| o.id == order_id)),
+            );
+            let Some((parent_id, index)) = slipped else {
+                result.add_warning(MrpWarning::warning(
+                    component_id.to_string(),
+                    format!(
+                        "子件 {} 於 {} 供應不足：{} 需求 {}，僅分配 {}",
+                        component_id,
+                        allocation.required_date,
+                        allocation.parent_id.as_deref().unwrap_or("獨立"),
+                        allocation.requested_qty,
+                        allocation.allocated_qty
+                    ),
+                ));
+                continue;
+            };
+
+            // 子件需求日相對父件開工日的偏移不變，父件開工日順延到子件補貨到位日
+            let delay = replenished - allocation.required_date;
+            let order = &mut planned_orders[index];
+            let kept =
+                (order.quantity * allocation.allocated_qty / allocation.requested_qty).floor();
+            let mut slipped_order = order.clone();
+            if kept > rust_decimal::Decimal::ZERO {
+                slipped_order.id = uuid::Uuid::new_v4();
+                slipped_order.quantity = order.quantity - kept;
+                order.quantity = kept;
+                split_from.insert(slipped_order.id, order.id);
+            }
+            self.delay_order(parent_id, &mut slipped_order, delay);
+            result.add_warning(MrpWarning::warning(
+                component_id.to_string(),
+                format!(
+                    "子件 {} 於 {} 供應不足：父件 {} 需求 {}，僅分配 {}，計劃訂單 {} 的 {} 延到 {} 開工（{} 完工）",
+                    component_id,
+                    allocation.required_date,
+                    parent_id,
+                    allocation.requested_qty,
+                    allocation.allocated_qty,
+                    order.id,
+                    slipped_order.quantity,
+                    slipped_order.order_date,
+                    slipped_order.required_date
+                ),
+            ));
+            let ids = changed.entry(parent_id.to_string()).or_default();
+            ids.push(order.id);
+            if slipped_order.id == order.id {
+                *order = slipped_order;
+            } else {
+                ids.push(slipped_order.id);
+                planned_orders.push(slipped_order);
+            }
+        }
+
+        // 重新展開變更的父件訂單，取代尚未計算子件的原相依需求
+        for (parent_id, ids) in changed {
+            let source_of = |demand: &Demand| {
+                demand
+                    .source_ref
+                    .as_deref()
+                    .and_then(|r| r.strip_prefix(parent_id.as_str()))
+                    .and_then(|r| r.strip_prefix(':'))
+                    .and_then(|id| uuid::Uuid::parse_str(id).ok())
+                    .filter(|id| ids.contains(id))
+            };
+            let mut templates: HashMap<(String, uuid::Uuid), Demand> = HashMap::new();
+            for (child_id, list) in dependent_demands.iter_mut() {
+                if processed_components.contains(child_id) {
+                    continue;
+                }
+                list.retain(|demand| match source_of(demand) {
+                    Some(order_id) => {
+                        templates.insert((child_id.clone(), order_id), demand.clone());
+                        false
+                    }
+                    None => true,
+                });
+            }
+            let orders: Vec<mrp_core::PlannedOrder> = planned_orders
+                .iter()
+                .filter(|o| ids.contains(&o.id))
+                .cloned()
+                .collect();
+            for (child_id, child_demands) in self.explode_bom(&parent_id, &orders, None)? {
+                if processed_components.contains(&child_id) {
+                    continue;
+                }
+                for mut demand in child_demands {
+                    let Some(order_id) = source_of(&demand) else {
+                        continue;
+                    };
+                    let original = split_from.get(&order_id).copied().unwrap_or(order_id);
+                    if let Some(template) = templates.get(&(child_id.clone(), original)) {
+                        demand.customer_tier = template.customer_tier;
+                        demand.priority = template.priority;
+                        demand.unit_value = template.unit_value;
+                    }
+                    dependent_demands
+                        .entry(child_id.clone())
+                        .or_default()
+                        .push(demand);
+                }
+            }
+        }
+
+        result.allocations.extend(allocations);
+        Ok(())
+    }
+
+    /// 父件計劃訂單延後 `delay`：開工日順延到工作日，完工日按提前期重算，原完工日記為需要日期
+    fn delay_order(
+        &self,
+        parent_id: &str,
+        order: &mut mrp_core::PlannedOrder,
+        delay: chrono::Duration,
+    ) {
+        let mut order_date = order.order_date + delay;
+        if !self.calendar.is_working_day(order_date) {
+            order_date = self.calendar.next_working_day(order_date);
+        }
+        let lead_time = self
+            .configs
+            .get(parent_id)
+            .map_or(0, |config| config.lead_time_for(order.quantity));
+        let need_date = order.need_date.unwrap_or(order.required_date);
+        order.order_date = order_date;
+        order.required_date = self.calendar.add_working_days(order_date, lead_time);
+        if order.required_date > need_date {
+            order.need_date = Some(need_date);
+        }
+    }
+
+    /// 計算每筆輸入需求的覆蓋明細與現有供應的清理建議
+    ///
+    /// 相依需求也參與分配（它們同樣消耗庫存與供應）；輸入需求的結果按輸入順序輸出，
+    /// 相依需求的結果另以需求ID為鍵回傳。清理建議依所有需求（含相依需求）的追溯判斷
+    fn calculate_coverage(
+        &self,
+        demands: &[Demand],
+        dependent_demands: &HashMap<String, Vec<Demand>>,
+        grouped_supplies: &HashMap<String, Vec<Supply>>,
+        inventory_map: &HashMap<String, Inventory>,
+        planned_orders: &[mrp_core::PlannedOrder],
+    ) -> (
+        Vec<crate::DemandCoverage>,
+        HashMap<uuid::Uuid, crate::DemandCoverage>,
+        Vec<crate::SupplyCleanup>,
+    ) {
+        let mut all_demands = self.group_demands_by_component(demands);
+        for (component_id, dep_demands) in dependent_demands {
+            all_demands
+                .entry(component_id.clone())
+                .or_default()
+                .extend(dep_demands.iter().cloned());
+        }
+
+        let safety_stock = |component_id: &str| {
+            self.configs
+                .get(component_id)
+                .map_or(rust_decimal::Decimal::ZERO, |c| c.safety_stock)
+        };
+        let mut coverage_map = HashMap::new();
+        let mut cleanups = Vec::new();
+        for (component_id, component_demands) in &all_demands {
+            let component_orders: Vec<_> = planned_orders
+                .iter()
+                .filter(|o| &o.component_id == component_id)
+                .cloned()
+                .collect();
+
+            let initial_inventory = inventory_map
+                .get(component_id)
+                .map(|inv| inv.available_qty)
+                .unwrap_or(rust_decimal::Decimal::ZERO);
+
+            let component_supplies = grouped_supplies
+                .get(component_id)
+                .map(|s| s.as_slice())
+                .unwrap_or_default();
+            let coverages = crate::coverage::CoverageCalculator::calculate(
+                component_demands,
+                initial_inventory,
+                component_supplies,
+                &component_orders,
+            );
+            cleanups.extend(crate::supply_cleanup::SupplyCleanupAdvisor::suggest(
+                component_supplies,
+                &coverages,
+                initial_inventory,
+                safety_stock(component_id),
+            ));
+
+            for coverage in coverages {
+                coverage_map.insert(coverage.demand_id, coverage);
+            }
+        }
+
+        // 沒有任何需求的物料：現有供應都不需要（安全庫存除外）
+        for (component_id, component_supplies) in grouped_supplies {
+            if all_demands.contains_key(component_id) {
+                continue;
+            }
+            let initial_inventory = inventory_map
+                .get(component_id)
+                .map(|inv| inv.available_qty)
+                .unwrap_or(rust_decimal::Decimal::ZERO);
+            cleanups.extend(crate::supply_cleanup::SupplyCleanupAdvisor::suggest(
+                component_supplies,
+                &[],
+                initial_inventory,
+                safety_stock(component_id),
+            ));
+        }
+        cleanups.sort_by(|a, b| {
+            (&a.component_id, a.available_date).cmp(&(&b.component_id, b.available_date))
+        });
+
+        // 按輸入需求的順序輸出
+        let coverage = demands
+            .iter()
+            .filter_map(|d| coverage_map.remove(&d.id))
+            .collect();
+        (coverage, coverage_map, cleanups)
+    }
+
     /// 按物料分組需求
     fn group_demands_by_component(&self, demands: &[Demand]) -> HashMap<String, Vec<Demand>> {
         let mut grouped = HashMap::new();
@@ -259,6 +2110,143 @@ impl MrpCalculator {
         grouped
     }
 
+    /// 依逾期政策調整開工日早於計劃起始日的訂單，並記錄調整警告
+    ///
+    /// 政策為失敗時記錄錯誤並回傳 false（呼叫端不產生該物料的計劃訂單）
+    fn apply_past_due(
+        &self,
+        component_id: &str,
+        orders: &mut [mrp_core::PlannedOrder],
+        config: &MrpConfig,
+        plan_start: chrono::NaiveDate,
+        policy: crate::PastDuePolicy,
+        warnings: &mut Vec<MrpWarning>,
+    ) -> bool {
+        let adjustments = match crate::past_due::PastDueHandler::apply(
+            orders,
+            config,
+            plan_start,
+            &self.calendar,
+            policy,
+        ) {
+            Ok(adjustments) => adjustments,
+            Err(error) => {
+                warnings.push(MrpWarning::error(
+                    component_id.to_string(),
+                    error.to_string(),
+                ));
+                return false;
+            }
+        };
+        for adjustment in adjustments {
+            let message = if adjustment.days_late() > 0 {
+                format!(
+                    "計劃訂單 {} 倒排開工日 {} 早於計劃起始日，改自 {} 開工，預計 {} 到貨（延遲 {} 天）",
+                    adjustment.order_id,
+                    adjustment.original_order_date,
+                    adjustment.order_date,
+                    adjustment.required_date,
+                    adjustment.days_late()
+                )
+            } else {
+                format!(
+                    "計劃訂單 {} 倒排開工日 {} 早於計劃起始日，改自 {} 開工，提前期壓縮為 {} 個工作日",
+                    adjustment.order_id,
+                    adjustment.original_order_date,
+                    adjustment.order_date,
+                    self.calendar
+                        .working_days_between(adjustment.order_date, adjustment.required_date)
+                )
+            };
+            warnings.push(MrpWarning::warning(component_id.to_string(), message));
+        }
+        true
+    }
+
+    /// 計劃訂單最終服務的獨立需求
+    ///
+    /// 相依需求的 `source_ref` 為 `父件:來源ID`；來源為父件計劃訂單時繼續追溯該訂單滿足的需求，
+    /// 來源為需求（計劃 BOM 展開）時直接取該需求
+    fn root_demands(
+        order_id: uuid::Uuid,
+        order_demands: &HashMap<uuid::Uuid, Vec<&Demand>>,
+        demand_index: &HashMap<uuid::Uuid, &Demand>,
+    ) -> BTreeSet<uuid::Uuid> {
+        let mut roots = BTreeSet::new();
+        let mut visited = std::collections::HashSet::new();
+        let mut pending: Vec<&Demand> = order_demands.get(&order_id).cloned().unwrap_or_default();
+        visited.insert(order_id);
+        while let Some(demand) = pending.pop() {
+            if demand.demand_type != mrp_core::DemandType::Dependent {
+                roots.insert(demand.id);
+                continue;
+            }
+            let Some(source_id) = demand
+                .source_ref
+                .as_deref()
+                .and_then(|r| r.rsplit(':').next())
+                .and_then(|id| uuid::Uuid::parse_str(id).ok())
+            else {
+                continue;
+            };
+            if let Some(&source) = demand_index.get(&source_id) {
+                pending.push(source);
+            } else if visited.insert(source_id) {
+                pending.extend(order_demands.get(&source_id).into_iter().flatten());
+            }
+        }
+        roots
+    }
+
+    /// 物料的計劃訂單各自滿足哪些需求（計劃訂單ID → (需求, 分配數量)）
+    ///
+    /// 以需求覆蓋分配判斷：庫存與現有供應先分配，其餘由計劃訂單按日期先後滿足
+    fn order_demand_sources<'d>(
+        &self,
+        component_id: &str,
+        component_demands: &'d [Demand],
+        planned_orders: &[mrp_core::PlannedOrder],
+        grouped_supplies: &HashMap<String, Vec<Supply>>,
+        inventory_map: &HashMap<String, Inventory>,
+    ) -> HashMap<uuid::Uuid, Vec<(&'d Demand, rust_decimal::Decimal)>> {
+        let mut sources: HashMap<uuid::Uuid, Vec<(&Demand, rust_decimal::Decimal)>> =
+            HashMap::new();
+        if planned_orders.is_empty() {
+            return sources;
+        }
+        let coverages = crate::coverage::CoverageCalculator::calculate(
+            component_demands,
+            inventory_map
+                .get(component_id)
+                .map_or(rust_decimal::Decimal::ZERO, |inv| inv.available_qty),
+            grouped_supplies
+                .get(component_id)
+                .map_or(&[][..], |list| list.as_slice()),
+            planned_orders,
+        );
+        for coverage in coverages {
+            let Some(demand) = component_demands
+                .iter()
+                .find(|d| d.id == coverage.demand_id)
+            else {
+                continue;
+            };
+            for allocation in coverage
+                .allocations
+                .iter()
+                .filter(|a| a.source_type == crate::CoverageSourceType::PlannedOrder)
+            {
+                if let Some(order_id) = allocation.source_id {
+                    sources
+                        .entry(order_id)
+                        .or_default()
+                        .push((demand, allocation.quantity));
+                }
+            }
+        }
+        sources
+    }
+
     /// 創建庫存映射
     fn create_inventory_map(&self, inventories: &[Inventory]) -> HashMap<String, Inventory> {
         inventories
@@ -267,6 +2255,77 @@ impl MrpCalculator {
             .collect()
     }
 
+    /// 逐物料時間分段記錄：現有可用庫存 + 供應 + 計劃訂單 − 獨立與相依需求，按異動日期累計
+    fn time_phase(
+        &self,
+        inventories: &HashMap<String, Inventory>,
+        supplies: &HashMap<String, Vec<Supply>>,
+        demands: &[&HashMap<String, Vec<Demand>>],
+        planned_orders: &[mrp_core::PlannedOrder],
+    ) -> HashMap<String, Vec<crate::TimePhasedRecord>> {
+        type Records =
+            HashMap<String, std::collections::BTreeMap<chrono::NaiveDate, crate::TimePhasedRecord>>;
+        fn record<'a>(
+            records: &'a mut Records,
+            component_id: &str,
+            date: chrono::NaiveDate,
+        ) -> &'a mut crate::TimePhasedRecord {
+            records
+                .entry(component_id.to_string())
+                .or_default()
+                .entry(date)
+                .or_insert_with(|| crate::TimePhasedRecord::new(date))
+        }
+
+        let mut records = Records::new();
+        for supply in supplies.values().flatten() {
+            record(&mut records, &supply.component_id, supply.available_date).scheduled_receipt +=
+                supply.quantity;
+        }
+        for order in planned_orders {
+            record(&mut records, &order.component_id, order.required_date).planned_receipt +=
+                order.quantity;
+        }
+        for demand in demands
+            .iter()
+            .flat_map(|grouped| grouped.values().flatten())
+        {
+            record(&mut records, &demand.component_id, demand.required_date).gross_requirement +=
+                demand.quantity;
+        }
+
+        records
+            .into_iter()
+            .map(|(component_id, entries)| {
+                let mut on_hand = inventories
+                    .get(&component_id)
+                    .map_or(rust_decimal::Decimal::ZERO, |inv| inv.available_qty);
+                let floor = self
+                    .config(&component_id)
+                    .filter(|config| !config.allow_negative_inventory)
+                    .map_or(rust_decimal::Decimal::ZERO, |config| config.safety_stock);
+                let rows = entries
+                    .into_values()
+                    .map(|mut row| {
+                        on_hand += row.scheduled_receipt - row.gross_requirement;
+                        row.net_requirement = (floor - on_hand).max(rust_decimal::Decimal::ZERO);
+                        on_hand += row.planned_receipt;
+                        row.projected_on_hand = on_hand;
+                        row
+                    })
+                    .collect();
+                (component_id, rows)
+            })
+            .collect()
+    }
+
+    /// 計劃起始日（未指定時為第一個時間桶）
+    fn plan_start(&self, time_buckets: &[chrono::NaiveDate]) -> Option<chrono::NaiveDate> {
+        self.policy
+            .plan_start_date
+            .or_else(|| time_buckets.first().copied())
+    }
+
     /// 獲取最大計劃時界（天數）
     fn get_max_planning_horizon(&self) -> u32 {
         self.configs
@@ -276,31 +2335,190 @@ impl MrpCalculator {
             .unwrap_or(90) // 預設 90 天
     }
 
-    /// 拓撲排序（依 BOM 層級）
-    /// 返回排序後的物料列表（從子件到父件）
+    /// 拓撲排序（依 BOM 低階碼）
+    ///
+    /// 自有需求的物料沿 BOM（含情境覆寫與計劃 BOM 變體）找出所有可能產生需求的物料，
+    /// 以 Kahn 演算法計算低階碼（物料在任何父件路徑上的最深層級），回傳按
+    /// （低階碼, 物料ID）排序的（物料ID, 低階碼）。共用子件因此在所有父件之後才計算，
+    /// 不會因多次計算而拆成重複訂單。
     fn topological_sort(
         &self,
         grouped_demands: &HashMap<String, Vec<Demand>>,
-    ) -> mrp_core::Result<Vec<String>> {
-        // 收集所有需要計算的物料
-        let components: Vec<String> = grouped_demands.keys().cloned().collect();
+    ) -> mrp_core::Result<Vec<(String, u32)>> {
+        // 收集可達物料與邊
+        let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+        let mut stack: Vec<String> = grouped_demands.keys().cloned().collect();
+        while let Some(parent_id) = stack.pop() {
+            if edges.contains_key(&parent_id) {
+                continue;
+            }
+            let mut children: Vec<String> = self
+                .bom_children(&parent_id)
+                .into_iter()
+                .map(|item| item.child_id.as_str().to_string())
+                .collect();
+            if let Some(config) = self.configs.get(&parent_id) {
+                children.extend(config.planning_percentages.keys().cloned());
+            }
+            children.sort_unstable();
+            children.dedup();
+            stack.extend(children.iter().cloned());
+            edges.insert(parent_id, children);
+        }
+
+        let mut in_degree: HashMap<&str, usize> = edges.keys().map(|id| (id.as_str(), 0)).collect();
+        for child_id in edges.values().flatten() {
+            *in_degree.entry(child_id.as_str()).or_default() += 1;
+        }
+
+        let mut ready: Vec<&str> = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        let mut codes: HashMap<&str, u32> = ready.iter().map(|id| (*id, 0)).collect();
+        while let Some(parent_id) = ready.pop() {
+            let parent_code = codes[parent_id];
+            for child_id in &edges[parent_id] {
+                let code = codes.entry(child_id.as_str()).or_default();
+                *code = (*code).max(parent_code + 1);
+                let degree = in_degree
+                    .get_mut(child_id.as_str())
+                    .expect("子件已計入入度");
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(child_id.as_str());
+                }
+            }
+        }
+
+        let blocked: BTreeSet<&str> = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree > 0)
+            .map(|(id, _)| *id)
+            .collect();
+        if !blocked.is_empty() {
+            let cycle = Self::find_cycle(&edges, &blocked);
+            return Err(mrp_core::MrpError::TopologicalSortError(format!(
+                "BOM 含循環結構：{}",
+                cycle.join(" → ")
+            )));
+        }
 
-        // 使用 BOM 圖進行拓撲排序
-        // 簡化實現：如果沒有 BOM 關係，直接返回
-        // 實際應該使用 BOM 圖的 topological_sort 方法
+        let mut sorted: Vec<(String, u32)> = codes
+            .into_iter()
+            .map(|(id, code)| (id.to_string(), code))
+            .collect();
+        sorted.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(sorted)
+    }
+
+    /// 在排序後仍有入度的物料中找出一條循環路徑（首尾為同一物料，依父件 → 子件順序）
+    ///
+    /// 這些物料都至少有一個同樣未排序的父件，沿父件往回走必定回到走過的物料。
+    fn find_cycle(edges: &HashMap<String, Vec<String>>, blocked: &BTreeSet<&str>) -> Vec<String> {
+        let mut parents: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for (parent_id, children) in edges {
+            if !blocked.contains(parent_id.as_str()) {
+                continue;
+            }
+            for child_id in children.iter().filter(|c| blocked.contains(c.as_str())) {
+                parents
+                    .entry(child_id.as_str())
+                    .or_default()
+                    .insert(parent_id.as_str());
+            }
+        }
+
+        let mut walk: Vec<&str> = Vec::new();
+        let mut current = *blocked.iter().next().expect("至少一個未排序物料");
+        while !walk.contains(&current) {
+            walk.push(current);
+            current = parents
+                .get(current)
+                .and_then(|p| p.iter().next())
+                .copied()
+                .expect("未排序物料必有未排序的父件");
+        }
+
+        let start = walk.iter().position(|id| *id == current).unwrap_or(0);
+        let mut cycle: Vec<String> = walk[start..]
+            .iter()
+            .rev()
+            .map(|id| id.to_string())
+            .collect();
+        cycle.insert(0, current.to_string());
+        cycle
+    }
+
+    /// 確認的生產計劃供應轉回計劃訂單（物料ID → 訂單），供展開子件需求
+    ///
+    /// 訂單ID沿用原計劃訂單（沒有引擎標記時為供應ID），開工日依物料提前期自可用日期倒推。
+    fn firm_planned_orders(
+        &self,
+        supplies: &[Supply],
+    ) -> BTreeMap<String, Vec<mrp_core::PlannedOrder>> {
+        let mut orders: BTreeMap<String, Vec<mrp_core::PlannedOrder>> = BTreeMap::new();
+        for supply in supplies.iter().filter(|s| s.is_firm && s.is_planned()) {
+            let Some(config) = self
+                .configs
+                .get(&supply.component_id)
+                .filter(|c| c.procurement_type == mrp_core::ProcurementType::Make)
+            else {
+                continue;
+            };
+            let mut order = mrp_core::PlannedOrder::new(
+                supply.component_id.clone(),
+                supply.quantity,
+                supply.available_date,
+                self.calendar.subtract_working_days(
+                    supply.available_date,
+                    config.lead_time_for(supply.quantity),
+                ),
+                mrp_core::PlannedOrderType::Production,
+            );
+            order.id = supply.engine_order_id().unwrap_or(supply.id);
+            orders
+                .entry(supply.component_id.clone())
+                .or_default()
+                .push(order);
+        }
+        orders
+    }
 
-        // TODO: 整合 BOM 引擎的拓撲排序
-        // let sorted = self.bom_graph.topological_sort(&components)?;
+    /// 父件的 BOM 行；情境覆寫優先於 BOM 提供者
+    pub fn bom_children(&self, parent_id: &str) -> Vec<bom_core::BomItem> {
+        match self.bom_overrides.get(parent_id) {
+            Some(items) => items.clone(),
+            None => self.bom_provider.children(parent_id),
+        }
+    }
 
-        // 臨時實現：直接返回原列表
-        Ok(components)
+    /// 檢查父件所有 BOM 行的用量異常
+    fn bom_anomalies(&self, parent_id: &str) -> Vec<BomAnomaly> {
+        self.bom_children(parent_id)
+            .iter()
+            .flat_map(|item| {
+                BomAnomalyDetector::check(
+                    parent_id,
+                    item.child_id.as_str(),
+                    item,
+                    self.policy.bom_quantity_threshold,
+                )
+            })
+            .collect()
     }
 
     /// BOM 展開：根據計劃訂單生成子件的相依需求
+    ///
+    /// 同一替代群組（`alternative_group`）的子件每張訂單只展開一個：按 `alternative_priority`
+    /// 由小到大選擇第一個預計可用量足夠者（未提供選擇器或都不足時使用主料），
+    /// 替換記錄於選擇器
     fn explode_bom(
         &self,
         parent_id: &str,
         planned_orders: &[mrp_core::PlannedOrder],
+        mut alternatives: Option<&mut AlternativeSelector<'_>>,
     ) -> mrp_core::Result<HashMap<String, Vec<Demand>>> {
         use mrp_core::DemandType;
 
@@ -311,22 +2529,30 @@ impl MrpCalculator {
             return Ok(child_demands);
         }
 
-        // 在 BOM 圖中查找父件節點
-        let parent_component_id = bom_core::ComponentId::new(parent_id);
-        let parent_node = match self.bom_graph.arena().find_node(&parent_component_id) {
-            Some(node) => node,
-            None => {
-                // 如果找不到節點，說明該物料沒有 BOM 或不在圖中
-                tracing::debug!("物料 {} 不在 BOM 圖中，無子件", parent_id);
-                return Ok(child_demands);
-            }
-        };
+        let parent_config = self.configs.get(parent_id);
 
-        // 獲取所有子件
-        let children: Vec<_> = self
-            .bom_graph
-            .arena()
-            .children(parent_node)
+        // 獲取所有子件（物料沒有 BOM 或不在圖中時為空）；
+        // 散裝發料的子件或 BOM 行由現場庫存補貨，異常 BOM 行（已於展開前警告）不展開
+        let bom_items = self.bom_children(parent_id);
+        let children: Vec<_> = bom_items
+            .iter()
+            .map(|item| (item.child_id.as_str(), item))
+            .filter(|&(child_id, bom_item)| {
+                let bulk = parent_config.is_some_and(|c| c.is_bulk_component(child_id))
+                    || self.configs.get(child_id).is_some_and(|c| c.is_bulk);
+                if bulk {
+                    tracing::debug!("BOM 行 {} → {} 為散裝發料，不展開", parent_id, child_id);
+                }
+                !bulk
+                    && !BomAnomalyDetector::check(
+                        parent_id,
+                        child_id,
+                        bom_item,
+                        self.policy.bom_quantity_threshold,
+                    )
+                    .iter()
+                    .any(|a| a.skips_explosion())
+            })
             .collect();
 
         if children.is_empty() {
@@ -334,28 +2560,38 @@ impl MrpCalculator {
             return Ok(child_demands);
         }
 
+        // 按替代群組分行：無群組的子件各自一行，同群組的子件按優先級排成一行
+        let mut lines: Vec<Vec<(&str, &bom_core::BomItem)>> = Vec::new();
+        for (child_id, bom_item) in children {
+            let group = bom_item.alternative_group.as_deref();
+            match lines
+                .iter_mut()
+                .find(|line| group.is_some() && line[0].1.alternative_group.as_deref() == group)
+            {
+                Some(line) => line.push((child_id, bom_item)),
+                None => lines.push(vec![(child_id, bom_item)]),
+            }
+        }
+        for line in &mut lines {
+            line.sort_by_key(|(_, item)| item.alternative_priority.unwrap_or(u32::MAX));
+        }
+
         // 對每張計劃訂單，展開子件需求
         for order in planned_orders {
-            for (child_node_idx, edge) in &children {
-                let child_node = self
-                    .bom_graph
-                    .arena()
-                    .node(*child_node_idx)
-                    .ok_or_else(|| {
-                        mrp_core::MrpError::BomExplosionError(
-                            "無法獲取子件節點".to_string(),
-                        )
-                    })?;
-
-                let child_component_id = &child_node.component_id;
-                let child_id = child_component_id.as_str();
-
-                // 計算子件需求數量 = 父件訂單數量 × 子件用量
-                let child_quantity = order.quantity * edge.bom_item.quantity;
-
-                // 計算子件需求日期（考慮父件的生產開始日期）
-                // 子件需求日期 = 父件訂單日期（生產開始日）
-                let child_required_date = order.order_date;
+            for line in &lines {
+                let candidates: Vec<(&str, rust_decimal::Decimal, chrono::NaiveDate)> = line
+                    .iter()
+                    .map(|&(child_id, bom_item)| {
+                        let (quantity, date) =
+                            self.child_requirement(parent_config, child_id, bom_item, order);
+                        (child_id, quantity, date)
+                    })
+                    .collect();
+                let chosen = match alternatives.as_deref_mut() {
+                    Some(selector) if candidates.len() > 1 => selector.select(&candidates),
+                    _ => 0,
+                };
+                let (child_id, child_quantity, child_required_date) = candidates[chosen];
 
                 // 創建相依需求
                 let dependent_demand = Demand::new(
@@ -367,6 +2603,25 @@ impl MrpCalculator {
                 .with_source_ref(format!("{}:{}", parent_id, order.id))
                 .with_priority(order.pegging.first().map(|_p| 5).unwrap_or(5));
 
+                if chosen > 0 {
+                    if let Some(selector) = alternatives.as_deref_mut() {
+                        selector.record(ComponentSubstitution {
+                            parent_id: parent_id.to_string(),
+                            parent_order_id: order.id,
+                            alternative_group: line[chosen]
+                                .1
+                                .alternative_group
+                                .clone()
+                                .unwrap_or_default(),
+                            primary_id: candidates[0].0.to_string(),
+                            substitute_id: child_id.to_string(),
+                            demand_id: dependent_demand.id,
+                            quantity: child_quantity,
+                            required_date: child_required_date,
+                        });
+                    }
+                }
+
                 child_demands
                     .entry(child_id.to_string())
                     .or_insert_with(Vec::new)
@@ -385,17 +2640,82 @@ impl MrpCalculator {
         Ok(child_demands)
     }
 
-    /// 為單一物料創建動態時間桶
-    ///
-    /// 合併基礎時間桶和該物料的實際需求/供應日期，確保所有相依需求日期都被包含
-    fn create_component_time_buckets(
+    /// 計劃 BOM 展開：產品族需求按百分比於同一日期轉為各變體的相依需求（不取整）
+    fn explode_planning_bom(
         &self,
-        base_time_buckets: &[chrono::NaiveDate],
-        component_demands: &[Demand],
-        component_supplies: &[Supply],
-    ) -> Vec<chrono::NaiveDate> {
-        use std::collections::HashSet;
-
+        config: &MrpConfig,
+        family_demands: &[Demand],
+    ) -> HashMap<String, Vec<Demand>> {
+        let mut child_demands: HashMap<String, Vec<Demand>> = HashMap::new();
+        for demand in family_demands {
+            for (variant_id, percentage) in &config.planning_percentages {
+                let quantity = demand.quantity * percentage / rust_decimal::Decimal::ONE_HUNDRED;
+                if quantity <= rust_decimal::Decimal::ZERO {
+                    continue;
+                }
+                child_demands.entry(variant_id.clone()).or_default().push(
+                    Demand::new(
+                        variant_id.clone(),
+                        quantity,
+                        demand.required_date,
+                        mrp_core::DemandType::Dependent,
+                    )
+                    .with_source_ref(format!("{}:{}", config.component_id, demand.id))
+                    .with_priority(demand.priority),
+                );
+            }
+        }
+        child_demands
+    }
+
+    /// 單一 BOM 行對父件訂單的子件需求數量與日期
+    ///
+    /// 數量 = 父件設定的子件用量規則（`component_quantity`：每張訂單固定量、
+    /// 依父件數量比例或階梯用量）計算後，依子件取整方式取整；
+    /// 日期 = 父件訂單開工日 + BOM 行／工序偏移（正偏移不晚於完工日，負偏移表示開工前到位）
+    fn child_requirement(
+        &self,
+        parent_config: Option<&MrpConfig>,
+        child_id: &str,
+        bom_item: &bom_core::BomItem,
+        order: &mrp_core::PlannedOrder,
+    ) -> (rust_decimal::Decimal, chrono::NaiveDate) {
+        let quantity = parent_config.map_or(order.quantity * bom_item.quantity, |c| {
+            c.component_quantity(child_id, order.quantity, bom_item.quantity)
+        });
+        let quantity = self
+            .configs
+            .get(child_id)
+            .map_or(quantity, |c| c.round_quantity(quantity));
+
+        let offset = parent_config
+            .map(|c| c.child_offset(child_id, bom_item.sequence, bom_item.operation_sequence))
+            .unwrap_or(0);
+        let date = match offset {
+            0 => order.order_date,
+            days if days > 0 => self
+                .calendar
+                .add_working_days(order.order_date, days as u32)
+                .min(order.required_date),
+            days => self
+                .calendar
+                .subtract_working_days(order.order_date, days.unsigned_abs()),
+        };
+
+        (quantity, date)
+    }
+
+    /// 為單一物料創建動態時間桶
+    ///
+    /// 合併基礎時間桶和該物料的實際需求/供應日期，確保所有相依需求日期都被包含
+    fn create_component_time_buckets(
+        &self,
+        base_time_buckets: &[chrono::NaiveDate],
+        component_demands: &[Demand],
+        component_supplies: &[Supply],
+    ) -> Vec<chrono::NaiveDate> {
+        use std::collections::HashSet;
+
         // 使用 HashSet 收集所有唯一日期
         let mut dates: HashSet<chrono::NaiveDate> = base_time_buckets.iter().copied().collect();
 
@@ -416,6 +2736,92 @@ impl MrpCalculator {
         sorted_dates
     }
 
+    /// 對未消耗完承諾量的合約發出警告
+    fn check_commitments(&self, planned_orders: &[mrp_core::PlannedOrder], result: &mut MrpResult) {
+        for status in CommitmentTracker::evaluate(planned_orders, &self.supply_contracts) {
+            if !status.is_under_consumed() {
+                continue;
+            }
+            let message = format!(
+                "供應商 {} 合約承諾量 {} 僅計劃採購 {}，差額 {}",
+                status.supplier_id,
+                status.minimum_quantity,
+                status.planned_quantity,
+                status.shortfall()
+            );
+            result.add_warning(if status.take_or_pay {
+                MrpWarning::warning(status.component_id, message)
+            } else {
+                MrpWarning::info(status.component_id, message)
+            });
+        }
+    }
+
+    /// 合約承諾量消耗狀態
+    pub fn commitment_status(&self, result: &MrpResult) -> Vec<crate::CommitmentStatus> {
+        CommitmentTracker::evaluate(&result.planned_orders, &self.supply_contracts)
+    }
+
+    /// BOM 計劃圖：根物料的 BOM 子樹標上提前期、累計提前期、計劃量與短缺
+    pub fn bom_diagram(&self, root_id: &str, result: &MrpResult) -> crate::BomDiagram {
+        crate::BomDiagram::build(
+            root_id,
+            |parent_id| self.bom_children(parent_id),
+            &self.configs,
+            result,
+        )
+    }
+
+    /// 安全庫存合理性檢查：以計算器的物料配置比較安全庫存與提前期需求波動
+    pub fn safety_stock_coverage(
+        &self,
+        profiles: &[crate::ItemDemandProfile],
+        service_factor: rust_decimal::Decimal,
+    ) -> Vec<crate::SafetyStockCheck> {
+        crate::SafetyStockChecker::check(profiles, &self.configs, service_factor)
+    }
+
+    /// 計劃訂單金額：數量 × 物料配置的單位成本（未設單位成本的物料不計）
+    pub fn planned_order_value(&self, orders: &[mrp_core::PlannedOrder]) -> rust_decimal::Decimal {
+        orders
+            .iter()
+            .filter_map(|order| {
+                self.config(&order.component_id)
+                    .and_then(|config| config.unit_cost)
+                    .map(|unit_cost| unit_cost * order.quantity)
+            })
+            .sum()
+    }
+
+    /// 採購視圖：將計算結果中的計劃採購訂單按供應商 × 週彙總
+    pub fn purchase_schedule(&self, result: &MrpResult) -> Vec<crate::SupplierWeekSchedule> {
+        crate::purchasing::PurchaseScheduleCalculator::aggregate(
+            &result.planned_orders,
+            &self.configs,
+        )
+    }
+
+    /// 供應商滾動交貨排程（類 DELFOR，含確定／權衡／預測區）
+    pub fn delivery_schedules(
+        &self,
+        result: &MrpResult,
+        issue_date: chrono::NaiveDate,
+        horizons: &HashMap<String, crate::ScheduleHorizon>,
+        default_horizon: crate::ScheduleHorizon,
+    ) -> Vec<crate::SupplierDeliverySchedule> {
+        crate::schedule_agreement::DeliveryScheduleCalculator::generate(
+            &result.planned_orders,
+            issue_date,
+            horizons,
+            default_horizon,
+        )
+    }
+
+    /// 獲取物料的 MRP 配置（含情境覆寫）
+    pub fn config(&self, component_id: &str) -> Option<&MrpConfig> {
+        self.configs.get(component_id)
+    }
+
     /// 獲取工作日曆引用
     pub fn calendar(&self) -> &WorkCalendar {
         &self.calendar
@@ -425,12 +2831,18 @@ impl MrpCalculator {
     pub fn bom_graph(&self) -> &BomGraph {
         &self.bom_graph
     }
+
+    /// 獲取共享的 BOM 圖（供其他計算器共用）
+    pub fn shared_bom_graph(&self) -> Arc<BomGraph> {
+        Arc::clone(&self.bom_graph)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::NaiveDate;
+    use crate::fixtures::{bom_graph, bom_item, calculator, config, date, demand};
+    use chrono::{Datelike, NaiveDate};
     use mrp_core::{Demand, DemandType, Supply, SupplyType};
 
     #[test]
@@ -438,6 +2850,953 @@ mod tests {
         // TODO: 實現測試
     }
 
+    #[test]
+    fn test_shared_component_planned_after_all_parents() {
+        // ASSY → SUB → BOLT，ASSY 也直接使用 BOLT
+        let bom_graph = bom_graph(
+            [("ASSY", "BOLT"), ("ASSY", "SUB"), ("SUB", "BOLT")]
+                .map(|(parent, child)| bom_item(parent, child, 1)),
+        );
+        let configs =
+            ["ASSY", "SUB", "BOLT"].map(|id| config(id, 1, mrp_core::ProcurementType::Make));
+        let calculator = calculator(bom_graph, configs);
+
+        let order = calculator
+            .topological_sort(&HashMap::from([("ASSY".to_string(), Vec::new())]))
+            .unwrap();
+        assert_eq!(
+            order,
+            vec![
+                ("ASSY".to_string(), 0),
+                ("SUB".to_string(), 1),
+                ("BOLT".to_string(), 2)
+            ]
+        );
+
+        let demand = demand("ASSY", 10, 20);
+        let result = calculator.calculate(vec![demand], vec![], vec![]).unwrap();
+
+        // BOLT 只在 ASSY 與 SUB 都展開後計算一次
+        let processed: Vec<&str> = result
+            .metrics
+            .items
+            .iter()
+            .map(|t| t.component_id.as_str())
+            .collect();
+        assert_eq!(processed, vec!["ASSY", "SUB", "BOLT"]);
+    }
+
+    #[test]
+    fn test_bom_cycle_reports_path() {
+        // TOP → A → B → C → A
+        let mut overrides: HashMap<String, Vec<bom_core::BomItem>> = HashMap::new();
+        for (parent, child) in [("TOP", "A"), ("A", "B"), ("B", "C"), ("C", "A")] {
+            overrides
+                .entry(parent.to_string())
+                .or_default()
+                .push(bom_item(parent, child, 1));
+        }
+        let calculator = MrpCalculator::from_parts(
+            Arc::new(BomGraph::new()),
+            ConfigSet::default(),
+            overrides,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+
+        let demand = demand("TOP", 10, 20);
+        match calculator.calculate(vec![demand], vec![], vec![]) {
+            Err(mrp_core::MrpError::TopologicalSortError(message)) => {
+                assert_eq!(message, "BOM 含循環結構：A → B → C → A");
+            }
+            other => panic!("預期循環錯誤，實際為 {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_explode_with_operation_offset() {
+        let bom_graph = bom_graph([("FRAME", 10), ("PAINT", 30), ("LABEL", 90)].map(
+            |(child, operation)| bom_core::BomItem {
+                operation_sequence: Some(operation),
+                ..bom_item("ASSY", child, 1)
+            },
+        ));
+
+        let configs = [config("ASSY", 10, mrp_core::ProcurementType::Make)
+            .with_operation_offset(30, 3)
+            .with_operation_offset(90, 20)];
+        let calculator = calculator(bom_graph, configs);
+
+        let order = mrp_core::PlannedOrder::new(
+            "ASSY".to_string(),
+            rust_decimal::Decimal::from(5),
+            date(11),
+            date(1),
+            mrp_core::PlannedOrderType::Production,
+        );
+
+        let child_demands = calculator
+            .explode_bom("ASSY", std::slice::from_ref(&order), None)
+            .unwrap();
+
+        // 工序 10 未設偏移：開工日即需要
+        assert_eq!(child_demands["FRAME"][0].required_date, order.order_date);
+        // 工序 30：開工後 3 天
+        assert_eq!(child_demands["PAINT"][0].required_date, date(4));
+        // 偏移超過提前期：不晚於完工日
+        assert_eq!(child_demands["LABEL"][0].required_date, order.required_date);
+    }
+
+    #[test]
+    fn test_explode_with_negative_component_offset() {
+        // CASTING 在 ASSY 下用於兩行：第 10 行開工前到位，第 20 行依工序偏移
+        let bom_graph = bom_graph([10, 20].map(|sequence| bom_core::BomItem {
+            sequence,
+            operation_sequence: Some(30),
+            ..bom_item("ASSY", "CASTING", 1)
+        }));
+
+        let configs = [config("ASSY", 10, mrp_core::ProcurementType::Make)
+            .with_operation_offset(30, 3)
+            .with_bom_line_offset("CASTING".to_string(), 10, -2)];
+        let calculator = calculator(bom_graph, configs);
+
+        let order = mrp_core::PlannedOrder::new(
+            "ASSY".to_string(),
+            rust_decimal::Decimal::from(5),
+            date(11),
+            date(1),
+            mrp_core::PlannedOrderType::Production,
+        );
+
+        let child_demands = calculator
+            .explode_bom("ASSY", std::slice::from_ref(&order), None)
+            .unwrap();
+
+        // BOM 行偏移優先於工序偏移：第 10 行開工前 2 天到位；第 20 行未設行偏移，開工後 3 天
+        let mut dates: Vec<NaiveDate> = child_demands["CASTING"]
+            .iter()
+            .map(|d| d.required_date)
+            .collect();
+        dates.sort();
+        assert_eq!(
+            dates,
+            vec![NaiveDate::from_ymd_opt(2025, 10, 30).unwrap(), date(4)]
+        );
+    }
+
+    #[test]
+    fn test_shared_bom_across_threads() {
+        let shared = Arc::new(bom_graph([bom_item("ASSY", "FRAME", 1)]));
+        let configs: HashMap<String, MrpConfig> = ["ASSY", "FRAME"]
+            .into_iter()
+            .map(|id| {
+                (
+                    id.to_string(),
+                    config(id, 2, mrp_core::ProcurementType::Make),
+                )
+            })
+            .collect();
+
+        let exploded: Vec<bool> = std::thread::scope(|scope| {
+            let handles: Vec<_> = [10, 20]
+                .into_iter()
+                .map(|qty| {
+                    let calculator = MrpCalculator::with_shared_bom(
+                        Arc::clone(&shared),
+                        configs.clone(),
+                        WorkCalendar::new_24_7("24/7".to_string()),
+                    );
+                    assert!(Arc::ptr_eq(&calculator.shared_bom_graph(), &shared));
+                    scope.spawn(move || {
+                        let demand = demand("ASSY", qty, 20);
+                        calculator
+                            .calculate(vec![demand], vec![], vec![])
+                            .unwrap()
+                            .planned_orders
+                            .iter()
+                            .any(|o| o.component_id == "FRAME")
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        // 每個模擬都透過共享的 BOM 展開到子件
+        assert_eq!(exploded, vec![true, true]);
+    }
+
+    #[test]
+    fn test_preview_top_level_and_budget() {
+        let bom_graph = bom_graph([bom_item("ASSY", "FRAME", 1)]);
+        let configs = ["ASSY", "FRAME"].map(|id| config(id, 2, mrp_core::ProcurementType::Make));
+        let calculator = calculator(bom_graph, configs);
+        let preview = calculator
+            .calculate_preview(
+                vec![demand("ASSY", 10, 20)],
+                vec![],
+                vec![],
+                crate::PreviewOptions::new(std::time::Duration::from_secs(60))
+                    .with_top_level_only(),
+            )
+            .unwrap();
+        assert!(!preview.truncated);
+        assert!(!preview.result.planned_orders.is_empty());
+        assert!(preview
+            .result
+            .planned_orders
+            .iter()
+            .all(|o| o.component_id == "ASSY"));
+
+        // 預算為零：不計算任何物料
+        let preview = calculator
+            .calculate_preview(
+                vec![demand("ASSY", 10, 20)],
+                vec![],
+                vec![],
+                crate::PreviewOptions::new(std::time::Duration::ZERO),
+            )
+            .unwrap();
+        assert!(preview.truncated);
+        assert_eq!(preview.pending_components[0], "ASSY");
+        assert!(preview.result.planned_orders.is_empty());
+    }
+
+    #[test]
+    fn test_time_budget_partial_result() {
+        let configs = ["LOW", "HIGH"].map(|id| config(id, 2, mrp_core::ProcurementType::Buy));
+        let demands = vec![
+            demand("LOW", 10, 20).with_priority(2),
+            demand("HIGH", 10, 20).with_priority(9),
+        ];
+
+        let calculator = calculator(BomGraph::new(), configs);
+        let complete = calculator
+            .calculate(demands.clone(), vec![], vec![])
+            .unwrap();
+        assert!(!complete.is_partial());
+
+        // 預算為零：不計算任何物料，高優先物料排在未計算清單最前
+        let partial = calculator
+            .with_time_budget(std::time::Duration::ZERO)
+            .calculate(demands, vec![], vec![])
+            .unwrap();
+        assert!(partial.is_partial());
+        assert_eq!(
+            partial.unprocessed_components,
+            vec!["HIGH".to_string(), "LOW".to_string()]
+        );
+        assert!(partial.planned_orders.is_empty());
+        assert_eq!(partial.warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_explosion_rounds_discrete_children() {
+        let mut item = bom_item("ASSY", "SCREW", 1);
+        item.quantity = rust_decimal::Decimal::ONE / rust_decimal::Decimal::from(3);
+        let bom_graph = bom_graph([item]);
+
+        let configs = [
+            config("ASSY", 2, mrp_core::ProcurementType::Make),
+            config("SCREW", 2, mrp_core::ProcurementType::Buy)
+                .with_rounding_policy(mrp_core::RoundingPolicy::RoundUpToUnit),
+        ];
+        let calculator = calculator(bom_graph, configs);
+        let demand = demand("ASSY", 100, 20);
+
+        let result = calculator.calculate(vec![demand], vec![], vec![]).unwrap();
+
+        // 100 × 1/3 = 33.333… → 34
+        assert!(result
+            .planned_orders
+            .iter()
+            .filter(|o| o.component_id == "SCREW")
+            .all(|o| o.quantity == rust_decimal::Decimal::from(34)));
+        assert!(result
+            .planned_orders
+            .iter()
+            .any(|o| o.component_id == "SCREW"));
+    }
+
+    #[test]
+    fn test_manual_item_projected_not_ordered() {
+        let bom_graph = bom_graph([bom_item("ASSY", "FRAME", 1)]);
+        let configs = [
+            config("ASSY", 2, mrp_core::ProcurementType::Make),
+            config("FRAME", 2, mrp_core::ProcurementType::Buy)
+                .with_planning_method(mrp_core::PlanningMethod::Manual),
+        ];
+        let calculator = calculator(bom_graph, configs);
+        let demand = demand("ASSY", 10, 20);
+
+        let result = calculator.calculate(vec![demand], vec![], vec![]).unwrap();
+
+        // 人工計劃的子件不產生計劃訂單，但相依需求仍反映在庫存預測中
+        assert!(result
+            .planned_orders
+            .iter()
+            .all(|o| o.component_id == "ASSY"));
+        let projection = &result.projections["FRAME"];
+        assert!(projection
+            .iter()
+            .any(|r| r.net_requirement > rust_decimal::Decimal::ZERO));
+        assert!(result.warnings.iter().any(|w| w.component_id == "FRAME"));
+        assert!(!result.projections.contains_key("ASSY"));
+    }
+
+    #[test]
+    fn test_forward_fallback_reports_late_demand() {
+        let configs = [config("ASSY", 5, mrp_core::ProcurementType::Make)
+            .with_scheduling_mode(mrp_core::SchedulingMode::BackwardThenForward)];
+        let calculator = calculator(BomGraph::new(), configs);
+        let demand = demand("ASSY", 10, 3).with_source_document(mrp_core::SourceDocument::new(
+            "SO".to_string(),
+            "1001".to_string(),
+        ));
+
+        let result = calculator.calculate(vec![demand], vec![], vec![]).unwrap();
+
+        // 倒排開工 10/29 早於計劃起始日 11/3 → 改由 11/3 順排，11/8 完工
+        let order = &result.planned_orders[0];
+        assert_eq!(order.order_date, date(3));
+        assert_eq!(order.days_late(), 5);
+        assert!(result.warnings.iter().any(|w| w
+            .message
+            .starts_with("需求 SO-1001（需要日期 2025-11-03）將延遲 5 天")));
+    }
+
+    #[test]
+    fn test_late_component_order_reports_end_demand() {
+        let bom_graph = bom_graph([bom_item("ASSY", "FRAME", 1)]);
+        let configs = [
+            config("ASSY", 2, mrp_core::ProcurementType::Make),
+            config("FRAME", 10, mrp_core::ProcurementType::Buy)
+                .with_scheduling_mode(mrp_core::SchedulingMode::BackwardThenForward),
+        ];
+        let calculator = calculator(bom_graph, configs)
+            .with_policy(PlanningPolicy::new().with_plan_start_date(date(3)));
+        let demand = demand("ASSY", 10, 10).with_source_document(mrp_core::SourceDocument::new(
+            "SO".to_string(),
+            "2001".to_string(),
+        ));
+
+        let result = calculator.calculate(vec![demand], vec![], vec![]).unwrap();
+
+        // ASSY 11/8 開工準時；FRAME 倒排 10/29 早於起始日，改由 11/3 順排 11/13 到貨（延遲 5 天）
+        assert!(result
+            .planned_orders
+            .iter()
+            .all(|o| o.component_id == "FRAME" || !o.is_late()));
+        assert!(result.warnings.iter().any(|w| w
+            .message
+            .starts_with("需求 SO-2001（需要日期 2025-11-10）將延遲 5 天")));
+    }
+
+    #[test]
+    fn test_level_trace_dump() {
+        let bom_graph = bom_graph([bom_item("BIKE", "FRAME", 2), bom_item("FRAME", "TUBE", 1)]);
+        let configs = [
+            config("BIKE", 1, mrp_core::ProcurementType::Make),
+            config("FRAME", 1, mrp_core::ProcurementType::Make),
+            config("TUBE", 1, mrp_core::ProcurementType::Buy),
+        ];
+        let path =
+            std::env::temp_dir().join(format!("mrp-level-trace-{}.jsonl", uuid::Uuid::new_v4()));
+        let calculator = calculator(bom_graph, configs).with_level_trace(&path);
+        let demand = demand("BIKE", 10, 20);
+
+        calculator.calculate(vec![demand], vec![], vec![]).unwrap();
+        let levels = crate::LevelTrace::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // 每個低階碼一行：第 0 層算完時 FRAME 已有 20 的相依需求，
+        // TUBE 的相依需求到第 1 層才由 FRAME 的計劃訂單產生
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[0].components, vec!["BIKE".to_string()]);
+        assert_eq!(
+            levels[0].dependent_quantity("FRAME"),
+            rust_decimal::Decimal::from(20)
+        );
+        assert_eq!(
+            levels[0].dependent_quantity("TUBE"),
+            rust_decimal::Decimal::ZERO
+        );
+        assert_eq!(
+            levels[1].dependent_quantity("TUBE"),
+            levels[1].planned_quantity("FRAME")
+        );
+        assert_eq!(levels[2].components, vec!["TUBE".to_string()]);
+    }
+
+    #[test]
+    fn test_prior_planned_supplies_replaced() {
+        let configs = [config("BOLT", 2, mrp_core::ProcurementType::Buy)];
+        let calculator = calculator(BomGraph::new(), configs);
+        let demand = demand("BOLT", 40, 20);
+
+        let first = calculator
+            .calculate(vec![demand.clone()], vec![], vec![])
+            .unwrap();
+        // 昨晚的計劃訂單經 ERP 回傳為未確認的計劃供應
+        let fed_back: Vec<Supply> = first.planned_orders.iter().map(|o| o.to_supply()).collect();
+        assert_eq!(
+            fed_back[0].engine_order_id(),
+            Some(first.planned_orders[0].id)
+        );
+
+        let second = calculator
+            .calculate(vec![demand.clone()], fed_back.clone(), vec![])
+            .unwrap();
+        assert_eq!(second.planned_orders.len(), 1);
+        assert_eq!(
+            second.planned_orders[0].quantity,
+            rust_decimal::Decimal::from(40)
+        );
+        assert!(second
+            .warnings
+            .iter()
+            .any(|w| w.message.starts_with("忽略 1 筆")));
+
+        // 保留時視為一般供應：需求已被覆蓋，不再產生訂單
+        let keep = PlanningPolicy::new()
+            .with_planned_supply_policy(crate::policy::PlannedSupplyPolicy::Keep);
+        let kept = calculator
+            .calculate_with_policy(vec![demand], fed_back, vec![], &keep)
+            .unwrap();
+        assert!(kept.planned_orders.is_empty());
+    }
+
+    #[test]
+    fn test_firm_planned_orders_kept_and_exploded() {
+        let bom_graph = bom_graph([bom_item("PUMP", "SEAL", 1)]);
+        let configs = [
+            config("PUMP", 3, mrp_core::ProcurementType::Make),
+            config("SEAL", 2, mrp_core::ProcurementType::Buy),
+        ];
+        let calculator = calculator(bom_graph, configs);
+        let demand = demand("PUMP", 12, 20);
+
+        let first = calculator
+            .calculate(vec![demand.clone()], vec![], vec![])
+            .unwrap();
+        let pump = first
+            .planned_orders
+            .iter()
+            .find(|o| o.component_id == "PUMP")
+            .unwrap();
+        // 計劃員確認 PUMP 的計劃訂單後回傳：不再產生 PUMP 訂單，SEAL 仍依確認訂單展開
+        let firmed = vec![pump.to_firm_supply()];
+
+        let second = calculator
+            .calculate(vec![demand], firmed.clone(), vec![])
+            .unwrap();
+        let seals = |result: &MrpResult| -> Vec<(rust_decimal::Decimal, NaiveDate)> {
+            result
+                .planned_orders
+                .iter()
+                .filter(|o| o.component_id == "SEAL")
+                .map(|o| (o.quantity, o.required_date))
+                .collect()
+        };
+        assert!(second
+            .planned_orders
+            .iter()
+            .all(|o| o.component_id == "SEAL"));
+        assert_eq!(seals(&second), seals(&first));
+        assert!(seals(&second)
+            .iter()
+            .any(|(_, date)| *date == pump.order_date));
+
+        // 需求取消後確認訂單仍須備料
+        let cancelled = calculator.calculate(vec![], firmed, vec![]).unwrap();
+        assert!(seals(&cancelled)
+            .iter()
+            .any(|(_, date)| *date == pump.order_date));
+        assert!(cancelled
+            .warnings
+            .iter()
+            .any(|w| w.component_id == "PUMP" && w.message.contains("確認計劃訂單視為固定供應")));
+    }
+
+    #[test]
+    fn test_fixed_usage_per_order_explosion() {
+        let bom_graph = bom_graph(["LABEL", "BOTTLE"].map(|child| bom_item("JUICE", child, 1)));
+        let configs = [
+            config("JUICE", 1, mrp_core::ProcurementType::Make).with_component_usage(
+                "LABEL".to_string(),
+                mrp_core::ComponentUsage::fixed(rust_decimal::Decimal::ONE),
+            ),
+            config("LABEL", 2, mrp_core::ProcurementType::Buy),
+            config("BOTTLE", 2, mrp_core::ProcurementType::Buy),
+        ];
+        let calculator = calculator(bom_graph, configs);
+        let demand = demand("JUICE", 500, 20);
+
+        let result = calculator.calculate(vec![demand], vec![], vec![]).unwrap();
+        // 標籤每張訂單一張，瓶子仍按數量比例展開
+        let quantities = |id: &str| -> Vec<rust_decimal::Decimal> {
+            result
+                .planned_orders
+                .iter()
+                .filter(|o| o.component_id == id)
+                .map(|o| o.quantity)
+                .collect()
+        };
+        assert!(!quantities("LABEL").is_empty());
+        assert!(quantities("LABEL")
+            .iter()
+            .all(|q| *q == rust_decimal::Decimal::ONE));
+        assert!(quantities("BOTTLE")
+            .iter()
+            .all(|q| *q == rust_decimal::Decimal::from(500)));
+    }
+
+    #[test]
+    fn test_bulk_components_not_exploded() {
+        let bom_graph =
+            bom_graph(["FRAME", "GLUE", "WASHER"].map(|child| bom_item("ASSY", child, 1)));
+        let configs = [
+            config("ASSY", 2, mrp_core::ProcurementType::Make)
+                .with_bulk_component("GLUE".to_string()),
+            config("FRAME", 2, mrp_core::ProcurementType::Buy),
+            config("GLUE", 2, mrp_core::ProcurementType::Buy),
+            config("WASHER", 2, mrp_core::ProcurementType::Buy).with_bulk(true),
+        ];
+        let calculator = calculator(bom_graph, configs);
+        let demand = demand("ASSY", 10, 20);
+
+        let result = calculator.calculate(vec![demand], vec![], vec![]).unwrap();
+
+        let ordered: std::collections::HashSet<&str> = result
+            .planned_orders
+            .iter()
+            .map(|o| o.component_id.as_str())
+            .collect();
+        assert!(ordered.contains("FRAME"));
+        assert!(!ordered.contains("GLUE"));
+        assert!(!ordered.contains("WASHER"));
+    }
+
+    #[test]
+    fn test_planning_bom_percentage_explosion() {
+        let configs = [
+            config("BIKE-FAMILY", 0, mrp_core::ProcurementType::Make)
+                .with_planning_percentage("BIKE-RED".to_string(), rust_decimal::Decimal::from(60))
+                .with_planning_percentage("BIKE-BLUE".to_string(), rust_decimal::Decimal::from(40)),
+            config("BIKE-RED", 2, mrp_core::ProcurementType::Make),
+            config("BIKE-BLUE", 2, mrp_core::ProcurementType::Make),
+        ];
+        let calculator = calculator(BomGraph::new(), configs);
+        let forecast = Demand::new(
+            "BIKE-FAMILY".to_string(),
+            rust_decimal::Decimal::from(25),
+            date(20),
+            DemandType::Forecast,
+        );
+
+        let result = calculator
+            .calculate(vec![forecast], vec![], vec![])
+            .unwrap();
+
+        let quantity_of = |id: &str| -> rust_decimal::Decimal {
+            result
+                .planned_orders
+                .iter()
+                .filter(|o| o.component_id == id)
+                .map(|o| o.quantity)
+                .sum()
+        };
+        assert_eq!(quantity_of("BIKE-FAMILY"), rust_decimal::Decimal::ZERO);
+        assert_eq!(quantity_of("BIKE-RED"), rust_decimal::Decimal::from(15));
+        assert_eq!(quantity_of("BIKE-BLUE"), rust_decimal::Decimal::from(10));
+    }
+
+    #[test]
+    fn test_alternative_substitution() {
+        let alternative = |child: &str, priority| {
+            let mut item = bom_item("ASSY", child, 1);
+            item.alternative_group = Some("CHIP".to_string());
+            item.alternative_priority = Some(priority);
+            item
+        };
+        let bom_graph = bom_graph([alternative("CHIP-B", 2), alternative("CHIP-A", 1)]);
+
+        let configs = [
+            config("ASSY", 2, mrp_core::ProcurementType::Make),
+            config("CHIP-A", 5, mrp_core::ProcurementType::Buy),
+            config("CHIP-B", 5, mrp_core::ProcurementType::Buy),
+        ];
+        let calculator = calculator(bom_graph, configs);
+        // 主料無庫存、替代料有庫存 → 改用替代料，主料不產生需求
+        let stock = Inventory::new(
+            "CHIP-B".to_string(),
+            rust_decimal::Decimal::from(50),
+            rust_decimal::Decimal::ZERO,
+        );
+        let result = calculator
+            .calculate(vec![demand("ASSY", 10, 20)], vec![], vec![stock])
+            .unwrap();
+        assert_eq!(result.substitutions.len(), 1);
+        assert_eq!(result.substitutions[0].primary_id, "CHIP-A");
+        assert_eq!(result.substitutions[0].substitute_id, "CHIP-B");
+        assert!(result
+            .planned_orders
+            .iter()
+            .all(|o| o.component_id == "ASSY"));
+        assert!(result.warnings.iter().any(|w| w.component_id == "CHIP-A"));
+
+        // 都沒有庫存 → 只對主料展開
+        let result = calculator
+            .calculate(vec![demand("ASSY", 10, 20)], vec![], vec![])
+            .unwrap();
+        assert!(result.substitutions.is_empty());
+        assert!(result
+            .planned_orders
+            .iter()
+            .any(|o| o.component_id == "CHIP-A"));
+        assert!(result
+            .planned_orders
+            .iter()
+            .all(|o| o.component_id != "CHIP-B"));
+
+        // 替代料的採購單趕得上替代需求；替代料早先另有獨立需求的計劃訂單，
+        // 替代需求不應追溯到那張訂單
+        let purchase = Supply::new(
+            "CHIP-B".to_string(),
+            rust_decimal::Decimal::from(20),
+            date(12),
+            SupplyType::PurchaseOrder,
+        );
+        let spare = demand("CHIP-B", 10, 5);
+        let result = calculator
+            .calculate(vec![demand("ASSY", 10, 20), spare], vec![purchase], vec![])
+            .unwrap();
+        assert_eq!(result.substitutions.len(), 1);
+        assert!(result
+            .planned_orders
+            .iter()
+            .any(|o| o.component_id == "CHIP-B"));
+        assert!(result
+            .pegging
+            .values()
+            .flatten()
+            .all(|record| record.substitute_for.is_none()));
+    }
+
+    #[test]
+    fn test_shortage_tolerance_skips_micro_shortage() {
+        let configs = [
+            config("RESIN", 2, mrp_core::ProcurementType::Buy).with_shortage_tolerance(
+                mrp_core::ShortageTolerance::Percent(rust_decimal::Decimal::ONE),
+            ),
+        ];
+        let calculator = calculator(BomGraph::new(), configs);
+        let demand = |quantity: rust_decimal::Decimal, day: u32| {
+            Demand::new(
+                "RESIN".to_string(),
+                quantity,
+                date(day),
+                DemandType::SalesOrder,
+            )
+        };
+        let stock = Inventory::new(
+            "RESIN".to_string(),
+            rust_decimal::Decimal::from(10),
+            rust_decimal::Decimal::ZERO,
+        );
+
+        // 11/10 短缺 0.003 kg（需求的 0.03%）在容差內；11/20 短缺 10 kg 照常下單
+        let result = calculator
+            .calculate(
+                vec![
+                    demand(rust_decimal::Decimal::new(10003, 3), 10),
+                    demand(rust_decimal::Decimal::from(10), 20),
+                ],
+                vec![],
+                vec![stock],
+            )
+            .unwrap();
+
+        assert_eq!(result.planned_orders.len(), 1);
+        assert_eq!(result.planned_orders[0].required_date, date(20));
+        assert!(result.warnings.iter().any(|w| {
+            w.severity == crate::WarningSeverity::Info && w.message.contains("短缺容差")
+        }));
+    }
+
+    #[test]
+    fn test_shortage_tolerance_accumulates_waived_shortages() {
+        let configs = [
+            config("RESIN", 2, mrp_core::ProcurementType::Buy).with_shortage_tolerance(
+                mrp_core::ShortageTolerance::Absolute(rust_decimal::Decimal::new(5, 3)),
+            ),
+        ];
+        let calculator = calculator(BomGraph::new(), configs);
+        let demand = |quantity: rust_decimal::Decimal, day: u32| {
+            Demand::new(
+                "RESIN".to_string(),
+                quantity,
+                date(day),
+                DemandType::SalesOrder,
+            )
+        };
+        let stock = Inventory::new(
+            "RESIN".to_string(),
+            rust_decimal::Decimal::from(10),
+            rust_decimal::Decimal::ZERO,
+        );
+        let receipt = Supply::new(
+            "RESIN".to_string(),
+            rust_decimal::Decimal::new(3, 3),
+            date(12),
+            SupplyType::PurchaseOrder,
+        );
+
+        // 11/10 短缺 0.004、11/14 再短缺 0.004，各自在 0.005 的容差內；
+        // 合計 0.008 超過容差，11/14 下單
+        let result = calculator
+            .calculate(
+                vec![
+                    demand(rust_decimal::Decimal::new(10004, 3), 10),
+                    demand(rust_decimal::Decimal::new(4, 3), 14),
+                ],
+                vec![receipt],
+                vec![stock],
+            )
+            .unwrap();
+
+        assert_eq!(result.planned_orders.len(), 1);
+        assert_eq!(result.planned_orders[0].required_date, date(14));
+    }
+
+    #[test]
+    fn test_demand_time_fence_ignores_near_term_forecast() {
+        let configs = [config("PUMP", 2, mrp_core::ProcurementType::Buy).with_demand_time_fence(5)];
+        let calculator = calculator(BomGraph::new(), configs);
+        let demand = |quantity: i64, day: u32, demand_type: DemandType| {
+            Demand::new(
+                "PUMP".to_string(),
+                rust_decimal::Decimal::from(quantity),
+                date(day),
+                demand_type,
+            )
+        };
+
+        // 時界 11/3–11/7：11/5 的預測不計，銷售訂單與 11/12 的預測照常計算
+        let result = calculator
+            .calculate(
+                vec![
+                    demand(10, 3, DemandType::SalesOrder),
+                    demand(30, 5, DemandType::Forecast),
+                    demand(20, 12, DemandType::Forecast),
+                ],
+                vec![],
+                vec![],
+            )
+            .unwrap();
+
+        let mut days: Vec<u32> = result
+            .planned_orders
+            .iter()
+            .map(|o| o.required_date.day())
+            .collect();
+        days.sort_unstable();
+        assert_eq!(days, vec![3, 12]);
+        assert_eq!(result.coverage.len(), 2);
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.message.contains("需求時界內 1 筆預測需求")));
+    }
+
+    #[test]
+    fn test_planning_time_fence_expedites_near_term_requirement() {
+        let configs =
+            [config("PUMP", 2, mrp_core::ProcurementType::Buy).with_planning_time_fence(5)];
+        let calculator = calculator(BomGraph::new(), configs);
+        let demand = |day: u32| demand("PUMP", 10, day);
+
+        // 時界 11/3–11/7：11/4 的需求改發催交警告，計劃訂單順延到 11/8 到貨
+        let result = calculator
+            .calculate(vec![demand(3), demand(4), demand(12)], vec![], vec![])
+            .unwrap();
+
+        let mut days: Vec<u32> = result
+            .planned_orders
+            .iter()
+            .map(|o| o.required_date.day())
+            .collect();
+        days.sort_unstable();
+        assert_eq!(days, vec![8, 12]);
+        assert_eq!(
+            result
+                .warnings
+                .iter()
+                .filter(|w| w.message.starts_with("計劃時界內"))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_planning_time_fence_shorter_than_lead_time() {
+        let configs =
+            [config("PUMP", 8, mrp_core::ProcurementType::Buy).with_planning_time_fence(5)];
+        let plan_start = date(4);
+        let calculator = calculator(BomGraph::new(), configs);
+        let demands = vec![demand("PUMP", 10, 4)];
+
+        // 時界 11/4–11/8：邊界 11/9 倒排 8 天為 11/1，早於起始日，未設逾期政策時壓縮提前期
+        let result = calculator
+            .calculate(demands.clone(), vec![], vec![])
+            .unwrap();
+        assert!(!result.planned_orders.is_empty());
+        assert!(result
+            .planned_orders
+            .iter()
+            .all(|o| o.order_date == plan_start && o.required_date.day() == 9));
+        assert!(result.warnings.iter().any(|w| w
+            .message
+            .contains("倒排開工日 2025-11-01 早於計劃起始日，改自 2025-11-04")));
+
+        // 設定自起始日順排：11/4 開工、11/12 到貨
+        let result = calculator
+            .calculate_with_policy(
+                demands,
+                vec![],
+                vec![],
+                &PlanningPolicy::new().with_past_due(crate::PastDuePolicy::ScheduleAtStart),
+            )
+            .unwrap();
+        assert!(!result.planned_orders.is_empty());
+        assert!(result
+            .planned_orders
+            .iter()
+            .all(|o| o.order_date == plan_start && o.required_date.day() == 12));
+    }
+
+    #[test]
+    fn test_receipt_alignment_rechecks_plan_start() {
+        let configs = [config("PUMP", 3, mrp_core::ProcurementType::Buy)
+            .with_receipt_calendar("DOCK".to_string())];
+        let mut registry = mrp_core::CalendarRegistry::new();
+        // 只在週二收貨
+        registry.register(
+            WorkCalendar::new("DOCK".to_string())
+                .with_working_days([false, true, false, false, false, false, false]),
+        );
+        let plan_start = date(4);
+        let calculator = calculator(BomGraph::new(), configs)
+            .with_receipt_calendars(ReceiptCalendars::new(registry));
+        let demands = vec![demand("PUMP", 10, 8)];
+
+        // 11/8 收貨倒排為 11/5 開工；收貨日提前到 11/4（週二）後開工日 11/1 早於起始日，壓縮提前期
+        let result = calculator
+            .calculate_with_policy(
+                demands,
+                vec![],
+                vec![],
+                &PlanningPolicy::new().with_plan_start_date(plan_start),
+            )
+            .unwrap();
+        assert!(!result.planned_orders.is_empty());
+        assert!(result
+            .planned_orders
+            .iter()
+            .all(|o| o.order_date == plan_start && o.required_date == plan_start));
+        assert!(result.warnings.iter().any(|w| w
+            .message
+            .contains("倒排開工日 2025-11-01 早於計劃起始日，改自 2025-11-04")));
+    }
+
+    #[test]
+    fn test_plan_start_date_moves_past_due_and_gates_order_dates() {
+        let configs = [config("PUMP", 3, mrp_core::ProcurementType::Buy)];
+        let plan_start = date(5);
+        let calculator = calculator(BomGraph::new(), configs)
+            .with_policy(PlanningPolicy::new().with_plan_start_date(plan_start));
+        let demand = |quantity: i64, day: u32| demand("PUMP", quantity, day);
+
+        // 11/3 的需求已逾期；11/6 的需求倒排開工日 11/3 早於起始日，改由 11/5 順排
+        let overdue = Supply::new(
+            "PUMP".to_string(),
+            rust_decimal::Decimal::from(5),
+            date(1),
+            SupplyType::PurchaseOrder,
+        );
+        let result = calculator
+            .calculate(vec![demand(10, 3), demand(20, 6)], vec![overdue], vec![])
+            .unwrap();
+
+        assert!(!result.planned_orders.is_empty());
+        assert!(result
+            .planned_orders
+            .iter()
+            .all(|o| o.order_date >= plan_start));
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.message.starts_with("1 筆需求（合計 10）早於計劃起始日")));
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.message.starts_with("1 筆供應（合計 5）逾期未到")));
+    }
+
+    #[test]
+    fn test_past_due_policy_compresses_or_fails() {
+        let configs = [
+            config("PUMP", 3, mrp_core::ProcurementType::Buy),
+            config("SEAL", 1, mrp_core::ProcurementType::Buy),
+        ];
+        let calculator = calculator(BomGraph::new(), configs);
+        let demand = |id: &str, day: u32| demand(id, 10, day);
+        let demands = vec![demand("PUMP", 6), demand("SEAL", 10)];
+        let plan_start = date(5);
+        let policy = PlanningPolicy::new().with_plan_start_date(plan_start);
+
+        // 倒排開工日 11/3 早於起始日 11/5：壓縮為 1 天，到貨日不變
+        let result = calculator
+            .calculate_with_policy(
+                demands.clone(),
+                vec![],
+                vec![],
+                &policy
+                    .clone()
+                    .with_past_due(crate::PastDuePolicy::CompressLeadTime),
+            )
+            .unwrap();
+        let pump = result
+            .planned_orders
+            .iter()
+            .find(|o| o.component_id == "PUMP")
+            .unwrap();
+        assert_eq!(pump.order_date, plan_start);
+        assert_eq!(pump.required_date.day(), 6);
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.message.contains("提前期壓縮為 1 個工作日")));
+
+        // 失敗只影響 PUMP：記錄錯誤、不產生訂單，SEAL 照常計算
+        let failed = calculator
+            .calculate_with_policy(
+                demands,
+                vec![],
+                vec![],
+                &policy.with_past_due(crate::PastDuePolicy::Fail),
+            )
+            .unwrap();
+        assert!(failed
+            .planned_orders
+            .iter()
+            .all(|o| o.component_id == "SEAL"));
+        assert!(!failed.planned_orders.is_empty());
+        assert!(failed
+            .warnings
+            .iter()
+            .any(|w| w.component_id == "PUMP" && w.severity == crate::WarningSeverity::Error));
+    }
+
     #[test]
     fn test_dynamic_time_buckets() {
         // 測試動態時間桶：確保相依需求日期被包含
@@ -516,4 +3875,211 @@ mod tests {
         sorted.dedup();
         assert_eq!(sorted.len(), unique_count);
     }
+
+    #[test]
+    fn test_transfer_lanes_propose_rebalancing() {
+        let configs = [config("PUMP", 5, mrp_core::ProcurementType::Buy)];
+        let calculator = calculator(BomGraph::new(), configs)
+            .with_transfer_lanes(
+                vec![crate::TransferLane::new(
+                    "KHH".to_string(),
+                    "TPE".to_string(),
+                    2,
+                )],
+                30,
+            )
+            .with_policy(PlanningPolicy::new().with_plan_start_date(date(1)));
+        let demand = |warehouse: &str, quantity: i64, day: u32| {
+            let mut demand = demand("PUMP", quantity, day);
+            demand.plant_id = Some(warehouse.to_string());
+            demand
+        };
+        let inbound = Supply::new(
+            "PUMP".to_string(),
+            rust_decimal::Decimal::from(30),
+            date(15),
+            SupplyType::PurchaseOrder,
+        )
+        .with_warehouse_id("KHH".to_string());
+        let stock = Inventory::new(
+            "PUMP".to_string(),
+            rust_decimal::Decimal::from(50),
+            rust_decimal::Decimal::ZERO,
+        )
+        .with_warehouse_id("KHH".to_string());
+
+        let result = calculator
+            .calculate(
+                vec![demand("TPE", 60, 10), demand("KHH", 40, 20)],
+                vec![inbound],
+                vec![stock],
+            )
+            .unwrap();
+
+        // KHH 結存 50 → 80（11/15 在途）→ 40，可調出 40；TPE 仍短缺 20 由採購補足
+        assert_eq!(result.transfers.len(), 1);
+        assert_eq!(
+            result.transfers[0].quantity,
+            rust_decimal::Decimal::from(40)
+        );
+        assert_eq!(result.transfers[0].to_warehouse, "TPE");
+    }
+
+    #[test]
+    fn test_dependent_demand_tier_follows_source_order() {
+        let bom_graph = bom_graph(["PUMP", "VALVE"].map(|parent| bom_item(parent, "SEAL", 1)));
+        let configs = [
+            config("PUMP", 2, mrp_core::ProcurementType::Make),
+            config("VALVE", 2, mrp_core::ProcurementType::Make),
+            config("SEAL", 1, mrp_core::ProcurementType::Buy),
+        ];
+        let calculator =
+            calculator(bom_graph, configs).with_allocation_strategy(AllocationStrategy::Priority);
+        let demand = |id: &str, day: u32, tier: mrp_core::CustomerTier| {
+            demand(id, 10, day).with_customer_tier(tier)
+        };
+        let stock = Inventory::new(
+            "SEAL".to_string(),
+            rust_decimal::Decimal::from(10),
+            rust_decimal::Decimal::ZERO,
+        );
+
+        // VALVE 11/10 的訂單只服務現貨客戶；它 11/25 的策略客戶需求不應提高 11/10 訂單的等級
+        let result = calculator
+            .calculate(
+                vec![
+                    demand("PUMP", 10, mrp_core::CustomerTier::Standard),
+                    demand("VALVE", 10, mrp_core::CustomerTier::Spot),
+                    demand("VALVE", 25, mrp_core::CustomerTier::Strategic),
+                ],
+                vec![],
+                vec![stock],
+            )
+            .unwrap();
+
+        let first = |parent: &str| {
+            result
+                .allocations
+                .iter()
+                .filter(|a| a.parent_id.as_deref() == Some(parent))
+                .min_by_key(|a| a.required_date)
+                .unwrap()
+        };
+        assert_eq!(first("PUMP").allocated_qty, rust_decimal::Decimal::from(10));
+        assert!(first("VALVE").is_short());
+    }
+
+    #[test]
+    fn test_shared_child_allocation_slips_losing_parents() {
+        let bom_graph = bom_graph(["PUMP", "VALVE"].map(|parent| bom_item(parent, "SEAL", 1)));
+        let configs = [
+            config("PUMP", 2, mrp_core::ProcurementType::Make),
+            config("VALVE", 2, mrp_core::ProcurementType::Make),
+            config("SEAL", 7, mrp_core::ProcurementType::Buy),
+        ];
+        let calculator = calculator(bom_graph, configs)
+            .with_policy(PlanningPolicy::new().with_plan_start_date(date(3)))
+            .with_allocation_strategy(AllocationStrategy::Priority);
+        let demand = |id: &str, quantity: i64, day: u32, tier: mrp_core::CustomerTier| {
+            demand(id, quantity, day).with_customer_tier(tier)
+        };
+        let stock = Inventory::new(
+            "SEAL".to_string(),
+            rust_decimal::Decimal::from(10),
+            rust_decimal::Decimal::ZERO,
+        );
+
+        // SEAL 新訂單最早 11/10 到貨；11/8 只有庫存 10：備品需求 5 先滿足，PUMP 分到 5，VALVE 分不到
+        let result = calculator
+            .calculate(
+                vec![
+                    demand("SEAL", 5, 8, mrp_core::CustomerTier::Strategic),
+                    demand("PUMP", 10, 10, mrp_core::CustomerTier::Standard),
+                    demand("VALVE", 10, 10, mrp_core::CustomerTier::Spot),
+                ],
+                vec![],
+                vec![stock],
+            )
+            .unwrap();
+
+        let orders = |id: &str| {
+            let mut orders: Vec<(NaiveDate, NaiveDate, rust_decimal::Decimal)> = result
+                .planned_orders
+                .iter()
+                .filter(|o| o.component_id == id)
+                .map(|o| (o.order_date, o.required_date, o.quantity))
+                .collect();
+            orders.sort();
+            orders
+        };
+        let qty = rust_decimal::Decimal::from;
+        // PUMP 拆成兩張：分到的 5 件照原日期，其餘 5 件延到 SEAL 到貨日開工
+        assert_eq!(
+            orders("PUMP"),
+            vec![(date(8), date(10), qty(5)), (date(10), date(12), qty(5))]
+        );
+        assert_eq!(orders("VALVE"), vec![(date(10), date(12), qty(10))]);
+        let valve = result
+            .planned_orders
+            .iter()
+            .find(|o| o.component_id == "VALVE")
+            .unwrap();
+        assert_eq!(valve.need_date, Some(date(10)));
+        // 延後的父件需求改在 11/10，SEAL 不再產生過期的計劃訂單
+        assert!(result
+            .planned_orders
+            .iter()
+            .filter(|o| o.component_id == "SEAL")
+            .all(|o| o.required_date >= date(10)));
+        let independent = result
+            .allocations
+            .iter()
+            .find(|a| a.parent_id.is_none())
+            .unwrap();
+        assert!(!independent.is_short());
+    }
+
+    #[test]
+    fn test_dependent_demand_value_follows_source_order() {
+        let bom_graph = bom_graph(["PUMP", "VALVE"].map(|parent| bom_item(parent, "SEAL", 1)));
+        let configs = [
+            config("PUMP", 2, mrp_core::ProcurementType::Make),
+            config("VALVE", 2, mrp_core::ProcurementType::Make),
+            config("SEAL", 1, mrp_core::ProcurementType::Buy),
+        ];
+        let calculator =
+            calculator(bom_graph, configs).with_allocation_strategy(AllocationStrategy::Value);
+        let demand = |id: &str, day: u32, unit_value: i64| {
+            demand(id, 10, day).with_unit_value(rust_decimal::Decimal::from(unit_value))
+        };
+        let stock = Inventory::new(
+            "SEAL".to_string(),
+            rust_decimal::Decimal::from(10),
+            rust_decimal::Decimal::ZERO,
+        );
+
+        // VALVE 11/10 的訂單只追溯到單價 1 的需求；11/25 的高單價需求不應拉高它的金額
+        let result = calculator
+            .calculate(
+                vec![
+                    demand("PUMP", 10, 5),
+                    demand("VALVE", 10, 1),
+                    demand("VALVE", 25, 100),
+                ],
+                vec![],
+                vec![stock],
+            )
+            .unwrap();
+
+        let first = |parent: &str| {
+            result
+                .allocations
+                .iter()
+                .filter(|a| a.parent_id.as_deref() == Some(parent))
+                .min_by_key(|a| a.required_date)
+                .unwrap()
+        };
+        assert_eq!(first("PUMP").allocated_qty, rust_decimal::Decimal::from(10));
+        assert!(first("VALVE").is_short());
+    }
 }
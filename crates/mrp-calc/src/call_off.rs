@@ -0,0 +1,152 @@
+//! 框架訂單叫貨計劃：優先以框架訂單剩餘量滿足採購需求
+
+use mrp_core::{BlanketOrder, PlannedOrder, WorkCalendar};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// 叫貨計劃器
+pub struct CallOffPlanner;
+
+impl CallOffPlanner {
+    /// 將計劃採購訂單轉為對框架訂單的叫貨建議
+    ///
+    /// 按需求日期依序消耗有效期內的框架訂單（先到期者優先），
+    /// 框架訂單用盡或不在有效期內的數量保留為一般採購計劃訂單。
+    pub fn apply(
+        planned_orders: Vec<PlannedOrder>,
+        blankets: &[BlanketOrder],
+        calendar: &WorkCalendar,
+    ) -> Vec<PlannedOrder> {
+        let mut remaining: Vec<(&BlanketOrder, Decimal)> = blankets
+            .iter()
+            .map(|b| (b, b.remaining_quantity()))
+            .filter(|(_, qty)| *qty > Decimal::ZERO)
+            .collect();
+        if remaining.is_empty() {
+            return planned_orders;
+        }
+        remaining.sort_by_key(|(b, _)| b.valid_to);
+
+        let mut sorted_orders = planned_orders;
+        sorted_orders.sort_by_key(|o| o.required_date);
+
+        let mut result = Vec::with_capacity(sorted_orders.len());
+        for order in sorted_orders {
+            if !order.is_purchase() || order.blanket_order_id.is_some() {
+                result.push(order);
+                continue;
+            }
+
+            let mut open_qty = order.quantity;
+            for (blanket, blanket_qty) in remaining.iter_mut() {
+                if open_qty <= Decimal::ZERO {
+                    break;
+                }
+                if *blanket_qty <= Decimal::ZERO
+                    || blanket.component_id != order.component_id
+                    || !blanket.is_valid_on(order.required_date)
+                {
+                    continue;
+                }
+
+                let call_off_qty = open_qty.min(*blanket_qty);
+                *blanket_qty -= call_off_qty;
+                open_qty -= call_off_qty;
+
+                let mut call_off = order.clone();
+                if call_off_qty != order.quantity {
+                    call_off.id = Uuid::new_v4();
+                }
+                call_off.quantity = call_off_qty;
+                call_off.order_date = calendar
+                    .subtract_working_days(order.required_date, blanket.call_off_lead_time_days);
+                call_off.source_id = Some(blanket.supplier_id.clone());
+                call_off.blanket_order_id = Some(blanket.id);
+                result.push(call_off);
+            }
+
+            // 框架訂單不足的部分保留為一般採購
+            if open_qty > Decimal::ZERO {
+                let mut spot = order.clone();
+                if open_qty != order.quantity {
+                    spot.id = Uuid::new_v4();
+                }
+                spot.quantity = open_qty;
+                result.push(spot);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::PlannedOrderType;
+
+    fn purchase(qty: i64, day: u32) -> PlannedOrder {
+        PlannedOrder::new(
+            "BOLT-001".to_string(),
+            Decimal::from(qty),
+            NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 11, day).unwrap() - chrono::Duration::days(10),
+            PlannedOrderType::Purchase,
+        )
+    }
+
+    #[test]
+    fn test_call_off_until_exhausted() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let blanket = BlanketOrder::new(
+            "BOLT-001".to_string(),
+            "VENDOR-A".to_string(),
+            Decimal::from(500),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            2,
+        )
+        .with_released_quantity(Decimal::from(200));
+
+        let orders = CallOffPlanner::apply(
+            vec![purchase(100, 10), purchase(250, 20)],
+            std::slice::from_ref(&blanket),
+            &calendar,
+        );
+
+        assert_eq!(orders.len(), 3);
+
+        // 第一張全數叫貨，下單日改用叫貨提前期
+        assert_eq!(orders[0].blanket_order_id, Some(blanket.id));
+        assert_eq!(orders[0].source_id.as_deref(), Some("VENDOR-A"));
+        assert_eq!(
+            orders[0].order_date,
+            NaiveDate::from_ymd_opt(2025, 11, 8).unwrap()
+        );
+
+        // 第二張：剩餘 200 叫貨，50 轉為一般採購
+        assert_eq!(orders[1].quantity, Decimal::from(200));
+        assert!(orders[1].is_call_off());
+        assert_eq!(orders[2].quantity, Decimal::from(50));
+        assert!(!orders[2].is_call_off());
+    }
+
+    #[test]
+    fn test_expired_blanket_not_used() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let blanket = BlanketOrder::new(
+            "BOLT-001".to_string(),
+            "VENDOR-A".to_string(),
+            Decimal::from(500),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 10, 31).unwrap(),
+            2,
+        );
+
+        let orders = CallOffPlanner::apply(vec![purchase(100, 10)], &[blanket], &calendar);
+
+        assert_eq!(orders.len(), 1);
+        assert!(!orders[0].is_call_off());
+    }
+}
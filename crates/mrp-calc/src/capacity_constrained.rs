@@ -0,0 +1,314 @@
+//! 產能約束 MRP（CC-MRP）：批量與有限產能逐層一併求解
+
+use chrono::NaiveDate;
+use mrp_core::{MrpConfig, PlannedOrder, WorkCalendar};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::MrpResult;
+
+/// 產能調整類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CapacityAdjustment {
+    /// 提前生產（在需求日之前的空閒產能完成）
+    PreBuild,
+    /// 延後完成（需求日之前產能不足）
+    Late,
+}
+
+/// 產能約束診斷
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CapacityDiagnostic {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 調整後的計劃訂單ID
+    pub order_id: Uuid,
+
+    /// 原始需求日期
+    pub original_required_date: NaiveDate,
+
+    /// 調整後的完成日期
+    pub planned_date: NaiveDate,
+
+    /// 數量
+    pub quantity: Decimal,
+
+    /// 調整類型
+    pub adjustment: CapacityAdjustment,
+}
+
+impl CapacityDiagnostic {
+    /// 延遲天數（提前生產為 0）
+    pub fn days_late(&self) -> i64 {
+        (self.planned_date - self.original_required_date)
+            .num_days()
+            .max(0)
+    }
+}
+
+/// 產能約束 MRP 計算結果
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CapacityConstrainedResult {
+    /// MRP 計算結果（計劃訂單已符合產能）
+    pub result: MrpResult,
+
+    /// 產能調整診斷
+    pub diagnostics: Vec<CapacityDiagnostic>,
+}
+
+impl CapacityConstrainedResult {
+    /// 延後完成的訂單診斷
+    pub fn late_orders(&self) -> Vec<&CapacityDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.adjustment == CapacityAdjustment::Late)
+            .collect()
+    }
+}
+
+/// 產能約束計劃器
+pub struct CapacityConstrainedPlanner;
+
+impl CapacityConstrainedPlanner {
+    /// 將計劃訂單排入每日產能
+    ///
+    /// 每張訂單先從需求日往前（不早於 `earliest_date`）佔用空閒產能，
+    /// 仍不足時再往後排（延遲），超出單日產能的訂單會被拆分。
+    /// 拆出的每張訂單都符合物料的最小訂購量與訂購倍數：空閒產能向下取整到訂購倍數，
+    /// 不足最小訂購量的日子不排；最後的餘量向上取整（可能略多於原訂單數量）。
+    pub fn constrain(
+        component_id: &str,
+        orders: Vec<PlannedOrder>,
+        capacity_per_day: Decimal,
+        config: &MrpConfig,
+        earliest_date: NaiveDate,
+        calendar: &WorkCalendar,
+    ) -> mrp_core::Result<(Vec<PlannedOrder>, Vec<CapacityDiagnostic>)> {
+        if capacity_per_day <= Decimal::ZERO {
+            return Err(mrp_core::MrpError::CalculationError(format!(
+                "物料 {} 的每日產能必須大於 0",
+                component_id
+            )));
+        }
+        let smallest_lot = Self::lot_piece(config, Decimal::MAX, Decimal::new(1, 6));
+        if smallest_lot > capacity_per_day {
+            return Err(mrp_core::MrpError::CalculationError(format!(
+                "物料 {} 的每日產能 {} 小於最小批量 {}",
+                component_id, capacity_per_day, smallest_lot
+            )));
+        }
+
+        let mut sorted_orders = orders;
+        sorted_orders.sort_by_key(|o| o.required_date);
+
+        let mut load: HashMap<NaiveDate, Decimal> = HashMap::new();
+        let mut constrained = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        for order in sorted_orders {
+            let mut remaining = order.quantity;
+            let mut pieces: Vec<(NaiveDate, Decimal)> = Vec::new();
+
+            // 往前佔用產能（提前生產）
+            let mut date = if calendar.is_working_day(order.required_date) {
+                order.required_date
+            } else {
+                calendar.previous_working_day(order.required_date)
+            };
+            while remaining > Decimal::ZERO && date >= earliest_date {
+                let used = load.entry(date).or_insert(Decimal::ZERO);
+                let take = Self::lot_piece(config, capacity_per_day - *used, remaining);
+                if take > Decimal::ZERO {
+                    *used += take;
+                    remaining = (remaining - take).max(Decimal::ZERO);
+                    pieces.push((date, take));
+                }
+                date = calendar.previous_working_day(date);
+            }
+
+            // 往後佔用產能（延遲完成）
+            let mut date = order.required_date;
+            while remaining > Decimal::ZERO {
+                date = calendar.next_working_day(date);
+                let used = load.entry(date).or_insert(Decimal::ZERO);
+                let take = Self::lot_piece(config, capacity_per_day - *used, remaining);
+                if take > Decimal::ZERO {
+                    *used += take;
+                    remaining = (remaining - take).max(Decimal::ZERO);
+                    pieces.push((date, take));
+                }
+            }
+
+            for (date, quantity) in pieces {
+                let mut piece = order.clone();
+                if date != order.required_date || quantity != order.quantity {
+                    piece.id = Uuid::new_v4();
+                }
+                piece.quantity = quantity;
+                piece.required_date = date;
+                piece.order_date = calendar.subtract_working_days(date, config.lead_time_days);
+
+                if date != order.required_date {
+                    diagnostics.push(CapacityDiagnostic {
+                        component_id: component_id.to_string(),
+                        order_id: piece.id,
+                        original_required_date: order.required_date,
+                        planned_date: date,
+                        quantity,
+                        adjustment: if date < order.required_date {
+                            CapacityAdjustment::PreBuild
+                        } else {
+                            CapacityAdjustment::Late
+                        },
+                    });
+                }
+
+                constrained.push(piece);
+            }
+        }
+
+        constrained.sort_by_key(|o| o.required_date);
+
+        Ok((constrained, diagnostics))
+    }
+
+    /// 單日可排的批量：餘量取整後放得下時全部排入，否則排入向下取整到訂購倍數的空閒產能，
+    /// 不足最小訂購量時不排（回傳 0）
+    fn lot_piece(config: &MrpConfig, free: Decimal, remaining: Decimal) -> Decimal {
+        let multiple = config.order_multiple.filter(|m| *m > Decimal::ZERO);
+        let minimum = config.minimum_order_qty.unwrap_or(Decimal::ZERO);
+        let rest = multiple
+            .map_or(remaining, |m| (remaining / m).ceil() * m)
+            .max(minimum);
+        if rest <= free {
+            return rest;
+        }
+        let partial = multiple.map_or(free, |m| (free / m).floor() * m);
+        if partial > Decimal::ZERO && partial >= minimum {
+            partial
+        } else {
+            Decimal::ZERO
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+    use mrp_core::{PlannedOrderType, ProcurementType};
+
+    fn order(qty: i64, day: u32) -> PlannedOrder {
+        PlannedOrder::new(
+            "ASSY-001".to_string(),
+            Decimal::from(qty),
+            NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+            PlannedOrderType::Production,
+        )
+    }
+
+    #[test]
+    fn test_pre_build_into_free_capacity() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+
+        let (orders, diagnostics) = CapacityConstrainedPlanner::constrain(
+            "ASSY-001",
+            vec![order(250, 10)],
+            Decimal::from(100),
+            &MrpConfig::new("ASSY-001".to_string(), 2, ProcurementType::Make),
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            &calendar,
+        )
+        .unwrap();
+
+        // 11/8: 50, 11/9: 100, 11/10: 100
+        assert_eq!(orders.len(), 3);
+        let total: Decimal = orders.iter().map(|o| o.quantity).sum();
+        assert_eq!(total, Decimal::from(250));
+        assert!(orders.iter().all(|o| o.quantity <= Decimal::from(100)));
+        assert_eq!(
+            orders[0].required_date,
+            NaiveDate::from_ymd_opt(2025, 11, 8).unwrap()
+        );
+        assert_eq!(
+            orders[0].order_date,
+            NaiveDate::from_ymd_opt(2025, 11, 6).unwrap()
+        );
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.adjustment == CapacityAdjustment::PreBuild));
+    }
+
+    #[test]
+    fn test_late_when_no_earlier_capacity() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+
+        let (orders, diagnostics) = CapacityConstrainedPlanner::constrain(
+            "ASSY-001",
+            vec![order(150, 2)],
+            Decimal::from(100),
+            &MrpConfig::new("ASSY-001".to_string(), 0, ProcurementType::Make),
+            NaiveDate::from_ymd_opt(2025, 11, 2).unwrap(),
+            &calendar,
+        )
+        .unwrap();
+
+        assert_eq!(orders.len(), 2);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].adjustment, CapacityAdjustment::Late);
+        assert_eq!(diagnostics[0].quantity, Decimal::from(50));
+        assert_eq!(diagnostics[0].days_late(), 1);
+    }
+
+    #[test]
+    fn test_split_pieces_follow_lot_rules() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let config = MrpConfig::new("ASSY-001".to_string(), 0, ProcurementType::Make)
+            .with_minimum_order_qty(Decimal::from(40))
+            .with_order_multiple(Decimal::from(20));
+
+        // 第一張佔用 11/10 的 80，剩 20 不足最小訂購量；
+        // 第二張在 11/9 排 100，餘量 20 於 11/8 取整為最小訂購量 40
+        let (orders, _) = CapacityConstrainedPlanner::constrain(
+            "ASSY-001",
+            vec![order(80, 10), order(120, 10)],
+            Decimal::from(100),
+            &config,
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            &calendar,
+        )
+        .unwrap();
+
+        let pieces: Vec<(u32, Decimal)> = orders
+            .iter()
+            .map(|o| (o.required_date.day(), o.quantity))
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                (8, Decimal::from(40)),
+                (9, Decimal::from(100)),
+                (10, Decimal::from(80)),
+            ]
+        );
+
+        // 每日產能放不下最小批量
+        let error = CapacityConstrainedPlanner::constrain(
+            "ASSY-001",
+            vec![order(80, 10)],
+            Decimal::from(30),
+            &config,
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            &calendar,
+        );
+        assert!(error.is_err());
+    }
+}
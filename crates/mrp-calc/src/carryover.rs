@@ -0,0 +1,180 @@
+//! 跨次計算的計劃訂單延續：容差內視為未變更並沿用上次的訂單識別
+//!
+//! 每次重新計算都會產生新的計劃訂單ID；對日期與數量變動都在容差內的訂單，
+//! 沿用上一次的ID，讓採購員看到的是同一張訂單，依訂單ID掛載的資料也得以延續。
+
+use mrp_core::PlannedOrder;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 延續容差
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CarryoverTolerance {
+    /// 需求日期容差（±天）
+    pub date_days: u32,
+
+    /// 數量容差（±百分比，例如 10 表示 ±10%，以上次數量為基準）
+    pub quantity_pct: Decimal,
+}
+
+impl CarryoverTolerance {
+    /// 創建延續容差
+    pub fn new(date_days: u32, quantity_pct: Decimal) -> Self {
+        Self {
+            date_days,
+            quantity_pct,
+        }
+    }
+
+    /// 檢查新訂單是否在容差內
+    pub fn matches(&self, previous: &PlannedOrder, current: &PlannedOrder) -> bool {
+        if previous.component_id != current.component_id
+            || previous.order_type != current.order_type
+        {
+            return false;
+        }
+
+        let date_diff = (current.required_date - previous.required_date)
+            .num_days()
+            .abs();
+        if date_diff > self.date_days as i64 {
+            return false;
+        }
+
+        let allowed = previous.quantity.abs() * self.quantity_pct / Decimal::from(100);
+        (current.quantity - previous.quantity).abs() <= allowed
+    }
+}
+
+impl Default for CarryoverTolerance {
+    /// 預設：日期與數量都必須完全相同
+    fn default() -> Self {
+        Self::new(0, Decimal::ZERO)
+    }
+}
+
+/// 延續結果摘要
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct CarryoverSummary {
+    /// 新訂單ID → 沿用的上次訂單ID
+    pub carried_over: HashMap<Uuid, Uuid>,
+
+    /// 本次新產生的訂單ID
+    pub new_orders: Vec<Uuid>,
+
+    /// 上次有、本次未延續的訂單ID
+    pub dropped_orders: Vec<Uuid>,
+}
+
+/// 計劃訂單延續器
+pub struct OrderCarryover;
+
+impl OrderCarryover {
+    /// 為本次訂單配對上次訂單
+    ///
+    /// 按需求日期依序處理，每張新訂單在同物料、同類型、容差內的上次訂單中
+    /// 取日期最接近（其次數量最接近）者；每張上次訂單最多被沿用一次。
+    /// 回傳的摘要以本次原始ID為鍵，訂單本身不修改。
+    pub fn match_orders(
+        previous: &[PlannedOrder],
+        current: &[PlannedOrder],
+        tolerance: CarryoverTolerance,
+    ) -> CarryoverSummary {
+        let mut previous_by_component: HashMap<&str, Vec<&PlannedOrder>> = HashMap::new();
+        for order in previous {
+            previous_by_component
+                .entry(order.component_id.as_str())
+                .or_default()
+                .push(order);
+        }
+
+        let mut sorted_current: Vec<&PlannedOrder> = current.iter().collect();
+        sorted_current.sort_by_key(|o| o.required_date);
+
+        let mut summary = CarryoverSummary::default();
+        let mut used: std::collections::HashSet<Uuid> = std::collections::HashSet::new();
+
+        for order in sorted_current {
+            let best = previous_by_component
+                .get(order.component_id.as_str())
+                .into_iter()
+                .flatten()
+                .filter(|prev| !used.contains(&prev.id) && tolerance.matches(prev, order))
+                .min_by_key(|prev| {
+                    (
+                        (order.required_date - prev.required_date).num_days().abs(),
+                        (order.quantity - prev.quantity).abs(),
+                    )
+                });
+
+            match best {
+                Some(prev) => {
+                    used.insert(prev.id);
+                    summary.carried_over.insert(order.id, prev.id);
+                }
+                None => summary.new_orders.push(order.id),
+            }
+        }
+
+        summary.dropped_orders = previous
+            .iter()
+            .filter(|o| !used.contains(&o.id))
+            .map(|o| o.id)
+            .collect();
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::PlannedOrderType;
+
+    fn order(qty: i64, day: u32) -> PlannedOrder {
+        let date = NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        PlannedOrder::new(
+            "BOLT-001".to_string(),
+            Decimal::from(qty),
+            date,
+            date,
+            PlannedOrderType::Purchase,
+        )
+    }
+
+    #[test]
+    fn test_match_within_tolerance() {
+        let previous = vec![order(100, 10), order(100, 20), order(100, 25)];
+        let current = vec![order(105, 11), order(150, 20), order(100, 24)];
+
+        let summary = OrderCarryover::match_orders(
+            &previous,
+            &current,
+            CarryoverTolerance::new(2, Decimal::from(10)),
+        );
+
+        // 11/11 105：日期 +1、數量 +5% → 沿用 11/10
+        assert_eq!(summary.carried_over[&current[0].id], previous[0].id);
+        // 11/20 150：數量 +50% 超出容差
+        assert!(summary.new_orders.contains(&current[1].id));
+        // 11/24：最接近的是 11/25
+        assert_eq!(summary.carried_over[&current[2].id], previous[2].id);
+        assert_eq!(summary.dropped_orders, vec![previous[1].id]);
+    }
+
+    #[test]
+    fn test_exact_match_by_default() {
+        let previous = vec![order(100, 10)];
+        let current = vec![order(101, 10)];
+
+        let summary =
+            OrderCarryover::match_orders(&previous, &current, CarryoverTolerance::default());
+
+        assert!(summary.carried_over.is_empty());
+        assert_eq!(summary.new_orders.len(), 1);
+    }
+}
@@ -0,0 +1,179 @@
+//! 合約承諾量追蹤：比對計劃採購量與供應商最低承諾量
+
+use mrp_core::{PlannedOrder, SupplyContract};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// 單一合約的承諾量消耗狀態
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CommitmentStatus {
+    /// 合約ID
+    pub contract_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 供應商ID
+    pub supplier_id: String,
+
+    /// 最低承諾量
+    pub minimum_quantity: Decimal,
+
+    /// 有效期內的計劃採購量
+    pub planned_quantity: Decimal,
+
+    /// 是否為照付不議合約
+    pub take_or_pay: bool,
+}
+
+impl CommitmentStatus {
+    /// 未達承諾量的差額
+    pub fn shortfall(&self) -> Decimal {
+        (self.minimum_quantity - self.planned_quantity).max(Decimal::ZERO)
+    }
+
+    /// 檢查計劃是否未消耗完承諾量
+    pub fn is_under_consumed(&self) -> bool {
+        self.shortfall() > Decimal::ZERO
+    }
+}
+
+/// 合約承諾量追蹤器
+pub struct CommitmentTracker;
+
+impl CommitmentTracker {
+    /// 計算每份合約有效期內的累計計劃採購量
+    pub fn evaluate(
+        planned_orders: &[PlannedOrder],
+        contracts: &[SupplyContract],
+    ) -> Vec<CommitmentStatus> {
+        contracts
+            .iter()
+            .map(|contract| {
+                let planned_quantity = planned_orders
+                    .iter()
+                    .filter(|o| Self::counts_toward(o, contract))
+                    .map(|o| o.quantity)
+                    .sum();
+
+                CommitmentStatus {
+                    contract_id: contract.id,
+                    component_id: contract.component_id.clone(),
+                    supplier_id: contract.supplier_id.clone(),
+                    minimum_quantity: contract.minimum_quantity,
+                    planned_quantity,
+                    take_or_pay: contract.take_or_pay,
+                }
+            })
+            .collect()
+    }
+
+    /// 將採購改派給承諾量未達成的供應商
+    ///
+    /// 按需求日期依序把有效期內、非叫貨的採購訂單改派給合約供應商，
+    /// 直到計劃採購量達到最低承諾量為止（整張訂單改派，不拆單）。
+    pub fn bias_sourcing(planned_orders: &mut [PlannedOrder], contracts: &[SupplyContract]) {
+        for contract in contracts {
+            let mut planned: Decimal = planned_orders
+                .iter()
+                .filter(|o| Self::counts_toward(o, contract))
+                .map(|o| o.quantity)
+                .sum();
+
+            let mut candidates: Vec<&mut PlannedOrder> = planned_orders
+                .iter_mut()
+                .filter(|o| {
+                    o.is_purchase()
+                        && !o.is_call_off()
+                        && o.component_id == contract.component_id
+                        && o.source_id.as_deref() != Some(contract.supplier_id.as_str())
+                        && contract.is_valid_on(o.required_date)
+                })
+                .collect();
+            candidates.sort_by_key(|o| o.required_date);
+
+            for order in candidates {
+                if planned >= contract.minimum_quantity {
+                    break;
+                }
+                order.source_id = Some(contract.supplier_id.clone());
+                planned += order.quantity;
+            }
+        }
+    }
+
+    /// 訂單是否計入合約承諾量
+    fn counts_toward(order: &PlannedOrder, contract: &SupplyContract) -> bool {
+        order.is_purchase()
+            && order.component_id == contract.component_id
+            && order.source_id.as_deref() == Some(contract.supplier_id.as_str())
+            && contract.is_valid_on(order.required_date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::PlannedOrderType;
+
+    fn purchase(supplier: Option<&str>, qty: i64, day: u32) -> PlannedOrder {
+        let date = NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        let order = PlannedOrder::new(
+            "RESIN-001".to_string(),
+            Decimal::from(qty),
+            date,
+            date,
+            PlannedOrderType::Purchase,
+        );
+        match supplier {
+            Some(s) => order.with_source_id(s.to_string()),
+            None => order,
+        }
+    }
+
+    fn contract() -> SupplyContract {
+        SupplyContract::new(
+            "RESIN-001".to_string(),
+            "VENDOR-A".to_string(),
+            Decimal::from(500),
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 11, 30).unwrap(),
+        )
+        .as_take_or_pay()
+    }
+
+    #[test]
+    fn test_under_consumed_contract() {
+        let orders = vec![
+            purchase(Some("VENDOR-A"), 200, 5),
+            purchase(Some("VENDOR-B"), 300, 10),
+        ];
+
+        let status = CommitmentTracker::evaluate(&orders, &[contract()]);
+
+        assert_eq!(status[0].planned_quantity, Decimal::from(200));
+        assert_eq!(status[0].shortfall(), Decimal::from(300));
+        assert!(status[0].is_under_consumed());
+    }
+
+    #[test]
+    fn test_bias_sourcing_toward_committed_supplier() {
+        let mut orders = vec![
+            purchase(Some("VENDOR-A"), 200, 5),
+            purchase(Some("VENDOR-B"), 200, 10),
+            purchase(None, 200, 15),
+            purchase(Some("VENDOR-B"), 200, 20),
+        ];
+        let contracts = [contract()];
+
+        CommitmentTracker::bias_sourcing(&mut orders, &contracts);
+
+        // 改派 11/10、11/15 兩張後達到 600 ≥ 500，11/20 維持原供應商
+        assert_eq!(orders[1].source_id.as_deref(), Some("VENDOR-A"));
+        assert_eq!(orders[2].source_id.as_deref(), Some("VENDOR-A"));
+        assert_eq!(orders[3].source_id.as_deref(), Some("VENDOR-B"));
+        assert!(!CommitmentTracker::evaluate(&orders, &contracts)[0].is_under_consumed());
+    }
+}
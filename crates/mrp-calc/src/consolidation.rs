@@ -0,0 +1,129 @@
+//! 計劃訂單合併：將同物料、需求日期落在合併窗口內的計劃訂單合併為一張
+
+use mrp_core::{MrpConfig, PlannedOrder, WorkCalendar};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 合併結果
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ConsolidationOutcome {
+    /// 合併後的計劃訂單
+    pub orders: Vec<PlannedOrder>,
+
+    /// 被合併的訂單ID → 合併後的訂單ID
+    pub merged: HashMap<Uuid, Uuid>,
+}
+
+/// 計劃訂單合併器
+pub struct OrderConsolidator;
+
+impl OrderConsolidator {
+    /// 合併需求日期在窗口內的訂單（以每組第一張訂單的需求日期為窗口起點）
+    ///
+    /// 同一組訂單的數量加總後重新套用訂購修正（最小訂購量、倍數、最大訂購量拆單），
+    /// 沿用最早的需求日期並重算下單日；追溯記錄併入合併後的訂單。
+    /// 只有一張訂單的組不變動。設置最大涵蓋天數（保存期限）時，窗口不超過該天數。
+    pub fn consolidate(
+        orders: Vec<PlannedOrder>,
+        window_days: u32,
+        config: &MrpConfig,
+        calendar: &WorkCalendar,
+    ) -> ConsolidationOutcome {
+        let window_days = config
+            .max_coverage_days
+            .map_or(window_days, |days| window_days.min(days));
+        let mut sorted = orders;
+        sorted.sort_by_key(|o| o.required_date);
+
+        let mut groups: Vec<Vec<PlannedOrder>> = Vec::new();
+        for order in sorted {
+            match groups.last_mut() {
+                Some(group)
+                    if group[0].order_type == order.order_type
+                        && (order.required_date - group[0].required_date).num_days()
+                            < window_days as i64 =>
+                {
+                    group.push(order)
+                }
+                _ => groups.push(vec![order]),
+            }
+        }
+
+        let mut outcome = ConsolidationOutcome::default();
+        for group in groups {
+            if group.len() == 1 {
+                outcome.orders.extend(group);
+                continue;
+            }
+
+            let total: Decimal = group.iter().map(|o| o.quantity).sum();
+            let parts = config.split_order_quantity(total);
+            let survivor_id = group[0].id;
+            let pegging: Vec<_> = group.iter().flat_map(|o| o.pegging.clone()).collect();
+
+            for (index, quantity) in parts.iter().enumerate() {
+                let mut order = group[0].clone();
+                order.id = group.get(index).map(|o| o.id).unwrap_or_else(Uuid::new_v4);
+                order.quantity = *quantity;
+                order.order_date = calendar
+                    .subtract_working_days(order.required_date, config.lead_time_for(*quantity));
+                order.pegging = if index == 0 {
+                    pegging.clone()
+                } else {
+                    Vec::new()
+                };
+                outcome.orders.push(order);
+            }
+            for order in group.iter().skip(parts.len()) {
+                outcome.merged.insert(order.id, survivor_id);
+            }
+        }
+
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::{PeggingRecord, PlannedOrderType, ProcurementType};
+
+    fn order(qty: i64, day: u32) -> PlannedOrder {
+        let date = NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        let mut order = PlannedOrder::new(
+            "PAINT-001".to_string(),
+            Decimal::from(qty),
+            date,
+            date,
+            PlannedOrderType::Purchase,
+        );
+        order.add_pegging(PeggingRecord::new(Uuid::new_v4(), Decimal::from(qty)));
+        order
+    }
+
+    #[test]
+    fn test_consolidate_within_window() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let config = MrpConfig::new("PAINT-001".to_string(), 3, ProcurementType::Buy)
+            .with_order_multiple(Decimal::from(50));
+        let orders = vec![order(50, 3), order(50, 5), order(100, 12)];
+        let first_id = orders[0].id;
+        let second_id = orders[1].id;
+
+        let outcome = OrderConsolidator::consolidate(orders, 7, &config, &calendar);
+
+        // 11/3 與 11/5 合併為 100，沿用 11/3 與第一張訂單的ID
+        assert_eq!(outcome.orders.len(), 2);
+        assert_eq!(outcome.orders[0].id, first_id);
+        assert_eq!(outcome.orders[0].quantity, Decimal::from(100));
+        assert_eq!(
+            outcome.orders[0].order_date,
+            NaiveDate::from_ymd_opt(2025, 10, 31).unwrap()
+        );
+        assert_eq!(outcome.orders[0].pegging.len(), 2);
+        assert_eq!(outcome.merged[&second_id], first_id);
+    }
+}
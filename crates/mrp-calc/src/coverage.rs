@@ -0,0 +1,494 @@
+//! 需求覆蓋分析（逐筆需求的供需對應）
+
+use chrono::NaiveDate;
+use mrp_core::{CustomerTier, Demand, PlannedOrder, Supply, SupplyType};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 覆蓋來源類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CoverageSourceType {
+    /// 現有庫存
+    OnHand,
+    /// 確認的現有供應（已確認的採購單、工單、調撥與確認計劃訂單）
+    FirmSupply,
+    /// 未確認的現有供應（採購單、工單、調撥）
+    OpenSupply,
+    /// 未確認的計劃供應（外部或前次計算提供、尚未確認的計劃訂單）
+    PlannedSupply,
+    /// 計劃訂單（本次 MRP 生成）
+    PlannedOrder,
+}
+
+impl CoverageSourceType {
+    /// 依供應的確認狀態與類型決定來源類型（庫存類供應視為確認）
+    pub fn of_supply(supply: &Supply) -> Self {
+        if supply.is_firm || supply.supply_type == SupplyType::OnHand {
+            Self::FirmSupply
+        } else if supply.is_planned() {
+            Self::PlannedSupply
+        } else {
+            Self::OpenSupply
+        }
+    }
+
+    /// 是否為輸入的現有供應（確認、未確認或計劃供應，非庫存與本次計劃訂單）
+    pub fn is_supply(self) -> bool {
+        matches!(
+            self,
+            Self::FirmSupply | Self::OpenSupply | Self::PlannedSupply
+        )
+    }
+}
+
+/// 單筆覆蓋分配
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CoverageAllocation {
+    /// 來源類型
+    pub source_type: CoverageSourceType,
+
+    /// 來源ID（供應ID或計劃訂單ID，庫存為 None）
+    pub source_id: Option<Uuid>,
+
+    /// 分配數量
+    pub quantity: Decimal,
+
+    /// 來源可用日期（庫存為 None）
+    pub available_date: Option<NaiveDate>,
+}
+
+impl CoverageAllocation {
+    /// 檢查該分配是否晚於需求日期到位
+    pub fn is_late(&self, required_date: NaiveDate) -> bool {
+        self.available_date
+            .map(|date| date > required_date)
+            .unwrap_or(false)
+    }
+}
+
+/// 單筆需求的覆蓋明細
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DemandCoverage {
+    /// 需求ID
+    pub demand_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 需求日期
+    pub required_date: NaiveDate,
+
+    /// 需求數量
+    pub demand_quantity: Decimal,
+
+    /// 覆蓋分配明細（按分配順序）
+    pub allocations: Vec<CoverageAllocation>,
+
+    /// 未覆蓋數量
+    pub uncovered_quantity: Decimal,
+
+    /// 需求單位金額（營收或毛利）
+    pub unit_value: Option<Decimal>,
+}
+
+impl DemandCoverage {
+    /// 按來源類型匯總覆蓋數量
+    pub fn covered_by(&self, source_type: CoverageSourceType) -> Decimal {
+        self.allocations
+            .iter()
+            .filter(|a| a.source_type == source_type)
+            .map(|a| a.quantity)
+            .sum()
+    }
+
+    /// 庫存覆蓋數量
+    pub fn covered_by_on_hand(&self) -> Decimal {
+        self.covered_by(CoverageSourceType::OnHand)
+    }
+
+    /// 確認供應覆蓋數量
+    pub fn covered_by_firm_supply(&self) -> Decimal {
+        self.covered_by(CoverageSourceType::FirmSupply)
+    }
+
+    /// 未確認供應（含未確認的計劃供應）覆蓋數量
+    pub fn covered_by_open_supply(&self) -> Decimal {
+        self.covered_by(CoverageSourceType::OpenSupply)
+            + self.covered_by(CoverageSourceType::PlannedSupply)
+    }
+
+    /// 計劃訂單覆蓋數量
+    pub fn covered_by_planned_orders(&self) -> Decimal {
+        self.covered_by(CoverageSourceType::PlannedOrder)
+    }
+
+    /// 檢查是否完全覆蓋
+    pub fn is_fully_covered(&self) -> bool {
+        self.uncovered_quantity <= Decimal::ZERO
+    }
+
+    /// 未能準時滿足的數量（未覆蓋 + 晚到）
+    pub fn at_risk_quantity(&self) -> Decimal {
+        self.uncovered_quantity
+            + self
+                .allocations
+                .iter()
+                .filter(|a| a.is_late(self.required_date))
+                .map(|a| a.quantity)
+                .sum::<Decimal>()
+    }
+
+    /// 風險金額（未能準時滿足的數量 × 單位金額；未設金額時為零）
+    pub fn value_at_risk(&self) -> Decimal {
+        self.unit_value.map_or(Decimal::ZERO, |unit_value| {
+            unit_value * self.at_risk_quantity()
+        })
+    }
+
+    /// 最晚到位日期（用於判斷需求實際可滿足的日期）
+    pub fn last_available_date(&self) -> Option<NaiveDate> {
+        self.allocations
+            .iter()
+            .filter_map(|a| a.available_date)
+            .max()
+    }
+}
+
+/// 客戶等級的需求滿足率
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TierFillRate {
+    /// 客戶等級
+    pub tier: CustomerTier,
+
+    /// 需求總量
+    pub demand_quantity: Decimal,
+
+    /// 準時滿足數量（需求日期前到位的覆蓋）
+    pub on_time_quantity: Decimal,
+
+    /// 已覆蓋數量（含晚到）
+    pub covered_quantity: Decimal,
+}
+
+impl TierFillRate {
+    /// 準時滿足率（0–1；無需求時為 1）
+    pub fn fill_rate(&self) -> Decimal {
+        if self.demand_quantity <= Decimal::ZERO {
+            return Decimal::ONE;
+        }
+        self.on_time_quantity / self.demand_quantity
+    }
+}
+
+/// 待分配的供應來源
+struct CoverageSource {
+    source_type: CoverageSourceType,
+    source_id: Option<Uuid>,
+    available_date: Option<NaiveDate>,
+    remaining: Decimal,
+}
+
+/// 需求覆蓋計算器
+pub struct CoverageCalculator;
+
+impl CoverageCalculator {
+    /// 計算單一物料所有需求的覆蓋明細
+    ///
+    /// 需求按日期（同日按客戶等級、優先級高者先）依序分配，來源依序為：
+    /// 現有庫存 → 現有供應 → 計劃訂單，同類來源按可用日期先到先用。
+    /// 現有供應依確認狀態與類型標為確認、未確認或計劃供應。
+    pub fn calculate(
+        demands: &[Demand],
+        initial_inventory: Decimal,
+        supplies: &[Supply],
+        planned_orders: &[PlannedOrder],
+    ) -> Vec<DemandCoverage> {
+        let mut sources = Vec::new();
+
+        if initial_inventory > Decimal::ZERO {
+            sources.push(CoverageSource {
+                source_type: CoverageSourceType::OnHand,
+                source_id: None,
+                available_date: None,
+                remaining: initial_inventory,
+            });
+        }
+
+        let mut sorted_supplies: Vec<&Supply> = supplies.iter().collect();
+        sorted_supplies.sort_by_key(|s| s.available_date);
+        sources.extend(sorted_supplies.into_iter().map(|s| CoverageSource {
+            source_type: CoverageSourceType::of_supply(s),
+            source_id: Some(s.id),
+            available_date: Some(s.available_date),
+            remaining: s.quantity,
+        }));
+
+        let mut sorted_orders: Vec<&PlannedOrder> = planned_orders.iter().collect();
+        sorted_orders.sort_by_key(|o| o.required_date);
+        sources.extend(sorted_orders.into_iter().map(|o| CoverageSource {
+            source_type: CoverageSourceType::PlannedOrder,
+            source_id: Some(o.id),
+            available_date: Some(o.required_date),
+            remaining: o.quantity,
+        }));
+
+        let mut sorted_demands: Vec<&Demand> = demands.iter().collect();
+        sorted_demands.sort_by(|a, b| {
+            a.required_date
+                .cmp(&b.required_date)
+                .then(b.allocation_rank().cmp(&a.allocation_rank()))
+        });
+
+        let mut remaining: Vec<Decimal> = sorted_demands.iter().map(|d| d.quantity).collect();
+        let mut allocations: Vec<Vec<CoverageAllocation>> = vec![Vec::new(); sorted_demands.len()];
+
+        // 第一輪只分配準時到位的來源，第二輪再以晚到的來源補足
+        for on_time_pass in [true, false] {
+            for (idx, demand) in sorted_demands.iter().enumerate() {
+                for source in sources.iter_mut() {
+                    if remaining[idx] <= Decimal::ZERO {
+                        break;
+                    }
+                    if source.remaining <= Decimal::ZERO {
+                        continue;
+                    }
+
+                    let on_time = source
+                        .available_date
+                        .map(|date| date <= demand.required_date)
+                        .unwrap_or(true);
+                    if on_time != on_time_pass {
+                        continue;
+                    }
+
+                    let allocated = source.remaining.min(remaining[idx]);
+                    source.remaining -= allocated;
+                    remaining[idx] -= allocated;
+
+                    allocations[idx].push(CoverageAllocation {
+                        source_type: source.source_type,
+                        source_id: source.source_id,
+                        quantity: allocated,
+                        available_date: source.available_date,
+                    });
+                }
+            }
+        }
+
+        sorted_demands
+            .into_iter()
+            .zip(allocations)
+            .zip(remaining)
+            .map(|((demand, allocations), remaining)| DemandCoverage {
+                demand_id: demand.id,
+                component_id: demand.component_id.clone(),
+                required_date: demand.required_date,
+                demand_quantity: demand.quantity,
+                allocations,
+                uncovered_quantity: remaining.max(Decimal::ZERO),
+                unit_value: demand.unit_value,
+            })
+            .collect()
+    }
+
+    /// 按客戶等級彙總輸入需求的滿足率（依需求覆蓋明細），按等級由高到低排序
+    pub fn fill_rate_by_tier(demands: &[Demand], coverage: &[DemandCoverage]) -> Vec<TierFillRate> {
+        let coverage_by_demand: HashMap<Uuid, &DemandCoverage> =
+            coverage.iter().map(|c| (c.demand_id, c)).collect();
+
+        let mut by_tier: HashMap<CustomerTier, TierFillRate> = HashMap::new();
+        for demand in demands {
+            let entry = by_tier
+                .entry(demand.customer_tier)
+                .or_insert_with(|| TierFillRate {
+                    tier: demand.customer_tier,
+                    demand_quantity: Decimal::ZERO,
+                    on_time_quantity: Decimal::ZERO,
+                    covered_quantity: Decimal::ZERO,
+                });
+            entry.demand_quantity += demand.quantity;
+
+            if let Some(c) = coverage_by_demand.get(&demand.id) {
+                for allocation in &c.allocations {
+                    entry.covered_quantity += allocation.quantity;
+                    if !allocation.is_late(c.required_date) {
+                        entry.on_time_quantity += allocation.quantity;
+                    }
+                }
+            }
+        }
+
+        let mut rates: Vec<TierFillRate> = by_tier.into_values().collect();
+        rates.sort_by_key(|r| std::cmp::Reverse(r.tier.rank()));
+        rates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{DemandType, PlannedOrderType, SupplyType};
+
+    #[test]
+    fn test_coverage_breakdown() {
+        let demand = Demand::new(
+            "PART-001".to_string(),
+            Decimal::from(100),
+            NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
+            DemandType::SalesOrder,
+        );
+
+        let supply = Supply::new(
+            "PART-001".to_string(),
+            Decimal::from(30),
+            NaiveDate::from_ymd_opt(2025, 11, 5).unwrap(),
+            SupplyType::PurchaseOrder,
+        )
+        .as_firm();
+        let open_supply = Supply::new(
+            "PART-001".to_string(),
+            Decimal::from(5),
+            NaiveDate::from_ymd_opt(2025, 11, 6).unwrap(),
+            SupplyType::WorkOrder,
+        );
+
+        let order = PlannedOrder::new(
+            "PART-001".to_string(),
+            Decimal::from(40),
+            NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 11, 3).unwrap(),
+            PlannedOrderType::Purchase,
+        );
+
+        let result = CoverageCalculator::calculate(
+            std::slice::from_ref(&demand),
+            Decimal::from(20),
+            &[supply.clone(), open_supply.clone()],
+            std::slice::from_ref(&order),
+        );
+
+        assert_eq!(result.len(), 1);
+        let coverage = &result[0];
+        assert_eq!(coverage.demand_id, demand.id);
+        assert_eq!(coverage.covered_by_on_hand(), Decimal::from(20));
+        assert_eq!(coverage.covered_by_firm_supply(), Decimal::from(30));
+        assert_eq!(coverage.covered_by_open_supply(), Decimal::from(5));
+        assert_eq!(coverage.covered_by_planned_orders(), Decimal::from(40));
+        assert_eq!(coverage.uncovered_quantity, Decimal::from(5));
+        assert!(!coverage.is_fully_covered());
+        assert_eq!(coverage.allocations[1].source_id, Some(supply.id));
+        // 未確認的工單不標為確認供應
+        assert_eq!(
+            coverage.allocations[2].source_type,
+            CoverageSourceType::OpenSupply
+        );
+        assert_eq!(coverage.allocations[2].source_id, Some(open_supply.id));
+        assert_eq!(coverage.allocations[3].source_id, Some(order.id));
+    }
+
+    #[test]
+    fn test_value_at_risk_ranks_by_revenue() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        let cheap = Demand::new(
+            "PART-001".to_string(),
+            Decimal::from(100),
+            date,
+            DemandType::SalesOrder,
+        )
+        .with_unit_value(Decimal::from(2));
+        let premium = Demand::new(
+            "PART-001".to_string(),
+            Decimal::from(10),
+            date,
+            DemandType::SalesOrder,
+        )
+        .with_unit_value(Decimal::from(50));
+
+        // 只有 60 件在手：按輸入順序覆蓋，兩筆需求各缺 40 與 10 件
+        let result = CoverageCalculator::calculate(
+            &[cheap.clone(), premium.clone()],
+            Decimal::from(60),
+            &[],
+            &[],
+        );
+
+        assert_eq!(result[0].at_risk_quantity(), Decimal::from(40));
+        assert_eq!(result[0].value_at_risk(), Decimal::from(80));
+        assert_eq!(result[1].value_at_risk(), Decimal::from(500));
+    }
+
+    #[test]
+    fn test_coverage_prefers_on_time_sources() {
+        let early = Demand::new(
+            "PART-002".to_string(),
+            Decimal::from(50),
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            DemandType::SalesOrder,
+        );
+        let late = Demand::new(
+            "PART-002".to_string(),
+            Decimal::from(50),
+            NaiveDate::from_ymd_opt(2025, 11, 20).unwrap(),
+            DemandType::SalesOrder,
+        );
+
+        // 11/10 到貨的供應對 11/1 的需求是晚到
+        let supply = Supply::new(
+            "PART-002".to_string(),
+            Decimal::from(50),
+            NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
+            SupplyType::WorkOrder,
+        )
+        .as_firm();
+
+        let result = CoverageCalculator::calculate(
+            &[late.clone(), early.clone()],
+            Decimal::ZERO,
+            &[supply],
+            &[],
+        );
+
+        // 按需求日期排序輸出
+        assert_eq!(result[0].demand_id, early.id);
+        assert_eq!(result[1].demand_id, late.id);
+
+        // 供應保留給準時可用的後期需求，前期需求無法覆蓋
+        assert_eq!(result[0].uncovered_quantity, Decimal::from(50));
+        assert_eq!(result[1].covered_by_firm_supply(), Decimal::from(50));
+        assert!(!result[1].allocations[0].is_late(late.required_date));
+    }
+
+    #[test]
+    fn test_fill_rate_by_tier() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        let demand = |qty, tier| {
+            Demand::new(
+                "PART-001".to_string(),
+                Decimal::from(qty),
+                date,
+                DemandType::SalesOrder,
+            )
+            .with_customer_tier(tier)
+        };
+        // 策略客戶的數量較小但同日先分配
+        let demands = vec![
+            demand(80, CustomerTier::Spot),
+            demand(50, CustomerTier::Strategic),
+        ];
+
+        let coverage = CoverageCalculator::calculate(&demands, Decimal::from(60), &[], &[]);
+        let rates = CoverageCalculator::fill_rate_by_tier(&demands, &coverage);
+
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates[0].tier, CustomerTier::Strategic);
+        assert_eq!(rates[0].fill_rate(), Decimal::ONE);
+        assert_eq!(rates[1].tier, CustomerTier::Spot);
+        assert_eq!(rates[1].on_time_quantity, Decimal::from(10));
+        assert_eq!(rates[1].fill_rate(), Decimal::new(125, 3));
+    }
+}
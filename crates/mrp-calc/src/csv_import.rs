@@ -0,0 +1,462 @@
+//! CSV 匯入：支援 Big5／GBK／Shift_JIS 等非 UTF-8 編碼
+//!
+//! 逐行解碼（這些編碼的多位元組字元不含換行與逗號位元組），
+//! 解碼或解析失敗時錯誤訊息指出行號與欄位。引號內不支援換行。
+
+use encoding_rs::{Encoding, UTF_8};
+use mrp_core::{Demand, DemandType, MrpError, ResourceBill, ResourceLoad, Supply, SupplyType};
+
+use crate::locale::ImportLocale;
+
+/// 已解碼的 CSV 資料列
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CsvRow {
+    /// 檔案行號（從 1 起算，標題為第 1 行）
+    pub line: usize,
+
+    /// 欄位值
+    pub fields: Vec<String>,
+}
+
+/// 已解碼的 CSV 表格
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CsvTable {
+    /// 實際使用的編碼名稱
+    pub encoding: &'static str,
+
+    /// 標題列
+    pub headers: Vec<String>,
+
+    /// 資料列
+    pub rows: Vec<CsvRow>,
+}
+
+impl CsvTable {
+    /// 查詢欄位索引
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == name)
+    }
+
+    fn required_column(&self, name: &str) -> mrp_core::Result<usize> {
+        self.column(name)
+            .ok_or_else(|| MrpError::ImportError(format!("缺少欄位: {}", name)))
+    }
+
+    /// 取欄位值並解析，失敗時指出行號與欄位
+    fn parse<T>(
+        &self,
+        row: &CsvRow,
+        column: usize,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> mrp_core::Result<T> {
+        let value = row.fields.get(column).map(String::as_str).unwrap_or("");
+        parse(value.trim()).ok_or_else(|| {
+            MrpError::ImportError(format!(
+                "第 {} 行第 {} 欄（{}）無效: {:?}",
+                row.line,
+                column + 1,
+                self.headers[column],
+                value
+            ))
+        })
+    }
+
+    fn optional<'a>(&self, row: &'a CsvRow, column: Option<usize>) -> Option<&'a str> {
+        column
+            .and_then(|c| row.fields.get(c))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// CSV 匯入器
+#[derive(Debug, Clone)]
+pub struct CsvImporter {
+    /// 指定編碼（None 表示自動判斷）
+    encoding: Option<String>,
+
+    /// 自動判斷時依序嘗試的編碼（UTF-8 之後）
+    fallback_encodings: Vec<String>,
+
+    /// 分隔字元（ASCII）
+    delimiter: u8,
+
+    /// 數值與日期的地區設定
+    locale: ImportLocale,
+}
+
+impl CsvImporter {
+    /// 創建自動判斷編碼的匯入器（UTF-8 → Big5 → GBK → Shift_JIS）
+    pub fn new() -> Self {
+        Self {
+            encoding: None,
+            fallback_encodings: vec![
+                "Big5".to_string(),
+                "GBK".to_string(),
+                "Shift_JIS".to_string(),
+            ],
+            delimiter: b',',
+            locale: ImportLocale::new(),
+        }
+    }
+
+    /// 建構器模式：指定編碼（不再自動判斷）
+    pub fn with_encoding(mut self, encoding: &str) -> Self {
+        self.encoding = Some(encoding.to_string());
+        self
+    }
+
+    /// 建構器模式：設置自動判斷時嘗試的編碼順序
+    pub fn with_fallback_encodings(mut self, encodings: Vec<String>) -> Self {
+        self.fallback_encodings = encodings;
+        self
+    }
+
+    /// 建構器模式：設置分隔字元
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// 建構器模式：設置數值與日期的地區設定
+    pub fn with_locale(mut self, locale: ImportLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// 解碼並解析 CSV
+    pub fn read(&self, bytes: &[u8]) -> mrp_core::Result<CsvTable> {
+        let (encoding, bytes) = match Encoding::for_bom(bytes) {
+            Some((encoding, bom_length)) => (encoding, &bytes[bom_length..]),
+            None => (self.detect_encoding(bytes)?, bytes),
+        };
+        if !encoding.is_ascii_compatible() {
+            return Err(MrpError::ImportError(format!(
+                "不支援的編碼: {}",
+                encoding.name()
+            )));
+        }
+
+        let mut lines = Vec::new();
+        for (index, raw) in Self::split_lines(bytes).enumerate() {
+            let text = Self::decode_line(encoding, raw, self.delimiter, index + 1)?;
+            lines.push((index + 1, text));
+        }
+
+        let mut lines = lines
+            .into_iter()
+            .filter(|(_, text)| !text.trim().is_empty());
+        let headers = match lines.next() {
+            Some((line, text)) => self
+                .split_fields(&text, line)?
+                .into_iter()
+                .map(|h| h.trim().to_string())
+                .collect(),
+            None => Vec::new(),
+        };
+        let rows = lines
+            .map(|(line, text)| {
+                Ok(CsvRow {
+                    line,
+                    fields: self.split_fields(&text, line)?,
+                })
+            })
+            .collect::<mrp_core::Result<Vec<_>>>()?;
+
+        Ok(CsvTable {
+            encoding: encoding.name(),
+            headers,
+            rows,
+        })
+    }
+
+    /// 匯入需求（欄位：component_id, quantity, required_date，選填 demand_type, source_ref）
+    pub fn import_demands(&self, bytes: &[u8]) -> mrp_core::Result<Vec<Demand>> {
+        let table = self.read(bytes)?;
+        let component = table.required_column("component_id")?;
+        let quantity = table.required_column("quantity")?;
+        let date = table.required_column("required_date")?;
+        let demand_type = table.column("demand_type");
+        let source_ref = table.column("source_ref");
+
+        table
+            .rows
+            .iter()
+            .map(|row| {
+                let kind = match demand_type {
+                    Some(column) if table.optional(row, Some(column)).is_some() => {
+                        table.parse(row, column, |v| match v {
+                            "SalesOrder" => Some(DemandType::SalesOrder),
+                            "Forecast" => Some(DemandType::Forecast),
+                            "SafetyStock" => Some(DemandType::SafetyStock),
+                            _ => None,
+                        })?
+                    }
+                    _ => DemandType::SalesOrder,
+                };
+                let mut demand = Demand::new(
+                    table.parse(row, component, |v| (!v.is_empty()).then(|| v.to_string()))?,
+                    table.parse(row, quantity, |v| self.locale.parse_decimal(v))?,
+                    table.parse(row, date, |v| self.locale.parse_date(v))?,
+                    kind,
+                );
+                if let Some(reference) = table.optional(row, source_ref) {
+                    demand = demand.with_source_ref(reference.to_string());
+                }
+                Ok(demand)
+            })
+            .collect()
+    }
+
+    /// 匯入供應（欄位：component_id, quantity, available_date，選填 supply_type, source_ref）
+    pub fn import_supplies(&self, bytes: &[u8]) -> mrp_core::Result<Vec<Supply>> {
+        let table = self.read(bytes)?;
+        let component = table.required_column("component_id")?;
+        let quantity = table.required_column("quantity")?;
+        let date = table.required_column("available_date")?;
+        let supply_type = table.column("supply_type");
+        let source_ref = table.column("source_ref");
+
+        table
+            .rows
+            .iter()
+            .map(|row| {
+                let kind = match supply_type {
+                    Some(column) if table.optional(row, Some(column)).is_some() => {
+                        table.parse(row, column, |v| match v {
+                            "OnHand" => Some(SupplyType::OnHand),
+                            "PurchaseOrder" => Some(SupplyType::PurchaseOrder),
+                            "WorkOrder" => Some(SupplyType::WorkOrder),
+                            "Transfer" => Some(SupplyType::Transfer),
+                            _ => None,
+                        })?
+                    }
+                    _ => SupplyType::PurchaseOrder,
+                };
+                let mut supply = Supply::new(
+                    table.parse(row, component, |v| (!v.is_empty()).then(|| v.to_string()))?,
+                    table.parse(row, quantity, |v| self.locale.parse_decimal(v))?,
+                    table.parse(row, date, |v| self.locale.parse_date(v))?,
+                    kind,
+                );
+                if let Some(reference) = table.optional(row, source_ref) {
+                    supply = supply.with_source_ref(reference.to_string());
+                }
+                Ok(supply)
+            })
+            .collect()
+    }
+
+    /// 匯入資源清單（欄位：component_id, work_center_id, hours_per_unit，選填 setup_hours, offset_days）
+    ///
+    /// 每列一筆資源負荷，同一物料的列合併為一份資源清單（依首次出現順序）。
+    pub fn import_resource_bills(&self, bytes: &[u8]) -> mrp_core::Result<Vec<ResourceBill>> {
+        let table = self.read(bytes)?;
+        let component = table.required_column("component_id")?;
+        let work_center = table.required_column("work_center_id")?;
+        let hours = table.required_column("hours_per_unit")?;
+        let setup_hours = table.column("setup_hours");
+        let offset_days = table.column("offset_days");
+
+        let mut bills: Vec<ResourceBill> = Vec::new();
+        for row in &table.rows {
+            let component_id: String =
+                table.parse(row, component, |v| (!v.is_empty()).then(|| v.to_string()))?;
+            let mut load = ResourceLoad::new(
+                table.parse(row, work_center, |v| (!v.is_empty()).then(|| v.to_string()))?,
+                table.parse(row, hours, |v| self.locale.parse_decimal(v))?,
+            );
+            if let Some(column) = setup_hours.filter(|&c| table.optional(row, Some(c)).is_some()) {
+                load = load
+                    .with_setup_hours(table.parse(row, column, |v| self.locale.parse_decimal(v))?);
+            }
+            if let Some(column) = offset_days.filter(|&c| table.optional(row, Some(c)).is_some()) {
+                load = load.with_offset_days(table.parse(row, column, |v| v.parse().ok())?);
+            }
+            match bills.iter_mut().find(|b| b.component_id == component_id) {
+                Some(bill) => bill.loads.push(load),
+                None => bills.push(ResourceBill::new(component_id).with_resource_load(load)),
+            }
+        }
+        Ok(bills)
+    }
+
+    /// 決定編碼：指定編碼優先，否則 UTF-8 有效即採用，再依序嘗試備選編碼
+    fn detect_encoding(&self, bytes: &[u8]) -> mrp_core::Result<&'static Encoding> {
+        if let Some(label) = &self.encoding {
+            return Self::lookup(label);
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return Ok(UTF_8);
+        }
+        for label in &self.fallback_encodings {
+            let encoding = Self::lookup(label)?;
+            if encoding
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .is_some()
+            {
+                return Ok(encoding);
+            }
+        }
+
+        // 都無法解碼：以 UTF-8 指出第一個無效位置
+        for (index, raw) in Self::split_lines(bytes).enumerate() {
+            Self::decode_line(UTF_8, raw, self.delimiter, index + 1)
+                .map_err(|e| MrpError::ImportError(format!("無法自動判斷編碼（{}）", e)))?;
+        }
+        Ok(UTF_8)
+    }
+
+    fn lookup(label: &str) -> mrp_core::Result<&'static Encoding> {
+        Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| MrpError::ImportError(format!("不支援的編碼: {}", label)))
+    }
+
+    fn split_lines(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+        bytes
+            .split(|&b| b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+    }
+
+    /// 解碼單行，失敗時找出無法解碼的欄位
+    fn decode_line(
+        encoding: &'static Encoding,
+        raw: &[u8],
+        delimiter: u8,
+        line: usize,
+    ) -> mrp_core::Result<String> {
+        if let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(raw) {
+            return Ok(text.into_owned());
+        }
+        let column = raw
+            .split(|&b| b == delimiter)
+            .position(|field| {
+                encoding
+                    .decode_without_bom_handling_and_without_replacement(field)
+                    .is_none()
+            })
+            .map(|c| c + 1)
+            .unwrap_or(1);
+        Err(MrpError::ImportError(format!(
+            "第 {} 行第 {} 欄無法以 {} 解碼",
+            line,
+            column,
+            encoding.name()
+        )))
+    }
+
+    /// 拆分欄位（支援雙引號與 "" 跳脫）
+    fn split_fields(&self, text: &str, line: usize) -> mrp_core::Result<Vec<String>> {
+        let delimiter = self.delimiter as char;
+        let mut fields = Vec::new();
+        let mut current = String::new();
+        let mut in_quotes = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if in_quotes && chars.peek() == Some(&'"') => {
+                    current.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = !in_quotes,
+                c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut current)),
+                c => current.push(c),
+            }
+        }
+        if in_quotes {
+            return Err(MrpError::ImportError(format!(
+                "第 {} 行第 {} 欄引號未閉合",
+                line,
+                fields.len() + 1
+            )));
+        }
+        fields.push(current);
+        Ok(fields)
+    }
+}
+
+impl Default for CsvImporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::DateFormat;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_import_big5_demands() {
+        let csv = "component_id,quantity,required_date,source_ref\r\n\
+                   螺絲-M3,120,2025-11-10,\"SO-1,客戶甲\"\r\n";
+        let (bytes, _, _) = encoding_rs::BIG5.encode(csv);
+
+        let importer = CsvImporter::new();
+        let table = importer.read(&bytes).unwrap();
+        assert_eq!(table.encoding, "Big5");
+
+        let demands = importer.import_demands(&bytes).unwrap();
+        assert_eq!(demands.len(), 1);
+        assert_eq!(demands[0].component_id, "螺絲-M3");
+        assert_eq!(demands[0].quantity, Decimal::from(120));
+        assert_eq!(demands[0].source_ref.as_deref(), Some("SO-1,客戶甲"));
+    }
+
+    #[test]
+    fn test_errors_point_at_row_and_column() {
+        let importer = CsvImporter::new().with_encoding("UTF-8");
+        let mut bytes = b"component_id,quantity,available_date\nBOLT,10,2025-11-10\nBOLT,".to_vec();
+        bytes.extend_from_slice(&[0xB8, 0x4A]);
+        bytes.extend_from_slice(b",2025-11-11\n");
+
+        let err = importer.import_supplies(&bytes).unwrap_err().to_string();
+        assert!(err.contains("第 3 行第 2 欄"), "{}", err);
+
+        let bad_date = b"component_id,quantity,available_date\nBOLT,10,2025/11/10\n";
+        let err = importer.import_supplies(bad_date).unwrap_err().to_string();
+        assert!(err.contains("第 2 行第 3 欄（available_date）"), "{}", err);
+    }
+
+    #[test]
+    fn test_import_with_locale() {
+        let csv = "component_id;quantity;required_date\nBOLT;1.250,5;10/11/2025\nNUT;8;45971\n";
+        let importer = CsvImporter::new().with_delimiter(b';').with_locale(
+            ImportLocale::european().with_date_formats(vec![
+                DateFormat::pattern("%d/%m/%Y"),
+                DateFormat::ExcelSerial,
+            ]),
+        );
+
+        let demands = importer.import_demands(csv.as_bytes()).unwrap();
+        assert_eq!(demands[0].quantity, Decimal::new(12505, 1));
+        assert_eq!(demands[0].required_date, demands[1].required_date);
+    }
+
+    #[test]
+    fn test_import_resource_bills() {
+        let csv = "component_id,work_center_id,hours_per_unit,setup_hours,offset_days\n\
+                   BIKE,ASSEMBLY,1.5,,\n\
+                   FRAME,WELD,0.5,2,\n\
+                   BIKE,PAINT,0.25,1,3\n";
+
+        let bills = CsvImporter::new()
+            .import_resource_bills(csv.as_bytes())
+            .unwrap();
+
+        assert_eq!(bills.len(), 2);
+        assert_eq!(bills[0].component_id, "BIKE");
+        assert_eq!(bills[0].loads.len(), 2);
+        assert_eq!(bills[0].loads[0].setup_hours, Decimal::ZERO);
+        assert_eq!(bills[0].loads[1].offset_days, 3);
+        assert_eq!(
+            bills[0].total_hours(Decimal::from(10)),
+            Decimal::new(185, 1)
+        );
+        assert_eq!(bills[1].loads[0].setup_hours, Decimal::from(2));
+    }
+}
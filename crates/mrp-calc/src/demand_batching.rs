@@ -0,0 +1,129 @@
+//! 相依需求批次彙總：按（子件, 日期）合併 BOM 展開產生的相依需求
+//!
+//! 深層 BOM 搭配大量計劃訂單時，逐單展開會產生大量細小的 `Demand`。
+//! 啟用批次彙總後，同一子件同一日期只保留一筆相依需求，
+//! 來源父件訂單改記錄在精簡的來源對照表中。
+
+use chrono::NaiveDate;
+use mrp_core::Demand;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 相依需求的來源父件訂單
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DependentDemandSource {
+    /// 父件物料ID
+    pub parent_id: String,
+
+    /// 父件計劃訂單ID（來源單據無法解析時為 None）
+    pub parent_order_id: Option<Uuid>,
+
+    /// 該父件訂單貢獻的數量
+    pub quantity: Decimal,
+}
+
+impl DependentDemandSource {
+    /// 從相依需求的來源單據（格式 `父件ID:計劃訂單ID`）解析
+    fn from_demand(demand: &Demand) -> Self {
+        let source_ref = demand.source_ref.as_deref().unwrap_or_default();
+        let (parent_id, parent_order_id) = match source_ref.rsplit_once(':') {
+            Some((parent_id, order_id)) => (parent_id, Uuid::parse_str(order_id).ok()),
+            None => (source_ref, None),
+        };
+
+        Self {
+            parent_id: parent_id.to_string(),
+            parent_order_id,
+            quantity: demand.quantity,
+        }
+    }
+}
+
+/// 相依需求批次彙總器
+#[derive(Debug, Default)]
+pub(crate) struct DependentDemandBatcher {
+    /// (子件, 日期) → 該子件相依需求列表中的位置
+    index: HashMap<(String, NaiveDate), usize>,
+
+    /// 彙總後相依需求ID → 來源父件訂單
+    sources: HashMap<Uuid, Vec<DependentDemandSource>>,
+}
+
+impl DependentDemandBatcher {
+    /// 將新展開的相依需求併入子件的需求列表
+    ///
+    /// 已有相同日期的需求時累加數量；合併後的需求保留第一張父件訂單的 `source_ref`
+    /// （供追溯與齊套檢查沿用），完整來源由 `sources` 對照表提供。
+    pub fn merge(&mut self, child_id: &str, existing: &mut Vec<Demand>, incoming: Vec<Demand>) {
+        for demand in incoming {
+            let source = DependentDemandSource::from_demand(&demand);
+            let key = (child_id.to_string(), demand.required_date);
+
+            match self.index.get(&key) {
+                Some(&pos) => {
+                    let batched = &mut existing[pos];
+                    batched.quantity += demand.quantity;
+                    self.sources.entry(batched.id).or_default().push(source);
+                }
+                None => {
+                    self.index.insert(key, existing.len());
+                    self.sources.insert(demand.id, vec![source]);
+                    existing.push(demand);
+                }
+            }
+        }
+    }
+
+    /// 取出來源對照表
+    pub fn into_sources(self) -> HashMap<Uuid, Vec<DependentDemandSource>> {
+        self.sources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::DemandType;
+
+    fn dependent(parent: &str, qty: i64, day: u32) -> Demand {
+        Demand::new(
+            "CHILD-001".to_string(),
+            Decimal::from(qty),
+            NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+            DemandType::Dependent,
+        )
+        .with_source_ref(format!("{}:{}", parent, Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_merge_by_child_and_date() {
+        let mut batcher = DependentDemandBatcher::default();
+        let mut demands = Vec::new();
+
+        batcher.merge(
+            "CHILD-001",
+            &mut demands,
+            vec![dependent("PARENT-A", 10, 3), dependent("PARENT-A", 20, 4)],
+        );
+        batcher.merge("CHILD-001", &mut demands, vec![dependent("PARENT-B", 5, 3)]);
+
+        assert_eq!(demands.len(), 2);
+        assert_eq!(demands[0].quantity, Decimal::from(15));
+        // 合併後保留第一張父件訂單的來源單據
+        assert!(demands[0]
+            .source_ref
+            .as_deref()
+            .is_some_and(|r| r.starts_with("PARENT-A:")));
+        assert!(demands[1].source_ref.is_some());
+
+        let sources = batcher.into_sources();
+        let first = &sources[&demands[0].id];
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].parent_id, "PARENT-A");
+        assert_eq!(first[1].parent_id, "PARENT-B");
+        assert!(first[1].parent_order_id.is_some());
+        assert_eq!(first[1].quantity, Decimal::from(5));
+    }
+}
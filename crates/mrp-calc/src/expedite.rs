@@ -0,0 +1,154 @@
+//! 催貨建議：找出提前到貨即可消除客戶需求延遲的現有供應
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::coverage::{CoverageSourceType, DemandCoverage};
+
+/// 催貨候選
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ExpediteCandidate {
+    /// 供應ID
+    pub supply_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 目前可用日期
+    pub available_date: NaiveDate,
+
+    /// 須到貨日期（所覆蓋的延遲需求中最早的需求日期）
+    pub needed_by: NaiveDate,
+
+    /// 延遲覆蓋的需求數量（提前到貨即可準時滿足）
+    pub late_demand_qty: Decimal,
+
+    /// 受影響的需求ID
+    pub demand_ids: Vec<Uuid>,
+}
+
+impl ExpediteCandidate {
+    /// 須提前的天數
+    pub fn days_to_expedite(&self) -> i64 {
+        (self.available_date - self.needed_by).num_days().max(0)
+    }
+}
+
+/// 催貨分析器
+pub struct ExpediteAnalyzer;
+
+impl ExpediteAnalyzer {
+    /// 依需求覆蓋明細產生催貨候選清單
+    ///
+    /// 只考慮晚於需求日期到位的現有供應（計劃訂單尚未下達，不需催貨）。
+    /// 按延遲需求數量由大到小排序，數量相同時須提前天數少者優先。
+    pub fn candidates(coverage: &[DemandCoverage]) -> Vec<ExpediteCandidate> {
+        let mut by_supply: HashMap<Uuid, ExpediteCandidate> = HashMap::new();
+
+        for demand in coverage {
+            for allocation in &demand.allocations {
+                if !matches!(
+                    allocation.source_type,
+                    CoverageSourceType::FirmSupply | CoverageSourceType::OpenSupply
+                ) || !allocation.is_late(demand.required_date)
+                {
+                    continue;
+                }
+                let (Some(supply_id), Some(available_date)) =
+                    (allocation.source_id, allocation.available_date)
+                else {
+                    continue;
+                };
+
+                let candidate = by_supply
+                    .entry(supply_id)
+                    .or_insert_with(|| ExpediteCandidate {
+                        supply_id,
+                        component_id: demand.component_id.clone(),
+                        available_date,
+                        needed_by: demand.required_date,
+                        late_demand_qty: Decimal::ZERO,
+                        demand_ids: Vec::new(),
+                    });
+                candidate.needed_by = candidate.needed_by.min(demand.required_date);
+                candidate.late_demand_qty += allocation.quantity;
+                if !candidate.demand_ids.contains(&demand.demand_id) {
+                    candidate.demand_ids.push(demand.demand_id);
+                }
+            }
+        }
+
+        let mut candidates: Vec<ExpediteCandidate> = by_supply.into_values().collect();
+        candidates.sort_by(|a, b| {
+            b.late_demand_qty
+                .cmp(&a.late_demand_qty)
+                .then(a.days_to_expedite().cmp(&b.days_to_expedite()))
+                .then(a.needed_by.cmp(&b.needed_by))
+        });
+
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::CoverageCalculator;
+    use mrp_core::{Demand, DemandType, Supply, SupplyType};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_late_supply_candidates() {
+        let demands = vec![
+            Demand::new(
+                "BIKE-001".to_string(),
+                Decimal::from(30),
+                date(5),
+                DemandType::SalesOrder,
+            ),
+            Demand::new(
+                "BIKE-001".to_string(),
+                Decimal::from(80),
+                date(8),
+                DemandType::SalesOrder,
+            ),
+        ];
+        let early_supply = Supply::new(
+            "BIKE-001".to_string(),
+            Decimal::from(30),
+            date(7),
+            SupplyType::WorkOrder,
+        );
+        let late_supply = Supply::new(
+            "BIKE-001".to_string(),
+            Decimal::from(80),
+            date(12),
+            SupplyType::WorkOrder,
+        );
+
+        let coverage = CoverageCalculator::calculate(
+            &demands,
+            Decimal::ZERO,
+            &[early_supply.clone(), late_supply.clone()],
+            &[],
+        );
+
+        let candidates = ExpediteAnalyzer::candidates(&coverage);
+
+        // 11/7 的供應準時覆蓋 11/8 的需求，不列入；
+        // 11/12 的供應延遲覆蓋兩筆需求，須提前到 11/5
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].supply_id, late_supply.id);
+        assert_eq!(candidates[0].late_demand_qty, Decimal::from(80));
+        assert_eq!(candidates[0].needed_by, date(5));
+        assert_eq!(candidates[0].days_to_expedite(), 7);
+        assert_eq!(candidates[0].demand_ids.len(), 2);
+        assert_ne!(candidates[0].supply_id, early_supply.id);
+    }
+}
@@ -0,0 +1,79 @@
+//! 單元測試共用的測試資料：BOM 行、24/7 日曆的計算器、物料配置與需求
+
+use bom_core::{BomItem, ComponentId};
+use bom_graph::BomGraph;
+use chrono::NaiveDate;
+use mrp_core::{Demand, DemandType, MrpConfig, ProcurementType, WorkCalendar};
+use rust_decimal::Decimal;
+
+use crate::MrpCalculator;
+
+/// BOM 行（無損耗、無工序、無替代群組）
+pub(crate) fn bom_item(parent: &str, child: &str, quantity: i64) -> BomItem {
+    BomItem {
+        id: uuid::Uuid::new_v4(),
+        parent_id: ComponentId::new(parent),
+        child_id: ComponentId::new(child),
+        quantity: Decimal::from(quantity),
+        scrap_factor: Decimal::ZERO,
+        sequence: 10,
+        operation_sequence: None,
+        is_phantom: false,
+        effective_from: None,
+        effective_to: None,
+        alternative_group: None,
+        alternative_priority: None,
+        reference_designator: None,
+        position: None,
+        notes: None,
+        version: 1,
+    }
+}
+
+/// 由 BOM 行建立 BOM 圖
+pub(crate) fn bom_graph(items: impl IntoIterator<Item = BomItem>) -> BomGraph {
+    let mut bom_graph = BomGraph::new();
+    for item in items {
+        bom_graph.add_bom_item(item).unwrap();
+    }
+    bom_graph
+}
+
+/// 物料配置
+pub(crate) fn config(
+    id: &str,
+    lead_time_days: u32,
+    procurement_type: ProcurementType,
+) -> MrpConfig {
+    MrpConfig::new(id.to_string(), lead_time_days, procurement_type)
+}
+
+/// 以 24/7 日曆建立計算器
+pub(crate) fn calculator(
+    bom_graph: BomGraph,
+    configs: impl IntoIterator<Item = MrpConfig>,
+) -> MrpCalculator {
+    MrpCalculator::new(
+        bom_graph,
+        configs
+            .into_iter()
+            .map(|config| (config.component_id.clone(), config))
+            .collect(),
+        WorkCalendar::new_24_7("24/7".to_string()),
+    )
+}
+
+/// 2025 年 11 月的日期
+pub(crate) fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+}
+
+/// 2025 年 11 月 `day` 日到期的銷售訂單
+pub(crate) fn demand(component_id: &str, quantity: i64, day: u32) -> Demand {
+    Demand::new(
+        component_id.to_string(),
+        Decimal::from(quantity),
+        date(day),
+        DemandType::SalesOrder,
+    )
+}
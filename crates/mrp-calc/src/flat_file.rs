@@ -0,0 +1,377 @@
+//! 固定欄寬平面檔匯出（舊 ERP 匯入格式）
+//!
+//! 欄位位置與寬度以目標編碼的位元組計算（Big5 中文字佔 2 位元組），
+//! 截斷時不會切開多位元組字元。
+
+use chrono::NaiveDate;
+use encoding_rs::Encoding;
+use mrp_core::{PlannedOrder, PlannedOrderType};
+use rust_decimal::Decimal;
+
+/// 欄位對齊方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FieldAlignment {
+    /// 靠左（文字）
+    Left,
+    /// 靠右（數字）
+    Right,
+}
+
+/// 平面檔欄位定義
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FlatField {
+    /// 欄位名稱（對應記錄的欄位）
+    pub name: String,
+
+    /// 起始位置（從 1 起算的位元組位置）
+    pub start: usize,
+
+    /// 欄寬（位元組）
+    pub width: usize,
+
+    /// 對齊方式
+    pub alignment: FieldAlignment,
+
+    /// 填充字元（單位元組字元，如空白或 0）
+    pub pad: char,
+
+    /// 數值小數位數（None 表示照原值輸出）
+    pub decimal_places: Option<u32>,
+}
+
+impl FlatField {
+    /// 創建靠左、以空白填充的欄位
+    pub fn new(name: &str, start: usize, width: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            start,
+            width,
+            alignment: FieldAlignment::Left,
+            pad: ' ',
+            decimal_places: None,
+        }
+    }
+
+    /// 建構器模式：靠右對齊
+    pub fn right_aligned(mut self) -> Self {
+        self.alignment = FieldAlignment::Right;
+        self
+    }
+
+    /// 建構器模式：設置填充字元（如數字欄位補 0）
+    pub fn with_pad(mut self, pad: char) -> Self {
+        self.pad = pad;
+        self
+    }
+
+    /// 建構器模式：設置數值小數位數
+    pub fn with_decimal_places(mut self, decimal_places: u32) -> Self {
+        self.decimal_places = Some(decimal_places);
+        self
+    }
+}
+
+/// 平面檔版面
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FlatFileLayout {
+    /// 欄位定義
+    pub fields: Vec<FlatField>,
+
+    /// 編碼標籤（如 "UTF-8"、"Big5"、"Shift_JIS"）
+    pub encoding: String,
+
+    /// 日期格式（chrono 格式字串）
+    pub date_format: String,
+
+    /// 換行符號
+    pub line_ending: String,
+}
+
+impl FlatFileLayout {
+    /// 創建空版面（UTF-8、日期 YYYYMMDD、CRLF 換行）
+    pub fn new() -> Self {
+        Self {
+            fields: Vec::new(),
+            encoding: "UTF-8".to_string(),
+            date_format: "%Y%m%d".to_string(),
+            line_ending: "\r\n".to_string(),
+        }
+    }
+
+    /// 建構器模式：添加欄位
+    pub fn with_field(mut self, field: FlatField) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// 建構器模式：設置編碼
+    pub fn with_encoding(mut self, encoding: &str) -> Self {
+        self.encoding = encoding.to_string();
+        self
+    }
+
+    /// 建構器模式：設置日期格式
+    pub fn with_date_format(mut self, date_format: &str) -> Self {
+        self.date_format = date_format.to_string();
+        self
+    }
+
+    /// 建構器模式：設置換行符號
+    pub fn with_line_ending(mut self, line_ending: &str) -> Self {
+        self.line_ending = line_ending.to_string();
+        self
+    }
+
+    /// 每行長度（位元組）
+    pub fn record_length(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|f| f.start.saturating_sub(1) + f.width)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Default for FlatFileLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 平面檔欄位值
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum FlatValue {
+    /// 文字
+    Text(String),
+    /// 日期
+    Date(NaiveDate),
+    /// 數值
+    Number(Decimal),
+    /// 空值
+    Empty,
+}
+
+/// 可匯出為平面檔的記錄
+pub trait FlatFileRecord {
+    /// 依欄位名稱取值（未知欄位回傳 None）
+    fn field_value(&self, name: &str) -> Option<FlatValue>;
+}
+
+impl FlatFileRecord for PlannedOrder {
+    fn field_value(&self, name: &str) -> Option<FlatValue> {
+        let optional_text = |value: &Option<String>| {
+            value
+                .clone()
+                .map(FlatValue::Text)
+                .unwrap_or(FlatValue::Empty)
+        };
+        Some(match name {
+            "id" => FlatValue::Text(self.id.to_string()),
+            "component_id" => FlatValue::Text(self.component_id.clone()),
+            "quantity" => FlatValue::Number(self.quantity),
+            "required_date" => FlatValue::Date(self.required_date),
+            "order_date" => FlatValue::Date(self.order_date),
+            "order_type" => FlatValue::Text(
+                match self.order_type {
+                    PlannedOrderType::Purchase => "P",
+                    PlannedOrderType::Production => "M",
+                    PlannedOrderType::Transfer => "T",
+                    _ => "",
+                }
+                .to_string(),
+            ),
+            "source_id" => optional_text(&self.source_id),
+            "transport_mode" => optional_text(&self.transport_mode),
+            _ => return None,
+        })
+    }
+}
+
+/// 平面檔寫出器
+pub struct FlatFileWriter;
+
+impl FlatFileWriter {
+    /// 依版面將記錄寫成固定欄寬平面檔（已編碼的位元組）
+    ///
+    /// 版面欄位重疊、欄位名稱未知或字元無法以目標編碼表示時回傳錯誤。
+    pub fn write<R: FlatFileRecord>(
+        records: &[R],
+        layout: &FlatFileLayout,
+    ) -> mrp_core::Result<Vec<u8>> {
+        let encoding = Encoding::for_label(layout.encoding.as_bytes()).ok_or_else(|| {
+            mrp_core::MrpError::ExportError(format!("不支援的編碼: {}", layout.encoding))
+        })?;
+        Self::validate_layout(layout)?;
+
+        let record_length = layout.record_length();
+        let line_ending = Self::encode_all(encoding, &layout.line_ending)?;
+        let mut output = Vec::new();
+
+        for (row, record) in records.iter().enumerate() {
+            let mut line = vec![b' '; record_length];
+            for field in &layout.fields {
+                let value = record.field_value(&field.name).ok_or_else(|| {
+                    mrp_core::MrpError::ExportError(format!("未知的欄位: {}", field.name))
+                })?;
+                let text = Self::format_value(&value, field, layout);
+                let bytes = Self::encode_fitted(encoding, &text, field).map_err(|c| {
+                    mrp_core::MrpError::ExportError(format!(
+                        "第 {} 筆欄位 {} 的字元 '{}' 無法以 {} 編碼",
+                        row + 1,
+                        field.name,
+                        c,
+                        encoding.name()
+                    ))
+                })?;
+                let offset = field.start - 1;
+                line[offset..offset + field.width].copy_from_slice(&bytes);
+            }
+            output.extend_from_slice(&line);
+            output.extend_from_slice(&line_ending);
+        }
+
+        Ok(output)
+    }
+
+    /// 檢查欄位位置（從 1 起算、不重疊）
+    fn validate_layout(layout: &FlatFileLayout) -> mrp_core::Result<()> {
+        let mut ranges: Vec<(usize, usize, &str)> = layout
+            .fields
+            .iter()
+            .map(|f| (f.start, f.start + f.width, f.name.as_str()))
+            .collect();
+        ranges.sort();
+
+        if let Some((_, _, name)) = ranges.iter().find(|(start, _, _)| *start == 0) {
+            return Err(mrp_core::MrpError::ExportError(format!(
+                "欄位 {} 的起始位置必須從 1 起算",
+                name
+            )));
+        }
+        for pair in ranges.windows(2) {
+            if pair[1].0 < pair[0].1 {
+                return Err(mrp_core::MrpError::ExportError(format!(
+                    "欄位 {} 與 {} 位置重疊",
+                    pair[0].2, pair[1].2
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// 格式化欄位值
+    fn format_value(value: &FlatValue, field: &FlatField, layout: &FlatFileLayout) -> String {
+        match value {
+            FlatValue::Text(text) => text.clone(),
+            FlatValue::Date(date) => date.format(&layout.date_format).to_string(),
+            FlatValue::Number(number) => match field.decimal_places {
+                Some(places) => format!("{:.*}", places as usize, number.round_dp(places)),
+                None => number.normalize().to_string(),
+            },
+            FlatValue::Empty => String::new(),
+        }
+    }
+
+    /// 編碼並截斷／填充到欄寬（無法編碼時回傳該字元）
+    fn encode_fitted(
+        encoding: &'static Encoding,
+        text: &str,
+        field: &FlatField,
+    ) -> std::result::Result<Vec<u8>, char> {
+        let mut bytes = Vec::with_capacity(field.width);
+        for c in text.chars() {
+            let encoded = Self::encode_char(encoding, c)?;
+            if bytes.len() + encoded.len() > field.width {
+                break;
+            }
+            bytes.extend_from_slice(&encoded);
+        }
+
+        let pad = Self::encode_char(encoding, field.pad)?;
+        let pad_byte = pad.first().copied().unwrap_or(b' ');
+        let padding = vec![pad_byte; field.width - bytes.len()];
+        Ok(match field.alignment {
+            FieldAlignment::Left => [bytes, padding].concat(),
+            FieldAlignment::Right => [padding, bytes].concat(),
+        })
+    }
+
+    fn encode_char(encoding: &'static Encoding, c: char) -> std::result::Result<Vec<u8>, char> {
+        let mut buffer = [0u8; 4];
+        let (encoded, _, had_errors) = encoding.encode(c.encode_utf8(&mut buffer));
+        if had_errors {
+            Err(c)
+        } else {
+            Ok(encoded.into_owned())
+        }
+    }
+
+    fn encode_all(encoding: &'static Encoding, text: &str) -> mrp_core::Result<Vec<u8>> {
+        let (encoded, _, had_errors) = encoding.encode(text);
+        if had_errors {
+            return Err(mrp_core::MrpError::ExportError(format!(
+                "無法以 {} 編碼: {:?}",
+                encoding.name(),
+                text
+            )));
+        }
+        Ok(encoded.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order() -> PlannedOrder {
+        PlannedOrder::new(
+            "螺絲-M3".to_string(),
+            Decimal::new(1205, 1),
+            NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 11, 3).unwrap(),
+            PlannedOrderType::Purchase,
+        )
+    }
+
+    #[test]
+    fn test_big5_fixed_width() {
+        let layout = FlatFileLayout::new()
+            .with_encoding("Big5")
+            .with_field(FlatField::new("order_type", 1, 1))
+            .with_field(FlatField::new("component_id", 2, 10))
+            .with_field(
+                FlatField::new("quantity", 12, 8)
+                    .right_aligned()
+                    .with_pad('0')
+                    .with_decimal_places(2),
+            )
+            .with_field(FlatField::new("required_date", 20, 8));
+
+        let bytes = FlatFileWriter::write(&[order()], &layout).unwrap();
+
+        // 每行 27 位元組 + CRLF；「螺絲」在 Big5 佔 4 位元組
+        assert_eq!(bytes.len(), 29);
+        let (decoded, _, _) = encoding_rs::BIG5.decode(&bytes);
+        assert_eq!(decoded, "P螺絲-M3   00120.5020251110\r\n");
+    }
+
+    #[test]
+    fn test_truncate_without_splitting_characters() {
+        let layout = FlatFileLayout::new()
+            .with_encoding("Big5")
+            .with_field(FlatField::new("component_id", 1, 3));
+
+        let bytes = FlatFileWriter::write(&[order()], &layout).unwrap();
+
+        // 3 位元組只容得下「螺」，剩餘 1 位元組補空白
+        let (decoded, _, _) = encoding_rs::BIG5.decode(&bytes);
+        assert_eq!(decoded, "螺 \r\n");
+
+        let overlapping = layout.with_field(FlatField::new("quantity", 2, 5));
+        assert!(FlatFileWriter::write(&[order()], &overlapping).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! 跨計算的 KPI 時間序列：每次發布計劃快照時記錄關鍵指標，輸出 CSV／JSON 供 BI 儀表板使用
+
+use chrono::{NaiveDate, NaiveDateTime};
+use mrp_core::MrpError;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{MrpCalculator, PlanSnapshot, WarningSeverity};
+
+/// 單次計算的 KPI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RunKpi {
+    /// 計算（發布）時間
+    pub run_at: NaiveDateTime,
+
+    /// 計劃快照版本
+    pub plan_version: u64,
+
+    /// 計劃訂單數
+    pub planned_order_count: usize,
+
+    /// 計劃訂單金額（數量 × 單位成本；未設單位成本的物料不計）
+    pub planned_inventory_value: Decimal,
+
+    /// 未能準時滿足的輸入需求筆數
+    pub late_demand_count: usize,
+
+    /// 未能準時滿足的需求數量（未覆蓋 + 晚到）
+    pub late_demand_quantity: Decimal,
+
+    /// 警告數
+    pub warning_count: usize,
+
+    /// 錯誤數
+    pub error_count: usize,
+}
+
+impl RunKpi {
+    /// 從計劃快照計算 KPI（單位成本取自計算器的物料配置）
+    pub fn from_snapshot(
+        run_at: NaiveDateTime,
+        snapshot: &PlanSnapshot,
+        calculator: &MrpCalculator,
+    ) -> Self {
+        let late: Vec<Decimal> = snapshot
+            .coverage
+            .iter()
+            .map(|c| c.at_risk_quantity())
+            .filter(|quantity| *quantity > Decimal::ZERO)
+            .collect();
+        let count = |severity: WarningSeverity| {
+            snapshot
+                .warnings
+                .iter()
+                .filter(|w| w.severity == severity)
+                .count()
+        };
+        Self {
+            run_at,
+            plan_version: snapshot.version(),
+            planned_order_count: snapshot.planned_orders.len(),
+            planned_inventory_value: calculator.planned_order_value(&snapshot.planned_orders),
+            late_demand_count: late.len(),
+            late_demand_quantity: late.iter().sum(),
+            warning_count: count(WarningSeverity::Warning),
+            error_count: count(WarningSeverity::Error),
+        }
+    }
+}
+
+/// KPI 歷史（按計算時間排序）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KpiHistory {
+    runs: Vec<RunKpi>,
+}
+
+impl KpiHistory {
+    /// CSV 標題列
+    pub const CSV_HEADER: &'static str = "run_at,plan_version,planned_order_count,\
+        planned_inventory_value,late_demand_count,late_demand_quantity,warning_count,error_count";
+
+    /// 創建空的歷史
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 記錄一次計算
+    pub fn record(&mut self, kpi: RunKpi) {
+        let index = self.runs.partition_point(|run| run.run_at <= kpi.run_at);
+        self.runs.insert(index, kpi);
+    }
+
+    /// 所有計算的 KPI
+    pub fn runs(&self) -> &[RunKpi] {
+        &self.runs
+    }
+
+    /// 指定期間（含起訖日）的計算
+    pub fn between(&self, from: NaiveDate, to: NaiveDate) -> KpiHistory {
+        KpiHistory {
+            runs: self
+                .runs
+                .iter()
+                .filter(|run| (from..=to).contains(&run.run_at.date()))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// 每日只保留當天最後一次計算（一天多次重算時，儀表板以日為單位呈現）
+    pub fn daily(&self) -> KpiHistory {
+        let mut runs: Vec<RunKpi> = Vec::new();
+        for run in &self.runs {
+            match runs.last_mut() {
+                Some(last) if last.run_at.date() == run.run_at.date() => *last = run.clone(),
+                _ => runs.push(run.clone()),
+            }
+        }
+        KpiHistory { runs }
+    }
+
+    /// 輸出 CSV（含標題列）
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(Self::CSV_HEADER);
+        csv.push('\n');
+        for run in &self.runs {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                run.run_at.format("%Y-%m-%dT%H:%M:%S"),
+                run.plan_version,
+                run.planned_order_count,
+                run.planned_inventory_value,
+                run.late_demand_count,
+                run.late_demand_quantity,
+                run.warning_count,
+                run.error_count
+            ));
+        }
+        csv
+    }
+
+    /// 輸出 JSON 陣列（每次計算一個物件）
+    pub fn to_json(&self) -> mrp_core::Result<String> {
+        serde_json::to_string(&self.runs)
+            .map_err(|e| MrpError::ExportError(format!("KPI 序列化失敗: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlanStore;
+    use bom_graph::BomGraph;
+    use mrp_core::{Demand, DemandType, MrpConfig, ProcurementType, WorkCalendar};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_kpi_series_across_runs() {
+        let configs = HashMap::from([(
+            "BOLT".to_string(),
+            MrpConfig::new("BOLT".to_string(), 2, ProcurementType::Buy)
+                .with_unit_cost(Decimal::TWO),
+        )]);
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+        let date = |day: u32| NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        let demand = |quantity: i64| {
+            vec![Demand::new(
+                "BOLT".to_string(),
+                Decimal::from(quantity),
+                date(20),
+                DemandType::SalesOrder,
+            )]
+        };
+
+        let store = PlanStore::new();
+        let mut history = KpiHistory::new();
+        for (day, hour, quantity) in [(3, 9, 30), (3, 17, 40), (4, 9, 50)] {
+            let mut result = calculator
+                .calculate(demand(quantity), vec![], vec![])
+                .unwrap();
+            if quantity == 50 {
+                result.coverage[0].uncovered_quantity = Decimal::from(5);
+            }
+            let snapshot = store.publish(result);
+            let run_at = date(day).and_hms_opt(hour, 0, 0).unwrap();
+            history.record(RunKpi::from_snapshot(run_at, &snapshot, &calculator));
+        }
+
+        let daily = history.daily();
+        assert_eq!(daily.runs().len(), 2);
+        assert_eq!(daily.runs()[0].plan_version, 2);
+        assert_eq!(daily.runs()[0].planned_inventory_value, Decimal::from(80));
+        assert_eq!(daily.runs()[1].late_demand_count, 1);
+        assert_eq!(daily.runs()[1].late_demand_quantity, Decimal::from(5));
+
+        let csv = daily.to_csv();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("2025-11-03T17:00:00,2,1,80,0,0,"));
+        assert_eq!(history.between(date(4), date(4)).runs().len(), 1);
+        assert!(history.to_json().unwrap().contains("\"plan_version\":3"));
+    }
+}
@@ -1,7 +1,8 @@
 //! 交期計算
 
 use chrono::NaiveDate;
-use mrp_core::WorkCalendar;
+use mrp_core::{MrpConfig, WorkCalendar};
+use rust_decimal::Decimal;
 
 /// 交期計算器
 pub struct LeadTimeCalculator;
@@ -25,6 +26,26 @@ impl LeadTimeCalculator {
         calendar.add_working_days(order_date, lead_time_days)
     }
 
+    /// 依訂單數量計算下單日期（固定 + 變動提前期）
+    pub fn calculate_order_date_for_quantity(
+        required_date: NaiveDate,
+        quantity: Decimal,
+        config: &MrpConfig,
+        calendar: &WorkCalendar,
+    ) -> NaiveDate {
+        Self::calculate_order_date(required_date, config.lead_time_for(quantity), calendar)
+    }
+
+    /// 依訂單數量計算到貨日期（固定 + 變動提前期）
+    pub fn calculate_delivery_date_for_quantity(
+        order_date: NaiveDate,
+        quantity: Decimal,
+        config: &MrpConfig,
+        calendar: &WorkCalendar,
+    ) -> NaiveDate {
+        Self::calculate_delivery_date(order_date, config.lead_time_for(quantity), calendar)
+    }
+
     /// 計算兩個日期之間的工作日數
     pub fn working_days_between(
         start: NaiveDate,
@@ -2,19 +2,116 @@
 //!
 //! 核心 MRP 計算引擎
 
+pub mod allocation;
+pub mod archive;
+pub mod atp;
+pub mod bom_diagram;
+pub mod bom_provider;
+pub mod bom_validation;
 pub mod bucketing;
 pub mod calculator;
+pub mod call_off;
+pub mod capacity_constrained;
+pub mod carryover;
+pub mod commitment;
+pub mod consolidation;
+pub mod coverage;
+pub mod csv_import;
+pub mod demand_batching;
+pub mod expedite;
+pub mod flat_file;
+pub mod kpi;
 pub mod lead_time;
+pub mod lifecycle;
+pub mod locale;
 pub mod lot_sizing;
+pub mod manifest;
+pub mod matched_set;
+pub mod material_check;
+pub mod metrics;
+pub mod monte_carlo;
 pub mod netting;
+pub mod normalization;
+pub mod ordering_days;
+pub mod past_due;
 pub mod pegging;
+pub mod planning_fence;
+pub mod policy;
+pub mod preview;
+pub mod purchasing;
+pub mod rebalancing;
+pub mod receiving;
+pub mod reorder_point;
+pub mod safety_buffer;
+pub mod safety_stock_check;
+pub mod scenario;
+pub mod schedule_agreement;
+pub mod scheduling;
+pub mod shell;
+pub mod slow_movers;
+pub mod snapshot;
+pub mod substitution;
+pub mod supply_cleanup;
+pub mod supply_reschedule;
+pub mod trace;
+pub mod transport;
+pub mod tuning;
+
+#[cfg(test)]
+mod fixtures;
 
 // Re-export 主要類型
+pub use allocation::{AllocationStrategy, ComponentAllocation};
+pub use archive::{PlanArchive, RESULT_SCHEMA_VERSION};
+pub use atp::{AtpCalculator, AtpCheck, AtpPeriod};
+pub use bom_diagram::{BomDiagram, DiagramEdge, DiagramFormat, DiagramNode};
+pub use bom_provider::{BomProvider, CachedBomProvider};
+pub use bom_validation::{BomAnomaly, BomAnomalyKind};
 pub use calculator::MrpCalculator;
-pub use netting::NetRequirement;
+pub use capacity_constrained::{CapacityConstrainedResult, CapacityDiagnostic};
+pub use carryover::{CarryoverSummary, CarryoverTolerance};
+pub use commitment::CommitmentStatus;
+pub use consolidation::{ConsolidationOutcome, OrderConsolidator};
+pub use coverage::{CoverageSourceType, DemandCoverage, TierFillRate};
+pub use csv_import::{CsvImporter, CsvRow, CsvTable};
+pub use demand_batching::DependentDemandSource;
+pub use expedite::ExpediteCandidate;
+pub use flat_file::{
+    FieldAlignment, FlatField, FlatFileLayout, FlatFileRecord, FlatFileWriter, FlatValue,
+};
+pub use kpi::{KpiHistory, RunKpi};
+pub use lifecycle::PhaseOutReport;
+pub use locale::{DateFormat, ImportLocale};
+pub use manifest::RunManifest;
+pub use matched_set::{MatchedSet, MatchedSetReport};
+pub use material_check::{MaterialCheckMode, MaterialConstraint};
+pub use metrics::{ItemTiming, RunMetrics};
+pub use monte_carlo::MonteCarloSimulator;
+pub use netting::{NetRequirement, TimePhasedRecord};
+pub use normalization::{DateAdjustment, DateSnapPolicy};
+pub use ordering_days::OrderingDays;
+pub use past_due::{PastDueAdjustment, PastDuePolicy};
+pub use policy::{PlannedSupplyPolicy, PlanningPolicy};
+pub use preview::{PlanPreview, PreviewOptions};
+pub use purchasing::{PurchaseScheduleLine, SupplierWeekSchedule};
+pub use rebalancing::{RebalancingAnalyzer, TransferLane, TransferProposal};
+pub use receiving::ReceiptCalendars;
+pub use safety_buffer::{BufferEvaluation, BufferStrategy, ItemDemandProfile};
+pub use safety_stock_check::{SafetyStockCheck, SafetyStockChecker, SafetyStockStatus};
+pub use scenario::{ConfigSet, MasterDataOverride, Scenario, ScenarioResult};
+pub use schedule_agreement::{ScheduleHorizon, ScheduleZone, SupplierDeliverySchedule};
+pub use shell::PlanShell;
+pub use slow_movers::{SlowMover, SlowMoverKind};
+pub use snapshot::{PlanEdit, PlanSnapshot, PlanStore};
+pub use substitution::ComponentSubstitution;
+pub use supply_cleanup::{CleanupAction, SupplyCleanup};
+pub use supply_reschedule::{RescheduleDirection, RescheduleMessage};
+pub use trace::LevelTrace;
+pub use tuning::{ParameterCandidate, TuningAdvisor, TuningItem, TuningOutcome, TuningReport};
 
 /// MRP 計算結果
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct MrpResult {
     /// 計劃訂單
     pub planned_orders: Vec<mrp_core::PlannedOrder>,
@@ -22,11 +119,68 @@ pub struct MrpResult {
     /// 需求追溯
     pub pegging: std::collections::HashMap<uuid::Uuid, Vec<mrp_core::PeggingRecord>>,
 
+    /// 需求覆蓋明細（每筆輸入需求一筆）
+    pub coverage: Vec<DemandCoverage>,
+
+    /// 共用子件分配結果（啟用分配策略時）
+    pub allocations: Vec<ComponentAllocation>,
+
+    /// 相依需求ID → 來源父件訂單（啟用相依需求批次彙總時）
+    pub dependent_sources: std::collections::HashMap<uuid::Uuid, Vec<DependentDemandSource>>,
+
+    /// 輸入日期正規化的調整明細（啟用日期正規化時）
+    pub date_adjustments: Vec<DateAdjustment>,
+
+    /// 計劃員註記（計劃訂單ID → 註記）
+    pub annotations: std::collections::HashMap<uuid::Uuid, mrp_core::OrderAnnotation>,
+
+    /// 呆滯／慢動物料（啟用慢動分析時）
+    pub slow_movers: Vec<SlowMover>,
+
     /// 警告信息
     pub warnings: Vec<MrpWarning>,
 
     /// 計算耗時（毫秒）
     pub calculation_time_ms: Option<u128>,
+
+    /// 逐物料效能指標
+    pub metrics: RunMetrics,
+
+    /// 因超過時間預算而未計算的物料（非空時為部分結果）
+    pub unprocessed_components: Vec<String>,
+
+    /// 非 MRP 計劃物料（人工、再訂購點、消耗導向）的庫存預測（物料ID → 逐期預測）
+    pub projections: std::collections::HashMap<String, Vec<NetRequirement>>,
+
+    /// 含計劃訂單入庫的時間分段記錄（物料ID → 各異動日期一筆）
+    pub time_phased: std::collections::HashMap<String, Vec<TimePhasedRecord>>,
+
+    /// 替代料替換記錄（主料預計可用量不足時改用替代群組中的子件）
+    pub substitutions: Vec<ComponentSubstitution>,
+
+    /// 停產／已淘汰物料的剩餘庫存與耗盡日期
+    pub phase_outs: Vec<PhaseOutReport>,
+
+    /// 按客戶等級的需求滿足率（輸入需求）
+    pub tier_fill_rates: Vec<TierFillRate>,
+
+    /// 物料受限的生產計劃訂單（啟用齊套檢查時）
+    pub material_constraints: Vec<MaterialConstraint>,
+
+    /// 配套物料的計劃期末成套情況
+    pub matched_sets: Vec<MatchedSetReport>,
+
+    /// 已不被需求追溯的現有供應（建議取消或減量）
+    pub supply_cleanups: Vec<SupplyCleanup>,
+
+    /// 現有供應的重排建議（提前／延後）
+    pub reschedule_messages: Vec<RescheduleMessage>,
+
+    /// 跨倉調撥建議（設置調撥路線時）
+    pub transfers: Vec<TransferProposal>,
+
+    /// 執行清單（引擎版本、亂數種子）
+    pub manifest: RunManifest,
 }
 
 impl MrpResult {
@@ -35,19 +189,279 @@ impl MrpResult {
         Self {
             planned_orders: Vec::new(),
             pegging: std::collections::HashMap::new(),
+            coverage: Vec::new(),
+            allocations: Vec::new(),
+            dependent_sources: std::collections::HashMap::new(),
+            date_adjustments: Vec::new(),
+            annotations: std::collections::HashMap::new(),
+            slow_movers: Vec::new(),
             warnings: Vec::new(),
             calculation_time_ms: None,
+            metrics: RunMetrics::default(),
+            unprocessed_components: Vec::new(),
+            projections: std::collections::HashMap::new(),
+            time_phased: std::collections::HashMap::new(),
+            substitutions: Vec::new(),
+            phase_outs: Vec::new(),
+            tier_fill_rates: Vec::new(),
+            material_constraints: Vec::new(),
+            matched_sets: Vec::new(),
+            supply_cleanups: Vec::new(),
+            reschedule_messages: Vec::new(),
+            transfers: Vec::new(),
+            manifest: RunManifest::default(),
         }
     }
 
+    /// 是否為部分結果（超過時間預算，尚有物料未計算）
+    pub fn is_partial(&self) -> bool {
+        !self.unprocessed_components.is_empty()
+    }
+
     /// 添加警告
     pub fn add_warning(&mut self, warning: MrpWarning) {
         self.warnings.push(warning);
     }
+
+    /// 沿用上次計算的訂單識別
+    ///
+    /// 容差內視為未變更的計劃訂單改用上次的訂單ID，並同步更新追溯、覆蓋與分配中的引用；
+    /// 上次的計劃員註記隨沿用的訂單帶入
+    pub fn carry_over_from(
+        &mut self,
+        previous: &MrpResult,
+        tolerance: CarryoverTolerance,
+    ) -> CarryoverSummary {
+        let summary = carryover::OrderCarryover::match_orders(
+            &previous.planned_orders,
+            &self.planned_orders,
+            tolerance,
+        );
+        let remap = |id: &mut uuid::Uuid| {
+            if let Some(previous_id) = summary.carried_over.get(id) {
+                *id = *previous_id;
+            }
+        };
+
+        for order in &mut self.planned_orders {
+            remap(&mut order.id);
+        }
+        self.pegging = std::mem::take(&mut self.pegging)
+            .into_iter()
+            .map(|(mut id, records)| {
+                remap(&mut id);
+                (id, records)
+            })
+            .collect();
+        for allocation in self
+            .coverage
+            .iter_mut()
+            .flat_map(|c| c.allocations.iter_mut())
+        {
+            if allocation.source_type == CoverageSourceType::PlannedOrder {
+                if let Some(id) = allocation.source_id.as_mut() {
+                    remap(id);
+                }
+            }
+        }
+        for id in self
+            .allocations
+            .iter_mut()
+            .filter_map(|a| a.parent_order_id.as_mut())
+        {
+            remap(id);
+        }
+        // 延續訂單的註記隨訂單ID帶入本次結果
+        for previous_id in summary.carried_over.values() {
+            if let Some(annotation) = previous.annotations.get(previous_id) {
+                self.annotations
+                    .entry(*previous_id)
+                    .or_insert_with(|| annotation.clone());
+            }
+        }
+        for id in self
+            .dependent_sources
+            .values_mut()
+            .flatten()
+            .filter_map(|s| s.parent_order_id.as_mut())
+        {
+            remap(id);
+        }
+
+        summary
+    }
+
+    /// 以部分物料的重算結果取代本結果中這些物料的資料
+    ///
+    /// `partial` 為只含受影響物料輸入的計算結果；`components` 內物料的計劃訂單、追溯、
+    /// 覆蓋、分配、警告與各項報告改用 `partial`，其餘物料保留本結果。
+    /// 被取代訂單的註記一併移除；按客戶等級的滿足率需由呼叫端以完整需求重算
+    pub fn replace_components(
+        &mut self,
+        partial: MrpResult,
+        components: &std::collections::HashSet<String>,
+    ) {
+        let affected = |component_id: &str| components.contains(component_id);
+
+        let removed_orders: std::collections::HashSet<uuid::Uuid> = self
+            .planned_orders
+            .iter()
+            .filter(|o| affected(&o.component_id))
+            .map(|o| o.id)
+            .collect();
+        let removed_demands: std::collections::HashSet<uuid::Uuid> = removed_orders
+            .iter()
+            .filter_map(|id| self.pegging.get(id))
+            .flatten()
+            .map(|p| p.demand_id)
+            .collect();
+        self.planned_orders
+            .retain(|o| !removed_orders.contains(&o.id));
+        self.pegging.retain(|id, _| !removed_orders.contains(id));
+        self.annotations
+            .retain(|id, _| !removed_orders.contains(id));
+        self.dependent_sources
+            .retain(|id, _| !removed_demands.contains(id));
+
+        let added_orders: Vec<mrp_core::PlannedOrder> = partial
+            .planned_orders
+            .into_iter()
+            .filter(|o| affected(&o.component_id))
+            .collect();
+        let mut added_demands: std::collections::HashSet<uuid::Uuid> = partial
+            .allocations
+            .iter()
+            .filter(|a| affected(&a.component_id))
+            .map(|a| a.demand_id)
+            .collect();
+        let mut pegging = partial.pegging;
+        for order in &added_orders {
+            if let Some(records) = pegging.remove(&order.id) {
+                added_demands.extend(records.iter().map(|p| p.demand_id));
+                self.pegging.insert(order.id, records);
+            }
+        }
+        self.planned_orders.extend(added_orders);
+        self.dependent_sources.extend(
+            partial
+                .dependent_sources
+                .into_iter()
+                .filter(|(id, _)| added_demands.contains(id)),
+        );
+
+        replace_records(&mut self.coverage, partial.coverage, |r| {
+            affected(&r.component_id)
+        });
+        replace_records(&mut self.allocations, partial.allocations, |r| {
+            affected(&r.component_id)
+        });
+        replace_records(&mut self.date_adjustments, partial.date_adjustments, |r| {
+            affected(&r.component_id)
+        });
+        replace_records(&mut self.slow_movers, partial.slow_movers, |r| {
+            affected(&r.component_id)
+        });
+        replace_records(&mut self.warnings, partial.warnings, |r| {
+            affected(&r.component_id)
+        });
+        replace_records(&mut self.metrics.items, partial.metrics.items, |r| {
+            affected(&r.component_id)
+        });
+        replace_records(&mut self.substitutions, partial.substitutions, |r| {
+            affected(&r.parent_id)
+        });
+        replace_records(&mut self.phase_outs, partial.phase_outs, |r| {
+            affected(&r.component_id)
+        });
+        replace_records(
+            &mut self.material_constraints,
+            partial.material_constraints,
+            |r| affected(&r.component_id),
+        );
+        replace_records(&mut self.supply_cleanups, partial.supply_cleanups, |r| {
+            affected(&r.component_id)
+        });
+        replace_records(
+            &mut self.reschedule_messages,
+            partial.reschedule_messages,
+            |r| affected(&r.component_id),
+        );
+        replace_records(&mut self.transfers, partial.transfers, |r| {
+            affected(&r.component_id)
+        });
+
+        self.unprocessed_components.retain(|id| !affected(id));
+        self.unprocessed_components.extend(
+            partial
+                .unprocessed_components
+                .into_iter()
+                .filter(|id| affected(id)),
+        );
+        self.projections.retain(|id, _| !affected(id));
+        self.projections.extend(
+            partial
+                .projections
+                .into_iter()
+                .filter(|(id, _)| affected(id)),
+        );
+        self.time_phased.retain(|id, _| !affected(id));
+        self.time_phased.extend(
+            partial
+                .time_phased
+                .into_iter()
+                .filter(|(id, _)| affected(id)),
+        );
+
+        self.calculation_time_ms = partial.calculation_time_ms;
+        self.manifest = partial.manifest;
+    }
+
+    /// 查詢計劃訂單的註記
+    pub fn annotation(&self, order_id: uuid::Uuid) -> Option<&mrp_core::OrderAnnotation> {
+        self.annotations.get(&order_id)
+    }
+
+    /// 催貨候選清單（提前到貨可消除需求延遲的現有供應，按影響排序）
+    pub fn expedite_candidates(&self) -> Vec<ExpediteCandidate> {
+        expedite::ExpediteAnalyzer::candidates(&self.coverage)
+    }
+
+    /// 查詢單筆需求的覆蓋明細
+    pub fn coverage_for(&self, demand_id: uuid::Uuid) -> Option<&DemandCoverage> {
+        self.coverage.iter().find(|c| c.demand_id == demand_id)
+    }
+
+    /// 計劃訂單追溯到的需求金額合計（未設金額的需求不計）
+    pub fn order_value(&self, order_id: uuid::Uuid) -> rust_decimal::Decimal {
+        self.pegging
+            .get(&order_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.value)
+            .sum()
+    }
+
+    /// 有風險金額的需求覆蓋，按風險金額由高到低排序
+    pub fn value_at_risk(&self) -> Vec<&DemandCoverage> {
+        let mut at_risk: Vec<&DemandCoverage> = self
+            .coverage
+            .iter()
+            .filter(|c| c.value_at_risk() > rust_decimal::Decimal::ZERO)
+            .collect();
+        at_risk.sort_by_key(|c| std::cmp::Reverse(c.value_at_risk()));
+        at_risk
+    }
+}
+
+/// 移除 `current` 中符合條件的記錄，改以 `partial` 中符合條件的記錄補上
+fn replace_records<T>(current: &mut Vec<T>, partial: Vec<T>, affected: impl Fn(&T) -> bool) {
+    current.retain(|r| !affected(r));
+    current.extend(partial.into_iter().filter(|r| affected(r)));
 }
 
 /// MRP 警告
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
 pub struct MrpWarning {
     pub component_id: String,
     pub message: String,
@@ -76,7 +490,9 @@ impl MrpWarning {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// 警告嚴重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
 pub enum WarningSeverity {
     Info,
     Warning,
@@ -88,4 +504,10 @@ pub enum WarningSeverity {
 pub struct ComponentMrpResult {
     pub component_id: String,
     pub planned_orders: Vec<mrp_core::PlannedOrder>,
+    pub timing: ItemTiming,
+    pub warnings: Vec<MrpWarning>,
+    /// 非 MRP 計劃物料的庫存預測（不展開、不產生計劃訂單）
+    pub projection: Option<Vec<NetRequirement>>,
+    /// 現有供應的重排建議
+    pub reschedules: Vec<RescheduleMessage>,
 }
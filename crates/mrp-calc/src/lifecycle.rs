@@ -0,0 +1,173 @@
+//! 停產與淘汰計劃：停產日後不再下新訂單，以剩餘庫存滿足需求並推算耗盡日
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, LifecycleStatus, MrpConfig, PlannedOrder, Supply};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// 停產物料報告
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PhaseOutReport {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 生命週期狀態
+    pub status: LifecycleStatus,
+
+    /// 剩餘庫存（現有可用 + 已排定收貨 + 停產日前的計劃訂單）
+    pub remaining_stock: Decimal,
+
+    /// 總需求
+    pub total_demand: Decimal,
+
+    /// 需求超過剩餘庫存的數量
+    pub shortfall: Decimal,
+
+    /// 預計耗盡日期（剩餘庫存足以滿足所有需求時為 None）
+    pub run_out_date: Option<NaiveDate>,
+
+    /// 因停產而取消的計劃訂單數
+    pub suppressed_orders: usize,
+}
+
+/// 停產計劃器
+pub struct PhaseOutPlanner;
+
+impl PhaseOutPlanner {
+    /// 取消停產日後（已淘汰則全部）的計劃訂單並產生報告；正常供貨的物料回傳 None
+    ///
+    /// 耗盡日期為預計庫存首次降到零以下（或剛好為零）的需求日期。
+    pub fn apply(
+        component_id: &str,
+        config: &MrpConfig,
+        planned_orders: &mut Vec<PlannedOrder>,
+        demands: &[Demand],
+        supplies: &[Supply],
+        initial_inventory: Decimal,
+    ) -> Option<PhaseOutReport> {
+        let status = config.lifecycle;
+        if status == LifecycleStatus::Active {
+            return None;
+        }
+
+        let before = planned_orders.len();
+        planned_orders.retain(|order| status.allows_order_on(order.order_date));
+        let suppressed_orders = before - planned_orders.len();
+
+        // 日期 →（收貨, 需求）
+        let mut events: BTreeMap<NaiveDate, (Decimal, Decimal)> = BTreeMap::new();
+        for supply in supplies {
+            events.entry(supply.available_date).or_default().0 += supply.quantity;
+        }
+        for order in planned_orders.iter() {
+            events.entry(order.required_date).or_default().0 += order.quantity;
+        }
+        for demand in demands {
+            events.entry(demand.required_date).or_default().1 += demand.quantity;
+        }
+
+        let remaining_stock = initial_inventory
+            + supplies.iter().map(|s| s.quantity).sum::<Decimal>()
+            + planned_orders.iter().map(|o| o.quantity).sum::<Decimal>();
+        let total_demand: Decimal = demands.iter().map(|d| d.quantity).sum();
+
+        let mut projected = initial_inventory;
+        let mut run_out_date = None;
+        for (date, (receipts, demand)) in events {
+            projected += receipts - demand;
+            if demand > Decimal::ZERO && projected <= Decimal::ZERO {
+                run_out_date = Some(date);
+                break;
+            }
+        }
+
+        Some(PhaseOutReport {
+            component_id: component_id.to_string(),
+            status,
+            remaining_stock,
+            total_demand,
+            shortfall: (total_demand - remaining_stock).max(Decimal::ZERO),
+            run_out_date,
+            suppressed_orders,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{DemandType, PlannedOrderType, ProcurementType, SupplyType};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_suppress_orders_after_end_date() {
+        let config = MrpConfig::new("LCD-V1".to_string(), 2, ProcurementType::Buy)
+            .with_lifecycle(LifecycleStatus::PhaseOut { end_date: date(10) });
+        let mut orders = vec![
+            PlannedOrder::new(
+                "LCD-V1".to_string(),
+                Decimal::from(30),
+                date(8),
+                date(6),
+                PlannedOrderType::Purchase,
+            ),
+            PlannedOrder::new(
+                "LCD-V1".to_string(),
+                Decimal::from(50),
+                date(20),
+                date(18),
+                PlannedOrderType::Purchase,
+            ),
+        ];
+        let demands: Vec<Demand> = [(5, 40), (8, 30), (20, 50)]
+            .into_iter()
+            .map(|(day, qty)| {
+                Demand::new(
+                    "LCD-V1".to_string(),
+                    Decimal::from(qty),
+                    date(day),
+                    DemandType::SalesOrder,
+                )
+            })
+            .collect();
+        let supplies = vec![Supply::new(
+            "LCD-V1".to_string(),
+            Decimal::from(10),
+            date(3),
+            SupplyType::PurchaseOrder,
+        )];
+
+        let report = PhaseOutPlanner::apply(
+            "LCD-V1",
+            &config,
+            &mut orders,
+            &demands,
+            &supplies,
+            Decimal::from(40),
+        )
+        .unwrap();
+
+        assert_eq!(orders.len(), 1);
+        assert_eq!(report.suppressed_orders, 1);
+        // 40 + 10 + 30 = 80，需求 120
+        assert_eq!(report.remaining_stock, Decimal::from(80));
+        assert_eq!(report.shortfall, Decimal::from(40));
+        // 11/3 → 50，11/5 → 10，11/8 → 10，11/20 → -40
+        assert_eq!(report.run_out_date, Some(date(20)));
+
+        let active = MrpConfig::new("LCD-V2".to_string(), 2, ProcurementType::Buy);
+        assert!(PhaseOutPlanner::apply(
+            "LCD-V2",
+            &active,
+            &mut orders,
+            &demands,
+            &[],
+            Decimal::ZERO
+        )
+        .is_none());
+    }
+}
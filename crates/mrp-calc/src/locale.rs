@@ -0,0 +1,156 @@
+//! 匯入地區設定：小數點、千分位與日期格式（含 Excel 序列日期）
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// 日期格式
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DateFormat {
+    /// chrono 格式字串（如 `%d/%m/%Y`）
+    Pattern(String),
+    /// Excel 序列日期（1900 日期系統，如 45971 = 2025-11-10）
+    ExcelSerial,
+}
+
+impl DateFormat {
+    /// 建立格式字串日期格式
+    pub fn pattern(pattern: &str) -> Self {
+        Self::Pattern(pattern.to_string())
+    }
+}
+
+/// 匯入地區設定（每個檔案可使用不同設定）
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ImportLocale {
+    /// 小數點符號
+    pub decimal_separator: char,
+
+    /// 千分位符號（None 表示不接受千分位）
+    pub thousands_separator: Option<char>,
+
+    /// 依序嘗試的日期格式
+    pub date_formats: Vec<DateFormat>,
+}
+
+impl ImportLocale {
+    /// 預設設定：小數點 `.`、日期 `YYYY-MM-DD`
+    pub fn new() -> Self {
+        Self {
+            decimal_separator: '.',
+            thousands_separator: None,
+            date_formats: vec![DateFormat::pattern("%Y-%m-%d")],
+        }
+    }
+
+    /// 歐陸設定：小數點 `,`、千分位 `.`、日期 `DD/MM/YYYY` 或 `DD.MM.YYYY`
+    ///
+    /// 小數點為逗號時 CSV 分隔字元通常為分號。
+    pub fn european() -> Self {
+        Self {
+            decimal_separator: ',',
+            thousands_separator: Some('.'),
+            date_formats: vec![
+                DateFormat::pattern("%d/%m/%Y"),
+                DateFormat::pattern("%d.%m.%Y"),
+            ],
+        }
+    }
+
+    /// 建構器模式：設置小數點符號
+    pub fn with_decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// 建構器模式：設置千分位符號
+    pub fn with_thousands_separator(mut self, separator: char) -> Self {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    /// 建構器模式：設置日期格式（依序嘗試）
+    pub fn with_date_formats(mut self, formats: Vec<DateFormat>) -> Self {
+        self.date_formats = formats;
+        self
+    }
+
+    /// 解析數值
+    pub fn parse_decimal(&self, value: &str) -> Option<Decimal> {
+        let normalized: String = value
+            .trim()
+            .chars()
+            .filter(|c| Some(*c) != self.thousands_separator)
+            .map(|c| if c == self.decimal_separator { '.' } else { c })
+            .collect();
+        Decimal::from_str(&normalized).ok()
+    }
+
+    /// 解析日期（依序嘗試各日期格式）
+    pub fn parse_date(&self, value: &str) -> Option<NaiveDate> {
+        let value = value.trim();
+        self.date_formats.iter().find_map(|format| match format {
+            DateFormat::Pattern(pattern) => NaiveDate::parse_from_str(value, pattern).ok(),
+            DateFormat::ExcelSerial => Self::parse_excel_serial(value),
+        })
+    }
+
+    /// Excel 序列日期：以 1899-12-30 為基準（已涵蓋 Excel 的 1900 閏年錯誤，適用 1900-03-01 之後）
+    fn parse_excel_serial(value: &str) -> Option<NaiveDate> {
+        let serial: f64 = value.parse().ok()?;
+        if !(61.0..2_958_466.0).contains(&serial) {
+            return None;
+        }
+        NaiveDate::from_ymd_opt(1899, 12, 30)?
+            .checked_add_signed(chrono::Duration::days(serial.trunc() as i64))
+    }
+}
+
+impl Default for ImportLocale {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_european_locale() {
+        let locale = ImportLocale::european();
+
+        assert_eq!(
+            locale.parse_decimal("1.234,5"),
+            Some(Decimal::new(12345, 1))
+        );
+        assert_eq!(
+            locale.parse_date("10/11/2025"),
+            NaiveDate::from_ymd_opt(2025, 11, 10)
+        );
+        assert_eq!(
+            locale.parse_date("10.11.2025"),
+            NaiveDate::from_ymd_opt(2025, 11, 10)
+        );
+    }
+
+    #[test]
+    fn test_excel_serial_and_slash_dates() {
+        let locale = ImportLocale::new().with_date_formats(vec![
+            DateFormat::pattern("%Y/%m/%d"),
+            DateFormat::ExcelSerial,
+        ]);
+
+        assert_eq!(
+            locale.parse_date("2025/11/10"),
+            NaiveDate::from_ymd_opt(2025, 11, 10)
+        );
+        assert_eq!(
+            locale.parse_date("45971"),
+            NaiveDate::from_ymd_opt(2025, 11, 10)
+        );
+        assert_eq!(locale.parse_date("2025-11-10"), None);
+    }
+}
@@ -9,14 +9,287 @@ use crate::netting::NetRequirement;
 pub struct LotSizingCalculator;
 
 impl LotSizingCalculator {
-    /// 應用批量規則
+    /// 應用批量規則（近期區段自第一個時間桶起算，見 [`Self::apply_with_plan_start`]）
     pub fn apply(
         component_id: &str,
         net_requirements: &[NetRequirement],
         config: &MrpConfig,
         calendar: &mrp_core::WorkCalendar,
     ) -> mrp_core::Result<Vec<PlannedOrder>> {
-        match config.lot_sizing_rule {
+        Self::apply_with_plan_start(component_id, net_requirements, config, calendar, None)
+    }
+
+    /// 以指定計劃起始日應用批量規則
+    ///
+    /// 設置近期區段規則時，區段內（自計劃起始日起；未指定時自第一個時間桶起）與區段外的
+    /// 淨需求分別套用各自的規則；區段內多訂的數量帶入區段外沖抵。
+    /// 設置最大涵蓋天數（保存期限）時，每段再按涵蓋天數切分後獨立套用規則。
+    /// 設置最小訂單間隔時，最後再合併間隔內的訂單（不超過涵蓋天數）。
+    pub fn apply_with_plan_start(
+        component_id: &str,
+        net_requirements: &[NetRequirement],
+        config: &MrpConfig,
+        calendar: &mrp_core::WorkCalendar,
+        plan_start: Option<chrono::NaiveDate>,
+    ) -> mrp_core::Result<Vec<PlannedOrder>> {
+        Self::apply_with_coverage(
+            component_id,
+            net_requirements,
+            config,
+            calendar,
+            plan_start,
+            config.max_coverage_days,
+        )
+    }
+
+    /// 保存期限限制多產生的訂單數（未設置最大涵蓋天數時為 0）
+    pub fn shelf_life_extra_orders(
+        component_id: &str,
+        net_requirements: &[NetRequirement],
+        config: &MrpConfig,
+        calendar: &mrp_core::WorkCalendar,
+        plan_start: Option<chrono::NaiveDate>,
+        constrained: &[PlannedOrder],
+    ) -> mrp_core::Result<usize> {
+        if config.max_coverage_days.is_none() {
+            return Ok(0);
+        }
+        let unconstrained = Self::apply_with_coverage(
+            component_id,
+            net_requirements,
+            config,
+            calendar,
+            plan_start,
+            None,
+        )?;
+        Ok(constrained.len().saturating_sub(unconstrained.len()))
+    }
+
+    /// 以指定的最大涵蓋天數套用批量規則
+    fn apply_with_coverage(
+        component_id: &str,
+        net_requirements: &[NetRequirement],
+        config: &MrpConfig,
+        calendar: &mrp_core::WorkCalendar,
+        plan_start: Option<chrono::NaiveDate>,
+        max_coverage_days: Option<u32>,
+    ) -> mrp_core::Result<Vec<PlannedOrder>> {
+        let planned_orders = Self::apply_zones(
+            component_id,
+            net_requirements,
+            config,
+            calendar,
+            plan_start,
+            max_coverage_days,
+        )?;
+
+        let interval = match (config.min_order_interval_days, max_coverage_days) {
+            (Some(interval), Some(coverage)) => Some(interval.min(coverage)),
+            (interval, _) => interval,
+        };
+        Ok(match interval {
+            Some(days) if days > 0 => {
+                Self::enforce_min_interval(planned_orders, days, config, calendar)
+            }
+            _ => planned_orders,
+        })
+    }
+
+    /// 依近期區段分段套用批量規則
+    ///
+    /// 區段交界自計劃起始日起算；區段內訂單超出淨需求的剩餘量帶入區段外，
+    /// 避免跨交界重複訂購（設置最大涵蓋天數時剩餘量視為已過保存期限，不帶入）。
+    fn apply_zones(
+        component_id: &str,
+        net_requirements: &[NetRequirement],
+        config: &MrpConfig,
+        calendar: &mrp_core::WorkCalendar,
+        plan_start: Option<chrono::NaiveDate>,
+        max_coverage_days: Option<u32>,
+    ) -> mrp_core::Result<Vec<PlannedOrder>> {
+        let start = plan_start.or_else(|| net_requirements.first().map(|r| r.date));
+        let (Some(zone), Some(start)) = (config.zone_lot_sizing, start) else {
+            return Self::apply_coverage_windows(
+                config.lot_sizing_rule,
+                component_id,
+                net_requirements,
+                config,
+                calendar,
+                max_coverage_days,
+            );
+        };
+
+        let boundary = start + chrono::Duration::days(zone.days as i64);
+        let split = net_requirements.partition_point(|r| r.date < boundary);
+        let (near, far) = net_requirements.split_at(split);
+        let mut planned_orders = Self::apply_coverage_windows(
+            zone.rule,
+            component_id,
+            near,
+            config,
+            calendar,
+            max_coverage_days,
+        )?;
+        let far = match max_coverage_days.filter(|d| *d > 0) {
+            Some(_) => far.to_vec(),
+            None => {
+                let surplus = Self::zone_surplus(zone.rule, near, &planned_orders);
+                Self::carry_surplus(config.lot_sizing_rule, far, surplus)
+            }
+        };
+        planned_orders.extend(Self::apply_coverage_windows(
+            config.lot_sizing_rule,
+            component_id,
+            &far,
+            config,
+            calendar,
+            max_coverage_days,
+        )?);
+
+        Ok(planned_orders)
+    }
+
+    /// 區段結束時訂單超出需求的剩餘量
+    ///
+    /// 固定批量、EOQ、最小-最大以毛需求與預計收貨累計預計庫存，剩餘量為期末預計庫存；
+    /// 其他規則按淨需求訂購，剩餘量為訂購量超出淨需求的部分（最小訂購量、取整）。
+    fn zone_surplus(
+        rule: LotSizingRule,
+        net_requirements: &[NetRequirement],
+        orders: &[PlannedOrder],
+    ) -> Decimal {
+        let ordered: Decimal = orders.iter().map(|o| o.quantity).sum();
+        let surplus = if Self::tracks_inventory(rule) {
+            net_requirements
+                .iter()
+                .map(|r| r.scheduled_receipt - r.gross_requirement)
+                .sum::<Decimal>()
+                + ordered
+        } else {
+            ordered
+                - net_requirements
+                    .iter()
+                    .map(|r| r.net_requirement)
+                    .sum::<Decimal>()
+        };
+        surplus.max(Decimal::ZERO)
+    }
+
+    /// 以前一區段的剩餘量沖抵本區段的需求
+    ///
+    /// 累計預計庫存的規則把剩餘量視為第一期的預計收貨；其他規則依序扣減淨需求。
+    fn carry_surplus(
+        rule: LotSizingRule,
+        net_requirements: &[NetRequirement],
+        surplus: Decimal,
+    ) -> Vec<NetRequirement> {
+        let mut net_requirements = net_requirements.to_vec();
+        if surplus <= Decimal::ZERO {
+            return net_requirements;
+        }
+        if Self::tracks_inventory(rule) {
+            if let Some(first) = net_requirements.first_mut() {
+                first.scheduled_receipt += surplus;
+            }
+            return net_requirements;
+        }
+        let mut remaining = surplus;
+        for req in &mut net_requirements {
+            let used = remaining.min(req.net_requirement);
+            req.net_requirement -= used;
+            remaining -= used;
+        }
+        net_requirements
+    }
+
+    /// 規則是否自行以毛需求與預計收貨累計預計庫存（而非直接使用淨需求）
+    fn tracks_inventory(rule: LotSizingRule) -> bool {
+        matches!(
+            rule,
+            LotSizingRule::FixedOrderQuantity
+                | LotSizingRule::EconomicOrderQuantity
+                | LotSizingRule::MinMax
+        )
+    }
+
+    /// 按最大涵蓋天數切分淨需求後逐段套用批量規則
+    ///
+    /// 每段自段內第一個淨需求日期起算，段與段之間不互相沖抵
+    /// （前段多訂的數量視為已過保存期限）。
+    fn apply_coverage_windows(
+        rule: LotSizingRule,
+        component_id: &str,
+        net_requirements: &[NetRequirement],
+        config: &MrpConfig,
+        calendar: &mrp_core::WorkCalendar,
+        max_coverage_days: Option<u32>,
+    ) -> mrp_core::Result<Vec<PlannedOrder>> {
+        let Some(days) = max_coverage_days.filter(|d| *d > 0) else {
+            return Self::apply_rule(rule, component_id, net_requirements, config, calendar);
+        };
+
+        let mut planned_orders = Vec::new();
+        let mut remaining = net_requirements;
+        while let Some(first) = remaining.first() {
+            let boundary = first.date + chrono::Duration::days(days as i64);
+            let split = remaining.partition_point(|r| r.date < boundary);
+            planned_orders.extend(Self::apply_rule(
+                rule,
+                component_id,
+                &remaining[..split],
+                config,
+                calendar,
+            )?);
+            remaining = &remaining[split..];
+        }
+
+        Ok(planned_orders)
+    }
+
+    /// 合併需求日期落在前一張訂單 `interval_days` 天內的訂單
+    ///
+    /// 合併後的訂單沿用較早的需求日期並重算下單日；合併後會超過最大訂購量時不合併
+    /// （保留拆單結果）。
+    fn enforce_min_interval(
+        mut orders: Vec<PlannedOrder>,
+        interval_days: u32,
+        config: &MrpConfig,
+        calendar: &mrp_core::WorkCalendar,
+    ) -> Vec<PlannedOrder> {
+        orders.sort_by_key(|o| o.required_date);
+
+        let mut merged: Vec<PlannedOrder> = Vec::with_capacity(orders.len());
+        for order in orders {
+            if let Some(previous) = merged.last_mut() {
+                let within_interval = (order.required_date - previous.required_date).num_days()
+                    < interval_days as i64;
+                let fits = config
+                    .maximum_order_qty
+                    .map_or(true, |max| previous.quantity + order.quantity <= max);
+                if within_interval && fits {
+                    previous.quantity += order.quantity;
+                    previous.order_date = calendar.subtract_working_days(
+                        previous.required_date,
+                        config.lead_time_for(previous.quantity),
+                    );
+                    continue;
+                }
+            }
+            merged.push(order);
+        }
+
+        merged
+    }
+
+    /// 對淨需求套用指定批量規則
+    fn apply_rule(
+        rule: LotSizingRule,
+        component_id: &str,
+        net_requirements: &[NetRequirement],
+        config: &MrpConfig,
+        calendar: &mrp_core::WorkCalendar,
+    ) -> mrp_core::Result<Vec<PlannedOrder>> {
+        match rule {
             LotSizingRule::LotForLot => {
                 Self::lot_for_lot(component_id, net_requirements, config, calendar)
             }
@@ -32,6 +305,10 @@ impl LotSizingCalculator {
             LotSizingRule::MinMax => {
                 Self::min_max(component_id, net_requirements, config, calendar)
             }
+            rule => Err(mrp_core::MrpError::CalculationError(format!(
+                "不支援的批量規則: {:?}",
+                rule
+            ))),
         }
     }
 
@@ -46,18 +323,14 @@ impl LotSizingCalculator {
 
         for req in net_requirements {
             if req.net_requirement > Decimal::ZERO {
-                let order_date =
-                    calendar.subtract_working_days(req.date, config.lead_time_days);
-
-                let quantity = config.adjust_order_quantity(req.net_requirement);
-
-                planned_orders.push(PlannedOrder::new(
-                    component_id.to_string(),
-                    quantity,
+                Self::push_orders(
+                    &mut planned_orders,
+                    component_id,
+                    req.net_requirement,
                     req.date,
-                    order_date,
-                    Self::determine_order_type(config.procurement_type),
-                ));
+                    config,
+                    calendar,
+                );
             }
         }
 
@@ -95,18 +368,14 @@ impl LotSizingCalculator {
                 };
 
                 let order_quantity = fixed_lot_size * Decimal::from(batches_needed);
-                let adjusted_quantity = config.adjust_order_quantity(order_quantity);
-
-                let order_date =
-                    calendar.subtract_working_days(req.date, config.lead_time_days);
-
-                planned_orders.push(PlannedOrder::new(
-                    component_id.to_string(),
-                    adjusted_quantity,
+                let adjusted_quantity = Self::push_orders(
+                    &mut planned_orders,
+                    component_id,
+                    order_quantity,
                     req.date,
-                    order_date,
-                    Self::determine_order_type(config.procurement_type),
-                ));
+                    config,
+                    calendar,
+                );
 
                 remaining_inventory += adjusted_quantity;
             }
@@ -117,34 +386,14 @@ impl LotSizingCalculator {
 
     /// 經濟訂購量（EOQ）
     /// 基於成本優化的批量計算
-    /// EOQ = sqrt(2 * 年需求量 * 訂購成本 / 持有成本)
+    /// EOQ = sqrt(2 * 年需求量 * 訂購成本 / 持有成本)，參數見 `MrpConfig::economic_order_quantity`
     fn economic_order_quantity(
         component_id: &str,
         net_requirements: &[NetRequirement],
         config: &MrpConfig,
         calendar: &mrp_core::WorkCalendar,
     ) -> mrp_core::Result<Vec<PlannedOrder>> {
-        // 簡化實現：如果沒有配置 EOQ 參數，退回到固定批量或 LFL
-        let eoq_size = if let Some(fixed_size) = config.fixed_lot_size {
-            fixed_size
-        } else {
-            // 如果沒有配置，計算簡化的 EOQ
-            // 預設使用年需求量的平方根作為批量
-            let total_requirement: Decimal = net_requirements
-                .iter()
-                .map(|r| r.net_requirement)
-                .sum();
-
-            if total_requirement > Decimal::ZERO {
-                // 簡化公式：sqrt(total_demand) * 10
-                // 使用 f64 計算平方根，然後轉回 Decimal
-                let total_f64 = total_requirement.to_string().parse::<f64>().unwrap_or(100.0);
-                let sqrt_value = total_f64.sqrt();
-                Decimal::try_from(sqrt_value * 10.0).unwrap_or(Decimal::from(100))
-            } else {
-                Decimal::from(100) // 預設批量
-            }
-        };
+        let eoq_size = Self::eoq_lot_size(net_requirements, config)?;
 
         // 使用計算出的 EOQ 作為固定批量
         let mut planned_orders = Vec::new();
@@ -161,18 +410,14 @@ impl LotSizingCalculator {
                     ratio.ceil().to_string().parse::<u32>().unwrap_or(1)
                 };
                 let order_quantity = eoq_size * Decimal::from(batches_needed);
-                let adjusted_quantity = config.adjust_order_quantity(order_quantity);
-
-                let order_date =
-                    calendar.subtract_working_days(req.date, config.lead_time_days);
-
-                planned_orders.push(PlannedOrder::new(
-                    component_id.to_string(),
-                    adjusted_quantity,
+                let adjusted_quantity = Self::push_orders(
+                    &mut planned_orders,
+                    component_id,
+                    order_quantity,
                     req.date,
-                    order_date,
-                    Self::determine_order_type(config.procurement_type),
-                ));
+                    config,
+                    calendar,
+                );
 
                 remaining_inventory += adjusted_quantity;
             }
@@ -182,30 +427,30 @@ impl LotSizingCalculator {
     }
 
     /// 週期訂購量（POQ）
-    /// 合併指定週期內的需求，一次性訂購
+    /// 合併指定週期內的需求，一次性訂購（週期長度與對齊方式見 `MrpConfig`）
     fn period_order_quantity(
         component_id: &str,
         net_requirements: &[NetRequirement],
         config: &MrpConfig,
         calendar: &mrp_core::WorkCalendar,
     ) -> mrp_core::Result<Vec<PlannedOrder>> {
-        // POQ 週期（天數），預設 7 天
-        let period_days = 7;
-
         let mut planned_orders = Vec::new();
         let mut period_start_index = 0;
 
         while period_start_index < net_requirements.len() {
             let period_start_date = net_requirements[period_start_index].date;
+            let (_, period_end_date) = calendar.period_bounds(
+                period_start_date,
+                config.poq_period_days,
+                config.poq_alignment,
+            );
 
             // 收集週期內的所有需求
             let mut period_total = Decimal::ZERO;
             let mut period_end_index = period_start_index;
 
             for (idx, req) in net_requirements.iter().enumerate().skip(period_start_index) {
-                let days_diff = (req.date - period_start_date).num_days();
-
-                if days_diff < period_days as i64 {
+                if req.date < period_end_date {
                     period_total += req.net_requirement;
                     period_end_index = idx;
                 } else {
@@ -215,17 +460,14 @@ impl LotSizingCalculator {
 
             // 如果週期內有需求，生成一張訂單
             if period_total > Decimal::ZERO {
-                let adjusted_quantity = config.adjust_order_quantity(period_total);
-                let order_date =
-                    calendar.subtract_working_days(period_start_date, config.lead_time_days);
-
-                planned_orders.push(PlannedOrder::new(
-                    component_id.to_string(),
-                    adjusted_quantity,
+                Self::push_orders(
+                    &mut planned_orders,
+                    component_id,
+                    period_total,
                     period_start_date,
-                    order_date,
-                    Self::determine_order_type(config.procurement_type),
-                ));
+                    config,
+                    calendar,
+                );
             }
 
             period_start_index = period_end_index + 1;
@@ -262,18 +504,14 @@ impl LotSizingCalculator {
             // 如果庫存低於最小值，補充至最大值
             if current_inventory < min_level {
                 let order_quantity = max_level - current_inventory;
-                let adjusted_quantity = config.adjust_order_quantity(order_quantity);
-
-                let order_date =
-                    calendar.subtract_working_days(req.date, config.lead_time_days);
-
-                planned_orders.push(PlannedOrder::new(
-                    component_id.to_string(),
-                    adjusted_quantity,
+                let adjusted_quantity = Self::push_orders(
+                    &mut planned_orders,
+                    component_id,
+                    order_quantity,
                     req.date,
-                    order_date,
-                    Self::determine_order_type(config.procurement_type),
-                ));
+                    config,
+                    calendar,
+                );
 
                 current_inventory += adjusted_quantity;
             }
@@ -282,12 +520,59 @@ impl LotSizingCalculator {
         Ok(planned_orders)
     }
 
+    /// 建立計劃訂單並回傳訂購總量
+    ///
+    /// 調整後數量超過最大訂購量時拆成多張同日訂單（每張不超過最大訂購量），
+    /// 而不是截斷到最大訂購量而遺漏需求。
+    fn push_orders(
+        planned_orders: &mut Vec<PlannedOrder>,
+        component_id: &str,
+        quantity: Decimal,
+        required_date: chrono::NaiveDate,
+        config: &MrpConfig,
+        calendar: &mrp_core::WorkCalendar,
+    ) -> Decimal {
+        let parts = config.split_order_quantity(quantity);
+        for &part in &parts {
+            let order_date =
+                calendar.subtract_working_days(required_date, config.lead_time_for(part));
+            planned_orders.push(PlannedOrder::new(
+                component_id.to_string(),
+                part,
+                required_date,
+                order_date,
+                Self::determine_order_type(config.procurement_type),
+            ));
+        }
+        parts.into_iter().sum()
+    }
+
+    /// EOQ 批量：設置固定批量時直接使用，否則以計劃時界內的毛需求換算年需求量後由成本參數計算
+    pub(crate) fn eoq_lot_size(
+        net_requirements: &[NetRequirement],
+        config: &MrpConfig,
+    ) -> mrp_core::Result<Decimal> {
+        if let Some(fixed_size) = config.fixed_lot_size {
+            return Ok(fixed_size);
+        }
+
+        let total_requirement: Decimal = net_requirements.iter().map(|r| r.gross_requirement).sum();
+        let horizon_days = Decimal::from(config.planning_horizon_days.max(1));
+        let annual_demand = total_requirement * Decimal::from(365) / horizon_days;
+
+        Ok(config
+            .economic_order_quantity(annual_demand)?
+            .max(Decimal::ONE))
+    }
+
     /// 決定訂單類型
-    fn determine_order_type(procurement_type: ProcurementType) -> PlannedOrderType {
+    pub(crate) fn determine_order_type(procurement_type: ProcurementType) -> PlannedOrderType {
         match procurement_type {
             ProcurementType::Buy => PlannedOrderType::Purchase,
             ProcurementType::Make => PlannedOrderType::Production,
             ProcurementType::Transfer => PlannedOrderType::Transfer,
+            // 未知的採購類型按採購處理
+            _ => PlannedOrderType::Purchase,
         }
     }
 }
@@ -405,6 +690,206 @@ mod tests {
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].quantity, Decimal::from(80));
         assert_eq!(result[1].quantity, Decimal::from(40));
+
+        // 對齊日曆週：11/1（週六）與 11/3（週一）分屬不同週
+        let config = config.with_poq_period(7, mrp_core::PeriodAlignment::Week);
+        let result = LotSizingCalculator::period_order_quantity(
+            "TEST-003",
+            &net_reqs,
+            &config,
+            &calendar,
+        ).unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].quantity, Decimal::from(50));
+    }
+
+    #[test]
+    fn test_lot_for_lot_splits_over_maximum() {
+        let calendar = WorkCalendar::default();
+        let config = MrpConfig::new("TEST-005".to_string(), 2, ProcurementType::Buy)
+            .with_maximum_order_qty(Decimal::from(400));
+
+        let net_reqs = vec![NetRequirement {
+            date: NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
+            gross_requirement: Decimal::from(1000),
+            scheduled_receipt: Decimal::ZERO,
+            projected_on_hand: Decimal::ZERO,
+            net_requirement: Decimal::from(1000),
+        }];
+
+        let result = LotSizingCalculator::apply("TEST-005", &net_reqs, &config, &calendar).unwrap();
+
+        // 1000 拆成 400 + 400 + 200，總量不因最大訂購量而短少
+        assert_eq!(result.len(), 3);
+        assert_eq!(
+            result.iter().map(|o| o.quantity).sum::<Decimal>(),
+            Decimal::from(1000)
+        );
+        assert!(result.iter().all(|o| o.quantity <= Decimal::from(400)));
+    }
+
+    #[test]
+    fn test_zone_lot_sizing_rule() {
+        let calendar = WorkCalendar::default();
+        let config = MrpConfig::new("TEST-006".to_string(), 1, ProcurementType::Make)
+            .with_lot_sizing_rule(mrp_core::LotSizingRule::PeriodOrderQuantity)
+            .with_zone_lot_sizing_rule(14, mrp_core::LotSizingRule::LotForLot);
+
+        let net_reqs: Vec<NetRequirement> = [3, 5, 17, 19]
+            .into_iter()
+            .map(|day| NetRequirement {
+                date: NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+                gross_requirement: Decimal::from(10),
+                scheduled_receipt: Decimal::ZERO,
+                projected_on_hand: Decimal::ZERO,
+                net_requirement: Decimal::from(10),
+            })
+            .collect();
+
+        let result = LotSizingCalculator::apply("TEST-006", &net_reqs, &config, &calendar).unwrap();
+
+        // 前 14 天批對批（11/3、11/5 各一張），之後 POQ 合併 11/17 與 11/19
+        let quantities: Vec<Decimal> = result.iter().map(|o| o.quantity).collect();
+        assert_eq!(
+            quantities,
+            vec![Decimal::from(10), Decimal::from(10), Decimal::from(20)]
+        );
+    }
+
+    #[test]
+    fn test_zone_boundary_measured_from_plan_start() {
+        let calendar = WorkCalendar::default();
+        let config = MrpConfig::new("TEST-006".to_string(), 1, ProcurementType::Make)
+            .with_lot_sizing_rule(mrp_core::LotSizingRule::PeriodOrderQuantity)
+            .with_zone_lot_sizing_rule(14, mrp_core::LotSizingRule::LotForLot);
+
+        let net_reqs: Vec<NetRequirement> = [10, 12, 20, 22]
+            .into_iter()
+            .map(|day| NetRequirement {
+                date: NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+                gross_requirement: Decimal::from(10),
+                scheduled_receipt: Decimal::ZERO,
+                projected_on_hand: Decimal::ZERO,
+                net_requirement: Decimal::from(10),
+            })
+            .collect();
+
+        // 區段自計劃起始日 11/1 起算到 11/15：11/10、11/12 批對批，11/20 與 11/22 POQ 合併
+        let result = LotSizingCalculator::apply_with_plan_start(
+            "TEST-006",
+            &net_reqs,
+            &config,
+            &calendar,
+            NaiveDate::from_ymd_opt(2025, 11, 1),
+        )
+        .unwrap();
+        let quantities: Vec<Decimal> = result.iter().map(|o| o.quantity).collect();
+        assert_eq!(
+            quantities,
+            vec![Decimal::from(10), Decimal::from(10), Decimal::from(20)]
+        );
+    }
+
+    #[test]
+    fn test_zone_surplus_carries_across_boundary() {
+        use chrono::Datelike;
+
+        let calendar = WorkCalendar::default();
+        let config = MrpConfig::new("TEST-009".to_string(), 1, ProcurementType::Buy)
+            .with_fixed_lot_size(Decimal::from(100))
+            .with_zone_lot_sizing_rule(14, mrp_core::LotSizingRule::FixedOrderQuantity);
+
+        let net_reqs: Vec<NetRequirement> = [(10, 30), (20, 30), (25, 60)]
+            .into_iter()
+            .map(|(day, quantity)| NetRequirement {
+                date: NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+                gross_requirement: Decimal::from(quantity),
+                scheduled_receipt: Decimal::ZERO,
+                projected_on_hand: Decimal::ZERO,
+                net_requirement: Decimal::from(quantity),
+            })
+            .collect();
+
+        // 11/10 的固定批量 100 跨過交界（11/15）：剩餘 70 沖抵 11/20 的 30 與 11/25 的 40，
+        // 區段外批對批只再訂 20
+        let result = LotSizingCalculator::apply_with_plan_start(
+            "TEST-009",
+            &net_reqs,
+            &config,
+            &calendar,
+            NaiveDate::from_ymd_opt(2025, 11, 1),
+        )
+        .unwrap();
+        let orders: Vec<(u32, Decimal)> = result
+            .iter()
+            .map(|o| (o.required_date.day(), o.quantity))
+            .collect();
+        assert_eq!(
+            orders,
+            vec![(10, Decimal::from(100)), (25, Decimal::from(20))]
+        );
+    }
+
+    #[test]
+    fn test_min_order_interval() {
+        let calendar = WorkCalendar::default();
+        let config = MrpConfig::new("TEST-007".to_string(), 1, ProcurementType::Buy)
+            .with_min_order_interval(5);
+
+        let net_reqs: Vec<NetRequirement> = [3, 4, 6, 10]
+            .into_iter()
+            .map(|day| NetRequirement {
+                date: NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+                gross_requirement: Decimal::from(10),
+                scheduled_receipt: Decimal::ZERO,
+                projected_on_hand: Decimal::ZERO,
+                net_requirement: Decimal::from(10),
+            })
+            .collect();
+
+        let result = LotSizingCalculator::apply("TEST-007", &net_reqs, &config, &calendar).unwrap();
+
+        // 11/3、11/4、11/6 合併為一張；11/10 距 11/3 已達 5 天
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].quantity, Decimal::from(30));
+        assert_eq!(
+            result[0].required_date,
+            NaiveDate::from_ymd_opt(2025, 11, 3).unwrap()
+        );
+        assert_eq!(result[1].quantity, Decimal::from(10));
+    }
+
+    #[test]
+    fn test_shelf_life_limits_coverage() {
+        let calendar = WorkCalendar::default();
+        let config = MrpConfig::new("TEST-008".to_string(), 1, ProcurementType::Buy)
+            .with_lot_sizing_rule(mrp_core::LotSizingRule::PeriodOrderQuantity)
+            .with_poq_period(14, mrp_core::PeriodAlignment::FirstRequirement)
+            .with_max_coverage_days(5);
+
+        let net_reqs: Vec<NetRequirement> = [3, 4, 6, 10]
+            .into_iter()
+            .map(|day| NetRequirement {
+                date: NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+                gross_requirement: Decimal::from(10),
+                scheduled_receipt: Decimal::ZERO,
+                projected_on_hand: Decimal::ZERO,
+                net_requirement: Decimal::from(10),
+            })
+            .collect();
+
+        let result = LotSizingCalculator::apply("TEST-008", &net_reqs, &config, &calendar).unwrap();
+
+        // 14 天週期原本一張 40；保存期限 5 天 → 11/3–11/7 一張、11/10 一張
+        let quantities: Vec<Decimal> = result.iter().map(|o| o.quantity).collect();
+        assert_eq!(quantities, vec![Decimal::from(30), Decimal::from(10)]);
+        assert_eq!(
+            LotSizingCalculator::shelf_life_extra_orders(
+                "TEST-008", &net_reqs, &config, &calendar, None, &result
+            )
+            .unwrap(),
+            1
+        );
     }
 
     #[test]
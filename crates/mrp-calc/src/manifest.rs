@@ -0,0 +1,71 @@
+//! 執行清單：記錄單次計算的引擎版本與隨機種子，稽核時可重現模擬結果
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::monte_carlo::MonteCarloSimulator;
+use crate::policy::PlanningPolicy;
+
+/// 執行清單
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct RunManifest {
+    /// 引擎版本
+    pub engine_version: String,
+
+    /// 亂數種子（來自計劃政策）
+    pub seed: u64,
+
+    /// 計劃起始日
+    pub plan_start_date: Option<NaiveDate>,
+}
+
+impl RunManifest {
+    /// 以計劃政策建立執行清單
+    pub fn new(policy: &PlanningPolicy) -> Self {
+        Self {
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            seed: policy.seed,
+            plan_start_date: policy.plan_start_date,
+        }
+    }
+
+    /// 以記錄的種子重建模擬器（重現當次的模擬）
+    pub fn simulator(&self, trials: u32) -> MonteCarloSimulator {
+        MonteCarloSimulator::new(self.seed, trials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MrpCalculator, PlanArchive, PlanSnapshot};
+    use bom_graph::BomGraph;
+    use mrp_core::WorkCalendar;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_seed_recorded_and_replayed() {
+        let policy = PlanningPolicy::new().with_seed(20251110);
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            HashMap::new(),
+            WorkCalendar::new_24_7("24/7".to_string()),
+        )
+        .with_policy(policy.clone());
+        let result = calculator.calculate(vec![], vec![], vec![]).unwrap();
+        assert_eq!(result.manifest.seed, 20251110);
+
+        let json = PlanArchive::save(&PlanSnapshot::new(result)).unwrap();
+        let manifest = PlanArchive::load(&json).unwrap().manifest.clone();
+        assert_eq!(manifest.engine_version, env!("CARGO_PKG_VERSION"));
+
+        let trial =
+            |rng: &mut rand::rngs::StdRng| MonteCarloSimulator::sample_normal(rng, 5.0, 1.0);
+        assert_eq!(
+            manifest.simulator(50).run(trial),
+            MonteCarloSimulator::from_policy(&policy, 50).run(trial)
+        );
+    }
+}
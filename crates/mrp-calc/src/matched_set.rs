@@ -0,0 +1,268 @@
+//! 配套計劃：按固定比例成套使用的物料（如左右側板）統一批量規則，避免因最小訂購量不同而訂購不成套的數量
+//!
+//! 以「套」為單位統一批量：套最小訂購量 = 各物料（最小訂購量 / 每套用量，進位到整套）的最大值，
+//! 套訂購倍數 = 各物料（訂購倍數 / 每套用量）的最小公倍數（非整數時不統一）。
+//! 庫存、供應或需求不成比例時仍可能不成套，計算後回報計劃期末不成套的剩餘數量。
+
+use mrp_core::{Demand, Inventory, PlannedOrder, Supply};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use crate::ConfigSet;
+
+/// 配套物料組
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MatchedSet {
+    /// 配套ID
+    pub set_id: String,
+
+    /// 成員物料（物料ID, 每套用量）
+    pub components: Vec<(String, Decimal)>,
+}
+
+impl MatchedSet {
+    /// 創建空的配套
+    pub fn new(set_id: String) -> Self {
+        Self {
+            set_id,
+            components: Vec::new(),
+        }
+    }
+
+    /// 建構器模式：加入成員物料（每套用量須大於 0）
+    pub fn with_component(mut self, component_id: String, quantity_per_set: Decimal) -> Self {
+        if quantity_per_set > Decimal::ZERO {
+            self.components.push((component_id, quantity_per_set));
+        }
+        self
+    }
+
+    /// 將成員物料的最小訂購量與訂購倍數換算為同一套數（只覆寫需要變更的物料）
+    pub(crate) fn harmonize(&self, configs: &mut ConfigSet) {
+        let members: Vec<_> = self
+            .components
+            .iter()
+            .filter_map(|(id, per_set)| configs.get(id).map(|config| (config, *per_set)))
+            .collect();
+
+        let set_minimum = members
+            .iter()
+            .filter_map(|(config, per_set)| {
+                config
+                    .minimum_order_qty
+                    .map(|qty| (qty / per_set).ceil().normalize())
+            })
+            .max();
+        let multiples: Vec<Decimal> = members
+            .iter()
+            .filter_map(|(config, per_set)| config.order_multiple.map(|m| m / per_set))
+            .collect();
+        let set_multiple = multiples.iter().try_fold(1u64, |lcm, multiple| {
+            let multiple = multiple.normalize();
+            (multiple.fract().is_zero() && multiple > Decimal::ZERO)
+                .then(|| multiple.to_u64())
+                .flatten()
+                .map(|m| lcm / gcd(lcm, m) * m)
+        });
+        let set_multiple = set_multiple.filter(|_| !multiples.is_empty());
+
+        let changed: Vec<_> = members
+            .into_iter()
+            .filter_map(|(config, per_set)| {
+                let minimum = set_minimum
+                    .map(|qty| qty * per_set)
+                    .or(config.minimum_order_qty);
+                let multiple = set_multiple
+                    .map(|m| Decimal::from(m) * per_set)
+                    .or(config.order_multiple);
+                if minimum == config.minimum_order_qty && multiple == config.order_multiple {
+                    return None;
+                }
+                let mut config = config.clone();
+                config.minimum_order_qty = minimum;
+                config.order_multiple = multiple;
+                Some(config)
+            })
+            .collect();
+
+        for config in changed {
+            configs.set(config);
+        }
+    }
+
+    /// 計劃期末的成套情況
+    ///
+    /// 期末量 = 現有可用庫存 + 供應 + 計劃訂單 − 獨立與相依需求（不足時視為 0）
+    pub(crate) fn evaluate(
+        &self,
+        inventories: &HashMap<String, Inventory>,
+        supplies: &HashMap<String, Vec<Supply>>,
+        demands: &[&HashMap<String, Vec<Demand>>],
+        planned_orders: &[PlannedOrder],
+    ) -> MatchedSetReport {
+        let ending: Vec<(&str, Decimal, Decimal)> = self
+            .components
+            .iter()
+            .map(|(id, per_set)| {
+                let on_hand = inventories
+                    .get(id)
+                    .map_or(Decimal::ZERO, |inv| inv.available_qty);
+                let supplied: Decimal = supplies
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .map(|s| s.quantity)
+                    .sum();
+                let planned: Decimal = planned_orders
+                    .iter()
+                    .filter(|o| o.component_id == *id)
+                    .map(|o| o.quantity)
+                    .sum();
+                let required: Decimal = demands
+                    .iter()
+                    .filter_map(|grouped| grouped.get(id))
+                    .flatten()
+                    .map(|d| d.quantity)
+                    .sum();
+                let ending = (on_hand + supplied + planned - required).max(Decimal::ZERO);
+                (id.as_str(), *per_set, ending)
+            })
+            .collect();
+
+        let complete_sets = ending
+            .iter()
+            .map(|(_, per_set, quantity)| (quantity / per_set).floor())
+            .min()
+            .unwrap_or(Decimal::ZERO);
+        let surplus = ending
+            .into_iter()
+            .map(|(id, per_set, quantity)| (id.to_string(), quantity - complete_sets * per_set))
+            .filter(|(_, quantity)| *quantity > Decimal::ZERO)
+            .collect();
+
+        MatchedSetReport {
+            set_id: self.set_id.clone(),
+            complete_sets,
+            surplus,
+        }
+    }
+}
+
+/// 配套的計劃期末成套情況
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MatchedSetReport {
+    /// 配套ID
+    pub set_id: String,
+
+    /// 期末剩餘可組成的完整套數
+    pub complete_sets: Decimal,
+
+    /// 不成套的剩餘數量（物料ID, 數量）
+    pub surplus: Vec<(String, Decimal)>,
+}
+
+impl MatchedSetReport {
+    /// 是否有不成套的剩餘
+    pub fn is_mismatched(&self) -> bool {
+        !self.surplus.is_empty()
+    }
+}
+
+/// 最大公因數
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{MrpConfig, PlannedOrderType, ProcurementType};
+
+    #[test]
+    fn test_harmonize_lot_rules_and_report_surplus() {
+        let mut configs = ConfigSet::default();
+        configs.set(
+            MrpConfig::new("PANEL-L".to_string(), 2, ProcurementType::Buy)
+                .with_minimum_order_qty(Decimal::from(100))
+                .with_order_multiple(Decimal::from(20)),
+        );
+        configs.set(
+            MrpConfig::new("PANEL-R".to_string(), 2, ProcurementType::Buy)
+                .with_minimum_order_qty(Decimal::from(50))
+                .with_order_multiple(Decimal::from(30)),
+        );
+        configs.set(MrpConfig::new("HINGE".to_string(), 2, ProcurementType::Buy));
+        let set = MatchedSet::new("DOOR-SET".to_string())
+            .with_component("PANEL-L".to_string(), Decimal::ONE)
+            .with_component("PANEL-R".to_string(), Decimal::ONE)
+            .with_component("HINGE".to_string(), Decimal::TWO);
+
+        set.harmonize(&mut configs);
+
+        // 每套最小 100 套、倍數 lcm(20, 30) = 60 套
+        let hinge = configs.get("HINGE").unwrap();
+        assert_eq!(hinge.minimum_order_qty, Some(Decimal::from(200)));
+        assert_eq!(hinge.order_multiple, Some(Decimal::from(120)));
+        assert_eq!(
+            configs.get("PANEL-R").unwrap().minimum_order_qty,
+            Some(Decimal::from(100))
+        );
+
+        // 右側板有 30 件庫存：期末多出 30 件右側板不成套
+        let inventories = HashMap::from([(
+            "PANEL-R".to_string(),
+            Inventory::new("PANEL-R".to_string(), Decimal::from(30), Decimal::ZERO),
+        )]);
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        let orders: Vec<PlannedOrder> = [("PANEL-L", 120), ("PANEL-R", 120), ("HINGE", 240)]
+            .into_iter()
+            .map(|(id, quantity)| {
+                PlannedOrder::new(
+                    id.to_string(),
+                    Decimal::from(quantity),
+                    date,
+                    date,
+                    PlannedOrderType::Purchase,
+                )
+            })
+            .collect();
+        let report = set.evaluate(&inventories, &HashMap::new(), &[], &orders);
+
+        assert_eq!(report.complete_sets, Decimal::from(120));
+        assert_eq!(
+            report.surplus,
+            vec![("PANEL-R".to_string(), Decimal::from(30))]
+        );
+        assert!(report.is_mismatched());
+    }
+
+    #[test]
+    fn test_set_minimum_rounds_up_to_whole_sets() {
+        let mut configs = ConfigSet::default();
+        configs.set(
+            MrpConfig::new("BOLT".to_string(), 2, ProcurementType::Buy)
+                .with_minimum_order_qty(Decimal::from(10)),
+        );
+        configs.set(MrpConfig::new("NUT".to_string(), 2, ProcurementType::Buy));
+        let set = MatchedSet::new("FASTENER-SET".to_string())
+            .with_component("BOLT".to_string(), Decimal::from(3))
+            .with_component("NUT".to_string(), Decimal::from(3));
+
+        set.harmonize(&mut configs);
+
+        // 10 / 3 套進位為 4 套 → 每種 12 件
+        for id in ["BOLT", "NUT"] {
+            assert_eq!(
+                configs.get(id).unwrap().minimum_order_qty,
+                Some(Decimal::from(12))
+            );
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! 齊套檢查：生產計劃訂單開工日檢查子件預計可用量，物料未齊時順延訂單或標記為物料受限
+//!
+//! 子件可用量 = 現有可用庫存 + 供應 + 子件計劃訂單（開工日已過的訂單按提前期自計劃起始日順推到貨），
+//! 依需求日期先到先得分配給子件的獨立需求與相依需求。
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, Inventory, PlannedOrder, PlannedOrderType, Supply, WorkCalendar};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 物料未齊時的處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum MaterialCheckMode {
+    /// 順延到所有子件都可用的最早日期（提前期不變）
+    Shift,
+
+    /// 不改日期，只標記為物料受限
+    Flag,
+}
+
+/// 物料受限的生產計劃訂單
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MaterialConstraint {
+    /// 計劃訂單ID
+    pub order_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 原開工日
+    pub order_date: NaiveDate,
+
+    /// 所有子件都可用的最早日期（None 表示現有供應與計劃訂單無法齊套）
+    pub available_date: Option<NaiveDate>,
+
+    /// 開工日可用量不足的子件
+    pub short_components: Vec<String>,
+
+    /// 是否已順延訂單
+    pub shifted: bool,
+}
+
+/// 齊套檢查器
+pub(crate) struct MaterialChecker<'a> {
+    inventories: &'a HashMap<String, Inventory>,
+    supplies: &'a HashMap<String, Vec<Supply>>,
+    independent_demands: &'a HashMap<String, Vec<Demand>>,
+    dependent_demands: &'a HashMap<String, Vec<Demand>>,
+    plan_start: NaiveDate,
+    calendar: &'a WorkCalendar,
+}
+
+impl<'a> MaterialChecker<'a> {
+    /// 創建檢查器
+    pub(crate) fn new(
+        inventories: &'a HashMap<String, Inventory>,
+        supplies: &'a HashMap<String, Vec<Supply>>,
+        independent_demands: &'a HashMap<String, Vec<Demand>>,
+        dependent_demands: &'a HashMap<String, Vec<Demand>>,
+        plan_start: NaiveDate,
+        calendar: &'a WorkCalendar,
+    ) -> Self {
+        Self {
+            inventories,
+            supplies,
+            independent_demands,
+            dependent_demands,
+            plan_start,
+            calendar,
+        }
+    }
+
+    /// 檢查所有生產計劃訂單，回傳物料受限的訂單
+    ///
+    /// 訂單按 MRP 計算順序反向（子件先於父件）檢查，子件訂單順延後父件以順延後的到貨日判斷。
+    /// 子件的需求分配以展開時的需求日期為準，父件順延不會釋放已分配的子件數量。
+    /// 無法對應來源訂單的相依需求（例如批次彙總後的需求）只參與子件的分配順序。
+    pub(crate) fn check(
+        &self,
+        orders: &mut [PlannedOrder],
+        mode: MaterialCheckMode,
+    ) -> Vec<MaterialConstraint> {
+        let mut children_by_order: HashMap<Uuid, Vec<(&str, Uuid)>> = HashMap::new();
+        for (child_id, demands) in self.dependent_demands {
+            for demand in demands {
+                let source_id = demand
+                    .source_ref
+                    .as_deref()
+                    .and_then(|r| r.rsplit(':').next())
+                    .and_then(|id| Uuid::parse_str(id).ok());
+                if let Some(order_id) = source_id {
+                    children_by_order
+                        .entry(order_id)
+                        .or_default()
+                        .push((child_id.as_str(), demand.id));
+                }
+            }
+        }
+
+        let mut components: Vec<String> = Vec::new();
+        for order in orders.iter() {
+            if !components.contains(&order.component_id) {
+                components.push(order.component_id.clone());
+            }
+        }
+
+        let mut ready_dates: HashMap<String, HashMap<Uuid, Option<NaiveDate>>> = HashMap::new();
+        let mut constraints = Vec::new();
+        for component_id in components.iter().rev() {
+            for index in 0..orders.len() {
+                let order = &orders[index];
+                if order.component_id != *component_id
+                    || order.order_type != PlannedOrderType::Production
+                {
+                    continue;
+                }
+                let Some(children) = children_by_order.get(&order.id) else {
+                    continue;
+                };
+
+                let mut available_date = Some(order.order_date);
+                let mut short_components: Vec<String> = Vec::new();
+                for &(child_id, demand_id) in children {
+                    if !ready_dates.contains_key(child_id) {
+                        let ready = self.ready_dates(child_id, orders);
+                        ready_dates.insert(child_id.to_string(), ready);
+                    }
+                    let ready = ready_dates[child_id]
+                        .get(&demand_id)
+                        .copied()
+                        .unwrap_or(Some(NaiveDate::MIN));
+                    if ready.map_or(true, |date| date > orders[index].order_date) {
+                        if !short_components.iter().any(|id| id == child_id) {
+                            short_components.push(child_id.to_string());
+                        }
+                        available_date = available_date.zip(ready).map(|(a, b)| a.max(b));
+                    }
+                }
+                if short_components.is_empty() {
+                    continue;
+                }
+
+                let order = &mut orders[index];
+                let original_date = order.order_date;
+                let shifted = match (mode, available_date) {
+                    (MaterialCheckMode::Shift, Some(date)) => {
+                        let lead_time = self
+                            .calendar
+                            .working_days_between(order.order_date, order.required_date);
+                        order.need_date.get_or_insert(order.required_date);
+                        order.order_date = date;
+                        order.required_date = self.calendar.add_working_days(date, lead_time);
+                        true
+                    }
+                    _ => false,
+                };
+                constraints.push(MaterialConstraint {
+                    order_id: order.id,
+                    component_id: order.component_id.clone(),
+                    order_date: original_date,
+                    available_date,
+                    short_components,
+                    shifted,
+                });
+            }
+        }
+        constraints
+    }
+
+    /// 子件每筆需求可滿足的最早日期（None 表示無法滿足；庫存即可滿足時為 `NaiveDate::MIN`）
+    fn ready_dates(
+        &self,
+        component_id: &str,
+        orders: &[PlannedOrder],
+    ) -> HashMap<Uuid, Option<NaiveDate>> {
+        let mut receipts: Vec<(NaiveDate, Decimal)> = self
+            .supplies
+            .get(component_id)
+            .into_iter()
+            .flatten()
+            .map(|s| (s.available_date, s.quantity))
+            .chain(
+                orders
+                    .iter()
+                    .filter(|o| o.component_id == component_id)
+                    .map(|o| (self.receipt_date(o), o.quantity)),
+            )
+            .collect();
+        receipts.sort_by_key(|(date, _)| *date);
+
+        let mut demands: Vec<&Demand> = self
+            .independent_demands
+            .get(component_id)
+            .into_iter()
+            .chain(self.dependent_demands.get(component_id))
+            .flatten()
+            .collect();
+        demands.sort_by_key(|d| d.required_date);
+
+        let mut available = self
+            .inventories
+            .get(component_id)
+            .map_or(Decimal::ZERO, |inv| inv.available_qty);
+        let mut ready = NaiveDate::MIN;
+        let mut next_receipt = receipts.iter();
+        let mut required = Decimal::ZERO;
+        let mut dates = HashMap::new();
+        for demand in demands {
+            required += demand.quantity;
+            let mut satisfied = available >= required;
+            while !satisfied {
+                let Some(&(date, quantity)) = next_receipt.next() else {
+                    break;
+                };
+                available += quantity;
+                ready = ready.max(date);
+                satisfied = available >= required;
+            }
+            dates.insert(demand.id, satisfied.then_some(ready));
+        }
+        dates
+    }
+
+    /// 計劃訂單的預計到貨日（開工日早於計劃起始日時，自計劃起始日按提前期順推）
+    fn receipt_date(&self, order: &PlannedOrder) -> NaiveDate {
+        if order.order_date >= self.plan_start {
+            return order.required_date;
+        }
+        let lead_time = self
+            .calendar
+            .working_days_between(order.order_date, order.required_date);
+        self.calendar
+            .add_working_days(self.plan_start, lead_time)
+            .max(order.required_date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{DemandType, SupplyType};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_shift_or_flag_until_children_available() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let inventories = HashMap::from([(
+            "PCB".to_string(),
+            Inventory::new("PCB".to_string(), Decimal::from(5), Decimal::ZERO),
+        )]);
+        let supplies = HashMap::from([(
+            "PCB".to_string(),
+            vec![Supply::new(
+                "PCB".to_string(),
+                Decimal::TEN,
+                date(14),
+                SupplyType::PurchaseOrder,
+            )],
+        )]);
+        let order = PlannedOrder::new(
+            "BOARD".to_string(),
+            Decimal::TEN,
+            date(12),
+            date(10),
+            PlannedOrderType::Production,
+        );
+        let dependent = HashMap::from([(
+            "PCB".to_string(),
+            vec![Demand::new(
+                "PCB".to_string(),
+                Decimal::TEN,
+                date(10),
+                DemandType::Dependent,
+            )
+            .with_source_ref(format!("BOARD:{}", order.id))],
+        )]);
+        let independent = HashMap::new();
+        let checker = MaterialChecker::new(
+            &inventories,
+            &supplies,
+            &independent,
+            &dependent,
+            date(3),
+            &calendar,
+        );
+
+        // 庫存 5 件不足 10 件，11/14 採購到貨後齊套
+        let mut flagged = vec![order.clone()];
+        let constraints = checker.check(&mut flagged, MaterialCheckMode::Flag);
+        assert_eq!(constraints.len(), 1);
+        assert_eq!(constraints[0].available_date, Some(date(14)));
+        assert_eq!(constraints[0].short_components, vec!["PCB".to_string()]);
+        assert!(!constraints[0].shifted);
+        assert_eq!(flagged[0].order_date, date(10));
+
+        let mut shifted = vec![order];
+        let constraints = checker.check(&mut shifted, MaterialCheckMode::Shift);
+        assert!(constraints[0].shifted);
+        assert_eq!(shifted[0].order_date, date(14));
+        assert_eq!(shifted[0].required_date, date(16));
+        assert_eq!(shifted[0].days_late(), 4);
+    }
+}
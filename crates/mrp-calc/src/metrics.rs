@@ -0,0 +1,113 @@
+//! 計算效能指標：逐物料的階段耗時與時間桶數量
+//!
+//! 用於找出拖慢計算的主資料異常（例如時間桶數量異常多的物料）。
+
+use std::time::Duration;
+
+/// 單物料計算耗時
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ItemTiming {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 時間桶數量（合併需求／供應日期後）
+    pub bucket_count: usize,
+
+    /// 產生的計劃訂單數量
+    pub planned_order_count: usize,
+
+    /// 淨需求計算耗時
+    pub netting: Duration,
+
+    /// 批量規則耗時
+    pub lot_sizing: Duration,
+
+    /// 產能約束耗時（僅產能約束模式）
+    pub capacity: Duration,
+
+    /// BOM 展開耗時
+    pub explosion: Duration,
+
+    /// 該物料總耗時
+    pub total: Duration,
+}
+
+impl ItemTiming {
+    /// 創建空的物料耗時記錄
+    pub fn new(component_id: String) -> Self {
+        Self {
+            component_id,
+            ..Default::default()
+        }
+    }
+}
+
+/// 單次計算的效能指標
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct RunMetrics {
+    /// 逐物料耗時（按計算順序）
+    pub items: Vec<ItemTiming>,
+}
+
+impl RunMetrics {
+    /// 查詢單物料耗時
+    pub fn item(&self, component_id: &str) -> Option<&ItemTiming> {
+        self.items.iter().find(|t| t.component_id == component_id)
+    }
+
+    /// 總耗時最長的前 N 個物料
+    pub fn slowest(&self, n: usize) -> Vec<&ItemTiming> {
+        let mut items: Vec<&ItemTiming> = self.items.iter().collect();
+        items.sort_by_key(|t| std::cmp::Reverse(t.total));
+        items.truncate(n);
+        items
+    }
+
+    /// 時間桶數量最多的前 N 個物料
+    pub fn largest_bucket_counts(&self, n: usize) -> Vec<&ItemTiming> {
+        let mut items: Vec<&ItemTiming> = self.items.iter().collect();
+        items.sort_by_key(|t| std::cmp::Reverse(t.bucket_count));
+        items.truncate(n);
+        items
+    }
+
+    /// 所有物料的時間桶總數
+    pub fn total_buckets(&self) -> usize {
+        self.items.iter().map(|t| t.bucket_count).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(component_id: &str, buckets: usize, total_ms: u64) -> ItemTiming {
+        ItemTiming {
+            bucket_count: buckets,
+            total: Duration::from_millis(total_ms),
+            ..ItemTiming::new(component_id.to_string())
+        }
+    }
+
+    #[test]
+    fn test_hotspots() {
+        let metrics = RunMetrics {
+            items: vec![
+                timing("A", 30, 2),
+                timing("B", 5000, 40),
+                timing("C", 120, 9),
+            ],
+        };
+
+        let slowest = metrics.slowest(2);
+        assert_eq!(slowest.len(), 2);
+        assert_eq!(slowest[0].component_id, "B");
+        assert_eq!(slowest[1].component_id, "C");
+
+        assert_eq!(metrics.largest_bucket_counts(1)[0].component_id, "B");
+        assert_eq!(metrics.total_buckets(), 5150);
+        assert!(metrics.item("A").is_some());
+    }
+}
@@ -5,6 +5,7 @@ use rust_decimal::Decimal;
 
 /// 淨需求計算結果
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct NetRequirement {
     /// 日期
     pub date: NaiveDate,
@@ -31,6 +32,38 @@ impl NetRequirement {
     }
 }
 
+/// 含計劃訂單的時間分段記錄（MRP 表格中的一期）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TimePhasedRecord {
+    /// 日期
+    pub date: NaiveDate,
+    /// 毛需求（獨立需求與相依需求）
+    pub gross_requirement: Decimal,
+    /// 預計收貨（現有供應）
+    pub scheduled_receipt: Decimal,
+    /// 計劃訂單入庫
+    pub planned_receipt: Decimal,
+    /// 當日結束時的預計庫存（含計劃訂單入庫）
+    pub projected_on_hand: Decimal,
+    /// 淨需求（計劃訂單入庫前低於安全庫存的數量）
+    pub net_requirement: Decimal,
+}
+
+impl TimePhasedRecord {
+    /// 創建新的時間分段記錄
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            gross_requirement: Decimal::ZERO,
+            scheduled_receipt: Decimal::ZERO,
+            planned_receipt: Decimal::ZERO,
+            projected_on_hand: Decimal::ZERO,
+            net_requirement: Decimal::ZERO,
+        }
+    }
+}
+
 /// 淨需求計算器
 pub struct NettingCalculator;
 
@@ -100,6 +133,23 @@ impl NettingCalculator {
 
         Ok(results)
     }
+
+    /// 以結構化 tracing 事件輸出單一物料的每個淨需求時間桶
+    #[cfg(feature = "netting-trace")]
+    pub fn trace(component_id: &str, net_requirements: &[NetRequirement]) {
+        for row in net_requirements {
+            tracing::debug!(
+                target: "mrp_calc::netting",
+                item = component_id,
+                date = %row.date,
+                gross = %row.gross_requirement,
+                receipts = %row.scheduled_receipt,
+                poh = %row.projected_on_hand,
+                net = %row.net_requirement,
+                "netting bucket"
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -345,4 +395,86 @@ mod tests {
         // 允許負庫存模式：庫存為正，不產生淨需求（即使低於安全庫存）
         assert_eq!(result[0].net_requirement, Decimal::ZERO);
     }
+
+    #[cfg(feature = "netting-trace")]
+    mod trace {
+        use crate::fixtures::{bom_graph, calculator, config, date, demand};
+        use mrp_core::{Inventory, ProcurementType};
+        use rust_decimal::Decimal;
+        use std::collections::BTreeMap;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::{span, Event, Metadata, Subscriber};
+
+        type Row = BTreeMap<&'static str, String>;
+
+        /// 收集 `mrp_calc::netting` 事件欄位的最小 subscriber
+        #[derive(Default, Clone)]
+        struct Recorder {
+            rows: Arc<Mutex<Vec<Row>>>,
+        }
+
+        struct RowVisitor<'a>(&'a mut Row);
+
+        impl Visit for RowVisitor<'_> {
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.insert(field.name(), value.to_string());
+            }
+
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0.insert(field.name(), format!("{value:?}"));
+            }
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+                metadata.target() == "mrp_calc::netting"
+            }
+
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut row = Row::new();
+                event.record(&mut RowVisitor(&mut row));
+                self.rows.lock().unwrap().push(row);
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        #[test]
+        fn test_netting_trace_emits_bucket_rows() {
+            let calculator =
+                calculator(bom_graph([]), [config("BIKE-001", 2, ProcurementType::Buy)]);
+            let inventory =
+                Inventory::new("BIKE-001".to_string(), Decimal::from(30), Decimal::ZERO);
+            let recorder = Recorder::default();
+
+            tracing::subscriber::with_default(recorder.clone(), || {
+                calculator
+                    .calculate(vec![demand("BIKE-001", 100, 20)], vec![], vec![inventory])
+                    .unwrap()
+            });
+
+            let rows = recorder.rows.lock().unwrap();
+            let row = rows
+                .iter()
+                .find(|row| row["date"] == date(20).to_string())
+                .expect("需求日應有淨需求事件");
+            assert!(rows.iter().all(|row| row["item"] == "BIKE-001"));
+            assert_eq!(row["message"], "netting bucket");
+            assert_eq!(row["gross"], "100");
+            assert_eq!(row["receipts"], "0");
+            assert_eq!(row["poh"], "-70");
+            assert_eq!(row["net"], "70");
+        }
+    }
 }
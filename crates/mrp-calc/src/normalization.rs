@@ -0,0 +1,177 @@
+//! 輸入正規化：將落在非工作日的需求／供應日期移到工作日
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, Supply, WorkCalendar};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 非工作日日期的調整方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum DateSnapPolicy {
+    /// 移到前一個工作日（保守：需求提前）
+    PreviousWorkingDay,
+    /// 移到下一個工作日
+    NextWorkingDay,
+}
+
+/// 被調整的記錄類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AdjustedRecordKind {
+    /// 需求
+    Demand,
+    /// 供應
+    Supply,
+}
+
+/// 單筆日期調整
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DateAdjustment {
+    /// 記錄類型
+    pub kind: AdjustedRecordKind,
+
+    /// 需求ID或供應ID
+    pub record_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 原始日期
+    pub original_date: NaiveDate,
+
+    /// 調整後日期
+    pub adjusted_date: NaiveDate,
+}
+
+/// 日期正規化器
+pub struct DateNormalizer;
+
+impl DateNormalizer {
+    /// 將需求日期與供應可用日期移到工作日，回傳每筆調整
+    ///
+    /// 需求與供應使用同一方向，確保同一天的供需調整後仍在同一時間桶
+    pub fn normalize(
+        demands: &mut [Demand],
+        supplies: &mut [Supply],
+        calendar: &WorkCalendar,
+        policy: DateSnapPolicy,
+    ) -> Vec<DateAdjustment> {
+        let mut adjustments = Vec::new();
+
+        for demand in demands.iter_mut() {
+            if let Some(adjusted) = Self::snap(demand.required_date, calendar, policy) {
+                adjustments.push(DateAdjustment {
+                    kind: AdjustedRecordKind::Demand,
+                    record_id: demand.id,
+                    component_id: demand.component_id.clone(),
+                    original_date: demand.required_date,
+                    adjusted_date: adjusted,
+                });
+                demand.required_date = adjusted;
+            }
+        }
+
+        for supply in supplies.iter_mut() {
+            if let Some(adjusted) = Self::snap(supply.available_date, calendar, policy) {
+                adjustments.push(DateAdjustment {
+                    kind: AdjustedRecordKind::Supply,
+                    record_id: supply.id,
+                    component_id: supply.component_id.clone(),
+                    original_date: supply.available_date,
+                    adjusted_date: adjusted,
+                });
+                supply.available_date = adjusted;
+            }
+        }
+
+        adjustments
+    }
+
+    /// 非工作日時回傳調整後日期
+    fn snap(date: NaiveDate, calendar: &WorkCalendar, policy: DateSnapPolicy) -> Option<NaiveDate> {
+        if calendar.is_working_day(date) {
+            return None;
+        }
+        Some(match policy {
+            DateSnapPolicy::PreviousWorkingDay => calendar.previous_working_day(date),
+            DateSnapPolicy::NextWorkingDay => calendar.next_working_day(date),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{DemandType, SupplyType};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_snap_sunday_dates() {
+        let calendar = WorkCalendar::new("STD".to_string());
+        // 2025-11-09 是週日
+        let sunday = NaiveDate::from_ymd_opt(2025, 11, 9).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+
+        let mut demands = vec![
+            Demand::new(
+                "BIKE-001".to_string(),
+                Decimal::from(10),
+                sunday,
+                DemandType::SalesOrder,
+            ),
+            Demand::new(
+                "BIKE-001".to_string(),
+                Decimal::from(10),
+                monday,
+                DemandType::SalesOrder,
+            ),
+        ];
+        let mut supplies = vec![Supply::new(
+            "BIKE-001".to_string(),
+            Decimal::from(5),
+            sunday,
+            SupplyType::PurchaseOrder,
+        )];
+
+        let adjustments = DateNormalizer::normalize(
+            &mut demands,
+            &mut supplies,
+            &calendar,
+            DateSnapPolicy::PreviousWorkingDay,
+        );
+
+        let friday = NaiveDate::from_ymd_opt(2025, 11, 7).unwrap();
+        assert_eq!(adjustments.len(), 2);
+        assert_eq!(adjustments[0].kind, AdjustedRecordKind::Demand);
+        assert_eq!(adjustments[0].original_date, sunday);
+        assert_eq!(demands[0].required_date, friday);
+        assert_eq!(demands[1].required_date, monday);
+        assert_eq!(supplies[0].available_date, friday);
+    }
+
+    #[test]
+    fn test_snap_to_next_working_day() {
+        let calendar = WorkCalendar::new("STD".to_string());
+        let saturday = NaiveDate::from_ymd_opt(2025, 11, 8).unwrap();
+        let mut demands = vec![Demand::new(
+            "BIKE-001".to_string(),
+            Decimal::from(10),
+            saturday,
+            DemandType::SalesOrder,
+        )];
+
+        DateNormalizer::normalize(
+            &mut demands,
+            &mut [],
+            &calendar,
+            DateSnapPolicy::NextWorkingDay,
+        );
+
+        assert_eq!(
+            demands[0].required_date,
+            NaiveDate::from_ymd_opt(2025, 11, 10).unwrap()
+        );
+    }
+}
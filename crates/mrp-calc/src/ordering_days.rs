@@ -0,0 +1,145 @@
+//! 固定下單日：供應商只在特定星期幾接單時，將採購計劃訂單的下單日對齊到下單日
+
+use chrono::{Datelike, NaiveDate};
+use mrp_core::{PlannedOrder, WorkCalendar};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 對齊下單日後無法在需要日期前到貨的訂單
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LateRelease {
+    /// 計劃訂單ID
+    pub(crate) order_id: Uuid,
+    /// 計劃起始日後最早的下單日
+    pub(crate) order_date: NaiveDate,
+    /// 順延後的預計到貨日
+    pub(crate) required_date: NaiveDate,
+    /// 原到貨日
+    pub(crate) need_date: NaiveDate,
+}
+
+/// 供應商的固定下單星期
+#[derive(Debug, Clone, Default)]
+pub struct OrderingDays {
+    /// 供應商ID → 可下單的星期（索引 0 = 週一，與 `WorkCalendar::working_days` 相同）
+    suppliers: HashMap<String, [bool; 7]>,
+}
+
+impl OrderingDays {
+    /// 創建空的設定（所有供應商每天都可下單）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 建構器模式：設置供應商的可下單星期
+    pub fn with_supplier(mut self, supplier_id: String, weekdays: [bool; 7]) -> Self {
+        self.suppliers.insert(supplier_id, weekdays);
+        self
+    }
+
+    /// 供應商在指定日期是否可下單（未設置的供應商每天都可下單）
+    pub fn is_ordering_day(&self, supplier_id: &str, date: NaiveDate) -> bool {
+        self.suppliers.get(supplier_id).map_or(true, |weekdays| {
+            weekdays[date.weekday().num_days_from_monday() as usize]
+        })
+    }
+
+    /// 將採購計劃訂單的下單日提前到不晚於原下單日的最近下單日，回傳（調整數, 延遲訂單）
+    ///
+    /// 提前後早於 `plan_start` 的訂單提前期不足：改在計劃起始日後最早的下單日下單，
+    /// 到貨日按原提前期（生產日曆工作日數）順延，原到貨日記錄為需要日期。
+    /// 沒有任何可下單星期的供應商不調整。
+    pub(crate) fn align(
+        &self,
+        orders: &mut [PlannedOrder],
+        plan_start: NaiveDate,
+        calendar: &WorkCalendar,
+    ) -> (usize, Vec<LateRelease>) {
+        let mut adjusted = 0;
+        let mut late = Vec::new();
+        for order in orders.iter_mut().filter(|o| o.is_purchase()) {
+            let Some(supplier_id) = order.source_id.clone() else {
+                continue;
+            };
+            if !self
+                .suppliers
+                .get(&supplier_id)
+                .is_some_and(|weekdays| weekdays.contains(&true))
+                || self.is_ordering_day(&supplier_id, order.order_date)
+            {
+                continue;
+            }
+
+            let mut release = order.order_date;
+            while !self.is_ordering_day(&supplier_id, release) {
+                release = release.pred_opt().expect("日期溢出");
+            }
+            adjusted += 1;
+            if release >= plan_start {
+                order.order_date = release;
+                continue;
+            }
+
+            // 提前期不足：計劃起始日後最早的下單日下單，到貨日順延
+            let lead_time = calendar.working_days_between(order.order_date, order.required_date);
+            let mut release = plan_start;
+            while !self.is_ordering_day(&supplier_id, release) {
+                release = release.succ_opt().expect("日期溢出");
+            }
+            let need_date = *order.need_date.get_or_insert(order.required_date);
+            order.order_date = release;
+            order.required_date = calendar.add_working_days(release, lead_time);
+            if order.required_date > need_date {
+                late.push(LateRelease {
+                    order_id: order.id,
+                    order_date: order.order_date,
+                    required_date: order.required_date,
+                    need_date,
+                });
+            }
+        }
+        (adjusted, late)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::PlannedOrderType;
+    use rust_decimal::Decimal;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_align_to_tuesday_ordering() {
+        const TUESDAY: [bool; 7] = [false, true, false, false, false, false, false];
+        let ordering_days = OrderingDays::new().with_supplier("SUP-A".to_string(), TUESDAY);
+        let calendar = WorkCalendar::new("PLANT".to_string());
+        let order = |order_day: u32, required_day: u32| {
+            PlannedOrder::new(
+                "RESIN".to_string(),
+                Decimal::TEN,
+                date(required_day),
+                date(order_day),
+                PlannedOrderType::Purchase,
+            )
+            .with_source_id("SUP-A".to_string())
+        };
+        // 11/13（週四）下單 → 提前到 11/11（週二）；
+        // 11/6（週四）下單、計劃起始日 11/5 → 前一個週二 11/4 已過，改 11/11 下單，到貨順延一週
+        let mut orders = vec![order(13, 17), order(6, 10)];
+
+        let (adjusted, late) = ordering_days.align(&mut orders, date(5), &calendar);
+
+        assert_eq!(adjusted, 2);
+        assert_eq!(orders[0].order_date, date(11));
+        assert_eq!(orders[0].required_date, date(17));
+        assert_eq!(orders[1].order_date, date(11));
+        assert_eq!(orders[1].required_date, date(13));
+        assert_eq!(orders[1].days_late(), 3);
+        assert_eq!(late.len(), 1);
+        assert_eq!(late[0].order_id, orders[1].id);
+    }
+}
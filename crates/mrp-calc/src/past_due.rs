@@ -0,0 +1,168 @@
+//! 逾期訂單處理：倒排開工日早於計劃起始日時，依政策壓縮提前期、自起始日順排或讓物料計算失敗
+
+use chrono::NaiveDate;
+use mrp_core::{MrpConfig, MrpError, PlannedOrder, WorkCalendar};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::lead_time::LeadTimeCalculator;
+
+/// 逾期訂單處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum PastDuePolicy {
+    /// 開工日移到計劃起始日、到貨日不變（壓縮提前期）
+    CompressLeadTime,
+
+    /// 自計劃起始日按完整提前期順排（到貨延遲）
+    ScheduleAtStart,
+
+    /// 物料計算失敗（回報錯誤、該物料不產生計劃訂單，其他物料照常計算）
+    Fail,
+}
+
+/// 已調整的逾期訂單
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PastDueAdjustment {
+    /// 計劃訂單ID
+    pub order_id: Uuid,
+
+    /// 原倒排開工日
+    pub original_order_date: NaiveDate,
+
+    /// 調整後開工日
+    pub order_date: NaiveDate,
+
+    /// 調整後到貨日
+    pub required_date: NaiveDate,
+
+    /// 原需要日期
+    pub need_date: NaiveDate,
+}
+
+impl PastDueAdjustment {
+    /// 到貨晚於需要日期的天數
+    pub fn days_late(&self) -> i64 {
+        (self.required_date - self.need_date).num_days().max(0)
+    }
+}
+
+/// 逾期訂單處理器
+pub(crate) struct PastDueHandler;
+
+impl PastDueHandler {
+    /// 調整開工日早於計劃起始日的訂單（起始日不是工作日時以下一個工作日為準）
+    ///
+    /// 需要日期本身已早於起始日時，壓縮提前期也只能在起始日到貨。
+    pub(crate) fn apply(
+        orders: &mut [PlannedOrder],
+        config: &MrpConfig,
+        plan_start: NaiveDate,
+        calendar: &WorkCalendar,
+        policy: PastDuePolicy,
+    ) -> mrp_core::Result<Vec<PastDueAdjustment>> {
+        let start = if calendar.is_working_day(plan_start) {
+            plan_start
+        } else {
+            calendar.next_working_day(plan_start)
+        };
+
+        let mut adjustments = Vec::new();
+        for order in orders.iter_mut().filter(|o| o.order_date < plan_start) {
+            let original_order_date = order.order_date;
+            let need_date = *order.need_date.get_or_insert(order.required_date);
+            match policy {
+                PastDuePolicy::Fail => {
+                    return Err(MrpError::CalculationError(format!(
+                        "物料 {} 的計劃訂單 {} 倒排開工日 {} 早於計劃起始日 {}",
+                        order.component_id, order.id, original_order_date, plan_start
+                    )));
+                }
+                PastDuePolicy::CompressLeadTime => {
+                    order.required_date = order.required_date.max(start);
+                }
+                PastDuePolicy::ScheduleAtStart => {
+                    let lead_time = config.lead_time_for(order.quantity)
+                        + config.transit_days(order.transport_mode.as_deref());
+                    order.required_date =
+                        LeadTimeCalculator::calculate_delivery_date(start, lead_time, calendar);
+                }
+            }
+            order.order_date = start;
+            if order.required_date == need_date {
+                order.need_date = None;
+            }
+            adjustments.push(PastDueAdjustment {
+                order_id: order.id,
+                original_order_date,
+                order_date: order.order_date,
+                required_date: order.required_date,
+                need_date,
+            });
+        }
+        Ok(adjustments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{PlannedOrderType, ProcurementType};
+    use rust_decimal::Decimal;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_compress_schedule_or_fail() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let config = MrpConfig::new("CASTING".to_string(), 6, ProcurementType::Make);
+        // 11/8 需要、提前期 6 天，倒排開工日 11/2 早於起始日 11/5
+        let orders = vec![PlannedOrder::new(
+            "CASTING".to_string(),
+            Decimal::TEN,
+            date(8),
+            date(2),
+            PlannedOrderType::Production,
+        )];
+
+        let mut compressed = orders.clone();
+        let adjustments = PastDueHandler::apply(
+            &mut compressed,
+            &config,
+            date(5),
+            &calendar,
+            PastDuePolicy::CompressLeadTime,
+        )
+        .unwrap();
+        assert_eq!(adjustments[0].days_late(), 0);
+        assert_eq!(compressed[0].order_date, date(5));
+        assert_eq!(compressed[0].required_date, date(8));
+        assert_eq!(compressed[0].need_date, None);
+
+        let mut scheduled = orders.clone();
+        let adjustments = PastDueHandler::apply(
+            &mut scheduled,
+            &config,
+            date(5),
+            &calendar,
+            PastDuePolicy::ScheduleAtStart,
+        )
+        .unwrap();
+        assert_eq!(scheduled[0].required_date, date(11));
+        assert_eq!(scheduled[0].need_date, Some(date(8)));
+        assert_eq!(adjustments[0].days_late(), 3);
+
+        let mut failed = orders;
+        assert!(PastDueHandler::apply(
+            &mut failed,
+            &config,
+            date(5),
+            &calendar,
+            PastDuePolicy::Fail
+        )
+        .is_err());
+    }
+}
@@ -2,11 +2,13 @@
 
 use mrp_core::{Demand, DemandType, PeggingRecord, PlannedOrder};
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
 /// 追溯類型
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum PeggingType {
     /// 單層追溯
     SingleLevel,
@@ -27,10 +29,11 @@ impl PeggingCalculator {
         let mut pegging_map = HashMap::new();
 
         for order in planned_orders {
+            // 排程改變完工日時，以原需要日期追溯
             let pegging = Self::trace_demand_source(
                 &order.component_id,
                 order.quantity,
-                order.required_date,
+                order.need_date.unwrap_or(order.required_date),
                 original_demands,
                 pegging_type,
             )?;
@@ -88,9 +91,10 @@ impl PeggingCalculator {
                 }
             };
 
-            pegging_records.push(
-                PeggingRecord::new(demand.id, pegged_qty).with_path(path),
-            );
+            let mut record = PeggingRecord::new(demand.id, pegged_qty).with_path(path);
+            record.source_document = demand.source_document.clone();
+            record.value = demand.unit_value.map(|unit_value| unit_value * pegged_qty);
+            pegging_records.push(record);
 
             remaining_qty -= pegged_qty;
         }
@@ -151,6 +155,38 @@ mod tests {
         assert_eq!(pegging_records[0].path, vec!["COMP-001".to_string()]);
     }
 
+    #[test]
+    fn test_pegging_carries_source_document() {
+        let planned_order = PlannedOrder::new(
+            "COMP-001".to_string(),
+            Decimal::from(100),
+            NaiveDate::from_ymd_opt(2025, 11, 8).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            PlannedOrderType::Production,
+        );
+        let document = mrp_core::SourceDocument::new("SO".to_string(), "10045".to_string())
+            .with_line(20)
+            .with_system_id("SAP-EU".to_string());
+        let demand = Demand::new(
+            "COMP-001".to_string(),
+            Decimal::from(100),
+            NaiveDate::from_ymd_opt(2025, 11, 8).unwrap(),
+            DemandType::SalesOrder,
+        )
+        .with_source_document(document.clone());
+
+        let result = PeggingCalculator::perform(
+            std::slice::from_ref(&planned_order),
+            std::slice::from_ref(&demand),
+            PeggingType::SingleLevel,
+        )
+        .unwrap();
+
+        let record = &result[&planned_order.id][0];
+        assert_eq!(record.source_document.as_ref(), Some(&document));
+        assert_eq!(record.source_document.as_ref().unwrap().line, Some(20));
+    }
+
     #[test]
     fn test_multi_level_pegging_with_dependent_demand() {
         // 建立計劃訂單（子件）
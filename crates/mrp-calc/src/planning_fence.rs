@@ -0,0 +1,113 @@
+//! 計劃時界：時界內不產生也不移動計劃訂單，時界內的需求改由計劃員催交現有供應處理
+
+use chrono::NaiveDate;
+use mrp_core::{MrpConfig, PlannedOrder, WorkCalendar};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// 落在計劃時界內的需求
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FencedRequirement {
+    /// 原計劃訂單ID
+    pub order_id: Uuid,
+
+    /// 需要日期
+    pub need_date: NaiveDate,
+
+    /// 數量
+    pub quantity: Decimal,
+}
+
+/// 計劃時界
+pub struct PlanningTimeFence;
+
+impl PlanningTimeFence {
+    /// 將到貨日早於時界邊界的計劃訂單合併為一張、順延到邊界到貨
+    ///
+    /// 合併後的數量重新套用訂購修正，沿用最早一張訂單的ID並保留其需要日期，
+    /// 追溯記錄併入合併後的訂單。回傳時界內的需求（依需要日期排序）供發出催交警告。
+    pub fn apply(
+        orders: &mut Vec<PlannedOrder>,
+        fence_end: NaiveDate,
+        config: &MrpConfig,
+        calendar: &WorkCalendar,
+    ) -> Vec<FencedRequirement> {
+        let (mut fenced, outside): (Vec<PlannedOrder>, Vec<PlannedOrder>) =
+            orders.drain(..).partition(|o| o.required_date < fence_end);
+        *orders = outside;
+        if fenced.is_empty() {
+            return Vec::new();
+        }
+        fenced.sort_by_key(|o| o.need_date.unwrap_or(o.required_date));
+
+        let requirements: Vec<FencedRequirement> = fenced
+            .iter()
+            .map(|o| FencedRequirement {
+                order_id: o.id,
+                need_date: o.need_date.unwrap_or(o.required_date),
+                quantity: o.quantity,
+            })
+            .collect();
+
+        let total: Decimal = fenced.iter().map(|o| o.quantity).sum();
+        let pegging: Vec<_> = fenced.iter().flat_map(|o| o.pegging.clone()).collect();
+        for (index, quantity) in config.split_order_quantity(total).into_iter().enumerate() {
+            let mut order = fenced[0].clone();
+            order.id = fenced.get(index).map(|o| o.id).unwrap_or_else(Uuid::new_v4);
+            order.quantity = quantity;
+            order.need_date = Some(requirements[0].need_date);
+            order.required_date = fence_end;
+            order.order_date =
+                calendar.subtract_working_days(fence_end, config.lead_time_for(quantity));
+            order.pegging = if index == 0 {
+                pegging.clone()
+            } else {
+                Vec::new()
+            };
+            orders.insert(index, order);
+        }
+
+        requirements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{PlannedOrderType, ProcurementType};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_push_fenced_orders_to_boundary() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let config = MrpConfig::new("MOTOR".to_string(), 2, ProcurementType::Buy);
+        let order = |quantity: i64, day: u32| {
+            PlannedOrder::new(
+                "MOTOR".to_string(),
+                Decimal::from(quantity),
+                date(day),
+                date(day - 2),
+                PlannedOrderType::Purchase,
+            )
+        };
+        let mut orders = vec![order(20, 6), order(10, 4), order(40, 15)];
+        let earliest_id = orders[1].id;
+
+        let fenced = PlanningTimeFence::apply(&mut orders, date(8), &config, &calendar);
+
+        // 11/4 與 11/6 的需求在時界內，合併為 11/8 到貨的一張訂單
+        assert_eq!(fenced.len(), 2);
+        assert_eq!(fenced[0].need_date, date(4));
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].id, earliest_id);
+        assert_eq!(orders[0].quantity, Decimal::from(30));
+        assert_eq!(orders[0].required_date, date(8));
+        assert_eq!(orders[0].order_date, date(6));
+        assert_eq!(orders[0].need_date, Some(date(4)));
+        assert_eq!(orders[1].required_date, date(15));
+    }
+}
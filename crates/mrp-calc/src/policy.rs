@@ -0,0 +1,253 @@
+//! 計劃政策：單次計算的全域行為設定（可序列化，隨計算結果保存與版本化）
+
+use chrono::NaiveDate;
+use mrp_core::RoundingPolicy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::allocation::AllocationStrategy;
+use crate::bucketing::BucketingStrategy;
+use crate::material_check::MaterialCheckMode;
+use crate::normalization::DateSnapPolicy;
+use crate::past_due::PastDuePolicy;
+use crate::pegging::PeggingType;
+use crate::scenario::ConfigSet;
+
+/// 帶引擎標記（[`mrp_core::ENGINE_SUPPLY_TAG`]）的未確認計劃供應處理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum PlannedSupplyPolicy {
+    /// 忽略，由本次計算的計劃訂單取代（預設）
+    #[default]
+    Replace,
+
+    /// 視為一般供應計入（前次計劃的訂單會與本次計劃重複）
+    Keep,
+}
+
+/// 計劃政策
+///
+/// 物料層級的設定（負庫存、取整）只在物料未另行設置時作為預設值；
+/// 其餘為整次計算的行為開關。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct PlanningPolicy {
+    /// 所有物料允許負庫存（false 時依物料配置）
+    pub allow_negative_inventory: bool,
+
+    /// 預設取整方式（套用於取整方式為 `AllowFractional` 的物料；None 表示不套用）
+    pub rounding: Option<RoundingPolicy>,
+
+    /// 固定週期時間桶（None 表示只以需求／供應日期分桶）
+    pub bucketing: Option<BucketingStrategy>,
+
+    /// 需求追溯類型
+    pub pegging_type: PeggingType,
+
+    /// 共用子件分配策略（None 表示不執行分配檢查）
+    pub allocation_strategy: Option<AllocationStrategy>,
+
+    /// 是否按（子件, 日期）批次彙總相依需求
+    pub batch_dependent_demands: bool,
+
+    /// 是否將採購改派給承諾量未達成的供應商
+    pub commitment_sourcing: bool,
+
+    /// 慢動分析窗口（天；None 表示不分析）
+    pub slow_mover_window_days: Option<u32>,
+
+    /// 非工作日輸入日期的調整方向（None 表示不調整）
+    pub date_snap_policy: Option<DateSnapPolicy>,
+
+    /// BOM 用量合理上限（超過時警告；None 表示不檢查）
+    pub bom_quantity_threshold: Option<rust_decimal::Decimal>,
+
+    /// 計算時間預算（超過時回傳部分結果；None 表示不限制）
+    pub time_budget: Option<Duration>,
+
+    /// 逐層追蹤檔路徑（除錯用；None 表示不追蹤）
+    pub level_trace_path: Option<PathBuf>,
+
+    /// 前次計算產生、回傳為未確認計劃供應的處理方式
+    pub planned_supply_policy: PlannedSupplyPolicy,
+
+    /// 生產計劃訂單的齊套檢查（None 表示不檢查）
+    pub material_check: Option<MaterialCheckMode>,
+
+    /// 計劃起始日（計算當天；None 表示以最早的需求或供應日期為起始日）
+    pub plan_start_date: Option<NaiveDate>,
+
+    /// 倒排開工日早於計劃起始日時的處理方式（None 表示依物料排程方式，純倒排時只發警告）
+    pub past_due: Option<PastDuePolicy>,
+
+    /// 現有供應重排建議的容差（天；日期差不超過此值時不發建議）
+    pub reschedule_tolerance_days: u32,
+
+    /// 隨機模組（蒙地卡羅模擬等）的亂數種子，記錄於執行清單
+    pub seed: u64,
+}
+
+impl Default for PlanningPolicy {
+    fn default() -> Self {
+        Self {
+            allow_negative_inventory: false,
+            rounding: None,
+            bucketing: None,
+            pegging_type: PeggingType::MultiLevel,
+            allocation_strategy: None,
+            batch_dependent_demands: false,
+            commitment_sourcing: false,
+            slow_mover_window_days: None,
+            date_snap_policy: None,
+            bom_quantity_threshold: None,
+            time_budget: None,
+            level_trace_path: None,
+            planned_supply_policy: PlannedSupplyPolicy::default(),
+            material_check: None,
+            plan_start_date: None,
+            past_due: None,
+            reschedule_tolerance_days: 0,
+            seed: 0,
+        }
+    }
+}
+
+impl PlanningPolicy {
+    /// 創建預設政策
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 建構器模式：所有物料允許負庫存
+    pub fn with_allow_negative_inventory(mut self, allow: bool) -> Self {
+        self.allow_negative_inventory = allow;
+        self
+    }
+
+    /// 建構器模式：設置預設取整方式
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = Some(rounding);
+        self
+    }
+
+    /// 建構器模式：設置固定週期時間桶
+    pub fn with_bucketing(mut self, strategy: BucketingStrategy) -> Self {
+        self.bucketing = Some(strategy);
+        self
+    }
+
+    /// 建構器模式：設置需求追溯類型
+    pub fn with_pegging_type(mut self, pegging_type: PeggingType) -> Self {
+        self.pegging_type = pegging_type;
+        self
+    }
+
+    /// 建構器模式：設置前次計劃供應的處理方式
+    pub fn with_planned_supply_policy(mut self, policy: PlannedSupplyPolicy) -> Self {
+        self.planned_supply_policy = policy;
+        self
+    }
+
+    /// 建構器模式：開工日檢查子件是否齊套
+    pub fn with_material_check(mut self, mode: MaterialCheckMode) -> Self {
+        self.material_check = Some(mode);
+        self
+    }
+
+    /// 建構器模式：設置計劃起始日
+    ///
+    /// 早於起始日的需求與供應視為逾期，移到起始日計算；計劃訂單的開工日不早於起始日。
+    pub fn with_plan_start_date(mut self, date: NaiveDate) -> Self {
+        self.plan_start_date = Some(date);
+        self
+    }
+
+    /// 建構器模式：設置逾期訂單處理方式
+    pub fn with_past_due(mut self, policy: PastDuePolicy) -> Self {
+        self.past_due = Some(policy);
+        self
+    }
+
+    /// 建構器模式：設置重排建議容差（天）
+    pub fn with_reschedule_tolerance(mut self, days: u32) -> Self {
+        self.reschedule_tolerance_days = days;
+        self
+    }
+
+    /// 建構器模式：設置亂數種子
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// 以政策的物料預設值覆寫配置（只覆寫需要變更的物料）
+    pub(crate) fn apply_item_defaults(&self, configs: &mut ConfigSet) {
+        let changed: Vec<_> = configs
+            .values()
+            .filter_map(|config| {
+                let negative = self.allow_negative_inventory && !config.allow_negative_inventory;
+                let rounding = self
+                    .rounding
+                    .filter(|_| config.rounding_policy == RoundingPolicy::AllowFractional);
+                if !negative && rounding.is_none() {
+                    return None;
+                }
+
+                let mut config = config.clone();
+                config.allow_negative_inventory |= negative;
+                if let Some(rounding) = rounding {
+                    config.rounding_policy = rounding;
+                }
+                Some(config)
+            })
+            .collect();
+
+        for config in changed {
+            configs.set(config);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{MrpConfig, ProcurementType};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_policy_roundtrip_and_item_defaults() {
+        let policy = PlanningPolicy::new()
+            .with_rounding(RoundingPolicy::RoundUpToUnit)
+            .with_bucketing(BucketingStrategy::Weekly)
+            .with_pegging_type(PeggingType::SingleLevel);
+        let json = serde_json::to_string(&policy).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PlanningPolicy>(&json).unwrap(),
+            policy
+        );
+        // 舊版保存的政策缺少欄位時使用預設值
+        assert_eq!(
+            serde_json::from_str::<PlanningPolicy>("{}").unwrap(),
+            PlanningPolicy::default()
+        );
+
+        let mut configs = ConfigSet::default();
+        configs.set(MrpConfig::new("BOLT".to_string(), 1, ProcurementType::Buy));
+        configs.set(
+            MrpConfig::new("RESIN".to_string(), 1, ProcurementType::Buy)
+                .with_rounding_policy(RoundingPolicy::RoundToMultiple(Decimal::from(25))),
+        );
+        policy.apply_item_defaults(&mut configs);
+
+        assert_eq!(
+            configs.get("BOLT").unwrap().rounding_policy,
+            RoundingPolicy::RoundUpToUnit
+        );
+        assert_eq!(
+            configs.get("RESIN").unwrap().rounding_policy,
+            RoundingPolicy::RoundToMultiple(Decimal::from(25))
+        );
+    }
+}
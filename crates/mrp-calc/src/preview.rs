@@ -0,0 +1,142 @@
+//! 互動預覽：在時間預算內計算近似計劃
+//!
+//! 預覽只計算頂層物料或前 K 週，超過時間預算即停止，讓介面在數秒內顯示粗略計劃；
+//! 完整計算可同時在背景以 `MrpCalculator::calculate` 進行。
+
+use chrono::{Duration as DateDuration, NaiveDate};
+use mrp_core::{Demand, Supply};
+use std::time::{Duration, Instant};
+
+use crate::MrpResult;
+
+/// 預覽選項
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PreviewOptions {
+    /// 時間預算（逐物料計算超過時停止）
+    pub time_budget: Duration,
+
+    /// 只計算有獨立需求的頂層物料（不展開 BOM）
+    pub top_level_only: bool,
+
+    /// 只計算前 K 週的需求與供應（自最早需求日期起算）
+    pub weeks: Option<u32>,
+}
+
+impl PreviewOptions {
+    /// 創建預覽選項
+    pub fn new(time_budget: Duration) -> Self {
+        Self {
+            time_budget,
+            top_level_only: false,
+            weeks: None,
+        }
+    }
+
+    /// 建構器模式：只計算頂層物料
+    pub fn with_top_level_only(mut self) -> Self {
+        self.top_level_only = true;
+        self
+    }
+
+    /// 建構器模式：只計算前 K 週
+    pub fn with_weeks(mut self, weeks: u32) -> Self {
+        self.weeks = Some(weeks);
+        self
+    }
+
+    /// 依週數範圍篩選需求與供應，回傳範圍結束日（不含）
+    pub(crate) fn restrict_horizon(
+        &self,
+        demands: &mut Vec<Demand>,
+        supplies: &mut Vec<Supply>,
+    ) -> Option<NaiveDate> {
+        let weeks = self.weeks?;
+        let start = demands.iter().map(|d| d.required_date).min()?;
+        let end = start + DateDuration::weeks(weeks as i64);
+
+        demands.retain(|d| d.required_date < end);
+        supplies.retain(|s| s.available_date < end);
+        Some(end)
+    }
+}
+
+/// 預覽計算結果
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PlanPreview {
+    /// 近似的 MRP 計算結果
+    pub result: MrpResult,
+
+    /// 是否因超過時間預算而提前停止
+    pub truncated: bool,
+
+    /// 因時間預算而未計算的物料
+    pub pending_components: Vec<String>,
+
+    /// 週數範圍結束日（不含；未限制週數時為 None）
+    pub horizon_end: Option<NaiveDate>,
+}
+
+/// 逐物料計算的時間預算（預覽與設置時間預算的完整計算共用）
+pub(crate) struct RunBudget {
+    /// 截止時間
+    deadline: Instant,
+
+    /// 只計算頂層物料
+    pub(crate) top_level_only: bool,
+
+    /// 因時間預算而未計算的物料
+    pub(crate) pending_components: Vec<String>,
+}
+
+impl RunBudget {
+    /// 自現在起計算截止時間
+    pub(crate) fn start(time_budget: Duration, top_level_only: bool) -> Self {
+        Self {
+            deadline: Instant::now() + time_budget,
+            top_level_only,
+            pending_components: Vec::new(),
+        }
+    }
+
+    /// 是否已超過時間預算
+    pub(crate) fn expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{DemandType, SupplyType};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_restrict_horizon_to_weeks() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        let demand = |day| {
+            Demand::new(
+                "BIKE-001".to_string(),
+                Decimal::from(10),
+                date(day),
+                DemandType::SalesOrder,
+            )
+        };
+        let mut demands = vec![demand(20), demand(3), demand(16), demand(17)];
+        let mut supplies = vec![Supply::new(
+            "BIKE-001".to_string(),
+            Decimal::from(5),
+            date(25),
+            SupplyType::PurchaseOrder,
+        )];
+
+        let end = PreviewOptions::new(Duration::from_secs(1))
+            .with_weeks(2)
+            .restrict_horizon(&mut demands, &mut supplies);
+
+        assert_eq!(end, Some(date(17)));
+        assert_eq!(demands.len(), 2);
+        assert!(supplies.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! 採購視圖：按供應商 × 週彙總計劃採購訂單
+
+use chrono::{Datelike, NaiveDate};
+use mrp_core::PlannedOrder;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::scenario::ConfigSet;
+
+/// 供應商週排程中的單一物料行
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PurchaseScheduleLine {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 彙總數量
+    pub quantity: Decimal,
+
+    /// 彙總金額（物料未設置單位成本時為 None）
+    pub value: Option<Decimal>,
+
+    /// 構成此行的計劃訂單ID（用於下鑽）
+    pub order_ids: Vec<Uuid>,
+}
+
+/// 供應商 × 週的採購彙總
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SupplierWeekSchedule {
+    /// 供應商ID（未指定供應商為 None）
+    pub supplier_id: Option<String>,
+
+    /// ISO 年
+    pub iso_year: i32,
+
+    /// ISO 週次
+    pub iso_week: u32,
+
+    /// 週起始日（週一）
+    pub week_start: NaiveDate,
+
+    /// 物料明細
+    pub lines: Vec<PurchaseScheduleLine>,
+}
+
+impl SupplierWeekSchedule {
+    /// 該週總金額（僅計入有單位成本的物料）
+    pub fn total_value(&self) -> Decimal {
+        self.lines.iter().filter_map(|l| l.value).sum()
+    }
+
+    /// 該週所有計劃訂單ID
+    pub fn order_ids(&self) -> Vec<Uuid> {
+        self.lines
+            .iter()
+            .flat_map(|l| l.order_ids.iter().copied())
+            .collect()
+    }
+}
+
+/// 採購排程彙總器
+pub struct PurchaseScheduleCalculator;
+
+impl PurchaseScheduleCalculator {
+    /// 將計劃採購訂單按供應商與交貨週（需求日期所在 ISO 週）彙總
+    ///
+    /// 結果按供應商、週次、物料排序
+    pub fn aggregate(
+        planned_orders: &[PlannedOrder],
+        configs: &ConfigSet,
+    ) -> Vec<SupplierWeekSchedule> {
+        type LineKey = (Option<String>, NaiveDate, String);
+        let mut lines: BTreeMap<LineKey, PurchaseScheduleLine> = BTreeMap::new();
+
+        for order in planned_orders.iter().filter(|o| o.is_purchase()) {
+            let week_start = Self::week_start(order.required_date);
+            let unit_cost = configs.get(&order.component_id).and_then(|c| c.unit_cost);
+
+            let line = lines
+                .entry((
+                    order.source_id.clone(),
+                    week_start,
+                    order.component_id.clone(),
+                ))
+                .or_insert_with(|| PurchaseScheduleLine {
+                    component_id: order.component_id.clone(),
+                    quantity: Decimal::ZERO,
+                    value: unit_cost.map(|_| Decimal::ZERO),
+                    order_ids: Vec::new(),
+                });
+
+            line.quantity += order.quantity;
+            if let (Some(value), Some(cost)) = (line.value.as_mut(), unit_cost) {
+                *value += order.quantity * cost;
+            }
+            line.order_ids.push(order.id);
+        }
+
+        let mut schedules: Vec<SupplierWeekSchedule> = Vec::new();
+        for ((supplier_id, week_start, _), line) in lines {
+            match schedules.last_mut() {
+                Some(last) if last.supplier_id == supplier_id && last.week_start == week_start => {
+                    last.lines.push(line);
+                }
+                _ => {
+                    let iso = week_start.iso_week();
+                    schedules.push(SupplierWeekSchedule {
+                        supplier_id,
+                        iso_year: iso.year(),
+                        iso_week: iso.week(),
+                        week_start,
+                        lines: vec![line],
+                    });
+                }
+            }
+        }
+
+        schedules
+    }
+
+    /// 日期所在週的週一
+    fn week_start(date: NaiveDate) -> NaiveDate {
+        date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{MrpConfig, PlannedOrderType, ProcurementType};
+    use std::collections::HashMap;
+
+    fn purchase(component_id: &str, supplier: &str, qty: i64, date: NaiveDate) -> PlannedOrder {
+        PlannedOrder::new(
+            component_id.to_string(),
+            Decimal::from(qty),
+            date,
+            date,
+            PlannedOrderType::Purchase,
+        )
+        .with_source_id(supplier.to_string())
+    }
+
+    #[test]
+    fn test_aggregate_by_supplier_and_week() {
+        let mut configs = HashMap::new();
+        configs.insert(
+            "BOLT-001".to_string(),
+            MrpConfig::new("BOLT-001".to_string(), 3, ProcurementType::Buy)
+                .with_unit_cost(Decimal::new(25, 1)),
+        );
+
+        // 2025-11-03 是週一
+        let monday = NaiveDate::from_ymd_opt(2025, 11, 3).unwrap();
+        let friday = NaiveDate::from_ymd_opt(2025, 11, 7).unwrap();
+        let next_week = NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+
+        let orders = vec![
+            purchase("BOLT-001", "VENDOR-A", 100, monday),
+            purchase("BOLT-001", "VENDOR-A", 50, friday),
+            purchase("NUT-001", "VENDOR-A", 10, friday),
+            purchase("BOLT-001", "VENDOR-A", 30, next_week),
+            purchase("BOLT-001", "VENDOR-B", 70, monday),
+        ];
+
+        let schedules = PurchaseScheduleCalculator::aggregate(&orders, &ConfigSet::from(configs));
+
+        assert_eq!(schedules.len(), 3);
+
+        let first = &schedules[0];
+        assert_eq!(first.supplier_id.as_deref(), Some("VENDOR-A"));
+        assert_eq!(first.week_start, monday);
+        assert_eq!(first.iso_week, 45);
+        assert_eq!(first.lines.len(), 2);
+        assert_eq!(first.lines[0].quantity, Decimal::from(150));
+        assert_eq!(first.lines[0].value, Some(Decimal::from(375)));
+        assert_eq!(first.lines[0].order_ids.len(), 2);
+        // NUT-001 無單位成本
+        assert_eq!(first.lines[1].value, None);
+        assert_eq!(first.total_value(), Decimal::from(375));
+
+        assert_eq!(schedules[1].week_start, next_week);
+        assert_eq!(schedules[2].supplier_id.as_deref(), Some("VENDOR-B"));
+    }
+
+    #[test]
+    fn test_production_orders_excluded() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 3).unwrap();
+        let orders = vec![PlannedOrder::new(
+            "ASSY-001".to_string(),
+            Decimal::from(10),
+            date,
+            date,
+            PlannedOrderType::Production,
+        )];
+
+        let schedules = PurchaseScheduleCalculator::aggregate(&orders, &ConfigSet::default());
+        assert!(schedules.is_empty());
+    }
+}
@@ -0,0 +1,380 @@
+//! 跨倉庫存調撥建議：同一物料一倉過剩、另一倉短缺時，以調撥取代新採購
+//!
+//! 庫存以 `Inventory::warehouse_id`、在途供應以 `Supply::warehouse_id` 區分倉庫，
+//! 需求以 `Demand::plant_id` 對應倉庫；未指定倉庫的庫存、供應與需求不參與調撥分析。
+
+use chrono::{Duration, NaiveDate};
+use mrp_core::{Demand, Inventory, PlannedOrder, PlannedOrderType, Supply};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+/// 調撥路線
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TransferLane {
+    /// 調出倉庫
+    pub from_warehouse: String,
+
+    /// 調入倉庫
+    pub to_warehouse: String,
+
+    /// 調撥提前期（天）
+    pub lead_time_days: u32,
+
+    /// 最小調撥數量
+    pub min_transfer_qty: Decimal,
+}
+
+impl TransferLane {
+    /// 創建調撥路線
+    pub fn new(from_warehouse: String, to_warehouse: String, lead_time_days: u32) -> Self {
+        Self {
+            from_warehouse,
+            to_warehouse,
+            lead_time_days,
+            min_transfer_qty: Decimal::ZERO,
+        }
+    }
+
+    /// 建構器模式：設置最小調撥數量
+    pub fn with_min_transfer_qty(mut self, min_transfer_qty: Decimal) -> Self {
+        self.min_transfer_qty = min_transfer_qty;
+        self
+    }
+}
+
+/// 調撥建議
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TransferProposal {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 調出倉庫
+    pub from_warehouse: String,
+
+    /// 調入倉庫
+    pub to_warehouse: String,
+
+    /// 調撥數量
+    pub quantity: Decimal,
+
+    /// 出貨日期
+    pub ship_date: NaiveDate,
+
+    /// 預計到貨日期
+    pub arrival_date: NaiveDate,
+
+    /// 調入倉庫的短缺日期（最早一筆無法滿足的需求日期）
+    pub shortage_date: NaiveDate,
+}
+
+impl TransferProposal {
+    /// 轉為調撥計劃訂單（來源為調出倉庫）
+    pub fn to_planned_order(&self) -> PlannedOrder {
+        let mut order = PlannedOrder::new(
+            self.component_id.clone(),
+            self.quantity,
+            self.arrival_date,
+            self.ship_date,
+            PlannedOrderType::Transfer,
+        );
+        order.source_id = Some(self.from_warehouse.clone());
+        order
+    }
+}
+
+/// 單一倉庫的物料狀態
+struct WarehousePosition {
+    /// 可調出的過剩數量
+    excess: Decimal,
+
+    /// 短缺數量與最早短缺日期
+    shortage: Option<(Decimal, NaiveDate)>,
+}
+
+/// 跨倉調撥分析器
+pub struct RebalancingAnalyzer;
+
+impl RebalancingAnalyzer {
+    /// 依 `as_of + horizon_days` 內的需求找出過剩與短缺倉庫，產生調撥建議
+    ///
+    /// 倉庫的期初可用量為可用庫存扣除安全庫存，再按日期加入窗口內的在途供應、扣除需求
+    /// （同日先入後出）。預計結存的最低點即可調出的過剩（調撥在 `as_of` 出貨，不得讓調出倉
+    /// 在後續供應到貨前短缺）；結存低於零時自第一次低於零的日期起短缺，短缺量為最低點的缺口。
+    /// 短缺倉庫按短缺日期先後處理，
+    /// 只使用能在短缺日期前到貨的路線，並優先選提前期最短者；
+    /// 調撥數量不足最小調撥數量時不建議（短缺仍由新採購補足）。
+    pub fn propose(
+        inventories: &[Inventory],
+        supplies: &[Supply],
+        demands: &[Demand],
+        lanes: &[TransferLane],
+        as_of: NaiveDate,
+        horizon_days: u32,
+    ) -> Vec<TransferProposal> {
+        let horizon_end = as_of + Duration::days(horizon_days as i64);
+
+        // 物料 → 倉庫 → 窗口內需求
+        let mut demands_by_location: BTreeMap<&str, BTreeMap<&str, Vec<&Demand>>> = BTreeMap::new();
+        for demand in demands.iter().filter(|d| d.required_date <= horizon_end) {
+            if let Some(warehouse) = demand.plant_id.as_deref() {
+                demands_by_location
+                    .entry(demand.component_id.as_str())
+                    .or_default()
+                    .entry(warehouse)
+                    .or_default()
+                    .push(demand);
+            }
+        }
+
+        // 物料 → 倉庫 → 窗口內在途供應
+        let mut supplies_by_location: BTreeMap<&str, BTreeMap<&str, Vec<&Supply>>> =
+            BTreeMap::new();
+        for supply in supplies.iter().filter(|s| s.available_date <= horizon_end) {
+            if let Some(warehouse) = supply.warehouse_id.as_deref() {
+                supplies_by_location
+                    .entry(supply.component_id.as_str())
+                    .or_default()
+                    .entry(warehouse)
+                    .or_default()
+                    .push(supply);
+            }
+        }
+
+        // 物料 → 倉庫 → 庫存
+        let mut stock_by_location: BTreeMap<&str, BTreeMap<&str, &Inventory>> = BTreeMap::new();
+        for inventory in inventories {
+            if let Some(warehouse) = inventory.warehouse_id.as_deref() {
+                stock_by_location
+                    .entry(inventory.component_id.as_str())
+                    .or_default()
+                    .insert(warehouse, inventory);
+            }
+        }
+
+        let mut components: Vec<&str> = stock_by_location
+            .keys()
+            .chain(supplies_by_location.keys())
+            .copied()
+            .collect();
+        components.sort_unstable();
+        components.dedup();
+
+        let mut proposals = Vec::new();
+        for component_id in components {
+            let stocks = stock_by_location.get(component_id);
+            let component_supplies = supplies_by_location.get(component_id);
+            let component_demands = demands_by_location.get(component_id);
+            let mut warehouses: Vec<&str> = stocks
+                .into_iter()
+                .flat_map(|by_warehouse| by_warehouse.keys().copied())
+                .chain(
+                    component_supplies
+                        .into_iter()
+                        .flat_map(|by_warehouse| by_warehouse.keys().copied()),
+                )
+                .chain(
+                    component_demands
+                        .into_iter()
+                        .flat_map(|by_warehouse| by_warehouse.keys().copied()),
+                )
+                .collect();
+            warehouses.sort_unstable();
+            warehouses.dedup();
+
+            let mut positions: HashMap<&str, WarehousePosition> = warehouses
+                .iter()
+                .map(|&warehouse| {
+                    let position = Self::position(
+                        stocks.and_then(|by_warehouse| by_warehouse.get(warehouse).copied()),
+                        component_supplies
+                            .and_then(|by_warehouse| by_warehouse.get(warehouse))
+                            .map_or(&[][..], |s| s.as_slice()),
+                        component_demands
+                            .and_then(|by_warehouse| by_warehouse.get(warehouse))
+                            .map_or(&[][..], |d| d.as_slice()),
+                    );
+                    (warehouse, position)
+                })
+                .collect();
+
+            let mut shortages: Vec<(&str, Decimal, NaiveDate)> = positions
+                .iter()
+                .filter_map(|(&warehouse, p)| p.shortage.map(|(qty, date)| (warehouse, qty, date)))
+                .collect();
+            shortages.sort_by(|a, b| a.2.cmp(&b.2).then(a.0.cmp(b.0)));
+
+            for (to_warehouse, mut remaining, shortage_date) in shortages {
+                let mut candidates: Vec<&TransferLane> = lanes
+                    .iter()
+                    .filter(|lane| {
+                        lane.to_warehouse == to_warehouse
+                            && lane.from_warehouse != to_warehouse
+                            && as_of + Duration::days(lane.lead_time_days as i64) <= shortage_date
+                    })
+                    .collect();
+                candidates.sort_by(|a, b| {
+                    a.lead_time_days
+                        .cmp(&b.lead_time_days)
+                        .then(a.from_warehouse.cmp(&b.from_warehouse))
+                });
+
+                for lane in candidates {
+                    if remaining <= Decimal::ZERO {
+                        break;
+                    }
+                    let Some(source) = positions.get_mut(lane.from_warehouse.as_str()) else {
+                        continue;
+                    };
+                    // 不足最小調撥數量時以最小數量調撥（來源過剩足夠時）
+                    let quantity = remaining.max(lane.min_transfer_qty).min(source.excess);
+                    if quantity <= Decimal::ZERO || quantity < lane.min_transfer_qty {
+                        continue;
+                    }
+
+                    source.excess -= quantity;
+                    remaining -= quantity;
+                    proposals.push(TransferProposal {
+                        component_id: component_id.to_string(),
+                        from_warehouse: lane.from_warehouse.clone(),
+                        to_warehouse: to_warehouse.to_string(),
+                        quantity,
+                        ship_date: as_of,
+                        arrival_date: as_of + Duration::days(lane.lead_time_days as i64),
+                        shortage_date,
+                    });
+                }
+            }
+        }
+
+        proposals
+    }
+
+    /// 計算倉庫的過剩與短缺（按日期推算預計結存）
+    fn position(
+        inventory: Option<&Inventory>,
+        supplies: &[&Supply],
+        demands: &[&Demand],
+    ) -> WarehousePosition {
+        let available = inventory.map_or(Decimal::ZERO, |inv| {
+            (inv.available_qty - inv.safety_stock).max(Decimal::ZERO)
+        });
+
+        // (日期, 先入後出順序, 數量變動)
+        let mut movements: Vec<(NaiveDate, u8, Decimal)> = supplies
+            .iter()
+            .map(|s| (s.available_date, 0, s.quantity))
+            .chain(demands.iter().map(|d| (d.required_date, 1, -d.quantity)))
+            .collect();
+        movements.sort_by_key(|(date, order, _)| (*date, *order));
+
+        let mut balance = available;
+        let mut lowest = available;
+        let mut shortage_date = None;
+        for (date, _, change) in movements {
+            balance += change;
+            lowest = lowest.min(balance);
+            if shortage_date.is_none() && balance < Decimal::ZERO {
+                shortage_date = Some(date);
+            }
+        }
+
+        WarehousePosition {
+            excess: lowest.max(Decimal::ZERO),
+            shortage: shortage_date.map(|date| (-lowest, date)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::DemandType;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    fn demand(warehouse: &str, qty: i64, day: u32) -> Demand {
+        let mut demand = Demand::new(
+            "PUMP-001".to_string(),
+            Decimal::from(qty),
+            date(day),
+            DemandType::SalesOrder,
+        );
+        demand.plant_id = Some(warehouse.to_string());
+        demand
+    }
+
+    #[test]
+    fn test_transfer_excess_to_shortage() {
+        let inventories = vec![
+            Inventory::new(
+                "PUMP-001".to_string(),
+                Decimal::from(200),
+                Decimal::from(20),
+            )
+            .with_warehouse_id("TPE".to_string()),
+            Inventory::new("PUMP-001".to_string(), Decimal::from(200), Decimal::ZERO)
+                .with_warehouse_id("KHH".to_string()),
+            Inventory::new("PUMP-001".to_string(), Decimal::from(10), Decimal::ZERO)
+                .with_warehouse_id("TXG".to_string()),
+        ];
+        let demands = vec![
+            demand("TPE", 50, 10),
+            demand("KHH", 150, 5),
+            demand("TXG", 40, 6),
+            demand("TXG", 30, 20),
+        ];
+        let lanes = vec![
+            // 太慢：趕不上 11/6 的短缺
+            TransferLane::new("TPE".to_string(), "TXG".to_string(), 7),
+            TransferLane::new("KHH".to_string(), "TXG".to_string(), 3)
+                .with_min_transfer_qty(Decimal::from(50)),
+        ];
+
+        // KHH 11/25 的在途採購在窗口內，但趕不及 11/5 的需求，不增加可調出量
+        let supplies = vec![Supply::new(
+            "PUMP-001".to_string(),
+            Decimal::from(100),
+            date(25),
+            mrp_core::SupplyType::PurchaseOrder,
+        )
+        .with_warehouse_id("KHH".to_string())];
+
+        let proposals =
+            RebalancingAnalyzer::propose(&inventories, &supplies, &demands, &lanes, date(1), 30);
+
+        // TXG 短缺 60；KHH 過剩 50 → 調 50，剩餘 10 由採購補足
+        assert_eq!(proposals.len(), 1);
+        let proposal = &proposals[0];
+        assert_eq!(proposal.from_warehouse, "KHH");
+        assert_eq!(proposal.to_warehouse, "TXG");
+        assert_eq!(proposal.quantity, Decimal::from(50));
+        assert_eq!(proposal.arrival_date, date(4));
+        assert_eq!(proposal.shortage_date, date(6));
+
+        let order = proposal.to_planned_order();
+        assert_eq!(order.order_type, PlannedOrderType::Transfer);
+        assert_eq!(order.source_id.as_deref(), Some("KHH"));
+
+        // TXG 11/3 到貨的在途供應補足短缺，不再建議調撥
+        let inbound = Supply::new(
+            "PUMP-001".to_string(),
+            Decimal::from(60),
+            date(3),
+            mrp_core::SupplyType::Transfer,
+        )
+        .with_warehouse_id("TXG".to_string());
+        assert!(RebalancingAnalyzer::propose(
+            &inventories,
+            &[inbound],
+            &demands,
+            &lanes,
+            date(1),
+            30
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! 收貨日曆：供應商只在特定日子到貨（或全天候到貨）時，將計劃收貨日對齊到可收貨日
+//!
+//! 收貨日曆與生產日曆分開：提前期仍按生產日曆計算，收貨日曆只決定收貨日能落在哪些日子。
+
+use mrp_core::{CalendarRegistry, MrpConfig, PlannedOrder, WorkCalendar};
+use std::collections::HashMap;
+
+/// 收貨日曆設定：物料設置的收貨日曆優先，否則使用計劃訂單供應商的收貨日曆
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptCalendars {
+    /// 收貨日曆（可使用覆寫日曆）
+    calendars: CalendarRegistry,
+
+    /// 供應商ID → 收貨日曆ID
+    suppliers: HashMap<String, String>,
+}
+
+impl ReceiptCalendars {
+    /// 以日曆註冊表創建
+    pub fn new(calendars: CalendarRegistry) -> Self {
+        Self {
+            calendars,
+            suppliers: HashMap::new(),
+        }
+    }
+
+    /// 建構器模式：設置供應商的收貨日曆
+    pub fn with_supplier(mut self, supplier_id: String, calendar_id: String) -> Self {
+        self.suppliers.insert(supplier_id, calendar_id);
+        self
+    }
+
+    /// 物料與供應商適用的收貨日曆（都未設置時為 None）
+    pub fn calendar_for(
+        &self,
+        config: &MrpConfig,
+        supplier_id: Option<&str>,
+    ) -> mrp_core::Result<Option<WorkCalendar>> {
+        config
+            .receipt_calendar_id
+            .as_ref()
+            .or_else(|| supplier_id.and_then(|id| self.suppliers.get(id)))
+            .map(|calendar_id| self.calendars.resolve(calendar_id))
+            .transpose()
+    }
+
+    /// 將採購／調撥計劃訂單的收貨日提前到前一個收貨日，回傳調整的訂單數
+    ///
+    /// 下單日按生產日曆保持原提前期工作日數；原收貨日記錄為需要日期。
+    pub(crate) fn align(
+        &self,
+        orders: &mut [PlannedOrder],
+        config: &MrpConfig,
+        calendar: &WorkCalendar,
+    ) -> mrp_core::Result<usize> {
+        let mut adjusted = 0;
+        for order in orders.iter_mut().filter(|o| !o.is_production()) {
+            let Some(receipt) = self.calendar_for(config, order.source_id.as_deref())? else {
+                continue;
+            };
+            if receipt.is_working_day(order.required_date) {
+                continue;
+            }
+            let lead_time = calendar.working_days_between(order.order_date, order.required_date);
+            let receipt_date = receipt.previous_working_day(order.required_date);
+            order.need_date.get_or_insert(order.required_date);
+            order.order_date = calendar.subtract_working_days(receipt_date, lead_time);
+            order.required_date = receipt_date;
+            adjusted += 1;
+        }
+        Ok(adjusted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::{PlannedOrderType, ProcurementType};
+    use rust_decimal::Decimal;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_align_receipts_to_supplier_days() {
+        let mut registry = CalendarRegistry::new();
+        // 供應商只在週二、週四送貨
+        registry.register(
+            WorkCalendar::new("DOCK".to_string())
+                .with_working_days([false, true, false, true, false, false, false]),
+        );
+        registry.register(WorkCalendar::new_24_7("ANYTIME".to_string()));
+        let receipts = ReceiptCalendars::new(registry)
+            .with_supplier("SUP-A".to_string(), "DOCK".to_string())
+            .with_supplier("SUP-B".to_string(), "ANYTIME".to_string());
+        let production = WorkCalendar::new("PLANT".to_string());
+        let config = MrpConfig::new("BOLT".to_string(), 2, ProcurementType::Buy);
+
+        // 11/8 週六收貨：SUP-A 提前到 11/6（週四），SUP-B 週末也收貨
+        let order = |supplier: &str| {
+            PlannedOrder::new(
+                "BOLT".to_string(),
+                Decimal::TEN,
+                date(8),
+                date(6),
+                PlannedOrderType::Purchase,
+            )
+            .with_source_id(supplier.to_string())
+        };
+        let mut orders = vec![order("SUP-A"), order("SUP-B")];
+
+        let adjusted = receipts.align(&mut orders, &config, &production).unwrap();
+
+        assert_eq!(adjusted, 1);
+        assert_eq!(orders[0].required_date, date(6));
+        assert_eq!(orders[0].need_date, Some(date(8)));
+        // 原提前期 1 個生產工作日（週五），下單日自 11/6 往前一個工作日
+        assert_eq!(orders[0].order_date, date(5));
+        assert_eq!(orders[1].required_date, date(8));
+
+        // 物料設置的收貨日曆優先於供應商
+        let config = config.with_receipt_calendar("ANYTIME".to_string());
+        let mut orders = vec![order("SUP-A")];
+        assert_eq!(
+            receipts.align(&mut orders, &config, &production).unwrap(),
+            0
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! 再訂購點（ROP）計劃：消耗導向補貨，不依需求追溯
+
+use mrp_core::{MrpConfig, PlannedOrder, WorkCalendar};
+use rust_decimal::Decimal;
+
+use crate::lead_time::LeadTimeCalculator;
+use crate::lot_sizing::LotSizingCalculator;
+use crate::netting::NetRequirement;
+
+/// 再訂購點計劃器
+pub struct ReorderPointPlanner;
+
+impl ReorderPointPlanner {
+    /// 依再訂購點產生計劃訂單
+    ///
+    /// 逐期累計庫存位置（期初庫存 + 預計收貨 − 毛需求 + 已產生的計劃訂單），
+    /// 低於再訂購點時於該期（順延到工作日）下單，到貨日依提前期順推。
+    /// 批量為固定批量，未設置時為 EOQ；訂單不掛需求追溯。
+    pub fn plan(
+        component_id: &str,
+        net_requirements: &[NetRequirement],
+        initial_inventory: Decimal,
+        config: &MrpConfig,
+        calendar: &WorkCalendar,
+    ) -> mrp_core::Result<Vec<PlannedOrder>> {
+        let reorder_point = config.reorder_point.ok_or_else(|| {
+            mrp_core::MrpError::InvalidConfig(format!("物料 {} 未設置再訂購點", component_id))
+        })?;
+        let lot_size = config
+            .adjust_order_quantity(LotSizingCalculator::eoq_lot_size(net_requirements, config)?);
+        if lot_size <= Decimal::ZERO {
+            return Err(mrp_core::MrpError::InvalidConfig(format!(
+                "物料 {} 的再訂購批量必須大於 0",
+                component_id
+            )));
+        }
+
+        let order_type = LotSizingCalculator::determine_order_type(config.procurement_type);
+        let mut planned_orders = Vec::new();
+        let mut position = initial_inventory;
+
+        for req in net_requirements {
+            position += req.scheduled_receipt - req.gross_requirement;
+
+            while position < reorder_point {
+                let order_date = if calendar.is_working_day(req.date) {
+                    req.date
+                } else {
+                    calendar.next_working_day(req.date)
+                };
+                let receipt_date = LeadTimeCalculator::calculate_delivery_date_for_quantity(
+                    order_date, lot_size, config, calendar,
+                );
+                planned_orders.push(PlannedOrder::new(
+                    component_id.to_string(),
+                    lot_size,
+                    receipt_date,
+                    order_date,
+                    order_type,
+                ));
+                position += lot_size;
+            }
+        }
+
+        Ok(planned_orders)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::ProcurementType;
+
+    #[test]
+    fn test_order_when_crossing_reorder_point() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let config = MrpConfig::new("WASHER-M6".to_string(), 3, ProcurementType::Buy)
+            .with_fixed_lot_size(Decimal::from(500))
+            .with_reorder_point(Decimal::from(200));
+
+        let net_reqs: Vec<NetRequirement> = [(3, 150), (5, 100), (8, 400)]
+            .into_iter()
+            .map(|(day, qty)| NetRequirement {
+                date: NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+                gross_requirement: Decimal::from(qty),
+                scheduled_receipt: Decimal::ZERO,
+                projected_on_hand: Decimal::ZERO,
+                net_requirement: Decimal::ZERO,
+            })
+            .collect();
+
+        let orders = ReorderPointPlanner::plan(
+            "WASHER-M6",
+            &net_reqs,
+            Decimal::from(400),
+            &config,
+            &calendar,
+        )
+        .unwrap();
+
+        // 400 → 250 → 150（低於 200，11/5 下單 500 → 650）→ 250
+        assert_eq!(orders.len(), 1);
+        assert_eq!(
+            orders[0].order_date,
+            NaiveDate::from_ymd_opt(2025, 11, 5).unwrap()
+        );
+        assert_eq!(
+            orders[0].required_date,
+            NaiveDate::from_ymd_opt(2025, 11, 8).unwrap()
+        );
+        assert_eq!(orders[0].quantity, Decimal::from(500));
+        assert!(orders[0].pegging.is_empty());
+    }
+}
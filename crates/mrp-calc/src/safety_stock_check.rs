@@ -0,0 +1,140 @@
+//! 安全庫存合理性檢查：比較各物料的安全庫存與補貨提前期內的需求波動，找出明顯不足或過多的物料
+//!
+//! 建議值 = 服務係數 z × 提前期需求標準差，其中提前期需求標準差
+//! = √(提前期 × 日需求標準差² + 日平均需求² × 提前期標準差²)。
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+use crate::safety_buffer::ItemDemandProfile;
+use crate::ConfigSet;
+
+/// 安全庫存判定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SafetyStockStatus {
+    /// 低於建議值的一半
+    Insufficient,
+
+    /// 介於建議值的一半與兩倍之間
+    Adequate,
+
+    /// 超過建議值的兩倍
+    Excessive,
+}
+
+/// 單一物料的安全庫存檢查結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SafetyStockCheck {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 目前的安全庫存
+    pub safety_stock: Decimal,
+
+    /// 提前期內的平均需求
+    pub lead_time_demand: Decimal,
+
+    /// 目前安全庫存可支應的平均需求天數（日平均需求為 0 時為 None）
+    pub coverage_days: Option<Decimal>,
+
+    /// 建議安全庫存
+    pub recommended: Decimal,
+
+    /// 判定
+    pub status: SafetyStockStatus,
+}
+
+/// 安全庫存檢查器
+pub struct SafetyStockChecker;
+
+impl SafetyStockChecker {
+    /// 依物料需求特性檢查配置中的安全庫存（沒有配置的物料略過）
+    ///
+    /// `service_factor` 為目標服務水準對應的常態分位數（例如 95% 為 1.65）。
+    /// 提前期取物料配置中一般訂購量的總提前期（含變動提前期），不用需求特性上的提前期。
+    /// 建議值按物料的取整方式進位。
+    pub fn check(
+        profiles: &[ItemDemandProfile],
+        configs: &ConfigSet,
+        service_factor: Decimal,
+    ) -> Vec<SafetyStockCheck> {
+        profiles
+            .iter()
+            .filter_map(|profile| {
+                let config = configs.get(&profile.component_id)?;
+                let lead_time = Decimal::from(config.lead_time_for(profile.order_quantity));
+                let mean = profile.mean_daily_demand;
+                let daily_std_dev = profile.daily_demand_std_dev;
+                let variance = lead_time * daily_std_dev * daily_std_dev
+                    + mean * mean * profile.lead_time_std_dev * profile.lead_time_std_dev;
+                let std_dev = variance
+                    .to_f64()
+                    .and_then(|v| Decimal::from_f64(v.sqrt()))
+                    .unwrap_or(Decimal::ZERO);
+                let recommended =
+                    config.round_quantity((service_factor * std_dev).round_dp(2).normalize());
+
+                let safety_stock = config.safety_stock;
+                let status = if safety_stock * Decimal::TWO < recommended {
+                    SafetyStockStatus::Insufficient
+                } else if safety_stock > recommended * Decimal::TWO {
+                    SafetyStockStatus::Excessive
+                } else {
+                    SafetyStockStatus::Adequate
+                };
+                Some(SafetyStockCheck {
+                    component_id: profile.component_id.clone(),
+                    safety_stock,
+                    lead_time_demand: mean * lead_time,
+                    coverage_days: (mean > Decimal::ZERO)
+                        .then(|| (safety_stock / mean).round_dp(1)),
+                    recommended,
+                    status,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{MrpConfig, ProcurementType};
+
+    #[test]
+    fn test_flag_insufficient_and_excessive_buffers() {
+        let mut configs = ConfigSet::default();
+        for (id, safety_stock) in [("VALVE", 5), ("SEAL", 30), ("GASKET", 200)] {
+            configs.set(
+                MrpConfig::new(id.to_string(), 4, ProcurementType::Buy)
+                    .with_safety_stock(Decimal::from(safety_stock)),
+            );
+        }
+        // 配置提前期 4 天、日需求標準差 10 → 提前期需求標準差 20，z = 1.5 建議 30
+        // （需求特性上的 9 天提前期不採用）
+        let profiles: Vec<ItemDemandProfile> = ["VALVE", "SEAL", "GASKET", "UNKNOWN"]
+            .into_iter()
+            .map(|id| {
+                ItemDemandProfile::new(
+                    id.to_string(),
+                    Decimal::from(25),
+                    Decimal::TEN,
+                    9,
+                    Decimal::from(100),
+                )
+            })
+            .collect();
+
+        let checks = SafetyStockChecker::check(&profiles, &configs, Decimal::new(15, 1));
+
+        assert_eq!(checks.len(), 3);
+        assert_eq!(checks[0].recommended, Decimal::from(30));
+        assert_eq!(checks[0].lead_time_demand, Decimal::from(100));
+        assert_eq!(checks[0].status, SafetyStockStatus::Insufficient);
+        assert_eq!(checks[1].status, SafetyStockStatus::Adequate);
+        assert_eq!(checks[2].status, SafetyStockStatus::Excessive);
+        assert_eq!(checks[2].coverage_days, Some(Decimal::from(8)));
+    }
+}
@@ -0,0 +1,388 @@
+//! 模擬情境（what-if）：以寫入時複製方式覆寫少量主資料
+//!
+//! 情境共用基準主資料（BOM 圖、物料配置），只保存被覆寫的配置、BOM 子件清單與日曆，
+//! 計算結果附上覆寫差異，方便比較情境與基準的差別。
+
+use bom_core::BomItem;
+use bom_graph::BomGraph;
+use mrp_core::{Demand, Inventory, MrpConfig, Supply, WorkCalendar};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{MrpCalculator, MrpResult};
+
+/// 分層物料配置：共享的基準配置加上情境覆寫
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSet {
+    /// 基準配置（多個情境共用）
+    base: Arc<HashMap<String, MrpConfig>>,
+
+    /// 情境覆寫（物料ID → 配置）
+    overrides: HashMap<String, MrpConfig>,
+}
+
+impl ConfigSet {
+    /// 以共享的基準配置創建
+    pub fn new(base: Arc<HashMap<String, MrpConfig>>) -> Self {
+        Self {
+            base,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// 覆寫（或新增）一筆物料配置
+    pub fn set(&mut self, config: MrpConfig) {
+        self.overrides.insert(config.component_id.clone(), config);
+    }
+
+    /// 查詢物料配置（覆寫優先）
+    pub fn get(&self, component_id: &str) -> Option<&MrpConfig> {
+        self.overrides
+            .get(component_id)
+            .or_else(|| self.base.get(component_id))
+    }
+
+    /// 所有生效的物料配置
+    pub fn values(&self) -> impl Iterator<Item = &MrpConfig> {
+        self.overrides.values().chain(
+            self.base
+                .iter()
+                .filter(|(id, _)| !self.overrides.contains_key(*id))
+                .map(|(_, config)| config),
+        )
+    }
+
+    /// 基準配置
+    pub fn base(&self) -> &Arc<HashMap<String, MrpConfig>> {
+        &self.base
+    }
+
+    /// 情境覆寫
+    pub fn overrides(&self) -> &HashMap<String, MrpConfig> {
+        &self.overrides
+    }
+}
+
+impl From<HashMap<String, MrpConfig>> for ConfigSet {
+    fn from(configs: HashMap<String, MrpConfig>) -> Self {
+        Self::new(Arc::new(configs))
+    }
+}
+
+/// 主資料覆寫差異
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MasterDataOverride {
+    /// 物料配置覆寫
+    Config {
+        /// 物料ID
+        component_id: String,
+
+        /// 變更的欄位（基準中不存在時為空，表示新增物料）
+        changed_fields: Vec<String>,
+
+        /// 基準中是否存在
+        in_base: bool,
+    },
+
+    /// BOM 子件清單覆寫
+    BomChildren {
+        /// 父件ID
+        parent_id: String,
+
+        /// 基準子件（子件ID, 用量）
+        base: Vec<(String, Decimal)>,
+
+        /// 情境子件（子件ID, 用量）
+        scenario: Vec<(String, Decimal)>,
+    },
+
+    /// 工作日曆覆寫
+    Calendar {
+        /// 基準日曆ID
+        base_calendar_id: String,
+
+        /// 情境日曆ID
+        scenario_calendar_id: String,
+    },
+}
+
+/// 情境計算結果
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ScenarioResult {
+    /// 情境名稱
+    pub name: String,
+
+    /// 主資料覆寫差異
+    pub overrides: Vec<MasterDataOverride>,
+
+    /// MRP 計算結果
+    pub result: MrpResult,
+}
+
+/// 模擬情境
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// 情境名稱
+    name: String,
+
+    /// 共享的 BOM 圖
+    bom_graph: Arc<BomGraph>,
+
+    /// 分層物料配置
+    configs: ConfigSet,
+
+    /// BOM 覆寫（父件ID → 完整子件清單）
+    bom_overrides: HashMap<String, Vec<BomItem>>,
+
+    /// 基準日曆
+    calendar: WorkCalendar,
+
+    /// 情境日曆
+    calendar_override: Option<WorkCalendar>,
+}
+
+impl Scenario {
+    /// 以共享的基準主資料創建情境
+    pub fn new(
+        name: String,
+        bom_graph: Arc<BomGraph>,
+        configs: Arc<HashMap<String, MrpConfig>>,
+        calendar: WorkCalendar,
+    ) -> Self {
+        Self {
+            name,
+            bom_graph,
+            configs: ConfigSet::new(configs),
+            bom_overrides: HashMap::new(),
+            calendar,
+            calendar_override: None,
+        }
+    }
+
+    /// 建構器模式：覆寫物料配置
+    pub fn with_config(mut self, config: MrpConfig) -> Self {
+        self.configs.set(config);
+        self
+    }
+
+    /// 建構器模式：以新的子件清單取代父件的 BOM（空清單表示移除所有子件）
+    pub fn with_bom_children(mut self, parent_id: String, children: Vec<BomItem>) -> Self {
+        self.bom_overrides.insert(parent_id, children);
+        self
+    }
+
+    /// 建構器模式：覆寫工作日曆
+    pub fn with_calendar(mut self, calendar: WorkCalendar) -> Self {
+        self.calendar_override = Some(calendar);
+        self
+    }
+
+    /// 情境名稱
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 主資料覆寫差異（配置、BOM、日曆依序；各類按ID排序）
+    pub fn overrides(&self) -> Vec<MasterDataOverride> {
+        let mut diffs = Vec::new();
+
+        let mut config_ids: Vec<&String> = self.configs.overrides().keys().collect();
+        config_ids.sort();
+        for id in config_ids {
+            let config = &self.configs.overrides()[id];
+            let base = self.configs.base().get(id);
+            diffs.push(MasterDataOverride::Config {
+                component_id: id.clone(),
+                changed_fields: base
+                    .map(|b| Self::changed_fields(b, config))
+                    .unwrap_or_default(),
+                in_base: base.is_some(),
+            });
+        }
+
+        let mut parent_ids: Vec<&String> = self.bom_overrides.keys().collect();
+        parent_ids.sort();
+        for parent_id in parent_ids {
+            diffs.push(MasterDataOverride::BomChildren {
+                parent_id: parent_id.clone(),
+                base: Self::base_children(&self.bom_graph, parent_id),
+                scenario: self.bom_overrides[parent_id]
+                    .iter()
+                    .map(|item| (item.child_id.as_str().to_string(), item.quantity))
+                    .collect(),
+            });
+        }
+
+        if let Some(calendar) = &self.calendar_override {
+            diffs.push(MasterDataOverride::Calendar {
+                base_calendar_id: self.calendar.calendar_id.clone(),
+                scenario_calendar_id: calendar.calendar_id.clone(),
+            });
+        }
+
+        diffs
+    }
+
+    /// 建立套用覆寫的計算器（BOM 圖與基準配置不複製）
+    pub fn calculator(&self) -> MrpCalculator {
+        MrpCalculator::from_parts(
+            Arc::clone(&self.bom_graph),
+            self.configs.clone(),
+            self.bom_overrides.clone(),
+            self.calendar_override
+                .clone()
+                .unwrap_or_else(|| self.calendar.clone()),
+        )
+    }
+
+    /// 執行情境計算，結果附上覆寫差異
+    pub fn run(
+        &self,
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+    ) -> mrp_core::Result<ScenarioResult> {
+        let result = self
+            .calculator()
+            .calculate(demands, supplies, inventories)?;
+        Ok(ScenarioResult {
+            name: self.name.clone(),
+            overrides: self.overrides(),
+            result,
+        })
+    }
+
+    /// 比較兩筆配置，回傳值不同的欄位名稱（按欄位名稱排序）
+    fn changed_fields(base: &MrpConfig, scenario: &MrpConfig) -> Vec<String> {
+        let (Ok(serde_json::Value::Object(base)), Ok(serde_json::Value::Object(scenario))) =
+            (serde_json::to_value(base), serde_json::to_value(scenario))
+        else {
+            return Vec::new();
+        };
+
+        let mut fields: Vec<String> = scenario
+            .iter()
+            .filter(|(key, value)| base.get(*key) != Some(*value))
+            .map(|(key, _)| key.clone())
+            .collect();
+        fields.sort();
+        fields
+    }
+
+    /// 基準 BOM 中父件的子件（子件ID, 用量）
+    fn base_children(bom_graph: &BomGraph, parent_id: &str) -> Vec<(String, Decimal)> {
+        let arena = bom_graph.arena();
+        let Some(parent_node) = arena.find_node(&bom_core::ComponentId::new(parent_id)) else {
+            return Vec::new();
+        };
+        arena
+            .children(parent_node)
+            .filter_map(|(child_idx, edge)| {
+                arena.node(child_idx).map(|child| {
+                    (
+                        child.component_id.as_str().to_string(),
+                        edge.bom_item.quantity,
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{bom_item, demand};
+    use mrp_core::ProcurementType;
+
+    fn base_data() -> (Arc<BomGraph>, Arc<HashMap<String, MrpConfig>>) {
+        let mut bom_graph = BomGraph::new();
+        bom_graph
+            .add_bom_item(bom_item("BIKE-001", "FRAME-001", 1))
+            .unwrap();
+
+        let mut configs = HashMap::new();
+        for (id, lead_time) in [("BIKE-001", 2), ("FRAME-001", 3), ("FRAME-002", 3)] {
+            configs.insert(
+                id.to_string(),
+                MrpConfig::new(id.to_string(), lead_time, ProcurementType::Make),
+            );
+        }
+        (Arc::new(bom_graph), Arc::new(configs))
+    }
+
+    #[test]
+    fn test_overrides_reported_and_applied() {
+        let (bom_graph, configs) = base_data();
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+
+        let scenario = Scenario::new(
+            "new-frame".to_string(),
+            Arc::clone(&bom_graph),
+            Arc::clone(&configs),
+            calendar,
+        )
+        .with_config(MrpConfig::new(
+            "BIKE-001".to_string(),
+            5,
+            ProcurementType::Make,
+        ))
+        .with_bom_children(
+            "BIKE-001".to_string(),
+            vec![bom_item("BIKE-001", "FRAME-002", 2)],
+        );
+
+        let overrides = scenario.overrides();
+        assert_eq!(
+            overrides[0],
+            MasterDataOverride::Config {
+                component_id: "BIKE-001".to_string(),
+                changed_fields: vec!["lead_time_days".to_string()],
+                in_base: true,
+            }
+        );
+        assert_eq!(
+            overrides[1],
+            MasterDataOverride::BomChildren {
+                parent_id: "BIKE-001".to_string(),
+                base: vec![("FRAME-001".to_string(), Decimal::ONE)],
+                scenario: vec![("FRAME-002".to_string(), Decimal::from(2))],
+            }
+        );
+
+        let outcome = scenario
+            .run(vec![demand("BIKE-001", 10, 20)], vec![], vec![])
+            .unwrap();
+
+        let frame_orders: Vec<_> = outcome
+            .result
+            .planned_orders
+            .iter()
+            .filter(|o| o.component_id.starts_with("FRAME"))
+            .collect();
+        assert!(frame_orders.iter().all(|o| o.component_id == "FRAME-002"));
+        assert!(frame_orders.iter().any(|o| o.quantity == Decimal::from(20)));
+
+        // 基準主資料不受影響
+        assert_eq!(configs["BIKE-001"].lead_time_days, 2);
+        assert_eq!(Arc::strong_count(&bom_graph), 2);
+    }
+
+    #[test]
+    fn test_config_set_layering() {
+        let (_, configs) = base_data();
+        let mut set = ConfigSet::new(configs);
+        set.set(MrpConfig::new(
+            "FRAME-001".to_string(),
+            9,
+            ProcurementType::Buy,
+        ));
+
+        assert_eq!(set.get("FRAME-001").unwrap().lead_time_days, 9);
+        assert_eq!(set.get("BIKE-001").unwrap().lead_time_days, 2);
+        assert_eq!(set.values().count(), 3);
+    }
+}
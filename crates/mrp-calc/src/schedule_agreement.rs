@@ -0,0 +1,337 @@
+//! 供應商交貨排程（排程協議）匯出：類 DELFOR 的滾動預測
+//!
+//! 依發布日期將每位供應商的計劃採購訂單分為確定區、權衡區與預測區，
+//! 可序列化為 JSON 或輸出 CSV 供供應商協同使用。
+
+use chrono::NaiveDate;
+use mrp_core::PlannedOrder;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// 排程區段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum ScheduleZone {
+    /// 確定區（供應商可據此生產與出貨）
+    Firm,
+    /// 權衡區（供應商可據此備料）
+    TradeOff,
+    /// 預測區（僅供參考）
+    Forecast,
+}
+
+impl ScheduleZone {
+    /// EDI 區段代碼
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScheduleZone::Firm => "FIRM",
+            ScheduleZone::TradeOff => "TRADE_OFF",
+            ScheduleZone::Forecast => "FORECAST",
+        }
+    }
+}
+
+/// 供應商排程區段長度（天，自發布日期起連續計算）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ScheduleHorizon {
+    /// 確定區天數
+    pub firm_days: u32,
+
+    /// 權衡區天數
+    pub trade_off_days: u32,
+
+    /// 預測區天數
+    pub forecast_days: u32,
+}
+
+impl ScheduleHorizon {
+    /// 創建區段長度設定
+    pub fn new(firm_days: u32, trade_off_days: u32, forecast_days: u32) -> Self {
+        Self {
+            firm_days,
+            trade_off_days,
+            forecast_days,
+        }
+    }
+
+    /// 總展望天數
+    pub fn total_days(&self) -> u32 {
+        self.firm_days + self.trade_off_days + self.forecast_days
+    }
+
+    /// 判斷交貨日期所屬區段（超出展望期為 None；逾期交貨歸入確定區）
+    pub fn zone_for(
+        &self,
+        issue_date: NaiveDate,
+        delivery_date: NaiveDate,
+    ) -> Option<ScheduleZone> {
+        let offset = (delivery_date - issue_date).num_days();
+        if offset < self.firm_days as i64 {
+            Some(ScheduleZone::Firm)
+        } else if offset < (self.firm_days + self.trade_off_days) as i64 {
+            Some(ScheduleZone::TradeOff)
+        } else if offset < self.total_days() as i64 {
+            Some(ScheduleZone::Forecast)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for ScheduleHorizon {
+    /// 預設：確定 2 週、權衡 4 週、預測 20 週
+    fn default() -> Self {
+        Self::new(14, 28, 140)
+    }
+}
+
+/// 交貨排程行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct DeliveryScheduleLine {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 交貨日期
+    pub delivery_date: NaiveDate,
+
+    /// 交貨數量
+    pub quantity: Decimal,
+
+    /// 所屬區段
+    pub zone: ScheduleZone,
+
+    /// 構成此行的計劃訂單ID
+    pub order_ids: Vec<Uuid>,
+}
+
+/// 單一供應商的交貨排程
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SupplierDeliverySchedule {
+    /// 供應商ID
+    pub supplier_id: String,
+
+    /// 發布日期
+    pub issue_date: NaiveDate,
+
+    /// 區段長度
+    pub horizon: ScheduleHorizon,
+
+    /// 排程行（按物料、交貨日期排序）
+    pub lines: Vec<DeliveryScheduleLine>,
+}
+
+impl SupplierDeliverySchedule {
+    /// 某區段的總數量
+    pub fn zone_quantity(&self, zone: ScheduleZone) -> Decimal {
+        self.lines
+            .iter()
+            .filter(|l| l.zone == zone)
+            .map(|l| l.quantity)
+            .sum()
+    }
+
+    /// CSV 標題列
+    pub const CSV_HEADER: &'static str =
+        "supplier_id,issue_date,component_id,delivery_date,quantity,zone";
+
+    /// 輸出 CSV（含標題列）
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(Self::CSV_HEADER);
+        csv.push('\n');
+        self.write_csv_rows(&mut csv);
+        csv
+    }
+
+    /// 寫入 CSV 資料列（不含標題列）
+    fn write_csv_rows(&self, out: &mut String) {
+        for line in &self.lines {
+            out.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                csv_field(&self.supplier_id),
+                self.issue_date,
+                csv_field(&line.component_id),
+                line.delivery_date,
+                line.quantity,
+                line.zone.code()
+            ));
+        }
+    }
+}
+
+/// 交貨排程產生器
+pub struct DeliveryScheduleCalculator;
+
+impl DeliveryScheduleCalculator {
+    /// 從計劃採購訂單產生各供應商的滾動交貨排程
+    ///
+    /// 未指定供應商的訂單與超出展望期的訂單不列入；
+    /// `horizons` 未設定的供應商使用 `default_horizon`。
+    pub fn generate(
+        planned_orders: &[PlannedOrder],
+        issue_date: NaiveDate,
+        horizons: &HashMap<String, ScheduleHorizon>,
+        default_horizon: ScheduleHorizon,
+    ) -> Vec<SupplierDeliverySchedule> {
+        type LineKey = (String, String, NaiveDate);
+        let mut lines: BTreeMap<LineKey, DeliveryScheduleLine> = BTreeMap::new();
+
+        for order in planned_orders.iter().filter(|o| o.is_purchase()) {
+            let Some(supplier_id) = order.source_id.as_ref() else {
+                continue;
+            };
+            let horizon = horizons.get(supplier_id).unwrap_or(&default_horizon);
+            let Some(zone) = horizon.zone_for(issue_date, order.required_date) else {
+                continue;
+            };
+
+            let line = lines
+                .entry((
+                    supplier_id.clone(),
+                    order.component_id.clone(),
+                    order.required_date,
+                ))
+                .or_insert_with(|| DeliveryScheduleLine {
+                    component_id: order.component_id.clone(),
+                    delivery_date: order.required_date,
+                    quantity: Decimal::ZERO,
+                    zone,
+                    order_ids: Vec::new(),
+                });
+            line.quantity += order.quantity;
+            line.order_ids.push(order.id);
+        }
+
+        let mut schedules: Vec<SupplierDeliverySchedule> = Vec::new();
+        for ((supplier_id, _, _), line) in lines {
+            match schedules.last_mut() {
+                Some(last) if last.supplier_id == supplier_id => last.lines.push(line),
+                _ => {
+                    let horizon = horizons
+                        .get(&supplier_id)
+                        .copied()
+                        .unwrap_or(default_horizon);
+                    schedules.push(SupplierDeliverySchedule {
+                        supplier_id,
+                        issue_date,
+                        horizon,
+                        lines: vec![line],
+                    });
+                }
+            }
+        }
+
+        schedules
+    }
+
+    /// 將多位供應商的排程輸出為單一 CSV
+    pub fn to_csv(schedules: &[SupplierDeliverySchedule]) -> String {
+        let mut csv = String::from(SupplierDeliverySchedule::CSV_HEADER);
+        csv.push('\n');
+        for schedule in schedules {
+            schedule.write_csv_rows(&mut csv);
+        }
+        csv
+    }
+}
+
+/// CSV 欄位轉義（含逗號、引號或換行時加引號）
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::PlannedOrderType;
+
+    fn purchase(component_id: &str, supplier: &str, qty: i64, date: NaiveDate) -> PlannedOrder {
+        PlannedOrder::new(
+            component_id.to_string(),
+            Decimal::from(qty),
+            date,
+            date,
+            PlannedOrderType::Purchase,
+        )
+        .with_source_id(supplier.to_string())
+    }
+
+    #[test]
+    fn test_zones_per_supplier() {
+        let issue = NaiveDate::from_ymd_opt(2025, 11, 3).unwrap();
+        let day = |n: i64| issue + chrono::Duration::days(n);
+
+        let mut horizons = HashMap::new();
+        horizons.insert("VENDOR-A".to_string(), ScheduleHorizon::new(7, 7, 14));
+
+        let orders = vec![
+            purchase("BOLT-001", "VENDOR-A", 100, day(2)),
+            purchase("BOLT-001", "VENDOR-A", 50, day(2)),
+            purchase("BOLT-001", "VENDOR-A", 80, day(10)),
+            purchase("BOLT-001", "VENDOR-A", 60, day(20)),
+            // 超出 VENDOR-A 的展望期
+            purchase("BOLT-001", "VENDOR-A", 40, day(30)),
+            // VENDOR-B 使用預設區段，第 20 天仍在權衡區
+            purchase("NUT-001", "VENDOR-B", 70, day(20)),
+        ];
+
+        let schedules = DeliveryScheduleCalculator::generate(
+            &orders,
+            issue,
+            &horizons,
+            ScheduleHorizon::default(),
+        );
+
+        assert_eq!(schedules.len(), 2);
+
+        let vendor_a = &schedules[0];
+        assert_eq!(vendor_a.supplier_id, "VENDOR-A");
+        assert_eq!(vendor_a.lines.len(), 3);
+        assert_eq!(vendor_a.lines[0].quantity, Decimal::from(150));
+        assert_eq!(vendor_a.lines[0].order_ids.len(), 2);
+        assert_eq!(
+            vendor_a.zone_quantity(ScheduleZone::Firm),
+            Decimal::from(150)
+        );
+        assert_eq!(
+            vendor_a.zone_quantity(ScheduleZone::TradeOff),
+            Decimal::from(80)
+        );
+        assert_eq!(
+            vendor_a.zone_quantity(ScheduleZone::Forecast),
+            Decimal::from(60)
+        );
+
+        assert_eq!(schedules[1].lines[0].zone, ScheduleZone::TradeOff);
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let issue = NaiveDate::from_ymd_opt(2025, 11, 3).unwrap();
+        let orders = vec![purchase("BOLT,M8", "VENDOR-A", 100, issue)];
+
+        let schedules = DeliveryScheduleCalculator::generate(
+            &orders,
+            issue,
+            &HashMap::new(),
+            ScheduleHorizon::default(),
+        );
+        let csv = DeliveryScheduleCalculator::to_csv(&schedules);
+
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], SupplierDeliverySchedule::CSV_HEADER);
+        assert_eq!(
+            rows[1],
+            "VENDOR-A,2025-11-03,\"BOLT,M8\",2025-11-03,100,FIRM"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! 計劃訂單排程：倒排、順排與「倒排不可行時順排」
+
+use chrono::NaiveDate;
+use mrp_core::{MrpConfig, PlannedOrder, SchedulingMode, WorkCalendar};
+
+use crate::lead_time::LeadTimeCalculator;
+
+/// 訂單排程器
+pub struct OrderScheduler;
+
+impl OrderScheduler {
+    /// 依排程方式重新計算訂單的開工日與預計完工日
+    ///
+    /// 批量規則產生的訂單已是倒排結果（完工日 = 需要日期）。
+    /// 順排時開工日為 `plan_start`，完工日依訂單數量順推提前期（含變動提前期與運輸天數）；
+    /// 完工日與需要日期不同時，原需要日期記錄於 `need_date`。
+    /// 設置最早下單日期時，開工日早於該日的訂單（任何排程方式）改由該日順排。
+    pub fn schedule(
+        orders: &mut [PlannedOrder],
+        config: &MrpConfig,
+        plan_start: NaiveDate,
+        calendar: &WorkCalendar,
+    ) {
+        let earliest = config
+            .earliest_order_date
+            .map_or(plan_start, |date| date.max(plan_start));
+
+        for order in orders.iter_mut() {
+            let forward = match config.scheduling_mode {
+                SchedulingMode::Forward => true,
+                SchedulingMode::BackwardThenForward => order.order_date < earliest,
+                _ => config
+                    .earliest_order_date
+                    .is_some_and(|date| order.order_date < date),
+            };
+            if !forward {
+                continue;
+            }
+
+            let start = if calendar.is_working_day(earliest) {
+                earliest
+            } else {
+                calendar.next_working_day(earliest)
+            };
+            let lead_time = config.lead_time_for(order.quantity)
+                + config.transit_days(order.transport_mode.as_deref());
+            let completion =
+                LeadTimeCalculator::calculate_delivery_date(start, lead_time, calendar);
+
+            if completion != order.required_date {
+                order.need_date = Some(order.need_date.unwrap_or(order.required_date));
+            }
+            order.order_date = start;
+            order.required_date = completion;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{PlannedOrderType, ProcurementType};
+    use rust_decimal::Decimal;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    fn backward_order(need: u32, lead_time: u32, calendar: &WorkCalendar) -> PlannedOrder {
+        PlannedOrder::new(
+            "ASSY-001".to_string(),
+            Decimal::from(10),
+            date(need),
+            calendar.subtract_working_days(date(need), lead_time),
+            PlannedOrderType::Production,
+        )
+    }
+
+    #[test]
+    fn test_backward_then_forward() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let mut orders = vec![
+            // 11/3 需要、提前期 5 → 倒排開工 10/29 早於計劃起始日
+            backward_order(3, 5, &calendar),
+            backward_order(20, 5, &calendar),
+        ];
+
+        let config = MrpConfig::new("ASSY-001".to_string(), 5, ProcurementType::Make)
+            .with_scheduling_mode(SchedulingMode::BackwardThenForward);
+
+        OrderScheduler::schedule(&mut orders, &config, date(1), &calendar);
+
+        assert_eq!(orders[0].order_date, date(1));
+        assert_eq!(orders[0].required_date, date(6));
+        assert_eq!(orders[0].need_date, Some(date(3)));
+        assert!(orders[0].is_late());
+
+        // 倒排可行的訂單不變
+        assert_eq!(orders[1].order_date, date(15));
+        assert!(orders[1].need_date.is_none());
+    }
+
+    #[test]
+    fn test_forward_completes_early() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let mut orders = vec![backward_order(20, 5, &calendar)];
+
+        let config = MrpConfig::new("ASSY-001".to_string(), 5, ProcurementType::Make)
+            .with_scheduling_mode(SchedulingMode::Forward);
+
+        OrderScheduler::schedule(&mut orders, &config, date(1), &calendar);
+
+        assert_eq!(orders[0].required_date, date(6));
+        assert_eq!(orders[0].need_date, Some(date(20)));
+        assert!(!orders[0].is_late());
+    }
+
+    #[test]
+    fn test_forward_with_variable_lead_time() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let mut orders = vec![backward_order(20, 5, &calendar)];
+        // 10 件、每件 0.5 天 → 固定 5 + 變動 5
+        let config = MrpConfig::new("ASSY-001".to_string(), 5, ProcurementType::Make)
+            .with_scheduling_mode(SchedulingMode::Forward)
+            .with_variable_lead_time(Decimal::new(5, 1), Decimal::ONE);
+
+        OrderScheduler::schedule(&mut orders, &config, date(1), &calendar);
+
+        assert_eq!(orders[0].required_date, date(11));
+    }
+
+    #[test]
+    fn test_earliest_order_date_gate() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let mut orders = vec![
+            backward_order(12, 5, &calendar),
+            backward_order(25, 5, &calendar),
+        ];
+        // 模具驗證 11/10 完成前不得下單
+        let config = MrpConfig::new("HOUSING-NPI".to_string(), 5, ProcurementType::Buy)
+            .with_earliest_order_date(date(10));
+
+        OrderScheduler::schedule(&mut orders, &config, date(1), &calendar);
+
+        assert_eq!(orders[0].order_date, date(10));
+        assert_eq!(orders[0].required_date, date(15));
+        assert!(orders[0].is_late());
+        assert_eq!(orders[1].order_date, date(20));
+        assert!(orders[1].need_date.is_none());
+    }
+}
@@ -0,0 +1,414 @@
+//! 計劃查詢殼層：載入計劃快照後以文字命令查詢物料、追溯與例外，對單一物料試算 what-if，並匯出選取的訂單
+//!
+//! 殼層只負責解析命令與產生文字輸出，不綁定終端機；命令列工具以 [`PlanShell::run`]
+//! 接上標準輸入輸出即可。
+//!
+//! 存檔只保存計劃訂單等內容（見 [`crate::archive`]），從存檔載入的快照沒有時間分段記錄，
+//! MRP 表格與 what-if 都需以 [`PlanShell::with_what_if`] 提供計算器與原始輸入。
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, DemandType, Inventory, MrpError, PlannedOrder, Supply};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{MrpCalculator, PlanArchive, PlanSnapshot, TimePhasedRecord, WarningSeverity};
+
+const HELP: &str = "\
+items                         列出有計劃訂單的物料
+grid <物料>                   物料的 MRP 表格（毛需求、預計收貨、計劃入庫、預計庫存、淨需求）
+orders <物料>                 物料的計劃訂單（依需要日期）
+pegging <物料>                計劃訂單追溯到的需求
+exceptions [物料]             警告與錯誤
+whatif <物料> <數量> <日期>   加入一筆需求重新計算，比較該物料的計劃訂單
+export <物料> <檔案>          以 JSON Lines 匯出物料的計劃訂單
+quit                          離開";
+
+/// what-if 試算所需的計算器與原始輸入
+struct WhatIfInputs {
+    calculator: MrpCalculator,
+    demands: Vec<Demand>,
+    supplies: Vec<Supply>,
+    inventories: Vec<Inventory>,
+}
+
+/// 計劃查詢殼層
+pub struct PlanShell {
+    snapshot: PlanSnapshot,
+    what_if: Option<WhatIfInputs>,
+}
+
+impl PlanShell {
+    /// 以計劃快照建立殼層
+    pub fn new(snapshot: PlanSnapshot) -> Self {
+        Self {
+            snapshot,
+            what_if: None,
+        }
+    }
+
+    /// 從 [`PlanArchive::save`] 保存的 JSON 載入
+    pub fn load(json: &str) -> mrp_core::Result<Self> {
+        PlanArchive::load(json).map(Self::new)
+    }
+
+    /// 建構器模式：提供計算器與原始輸入以啟用 what-if 試算
+    pub fn with_what_if(
+        mut self,
+        calculator: MrpCalculator,
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+    ) -> Self {
+        self.what_if = Some(WhatIfInputs {
+            calculator,
+            demands,
+            supplies,
+            inventories,
+        });
+        self
+    }
+
+    /// 互動迴圈：逐行讀取命令並寫出結果，直到 `quit`／`exit` 或輸入結束
+    ///
+    /// 命令錯誤只輸出錯誤訊息，不中斷迴圈。
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> mrp_core::Result<()> {
+        let io_error = |e: std::io::Error| MrpError::Other(e.to_string());
+        write!(output, "mrp> ").map_err(io_error)?;
+        output.flush().map_err(io_error)?;
+        for line in input.lines() {
+            let line = line.map_err(io_error)?;
+            let command = line.trim();
+            if command == "quit" || command == "exit" {
+                break;
+            }
+            if !command.is_empty() {
+                match self.execute(command) {
+                    Ok(text) => writeln!(output, "{}", text),
+                    Err(e) => writeln!(output, "錯誤: {}", e),
+                }
+                .map_err(io_error)?;
+            }
+            write!(output, "mrp> ").map_err(io_error)?;
+            output.flush().map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    /// 執行單一命令，回傳輸出文字
+    pub fn execute(&mut self, line: &str) -> mrp_core::Result<String> {
+        let args: Vec<&str> = line.split_whitespace().collect();
+        match args.as_slice() {
+            ["help"] => Ok(HELP.to_string()),
+            ["items"] => Ok(self.items()),
+            ["grid", item] => self.grid(item),
+            ["orders", item] => Ok(Self::order_list(&self.orders(item))),
+            ["pegging", item] => Ok(self.pegging(item)),
+            ["exceptions"] => Ok(self.exceptions(None)),
+            ["exceptions", item] => Ok(self.exceptions(Some(item))),
+            ["whatif", item, quantity, date] => self.what_if(item, quantity, date),
+            ["export", item, path] => self.export(item, Path::new(path)),
+            _ => Err(MrpError::Other(format!(
+                "無法辨識的命令: {}（輸入 help 查看命令）",
+                line
+            ))),
+        }
+    }
+
+    /// 物料的計劃訂單（依需要日期排序）
+    fn orders(&self, item: &str) -> Vec<&PlannedOrder> {
+        let mut orders: Vec<&PlannedOrder> = self
+            .snapshot
+            .planned_orders
+            .iter()
+            .filter(|o| o.component_id == item)
+            .collect();
+        orders.sort_by_key(|o| o.required_date);
+        orders
+    }
+
+    fn items(&self) -> String {
+        let mut items: BTreeMap<&str, (usize, Decimal)> = BTreeMap::new();
+        for order in &self.snapshot.planned_orders {
+            let entry = items.entry(&order.component_id).or_default();
+            entry.0 += 1;
+            entry.1 += order.quantity;
+        }
+        let mut text = String::new();
+        for (item, (count, quantity)) in items {
+            let _ = writeln!(text, "{:<20} {:>4} 張 {:>12}", item, count, quantity);
+        }
+        text.trim_end().to_string()
+    }
+
+    /// 物料的 MRP 表格：快照沒有時間分段記錄（從存檔載入）時以原始輸入重新計算
+    fn grid(&self, item: &str) -> mrp_core::Result<String> {
+        if !self.snapshot.time_phased.is_empty() {
+            return Ok(Self::time_phased_grid(
+                self.snapshot
+                    .time_phased
+                    .get(item)
+                    .map_or(&[], Vec::as_slice),
+            ));
+        }
+        let inputs = self.what_if.as_ref().ok_or_else(|| {
+            MrpError::Other("快照沒有時間分段記錄，需提供計算器與原始輸入".to_string())
+        })?;
+        let result = inputs.calculator.calculate(
+            inputs.demands.clone(),
+            inputs.supplies.clone(),
+            inputs.inventories.clone(),
+        )?;
+        Ok(Self::time_phased_grid(
+            result.time_phased.get(item).map_or(&[], Vec::as_slice),
+        ))
+    }
+
+    /// 以日期為欄、各數量為列的 MRP 表格
+    fn time_phased_grid(records: &[TimePhasedRecord]) -> String {
+        if records.is_empty() {
+            return "沒有時間分段記錄".to_string();
+        }
+        let mut text = format!("{:<10}", "日期");
+        for record in records {
+            let _ = write!(text, " {:>12}", record.date.to_string());
+        }
+        type Value = fn(&TimePhasedRecord) -> Decimal;
+        let rows: [(&str, Value); 5] = [
+            ("毛需求", |r| r.gross_requirement),
+            ("預計收貨", |r| r.scheduled_receipt),
+            ("計劃入庫", |r| r.planned_receipt),
+            ("預計庫存", |r| r.projected_on_hand),
+            ("淨需求", |r| r.net_requirement),
+        ];
+        for (label, value) in rows {
+            let _ = write!(text, "\n{:<10}", label);
+            for record in records {
+                let _ = write!(text, " {:>12}", value(record));
+            }
+        }
+        text
+    }
+
+    fn order_list(orders: &[&PlannedOrder]) -> String {
+        let mut text = format!(
+            "{:<12} {:<12} {:>12} {}",
+            "需要日期", "下單日", "數量", "類型"
+        );
+        for order in orders {
+            let _ = write!(
+                text,
+                "\n{:<12} {:<12} {:>12} {:?}",
+                order.required_date, order.order_date, order.quantity, order.order_type
+            );
+        }
+        text
+    }
+
+    fn pegging(&self, item: &str) -> String {
+        let mut text = String::new();
+        for order in self.orders(item) {
+            let _ = writeln!(
+                text,
+                "{} {} 數量 {}",
+                order.id, order.required_date, order.quantity
+            );
+            for record in &order.pegging {
+                let _ = writeln!(
+                    text,
+                    "  ← 需求 {} 數量 {} {}",
+                    record.demand_id,
+                    record.quantity,
+                    record.path.join(" > ")
+                );
+            }
+        }
+        text.trim_end().to_string()
+    }
+
+    fn exceptions(&self, item: Option<&str>) -> String {
+        let lines: Vec<String> = self
+            .snapshot
+            .warnings
+            .iter()
+            .filter(|w| w.severity != WarningSeverity::Info)
+            .filter(|w| item.map_or(true, |id| w.component_id == id))
+            .map(|w| format!("[{:?}] {}: {}", w.severity, w.component_id, w.message))
+            .collect();
+        if lines.is_empty() {
+            "沒有例外".to_string()
+        } else {
+            lines.join("\n")
+        }
+    }
+
+    fn what_if(&self, item: &str, quantity: &str, date: &str) -> mrp_core::Result<String> {
+        let inputs = self.what_if.as_ref().ok_or_else(|| {
+            MrpError::Other("未提供計算器與原始輸入，無法執行 what-if".to_string())
+        })?;
+        let quantity = Decimal::from_str(quantity)
+            .map_err(|e| MrpError::Other(format!("無效的數量 {}: {}", quantity, e)))?;
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| MrpError::InvalidDate(format!("{}: {}", date, e)))?;
+
+        let mut demands = inputs.demands.clone();
+        demands.push(Demand::new(
+            item.to_string(),
+            quantity,
+            date,
+            DemandType::SalesOrder,
+        ));
+        let result = inputs.calculator.calculate(
+            demands,
+            inputs.supplies.clone(),
+            inputs.inventories.clone(),
+        )?;
+
+        let before = self.orders(item);
+        let mut after: Vec<&PlannedOrder> = result
+            .planned_orders
+            .iter()
+            .filter(|o| o.component_id == item)
+            .collect();
+        after.sort_by_key(|o| o.required_date);
+        let total =
+            |orders: &[&PlannedOrder]| -> Decimal { orders.iter().map(|o| o.quantity).sum() };
+        Ok(format!(
+            "原計劃 {} 張（合計 {}），試算後 {} 張（合計 {}）\n{}",
+            before.len(),
+            total(&before),
+            after.len(),
+            total(&after),
+            Self::time_phased_grid(result.time_phased.get(item).map_or(&[], Vec::as_slice))
+        ))
+    }
+
+    fn export(&self, item: &str, path: &Path) -> mrp_core::Result<String> {
+        let orders = self.orders(item);
+        let mut lines = String::new();
+        for order in &orders {
+            let line =
+                serde_json::to_string(order).map_err(|e| MrpError::ExportError(e.to_string()))?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+        std::fs::write(path, lines)
+            .map_err(|e| MrpError::ExportError(format!("無法寫入 {}: {}", path.display(), e)))?;
+        Ok(format!(
+            "已匯出 {} 張計劃訂單到 {}",
+            orders.len(),
+            path.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bom_graph::BomGraph;
+    use mrp_core::{MrpConfig, ProcurementType, WorkCalendar};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_query_and_what_if() {
+        let configs = HashMap::from([(
+            "VALVE".to_string(),
+            MrpConfig::new("VALVE".to_string(), 2, ProcurementType::Buy),
+        )]);
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+        let demands = vec![Demand::new(
+            "VALVE".to_string(),
+            Decimal::from(40),
+            NaiveDate::from_ymd_opt(2025, 11, 10).unwrap(),
+            DemandType::SalesOrder,
+        )];
+        let result = calculator
+            .calculate(demands.clone(), vec![], vec![])
+            .unwrap();
+        let mut shell = PlanShell::new(PlanSnapshot::new(result)).with_what_if(
+            calculator,
+            demands,
+            vec![],
+            vec![],
+        );
+
+        assert!(shell.execute("items").unwrap().starts_with("VALVE"));
+        assert!(shell
+            .execute("orders VALVE")
+            .unwrap()
+            .contains("2025-11-10"));
+        let what_if = shell.execute("whatif VALVE 25 2025-11-20").unwrap();
+        assert!(what_if.starts_with("原計劃 1 張（合計 40），試算後 2 張"));
+        assert!(what_if.contains("2025-11-20"));
+        assert!(shell.execute("whatif VALVE 25 20251120").is_err());
+        assert!(shell.execute("frobnicate").is_err());
+
+        let mut output = Vec::new();
+        shell
+            .run("exceptions\nquit\nitems\n".as_bytes(), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("[Warning] VALVE"));
+        assert!(!output.contains("張"));
+    }
+
+    #[test]
+    fn test_grid_renders_time_phased_rows() {
+        let configs = HashMap::from([(
+            "VALVE".to_string(),
+            MrpConfig::new("VALVE".to_string(), 2, ProcurementType::Buy),
+        )]);
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+        let date = |day| NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        let demands = vec![Demand::new(
+            "VALVE".to_string(),
+            Decimal::from(40),
+            date(10),
+            DemandType::SalesOrder,
+        )];
+        let supplies = vec![Supply::new(
+            "VALVE".to_string(),
+            Decimal::from(15),
+            date(5),
+            mrp_core::SupplyType::PurchaseOrder,
+        )];
+        let result = calculator
+            .calculate(demands.clone(), supplies.clone(), vec![])
+            .unwrap();
+        let grid = [
+            "日期           2025-11-05   2025-11-10",
+            "毛需求                   0           40",
+            "預計收貨                 15            0",
+            "計劃入庫                  0           25",
+            "預計庫存                 15            0",
+            "淨需求                   0           25",
+        ]
+        .join("\n");
+
+        let mut shell = PlanShell::new(PlanSnapshot::new(result.clone()));
+        assert_eq!(shell.execute("grid VALVE").unwrap(), grid);
+        assert_eq!(shell.execute("grid PUMP").unwrap(), "沒有時間分段記錄");
+
+        // 存檔不保存時間分段記錄：沒有原始輸入時無法顯示表格，提供後重新計算
+        let archived = PlanArchive::save(&PlanSnapshot::new(result)).unwrap();
+        let mut shell = PlanShell::load(&archived).unwrap();
+        assert!(shell.execute("grid VALVE").is_err());
+        let mut shell = shell.with_what_if(calculator, demands, supplies, vec![]);
+        assert_eq!(shell.execute("grid VALVE").unwrap(), grid);
+        assert!(shell
+            .execute("whatif VALVE 10 2025-11-10")
+            .unwrap()
+            .contains("計劃入庫                  0           35"));
+    }
+}
@@ -0,0 +1,186 @@
+//! 呆滯／慢動物料分析：依計劃需求推算現有庫存的消耗
+//!
+//! 現有庫存按先進先出先於任何到貨被需求消耗；在分析窗口內未被消耗的庫存
+//! 即為慢動庫存，整個計劃期間都未被消耗的物料視為呆滯料。
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, Inventory};
+
+use crate::scenario::ConfigSet;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// 慢動類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SlowMoverKind {
+    /// 窗口內僅部分消耗
+    Slow,
+    /// 整個計劃期間都沒有消耗
+    Dead,
+}
+
+/// 慢動物料
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SlowMover {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 現有庫存
+    pub on_hand_qty: Decimal,
+
+    /// 窗口內未消耗的數量
+    pub unconsumed_qty: Decimal,
+
+    /// 未消耗庫存金額（物料未設置單位成本時為 None）
+    pub unconsumed_value: Option<Decimal>,
+
+    /// 現有庫存最後被消耗的日期（計劃期間內）
+    pub last_consumption_date: Option<NaiveDate>,
+
+    /// 慢動類型
+    pub kind: SlowMoverKind,
+}
+
+/// 慢動物料分析器
+pub struct SlowMoverAnalyzer;
+
+impl SlowMoverAnalyzer {
+    /// 找出現有庫存在 `as_of + window_days` 前未被消耗完的物料
+    ///
+    /// `demands_by_component` 應包含獨立需求與 BOM 展開的相依需求；
+    /// 已分配數量視為立即消耗。結果按未消耗金額（無金額時按數量）由大到小排序。
+    pub fn analyze(
+        inventories: &HashMap<String, Inventory>,
+        demands_by_component: &HashMap<String, Vec<&Demand>>,
+        configs: &ConfigSet,
+        as_of: NaiveDate,
+        window_days: u32,
+    ) -> Vec<SlowMover> {
+        let window_end = as_of + chrono::Duration::days(window_days as i64);
+        let mut slow_movers = Vec::new();
+
+        for (component_id, inventory) in inventories {
+            if inventory.on_hand_qty <= Decimal::ZERO {
+                continue;
+            }
+
+            let mut demands: Vec<&Demand> = demands_by_component
+                .get(component_id)
+                .cloned()
+                .unwrap_or_default();
+            demands.sort_by_key(|d| d.required_date);
+
+            // 先進先出：現有庫存（扣除已分配）先被需求消耗
+            let mut stock = (inventory.on_hand_qty - inventory.allocated_qty).max(Decimal::ZERO);
+            let mut unconsumed_in_window = stock;
+            let mut last_consumption_date = None;
+            for demand in demands {
+                if stock <= Decimal::ZERO {
+                    break;
+                }
+                let consumed = demand.quantity.min(stock);
+                stock -= consumed;
+                last_consumption_date = Some(demand.required_date);
+                if demand.required_date <= window_end {
+                    unconsumed_in_window -= consumed;
+                }
+            }
+
+            if unconsumed_in_window <= Decimal::ZERO {
+                continue;
+            }
+
+            let unit_cost = configs.get(component_id).and_then(|c| c.unit_cost);
+            slow_movers.push(SlowMover {
+                component_id: component_id.clone(),
+                on_hand_qty: inventory.on_hand_qty,
+                unconsumed_qty: unconsumed_in_window,
+                unconsumed_value: unit_cost.map(|cost| cost * unconsumed_in_window),
+                last_consumption_date,
+                kind: if last_consumption_date.is_none() {
+                    SlowMoverKind::Dead
+                } else {
+                    SlowMoverKind::Slow
+                },
+            });
+        }
+
+        slow_movers.sort_by(|a, b| {
+            b.unconsumed_value
+                .cmp(&a.unconsumed_value)
+                .then(b.unconsumed_qty.cmp(&a.unconsumed_qty))
+                .then(a.component_id.cmp(&b.component_id))
+        });
+
+        slow_movers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{DemandType, MrpConfig, ProcurementType};
+
+    #[test]
+    fn test_slow_and_dead_movers() {
+        let as_of = NaiveDate::from_ymd_opt(2025, 11, 1).unwrap();
+
+        let mut inventories = HashMap::new();
+        for (id, qty) in [("SLOW", 100), ("DEAD", 40), ("FAST", 10)] {
+            inventories.insert(
+                id.to_string(),
+                Inventory::new(id.to_string(), Decimal::from(qty), Decimal::ZERO),
+            );
+        }
+
+        let demand = |id: &str, qty: i64, day: i64| {
+            Demand::new(
+                id.to_string(),
+                Decimal::from(qty),
+                as_of + chrono::Duration::days(day),
+                DemandType::SalesOrder,
+            )
+        };
+        let demands = [
+            demand("SLOW", 30, 5),
+            demand("SLOW", 50, 60),
+            demand("FAST", 10, 3),
+        ];
+        let mut by_component: HashMap<String, Vec<&Demand>> = HashMap::new();
+        for d in &demands {
+            by_component
+                .entry(d.component_id.clone())
+                .or_default()
+                .push(d);
+        }
+
+        let mut configs = HashMap::new();
+        configs.insert(
+            "SLOW".to_string(),
+            MrpConfig::new("SLOW".to_string(), 5, ProcurementType::Buy)
+                .with_unit_cost(Decimal::from(2)),
+        );
+
+        let result = SlowMoverAnalyzer::analyze(
+            &inventories,
+            &by_component,
+            &ConfigSet::from(configs),
+            as_of,
+            30,
+        );
+
+        assert_eq!(result.len(), 2);
+
+        // 有金額者排前：SLOW 窗口內只消耗 30
+        assert_eq!(result[0].component_id, "SLOW");
+        assert_eq!(result[0].unconsumed_qty, Decimal::from(70));
+        assert_eq!(result[0].unconsumed_value, Some(Decimal::from(140)));
+        assert_eq!(result[0].kind, SlowMoverKind::Slow);
+
+        assert_eq!(result[1].component_id, "DEAD");
+        assert_eq!(result[1].kind, SlowMoverKind::Dead);
+        assert_eq!(result[1].unconsumed_value, None);
+    }
+}
@@ -0,0 +1,353 @@
+//! 計劃快照：不可變、可共享的計算結果與寫時複製編輯層
+//!
+//! 計算完成的 `MrpResult` 封裝為 `Arc` 快照，讀取端複製快照只增加引用計數；
+//! 編輯透過 `PlanEdit` 記錄差異，提交時才產生新快照，原快照保持不變。
+
+use mrp_core::{MrpError, OrderAnnotation, PlannedOrder};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+use crate::{CarryoverTolerance, MrpResult};
+
+/// 不可變計劃快照（複製成本為一次引用計數遞增）
+#[derive(Debug, Clone)]
+pub struct PlanSnapshot {
+    version: u64,
+    result: Arc<MrpResult>,
+}
+
+impl PlanSnapshot {
+    /// 從計算結果創建版本 1 的快照
+    pub fn new(result: MrpResult) -> Self {
+        Self {
+            version: 1,
+            result: Arc::new(result),
+        }
+    }
+
+    /// 以指定版本創建快照（從存檔還原時使用）
+    pub(crate) fn with_version(result: MrpResult, version: u64) -> Self {
+        Self {
+            version,
+            result: Arc::new(result),
+        }
+    }
+
+    /// 快照版本
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// 共享的計算結果
+    pub fn result(&self) -> &Arc<MrpResult> {
+        &self.result
+    }
+
+    /// 檢查兩個快照是否共享同一份資料
+    pub fn ptr_eq(&self, other: &PlanSnapshot) -> bool {
+        Arc::ptr_eq(&self.result, &other.result)
+    }
+
+    /// 開始編輯（寫時複製，不影響本快照）
+    pub fn edit(&self) -> PlanEdit {
+        PlanEdit {
+            base: self.clone(),
+            changes: HashMap::new(),
+            added: Vec::new(),
+            annotations: HashMap::new(),
+        }
+    }
+}
+
+impl Deref for PlanSnapshot {
+    type Target = MrpResult;
+
+    fn deref(&self) -> &MrpResult {
+        &self.result
+    }
+}
+
+/// 計劃編輯層（僅記錄差異，提交時才複製）
+#[derive(Debug, Clone)]
+pub struct PlanEdit {
+    base: PlanSnapshot,
+    /// 基底訂單的修改（None 表示刪除）
+    changes: HashMap<Uuid, Option<PlannedOrder>>,
+    added: Vec<PlannedOrder>,
+    /// 註記修改（None 表示清除）
+    annotations: HashMap<Uuid, Option<OrderAnnotation>>,
+}
+
+impl PlanEdit {
+    /// 編輯所基於的快照版本
+    pub fn base_version(&self) -> u64 {
+        self.base.version
+    }
+
+    /// 是否有未提交的修改
+    pub fn is_dirty(&self) -> bool {
+        !self.changes.is_empty() || !self.added.is_empty() || !self.annotations.is_empty()
+    }
+
+    /// 查詢訂單（含未提交的修改）
+    pub fn order(&self, order_id: Uuid) -> Option<&PlannedOrder> {
+        if let Some(added) = self.added.iter().find(|o| o.id == order_id) {
+            return Some(added);
+        }
+        match self.changes.get(&order_id) {
+            Some(changed) => changed.as_ref(),
+            None => self.base.planned_orders.iter().find(|o| o.id == order_id),
+        }
+    }
+
+    /// 修改訂單
+    pub fn update_order<F>(&mut self, order_id: Uuid, f: F) -> mrp_core::Result<()>
+    where
+        F: FnOnce(&mut PlannedOrder),
+    {
+        if let Some(added) = self.added.iter_mut().find(|o| o.id == order_id) {
+            f(added);
+            return Ok(());
+        }
+
+        let mut order = self
+            .order(order_id)
+            .cloned()
+            .ok_or_else(|| MrpError::Other(format!("找不到計劃訂單: {}", order_id)))?;
+        f(&mut order);
+        self.changes.insert(order_id, Some(order));
+        Ok(())
+    }
+
+    /// 刪除訂單
+    pub fn remove_order(&mut self, order_id: Uuid) -> mrp_core::Result<()> {
+        if let Some(pos) = self.added.iter().position(|o| o.id == order_id) {
+            self.added.remove(pos);
+            return Ok(());
+        }
+        if self.order(order_id).is_none() {
+            return Err(MrpError::Other(format!("找不到計劃訂單: {}", order_id)));
+        }
+        self.changes.insert(order_id, None);
+        Ok(())
+    }
+
+    /// 新增訂單
+    pub fn add_order(&mut self, order: PlannedOrder) {
+        self.added.push(order);
+    }
+
+    /// 設置訂單註記（空註記視為清除）
+    pub fn annotate(
+        &mut self,
+        order_id: Uuid,
+        annotation: OrderAnnotation,
+    ) -> mrp_core::Result<()> {
+        if self.order(order_id).is_none() {
+            return Err(MrpError::Other(format!("找不到計劃訂單: {}", order_id)));
+        }
+        let value = (!annotation.is_empty()).then_some(annotation);
+        self.annotations.insert(order_id, value);
+        Ok(())
+    }
+
+    /// 提交修改，產生新版本快照
+    pub fn commit(self) -> PlanSnapshot {
+        if !self.is_dirty() {
+            return self.base;
+        }
+
+        // 複製基底（編輯持有唯一引用時直接取用），只修改訂單與註記
+        let version = self.base.version + 1;
+        let mut result =
+            Arc::try_unwrap(self.base.result).unwrap_or_else(|shared| (*shared).clone());
+        let mut changes = self.changes;
+        result
+            .planned_orders
+            .retain(|o| !matches!(changes.get(&o.id), Some(None)));
+        for order in &mut result.planned_orders {
+            if let Some(Some(changed)) = changes.remove(&order.id) {
+                *order = changed;
+            }
+        }
+        result.planned_orders.extend(self.added);
+
+        for (order_id, annotation) in self.annotations {
+            match annotation {
+                Some(annotation) => result.annotations.insert(order_id, annotation),
+                None => result.annotations.remove(&order_id),
+            };
+        }
+        // 已刪除訂單的註記一併移除
+        let order_ids: std::collections::HashSet<Uuid> =
+            result.planned_orders.iter().map(|o| o.id).collect();
+        result.annotations.retain(|id, _| order_ids.contains(id));
+
+        PlanSnapshot {
+            version,
+            result: Arc::new(result),
+        }
+    }
+}
+
+/// 計劃發布點：多個讀取者共享目前快照，新計算完成後原子替換
+#[derive(Debug, Default)]
+pub struct PlanStore {
+    current: RwLock<Option<PlanSnapshot>>,
+}
+
+impl PlanStore {
+    /// 創建空的發布點
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 目前快照（尚未發布時為 None）
+    pub fn current(&self) -> Option<PlanSnapshot> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 發布新計算結果（版本號接續目前快照）
+    pub fn publish(&self, result: MrpResult) -> PlanSnapshot {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        let version = current.as_ref().map_or(0, |s| s.version) + 1;
+        let snapshot = PlanSnapshot {
+            version,
+            result: Arc::new(result),
+        };
+        *current = Some(snapshot.clone());
+        snapshot
+    }
+
+    /// 發布新計算結果，並從目前快照延續容差內訂單的識別與註記
+    pub fn publish_with_carryover(
+        &self,
+        mut result: MrpResult,
+        tolerance: CarryoverTolerance,
+    ) -> PlanSnapshot {
+        if let Some(current) = self.current() {
+            result.carry_over_from(&current, tolerance);
+        }
+        self.publish(result)
+    }
+
+    /// 提交編輯（樂觀鎖：基底版本須為目前版本）
+    pub fn commit(&self, edit: PlanEdit) -> mrp_core::Result<PlanSnapshot> {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        let current_version = current.as_ref().map(|s| s.version);
+        if current_version != Some(edit.base_version()) {
+            return Err(MrpError::Other(format!(
+                "計劃版本衝突: 編輯基於版本 {}，目前版本 {:?}",
+                edit.base_version(),
+                current_version
+            )));
+        }
+
+        if !edit.is_dirty() {
+            return Ok(edit.base);
+        }
+
+        let mut snapshot = edit.commit();
+        snapshot.version = current_version.unwrap_or(0) + 1;
+        *current = Some(snapshot.clone());
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::PlannedOrderType;
+    use rust_decimal::Decimal;
+
+    fn plan() -> MrpResult {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        let mut result = MrpResult::empty();
+        result.planned_orders.push(PlannedOrder::new(
+            "BIKE-001".to_string(),
+            Decimal::from(100),
+            date,
+            date,
+            PlannedOrderType::Production,
+        ));
+        result
+    }
+
+    #[test]
+    fn test_edit_is_copy_on_write() {
+        let mut result = plan();
+        result.add_warning(crate::MrpWarning::info(
+            "BIKE-001".to_string(),
+            "測試".to_string(),
+        ));
+        let snapshot = PlanSnapshot::new(result);
+        let reader = snapshot.clone();
+        assert!(reader.ptr_eq(&snapshot));
+
+        let order_id = snapshot.planned_orders[0].id;
+        let mut edit = snapshot.edit();
+        edit.update_order(order_id, |o| o.quantity = Decimal::from(120))
+            .unwrap();
+        assert_eq!(edit.order(order_id).unwrap().quantity, Decimal::from(120));
+
+        let edited = edit.commit();
+        assert_eq!(edited.version(), 2);
+        assert_eq!(edited.planned_orders[0].quantity, Decimal::from(120));
+        assert_eq!(edited.warnings.len(), 1);
+        // 原快照不受影響
+        assert_eq!(reader.planned_orders[0].quantity, Decimal::from(100));
+        assert!(!edited.ptr_eq(&reader));
+    }
+
+    #[test]
+    fn test_store_rejects_stale_edit() {
+        let store = PlanStore::new();
+        let first = store.publish(plan());
+
+        let mut stale = first.edit();
+        stale.remove_order(first.planned_orders[0].id).unwrap();
+
+        // 新一輪計算先發布
+        let second = store.publish(plan());
+        assert_eq!(second.version(), 2);
+
+        assert!(store.commit(stale).is_err());
+        assert_eq!(store.current().unwrap().version(), 2);
+
+        let mut fresh = second.edit();
+        fresh.remove_order(second.planned_orders[0].id).unwrap();
+        let committed = store.commit(fresh).unwrap();
+        assert_eq!(committed.version(), 3);
+        assert!(committed.planned_orders.is_empty());
+    }
+
+    #[test]
+    fn test_annotations_survive_regeneration() {
+        let store = PlanStore::new();
+        let first = store.publish(plan());
+        let order_id = first.planned_orders[0].id;
+
+        let mut edit = first.edit();
+        edit.annotate(
+            order_id,
+            OrderAnnotation::new()
+                .with_note("供應商確認可提前".to_string())
+                .with_tag("expedite".to_string()),
+        )
+        .unwrap();
+        store.commit(edit).unwrap();
+
+        // 重新計算產生新的訂單ID，容差內沿用上次識別與註記
+        let regenerated = store.publish_with_carryover(plan(), CarryoverTolerance::default());
+        assert_eq!(regenerated.planned_orders[0].id, order_id);
+        assert!(regenerated
+            .annotation(order_id)
+            .is_some_and(|a| a.has_tag("expedite")));
+    }
+}
@@ -0,0 +1,165 @@
+//! 替代料：主料預計可用量不足時，將相依需求轉給替代群組中的下一優先子件
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, Inventory, Supply};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 替代料替換記錄
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ComponentSubstitution {
+    /// 父件ID
+    pub parent_id: String,
+
+    /// 父件計劃訂單ID
+    pub parent_order_id: Uuid,
+
+    /// 替代群組
+    pub alternative_group: String,
+
+    /// 主料（群組中優先級最高的子件）
+    pub primary_id: String,
+
+    /// 實際使用的替代料
+    pub substitute_id: String,
+
+    /// 替代料的相依需求ID
+    pub demand_id: Uuid,
+
+    /// 替代料需求數量
+    pub quantity: Decimal,
+
+    /// 需求日期
+    pub required_date: NaiveDate,
+}
+
+/// 替代料選擇器
+///
+/// 預計可用量 = 現有可用庫存 + 截至需求日期的供應 − 截至需求日期的獨立需求
+/// − 本次計算已分派給該子件的相依需求（僅計入經由替代群組分派的數量）。
+pub(crate) struct AlternativeSelector<'a> {
+    inventories: &'a HashMap<String, Inventory>,
+    supplies: &'a HashMap<String, Vec<Supply>>,
+    independent_demands: &'a HashMap<String, Vec<Demand>>,
+    consumed: HashMap<String, Decimal>,
+    substitutions: Vec<ComponentSubstitution>,
+}
+
+impl<'a> AlternativeSelector<'a> {
+    /// 創建選擇器
+    pub(crate) fn new(
+        inventories: &'a HashMap<String, Inventory>,
+        supplies: &'a HashMap<String, Vec<Supply>>,
+        independent_demands: &'a HashMap<String, Vec<Demand>>,
+    ) -> Self {
+        Self {
+            inventories,
+            supplies,
+            independent_demands,
+            consumed: HashMap::new(),
+            substitutions: Vec::new(),
+        }
+    }
+
+    /// 子件截至指定日期的預計可用量
+    fn available(&self, component_id: &str, date: NaiveDate) -> Decimal {
+        let on_hand = self
+            .inventories
+            .get(component_id)
+            .map_or(Decimal::ZERO, |inv| inv.available_qty);
+        let receipts: Decimal = self
+            .supplies
+            .get(component_id)
+            .into_iter()
+            .flatten()
+            .filter(|s| s.available_date <= date)
+            .map(|s| s.quantity)
+            .sum();
+        let demand: Decimal = self
+            .independent_demands
+            .get(component_id)
+            .into_iter()
+            .flatten()
+            .filter(|d| d.required_date <= date)
+            .map(|d| d.quantity)
+            .sum();
+        let consumed = self
+            .consumed
+            .get(component_id)
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+
+        on_hand + receipts - demand - consumed
+    }
+
+    /// 從按優先級排序的候選（子件ID, 需求數量, 需求日期）中選擇第一個可用量足夠者；
+    /// 都不足時使用主料（由主料的 MRP 產生計劃訂單）
+    pub(crate) fn select(&mut self, candidates: &[(&str, Decimal, NaiveDate)]) -> usize {
+        let chosen = candidates
+            .iter()
+            .position(|&(id, quantity, date)| self.available(id, date) >= quantity)
+            .unwrap_or(0);
+        let (id, quantity, _) = candidates[chosen];
+        *self.consumed.entry(id.to_string()).or_default() += quantity;
+        chosen
+    }
+
+    /// 記錄替換
+    pub(crate) fn record(&mut self, substitution: ComponentSubstitution) {
+        self.substitutions.push(substitution);
+    }
+
+    /// 已記錄的替換數
+    pub(crate) fn substitution_count(&self) -> usize {
+        self.substitutions.len()
+    }
+
+    /// 已記錄的替換
+    pub(crate) fn substitutions(&self) -> &[ComponentSubstitution] {
+        &self.substitutions
+    }
+
+    /// 取出所有替換記錄
+    pub(crate) fn into_substitutions(self) -> Vec<ComponentSubstitution> {
+        self.substitutions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::SupplyType;
+
+    #[test]
+    fn test_select_by_projected_availability() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        let mut inventories = HashMap::new();
+        inventories.insert(
+            "CHIP-B".to_string(),
+            Inventory::new("CHIP-B".to_string(), Decimal::from(30), Decimal::ZERO),
+        );
+        let mut supplies = HashMap::new();
+        supplies.insert(
+            "CHIP-A".to_string(),
+            vec![Supply::new(
+                "CHIP-A".to_string(),
+                Decimal::from(100),
+                date + chrono::Duration::days(5),
+                SupplyType::PurchaseOrder,
+            )],
+        );
+        let demands = HashMap::new();
+        let mut selector = AlternativeSelector::new(&inventories, &supplies, &demands);
+
+        let candidates = [
+            ("CHIP-A", Decimal::from(20), date),
+            ("CHIP-B", Decimal::from(20), date),
+        ];
+        // 主料的供應在需求日之後到貨 → 改用有庫存的替代料
+        assert_eq!(selector.select(&candidates), 1);
+        // 替代料剩 10，不足 → 回到主料
+        assert_eq!(selector.select(&candidates), 0);
+    }
+}
@@ -0,0 +1,157 @@
+//! 現有供應清理建議：需求減少後，已不被任何需求追溯的採購單／工單建議取消或減量
+
+use chrono::NaiveDate;
+use mrp_core::{Supply, SupplyType};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::coverage::{CoverageSourceType, DemandCoverage};
+
+/// 清理建議動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CleanupAction {
+    /// 整筆供應已不需要
+    Cancel,
+
+    /// 只需要部分數量
+    Reduce,
+}
+
+/// 單筆現有供應的清理建議
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SupplyCleanup {
+    /// 供應ID
+    pub supply_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 供應類型
+    pub supply_type: SupplyType,
+
+    /// 可用日期
+    pub available_date: NaiveDate,
+
+    /// 供應數量
+    pub quantity: Decimal,
+
+    /// 仍需要的數量（需求追溯 + 維持安全庫存）
+    pub needed_quantity: Decimal,
+
+    /// 建議動作
+    pub action: CleanupAction,
+}
+
+impl SupplyCleanup {
+    /// 不需要的數量
+    pub fn unneeded_quantity(&self) -> Decimal {
+        self.quantity - self.needed_quantity
+    }
+}
+
+/// 清理建議計算器
+pub(crate) struct SupplyCleanupAdvisor;
+
+impl SupplyCleanupAdvisor {
+    /// 依需求覆蓋明細（含相依需求）找出單一物料未被完全追溯的現有供應
+    ///
+    /// 未確認的計劃供應由 MRP 自行調整，不列入建議。未被需求消耗的庫存先用於維持安全庫存，
+    /// 不足的部分按可用日期由早到晚保留在未追溯的供應上。
+    pub(crate) fn suggest(
+        supplies: &[Supply],
+        coverages: &[DemandCoverage],
+        initial_inventory: Decimal,
+        safety_stock: Decimal,
+    ) -> Vec<SupplyCleanup> {
+        let mut pegged: HashMap<Uuid, Decimal> = HashMap::new();
+        let mut on_hand_used = Decimal::ZERO;
+        for allocation in coverages.iter().flat_map(|c| &c.allocations) {
+            match (allocation.source_type, allocation.source_id) {
+                (CoverageSourceType::OnHand, _) => on_hand_used += allocation.quantity,
+                (source_type, Some(id)) if source_type.is_supply() => {
+                    *pegged.entry(id).or_default() += allocation.quantity
+                }
+                _ => {}
+            }
+        }
+        let mut reserve = (safety_stock - (initial_inventory - on_hand_used).max(Decimal::ZERO))
+            .max(Decimal::ZERO);
+
+        let mut candidates: Vec<&Supply> = supplies
+            .iter()
+            .filter(|s| !s.is_adjustable() && s.supply_type != SupplyType::OnHand)
+            .collect();
+        candidates.sort_by_key(|s| s.available_date);
+        candidates
+            .into_iter()
+            .filter_map(|supply| {
+                let pegged = pegged.get(&supply.id).copied().unwrap_or_default();
+                let kept = (supply.quantity - pegged).min(reserve).max(Decimal::ZERO);
+                reserve -= kept;
+                let needed_quantity = pegged + kept;
+                if needed_quantity >= supply.quantity {
+                    return None;
+                }
+                Some(SupplyCleanup {
+                    supply_id: supply.id,
+                    component_id: supply.component_id.clone(),
+                    supply_type: supply.supply_type,
+                    available_date: supply.available_date,
+                    quantity: supply.quantity,
+                    needed_quantity,
+                    action: if needed_quantity > Decimal::ZERO {
+                        CleanupAction::Reduce
+                    } else {
+                        CleanupAction::Cancel
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::CoverageCalculator;
+    use mrp_core::{Demand, DemandType};
+
+    #[test]
+    fn test_cancel_and_reduce_unpegged_supplies() {
+        let date = |day: u32| NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        let supply = |quantity: i64, day: u32, supply_type: SupplyType| {
+            Supply::new(
+                "BOLT".to_string(),
+                Decimal::from(quantity),
+                date(day),
+                supply_type,
+            )
+        };
+        let supplies = vec![
+            supply(100, 5, SupplyType::PurchaseOrder),
+            supply(80, 12, SupplyType::PurchaseOrder),
+            supply(50, 15, SupplyType::PlannedOrder),
+        ];
+        // 需求從 200 降到 60：第一張採購單只需 60 + 安全庫存 10，第二張整筆取消
+        let demands = vec![Demand::new(
+            "BOLT".to_string(),
+            Decimal::from(60),
+            date(10),
+            DemandType::SalesOrder,
+        )];
+        let coverages = CoverageCalculator::calculate(&demands, Decimal::ZERO, &supplies, &[]);
+
+        let cleanups =
+            SupplyCleanupAdvisor::suggest(&supplies, &coverages, Decimal::ZERO, Decimal::TEN);
+
+        assert_eq!(cleanups.len(), 2);
+        assert_eq!(cleanups[0].action, CleanupAction::Reduce);
+        assert_eq!(cleanups[0].needed_quantity, Decimal::from(70));
+        assert_eq!(cleanups[0].unneeded_quantity(), Decimal::from(30));
+        assert_eq!(cleanups[1].action, CleanupAction::Cancel);
+        assert_eq!(cleanups[1].supply_id, supplies[1].id);
+    }
+}
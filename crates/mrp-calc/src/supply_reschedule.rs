@@ -0,0 +1,184 @@
+//! 現有供應重排建議：在途的採購單／工單／調撥單日期與需求不符時發提前或延後建議
+//!
+//! 庫存與供應按可用日期先到先用（先保留安全庫存），每筆供應的需要日期為它開始被消耗的需求日期。
+//! 確認供應不由 MRP 移動；未確認供應的建議只供採購參考，不影響淨需求。
+
+use chrono::NaiveDate;
+use mrp_core::{Demand, Supply, SupplyType};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// 重排方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RescheduleDirection {
+    /// 提前（需要日期早於可用日期）
+    In,
+
+    /// 延後（需要日期晚於可用日期）
+    Out,
+}
+
+/// 現有供應的重排建議
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RescheduleMessage {
+    /// 供應ID
+    pub supply_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 供應類型
+    pub supply_type: SupplyType,
+
+    /// 來源單號
+    pub source_ref: Option<String>,
+
+    /// 是否為確認供應
+    pub is_firm: bool,
+
+    /// 供應數量
+    pub quantity: Decimal,
+
+    /// 目前可用日期
+    pub current_date: NaiveDate,
+
+    /// 建議可用日期
+    pub suggested_date: NaiveDate,
+
+    /// 重排方向
+    pub direction: RescheduleDirection,
+}
+
+impl RescheduleMessage {
+    /// 需移動的天數
+    pub fn days(&self) -> i64 {
+        (self.suggested_date - self.current_date).num_days().abs()
+    }
+}
+
+/// 現有供應重排計算器
+pub(crate) struct SupplyRescheduler;
+
+impl SupplyRescheduler {
+    /// 計算單一物料現有供應的重排建議（在庫供應與 MRP 可調整的計劃供應不發建議）
+    ///
+    /// 建議提前的確認供應在 `supplies` 中改以需要日期計算淨需求，不在其上另產生計劃訂單；
+    /// 建議延後的供應不影響淨需求。日期差不超過 `tolerance_days` 天時不發建議（確認供應仍照常提前）。
+    /// 完全不被需要的供應由清理建議處理，不發重排建議。
+    pub(crate) fn reschedule(
+        demands: &[Demand],
+        supplies: &mut [Supply],
+        initial_inventory: Decimal,
+        reserve: Decimal,
+        tolerance_days: u32,
+    ) -> Vec<RescheduleMessage> {
+        let mut requirements: Vec<(NaiveDate, Decimal)> = demands
+            .iter()
+            .map(|d| (d.required_date, d.quantity))
+            .collect();
+        requirements.sort_by_key(|(date, _)| *date);
+        let mut cumulative = Decimal::ZERO;
+        for (_, quantity) in requirements.iter_mut() {
+            cumulative += *quantity;
+            *quantity = cumulative;
+        }
+
+        let mut order: Vec<usize> = (0..supplies.len()).collect();
+        order.sort_by_key(|&index| supplies[index].available_date);
+
+        let mut available = initial_inventory - reserve;
+        let mut messages = Vec::new();
+        for index in order {
+            let supply = &mut supplies[index];
+            let need_date = requirements
+                .iter()
+                .find(|(_, required)| *required > available)
+                .map(|(date, _)| *date);
+            available += supply.quantity;
+
+            let Some(need_date) = need_date
+                .filter(|_| !supply.is_adjustable() && supply.supply_type != SupplyType::OnHand)
+            else {
+                continue;
+            };
+            let direction = match need_date.cmp(&supply.available_date) {
+                std::cmp::Ordering::Less => RescheduleDirection::In,
+                std::cmp::Ordering::Greater => RescheduleDirection::Out,
+                std::cmp::Ordering::Equal => continue,
+            };
+            let message = RescheduleMessage {
+                supply_id: supply.id,
+                component_id: supply.component_id.clone(),
+                supply_type: supply.supply_type,
+                source_ref: supply.source_ref.clone(),
+                is_firm: supply.is_firm,
+                quantity: supply.quantity,
+                current_date: supply.available_date,
+                suggested_date: need_date,
+                direction,
+            };
+            if supply.is_firm && direction == RescheduleDirection::In {
+                supply.available_date = need_date;
+            }
+            if message.days() > i64::from(tolerance_days) {
+                messages.push(message);
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::DemandType;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_reschedule_open_supplies_in_and_out() {
+        let demand = |quantity: i64, day: u32| {
+            Demand::new(
+                "GEAR".to_string(),
+                Decimal::from(quantity),
+                date(day),
+                DemandType::SalesOrder,
+            )
+        };
+        let supply = |quantity: i64, day: u32| {
+            Supply::new(
+                "GEAR".to_string(),
+                Decimal::from(quantity),
+                date(day),
+                SupplyType::PurchaseOrder,
+            )
+        };
+        let demands = vec![demand(30, 5), demand(40, 20), demand(10, 24)];
+        // 庫存 10 件：11/10 的確認採購單 11/5 就需要，11/12 的採購單要到 11/20 才需要，
+        // 11/22 的採購單 11/24 需要（差 2 天，在容差內）
+        let mut supplies = vec![
+            supply(20, 10).as_firm(),
+            supply(40, 12).with_source_ref("PO-7".to_string()),
+            supply(10, 22),
+        ];
+
+        let messages =
+            SupplyRescheduler::reschedule(&demands, &mut supplies, Decimal::TEN, Decimal::ZERO, 2);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].direction, RescheduleDirection::In);
+        assert_eq!(messages[0].suggested_date, date(5));
+        assert_eq!(messages[0].days(), 5);
+        assert!(messages[0].is_firm);
+        assert_eq!(messages[1].direction, RescheduleDirection::Out);
+        assert_eq!(messages[1].suggested_date, date(20));
+        assert_eq!(messages[1].source_ref.as_deref(), Some("PO-7"));
+        assert!(!messages[1].is_firm);
+        assert_eq!(supplies[0].available_date, date(5));
+        assert_eq!(supplies[1].available_date, date(12));
+    }
+}
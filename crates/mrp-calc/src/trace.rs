@@ -0,0 +1,132 @@
+//! 逐層計算追蹤（除錯用）：每算完一個 BOM 低階碼，將累計的相依需求與計劃訂單寫入追蹤檔
+//!
+//! 追蹤檔為 JSON Lines，每行一個 [`LevelTrace`]；計算中途失敗時已完成的層級仍保留在檔案中。
+//! 深層 BOM 數量錯誤時，逐層比對某物料的相依需求量即可找出錯誤數量從哪一層進入。
+
+use mrp_core::{Demand, MrpError, PlannedOrder};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// 單一低階碼計算完成時的累計狀態
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct LevelTrace {
+    /// BOM 低階碼
+    pub low_level_code: u32,
+
+    /// 本層計算的物料
+    pub components: Vec<String>,
+
+    /// 截至本層累計的相依需求（物料ID → 需求）
+    pub dependent_demands: BTreeMap<String, Vec<Demand>>,
+
+    /// 截至本層累計的計劃訂單
+    pub planned_orders: Vec<PlannedOrder>,
+}
+
+impl LevelTrace {
+    /// 物料累計的相依需求量
+    pub fn dependent_quantity(&self, component_id: &str) -> Decimal {
+        self.dependent_demands
+            .get(component_id)
+            .map_or(Decimal::ZERO, |list| list.iter().map(|d| d.quantity).sum())
+    }
+
+    /// 物料累計的計劃訂購量
+    pub fn planned_quantity(&self, component_id: &str) -> Decimal {
+        self.planned_orders
+            .iter()
+            .filter(|o| o.component_id == component_id)
+            .map(|o| o.quantity)
+            .sum()
+    }
+
+    /// 讀取追蹤檔
+    pub fn read(path: &Path) -> mrp_core::Result<Vec<LevelTrace>> {
+        let file = File::open(path).map_err(|e| {
+            MrpError::ImportError(format!("無法開啟追蹤檔 {}: {}", path.display(), e))
+        })?;
+        BufReader::new(file)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+            .map(|(index, line)| {
+                let line = line.map_err(|e| MrpError::ImportError(e.to_string()))?;
+                serde_json::from_str(&line).map_err(|e| {
+                    MrpError::ImportError(format!("追蹤檔第 {} 行格式錯誤: {}", index + 1, e))
+                })
+            })
+            .collect()
+    }
+}
+
+/// 追蹤檔寫入器（計算主流程內部使用）
+pub(crate) struct LevelTracer {
+    writer: BufWriter<File>,
+    /// 目前層級與本層已計算的物料
+    current: Option<(u32, Vec<String>)>,
+}
+
+impl LevelTracer {
+    /// 建立（覆寫）追蹤檔
+    pub(crate) fn create(path: &Path) -> mrp_core::Result<Self> {
+        let file = File::create(path).map_err(|e| {
+            MrpError::ExportError(format!("無法建立追蹤檔 {}: {}", path.display(), e))
+        })?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            current: None,
+        })
+    }
+
+    /// 開始計算物料前呼叫：進入新的低階碼時先寫出上一層
+    pub(crate) fn enter(
+        &mut self,
+        low_level_code: u32,
+        component_id: &str,
+        dependent_demands: &HashMap<String, Vec<Demand>>,
+        planned_orders: &[PlannedOrder],
+    ) -> mrp_core::Result<()> {
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|(code, _)| *code != low_level_code)
+        {
+            self.finish(dependent_demands, planned_orders)?;
+        }
+        self.current
+            .get_or_insert_with(|| (low_level_code, Vec::new()))
+            .1
+            .push(component_id.to_string());
+        Ok(())
+    }
+
+    /// 寫出目前層級（計算結束時也須呼叫一次）
+    pub(crate) fn finish(
+        &mut self,
+        dependent_demands: &HashMap<String, Vec<Demand>>,
+        planned_orders: &[PlannedOrder],
+    ) -> mrp_core::Result<()> {
+        let Some((low_level_code, components)) = self.current.take() else {
+            return Ok(());
+        };
+        let trace = LevelTrace {
+            low_level_code,
+            components,
+            dependent_demands: dependent_demands
+                .iter()
+                .map(|(id, list)| (id.clone(), list.clone()))
+                .collect(),
+            planned_orders: planned_orders.to_vec(),
+        };
+        serde_json::to_writer(&mut self.writer, &trace)
+            .map_err(|e| MrpError::ExportError(format!("追蹤記錄序列化失敗: {}", e)))?;
+        writeln!(self.writer)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| MrpError::ExportError(format!("追蹤檔寫入失敗: {}", e)))
+    }
+}
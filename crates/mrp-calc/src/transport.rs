@@ -0,0 +1,90 @@
+//! 運輸方式選擇：選擇能趕上需要日期的最便宜運輸方式
+
+use chrono::NaiveDate;
+use mrp_core::{MrpConfig, PlannedOrder, TransportMode, WorkCalendar};
+
+/// 運輸方式選擇器
+pub struct TransportModeSelector;
+
+impl TransportModeSelector {
+    /// 為採購計劃訂單選擇運輸方式並重算下單日期
+    ///
+    /// 按運費由低到高嘗試，取下單日期不早於 `plan_start` 的第一個運輸方式；
+    /// 都趕不上時改用運輸天數最短者（交由排程判斷是否延遲）。
+    pub fn assign(
+        orders: &mut [PlannedOrder],
+        config: &MrpConfig,
+        plan_start: NaiveDate,
+        calendar: &WorkCalendar,
+    ) {
+        if config.transport_modes.is_empty() {
+            return;
+        }
+
+        for order in orders.iter_mut().filter(|o| o.is_purchase()) {
+            let lead_time = config.lead_time_for(order.quantity);
+            let order_date_for = |mode: &TransportMode| {
+                calendar.subtract_working_days(order.required_date, lead_time + mode.transit_days)
+            };
+
+            let mut by_cost: Vec<&TransportMode> = config.transport_modes.iter().collect();
+            by_cost.sort_by_key(|m| (m.cost_for(order.quantity), m.transit_days));
+
+            let chosen = by_cost
+                .iter()
+                .find(|m| order_date_for(m) >= plan_start)
+                .or_else(|| by_cost.iter().min_by_key(|m| m.transit_days))
+                .copied();
+
+            if let Some(mode) = chosen {
+                order.order_date = order_date_for(mode);
+                order.transport_mode = Some(mode.mode_id.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{PlannedOrderType, ProcurementType};
+    use rust_decimal::Decimal;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_escalate_to_faster_mode() {
+        let calendar = WorkCalendar::new_24_7("24/7".to_string());
+        let config = MrpConfig::new("CHIP-001".to_string(), 2, ProcurementType::Buy)
+            .with_transport_mode(TransportMode::new("SEA".to_string(), 20, Decimal::ONE))
+            .with_transport_mode(TransportMode::new("AIR".to_string(), 3, Decimal::from(5)));
+
+        let mut orders = vec![
+            PlannedOrder::new(
+                "CHIP-001".to_string(),
+                Decimal::from(100),
+                date(30),
+                date(28),
+                PlannedOrderType::Purchase,
+            ),
+            PlannedOrder::new(
+                "CHIP-001".to_string(),
+                Decimal::from(100),
+                date(10),
+                date(8),
+                PlannedOrderType::Purchase,
+            ),
+        ];
+
+        TransportModeSelector::assign(&mut orders, &config, date(1), &calendar);
+
+        // 11/30 需要：海運 22 天 → 11/8 下單可行
+        assert_eq!(orders[0].transport_mode.as_deref(), Some("SEA"));
+        assert_eq!(orders[0].order_date, date(8));
+        // 11/10 需要：海運來不及，升級空運（5 天 → 11/5 下單）
+        assert_eq!(orders[1].transport_mode.as_deref(), Some("AIR"));
+        assert_eq!(orders[1].order_date, date(5));
+    }
+}
@@ -0,0 +1,458 @@
+//! 參數調校建議：對選定物料試算候選的批量、POQ 週期與安全庫存，比較成本與服務水準
+//!
+//! 每個候選值以單一物料的隔離計劃（空 BOM，只含該物料的需求、供應與庫存）重新計算，
+//! 不影響正式計劃。相依需求須由呼叫端先從基準計劃取得，作為需求傳入。
+//!
+//! 計劃按名目需求與提前期產生，再以蒙地卡羅模擬執行：每次試驗依物料的變異抽樣實際需求量
+//! 與收貨日偏差，服務水準與持有成本取所有試驗的平均，安全庫存等緩衝參數因此能顯出效益。
+
+use bom_graph::BomGraph;
+use chrono::{Duration, NaiveDate};
+use mrp_core::{Demand, Inventory, LotSizingRule, MrpConfig, Supply, WorkCalendar};
+use rand::rngs::StdRng;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::monte_carlo::MonteCarloSimulator;
+use crate::MrpCalculator;
+
+/// 候選參數
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ParameterCandidate {
+    /// 固定訂購量（批量規則改為 FOQ）
+    FixedLotSize(Decimal),
+
+    /// POQ 週期天數（批量規則改為 POQ，沿用原週期對齊方式）
+    PoqPeriod(u32),
+
+    /// 安全庫存
+    SafetyStock(Decimal),
+}
+
+impl ParameterCandidate {
+    /// 套用到物料配置
+    pub fn apply(&self, config: &MrpConfig) -> MrpConfig {
+        let config = config.clone();
+        match self {
+            Self::FixedLotSize(size) => config
+                .with_lot_sizing_rule(LotSizingRule::FixedOrderQuantity)
+                .with_fixed_lot_size(*size),
+            Self::PoqPeriod(days) => {
+                let alignment = config.poq_alignment;
+                config
+                    .with_lot_sizing_rule(LotSizingRule::PeriodOrderQuantity)
+                    .with_poq_period(*days, alignment)
+            }
+            Self::SafetyStock(stock) => config.with_safety_stock(*stock),
+        }
+    }
+}
+
+/// 單一參數值的試算結果
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TuningOutcome {
+    /// 候選參數（None 為目前配置）
+    pub candidate: Option<ParameterCandidate>,
+
+    /// 計劃訂單數
+    pub order_count: usize,
+
+    /// 計劃訂購總量
+    pub ordered_quantity: Decimal,
+
+    /// 訂購成本（訂單數 × 每次訂購成本）
+    pub ordering_cost: Decimal,
+
+    /// 平均預計庫存（計劃期間逐日平均，各試驗平均）
+    pub average_inventory: Decimal,
+
+    /// 持有成本（逐日庫存 × 單位成本 × 年持有成本率 / 365，各試驗平均）
+    pub holding_cost: Decimal,
+
+    /// 準時滿足率（0–1；無需求時為 1；各試驗合計）
+    pub fill_rate: Decimal,
+}
+
+impl TuningOutcome {
+    /// 總成本（訂購 + 持有）
+    pub fn total_cost(&self) -> Decimal {
+        self.ordering_cost + self.holding_cost
+    }
+}
+
+/// 待調校物料：目前配置、隔離計劃的輸入與候選參數
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TuningItem {
+    /// 目前配置
+    pub config: MrpConfig,
+
+    /// 需求（含從基準計劃取得的相依需求）
+    pub demands: Vec<Demand>,
+
+    /// 現有供應
+    pub supplies: Vec<Supply>,
+
+    /// 現有庫存
+    pub on_hand: Decimal,
+
+    /// 需求量的變異係數（標準差 / 需求量）
+    pub demand_variability: Decimal,
+
+    /// 收貨日偏差的標準差（天）
+    pub lead_time_std_dev: Decimal,
+
+    /// 候選參數
+    pub candidates: Vec<ParameterCandidate>,
+}
+
+impl TuningItem {
+    /// 創建待調校物料（只計入該物料的需求與供應）
+    pub fn new(
+        config: MrpConfig,
+        demands: &[Demand],
+        supplies: &[Supply],
+        on_hand: Decimal,
+    ) -> Self {
+        let id = config.component_id.clone();
+        Self {
+            demands: demands
+                .iter()
+                .filter(|d| d.component_id == id)
+                .cloned()
+                .collect(),
+            supplies: supplies
+                .iter()
+                .filter(|s| s.component_id == id)
+                .cloned()
+                .collect(),
+            config,
+            on_hand,
+            demand_variability: Decimal::ZERO,
+            lead_time_std_dev: Decimal::ZERO,
+            candidates: Vec::new(),
+        }
+    }
+
+    /// 建構器模式：設定需求量變異係數與收貨日偏差標準差
+    pub fn with_variability(
+        mut self,
+        demand_variability: Decimal,
+        lead_time_std_dev: Decimal,
+    ) -> Self {
+        self.demand_variability = demand_variability;
+        self.lead_time_std_dev = lead_time_std_dev;
+        self
+    }
+
+    /// 建構器模式：添加候選參數
+    pub fn with_candidates(
+        mut self,
+        candidates: impl IntoIterator<Item = ParameterCandidate>,
+    ) -> Self {
+        self.candidates.extend(candidates);
+        self
+    }
+}
+
+/// 單一物料的調校報告
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TuningReport {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 目前配置的試算結果
+    pub baseline: TuningOutcome,
+
+    /// 候選參數的試算結果（按總成本由低到高）
+    pub outcomes: Vec<TuningOutcome>,
+}
+
+impl TuningReport {
+    /// 建議的參數：滿足率不低於目前配置、且總成本最低並低於目前配置的候選
+    pub fn recommendation(&self) -> Option<&TuningOutcome> {
+        self.outcomes.iter().find(|outcome| {
+            outcome.fill_rate >= self.baseline.fill_rate
+                && outcome.total_cost() < self.baseline.total_cost()
+        })
+    }
+
+    /// 採用建議可節省的成本（無建議時為零）
+    pub fn savings(&self) -> Decimal {
+        self.recommendation().map_or(Decimal::ZERO, |outcome| {
+            self.baseline.total_cost() - outcome.total_cost()
+        })
+    }
+}
+
+/// 參數調校顧問
+pub struct TuningAdvisor;
+
+impl TuningAdvisor {
+    /// 對多個物料試算，報告按可節省成本由高到低排序
+    pub fn advise(
+        items: &[TuningItem],
+        calendar: &WorkCalendar,
+        simulator: &MonteCarloSimulator,
+    ) -> mrp_core::Result<Vec<TuningReport>> {
+        let mut reports = items
+            .iter()
+            .map(|item| Self::sweep(item, calendar, simulator))
+            .collect::<mrp_core::Result<Vec<_>>>()?;
+        reports.sort_by(|a, b| {
+            b.savings()
+                .cmp(&a.savings())
+                .then_with(|| a.component_id.cmp(&b.component_id))
+        });
+        Ok(reports)
+    }
+
+    /// 對單一物料試算目前配置與每個候選參數
+    pub fn sweep(
+        item: &TuningItem,
+        calendar: &WorkCalendar,
+        simulator: &MonteCarloSimulator,
+    ) -> mrp_core::Result<TuningReport> {
+        let baseline = Self::evaluate(item, &item.config, None, calendar, simulator)?;
+        let mut outcomes = item
+            .candidates
+            .iter()
+            .map(|candidate| {
+                Self::evaluate(
+                    item,
+                    &candidate.apply(&item.config),
+                    Some(candidate.clone()),
+                    calendar,
+                    simulator,
+                )
+            })
+            .collect::<mrp_core::Result<Vec<_>>>()?;
+        // 穩定排序：同成本時保留候選的輸入順序
+        outcomes.sort_by_key(TuningOutcome::total_cost);
+
+        Ok(TuningReport {
+            component_id: item.config.component_id.clone(),
+            baseline,
+            outcomes,
+        })
+    }
+
+    /// 以指定配置執行隔離計劃，再模擬執行計算成本與服務指標
+    fn evaluate(
+        item: &TuningItem,
+        config: &MrpConfig,
+        candidate: Option<ParameterCandidate>,
+        calendar: &WorkCalendar,
+        simulator: &MonteCarloSimulator,
+    ) -> mrp_core::Result<TuningOutcome> {
+        let id = config.component_id.clone();
+        let configs = HashMap::from([(id.clone(), config.clone())]);
+        let result = MrpCalculator::new(BomGraph::new(), configs, calendar.clone()).calculate(
+            item.demands.clone(),
+            item.supplies.clone(),
+            vec![Inventory::new(id, item.on_hand, Decimal::ZERO)],
+        )?;
+
+        let order_count = result.planned_orders.len();
+        let ordered_quantity = result.planned_orders.iter().map(|o| o.quantity).sum();
+        let ordering_cost =
+            config.ordering_cost.unwrap_or(Decimal::ZERO) * Decimal::from(order_count);
+
+        let receipts: Vec<(NaiveDate, Decimal)> = item
+            .supplies
+            .iter()
+            .map(|s| (s.available_date, s.quantity))
+            .chain(
+                result
+                    .planned_orders
+                    .iter()
+                    .map(|o| (o.required_date, o.quantity)),
+            )
+            .collect();
+        let trials = simulator.run(|rng| Self::simulate(rng, item, &receipts));
+        let (mut demand, mut filled, mut inventory_days, mut days) =
+            (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, 0);
+        for trial in &trials {
+            demand += trial.0;
+            filled += trial.1;
+            inventory_days += trial.2;
+            days += trial.3;
+        }
+        let trial_count = Decimal::from(trials.len().max(1));
+        let holding_cost = match (config.unit_cost, config.annual_holding_cost_rate) {
+            (Some(unit_cost), Some(rate)) => {
+                (inventory_days / trial_count * unit_cost * rate / Decimal::from(365)).round_dp(4)
+            }
+            _ => Decimal::ZERO,
+        };
+
+        Ok(TuningOutcome {
+            candidate,
+            order_count,
+            ordered_quantity,
+            ordering_cost,
+            average_inventory: (inventory_days / Decimal::from(days.max(1))).round_dp(4),
+            holding_cost,
+            fill_rate: if demand > Decimal::ZERO {
+                (filled / demand).round_dp(4)
+            } else {
+                Decimal::ONE
+            },
+        })
+    }
+
+    /// 單次試驗：抽樣實際需求量與收貨日，逐日扣帳（缺貨延後回補）
+    ///
+    /// 回傳（總需求, 當日滿足量, 庫存日數合計, 天數），期間為第一筆到最後一筆異動（負庫存視為零）
+    fn simulate(
+        rng: &mut StdRng,
+        item: &TuningItem,
+        receipts: &[(NaiveDate, Decimal)],
+    ) -> (Decimal, Decimal, Decimal, i64) {
+        // 日期 → （入庫, 需求）
+        let mut movements: BTreeMap<NaiveDate, (Decimal, Decimal)> = BTreeMap::new();
+        for &(date, quantity) in receipts {
+            let delay = Self::sample(rng, Decimal::ZERO, item.lead_time_std_dev)
+                .round()
+                .to_i64()
+                .unwrap_or(0);
+            movements.entry(date + Duration::days(delay)).or_default().0 += quantity;
+        }
+        for demand in &item.demands {
+            let quantity = Self::sample(
+                rng,
+                demand.quantity,
+                demand.quantity * item.demand_variability,
+            )
+            .max(Decimal::ZERO);
+            movements.entry(demand.required_date).or_default().1 += quantity;
+        }
+
+        let (Some(&first), Some(&last)) = (movements.keys().next(), movements.keys().next_back())
+        else {
+            return (
+                Decimal::ZERO,
+                Decimal::ZERO,
+                item.on_hand.max(Decimal::ZERO),
+                1,
+            );
+        };
+        let mut balance = item.on_hand;
+        let (mut total, mut filled, mut inventory_days) =
+            (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+        for date in first.iter_days().take_while(|date| *date <= last) {
+            let (received, demand) = movements.get(&date).copied().unwrap_or_default();
+            balance += received;
+            filled += demand.min(balance.max(Decimal::ZERO));
+            total += demand;
+            balance -= demand;
+            inventory_days += balance.max(Decimal::ZERO);
+        }
+        (total, filled, inventory_days, (last - first).num_days() + 1)
+    }
+
+    /// 常態抽樣（標準差為零時直接回傳平均值，保留十進位精度）
+    fn sample(rng: &mut StdRng, mean: Decimal, std_dev: Decimal) -> Decimal {
+        if std_dev <= Decimal::ZERO {
+            return mean;
+        }
+        let value = MonteCarloSimulator::sample_normal(
+            rng,
+            mean.to_f64().unwrap_or(0.0),
+            std_dev.to_f64().unwrap_or(0.0),
+        );
+        Decimal::from_f64(value).unwrap_or(mean)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{DemandType, ProcurementType};
+
+    #[test]
+    fn test_sweep_recommends_cheaper_lot_size() {
+        let config = MrpConfig::new("BOLT".to_string(), 2, ProcurementType::Buy)
+            .with_unit_cost(Decimal::from(2))
+            .with_eoq_costs(Decimal::from(50), Decimal::new(25, 2));
+        let demands: Vec<Demand> = (0..4)
+            .map(|week| {
+                Demand::new(
+                    "BOLT".to_string(),
+                    Decimal::from(100),
+                    NaiveDate::from_ymd_opt(2025, 11, 3).unwrap() + chrono::Duration::weeks(week),
+                    DemandType::SalesOrder,
+                )
+            })
+            .collect();
+
+        let item = TuningItem::new(config, &demands, &[], Decimal::ZERO).with_candidates([
+            ParameterCandidate::FixedLotSize(Decimal::from(200)),
+            ParameterCandidate::SafetyStock(Decimal::from(50)),
+        ]);
+        let reports = TuningAdvisor::advise(
+            &[item],
+            &WorkCalendar::default(),
+            &MonteCarloSimulator::new(42, 1),
+        )
+        .unwrap();
+        let report = &reports[0];
+
+        // 批對批：4 張訂單、訂購成本 200；FOQ 200：2 張訂單、訂購成本 100
+        assert_eq!(report.baseline.order_count, 4);
+        assert_eq!(report.baseline.ordering_cost, Decimal::from(200));
+        let best = report.recommendation().unwrap();
+        assert_eq!(
+            best.candidate,
+            Some(ParameterCandidate::FixedLotSize(Decimal::from(200)))
+        );
+        assert_eq!(best.order_count, 2);
+        assert_eq!(best.fill_rate, Decimal::ONE);
+        assert!(report.savings() > Decimal::ZERO);
+
+        // 安全庫存只增加持有成本，不會被建議
+        let safety = report
+            .outcomes
+            .iter()
+            .find(|o| matches!(o.candidate, Some(ParameterCandidate::SafetyStock(_))))
+            .unwrap();
+        assert!(safety.holding_cost > report.baseline.holding_cost);
+    }
+
+    #[test]
+    fn test_variability_shows_safety_stock_benefit() {
+        let config = MrpConfig::new("SEAL".to_string(), 2, ProcurementType::Buy)
+            .with_unit_cost(Decimal::from(2))
+            .with_eoq_costs(Decimal::from(50), Decimal::new(25, 2));
+        let demands: Vec<Demand> = (0..4)
+            .map(|week| {
+                Demand::new(
+                    "SEAL".to_string(),
+                    Decimal::from(100),
+                    NaiveDate::from_ymd_opt(2025, 11, 3).unwrap() + chrono::Duration::weeks(week),
+                    DemandType::SalesOrder,
+                )
+            })
+            .collect();
+        let item = TuningItem::new(config, &demands, &[], Decimal::ZERO)
+            .with_variability(Decimal::new(2, 1), Decimal::from(2))
+            .with_candidates([ParameterCandidate::SafetyStock(Decimal::from(100))]);
+        let simulator = MonteCarloSimulator::new(42, 200);
+
+        let report = TuningAdvisor::sweep(&item, &WorkCalendar::default(), &simulator).unwrap();
+        let safety = &report.outcomes[0];
+
+        // 需求與收貨日波動下，批對批無法全數準時滿足，安全庫存提高滿足率
+        assert!(report.baseline.fill_rate < Decimal::ONE);
+        assert!(safety.fill_rate > report.baseline.fill_rate);
+        assert!(safety.holding_cost > report.baseline.holding_cost);
+
+        // 同一種子可重現
+        let again = TuningAdvisor::sweep(&item, &WorkCalendar::default(), &simulator).unwrap();
+        assert_eq!(again.outcomes[0], *safety);
+    }
+}
@@ -2,6 +2,7 @@
 
 use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 工作日曆
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,6 +129,67 @@ impl WorkCalendar {
     }
 }
 
+/// 需求合併週期的對齊方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum PeriodAlignment {
+    /// 從週期內第一筆需求日期起算（預設）
+    #[default]
+    FirstRequirement,
+
+    /// 對齊日曆週（週一起算）
+    Week,
+
+    /// 對齊日曆月（每月 1 日起算，週期長度為整月）
+    Month,
+
+    /// 從週期內第一筆需求日期起算，週期長度為工作日數（跨越假日與週末時自動延長）
+    WorkingDays,
+}
+
+impl WorkCalendar {
+    /// 計算包含指定日期的週期（起日含、訖日不含）
+    ///
+    /// `Week` 對齊時週期從該週週一起算 `length_days` 天；
+    /// `Month` 對齊時週期為整個日曆月，忽略 `length_days`。
+    /// `WorkingDays` 對齊時 `length_days` 為工作日數：週期含起日當天（或其後第一個工作日）
+    /// 起的 `length_days` 個工作日，訖日為其後的下一個工作日。
+    pub fn period_bounds(
+        &self,
+        date: NaiveDate,
+        length_days: u32,
+        alignment: PeriodAlignment,
+    ) -> (NaiveDate, NaiveDate) {
+        let length = chrono::Duration::days(length_days.max(1) as i64);
+        match alignment {
+            PeriodAlignment::Week => {
+                let start =
+                    date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+                (start, start + length)
+            }
+            PeriodAlignment::Month => {
+                let start = date.with_day(1).expect("日期溢出");
+                let end = if start.month() == 12 {
+                    NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+                }
+                .expect("日期溢出");
+                (start, end)
+            }
+            PeriodAlignment::WorkingDays => {
+                let first = if self.is_working_day(date) {
+                    date
+                } else {
+                    self.next_working_day(date)
+                };
+                (date, self.add_working_days(first, length_days.max(1)))
+            }
+            _ => (date, date + length),
+        }
+    }
+}
+
 impl Default for WorkCalendar {
     fn default() -> Self {
         Self::new("DEFAULT".to_string())
@@ -196,6 +258,122 @@ impl WorkCalendar {
     }
 }
 
+/// 日曆覆寫：繼承父日曆，只記錄差異（如工作中心日曆繼承工廠日曆）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CalendarOverride {
+    /// 日曆ID
+    pub calendar_id: String,
+
+    /// 父日曆ID
+    pub parent_id: String,
+
+    /// 覆寫的工作日模式（None 表示沿用父日曆）
+    pub working_days: Option<[bool; 7]>,
+
+    /// 額外停機日（加在父日曆節假日之上，如設備保養）
+    pub downtime: Vec<NaiveDate>,
+}
+
+impl CalendarOverride {
+    /// 創建繼承父日曆的覆寫
+    pub fn new(calendar_id: String, parent_id: String) -> Self {
+        Self {
+            calendar_id,
+            parent_id,
+            working_days: None,
+            downtime: Vec::new(),
+        }
+    }
+
+    /// 建構器模式：覆寫工作日模式
+    pub fn with_working_days(mut self, working_days: [bool; 7]) -> Self {
+        self.working_days = Some(working_days);
+        self
+    }
+
+    /// 建構器模式：添加停機日
+    pub fn with_downtime(mut self, downtime: Vec<NaiveDate>) -> Self {
+        self.downtime.extend(downtime);
+        self
+    }
+}
+
+/// 日曆註冊表：管理基礎日曆與階層式覆寫
+#[derive(Debug, Clone, Default)]
+pub struct CalendarRegistry {
+    calendars: HashMap<String, WorkCalendar>,
+    overrides: HashMap<String, CalendarOverride>,
+}
+
+impl CalendarRegistry {
+    /// 創建空的日曆註冊表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 註冊基礎日曆
+    pub fn register(&mut self, calendar: WorkCalendar) {
+        self.overrides.remove(&calendar.calendar_id);
+        self.calendars
+            .insert(calendar.calendar_id.clone(), calendar);
+    }
+
+    /// 註冊覆寫日曆
+    pub fn register_override(&mut self, calendar_override: CalendarOverride) {
+        self.calendars.remove(&calendar_override.calendar_id);
+        self.overrides
+            .insert(calendar_override.calendar_id.clone(), calendar_override);
+    }
+
+    /// 檢查日曆是否已註冊
+    pub fn contains(&self, calendar_id: &str) -> bool {
+        self.calendars.contains_key(calendar_id) || self.overrides.contains_key(calendar_id)
+    }
+
+    /// 解析日曆：沿繼承鏈合併工作日模式與節假日
+    ///
+    /// 子日曆的工作日模式覆寫父日曆，停機日與父日曆的節假日合併。
+    pub fn resolve(&self, calendar_id: &str) -> crate::Result<WorkCalendar> {
+        let mut chain: Vec<&CalendarOverride> = Vec::new();
+        let mut current = calendar_id;
+
+        let base = loop {
+            if let Some(calendar) = self.calendars.get(current) {
+                break calendar;
+            }
+            let calendar_override = self
+                .overrides
+                .get(current)
+                .ok_or_else(|| crate::MrpError::CalendarNotFound(current.to_string()))?;
+            if chain
+                .iter()
+                .any(|o| o.calendar_id == calendar_override.calendar_id)
+            {
+                return Err(crate::MrpError::CalculationError(format!(
+                    "日曆繼承出現循環: {}",
+                    calendar_id
+                )));
+            }
+            chain.push(calendar_override);
+            current = &calendar_override.parent_id;
+        };
+
+        let mut resolved = base.clone();
+        for calendar_override in chain.into_iter().rev() {
+            if let Some(working_days) = calendar_override.working_days {
+                resolved.working_days = working_days;
+            }
+            for &date in &calendar_override.downtime {
+                resolved.add_holiday(date);
+            }
+        }
+        resolved.calendar_id = calendar_id.to_string();
+
+        Ok(resolved)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,4 +444,76 @@ mod tests {
         let count = calendar.working_days_between(start, end);
         assert_eq!(count, 5);
     }
+
+    #[test]
+    fn test_period_bounds() {
+        let calendar = WorkCalendar::default();
+        // 2025-11-05 是週三
+        let wednesday = NaiveDate::from_ymd_opt(2025, 11, 5).unwrap();
+
+        assert_eq!(
+            calendar.period_bounds(wednesday, 7, PeriodAlignment::Week),
+            (
+                NaiveDate::from_ymd_opt(2025, 11, 3).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 11, 10).unwrap()
+            )
+        );
+        assert_eq!(
+            calendar.period_bounds(wednesday, 7, PeriodAlignment::Month),
+            (
+                NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 12, 1).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_working_day_period_bounds() {
+        // 2025-11-05（週三）放假：自 11/3（週一）起 5 個工作日延到 11/10（週一），訖日為 11/11
+        let holiday = NaiveDate::from_ymd_opt(2025, 11, 5).unwrap();
+        let calendar = WorkCalendar::new("PLANT".to_string()).with_holidays(vec![holiday]);
+        let monday = NaiveDate::from_ymd_opt(2025, 11, 3).unwrap();
+        assert_eq!(
+            calendar.period_bounds(monday, 5, PeriodAlignment::WorkingDays),
+            (monday, NaiveDate::from_ymd_opt(2025, 11, 11).unwrap())
+        );
+
+        // 從週六起算：由下週一開始數
+        let saturday = NaiveDate::from_ymd_opt(2025, 11, 15).unwrap();
+        assert_eq!(
+            calendar
+                .period_bounds(saturday, 5, PeriodAlignment::WorkingDays)
+                .1,
+            NaiveDate::from_ymd_opt(2025, 11, 24).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_calendar_registry_inheritance() {
+        let national_day = NaiveDate::from_ymd_opt(2025, 10, 10).unwrap();
+        let maintenance = NaiveDate::from_ymd_opt(2025, 10, 14).unwrap();
+        let saturday = NaiveDate::from_ymd_opt(2025, 10, 11).unwrap();
+
+        let mut registry = CalendarRegistry::new();
+        registry.register(WorkCalendar::new("PLANT".to_string()).with_holidays(vec![national_day]));
+        registry.register_override(
+            CalendarOverride::new("WC-PRESS".to_string(), "PLANT".to_string())
+                .with_working_days([true, true, true, true, true, true, false]),
+        );
+        registry.register_override(
+            CalendarOverride::new("WC-PRESS-2".to_string(), "WC-PRESS".to_string())
+                .with_downtime(vec![maintenance]),
+        );
+
+        let calendar = registry.resolve("WC-PRESS-2").unwrap();
+        assert_eq!(calendar.calendar_id, "WC-PRESS-2");
+        assert!(!calendar.is_working_day(national_day));
+        assert!(!calendar.is_working_day(maintenance));
+        assert!(calendar.is_working_day(saturday));
+
+        assert!(matches!(
+            registry.resolve("WC-UNKNOWN"),
+            Err(crate::MrpError::CalendarNotFound(_))
+        ));
+    }
 }
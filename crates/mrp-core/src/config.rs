@@ -1,10 +1,16 @@
 //! MRP 配置模型
 
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::PeriodAlignment;
 
 /// 物料MRP參數配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct MrpConfig {
     /// 物料ID
     pub component_id: String,
@@ -39,6 +45,26 @@ pub struct MrpConfig {
     /// 是否啟用 MRP（有些物料可能不需要 MRP）
     pub mrp_enabled: bool,
 
+    /// 計劃方式（非 MRP 計劃的物料只預測庫存，不產生計劃訂單）
+    #[serde(default)]
+    pub planning_method: PlanningMethod,
+
+    /// 再訂購點（計劃方式為 `ReorderPoint` 時使用）
+    #[serde(default)]
+    pub reorder_point: Option<Decimal>,
+
+    /// 新品爬坡曲線（預測需求按每週百分比調整）
+    #[serde(default)]
+    pub ramp_profile: Option<RampProfile>,
+
+    /// 最早下單日期（如模具驗證完成日；早於此日的訂單改由此日順排）
+    #[serde(default)]
+    pub earliest_order_date: Option<NaiveDate>,
+
+    /// 生命週期狀態（停產中的物料在停產日後不再產生新訂單）
+    #[serde(default)]
+    pub lifecycle: LifecycleStatus,
+
     /// 是否允許負庫存
     /// - true: 允許預計庫存為負值（適用於可超賣或 MTO 模式）
     /// - false: 不允許負庫存，當庫存不足時立即觸發計劃訂單（預設）
@@ -47,6 +73,117 @@ pub struct MrpConfig {
     /// - 允許：按單生產(MTO)、服務類物料、虛擬件
     /// - 不允許：實體庫存管理、批量生產(MTS)
     pub allow_negative_inventory: bool,
+
+    /// 每工作日產能（單位數量，用於產能約束 MRP；None 表示無限產能）
+    pub capacity_per_day: Option<Decimal>,
+
+    /// 預設供應商（採購件寫入計劃訂單的 source_id）
+    pub supplier_id: Option<String>,
+
+    /// 單位成本
+    pub unit_cost: Option<Decimal>,
+
+    /// 每次訂購成本（EOQ 使用）
+    #[serde(default)]
+    pub ordering_cost: Option<Decimal>,
+
+    /// 年持有成本率（佔單位成本的比例，如 0.25；EOQ 使用）
+    #[serde(default)]
+    pub annual_holding_cost_rate: Option<Decimal>,
+
+    /// 工序偏移：工序序號 → 自訂單開工起的工作日數
+    ///
+    /// BOM 行指定工序序號時，子件需求日期為父件訂單開工日加上該工序偏移
+    /// （不晚於父件完工日），未設置的工序在開工日即需要。
+    #[serde(default)]
+    pub operation_offsets: BTreeMap<u32, u32>,
+
+    /// BOM 行偏移：逐行（子件ID + BOM 行序號）相對父件訂單開工日的工作日數（可為負）
+    ///
+    /// 負值表示子件須在開工前到位（提前出貨件），正值表示延後投入（後段客製化）；
+    /// 同一子件在多個 BOM 行出現時可各自設置，設置後優先於工序偏移。
+    #[serde(default)]
+    pub bom_line_offsets: Vec<BomLineOffset>,
+
+    /// 散裝發料物料（現場庫存、以再訂購點補貨；BOM 展開時不產生相依需求）
+    #[serde(default)]
+    pub is_bulk: bool,
+
+    /// 散裝發料的 BOM 行：子件ID（僅在此父件下不產生相依需求）
+    #[serde(default)]
+    pub bulk_components: BTreeSet<String>,
+
+    /// 非線性用量的 BOM 行：子件ID → 用量方式（未設置的子件按父件數量 × BOM 用量計算）
+    #[serde(default)]
+    pub component_usages: BTreeMap<String, ComponentUsage>,
+
+    /// 計劃 BOM 百分比：變體ID → 計劃百分比（如 60 表示 60%）
+    ///
+    /// 設置後此物料為產品族（計劃物料）：不產生計劃訂單，需求（通常為預測）
+    /// 於需求日按百分比直接轉為各變體的相依需求（保留小數）。
+    #[serde(default)]
+    pub planning_percentages: BTreeMap<String, Decimal>,
+
+    /// 排程方式
+    #[serde(default)]
+    pub scheduling_mode: SchedulingMode,
+
+    /// 變動提前期（隨訂單數量增加的工作日數；None 表示只有固定提前期）
+    #[serde(default)]
+    pub variable_lead_time: Option<VariableLeadTime>,
+
+    /// 週期訂購量（POQ）的週期長度（天，預設 7；`WorkingDays` 對齊時為工作日數）
+    #[serde(default = "default_poq_period_days")]
+    pub poq_period_days: u32,
+
+    /// 週期訂購量（POQ）的週期對齊方式
+    #[serde(default)]
+    pub poq_alignment: PeriodAlignment,
+
+    /// 可選運輸方式（採購件；運輸天數加在提前期之後）
+    #[serde(default)]
+    pub transport_modes: Vec<TransportMode>,
+
+    /// 近期區段的批量規則（區段內使用此規則，區段外使用 `lot_sizing_rule`）
+    #[serde(default)]
+    pub zone_lot_sizing: Option<LotSizingZone>,
+
+    /// 計劃訂單最小間隔（天；間隔內的需求合併到前一張訂單）
+    #[serde(default)]
+    pub min_order_interval_days: Option<u32>,
+
+    /// 計劃訂單合併窗口（天；批量規則之後合併窗口內的訂單並重新套用訂購修正）
+    #[serde(default)]
+    pub consolidation_window_days: Option<u32>,
+
+    /// 最大涵蓋天數／保存期限（易腐物料；單張訂單涵蓋的需求不得超過此天數）
+    #[serde(default)]
+    pub max_coverage_days: Option<u32>,
+
+    /// 數量取整方式（離散物料避免出現 33.333 之類的小數計劃數量）
+    #[serde(default)]
+    pub rounding_policy: RoundingPolicy,
+
+    /// 收貨日曆ID（採購／調撥的計劃收貨日對齊到此日曆的收貨日；提前期仍用生產日曆；
+    /// None 時沿用供應商的收貨日曆）
+    #[serde(default)]
+    pub receipt_calendar_id: Option<String>,
+
+    /// 短缺容差（累計豁免的短缺在容差內時不產生計劃訂單，只發出提示；None 表示不容許短缺）
+    #[serde(default)]
+    pub shortage_tolerance: Option<ShortageTolerance>,
+
+    /// 需求時界（工作日；計劃起始日起此期間內只有銷售訂單計入淨需求，預測需求不計）
+    #[serde(default)]
+    pub demand_time_fence_days: Option<u32>,
+
+    /// 計劃時界（工作日；計劃起始日起此期間內不產生計劃訂單，時界內的需求改發催交警告）
+    #[serde(default)]
+    pub planning_time_fence_days: Option<u32>,
+}
+
+fn default_poq_period_days() -> u32 {
+    7
 }
 
 impl MrpConfig {
@@ -64,7 +201,37 @@ impl MrpConfig {
             planning_horizon_days: 90,
             procurement_type,
             mrp_enabled: true,
+            planning_method: PlanningMethod::Mrp,
+            reorder_point: None,
+            ramp_profile: None,
+            earliest_order_date: None,
+            lifecycle: LifecycleStatus::Active,
             allow_negative_inventory: false, // 預設不允許負庫存（保守策略）
+            capacity_per_day: None,
+            supplier_id: None,
+            unit_cost: None,
+            ordering_cost: None,
+            annual_holding_cost_rate: None,
+            operation_offsets: BTreeMap::new(),
+            bom_line_offsets: Vec::new(),
+            is_bulk: false,
+            bulk_components: BTreeSet::new(),
+            component_usages: BTreeMap::new(),
+            planning_percentages: BTreeMap::new(),
+            scheduling_mode: SchedulingMode::Backward,
+            variable_lead_time: None,
+            poq_period_days: default_poq_period_days(),
+            poq_alignment: PeriodAlignment::FirstRequirement,
+            transport_modes: Vec::new(),
+            zone_lot_sizing: None,
+            min_order_interval_days: None,
+            consolidation_window_days: None,
+            max_coverage_days: None,
+            rounding_policy: RoundingPolicy::AllowFractional,
+            receipt_calendar_id: None,
+            shortage_tolerance: None,
+            demand_time_fence_days: None,
+            planning_time_fence_days: None,
         }
     }
 
@@ -126,8 +293,275 @@ impl MrpConfig {
         self
     }
 
-    /// 調整訂購量以符合批量規則
-    pub fn adjust_order_quantity(&self, mut quantity: Decimal) -> Decimal {
+    /// 建構器模式：設置每工作日產能
+    pub fn with_capacity_per_day(mut self, capacity: Decimal) -> Self {
+        self.capacity_per_day = Some(capacity);
+        self
+    }
+
+    /// 建構器模式：設置預設供應商
+    pub fn with_supplier_id(mut self, supplier_id: String) -> Self {
+        self.supplier_id = Some(supplier_id);
+        self
+    }
+
+    /// 建構器模式：設置單位成本
+    pub fn with_unit_cost(mut self, unit_cost: Decimal) -> Self {
+        self.unit_cost = Some(unit_cost);
+        self
+    }
+
+    /// 建構器模式：設置 EOQ 成本參數（每次訂購成本、年持有成本率）
+    pub fn with_eoq_costs(
+        mut self,
+        ordering_cost: Decimal,
+        annual_holding_cost_rate: Decimal,
+    ) -> Self {
+        self.ordering_cost = Some(ordering_cost);
+        self.annual_holding_cost_rate = Some(annual_holding_cost_rate);
+        self
+    }
+
+    /// 計算經濟訂購量：EOQ = sqrt(2 × 年需求量 × 訂購成本 / (年持有成本率 × 單位成本))
+    ///
+    /// 結果無條件進位到整數單位；缺少訂購成本、年持有成本率或單位成本時回傳錯誤。
+    pub fn economic_order_quantity(&self, annual_demand: Decimal) -> crate::Result<Decimal> {
+        let missing = |name: &str| {
+            crate::MrpError::InvalidConfig(format!(
+                "物料 {} 使用 EOQ 但缺少 {}",
+                self.component_id, name
+            ))
+        };
+        let ordering_cost = self.ordering_cost.ok_or_else(|| missing("ordering_cost"))?;
+        let holding_rate = self
+            .annual_holding_cost_rate
+            .ok_or_else(|| missing("annual_holding_cost_rate"))?;
+        let unit_cost = self.unit_cost.ok_or_else(|| missing("unit_cost"))?;
+
+        let holding_cost = holding_rate * unit_cost;
+        if holding_cost <= Decimal::ZERO || ordering_cost < Decimal::ZERO {
+            return Err(crate::MrpError::InvalidConfig(format!(
+                "物料 {} 的 EOQ 持有成本必須大於 0、訂購成本不可為負",
+                self.component_id
+            )));
+        }
+
+        let ratio = (Decimal::TWO * annual_demand.max(Decimal::ZERO) * ordering_cost
+            / holding_cost)
+            .to_f64()
+            .unwrap_or(0.0);
+        Ok(Decimal::try_from(ratio.sqrt()).unwrap_or_default().ceil())
+    }
+
+    /// 建構器模式：設置工序偏移（工序序號 → 開工後工作日數）
+    pub fn with_operation_offset(mut self, operation_sequence: u32, days: u32) -> Self {
+        self.operation_offsets.insert(operation_sequence, days);
+        self
+    }
+
+    /// 查詢工序偏移（未設置時為 0）
+    pub fn operation_offset(&self, operation_sequence: Option<u32>) -> u32 {
+        operation_sequence
+            .and_then(|op| self.operation_offsets.get(&op).copied())
+            .unwrap_or(0)
+    }
+
+    /// 建構器模式：設置 BOM 行偏移（以子件ID與 BOM 行序號指定行，相對開工日的工作日數，可為負）
+    pub fn with_bom_line_offset(mut self, child_id: String, sequence: u32, days: i32) -> Self {
+        self.bom_line_offsets
+            .retain(|o| o.child_id != child_id || o.sequence != sequence);
+        self.bom_line_offsets.push(BomLineOffset {
+            child_id,
+            sequence,
+            days,
+        });
+        self
+    }
+
+    /// 建構器模式：設為散裝發料物料（所有 BOM 行都不產生相依需求）
+    pub fn with_bulk(mut self, is_bulk: bool) -> Self {
+        self.is_bulk = is_bulk;
+        self
+    }
+
+    /// 建構器模式：將此父件的某個子件 BOM 行設為散裝發料
+    pub fn with_bulk_component(mut self, child_id: String) -> Self {
+        self.bulk_components.insert(child_id);
+        self
+    }
+
+    /// 子件 BOM 行是否為散裝發料（僅檢查父件的 BOM 行設置）
+    pub fn is_bulk_component(&self, child_id: &str) -> bool {
+        self.bulk_components.contains(child_id)
+    }
+
+    /// 建構器模式：設置此父件某個子件 BOM 行的用量方式
+    pub fn with_component_usage(mut self, child_id: String, usage: ComponentUsage) -> Self {
+        self.component_usages.insert(child_id, usage);
+        self
+    }
+
+    /// 子件需求數量（依 BOM 行的用量方式；未設置時為父件數量 × BOM 用量）
+    pub fn component_quantity(
+        &self,
+        child_id: &str,
+        parent_quantity: Decimal,
+        bom_quantity: Decimal,
+    ) -> Decimal {
+        match self.component_usages.get(child_id) {
+            Some(usage) => usage.quantity_for(parent_quantity, bom_quantity),
+            None => parent_quantity * bom_quantity,
+        }
+    }
+
+    /// 建構器模式：設置計劃 BOM 變體的計劃百分比（如 60 表示 60%）
+    pub fn with_planning_percentage(mut self, variant_id: String, percentage: Decimal) -> Self {
+        self.planning_percentages.insert(variant_id, percentage);
+        self
+    }
+
+    /// 是否為計劃 BOM 的產品族物料
+    pub fn is_planning_bom(&self) -> bool {
+        !self.planning_percentages.is_empty()
+    }
+
+    /// 查詢 BOM 行相對開工日的偏移（BOM 行偏移優先，否則使用工序偏移）
+    pub fn child_offset(
+        &self,
+        child_id: &str,
+        sequence: u32,
+        operation_sequence: Option<u32>,
+    ) -> i32 {
+        self.bom_line_offsets
+            .iter()
+            .find(|o| o.child_id == child_id && o.sequence == sequence)
+            .map(|o| o.days)
+            .unwrap_or_else(|| self.operation_offset(operation_sequence) as i32)
+    }
+
+    /// 建構器模式：設置排程方式
+    pub fn with_scheduling_mode(mut self, mode: SchedulingMode) -> Self {
+        self.scheduling_mode = mode;
+        self
+    }
+
+    /// 建構器模式：設置變動提前期（每 `per_quantity` 單位增加 `days` 個工作日）
+    ///
+    /// `per_quantity` 設為 1 即每單位計算，設為批量即每批計算。
+    pub fn with_variable_lead_time(mut self, days: Decimal, per_quantity: Decimal) -> Self {
+        self.variable_lead_time = Some(VariableLeadTime { days, per_quantity });
+        self
+    }
+
+    /// 建構器模式：設置近期區段批量規則（如前 14 天批對批、之後 POQ）
+    pub fn with_zone_lot_sizing_rule(mut self, days: u32, rule: LotSizingRule) -> Self {
+        self.zone_lot_sizing = Some(LotSizingZone { days, rule });
+        self
+    }
+
+    /// 建構器模式：設置計劃訂單最小間隔（天）
+    pub fn with_min_order_interval(mut self, days: u32) -> Self {
+        self.min_order_interval_days = Some(days);
+        self
+    }
+
+    /// 建構器模式：設置計劃訂單合併窗口（天）
+    pub fn with_consolidation_window(mut self, days: u32) -> Self {
+        self.consolidation_window_days = Some(days);
+        self
+    }
+
+    /// 建構器模式：設置最大涵蓋天數（保存期限）
+    ///
+    /// 所有批量規則、最小訂單間隔與合併窗口都不會讓單張訂單涵蓋
+    /// 自其需求日期起超過此天數的需求。
+    pub fn with_max_coverage_days(mut self, days: u32) -> Self {
+        self.max_coverage_days = Some(days);
+        self
+    }
+
+    /// 建構器模式：設置週期訂購量（POQ）的週期長度與對齊方式
+    pub fn with_poq_period(mut self, days: u32, alignment: PeriodAlignment) -> Self {
+        self.poq_period_days = days;
+        self.poq_alignment = alignment;
+        self
+    }
+
+    /// 建構器模式：添加運輸方式
+    pub fn with_transport_mode(mut self, mode: TransportMode) -> Self {
+        self.transport_modes.push(mode);
+        self
+    }
+
+    /// 查詢運輸方式
+    pub fn transport_mode(&self, mode_id: &str) -> Option<&TransportMode> {
+        self.transport_modes.iter().find(|m| m.mode_id == mode_id)
+    }
+
+    /// 查詢運輸天數（未指定或找不到運輸方式時為 0）
+    pub fn transit_days(&self, mode_id: Option<&str>) -> u32 {
+        mode_id
+            .and_then(|id| self.transport_mode(id))
+            .map(|m| m.transit_days)
+            .unwrap_or(0)
+    }
+
+    /// 計算指定數量的總提前期（固定 + 變動，變動部分無條件進位到整工作日）
+    pub fn lead_time_for(&self, quantity: Decimal) -> u32 {
+        let variable = self
+            .variable_lead_time
+            .map(|v| v.days_for(quantity))
+            .unwrap_or(0);
+        self.lead_time_days + variable
+    }
+
+    /// 建構器模式：設置數量取整方式
+    pub fn with_rounding_policy(mut self, policy: RoundingPolicy) -> Self {
+        self.rounding_policy = policy;
+        self
+    }
+
+    /// 建構器模式：設置收貨日曆
+    pub fn with_receipt_calendar(mut self, calendar_id: String) -> Self {
+        self.receipt_calendar_id = Some(calendar_id);
+        self
+    }
+
+    /// 建構器模式：設置短缺容差
+    pub fn with_shortage_tolerance(mut self, tolerance: ShortageTolerance) -> Self {
+        self.shortage_tolerance = Some(tolerance);
+        self
+    }
+
+    /// 建構器模式：設置需求時界（工作日）
+    pub fn with_demand_time_fence(mut self, days: u32) -> Self {
+        self.demand_time_fence_days = Some(days);
+        self
+    }
+
+    /// 建構器模式：設置計劃時界（工作日）
+    pub fn with_planning_time_fence(mut self, days: u32) -> Self {
+        self.planning_time_fence_days = Some(days);
+        self
+    }
+
+    /// 依取整方式調整數量（BOM 展開的相依需求數量也使用）
+    pub fn round_quantity(&self, quantity: Decimal) -> Decimal {
+        match self.rounding_policy {
+            RoundingPolicy::RoundUpToUnit => quantity.ceil(),
+            RoundingPolicy::RoundToMultiple(multiple) if multiple > Decimal::ZERO => {
+                (quantity / multiple).ceil() * multiple
+            }
+            _ => quantity,
+        }
+    }
+
+    /// 調整訂購量以符合批量規則（超過最大訂購量時截斷，拆單見 `split_order_quantity`）
+    ///
+    /// 先依取整方式取整，再套用最小訂購量、訂購倍數與最大訂購量
+    pub fn adjust_order_quantity(&self, quantity: Decimal) -> Decimal {
+        let mut quantity = self.round_quantity(quantity);
+
         // 應用最小訂購量
         if let Some(min_qty) = self.minimum_order_qty {
             if quantity < min_qty {
@@ -155,14 +589,82 @@ impl MrpConfig {
         quantity
     }
 
+    /// 將訂購量拆成不超過最大訂購量的多筆數量
+    ///
+    /// 套用最小訂購量與訂購倍數後未超過最大訂購量時只回傳一筆
+    /// （與 `adjust_order_quantity` 相同）；超過時以最大訂購量
+    /// （向下取整到訂購倍數）為單位拆分，最後一筆為餘量。
+    pub fn split_order_quantity(&self, quantity: Decimal) -> Vec<Decimal> {
+        let Some(max_qty) = self.maximum_order_qty else {
+            return vec![self.adjust_order_quantity(quantity)];
+        };
+
+        let chunk = match self.order_multiple {
+            Some(multiple) if multiple > Decimal::ZERO => max_qty - max_qty % multiple,
+            _ => max_qty,
+        };
+        if chunk <= Decimal::ZERO || quantity <= chunk {
+            return vec![self.adjust_order_quantity(quantity)];
+        }
+
+        let mut parts = Vec::new();
+        let mut remaining = quantity;
+        while remaining > chunk {
+            parts.push(chunk);
+            remaining -= chunk;
+        }
+        parts.push(self.adjust_order_quantity(remaining));
+        parts
+    }
+
     /// 檢查是否需要 MRP 計算
     pub fn needs_mrp(&self) -> bool {
         self.mrp_enabled
     }
+
+    /// 建構器模式：設置計劃方式
+    pub fn with_planning_method(mut self, method: PlanningMethod) -> Self {
+        self.planning_method = method;
+        self
+    }
+
+    /// 建構器模式：以再訂購點計劃（同時將計劃方式設為 `ReorderPoint`）
+    ///
+    /// 預計庫存（含已計劃在途）低於再訂購點時，以固定批量（未設置時為 EOQ）下單，
+    /// 不依需求追溯；適用於低價值的 C 類物料
+    pub fn with_reorder_point(mut self, reorder_point: Decimal) -> Self {
+        self.reorder_point = Some(reorder_point);
+        self.planning_method = PlanningMethod::ReorderPoint;
+        self
+    }
+
+    /// 建構器模式：設置新品爬坡曲線
+    pub fn with_ramp_profile(mut self, profile: RampProfile) -> Self {
+        self.ramp_profile = Some(profile);
+        self
+    }
+
+    /// 建構器模式：設置最早下單日期
+    pub fn with_earliest_order_date(mut self, date: NaiveDate) -> Self {
+        self.earliest_order_date = Some(date);
+        self
+    }
+
+    /// 建構器模式：設置生命週期狀態
+    pub fn with_lifecycle(mut self, lifecycle: LifecycleStatus) -> Self {
+        self.lifecycle = lifecycle;
+        self
+    }
+
+    /// 是否由 MRP 展開產生計劃訂單（啟用 MRP 且計劃方式為 `Mrp`）
+    pub fn is_mrp_planned(&self) -> bool {
+        self.mrp_enabled && self.planning_method == PlanningMethod::Mrp
+    }
 }
 
 /// 採購類型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum ProcurementType {
     /// 採購
     Buy,
@@ -172,8 +674,303 @@ pub enum ProcurementType {
     Transfer,
 }
 
+/// 變動提前期：每 `per_quantity` 單位增加 `days` 個工作日
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct VariableLeadTime {
+    /// 每個計量基準增加的工作日數（可為小數，如每 1000 件 0.5 天）
+    pub days: Decimal,
+
+    /// 計量基準數量（1 = 每單位；批量 = 每批）
+    pub per_quantity: Decimal,
+}
+
+impl VariableLeadTime {
+    /// 計算指定數量的變動提前期（無條件進位）
+    pub fn days_for(&self, quantity: Decimal) -> u32 {
+        if self.per_quantity <= Decimal::ZERO || quantity <= Decimal::ZERO {
+            return 0;
+        }
+        (quantity / self.per_quantity * self.days)
+            .ceil()
+            .to_u32()
+            .unwrap_or(0)
+    }
+}
+
+/// BOM 行偏移（父件配置中以子件ID與 BOM 行序號指定一行）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BomLineOffset {
+    /// 子件ID
+    pub child_id: String,
+
+    /// BOM 行序號（`BomItem::sequence`）
+    pub sequence: u32,
+
+    /// 相對父件訂單開工日的工作日數（負值為開工前到位）
+    pub days: i32,
+}
+
+/// BOM 行的用量方式（整備損耗、每批一張標籤、每箱一個棧板等非比例用量）
+///
+/// 子件需求 = 每張訂單固定用量 + 比例用量；比例用量以 BOM 用量乘父件數量，
+/// 設置階梯數量時改為每滿（或不足）一階計一次 BOM 用量。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ComponentUsage {
+    /// 每張訂單的固定用量（與訂單數量無關）
+    #[serde(default)]
+    pub fixed_quantity_per_order: Decimal,
+
+    /// 是否計入比例用量
+    #[serde(default = "default_proportional")]
+    pub proportional: bool,
+
+    /// 階梯數量：每多少父件計一次 BOM 用量（None 表示逐單位比例計算）
+    #[serde(default)]
+    pub step_quantity: Option<Decimal>,
+}
+
+fn default_proportional() -> bool {
+    true
+}
+
+impl ComponentUsage {
+    /// 只有每張訂單固定用量（如每批一張標籤）
+    pub fn fixed(quantity_per_order: Decimal) -> Self {
+        Self {
+            fixed_quantity_per_order: quantity_per_order,
+            proportional: false,
+            step_quantity: None,
+        }
+    }
+
+    /// 比例用量另加每張訂單固定用量（如整備損耗）
+    pub fn with_setup_scrap(quantity_per_order: Decimal) -> Self {
+        Self {
+            fixed_quantity_per_order: quantity_per_order,
+            proportional: true,
+            step_quantity: None,
+        }
+    }
+
+    /// 階梯用量：每 `step_quantity` 件父件（不足一階以一階計）計一次 BOM 用量
+    pub fn stepwise(step_quantity: Decimal) -> Self {
+        Self {
+            fixed_quantity_per_order: Decimal::ZERO,
+            proportional: true,
+            step_quantity: Some(step_quantity),
+        }
+    }
+
+    /// 父件訂單數量對應的子件需求（父件數量為零時不需要子件）
+    pub fn quantity_for(&self, parent_quantity: Decimal, bom_quantity: Decimal) -> Decimal {
+        if parent_quantity <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let variable = match (self.proportional, self.step_quantity) {
+            (false, _) => Decimal::ZERO,
+            (true, Some(step)) if step > Decimal::ZERO => {
+                (parent_quantity / step).ceil() * bom_quantity
+            }
+            (true, _) => parent_quantity * bom_quantity,
+        };
+        self.fixed_quantity_per_order + variable
+    }
+}
+
+/// 運輸方式（如海運、空運、陸運）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TransportMode {
+    /// 運輸方式ID
+    pub mode_id: String,
+
+    /// 運輸天數（工作日）
+    pub transit_days: u32,
+
+    /// 每單位運費
+    pub cost_per_unit: Decimal,
+
+    /// 每批固定運費
+    pub fixed_cost: Decimal,
+}
+
+impl TransportMode {
+    /// 創建新的運輸方式
+    pub fn new(mode_id: String, transit_days: u32, cost_per_unit: Decimal) -> Self {
+        Self {
+            mode_id,
+            transit_days,
+            cost_per_unit,
+            fixed_cost: Decimal::ZERO,
+        }
+    }
+
+    /// 建構器模式：設置每批固定運費
+    pub fn with_fixed_cost(mut self, fixed_cost: Decimal) -> Self {
+        self.fixed_cost = fixed_cost;
+        self
+    }
+
+    /// 計算指定數量的運費
+    pub fn cost_for(&self, quantity: Decimal) -> Decimal {
+        self.fixed_cost + self.cost_per_unit * quantity
+    }
+}
+
+/// 排程方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum SchedulingMode {
+    /// 倒排：從需要日期扣除提前期（預設）
+    #[default]
+    Backward,
+
+    /// 順排：從計劃起始日開始，完工日 = 起始日 + 提前期
+    Forward,
+
+    /// 先倒排，開工日早於計劃起始日（不可行）時改為順排
+    BackwardThenForward,
+}
+
+/// 物料計劃方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum PlanningMethod {
+    /// MRP 展開產生計劃訂單（預設）
+    #[default]
+    Mrp,
+
+    /// 人工計劃
+    Manual,
+
+    /// 僅再訂購點補貨
+    ReorderPoint,
+
+    /// 消耗導向計劃（依歷史消耗補貨）
+    ConsumptionBased,
+}
+
+/// 新品爬坡曲線：自上市日起每週為穩定需求的百分比，曲線結束後為 100%
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RampProfile {
+    /// 上市日（第 1 週起始日；之前的預測視為 0）
+    pub start_date: NaiveDate,
+
+    /// 每週百分比（如 25 表示穩定需求的 25%）
+    pub weekly_percentages: Vec<Decimal>,
+}
+
+impl RampProfile {
+    /// 創建爬坡曲線
+    pub fn new(start_date: NaiveDate, weekly_percentages: Vec<Decimal>) -> Self {
+        Self {
+            start_date,
+            weekly_percentages,
+        }
+    }
+
+    /// 指定日期所在週的百分比
+    pub fn percentage_on(&self, date: NaiveDate) -> Decimal {
+        if date < self.start_date {
+            return Decimal::ZERO;
+        }
+        let week = ((date - self.start_date).num_days() / 7) as usize;
+        self.weekly_percentages
+            .get(week)
+            .copied()
+            .unwrap_or(Decimal::ONE_HUNDRED)
+    }
+
+    /// 將穩定需求數量換算為指定日期的爬坡數量
+    pub fn apply(&self, quantity: Decimal, date: NaiveDate) -> Decimal {
+        quantity * self.percentage_on(date) / Decimal::ONE_HUNDRED
+    }
+}
+
+/// 物料生命週期狀態
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum LifecycleStatus {
+    /// 正常供貨（預設）
+    #[default]
+    Active,
+
+    /// 停產中：停產日後不再下新訂單，需求以剩餘庫存滿足
+    PhaseOut {
+        /// 停產日（最後可下單日）
+        end_date: NaiveDate,
+    },
+
+    /// 已淘汰：不再產生任何新訂單
+    Obsolete,
+}
+
+impl LifecycleStatus {
+    /// 指定下單日期是否仍可下新訂單
+    pub fn allows_order_on(&self, order_date: NaiveDate) -> bool {
+        match self {
+            Self::Active => true,
+            Self::PhaseOut { end_date } => order_date <= *end_date,
+            Self::Obsolete => false,
+        }
+    }
+}
+
+/// 數量取整方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum RoundingPolicy {
+    /// 無條件進位到整數單位（離散物料）
+    RoundUpToUnit,
+
+    /// 無條件進位到指定倍數（如整包、整捲）
+    RoundToMultiple(Decimal),
+
+    /// 允許小數（散裝、液體等；預設）
+    #[default]
+    AllowFractional,
+}
+
+/// 短缺容差
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ShortageTolerance {
+    /// 絕對數量
+    Absolute(Decimal),
+
+    /// 當期總需求的百分比（0–100）
+    Percent(Decimal),
+}
+
+impl ShortageTolerance {
+    /// 短缺量是否在容差內
+    pub fn tolerates(&self, shortage: Decimal, gross_requirement: Decimal) -> bool {
+        let limit = match *self {
+            Self::Absolute(quantity) => quantity,
+            Self::Percent(percent) => gross_requirement * percent / Decimal::ONE_HUNDRED,
+        };
+        shortage <= limit
+    }
+}
+
+/// 近期區段批量規則
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct LotSizingZone {
+    /// 區段長度（自計劃起始日起的日曆天數）
+    pub days: u32,
+
+    /// 區段內的批量規則
+    pub rule: LotSizingRule,
+}
+
 /// 批量規則
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum LotSizingRule {
     /// 批對批（Lot for Lot）- 按實際需求訂購
     LotForLot,
@@ -248,6 +1045,23 @@ mod tests {
         assert_eq!(config.adjust_order_quantity(Decimal::from(600)), Decimal::from(500));
     }
 
+    #[test]
+    fn test_rounding_policy() {
+        let config = MrpConfig::new("BOLT-001".to_string(), 3, ProcurementType::Buy);
+        let third = Decimal::from(100) / Decimal::from(3);
+
+        // 預設允許小數
+        assert_eq!(config.adjust_order_quantity(third), third);
+
+        let config = config.with_rounding_policy(RoundingPolicy::RoundUpToUnit);
+        assert_eq!(config.adjust_order_quantity(third), Decimal::from(34));
+
+        let config =
+            config.with_rounding_policy(RoundingPolicy::RoundToMultiple(Decimal::from(12)));
+        assert_eq!(config.round_quantity(third), Decimal::from(36));
+        assert_eq!(config.round_quantity(Decimal::from(24)), Decimal::from(24));
+    }
+
     #[test]
     fn test_order_multiple_adjustment() {
         let config = MrpConfig::new(
@@ -263,4 +1077,111 @@ mod tests {
         // 200 已經是倍數，不需調整
         assert_eq!(config.adjust_order_quantity(Decimal::from(200)), Decimal::from(200));
     }
+
+    #[test]
+    fn test_variable_lead_time() {
+        let config = MrpConfig::new("GEAR-001".to_string(), 5, ProcurementType::Make)
+            .with_variable_lead_time(Decimal::new(5, 1), Decimal::from(1000));
+
+        assert_eq!(config.lead_time_for(Decimal::from(100)), 6);
+        assert_eq!(config.lead_time_for(Decimal::from(10000)), 10);
+        assert_eq!(config.lead_time_for(Decimal::ZERO), 5);
+    }
+
+    #[test]
+    fn test_component_usage() {
+        let config = MrpConfig::new("JUICE-001".to_string(), 1, ProcurementType::Make)
+            .with_component_usage("LABEL".to_string(), ComponentUsage::fixed(Decimal::ONE))
+            .with_component_usage(
+                "FILM".to_string(),
+                ComponentUsage::with_setup_scrap(Decimal::from(3)),
+            )
+            .with_component_usage(
+                "PALLET".to_string(),
+                ComponentUsage::stepwise(Decimal::from(48)),
+            );
+        let quantity = Decimal::from(100);
+
+        // 每批一張標籤、整備損耗 3 + 100 × 0.5、每 48 件一個棧板
+        assert_eq!(
+            config.component_quantity("LABEL", quantity, Decimal::ONE),
+            Decimal::ONE
+        );
+        assert_eq!(
+            config.component_quantity("FILM", quantity, Decimal::new(5, 1)),
+            Decimal::from(53)
+        );
+        assert_eq!(
+            config.component_quantity("PALLET", quantity, Decimal::ONE),
+            Decimal::from(3)
+        );
+        assert_eq!(
+            config.component_quantity("BOTTLE", quantity, Decimal::TWO),
+            Decimal::from(200)
+        );
+        assert_eq!(
+            config.component_quantity("LABEL", Decimal::ZERO, Decimal::ONE),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_economic_order_quantity() {
+        let config = MrpConfig::new("BEARING-001".to_string(), 5, ProcurementType::Buy)
+            .with_unit_cost(Decimal::from(20))
+            .with_eoq_costs(Decimal::from(100), Decimal::new(25, 2));
+
+        // sqrt(2 × 1000 × 100 / 5) = 200
+        assert_eq!(
+            config.economic_order_quantity(Decimal::from(1000)).unwrap(),
+            Decimal::from(200)
+        );
+
+        let missing_cost = MrpConfig::new("BEARING-002".to_string(), 5, ProcurementType::Buy)
+            .with_eoq_costs(Decimal::from(100), Decimal::new(25, 2));
+        assert!(matches!(
+            missing_cost.economic_order_quantity(Decimal::from(1000)),
+            Err(crate::MrpError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_ramp_profile() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        let profile = RampProfile::new(
+            date(3),
+            vec![Decimal::from(20), Decimal::from(50), Decimal::from(80)],
+        );
+
+        assert_eq!(profile.apply(Decimal::from(100), date(1)), Decimal::ZERO);
+        assert_eq!(
+            profile.apply(Decimal::from(100), date(9)),
+            Decimal::from(20)
+        );
+        assert_eq!(
+            profile.apply(Decimal::from(100), date(10)),
+            Decimal::from(50)
+        );
+        assert_eq!(
+            profile.apply(Decimal::from(100), date(24)),
+            Decimal::from(100)
+        );
+    }
+
+    #[test]
+    fn test_split_order_quantity() {
+        let config = MrpConfig::new("PLATE-001".to_string(), 3, ProcurementType::Buy)
+            .with_maximum_order_qty(Decimal::from(500))
+            .with_order_multiple(Decimal::from(30));
+
+        // 最大 500 取整到倍數 → 每張 480
+        assert_eq!(
+            config.split_order_quantity(Decimal::from(1000)),
+            vec![Decimal::from(480), Decimal::from(480), Decimal::from(60)]
+        );
+        assert_eq!(
+            config.split_order_quantity(Decimal::from(100)),
+            vec![Decimal::from(120)]
+        );
+    }
 }
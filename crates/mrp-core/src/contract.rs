@@ -0,0 +1,166 @@
+//! 採購合約模型（框架訂單）
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 框架採購訂單（Blanket PO）
+///
+/// 與供應商約定有效期內的總採購量，實際交貨以叫貨（call-off）逐次釋放
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BlanketOrder {
+    /// 框架訂單ID
+    pub id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 供應商ID
+    pub supplier_id: String,
+
+    /// 合約總量
+    pub total_quantity: Decimal,
+
+    /// 已叫貨數量
+    pub released_quantity: Decimal,
+
+    /// 有效期起
+    pub valid_from: NaiveDate,
+
+    /// 有效期迄
+    pub valid_to: NaiveDate,
+
+    /// 叫貨提前期（工作日）
+    pub call_off_lead_time_days: u32,
+
+    /// 來源單據（合約編號）
+    pub source_ref: Option<String>,
+}
+
+impl BlanketOrder {
+    /// 創建新的框架訂單
+    pub fn new(
+        component_id: String,
+        supplier_id: String,
+        total_quantity: Decimal,
+        valid_from: NaiveDate,
+        valid_to: NaiveDate,
+        call_off_lead_time_days: u32,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            component_id,
+            supplier_id,
+            total_quantity,
+            released_quantity: Decimal::ZERO,
+            valid_from,
+            valid_to,
+            call_off_lead_time_days,
+            source_ref: None,
+        }
+    }
+
+    /// 建構器模式：設置已叫貨數量
+    pub fn with_released_quantity(mut self, quantity: Decimal) -> Self {
+        self.released_quantity = quantity;
+        self
+    }
+
+    /// 建構器模式：設置來源單據
+    pub fn with_source_ref(mut self, source_ref: String) -> Self {
+        self.source_ref = Some(source_ref);
+        self
+    }
+
+    /// 剩餘可叫貨數量
+    pub fn remaining_quantity(&self) -> Decimal {
+        (self.total_quantity - self.released_quantity).max(Decimal::ZERO)
+    }
+
+    /// 檢查日期是否在有效期內
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        date >= self.valid_from && date <= self.valid_to
+    }
+}
+
+/// 供應商合約最低承諾量（Take-or-Pay）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SupplyContract {
+    /// 合約ID
+    pub id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 供應商ID
+    pub supplier_id: String,
+
+    /// 期間內最低承諾採購量
+    pub minimum_quantity: Decimal,
+
+    /// 有效期起
+    pub valid_from: NaiveDate,
+
+    /// 有效期迄
+    pub valid_to: NaiveDate,
+
+    /// 是否為照付不議（未達承諾量仍須付款）
+    pub take_or_pay: bool,
+}
+
+impl SupplyContract {
+    /// 創建新的承諾量合約
+    pub fn new(
+        component_id: String,
+        supplier_id: String,
+        minimum_quantity: Decimal,
+        valid_from: NaiveDate,
+        valid_to: NaiveDate,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            component_id,
+            supplier_id,
+            minimum_quantity,
+            valid_from,
+            valid_to,
+            take_or_pay: false,
+        }
+    }
+
+    /// 建構器模式：設置為照付不議合約
+    pub fn as_take_or_pay(mut self) -> Self {
+        self.take_or_pay = true;
+        self
+    }
+
+    /// 檢查日期是否在有效期內
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        date >= self.valid_from && date <= self.valid_to
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blanket_order() {
+        let blanket = BlanketOrder::new(
+            "BOLT-001".to_string(),
+            "VENDOR-A".to_string(),
+            Decimal::from(1000),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            2,
+        )
+        .with_released_quantity(Decimal::from(400));
+
+        assert_eq!(blanket.remaining_quantity(), Decimal::from(600));
+        assert!(blanket.is_valid_on(NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()));
+        assert!(!blanket.is_valid_on(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()));
+    }
+}
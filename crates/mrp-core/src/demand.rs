@@ -5,8 +5,11 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::SourceDocument;
+
 /// 需求類型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum DemandType {
     /// 銷售訂單
     SalesOrder,
@@ -18,8 +21,33 @@ pub enum DemandType {
     Dependent,
 }
 
+/// 客戶等級（需求分配與排程優先順序的第一鍵，其次才是數值優先級）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum CustomerTier {
+    /// 策略客戶
+    Strategic,
+    /// 一般客戶（預設）
+    #[default]
+    Standard,
+    /// 現貨／臨時客戶
+    Spot,
+}
+
+impl CustomerTier {
+    /// 等級排序值（越大越優先）
+    pub fn rank(&self) -> u8 {
+        match self {
+            Self::Strategic => 2,
+            Self::Standard => 1,
+            Self::Spot => 0,
+        }
+    }
+}
+
 /// 需求
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Demand {
     /// 需求ID
     pub id: Uuid,
@@ -39,11 +67,23 @@ pub struct Demand {
     /// 來源單據（如銷售訂單號）
     pub source_ref: Option<String>,
 
+    /// 結構化來源單據（單據類型、號碼、行號、外部系統）
+    #[serde(default)]
+    pub source_document: Option<SourceDocument>,
+
     /// 優先級（1-10，10最高）
     pub priority: u8,
 
     /// 工廠/組織
     pub plant_id: Option<String>,
+
+    /// 客戶等級
+    #[serde(default)]
+    pub customer_tier: CustomerTier,
+
+    /// 單位金額（營收或毛利；相依需求由父件需求換算）
+    #[serde(default)]
+    pub unit_value: Option<Decimal>,
 }
 
 impl Demand {
@@ -61,8 +101,11 @@ impl Demand {
             required_date,
             demand_type,
             source_ref: None,
+            source_document: None,
             priority: 5,
             plant_id: None,
+            customer_tier: CustomerTier::Standard,
+            unit_value: None,
         }
     }
 
@@ -72,6 +115,12 @@ impl Demand {
         self
     }
 
+    /// 建構器模式：設置結構化來源單據
+    pub fn with_source_document(mut self, document: SourceDocument) -> Self {
+        self.source_document = Some(document);
+        self
+    }
+
     /// 建構器模式：設置優先級
     pub fn with_priority(mut self, priority: u8) -> Self {
         self.priority = priority.min(10);
@@ -84,6 +133,28 @@ impl Demand {
         self
     }
 
+    /// 建構器模式：設置客戶等級
+    pub fn with_customer_tier(mut self, tier: CustomerTier) -> Self {
+        self.customer_tier = tier;
+        self
+    }
+
+    /// 建構器模式：設置單位金額
+    pub fn with_unit_value(mut self, unit_value: Decimal) -> Self {
+        self.unit_value = Some(unit_value);
+        self
+    }
+
+    /// 需求金額（數量 × 單位金額；未設置單位金額時為 None）
+    pub fn value(&self) -> Option<Decimal> {
+        self.unit_value.map(|unit_value| unit_value * self.quantity)
+    }
+
+    /// 分配與排程的排序鍵（客戶等級優先，其次為優先級；越大越優先）
+    pub fn allocation_rank(&self) -> (u8, u8) {
+        (self.customer_tier.rank(), self.priority)
+    }
+
     /// 檢查是否為獨立需求
     pub fn is_independent(&self) -> bool {
         matches!(
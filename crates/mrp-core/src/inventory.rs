@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 
 /// 庫存狀態
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Inventory {
     /// 物料ID
     pub component_id: String,
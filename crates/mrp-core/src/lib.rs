@@ -4,21 +4,36 @@
 
 pub mod calendar;
 pub mod config;
+pub mod contract;
 pub mod demand;
 pub mod inventory;
 pub mod plan;
+pub mod resource;
+pub mod routing;
+pub mod source;
 pub mod supply;
 
 // Re-export 主要類型
-pub use calendar::{ShiftSchedule, WorkCalendar};
-pub use config::{LotSizingRule, MrpConfig, ProcurementType};
-pub use demand::{Demand, DemandType};
+pub use calendar::{
+    CalendarOverride, CalendarRegistry, PeriodAlignment, ShiftSchedule, WorkCalendar,
+};
+pub use config::{
+    BomLineOffset, ComponentUsage, LifecycleStatus, LotSizingRule, LotSizingZone, MrpConfig,
+    PlanningMethod, ProcurementType, RampProfile, RoundingPolicy, SchedulingMode,
+    ShortageTolerance, TransportMode, VariableLeadTime,
+};
+pub use contract::{BlanketOrder, SupplyContract};
+pub use demand::{CustomerTier, Demand, DemandType};
 pub use inventory::Inventory;
-pub use plan::{PeggingRecord, PlannedOrder, PlannedOrderType};
-pub use supply::{Supply, SupplyType};
+pub use plan::{OrderAnnotation, PeggingRecord, PlannedOrder, PlannedOrderType};
+pub use resource::{ResourceBill, ResourceLoad};
+pub use routing::{Operation, Routing, WorkCenter};
+pub use source::SourceDocument;
+pub use supply::{Supply, SupplyType, ENGINE_SUPPLY_TAG};
 
 /// MRP 錯誤類型
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum MrpError {
     #[error("找不到物料配置: {0}")]
     ConfigNotFound(String),
@@ -32,9 +47,21 @@ pub enum MrpError {
     #[error("批量規則缺少必要參數")]
     MissingLotSize,
 
+    #[error("無效的物料配置: {0}")]
+    InvalidConfig(String),
+
+    #[error("找不到工作日曆: {0}")]
+    CalendarNotFound(String),
+
     #[error("無效的日期: {0}")]
     InvalidDate(String),
 
+    #[error("匯入錯誤: {0}")]
+    ImportError(String),
+
+    #[error("匯出錯誤: {0}")]
+    ExportError(String),
+
     #[error("計算錯誤: {0}")]
     CalculationError(String),
 
@@ -7,6 +7,7 @@ use uuid::Uuid;
 
 /// 計劃訂單類型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum PlannedOrderType {
     /// 採購
     Purchase,
@@ -18,6 +19,7 @@ pub enum PlannedOrderType {
 
 /// 計劃訂單（MRP計算結果）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct PlannedOrder {
     /// 計劃訂單ID
     pub id: Uuid,
@@ -42,6 +44,18 @@ pub struct PlannedOrder {
 
     /// 需求來源追溯
     pub pegging: Vec<PeggingRecord>,
+
+    /// 框架訂單ID（對框架訂單的叫貨建議）
+    #[serde(default)]
+    pub blanket_order_id: Option<Uuid>,
+
+    /// 原始需要日期（排程後完工日與需要日期不同時記錄；required_date 為預計完工日）
+    #[serde(default)]
+    pub need_date: Option<NaiveDate>,
+
+    /// 運輸方式ID（採購件選定的運輸方式）
+    #[serde(default)]
+    pub transport_mode: Option<String>,
 }
 
 impl PlannedOrder {
@@ -62,6 +76,9 @@ impl PlannedOrder {
             order_type,
             source_id: None,
             pegging: Vec::new(),
+            blanket_order_id: None,
+            need_date: None,
+            transport_mode: None,
         }
     }
 
@@ -71,6 +88,18 @@ impl PlannedOrder {
         self
     }
 
+    /// 建構器模式：設置框架訂單（叫貨建議）
+    pub fn with_blanket_order_id(mut self, blanket_order_id: Uuid) -> Self {
+        self.blanket_order_id = Some(blanket_order_id);
+        self
+    }
+
+    /// 建構器模式：設置運輸方式
+    pub fn with_transport_mode(mut self, mode_id: String) -> Self {
+        self.transport_mode = Some(mode_id);
+        self
+    }
+
     /// 建構器模式：設置需求追溯
     pub fn with_pegging(mut self, pegging: Vec<PeggingRecord>) -> Self {
         self.pegging = pegging;
@@ -92,14 +121,49 @@ impl PlannedOrder {
         self.order_type == PlannedOrderType::Purchase
     }
 
+    /// 檢查是否為框架訂單叫貨
+    pub fn is_call_off(&self) -> bool {
+        self.blanket_order_id.is_some()
+    }
+
+    /// 檢查預計完工日是否晚於需要日期
+    pub fn is_late(&self) -> bool {
+        self.need_date
+            .is_some_and(|need_date| self.required_date > need_date)
+    }
+
+    /// 預計完工日晚於需要日期的天數（未延遲為零）
+    pub fn days_late(&self) -> i64 {
+        self.need_date.map_or(0, |need_date| {
+            (self.required_date - need_date).num_days().max(0)
+        })
+    }
+
     /// 檢查是否為生產訂單
     pub fn is_production(&self) -> bool {
         self.order_type == PlannedOrderType::Production
     }
+
+    /// 轉為帶引擎標記的計劃供應（匯出到 ERP 用；回傳時可被辨識並取代）
+    pub fn to_supply(&self) -> crate::Supply {
+        crate::Supply::new(
+            self.component_id.clone(),
+            self.quantity,
+            self.required_date,
+            crate::SupplyType::PlannedOrder,
+        )
+        .with_engine_tag(self.id)
+    }
+
+    /// 轉為確認的計劃供應（計劃員確認後回傳；MRP 視為固定供應，不重新產生，生產訂單仍展開子件需求）
+    pub fn to_firm_supply(&self) -> crate::Supply {
+        self.to_supply().as_firm()
+    }
 }
 
 /// 需求追溯記錄
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct PeggingRecord {
     /// 源需求ID
     pub demand_id: Uuid,
@@ -109,6 +173,18 @@ pub struct PeggingRecord {
 
     /// 追溯路徑（多級）
     pub path: Vec<String>,
+
+    /// 源需求的結構化來源單據
+    #[serde(default)]
+    pub source_document: Option<crate::SourceDocument>,
+
+    /// 替代料追溯：被替代的主料ID（此記錄的物料為替代料時）
+    #[serde(default)]
+    pub substitute_for: Option<String>,
+
+    /// 追溯金額（追溯數量 × 源需求單位金額；源需求未設金額時為 None）
+    #[serde(default)]
+    pub value: Option<Decimal>,
 }
 
 impl PeggingRecord {
@@ -118,6 +194,9 @@ impl PeggingRecord {
             demand_id,
             quantity,
             path: Vec::new(),
+            source_document: None,
+            substitute_for: None,
+            value: None,
         }
     }
 
@@ -127,6 +206,24 @@ impl PeggingRecord {
         self
     }
 
+    /// 建構器模式：設置追溯金額
+    pub fn with_value(mut self, value: Decimal) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// 建構器模式：標記為替代料追溯（記錄被替代的主料ID）
+    pub fn with_substitute_for(mut self, primary_id: String) -> Self {
+        self.substitute_for = Some(primary_id);
+        self
+    }
+
+    /// 建構器模式：設置源需求的結構化來源單據
+    pub fn with_source_document(mut self, document: crate::SourceDocument) -> Self {
+        self.source_document = Some(document);
+        self
+    }
+
     /// 添加路徑節點
     pub fn add_path_node(&mut self, node: String) {
         self.path.push(node);
@@ -138,6 +235,48 @@ impl PeggingRecord {
     }
 }
 
+/// 計劃員對計劃訂單的註記（跨次計算延續）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct OrderAnnotation {
+    /// 備註
+    pub note: Option<String>,
+
+    /// 標籤
+    pub tags: Vec<String>,
+}
+
+impl OrderAnnotation {
+    /// 創建空的註記
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 建構器模式：設置備註
+    pub fn with_note(mut self, note: String) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    /// 建構器模式：添加標籤
+    pub fn with_tag(mut self, tag: String) -> Self {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
+
+    /// 檢查是否有指定標籤
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// 檢查是否為空註記
+    pub fn is_empty(&self) -> bool {
+        self.note.is_none() && self.tags.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
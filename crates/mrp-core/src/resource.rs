@@ -0,0 +1,206 @@
+//! 資源清單（Bill of Resources）：物料 → 各資源工時
+//!
+//! 粗略產能規劃、產能需求規劃與成本估算共用同一份資料；可手動建立、從工藝路線彙總或由 CSV 匯入。
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::routing::Routing;
+use crate::MrpError;
+
+/// 資源清單的單一資源負荷
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ResourceLoad {
+    /// 工作中心ID
+    pub work_center_id: String,
+
+    /// 每單位的工時
+    pub hours_per_unit: Decimal,
+
+    /// 每張訂單一次的整備工時
+    #[serde(default)]
+    pub setup_hours: Decimal,
+
+    /// 負荷發生在需要日期前幾天（反映前段工序的提前期）
+    #[serde(default)]
+    pub offset_days: u32,
+}
+
+impl ResourceLoad {
+    /// 創建資源負荷（沒有整備工時、負荷在需要日期）
+    pub fn new(work_center_id: String, hours_per_unit: Decimal) -> Self {
+        Self {
+            work_center_id,
+            hours_per_unit,
+            setup_hours: Decimal::ZERO,
+            offset_days: 0,
+        }
+    }
+
+    /// 建構器模式：設置整備工時
+    pub fn with_setup_hours(mut self, hours: Decimal) -> Self {
+        self.setup_hours = hours;
+        self
+    }
+
+    /// 建構器模式：設置偏移天數
+    pub fn with_offset_days(mut self, days: u32) -> Self {
+        self.offset_days = days;
+        self
+    }
+
+    /// 生產指定數量所需的工時（數量為零時不計整備）
+    pub fn hours_for(&self, quantity: Decimal) -> Decimal {
+        if quantity > Decimal::ZERO {
+            self.setup_hours + quantity * self.hours_per_unit
+        } else {
+            Decimal::ZERO
+        }
+    }
+}
+
+/// 資源清單（物料 → 資源工時）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ResourceBill {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 資源負荷
+    pub loads: Vec<ResourceLoad>,
+}
+
+impl ResourceBill {
+    /// 創建空的資源清單
+    pub fn new(component_id: String) -> Self {
+        Self {
+            component_id,
+            loads: Vec::new(),
+        }
+    }
+
+    /// 建構器模式：添加資源負荷
+    pub fn with_load(
+        mut self,
+        work_center_id: String,
+        hours_per_unit: Decimal,
+        offset_days: u32,
+    ) -> Self {
+        self.loads
+            .push(ResourceLoad::new(work_center_id, hours_per_unit).with_offset_days(offset_days));
+        self
+    }
+
+    /// 建構器模式：添加完整設定的資源負荷
+    pub fn with_resource_load(mut self, load: ResourceLoad) -> Self {
+        self.loads.push(load);
+        self
+    }
+
+    /// 從工藝路線建立資源清單（每道工序一筆，含整備工時；負荷都在需要日期）
+    pub fn from_routing(routing: &Routing) -> Self {
+        routing
+            .operations
+            .iter()
+            .fold(Self::new(routing.component_id.clone()), |bill, op| {
+                bill.with_resource_load(
+                    ResourceLoad::new(op.work_center_id.clone(), op.run_hours_per_unit)
+                        .with_setup_hours(op.setup_hours),
+                )
+            })
+    }
+
+    /// 從多條工藝路線建立資源清單（以物料ID為鍵）
+    pub fn from_routings<'a>(
+        routings: impl IntoIterator<Item = &'a Routing>,
+    ) -> HashMap<String, Self> {
+        routings
+            .into_iter()
+            .map(|routing| (routing.component_id.clone(), Self::from_routing(routing)))
+            .collect()
+    }
+
+    /// 生產指定數量時各工作中心的工時（同一工作中心的多筆負荷合計）
+    pub fn hours_by_resource(&self, quantity: Decimal) -> BTreeMap<String, Decimal> {
+        let mut hours: BTreeMap<String, Decimal> = BTreeMap::new();
+        for load in &self.loads {
+            *hours.entry(load.work_center_id.clone()).or_default() += load.hours_for(quantity);
+        }
+        hours
+    }
+
+    /// 生產指定數量的總工時
+    pub fn total_hours(&self, quantity: Decimal) -> Decimal {
+        self.loads.iter().map(|load| load.hours_for(quantity)).sum()
+    }
+
+    /// 生產指定數量的人工／機器成本（`rates` 為工作中心每小時費率）
+    pub fn cost(
+        &self,
+        quantity: Decimal,
+        rates: &HashMap<String, Decimal>,
+    ) -> crate::Result<Decimal> {
+        self.loads
+            .iter()
+            .map(|load| {
+                rates
+                    .get(&load.work_center_id)
+                    .map(|rate| load.hours_for(quantity) * rate)
+                    .ok_or_else(|| {
+                        MrpError::InvalidConfig(format!(
+                            "工作中心 {} 沒有每小時費率",
+                            load.work_center_id
+                        ))
+                    })
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::Operation;
+
+    #[test]
+    fn test_bill_from_routing_hours_and_cost() {
+        let routing = Routing::new("FRAME".to_string())
+            .with_operation(Operation::new(
+                10,
+                "WELD".to_string(),
+                Decimal::from(2),
+                Decimal::new(5, 1),
+            ))
+            .with_operation(Operation::new(
+                20,
+                "PAINT".to_string(),
+                Decimal::ONE,
+                Decimal::new(25, 2),
+            ))
+            .with_operation(Operation::new(
+                30,
+                "WELD".to_string(),
+                Decimal::ZERO,
+                Decimal::new(25, 2),
+            ));
+
+        let bills = ResourceBill::from_routings([&routing]);
+        let bill = &bills["FRAME"];
+
+        // WELD：整備 2 + 20 × 0.5 + 20 × 0.25；PAINT：整備 1 + 20 × 0.25
+        let hours = bill.hours_by_resource(Decimal::from(20));
+        assert_eq!(hours["WELD"], Decimal::from(17));
+        assert_eq!(hours["PAINT"], Decimal::from(6));
+        assert_eq!(bill.total_hours(Decimal::ZERO), Decimal::ZERO);
+
+        let mut rates = HashMap::from([("WELD".to_string(), Decimal::from(40))]);
+        assert!(bill.cost(Decimal::from(20), &rates).is_err());
+        rates.insert("PAINT".to_string(), Decimal::from(30));
+        assert_eq!(
+            bill.cost(Decimal::from(20), &rates).unwrap(),
+            Decimal::from(860)
+        );
+    }
+}
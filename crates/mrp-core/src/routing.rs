@@ -0,0 +1,271 @@
+//! 工藝路線與工作中心模型（產能規劃與排程使用）
+//!
+//! 時間單位一律為小時；工序序號與 BOM 行的 `operation_sequence` 對應。
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::calendar::WorkCalendar;
+
+/// 工作中心
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct WorkCenter {
+    /// 工作中心ID
+    pub id: String,
+
+    /// 工作中心日曆ID（於 `CalendarRegistry` 解析，可為繼承工廠日曆的覆寫日曆）
+    pub calendar_id: String,
+
+    /// 每個工作日每台設備的可用工時
+    pub hours_per_day: Decimal,
+
+    /// 並行設備（或班組）數
+    pub parallel_units: u32,
+
+    /// 效率（1 表示 100%；可用工時乘以效率）
+    pub efficiency: Decimal,
+}
+
+impl WorkCenter {
+    /// 創建工作中心（單台設備、效率 100%）
+    pub fn new(id: String, calendar_id: String, hours_per_day: Decimal) -> Self {
+        Self {
+            id,
+            calendar_id,
+            hours_per_day,
+            parallel_units: 1,
+            efficiency: Decimal::ONE,
+        }
+    }
+
+    /// 建構器模式：設置並行設備數
+    pub fn with_parallel_units(mut self, units: u32) -> Self {
+        self.parallel_units = units;
+        self
+    }
+
+    /// 建構器模式：設置效率
+    pub fn with_efficiency(mut self, efficiency: Decimal) -> Self {
+        self.efficiency = efficiency;
+        self
+    }
+
+    /// 每個工作日的有效產能（工時）
+    pub fn daily_capacity(&self) -> Decimal {
+        self.hours_per_day * Decimal::from(self.parallel_units) * self.efficiency
+    }
+
+    /// 指定日期的可用工時（非工作日為零）
+    pub fn available_hours(&self, calendar: &WorkCalendar, date: NaiveDate) -> Decimal {
+        if calendar.is_working_day(date) {
+            self.daily_capacity()
+        } else {
+            Decimal::ZERO
+        }
+    }
+
+    /// 期間 `[start, end)` 的可用工時
+    pub fn available_hours_between(
+        &self,
+        calendar: &WorkCalendar,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Decimal {
+        start
+            .iter_days()
+            .take_while(|date| *date < end)
+            .map(|date| self.available_hours(calendar, date))
+            .sum()
+    }
+}
+
+/// 工序
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Operation {
+    /// 工序序號
+    pub sequence: u32,
+
+    /// 工作中心ID
+    pub work_center_id: String,
+
+    /// 整備時間（每張訂單一次）
+    pub setup_hours: Decimal,
+
+    /// 單位加工時間
+    pub run_hours_per_unit: Decimal,
+
+    /// 開工前排隊時間
+    #[serde(default)]
+    pub queue_hours: Decimal,
+
+    /// 完工後搬運到下一工序的時間
+    #[serde(default)]
+    pub move_hours: Decimal,
+
+    /// 工序說明
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl Operation {
+    /// 創建工序
+    pub fn new(
+        sequence: u32,
+        work_center_id: String,
+        setup_hours: Decimal,
+        run_hours_per_unit: Decimal,
+    ) -> Self {
+        Self {
+            sequence,
+            work_center_id,
+            setup_hours,
+            run_hours_per_unit,
+            queue_hours: Decimal::ZERO,
+            move_hours: Decimal::ZERO,
+            description: None,
+        }
+    }
+
+    /// 建構器模式：設置排隊與搬運時間
+    pub fn with_queue_and_move(mut self, queue_hours: Decimal, move_hours: Decimal) -> Self {
+        self.queue_hours = queue_hours;
+        self.move_hours = move_hours;
+        self
+    }
+
+    /// 建構器模式：設置工序說明
+    pub fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    /// 加工指定數量所需的工作中心工時（整備 + 加工，不含排隊與搬運）
+    pub fn required_hours(&self, quantity: Decimal) -> Decimal {
+        self.setup_hours + self.run_hours_per_unit * quantity
+    }
+
+    /// 工序總經過時間（排隊 + 整備 + 加工 + 搬運）
+    pub fn elapsed_hours(&self, quantity: Decimal) -> Decimal {
+        self.queue_hours + self.required_hours(quantity) + self.move_hours
+    }
+}
+
+/// 工藝路線（物料的工序清單，按序號排列）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Routing {
+    /// 物料ID
+    pub component_id: String,
+
+    /// 工序（按序號由小到大）
+    pub operations: Vec<Operation>,
+}
+
+impl Routing {
+    /// 創建空的工藝路線
+    pub fn new(component_id: String) -> Self {
+        Self {
+            component_id,
+            operations: Vec::new(),
+        }
+    }
+
+    /// 建構器模式：添加工序（同序號時取代原工序）
+    pub fn with_operation(mut self, operation: Operation) -> Self {
+        match self
+            .operations
+            .binary_search_by_key(&operation.sequence, |op| op.sequence)
+        {
+            Ok(index) => self.operations[index] = operation,
+            Err(index) => self.operations.insert(index, operation),
+        }
+        self
+    }
+
+    /// 查詢工序
+    pub fn operation(&self, sequence: u32) -> Option<&Operation> {
+        self.operations.iter().find(|op| op.sequence == sequence)
+    }
+
+    /// 加工指定數量所需的總工時
+    pub fn total_hours(&self, quantity: Decimal) -> Decimal {
+        self.operations
+            .iter()
+            .map(|op| op.required_hours(quantity))
+            .sum()
+    }
+
+    /// 加工指定數量時各工作中心的負荷（工時）
+    pub fn load_by_work_center(&self, quantity: Decimal) -> BTreeMap<String, Decimal> {
+        let mut load = BTreeMap::new();
+        for op in &self.operations {
+            *load
+                .entry(op.work_center_id.clone())
+                .or_insert(Decimal::ZERO) += op.required_hours(quantity);
+        }
+        load
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routing_load_and_work_center_capacity() {
+        let routing = Routing::new("FRAME-001".to_string())
+            .with_operation(Operation::new(
+                20,
+                "WELD".to_string(),
+                Decimal::from(2),
+                Decimal::new(5, 1),
+            ))
+            .with_operation(
+                Operation::new(10, "CUT".to_string(), Decimal::ONE, Decimal::new(1, 1))
+                    .with_queue_and_move(Decimal::from(4), Decimal::ONE),
+            )
+            .with_operation(Operation::new(
+                30,
+                "CUT".to_string(),
+                Decimal::ZERO,
+                Decimal::new(2, 1),
+            ));
+
+        let sequences: Vec<u32> = routing.operations.iter().map(|op| op.sequence).collect();
+        assert_eq!(sequences, vec![10, 20, 30]);
+
+        // 數量 10：CUT 1 + 1 + 2 = 4，WELD 2 + 5 = 7
+        let load = routing.load_by_work_center(Decimal::from(10));
+        assert_eq!(load["CUT"], Decimal::from(4));
+        assert_eq!(load["WELD"], Decimal::from(7));
+        assert_eq!(routing.total_hours(Decimal::from(10)), Decimal::from(11));
+        assert_eq!(
+            routing
+                .operation(10)
+                .unwrap()
+                .elapsed_hours(Decimal::from(10)),
+            Decimal::from(7)
+        );
+
+        let center = WorkCenter::new("WELD".to_string(), "PLANT".to_string(), Decimal::from(8))
+            .with_parallel_units(2)
+            .with_efficiency(Decimal::new(9, 1));
+        let calendar = WorkCalendar::new("PLANT".to_string());
+        // 2025-11-07 週五、11-08 週六
+        let friday = NaiveDate::from_ymd_opt(2025, 11, 7).unwrap();
+        let saturday = NaiveDate::from_ymd_opt(2025, 11, 8).unwrap();
+        assert_eq!(
+            center.available_hours(&calendar, friday),
+            Decimal::new(144, 1)
+        );
+        assert_eq!(center.available_hours(&calendar, saturday), Decimal::ZERO);
+        assert_eq!(
+            center.available_hours_between(&calendar, friday, friday + chrono::Duration::days(4)),
+            Decimal::new(288, 1)
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! 來源單據引用模型
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 結構化來源單據引用（對應 ERP 單據行）
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SourceDocument {
+    /// 單據類型（如 SO、PO、WO）
+    pub document_type: String,
+
+    /// 單據號碼
+    pub number: String,
+
+    /// 行號
+    pub line: Option<u32>,
+
+    /// 外部系統ID（多 ERP 並存時區分來源）
+    pub system_id: Option<String>,
+}
+
+impl SourceDocument {
+    /// 創建新的來源單據引用
+    pub fn new(document_type: String, number: String) -> Self {
+        Self {
+            document_type,
+            number,
+            line: None,
+            system_id: None,
+        }
+    }
+
+    /// 建構器模式：設置行號
+    pub fn with_line(mut self, line: u32) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// 建構器模式：設置外部系統ID
+    pub fn with_system_id(mut self, system_id: String) -> Self {
+        self.system_id = Some(system_id);
+        self
+    }
+}
+
+impl fmt::Display for SourceDocument {
+    /// 格式：`[系統ID/]類型-號碼[#行號]`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(system_id) = &self.system_id {
+            write!(f, "{}/", system_id)?;
+        }
+        write!(f, "{}-{}", self.document_type, self.number)?;
+        if let Some(line) = self.line {
+            write!(f, "#{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_document_display() {
+        let doc = SourceDocument::new("SO".to_string(), "10045".to_string())
+            .with_line(20)
+            .with_system_id("SAP-EU".to_string());
+
+        assert_eq!(doc.to_string(), "SAP-EU/SO-10045#20");
+        assert_eq!(
+            SourceDocument::new("PO".to_string(), "7".to_string()).to_string(),
+            "PO-7"
+        );
+    }
+}
@@ -5,8 +5,16 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::SourceDocument;
+
+/// 引擎產生的計劃供應在 `source_ref` 中的標記前綴（後接計劃訂單ID）
+///
+/// 計劃訂單匯出到 ERP 後若以未確認的計劃供應回傳，引擎據此辨識為前次計算的結果。
+pub const ENGINE_SUPPLY_TAG: &str = "NEXUS-MRP:";
+
 /// 供應類型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum SupplyType {
     /// 現有庫存
     OnHand,
@@ -22,6 +30,7 @@ pub enum SupplyType {
 
 /// 供應
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Supply {
     /// 供應ID
     pub id: Uuid,
@@ -41,8 +50,16 @@ pub struct Supply {
     /// 來源單據
     pub source_ref: Option<String>,
 
+    /// 結構化來源單據（單據類型、號碼、行號、外部系統）
+    #[serde(default)]
+    pub source_document: Option<SourceDocument>,
+
     /// 是否已確認（確認的訂單不會被 MRP 修改）
     pub is_firm: bool,
+
+    /// 收貨倉庫
+    #[serde(default)]
+    pub warehouse_id: Option<String>,
 }
 
 impl Supply {
@@ -60,7 +77,9 @@ impl Supply {
             available_date,
             supply_type,
             source_ref: None,
+            source_document: None,
             is_firm: false,
+            warehouse_id: None,
         }
     }
 
@@ -70,12 +89,44 @@ impl Supply {
         self
     }
 
+    /// 建構器模式：設置結構化來源單據
+    pub fn with_source_document(mut self, document: SourceDocument) -> Self {
+        self.source_document = Some(document);
+        self
+    }
+
+    /// 建構器模式：設置收貨倉庫
+    pub fn with_warehouse_id(mut self, warehouse_id: String) -> Self {
+        self.warehouse_id = Some(warehouse_id);
+        self
+    }
+
     /// 建構器模式：設置為確認狀態
     pub fn as_firm(mut self) -> Self {
         self.is_firm = true;
         self
     }
 
+    /// 建構器模式：標記為引擎產生的計劃供應
+    pub fn with_engine_tag(mut self, order_id: Uuid) -> Self {
+        self.source_ref = Some(format!("{}{}", ENGINE_SUPPLY_TAG, order_id));
+        self
+    }
+
+    /// 產生此供應的計劃訂單ID（非引擎標記的供應為 None）
+    pub fn engine_order_id(&self) -> Option<Uuid> {
+        self.source_ref
+            .as_deref()?
+            .strip_prefix(ENGINE_SUPPLY_TAG)?
+            .parse()
+            .ok()
+    }
+
+    /// 檢查是否為前次計算產生、未經確認的計劃供應（重新計算時應由新計劃取代）
+    pub fn is_engine_planned(&self) -> bool {
+        self.is_adjustable() && self.engine_order_id().is_some()
+    }
+
     /// 檢查是否為計劃供應（MRP 生成）
     pub fn is_planned(&self) -> bool {
         self.supply_type == SupplyType::PlannedOrder
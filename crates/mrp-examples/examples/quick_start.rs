@@ -0,0 +1,29 @@
+//! 快速上手：產生範例資料集並執行 MRP
+//!
+//! ```bash
+//! cargo run -p mrp-examples --example quick_start
+//! ```
+
+use mrp_examples::{DemandPattern, SampleDataset, SampleSpec};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let spec = SampleSpec::new().with_demand_pattern(DemandPattern::Seasonal);
+    let dataset = SampleDataset::generate(&spec);
+    println!(
+        "範例資料：{} 個物料、{} 筆需求、{} 筆在途供應",
+        dataset.item_count(),
+        dataset.demands.len(),
+        dataset.supplies.len()
+    );
+
+    let result = dataset.run()?;
+    println!("計劃訂單: {}", result.planned_orders.len());
+    for order in result.planned_orders.iter().take(10) {
+        println!(
+            "  {} 數量 {} 下單 {} 到貨 {}",
+            order.component_id, order.quantity, order.order_date, order.required_date
+        );
+    }
+    println!("警告: {}", result.warnings.len());
+    Ok(())
+}
@@ -0,0 +1,289 @@
+//! 範例資料集產生
+
+use bom_core::{BomItem, ComponentId};
+use bom_graph::BomGraph;
+use chrono::{Datelike, Duration, NaiveDate};
+use mrp_calc::{MrpCalculator, MrpResult};
+use mrp_core::{
+    Demand, DemandType, Inventory, LotSizingRule, MrpConfig, ProcurementType, Supply, SupplyType,
+    WorkCalendar,
+};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::spec::{DemandPattern, SampleSpec};
+
+/// 範例資料集（可直接交給計算器）
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SampleDataset {
+    /// 多階 BOM
+    pub bom_graph: Arc<BomGraph>,
+
+    /// 物料配置（成品與半成品自製、最後一階原料採購）
+    pub configs: HashMap<String, MrpConfig>,
+
+    /// 工廠日曆（週一到週五，含一天國定假日）
+    pub calendar: WorkCalendar,
+
+    /// 成品需求（前兩週為銷售訂單，之後為預測）
+    pub demands: Vec<Demand>,
+
+    /// 原料在途採購單
+    pub supplies: Vec<Supply>,
+
+    /// 期初庫存
+    pub inventories: Vec<Inventory>,
+}
+
+impl SampleDataset {
+    /// 依規格產生資料集
+    ///
+    /// 第 k 階的物料數約為上一階 × 每父件子件數 / 2，各父件從下一階隨機挑選子件，
+    /// 因此共用件隨階數增加。
+    pub fn generate(spec: &SampleSpec) -> Self {
+        let mut rng = StdRng::seed_from_u64(spec.seed);
+
+        // 各階物料
+        let mut levels: Vec<Vec<String>> = vec![(1..=spec.end_items)
+            .map(|i| format!("FG-{:03}", i))
+            .collect()];
+        for level in 1..=spec.levels {
+            let parents = levels[level - 1].len();
+            let size = (parents * spec.children_per_parent)
+                .div_ceil(2)
+                .max(spec.children_per_parent);
+            let prefix = if level == spec.levels {
+                "RM".to_string()
+            } else {
+                format!("SA{}", level)
+            };
+            levels.push((1..=size).map(|i| format!("{}-{:03}", prefix, i)).collect());
+        }
+
+        // BOM
+        let mut bom_graph = BomGraph::new();
+        for level in 1..levels.len() {
+            let (parents, children) = (&levels[level - 1], &levels[level]);
+            for parent_id in parents {
+                let picked = sample(&mut rng, children.len(), spec.children_per_parent);
+                for (sequence, index) in picked.into_iter().enumerate() {
+                    let item = BomItem {
+                        id: Self::seeded_id(&mut rng),
+                        parent_id: ComponentId::new(parent_id.as_str()),
+                        child_id: ComponentId::new(children[index].as_str()),
+                        quantity: Decimal::from(rng.gen_range(1..=4)),
+                        scrap_factor: Decimal::ZERO,
+                        sequence: (sequence as u32 + 1) * 10,
+                        operation_sequence: None,
+                        is_phantom: false,
+                        effective_from: None,
+                        effective_to: None,
+                        alternative_group: None,
+                        alternative_priority: None,
+                        reference_designator: None,
+                        position: None,
+                        notes: None,
+                        version: 1,
+                    };
+                    bom_graph.add_bom_item(item).expect("範例 BOM 不含循環");
+                }
+            }
+        }
+
+        // 物料配置與期初庫存
+        let mut configs = HashMap::new();
+        let mut inventories = Vec::new();
+        let mut supplies = Vec::new();
+        for (level, items) in levels.iter().enumerate() {
+            let purchased = level == spec.levels;
+            for item_id in items {
+                let config = if purchased {
+                    let mut config = MrpConfig::new(
+                        item_id.clone(),
+                        rng.gen_range(3..=15),
+                        ProcurementType::Buy,
+                    )
+                    .with_supplier_id(format!("SUP-{}", rng.gen_range(1..=3)))
+                    .with_unit_cost(Decimal::new(rng.gen_range(50..=5000), 2));
+                    if rng.gen_bool(1.0 / 3.0) {
+                        config =
+                            config.with_minimum_order_qty(Decimal::from(rng.gen_range(1..=4) * 50));
+                    }
+                    if rng.gen_bool(0.25) {
+                        config =
+                            config.with_safety_stock(Decimal::from(rng.gen_range(2..=10) * 10));
+                    }
+                    config
+                } else {
+                    let config = MrpConfig::new(
+                        item_id.clone(),
+                        rng.gen_range(2..=5),
+                        ProcurementType::Make,
+                    )
+                    .with_unit_cost(Decimal::new(rng.gen_range(5000..=50000), 2));
+                    if level > 0 && rng.gen_bool(0.3) {
+                        config
+                            .with_lot_sizing_rule(LotSizingRule::FixedOrderQuantity)
+                            .with_fixed_lot_size(Decimal::from(rng.gen_range(1..=5) * 100))
+                    } else {
+                        config
+                    }
+                };
+                configs.insert(item_id.clone(), config);
+
+                let on_hand = if purchased {
+                    rng.gen_range(0..=500)
+                } else {
+                    rng.gen_range(0..=100)
+                };
+                inventories.push(Inventory::new(
+                    item_id.clone(),
+                    Decimal::from(on_hand),
+                    Decimal::ZERO,
+                ));
+
+                if purchased && rng.gen_bool(1.0 / 3.0) {
+                    let mut supply = Supply::new(
+                        item_id.clone(),
+                        Decimal::from(rng.gen_range(1..=8) * 50),
+                        spec.start_date + Duration::days(rng.gen_range(1..=10)),
+                        SupplyType::PurchaseOrder,
+                    )
+                    .as_firm();
+                    supply.id = Self::seeded_id(&mut rng);
+                    supplies.push(supply);
+                }
+            }
+        }
+
+        // 成品需求：每週五到期
+        let mut demands = Vec::new();
+        for item_id in &levels[0] {
+            let base = rng.gen_range(50..=200) as f64;
+            for week in 0..spec.horizon_weeks {
+                let quantity = match spec.demand_pattern {
+                    DemandPattern::Steady => base * rng.gen_range(0.9..=1.1),
+                    DemandPattern::Seasonal => {
+                        let phase = 2.0 * std::f64::consts::PI * f64::from(week) / 13.0;
+                        base * (1.0 + 0.5 * phase.sin())
+                    }
+                    DemandPattern::Lumpy => {
+                        if rng.gen_bool(0.6) {
+                            0.0
+                        } else {
+                            base * 2.5
+                        }
+                    }
+                }
+                .round() as i64;
+                if quantity <= 0 {
+                    continue;
+                }
+                let demand_type = if week < 2 {
+                    DemandType::SalesOrder
+                } else {
+                    DemandType::Forecast
+                };
+                let mut demand = Demand::new(
+                    item_id.clone(),
+                    Decimal::from(quantity),
+                    Self::friday(spec.start_date, week),
+                    demand_type,
+                );
+                demand.id = Self::seeded_id(&mut rng);
+                demands.push(demand);
+            }
+        }
+
+        let calendar = WorkCalendar::new("PLANT".to_string()).with_holidays(vec![
+            spec.start_date + Duration::weeks(4) + Duration::days(2),
+        ]);
+
+        Self {
+            bom_graph: Arc::new(bom_graph),
+            configs,
+            calendar,
+            demands,
+            supplies,
+            inventories,
+        }
+    }
+
+    /// 物料數
+    pub fn item_count(&self) -> usize {
+        self.configs.len()
+    }
+
+    /// 以資料集建立計算器（共用同一份 BOM 圖）
+    pub fn calculator(&self) -> MrpCalculator {
+        MrpCalculator::with_shared_bom(
+            Arc::clone(&self.bom_graph),
+            self.configs.clone(),
+            self.calendar.clone(),
+        )
+    }
+
+    /// 以預設計劃政策計算資料集
+    pub fn run(&self) -> mrp_core::Result<MrpResult> {
+        self.calculator().calculate(
+            self.demands.clone(),
+            self.supplies.clone(),
+            self.inventories.clone(),
+        )
+    }
+
+    /// 由種子亂數產生的識別碼（`Uuid::new_v4` 取自系統亂數，會破壞可重現性）
+    fn seeded_id(rng: &mut StdRng) -> Uuid {
+        uuid::Builder::from_random_bytes(rng.gen()).into_uuid()
+    }
+
+    /// 起始日後第 `week` 週的週五
+    fn friday(start_date: NaiveDate, week: u32) -> NaiveDate {
+        let to_friday = (4 + 7 - start_date.weekday().num_days_from_monday() as i64) % 7;
+        start_date + Duration::days(to_friday) + Duration::weeks(i64::from(week))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_is_reproducible_and_plannable() {
+        let spec = SampleSpec::small().with_demand_pattern(DemandPattern::Seasonal);
+
+        let dataset = SampleDataset::generate(&spec);
+        let again = SampleDataset::generate(&spec);
+
+        // 2 個成品 → 2 個半成品 → 2 個原料
+        assert_eq!(dataset.item_count(), 6);
+        assert_eq!(dataset.bom_graph.arena().edge_count(), 8);
+        assert_eq!(dataset.demands.len(), 24);
+        let demands = |d: &SampleDataset| -> Vec<(Uuid, Decimal)> {
+            d.demands
+                .iter()
+                .map(|demand| (demand.id, demand.quantity))
+                .collect()
+        };
+        assert_eq!(demands(&dataset), demands(&again));
+        let supply_ids =
+            |d: &SampleDataset| -> Vec<Uuid> { d.supplies.iter().map(|s| s.id).collect() };
+        assert_eq!(supply_ids(&dataset), supply_ids(&again));
+        assert_eq!(
+            dataset.demands[0].required_date,
+            NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()
+        );
+
+        let result = dataset.run().unwrap();
+        assert!(result
+            .planned_orders
+            .iter()
+            .any(|o| o.component_id.starts_with("RM-")));
+    }
+}
@@ -0,0 +1,11 @@
+//! # MRP Examples
+//!
+//! 範例資料產生器：依可設定的規模以程式產生多階 BOM、工作日曆、需求型態、庫存與在途供應，
+//! 供範例程式、效能基準與新使用者上手使用。相同設定（含亂數種子）產生相同的資料。
+
+pub mod dataset;
+pub mod spec;
+
+// Re-export 主要類型
+pub use dataset::SampleDataset;
+pub use spec::{DemandPattern, SampleSpec};
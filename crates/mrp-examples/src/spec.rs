@@ -0,0 +1,130 @@
+//! 範例資料規格
+
+use chrono::NaiveDate;
+
+/// 成品需求型態
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DemandPattern {
+    /// 穩定需求（每週在基準量上下 10% 波動）
+    #[default]
+    Steady,
+
+    /// 季節性需求（以 13 週為一個週期起伏 ±50%）
+    Seasonal,
+
+    /// 間歇需求（約六成的週沒有需求，有需求時為基準量的 2.5 倍）
+    Lumpy,
+}
+
+/// 範例資料規格
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SampleSpec {
+    /// 成品數
+    pub end_items: usize,
+
+    /// 成品以下的 BOM 階數（最後一階為採購原料）
+    pub levels: usize,
+
+    /// 每個父件的子件數
+    pub children_per_parent: usize,
+
+    /// 需求期間（週）
+    pub horizon_weeks: u32,
+
+    /// 計劃起始日
+    pub start_date: NaiveDate,
+
+    /// 成品需求型態
+    pub demand_pattern: DemandPattern,
+
+    /// 亂數種子
+    pub seed: u64,
+}
+
+impl Default for SampleSpec {
+    fn default() -> Self {
+        Self {
+            end_items: 3,
+            levels: 2,
+            children_per_parent: 3,
+            horizon_weeks: 12,
+            start_date: NaiveDate::from_ymd_opt(2025, 1, 6).expect("有效日期"),
+            demand_pattern: DemandPattern::default(),
+            seed: 42,
+        }
+    }
+}
+
+impl SampleSpec {
+    /// 創建預設規格（3 個成品、2 階 BOM、12 週需求）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 上手用的小型資料集（2 個成品、2 階 BOM）
+    pub fn small() -> Self {
+        Self::new().with_end_items(2).with_children_per_parent(2)
+    }
+
+    /// 中型資料集（20 個成品、4 階 BOM、26 週需求）
+    pub fn medium() -> Self {
+        Self::new()
+            .with_end_items(20)
+            .with_levels(4)
+            .with_children_per_parent(4)
+            .with_horizon_weeks(26)
+    }
+
+    /// 大型資料集（200 個成品、6 階 BOM、52 週需求；壓力測試用）
+    pub fn large() -> Self {
+        Self::new()
+            .with_end_items(200)
+            .with_levels(6)
+            .with_children_per_parent(5)
+            .with_horizon_weeks(52)
+    }
+
+    /// 建構器模式：設置成品數
+    pub fn with_end_items(mut self, end_items: usize) -> Self {
+        self.end_items = end_items.max(1);
+        self
+    }
+
+    /// 建構器模式：設置成品以下的 BOM 階數
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        self.levels = levels.max(1);
+        self
+    }
+
+    /// 建構器模式：設置每個父件的子件數
+    pub fn with_children_per_parent(mut self, children: usize) -> Self {
+        self.children_per_parent = children.max(1);
+        self
+    }
+
+    /// 建構器模式：設置需求期間（週）
+    pub fn with_horizon_weeks(mut self, weeks: u32) -> Self {
+        self.horizon_weeks = weeks.max(1);
+        self
+    }
+
+    /// 建構器模式：設置計劃起始日
+    pub fn with_start_date(mut self, start_date: NaiveDate) -> Self {
+        self.start_date = start_date;
+        self
+    }
+
+    /// 建構器模式：設置成品需求型態
+    pub fn with_demand_pattern(mut self, pattern: DemandPattern) -> Self {
+        self.demand_pattern = pattern;
+        self
+    }
+
+    /// 建構器模式：設置亂數種子
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
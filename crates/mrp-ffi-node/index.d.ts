@@ -0,0 +1,86 @@
+/* tslint:disable */
+/* eslint-disable */
+
+/* 由 napi-rs 產生；修改 Rust 綁定後以 `npm run build` 重新產生 */
+
+/** 物料配置 */
+export interface MrpConfigInput {
+  componentId: string
+  leadTimeDays: number
+  /** "Make"（預設）、"Buy" 或 "Transfer" */
+  procurementType?: string
+  /** "LotForLot"（預設）、"FOQ"、"EOQ"、"POQ" 或 "MinMax" */
+  lotSizingRule?: string
+  fixedLotSize?: number
+  minimumOrderQty?: number
+  maximumOrderQty?: number
+  orderMultiple?: number
+  safetyStock?: number
+  planningHorizonDays?: number
+  allowNegativeInventory?: boolean
+}
+/** BOM 行 */
+export interface BomLineInput {
+  parentId: string
+  childId: string
+  quantity: number
+  /** 損耗率（0.05 表示 5%） */
+  scrapFactor?: number
+}
+/** 需求 */
+export interface DemandInput {
+  componentId: string
+  quantity: number
+  requiredDate: string
+  /** "SalesOrder"（預設）、"Forecast" 或 "SafetyStock" */
+  demandType?: string
+  sourceRef?: string
+}
+/** 供應 */
+export interface SupplyInput {
+  componentId: string
+  quantity: number
+  availableDate: string
+  /** "PurchaseOrder"（預設）、"WorkOrder"、"Transfer" 或 "OnHand" */
+  supplyType?: string
+}
+/** 庫存 */
+export interface InventoryInput {
+  componentId: string
+  onHandQty: number
+  safetyStock?: number
+}
+/** 計劃訂單 */
+export interface PlannedOrderOutput {
+  id: string
+  componentId: string
+  quantity: number
+  requiredDate: string
+  orderDate: string
+  /** "Purchase"、"Production" 或 "Transfer" */
+  orderType: string
+  sourceId?: string
+}
+/** 警告 */
+export interface WarningOutput {
+  componentId: string
+  message: string
+  /** "Info"、"Warning" 或 "Error" */
+  severity: string
+}
+/** 計算結果 */
+export interface MrpResultOutput {
+  plannedOrders: Array<PlannedOrderOutput>
+  warnings: Array<WarningOutput>
+  calculationTimeMs?: number
+}
+/** Node.js MRP 計算器 */
+export declare class MrpCalculator {
+  constructor()
+  /** 設置物料配置（同一物料重複設置時覆寫） */
+  setConfig(config: MrpConfigInput): void
+  /** 添加 BOM 行 */
+  addBomLine(line: BomLineInput): void
+  /** 執行 MRP 計算 */
+  calculate(demands: Array<DemandInput>, supplies?: Array<SupplyInput> | undefined | null, inventories?: Array<InventoryInput> | undefined | null): MrpResultOutput
+}
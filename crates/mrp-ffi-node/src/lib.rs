@@ -0,0 +1,5 @@
+//! # MRP FFI (Node.js)
+//!
+//! Node.js 綁定層（napi-rs）；TypeScript 型別定義見 `index.d.ts`
+
+pub mod node;
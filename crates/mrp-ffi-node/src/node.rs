@@ -0,0 +1,426 @@
+//! Node.js 綁定實現
+//!
+//! 數量以 JavaScript `number` 傳遞，日期為 `YYYY-MM-DD` 字串；
+//! 物件欄位在 JavaScript 端為 camelCase。
+
+use bom_graph::BomGraph;
+use chrono::NaiveDate;
+use mrp_calc::{MrpCalculator, MrpResult, WarningSeverity};
+use mrp_core::{
+    Demand, DemandType, Inventory, LotSizingRule, MrpConfig, MrpError, PlannedOrderType,
+    ProcurementType, Supply, SupplyType, WorkCalendar,
+};
+use napi_derive::napi;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// 物料配置
+#[napi(object)]
+pub struct MrpConfigInput {
+    pub component_id: String,
+    pub lead_time_days: u32,
+    /// "Make"（預設）、"Buy" 或 "Transfer"
+    pub procurement_type: Option<String>,
+    /// "LotForLot"（預設）、"FOQ"、"EOQ"、"POQ" 或 "MinMax"
+    pub lot_sizing_rule: Option<String>,
+    pub fixed_lot_size: Option<f64>,
+    pub minimum_order_qty: Option<f64>,
+    pub maximum_order_qty: Option<f64>,
+    pub order_multiple: Option<f64>,
+    pub safety_stock: Option<f64>,
+    pub planning_horizon_days: Option<u32>,
+    pub allow_negative_inventory: Option<bool>,
+}
+
+/// BOM 行
+#[napi(object)]
+pub struct BomLineInput {
+    pub parent_id: String,
+    pub child_id: String,
+    pub quantity: f64,
+    /// 損耗率（0.05 表示 5%）
+    pub scrap_factor: Option<f64>,
+}
+
+/// 需求
+#[napi(object)]
+pub struct DemandInput {
+    pub component_id: String,
+    pub quantity: f64,
+    pub required_date: String,
+    /// "SalesOrder"（預設）、"Forecast" 或 "SafetyStock"
+    pub demand_type: Option<String>,
+    pub source_ref: Option<String>,
+}
+
+/// 供應
+#[napi(object)]
+pub struct SupplyInput {
+    pub component_id: String,
+    pub quantity: f64,
+    pub available_date: String,
+    /// "PurchaseOrder"（預設）、"WorkOrder"、"Transfer" 或 "OnHand"
+    pub supply_type: Option<String>,
+}
+
+/// 庫存
+#[napi(object)]
+pub struct InventoryInput {
+    pub component_id: String,
+    pub on_hand_qty: f64,
+    pub safety_stock: Option<f64>,
+}
+
+/// 計劃訂單
+#[napi(object)]
+pub struct PlannedOrderOutput {
+    pub id: String,
+    pub component_id: String,
+    pub quantity: f64,
+    pub required_date: String,
+    pub order_date: String,
+    /// "Purchase"、"Production" 或 "Transfer"
+    pub order_type: String,
+    pub source_id: Option<String>,
+}
+
+/// 警告
+#[napi(object)]
+pub struct WarningOutput {
+    pub component_id: String,
+    pub message: String,
+    /// "Info"、"Warning" 或 "Error"
+    pub severity: String,
+}
+
+/// 計算結果
+#[napi(object)]
+pub struct MrpResultOutput {
+    pub planned_orders: Vec<PlannedOrderOutput>,
+    pub warnings: Vec<WarningOutput>,
+    pub calculation_time_ms: Option<f64>,
+}
+
+/// Node.js MRP 計算器
+#[napi(js_name = "MrpCalculator")]
+pub struct NodeMrpCalculator {
+    configs: HashMap<String, MrpConfig>,
+    bom_lines: Vec<bom_core::BomItem>,
+}
+
+#[napi]
+impl NodeMrpCalculator {
+    #[napi(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            configs: HashMap::new(),
+            bom_lines: Vec::new(),
+        }
+    }
+
+    /// 設置物料配置（同一物料重複設置時覆寫）
+    #[napi]
+    pub fn set_config(&mut self, config: MrpConfigInput) -> napi::Result<()> {
+        let config = to_config(config).map_err(to_js_error)?;
+        self.configs.insert(config.component_id.clone(), config);
+        Ok(())
+    }
+
+    /// 添加 BOM 行
+    #[napi]
+    pub fn add_bom_line(&mut self, line: BomLineInput) -> napi::Result<()> {
+        self.bom_lines.push(to_bom_item(line).map_err(to_js_error)?);
+        Ok(())
+    }
+
+    /// 執行 MRP 計算
+    #[napi]
+    pub fn calculate(
+        &self,
+        demands: Vec<DemandInput>,
+        supplies: Option<Vec<SupplyInput>>,
+        inventories: Option<Vec<InventoryInput>>,
+    ) -> napi::Result<MrpResultOutput> {
+        let demands = demands
+            .into_iter()
+            .map(to_demand)
+            .collect::<mrp_core::Result<Vec<_>>>()
+            .map_err(to_js_error)?;
+        let supplies = supplies
+            .unwrap_or_default()
+            .into_iter()
+            .map(to_supply)
+            .collect::<mrp_core::Result<Vec<_>>>()
+            .map_err(to_js_error)?;
+        let inventories = inventories
+            .unwrap_or_default()
+            .into_iter()
+            .map(to_inventory)
+            .collect::<mrp_core::Result<Vec<_>>>()
+            .map_err(to_js_error)?;
+
+        let mut bom_graph = BomGraph::new();
+        for item in &self.bom_lines {
+            bom_graph
+                .add_bom_item(item.clone())
+                .map_err(|e| napi::Error::from_reason(format!("Invalid BOM line: {:?}", e)))?;
+        }
+
+        MrpCalculator::new(bom_graph, self.configs.clone(), WorkCalendar::default())
+            .calculate(demands, supplies, inventories)
+            .map(to_output)
+            .map_err(to_js_error)
+    }
+}
+
+fn to_js_error(error: MrpError) -> napi::Error {
+    napi::Error::from_reason(error.to_string())
+}
+
+fn decimal(value: f64, field: &str) -> mrp_core::Result<Decimal> {
+    Decimal::try_from(value)
+        .map_err(|_| MrpError::InvalidConfig(format!("Invalid {}: {}", field, value)))
+}
+
+fn date(value: &str) -> mrp_core::Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| MrpError::InvalidDate(value.to_string()))
+}
+
+fn to_config(input: MrpConfigInput) -> mrp_core::Result<MrpConfig> {
+    let procurement_type = match input.procurement_type.as_deref().unwrap_or("Make") {
+        "Make" => ProcurementType::Make,
+        "Buy" => ProcurementType::Buy,
+        "Transfer" => ProcurementType::Transfer,
+        other => {
+            return Err(MrpError::InvalidConfig(format!(
+                "Invalid procurementType: {}, must be 'Make', 'Buy', or 'Transfer'",
+                other
+            )))
+        }
+    };
+    let lot_sizing_rule = match input.lot_sizing_rule.as_deref().unwrap_or("LotForLot") {
+        "LotForLot" => LotSizingRule::LotForLot,
+        "FOQ" | "FixedOrderQuantity" => LotSizingRule::FixedOrderQuantity,
+        "EOQ" | "EconomicOrderQuantity" => LotSizingRule::EconomicOrderQuantity,
+        "POQ" | "PeriodOrderQuantity" => LotSizingRule::PeriodOrderQuantity,
+        "MinMax" => LotSizingRule::MinMax,
+        other => {
+            return Err(MrpError::InvalidConfig(format!(
+                "Invalid lotSizingRule: {}",
+                other
+            )))
+        }
+    };
+
+    let mut config = MrpConfig::new(input.component_id, input.lead_time_days, procurement_type)
+        .with_lot_sizing_rule(lot_sizing_rule)
+        .with_allow_negative_inventory(input.allow_negative_inventory.unwrap_or(false));
+    if let Some(days) = input.planning_horizon_days {
+        config = config.with_planning_horizon(days);
+    }
+    if let Some(stock) = input.safety_stock {
+        config = config.with_safety_stock(decimal(stock, "safetyStock")?);
+    }
+    if let Some(size) = input.fixed_lot_size {
+        config = config.with_fixed_lot_size(decimal(size, "fixedLotSize")?);
+    }
+    if let Some(min) = input.minimum_order_qty {
+        config = config.with_minimum_order_qty(decimal(min, "minimumOrderQty")?);
+    }
+    if let Some(max) = input.maximum_order_qty {
+        config = config.with_maximum_order_qty(decimal(max, "maximumOrderQty")?);
+    }
+    if let Some(multiple) = input.order_multiple {
+        config = config.with_order_multiple(decimal(multiple, "orderMultiple")?);
+    }
+    Ok(config)
+}
+
+fn to_bom_item(input: BomLineInput) -> mrp_core::Result<bom_core::BomItem> {
+    Ok(bom_core::BomItem {
+        id: uuid::Uuid::new_v4(),
+        parent_id: bom_core::ComponentId::new(input.parent_id),
+        child_id: bom_core::ComponentId::new(input.child_id),
+        quantity: decimal(input.quantity, "quantity")?,
+        scrap_factor: decimal(input.scrap_factor.unwrap_or(0.0), "scrapFactor")?,
+        sequence: 10,
+        operation_sequence: None,
+        is_phantom: false,
+        effective_from: None,
+        effective_to: None,
+        alternative_group: None,
+        alternative_priority: None,
+        reference_designator: None,
+        position: None,
+        notes: None,
+        version: 1,
+    })
+}
+
+fn to_demand(input: DemandInput) -> mrp_core::Result<Demand> {
+    let demand_type = match input.demand_type.as_deref().unwrap_or("SalesOrder") {
+        "SalesOrder" => DemandType::SalesOrder,
+        "Forecast" => DemandType::Forecast,
+        "SafetyStock" => DemandType::SafetyStock,
+        other => {
+            return Err(MrpError::ImportError(format!(
+                "Invalid demandType: {}",
+                other
+            )))
+        }
+    };
+    let mut demand = Demand::new(
+        input.component_id,
+        decimal(input.quantity, "quantity")?,
+        date(&input.required_date)?,
+        demand_type,
+    );
+    if let Some(reference) = input.source_ref {
+        demand = demand.with_source_ref(reference);
+    }
+    Ok(demand)
+}
+
+fn to_supply(input: SupplyInput) -> mrp_core::Result<Supply> {
+    let supply_type = match input.supply_type.as_deref().unwrap_or("PurchaseOrder") {
+        "PurchaseOrder" => SupplyType::PurchaseOrder,
+        "WorkOrder" => SupplyType::WorkOrder,
+        "Transfer" => SupplyType::Transfer,
+        "OnHand" => SupplyType::OnHand,
+        other => {
+            return Err(MrpError::ImportError(format!(
+                "Invalid supplyType: {}",
+                other
+            )))
+        }
+    };
+    Ok(Supply::new(
+        input.component_id,
+        decimal(input.quantity, "quantity")?,
+        date(&input.available_date)?,
+        supply_type,
+    ))
+}
+
+fn to_inventory(input: InventoryInput) -> mrp_core::Result<Inventory> {
+    Ok(Inventory::new(
+        input.component_id,
+        decimal(input.on_hand_qty, "onHandQty")?,
+        decimal(input.safety_stock.unwrap_or(0.0), "safetyStock")?,
+    ))
+}
+
+fn to_output(result: MrpResult) -> MrpResultOutput {
+    use rust_decimal::prelude::ToPrimitive;
+
+    MrpResultOutput {
+        planned_orders: result
+            .planned_orders
+            .into_iter()
+            .map(|order| PlannedOrderOutput {
+                id: order.id.to_string(),
+                component_id: order.component_id,
+                quantity: order.quantity.to_f64().unwrap_or_default(),
+                required_date: order.required_date.to_string(),
+                order_date: order.order_date.to_string(),
+                order_type: match order.order_type {
+                    PlannedOrderType::Purchase => "Purchase",
+                    PlannedOrderType::Production => "Production",
+                    PlannedOrderType::Transfer => "Transfer",
+                    _ => "Other",
+                }
+                .to_string(),
+                source_id: order.source_id,
+            })
+            .collect(),
+        warnings: result
+            .warnings
+            .into_iter()
+            .map(|warning| WarningOutput {
+                component_id: warning.component_id,
+                message: warning.message,
+                severity: match warning.severity {
+                    WarningSeverity::Info => "Info",
+                    WarningSeverity::Warning => "Warning",
+                    _ => "Error",
+                }
+                .to_string(),
+            })
+            .collect(),
+        calculation_time_ms: result.calculation_time_ms.map(|ms| ms as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(component_id: &str, lead_time_days: u32, procurement_type: &str) -> MrpConfigInput {
+        MrpConfigInput {
+            component_id: component_id.to_string(),
+            lead_time_days,
+            procurement_type: Some(procurement_type.to_string()),
+            lot_sizing_rule: None,
+            fixed_lot_size: None,
+            minimum_order_qty: None,
+            maximum_order_qty: None,
+            order_multiple: None,
+            safety_stock: None,
+            planning_horizon_days: None,
+            allow_negative_inventory: None,
+        }
+    }
+
+    #[test]
+    fn test_calculate_from_js_objects() {
+        let mut calculator = NodeMrpCalculator::new();
+        calculator.set_config(config("BIKE", 2, "Make")).unwrap();
+        calculator.set_config(config("WHEEL", 3, "Buy")).unwrap();
+        calculator
+            .add_bom_line(BomLineInput {
+                parent_id: "BIKE".to_string(),
+                child_id: "WHEEL".to_string(),
+                quantity: 2.0,
+                scrap_factor: None,
+            })
+            .unwrap();
+        assert!(calculator.set_config(config("SEAT", 1, "Steal")).is_err());
+
+        let result = calculator
+            .calculate(
+                vec![DemandInput {
+                    component_id: "BIKE".to_string(),
+                    quantity: 10.0,
+                    required_date: "2025-11-20".to_string(),
+                    demand_type: None,
+                    source_ref: Some("SO-001".to_string()),
+                }],
+                None,
+                None,
+            )
+            .unwrap();
+
+        let wheel = result
+            .planned_orders
+            .iter()
+            .find(|o| o.component_id == "WHEEL")
+            .unwrap();
+        assert_eq!(wheel.quantity, 20.0);
+        assert_eq!(wheel.order_type, "Purchase");
+
+        let bad_date = calculator.calculate(
+            vec![DemandInput {
+                component_id: "BIKE".to_string(),
+                quantity: 1.0,
+                required_date: "20/11/2025".to_string(),
+                demand_type: None,
+                source_ref: None,
+            }],
+            None,
+            None,
+        );
+        assert!(bad_date.is_err());
+    }
+}
@@ -0,0 +1,423 @@
+//! UniFFI 綁定實現
+//!
+//! 數量以 `Double` 傳遞，日期為 `YYYY-MM-DD` 字串。
+
+use bom_graph::BomGraph;
+use chrono::NaiveDate;
+use mrp_calc::{AtpCalculator, MrpCalculator, MrpResult, WarningSeverity};
+use mrp_core::{
+    Demand, DemandType, Inventory, MrpConfig, MrpError, PlannedOrderType, ProcurementType, Supply,
+    SupplyType, WorkCalendar,
+};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// 綁定層錯誤
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[non_exhaustive]
+pub enum MrpFfiError {
+    /// 輸入資料無效
+    #[error("{message}")]
+    InvalidInput { message: String },
+
+    /// 計算失敗
+    #[error("{message}")]
+    Calculation { message: String },
+}
+
+impl From<MrpError> for MrpFfiError {
+    fn from(error: MrpError) -> Self {
+        match error {
+            MrpError::InvalidConfig(_) | MrpError::InvalidDate(_) | MrpError::ImportError(_) => {
+                Self::InvalidInput {
+                    message: error.to_string(),
+                }
+            }
+            _ => Self::Calculation {
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+/// 物料配置
+#[derive(uniffi::Record)]
+pub struct MrpConfigInput {
+    pub component_id: String,
+    pub lead_time_days: u32,
+    /// "Make"、"Buy" 或 "Transfer"
+    pub procurement_type: String,
+    #[uniffi(default = None)]
+    pub safety_stock: Option<f64>,
+    #[uniffi(default = None)]
+    pub minimum_order_qty: Option<f64>,
+    #[uniffi(default = None)]
+    pub order_multiple: Option<f64>,
+}
+
+/// BOM 行
+#[derive(uniffi::Record)]
+pub struct BomLineInput {
+    pub parent_id: String,
+    pub child_id: String,
+    pub quantity: f64,
+}
+
+/// 需求（已承諾的客戶訂單或預測）
+#[derive(uniffi::Record)]
+pub struct DemandInput {
+    pub component_id: String,
+    pub quantity: f64,
+    pub required_date: String,
+    /// "SalesOrder"、"Forecast" 或 "SafetyStock"（None 為 "SalesOrder"）
+    #[uniffi(default = None)]
+    pub demand_type: Option<String>,
+}
+
+/// 已排定供應
+#[derive(uniffi::Record)]
+pub struct SupplyInput {
+    pub component_id: String,
+    pub quantity: f64,
+    pub available_date: String,
+}
+
+/// 庫存
+#[derive(uniffi::Record)]
+pub struct InventoryInput {
+    pub component_id: String,
+    pub on_hand_qty: f64,
+}
+
+/// 計劃訂單
+#[derive(uniffi::Record)]
+pub struct PlannedOrderOutput {
+    pub component_id: String,
+    pub quantity: f64,
+    pub required_date: String,
+    pub order_date: String,
+    /// "Purchase"、"Production" 或 "Transfer"
+    pub order_type: String,
+}
+
+/// 計算結果
+#[derive(uniffi::Record)]
+pub struct MrpResultOutput {
+    pub planned_orders: Vec<PlannedOrderOutput>,
+    /// 錯誤與警告訊息（不含提示）
+    pub warnings: Vec<String>,
+}
+
+/// ATP 查詢
+#[derive(uniffi::Record)]
+pub struct AtpRequest {
+    pub component_id: String,
+    pub quantity: f64,
+    pub requested_date: String,
+    pub on_hand: f64,
+    pub supplies: Vec<SupplyInput>,
+    pub demands: Vec<DemandInput>,
+}
+
+/// ATP 回覆
+#[derive(uniffi::Record)]
+pub struct AtpResponse {
+    pub available_quantity: f64,
+    pub promised_quantity: f64,
+    /// 可全數承諾的最早日期（已排定供應都不足時為 None）
+    pub promise_date: Option<String>,
+}
+
+/// ATP 檢查：要求日期能否承諾指定數量
+#[uniffi::export]
+pub fn check_atp(request: AtpRequest) -> Result<AtpResponse, MrpFfiError> {
+    let supplies = request
+        .supplies
+        .into_iter()
+        .map(to_supply)
+        .collect::<mrp_core::Result<Vec<_>>>()?;
+    let demands = request
+        .demands
+        .into_iter()
+        .map(to_demand)
+        .collect::<mrp_core::Result<Vec<_>>>()?;
+
+    let check = AtpCalculator::check(
+        &request.component_id,
+        decimal(request.quantity, "quantity")?,
+        date(&request.requested_date)?,
+        decimal(request.on_hand, "on_hand")?,
+        &supplies,
+        &demands,
+    );
+    Ok(AtpResponse {
+        available_quantity: check.available_quantity.to_f64().unwrap_or_default(),
+        promised_quantity: check.promised_quantity.to_f64().unwrap_or_default(),
+        promise_date: check.promise_date.map(|d| d.to_string()),
+    })
+}
+
+/// MRP 引擎（執行緒安全，可由多個執行緒共用）
+#[derive(uniffi::Object)]
+pub struct MrpEngine {
+    configs: Mutex<HashMap<String, MrpConfig>>,
+    bom_lines: Mutex<Vec<bom_core::BomItem>>,
+}
+
+#[uniffi::export]
+impl MrpEngine {
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            configs: Mutex::new(HashMap::new()),
+            bom_lines: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// 設置物料配置（同一物料重複設置時覆寫）
+    pub fn set_config(&self, config: MrpConfigInput) -> Result<(), MrpFfiError> {
+        let config = to_config(config)?;
+        self.configs
+            .lock()
+            .expect("配置鎖")
+            .insert(config.component_id.clone(), config);
+        Ok(())
+    }
+
+    /// 添加 BOM 行
+    pub fn add_bom_line(&self, line: BomLineInput) -> Result<(), MrpFfiError> {
+        let item = to_bom_item(line)?;
+        self.bom_lines.lock().expect("BOM 鎖").push(item);
+        Ok(())
+    }
+
+    /// 執行 MRP 計算（小範圍重算：只傳入相關物料的需求與供應）
+    pub fn calculate(
+        &self,
+        demands: Vec<DemandInput>,
+        supplies: Vec<SupplyInput>,
+        inventories: Vec<InventoryInput>,
+    ) -> Result<MrpResultOutput, MrpFfiError> {
+        let demands = demands
+            .into_iter()
+            .map(to_demand)
+            .collect::<mrp_core::Result<Vec<_>>>()?;
+        let supplies = supplies
+            .into_iter()
+            .map(to_supply)
+            .collect::<mrp_core::Result<Vec<_>>>()?;
+        let inventories = inventories
+            .into_iter()
+            .map(|input| {
+                Ok(Inventory::new(
+                    input.component_id,
+                    decimal(input.on_hand_qty, "on_hand_qty")?,
+                    Decimal::ZERO,
+                ))
+            })
+            .collect::<mrp_core::Result<Vec<_>>>()?;
+
+        let mut bom_graph = BomGraph::new();
+        for item in self.bom_lines.lock().expect("BOM 鎖").iter() {
+            bom_graph
+                .add_bom_item(item.clone())
+                .map_err(|e| MrpFfiError::InvalidInput {
+                    message: format!("Invalid BOM line: {:?}", e),
+                })?;
+        }
+        let configs = self.configs.lock().expect("配置鎖").clone();
+
+        let result = MrpCalculator::new(bom_graph, configs, WorkCalendar::default()).calculate(
+            demands,
+            supplies,
+            inventories,
+        )?;
+        Ok(to_output(result))
+    }
+}
+
+fn decimal(value: f64, field: &str) -> mrp_core::Result<Decimal> {
+    Decimal::try_from(value)
+        .map_err(|_| MrpError::InvalidConfig(format!("Invalid {}: {}", field, value)))
+}
+
+fn date(value: &str) -> mrp_core::Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| MrpError::InvalidDate(value.to_string()))
+}
+
+fn to_config(input: MrpConfigInput) -> mrp_core::Result<MrpConfig> {
+    let procurement_type = match input.procurement_type.as_str() {
+        "Make" => ProcurementType::Make,
+        "Buy" => ProcurementType::Buy,
+        "Transfer" => ProcurementType::Transfer,
+        other => {
+            return Err(MrpError::InvalidConfig(format!(
+                "Invalid procurement_type: {}, must be 'Make', 'Buy', or 'Transfer'",
+                other
+            )))
+        }
+    };
+    let mut config = MrpConfig::new(input.component_id, input.lead_time_days, procurement_type);
+    if let Some(stock) = input.safety_stock {
+        config = config.with_safety_stock(decimal(stock, "safety_stock")?);
+    }
+    if let Some(min) = input.minimum_order_qty {
+        config = config.with_minimum_order_qty(decimal(min, "minimum_order_qty")?);
+    }
+    if let Some(multiple) = input.order_multiple {
+        config = config.with_order_multiple(decimal(multiple, "order_multiple")?);
+    }
+    Ok(config)
+}
+
+fn to_bom_item(input: BomLineInput) -> mrp_core::Result<bom_core::BomItem> {
+    Ok(bom_core::BomItem {
+        id: uuid::Uuid::new_v4(),
+        parent_id: bom_core::ComponentId::new(input.parent_id),
+        child_id: bom_core::ComponentId::new(input.child_id),
+        quantity: decimal(input.quantity, "quantity")?,
+        scrap_factor: Decimal::ZERO,
+        sequence: 10,
+        operation_sequence: None,
+        is_phantom: false,
+        effective_from: None,
+        effective_to: None,
+        alternative_group: None,
+        alternative_priority: None,
+        reference_designator: None,
+        position: None,
+        notes: None,
+        version: 1,
+    })
+}
+
+fn to_demand(input: DemandInput) -> mrp_core::Result<Demand> {
+    let demand_type = match input.demand_type.as_deref().unwrap_or("SalesOrder") {
+        "SalesOrder" => DemandType::SalesOrder,
+        "Forecast" => DemandType::Forecast,
+        "SafetyStock" => DemandType::SafetyStock,
+        other => {
+            return Err(MrpError::ImportError(format!(
+                "Invalid demand_type: {}",
+                other
+            )))
+        }
+    };
+    Ok(Demand::new(
+        input.component_id,
+        decimal(input.quantity, "quantity")?,
+        date(&input.required_date)?,
+        demand_type,
+    ))
+}
+
+fn to_supply(input: SupplyInput) -> mrp_core::Result<Supply> {
+    Ok(Supply::new(
+        input.component_id,
+        decimal(input.quantity, "quantity")?,
+        date(&input.available_date)?,
+        SupplyType::PurchaseOrder,
+    ))
+}
+
+fn to_output(result: MrpResult) -> MrpResultOutput {
+    MrpResultOutput {
+        planned_orders: result
+            .planned_orders
+            .into_iter()
+            .map(|order| PlannedOrderOutput {
+                component_id: order.component_id,
+                quantity: order.quantity.to_f64().unwrap_or_default(),
+                required_date: order.required_date.to_string(),
+                order_date: order.order_date.to_string(),
+                order_type: match order.order_type {
+                    PlannedOrderType::Purchase => "Purchase",
+                    PlannedOrderType::Production => "Production",
+                    PlannedOrderType::Transfer => "Transfer",
+                    _ => "Other",
+                }
+                .to_string(),
+            })
+            .collect(),
+        warnings: result
+            .warnings
+            .into_iter()
+            .filter(|w| w.severity != WarningSeverity::Info)
+            .map(|w| format!("{}: {}", w.component_id, w.message))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_atp_and_replan() {
+        let response = check_atp(AtpRequest {
+            component_id: "PUMP".to_string(),
+            quantity: 25.0,
+            requested_date: "2025-11-05".to_string(),
+            on_hand: 40.0,
+            supplies: vec![SupplyInput {
+                component_id: "PUMP".to_string(),
+                quantity: 50.0,
+                available_date: "2025-11-10".to_string(),
+            }],
+            demands: vec![DemandInput {
+                component_id: "PUMP".to_string(),
+                quantity: 30.0,
+                required_date: "2025-11-04".to_string(),
+                demand_type: None,
+            }],
+        })
+        .unwrap();
+        assert_eq!(response.available_quantity, 10.0);
+        assert_eq!(response.promise_date.as_deref(), Some("2025-11-10"));
+
+        let engine = MrpEngine::new();
+        engine
+            .set_config(MrpConfigInput {
+                component_id: "PUMP".to_string(),
+                lead_time_days: 3,
+                procurement_type: "Buy".to_string(),
+                safety_stock: None,
+                minimum_order_qty: None,
+                order_multiple: None,
+            })
+            .unwrap();
+        let result = engine
+            .calculate(
+                vec![DemandInput {
+                    component_id: "PUMP".to_string(),
+                    quantity: 30.0,
+                    required_date: "2025-11-20".to_string(),
+                    demand_type: None,
+                }],
+                vec![],
+                vec![InventoryInput {
+                    component_id: "PUMP".to_string(),
+                    on_hand_qty: 10.0,
+                }],
+            )
+            .unwrap();
+        assert_eq!(result.planned_orders.len(), 1);
+        assert_eq!(result.planned_orders[0].quantity, 20.0);
+        assert_eq!(result.planned_orders[0].order_type, "Purchase");
+
+        assert!(matches!(
+            engine.set_config(MrpConfigInput {
+                component_id: "PUMP".to_string(),
+                lead_time_days: 3,
+                procurement_type: "Steal".to_string(),
+                safety_stock: None,
+                minimum_order_qty: None,
+                order_multiple: None,
+            }),
+            Err(MrpFfiError::InvalidInput { .. })
+        ));
+    }
+}
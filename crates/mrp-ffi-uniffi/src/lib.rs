@@ -0,0 +1,15 @@
+//! # MRP FFI (UniFFI)
+//!
+//! Kotlin/Java 綁定層（UniFFI），供 Android 倉儲 App 與 JVM 中介層直接呼叫 ATP 檢查與小範圍重算
+//!
+//! 產生 Kotlin 綁定：
+//!
+//! ```text
+//! cargo build -p mrp-ffi-uniffi --release
+//! cargo run -p mrp-ffi-uniffi --features cli --bin uniffi-bindgen -- \
+//!     generate --library target/release/libmrp_engine_uniffi.so --language kotlin --out-dir out
+//! ```
+
+uniffi::setup_scaffolding!();
+
+pub mod jvm;
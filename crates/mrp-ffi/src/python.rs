@@ -88,6 +88,12 @@ pub struct PyMrpConfig {
     pub planning_horizon_days: u32,
     #[pyo3(get, set)]
     pub allow_negative_inventory: bool,
+    #[pyo3(get, set)]
+    pub unit_cost: Option<f64>,
+    #[pyo3(get, set)]
+    pub ordering_cost: Option<f64>,
+    #[pyo3(get, set)]
+    pub annual_holding_cost_rate: Option<f64>,
 }
 
 #[pymethods]
@@ -112,6 +118,9 @@ impl PyMrpConfig {
             safety_stock: 0.0,
             planning_horizon_days: 90,
             allow_negative_inventory,
+            unit_cost: None,
+            ordering_cost: None,
+            annual_holding_cost_rate: None,
         }
     }
 }
@@ -167,6 +176,15 @@ impl PyMrpConfig {
         if let Some(multiple) = self.order_multiple {
             config = config.with_order_multiple(Decimal::try_from(multiple).unwrap_or_default());
         }
+        if let Some(cost) = self.unit_cost {
+            config = config.with_unit_cost(Decimal::try_from(cost).unwrap_or_default());
+        }
+        config.ordering_cost = self
+            .ordering_cost
+            .map(|cost| Decimal::try_from(cost).unwrap_or_default());
+        config.annual_holding_cost_rate = self
+            .annual_holding_cost_rate
+            .map(|rate| Decimal::try_from(rate).unwrap_or_default());
 
         Ok(config)
     }
@@ -1,5 +1,147 @@
 //! 產能規劃
 
+use chrono::{Duration, NaiveDate};
+use mrp_calc::bucketing::{BucketingCalculator, BucketingStrategy};
+use mrp_core::{CalendarRegistry, PlannedOrder, ResourceBill, WorkCalendar, WorkCenter};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use uuid::Uuid;
+
+use crate::OptimizationResult;
+
+/// 工作中心在單一時間桶的負荷
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct WorkCenterLoad {
+    /// 工作中心ID
+    pub work_center_id: String,
+
+    /// 時間桶起日
+    pub bucket_start: NaiveDate,
+
+    /// 時間桶迄日（不含）
+    pub bucket_end: NaiveDate,
+
+    /// 需求工時
+    pub required_hours: Decimal,
+
+    /// 可用工時
+    pub available_hours: Decimal,
+}
+
+impl WorkCenterLoad {
+    /// 是否超載
+    pub fn is_overloaded(&self) -> bool {
+        self.required_hours > self.available_hours
+    }
+
+    /// 超載工時（未超載時為零）
+    pub fn overload_hours(&self) -> Decimal {
+        (self.required_hours - self.available_hours).max(Decimal::ZERO)
+    }
+
+    /// 負荷率（百分比；無可用工時時為 None）
+    pub fn utilization(&self) -> Option<Decimal> {
+        (self.available_hours > Decimal::ZERO)
+            .then(|| self.required_hours / self.available_hours * Decimal::ONE_HUNDRED)
+    }
+}
+
+/// 產能負荷報告
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct LoadProfile {
+    /// 各工作中心逐期負荷（按工作中心、起日排序）
+    pub loads: Vec<WorkCenterLoad>,
+
+    /// 沒有資源清單而未計入負荷的生產訂單
+    pub unrouted_orders: Vec<Uuid>,
+
+    /// 資源清單引用但未定義的工作中心
+    pub unknown_work_centers: Vec<String>,
+}
+
+impl LoadProfile {
+    /// 超載的時間桶
+    pub fn overloads(&self) -> impl Iterator<Item = &WorkCenterLoad> {
+        self.loads.iter().filter(|load| load.is_overloaded())
+    }
+
+    /// 單一工作中心的逐期負荷
+    pub fn for_work_center<'a>(
+        &'a self,
+        work_center_id: &'a str,
+    ) -> impl Iterator<Item = &'a WorkCenterLoad> {
+        self.loads
+            .iter()
+            .filter(move |load| load.work_center_id == work_center_id)
+    }
+}
+
+/// 有限產能排程中被移動的訂單
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OrderShift {
+    /// 計劃訂單ID
+    pub order_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 原下單日期
+    pub original_order_date: NaiveDate,
+
+    /// 新下單日期
+    pub order_date: NaiveDate,
+
+    /// 移動天數（負數為提前）
+    pub shift_days: i64,
+}
+
+impl OrderShift {
+    /// 訂單可移動的天數範圍：提前到下單日不早於 `earliest_start`，
+    /// 延後到完工日不晚於原需要日期（`need_date`，未記錄時為完工日）
+    pub(crate) fn window(
+        order: &PlannedOrder,
+        earliest_start: NaiveDate,
+    ) -> std::ops::RangeInclusive<i64> {
+        let need_date = order.need_date.unwrap_or(order.required_date);
+        let latest = (need_date - order.required_date).num_days().max(0);
+        let earliest = (order.order_date - earliest_start).num_days().max(0);
+        -earliest..=latest
+    }
+
+    /// 移動訂單：下單日與完工日一起移動，並記錄原需要日期
+    pub(crate) fn apply(order: &mut PlannedOrder, days: i64) -> Self {
+        let original_order_date = order.order_date;
+        order.need_date = Some(order.need_date.unwrap_or(order.required_date));
+        order.order_date += Duration::days(days);
+        order.required_date += Duration::days(days);
+        Self {
+            order_id: order.id,
+            component_id: order.component_id.clone(),
+            original_order_date,
+            order_date: order.order_date,
+            shift_days: days,
+        }
+    }
+}
+
+impl fmt::Display for OrderShift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} 訂單 {} 下單日 {} → {}（{:+} 天）",
+            self.component_id,
+            self.order_id,
+            self.original_order_date,
+            self.order_date,
+            self.shift_days
+        )
+    }
+}
+
 /// 產能規劃器
 pub struct CapacityPlanner;
 
@@ -10,8 +152,517 @@ impl CapacityPlanner {
         true
     }
 
-    /// 產能平衡
-    pub fn balance_capacity() {
-        // TODO: 實現產能平衡
+    /// 有限產能排程：在訂單的寬裕時間內移動生產訂單，使工作中心負荷不超過產能
+    ///
+    /// 訂單按需要日期（未排程時為完工日）由早到晚依序排入，每張訂單依移動天數由近到遠
+    /// 嘗試，同距離時優先延後；下單日與完工日一起移動，負荷分攤方式與 [`Self::load_profile`] 相同。
+    /// 可移動的範圍：
+    /// - 提前：下單日不早於 `earliest_start`
+    /// - 延後：完工日不晚於原需要日期（`need_date`，未記錄時為原完工日，即不可延後）
+    /// - 移動後的下單日須為訂單用到的各工作中心的工作日
+    ///
+    /// 找不到可排入的位置時訂單留在原處，超載記入 `violations`（逐日負荷）。
+    /// 採購、調撥與沒有資源清單的訂單原樣保留。
+    pub fn balance_capacity(
+        orders: &[PlannedOrder],
+        bills: &HashMap<String, ResourceBill>,
+        work_centers: &[WorkCenter],
+        calendars: &CalendarRegistry,
+        earliest_start: NaiveDate,
+    ) -> mrp_core::Result<OptimizationResult> {
+        let centers: HashMap<&str, &WorkCenter> =
+            work_centers.iter().map(|wc| (wc.id.as_str(), wc)).collect();
+        let mut center_calendars: HashMap<&str, WorkCalendar> = HashMap::new();
+        for center in work_centers {
+            center_calendars.insert(center.id.as_str(), calendars.resolve(&center.calendar_id)?);
+        }
+
+        let mut optimized = orders.to_vec();
+        let mut sequence: Vec<usize> = (0..optimized.len())
+            .filter(|&i| {
+                optimized[i].is_production() && bills.contains_key(&optimized[i].component_id)
+            })
+            .collect();
+        sequence.sort_by_key(|&i| {
+            let order = &optimized[i];
+            (
+                order.need_date.unwrap_or(order.required_date),
+                order.order_date,
+                order.component_id.clone(),
+            )
+        });
+
+        // 工作中心 → 日期 → 已排入工時
+        let mut committed: HashMap<&str, BTreeMap<NaiveDate, Decimal>> = HashMap::new();
+        let mut shifts = Vec::new();
+        for index in sequence {
+            let order = &optimized[index];
+            let bill = &bills[&order.component_id];
+            let mut offsets: Vec<i64> = OrderShift::window(order, earliest_start)
+                .filter(|&offset| {
+                    let start = order.order_date + Duration::days(offset);
+                    offset == 0
+                        || bill.loads.iter().all(|load| {
+                            center_calendars
+                                .get(load.work_center_id.as_str())
+                                .map_or(true, |calendar| calendar.is_working_day(start))
+                        })
+                })
+                .collect();
+            offsets.sort_by_key(|offset| (offset.abs(), -offset));
+
+            let placement = |offset: i64| {
+                let start = order.order_date + Duration::days(offset);
+                let end = order.required_date + Duration::days(offset);
+                let mut entries = Vec::new();
+                for load in &bill.loads {
+                    let Some((&id, _)) = centers.get_key_value(load.work_center_id.as_str()) else {
+                        continue;
+                    };
+                    let lag = Duration::days(i64::from(load.offset_days));
+                    for (date, share) in Self::spread_hours(
+                        start - lag,
+                        end - lag,
+                        load.hours_for(order.quantity),
+                        &center_calendars[id],
+                    ) {
+                        entries.push((id, date, share));
+                    }
+                }
+                entries
+            };
+            let fits = |entries: &[(&str, NaiveDate, Decimal)]| {
+                entries.iter().all(|&(id, date, hours)| {
+                    let used = committed
+                        .get(id)
+                        .and_then(|by_date| by_date.get(&date))
+                        .copied()
+                        .unwrap_or(Decimal::ZERO);
+                    used + hours <= centers[id].available_hours(&center_calendars[id], date)
+                })
+            };
+
+            let offset = offsets
+                .into_iter()
+                .find(|&offset| fits(&placement(offset)))
+                .unwrap_or(0);
+            for (id, date, hours) in placement(offset) {
+                *committed.entry(id).or_default().entry(date).or_default() += hours;
+            }
+
+            if offset != 0 {
+                shifts.push(OrderShift::apply(&mut optimized[index], offset));
+            }
+        }
+
+        let violations: Vec<WorkCenterLoad> = Self::load_profile(
+            &optimized,
+            bills,
+            work_centers,
+            calendars,
+            BucketingStrategy::Daily,
+        )?
+        .overloads()
+        .cloned()
+        .collect();
+
+        let mut messages: Vec<String> = shifts.iter().map(ToString::to_string).collect();
+        messages.extend(violations.iter().map(|load| {
+            format!(
+                "工作中心 {} 於 {} 超載 {} 小時",
+                load.work_center_id,
+                load.bucket_start,
+                load.overload_hours()
+            )
+        }));
+
+        Ok(OptimizationResult {
+            feasible: violations.is_empty(),
+            optimized_orders: optimized,
+            messages,
+            shifts,
+            violations,
+        })
+    }
+
+    /// 產能需求計劃（CRP）：計劃生產訂單在各工作中心、各時間桶的需求與可用工時
+    ///
+    /// 每張生產訂單按資源清單計算各工作中心工時（含整備），平均分攤在下單日到完工日（不含）之間
+    /// 該工作中心的工作日上，有偏移天數的負荷整段提前；期間內沒有工作日時全部計入起日。
+    /// 時間桶自最早負荷日起按 `strategy` 切分，涵蓋到最晚完工日。
+    /// 工藝路線可用 [`ResourceBill::from_routings`] 轉成資源清單。
+    pub fn load_profile(
+        orders: &[PlannedOrder],
+        bills: &HashMap<String, ResourceBill>,
+        work_centers: &[WorkCenter],
+        calendars: &CalendarRegistry,
+        strategy: BucketingStrategy,
+    ) -> mrp_core::Result<LoadProfile> {
+        let mut profile = LoadProfile::default();
+        let production: Vec<&PlannedOrder> = orders.iter().filter(|o| o.is_production()).collect();
+        let (Some(start), Some(end)) = (
+            production.iter().map(|o| o.order_date).min(),
+            production.iter().map(|o| o.required_date).max(),
+        ) else {
+            return Ok(profile);
+        };
+
+        let centers: HashMap<&str, &WorkCenter> =
+            work_centers.iter().map(|wc| (wc.id.as_str(), wc)).collect();
+        let mut center_calendars: HashMap<&str, WorkCalendar> = HashMap::new();
+        for center in work_centers {
+            center_calendars.insert(center.id.as_str(), calendars.resolve(&center.calendar_id)?);
+        }
+
+        // 工作中心 → 日期 → 需求工時
+        let mut daily: BTreeMap<&str, BTreeMap<NaiveDate, Decimal>> = BTreeMap::new();
+        for order in production {
+            let Some(bill) = bills.get(&order.component_id) else {
+                profile.unrouted_orders.push(order.id);
+                continue;
+            };
+            for load in &bill.loads {
+                let Some((&id, _)) = centers.get_key_value(load.work_center_id.as_str()) else {
+                    if !profile.unknown_work_centers.contains(&load.work_center_id) {
+                        profile
+                            .unknown_work_centers
+                            .push(load.work_center_id.clone());
+                    }
+                    continue;
+                };
+                let lag = Duration::days(i64::from(load.offset_days));
+                let by_date = daily.entry(id).or_default();
+                for (date, share) in Self::spread_hours(
+                    order.order_date - lag,
+                    order.required_date - lag,
+                    load.hours_for(order.quantity),
+                    &center_calendars[id],
+                ) {
+                    *by_date.entry(date).or_default() += share;
+                }
+            }
+        }
+
+        let start = daily
+            .values()
+            .filter_map(|by_date| by_date.keys().next().copied())
+            .fold(start, NaiveDate::min);
+        let bounds = Self::bucket_bounds(start, end, strategy);
+
+        for (id, by_date) in daily {
+            let center = centers[id];
+            let calendar = &center_calendars[id];
+            for &(bucket_start, bucket_end) in &bounds {
+                let required_hours: Decimal = by_date
+                    .range(bucket_start..bucket_end)
+                    .map(|(_, hours)| *hours)
+                    .sum();
+                profile.loads.push(WorkCenterLoad {
+                    work_center_id: id.to_string(),
+                    bucket_start,
+                    bucket_end,
+                    required_hours,
+                    available_hours: center.available_hours_between(
+                        calendar,
+                        bucket_start,
+                        bucket_end,
+                    ),
+                });
+            }
+        }
+
+        Ok(profile)
+    }
+
+    /// 生產訂單的人工／機器成本合計（資源清單工時 × 工作中心每小時費率）
+    ///
+    /// 採購、調撥與沒有資源清單的訂單不計；資源清單用到沒有費率的工作中心時回傳錯誤。
+    pub fn production_cost(
+        orders: &[PlannedOrder],
+        bills: &HashMap<String, ResourceBill>,
+        rates: &HashMap<String, Decimal>,
+    ) -> mrp_core::Result<Decimal> {
+        orders
+            .iter()
+            .filter(|order| order.is_production())
+            .filter_map(|order| {
+                bills
+                    .get(&order.component_id)
+                    .map(|bill| bill.cost(order.quantity, rates))
+            })
+            .sum()
+    }
+
+    /// 自 `start` 起按 `strategy` 切分、涵蓋到 `end` 的時間桶（起日, 迄日不含）
+    pub(crate) fn bucket_bounds(
+        start: NaiveDate,
+        end: NaiveDate,
+        strategy: BucketingStrategy,
+    ) -> Vec<(NaiveDate, NaiveDate)> {
+        // 多切一段以取得最後一個時間桶的迄日（月桶為 30 天）
+        let starts = BucketingCalculator::create_buckets_by_strategy(
+            start,
+            end + Duration::days(31),
+            strategy,
+        );
+        starts
+            .windows(2)
+            .map(|w| (w[0], w[1]))
+            .take_while(|(bucket_start, _)| *bucket_start <= end)
+            .collect()
+    }
+
+    /// 將工時平均分攤在 `[start, end)` 的工作日上（沒有工作日時全部計入起日）
+    pub(crate) fn spread_hours(
+        start: NaiveDate,
+        end: NaiveDate,
+        hours: Decimal,
+        calendar: &WorkCalendar,
+    ) -> Vec<(NaiveDate, Decimal)> {
+        let days: Vec<NaiveDate> = start
+            .iter_days()
+            .take_while(|date| *date < end)
+            .filter(|date| calendar.is_working_day(*date))
+            .collect();
+        if days.is_empty() {
+            return vec![(start, hours)];
+        }
+        let share = hours / Decimal::from(days.len());
+        days.into_iter().map(|date| (date, share)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{Operation, PlannedOrderType, Routing};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_load_profile_flags_overloads() {
+        let mut calendars = CalendarRegistry::new();
+        calendars.register(WorkCalendar::new("PLANT".to_string()));
+        let work_centers = vec![WorkCenter::new(
+            "WELD".to_string(),
+            "PLANT".to_string(),
+            Decimal::from(8),
+        )];
+        let routings = HashMap::from([(
+            "FRAME".to_string(),
+            Routing::new("FRAME".to_string()).with_operation(Operation::new(
+                10,
+                "WELD".to_string(),
+                Decimal::from(2),
+                Decimal::ONE,
+            )),
+        )]);
+        let bills = ResourceBill::from_routings(routings.values());
+
+        // 11/3（週一）→ 11/5：2 個工作日，2 + 28 = 30 小時，每日 15 小時
+        // 11/10 → 11/12：2 + 8 = 10 小時，每日 5 小時
+        let orders = vec![
+            PlannedOrder::new(
+                "FRAME".to_string(),
+                Decimal::from(28),
+                date(5),
+                date(3),
+                PlannedOrderType::Production,
+            ),
+            PlannedOrder::new(
+                "FRAME".to_string(),
+                Decimal::from(8),
+                date(12),
+                date(10),
+                PlannedOrderType::Production,
+            ),
+            PlannedOrder::new(
+                "SEAT".to_string(),
+                Decimal::from(5),
+                date(12),
+                date(10),
+                PlannedOrderType::Production,
+            ),
+            PlannedOrder::new(
+                "BOLT".to_string(),
+                Decimal::from(500),
+                date(12),
+                date(5),
+                PlannedOrderType::Purchase,
+            ),
+        ];
+
+        let profile = CapacityPlanner::load_profile(
+            &orders,
+            &bills,
+            &work_centers,
+            &calendars,
+            BucketingStrategy::Weekly,
+        )
+        .unwrap();
+
+        assert_eq!(profile.unrouted_orders, vec![orders[2].id]);
+        let weld: Vec<&WorkCenterLoad> = profile.for_work_center("WELD").collect();
+        assert_eq!(weld.len(), 2);
+        assert_eq!(weld[0].bucket_start, date(3));
+        assert_eq!(weld[0].required_hours, Decimal::from(30));
+        assert_eq!(weld[0].available_hours, Decimal::from(40));
+        assert_eq!(weld[1].required_hours, Decimal::from(10));
+        assert!(profile.overloads().next().is_none());
+
+        let daily = CapacityPlanner::load_profile(
+            &orders,
+            &bills,
+            &work_centers,
+            &calendars,
+            BucketingStrategy::Daily,
+        )
+        .unwrap();
+        let overloaded: Vec<NaiveDate> = daily.overloads().map(|l| l.bucket_start).collect();
+        assert_eq!(overloaded, vec![date(3), date(4)]);
+        assert_eq!(
+            daily.overloads().next().unwrap().overload_hours(),
+            Decimal::from(7)
+        );
+
+        // 成本只計有資源清單的生產訂單：(30 + 10) 小時 × 50
+        let rates = HashMap::from([("WELD".to_string(), Decimal::from(50))]);
+        assert_eq!(
+            CapacityPlanner::production_cost(&orders, &bills, &rates).unwrap(),
+            Decimal::from(2000)
+        );
+    }
+
+    #[test]
+    fn test_balance_capacity_shifts_within_slack() {
+        let mut calendars = CalendarRegistry::new();
+        calendars.register(WorkCalendar::new("PLANT".to_string()));
+        let work_centers = vec![WorkCenter::new(
+            "WELD".to_string(),
+            "PLANT".to_string(),
+            Decimal::from(8),
+        )];
+        let routings = HashMap::from([
+            (
+                "FRAME".to_string(),
+                Routing::new("FRAME".to_string()).with_operation(Operation::new(
+                    10,
+                    "WELD".to_string(),
+                    Decimal::from(2),
+                    Decimal::ONE,
+                )),
+            ),
+            (
+                "GATE".to_string(),
+                Routing::new("GATE".to_string()).with_operation(Operation::new(
+                    10,
+                    "WELD".to_string(),
+                    Decimal::ZERO,
+                    Decimal::ONE,
+                )),
+            ),
+        ]);
+        let bills = ResourceBill::from_routings(routings.values());
+
+        // 每張 FRAME 訂單 8 小時，都排在 11/5（週三）；GATE 24 小時怎麼移都放不下
+        let frame = |need_date: Option<NaiveDate>| {
+            let mut order = PlannedOrder::new(
+                "FRAME".to_string(),
+                Decimal::from(6),
+                date(6),
+                date(5),
+                PlannedOrderType::Production,
+            );
+            order.need_date = need_date;
+            order
+        };
+        let orders = vec![
+            frame(None),
+            frame(None),
+            frame(Some(date(7))),
+            PlannedOrder::new(
+                "GATE".to_string(),
+                Decimal::from(24),
+                date(6),
+                date(5),
+                PlannedOrderType::Production,
+            ),
+        ];
+
+        let result =
+            CapacityPlanner::balance_capacity(&orders, &bills, &work_centers, &calendars, date(3))
+                .unwrap();
+
+        let order_dates: Vec<NaiveDate> = result
+            .optimized_orders
+            .iter()
+            .map(|o| o.order_date)
+            .collect();
+        // 第二張提前一天，有寬裕時間的第三張延後一天，GATE 留在原處
+        assert_eq!(order_dates, vec![date(5), date(4), date(6), date(5)]);
+        assert_eq!(result.shifts.len(), 2);
+        assert_eq!(result.shifts[0].shift_days, -1);
+        assert_eq!(result.optimized_orders[1].need_date, Some(date(6)));
+        assert!(!result.optimized_orders[2].is_late());
+
+        assert!(!result.feasible);
+        assert_eq!(result.violations.len(), 1);
+        assert_eq!(result.violations[0].bucket_start, date(5));
+        assert_eq!(result.violations[0].overload_hours(), Decimal::from(24));
+    }
+
+    #[test]
+    fn test_balance_capacity_shifts_onto_working_days() {
+        let mut calendars = CalendarRegistry::new();
+        calendars.register(WorkCalendar::new("PLANT".to_string()));
+        let work_centers = vec![WorkCenter::new(
+            "WELD".to_string(),
+            "PLANT".to_string(),
+            Decimal::from(8),
+        )];
+        let routings = HashMap::from([(
+            "FRAME".to_string(),
+            Routing::new("FRAME".to_string()).with_operation(Operation::new(
+                10,
+                "WELD".to_string(),
+                Decimal::ZERO,
+                Decimal::ONE,
+            )),
+        )]);
+        let bills = ResourceBill::from_routings(routings.values());
+
+        // 第一張佔滿 11/7（週五）；第二張 11/6 → 11/8 可延後到 11/12
+        let first = PlannedOrder::new(
+            "FRAME".to_string(),
+            Decimal::from(8),
+            date(8),
+            date(7),
+            PlannedOrderType::Production,
+        );
+        let mut second = PlannedOrder::new(
+            "FRAME".to_string(),
+            Decimal::from(8),
+            date(8),
+            date(6),
+            PlannedOrderType::Production,
+        );
+        second.need_date = Some(date(12));
+
+        let result = CapacityPlanner::balance_capacity(
+            &[first, second],
+            &bills,
+            &work_centers,
+            &calendars,
+            date(6),
+        )
+        .unwrap();
+
+        // 延後 3 天的下單日為週日，改延後 4 天到 11/10（週一）
+        assert!(result.feasible);
+        assert_eq!(result.shifts.len(), 1);
+        assert_eq!(result.shifts[0].order_date, date(10));
+        assert_eq!(result.shifts[0].shift_days, 4);
     }
 }
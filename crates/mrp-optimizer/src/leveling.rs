@@ -0,0 +1,369 @@
+//! 產能平準化：在訂單的寬裕時間內移動生產訂單，使工作中心各時間桶的負荷更平均
+//!
+//! 與有限產能排程（[`CapacityPlanner::balance_capacity`]）不同，平準化不要求負荷低於產能，
+//! 而是以局部搜尋降低目標值：每輪逐張訂單嘗試所有可移動天數，採用讓目標值下降最多的位置，
+//! 直到沒有任何移動能再改善為止。
+
+use chrono::NaiveDate;
+use mrp_calc::bucketing::BucketingStrategy;
+use mrp_core::{CalendarRegistry, PlannedOrder, ResourceBill, WorkCalendar, WorkCenter};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use crate::capacity::{CapacityPlanner, OrderShift, WorkCenterLoad};
+use crate::OptimizationResult;
+
+/// 局部搜尋的最大輪數
+const MAX_PASSES: usize = 50;
+
+/// 平準化目標
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum LevelingObjective {
+    /// 最小化最高負荷率（同峰值時再比較平方差）
+    #[default]
+    PeakLoad,
+
+    /// 最小化各工作中心時間桶負荷與平均負荷的平方差總和
+    SquaredDeviation,
+}
+
+/// 可移動的訂單
+struct Movable {
+    /// 在訂單清單中的位置
+    index: usize,
+    /// （工作中心, 工時, 偏移天數）
+    hours: Vec<(usize, Decimal, i64)>,
+    /// 候選移動天數（由近到遠）
+    offsets: Vec<i64>,
+}
+
+/// 產能平準器
+pub struct CapacityLeveler;
+
+impl CapacityLeveler {
+    /// 平準化生產訂單的工作中心負荷
+    ///
+    /// 可移動範圍與負荷分攤方式同 [`CapacityPlanner::balance_capacity`]；時間桶自最早可開工日
+    /// （`earliest_start` 與最早下單日較早者）起按 `strategy` 切分。
+    /// `messages` 依序為訂單移動、各工作中心平準前後的峰值與平方差、以及負荷有變動的時間桶。
+    pub fn level(
+        orders: &[PlannedOrder],
+        bills: &HashMap<String, ResourceBill>,
+        work_centers: &[WorkCenter],
+        calendars: &CalendarRegistry,
+        earliest_start: NaiveDate,
+        strategy: BucketingStrategy,
+        objective: LevelingObjective,
+    ) -> mrp_core::Result<OptimizationResult> {
+        let center_calendars = work_centers
+            .iter()
+            .map(|center| calendars.resolve(&center.calendar_id))
+            .collect::<mrp_core::Result<Vec<WorkCalendar>>>()?;
+
+        let mut optimized = orders.to_vec();
+        let mut movables = Vec::new();
+        for (index, order) in optimized.iter().enumerate() {
+            if !order.is_production() {
+                continue;
+            }
+            let Some(bill) = bills.get(&order.component_id) else {
+                continue;
+            };
+            let hours: Vec<(usize, Decimal, i64)> = bill
+                .loads
+                .iter()
+                .filter_map(|load| {
+                    work_centers
+                        .iter()
+                        .position(|center| center.id == load.work_center_id)
+                        .map(|center| {
+                            (
+                                center,
+                                load.hours_for(order.quantity),
+                                i64::from(load.offset_days),
+                            )
+                        })
+                })
+                .collect();
+            // 移動後的下單日須為各工作中心的工作日
+            let mut offsets: Vec<i64> = OrderShift::window(order, earliest_start)
+                .filter(|&offset| {
+                    let start = order.order_date + chrono::Duration::days(offset);
+                    offset == 0
+                        || hours
+                            .iter()
+                            .all(|&(center, _, _)| center_calendars[center].is_working_day(start))
+                })
+                .collect();
+            offsets.sort_by_key(|offset| (offset.abs(), -offset));
+            movables.push(Movable {
+                index,
+                hours,
+                offsets,
+            });
+        }
+
+        let Some(start) = optimized
+            .iter()
+            .map(|o| o.order_date)
+            .chain(std::iter::once(earliest_start))
+            .min()
+        else {
+            return Ok(OptimizationResult::feasible(optimized));
+        };
+        let end = optimized
+            .iter()
+            .map(|o| o.need_date.unwrap_or(o.required_date).max(o.required_date))
+            .max()
+            .unwrap_or(start);
+        let bounds = CapacityPlanner::bucket_bounds(start, end, strategy);
+        let available: Vec<Vec<Decimal>> = work_centers
+            .iter()
+            .zip(&center_calendars)
+            .map(|(center, calendar)| {
+                bounds
+                    .iter()
+                    .map(|&(s, e)| center.available_hours_between(calendar, s, e))
+                    .collect()
+            })
+            .collect();
+
+        // 訂單在指定移動天數下的負荷（工作中心, 時間桶, 工時）
+        let contribution = |movable: &Movable, order: &PlannedOrder, offset: i64| {
+            let shift = chrono::Duration::days(offset);
+            let mut entries = Vec::new();
+            for &(center, hours, lag) in &movable.hours {
+                let lag = chrono::Duration::days(lag);
+                for (date, share) in CapacityPlanner::spread_hours(
+                    order.order_date + shift - lag,
+                    order.required_date + shift - lag,
+                    hours,
+                    &center_calendars[center],
+                ) {
+                    let bucket = bounds.partition_point(|&(_, bucket_end)| bucket_end <= date);
+                    entries.push((center, bucket.min(bounds.len() - 1), share));
+                }
+            }
+            entries
+        };
+
+        let mut loads = vec![vec![Decimal::ZERO; bounds.len()]; work_centers.len()];
+        for movable in &movables {
+            for (center, bucket, hours) in contribution(movable, &optimized[movable.index], 0) {
+                loads[center][bucket] += hours;
+            }
+        }
+        let before = loads.clone();
+
+        let mut current: Vec<i64> = vec![0; movables.len()];
+        for _ in 0..MAX_PASSES {
+            let mut improved = false;
+            for (i, movable) in movables.iter().enumerate() {
+                let order = &optimized[movable.index];
+                for (center, bucket, hours) in contribution(movable, order, current[i]) {
+                    loads[center][bucket] -= hours;
+                }
+                let mut best: Option<(i64, (Decimal, Decimal))> = None;
+                for &offset in &movable.offsets {
+                    let entries = contribution(movable, order, offset);
+                    for &(center, bucket, hours) in &entries {
+                        loads[center][bucket] += hours;
+                    }
+                    let score = Self::score(&loads, &available, objective);
+                    for &(center, bucket, hours) in &entries {
+                        loads[center][bucket] -= hours;
+                    }
+                    let is_current = offset == current[i];
+                    match best {
+                        Some((_, best_score)) if score > best_score => {}
+                        Some((_, best_score)) if score == best_score && !is_current => {}
+                        _ => best = Some((offset, score)),
+                    }
+                }
+                let offset = best.map_or(current[i], |(offset, _)| offset);
+                improved |= offset != current[i];
+                current[i] = offset;
+                for (center, bucket, hours) in contribution(movable, order, offset) {
+                    loads[center][bucket] += hours;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+
+        let mut shifts = Vec::new();
+        for (movable, &offset) in movables.iter().zip(&current) {
+            if offset != 0 {
+                shifts.push(OrderShift::apply(&mut optimized[movable.index], offset));
+            }
+        }
+
+        let mut messages: Vec<String> = shifts.iter().map(ToString::to_string).collect();
+        let mut violations = Vec::new();
+        for (center_index, center) in work_centers.iter().enumerate() {
+            if before[center_index].iter().all(|h| h.is_zero()) {
+                continue;
+            }
+            let (before_peak, before_deviation) =
+                Self::center_score(&before[center_index], &available[center_index]);
+            let (after_peak, after_deviation) =
+                Self::center_score(&loads[center_index], &available[center_index]);
+            messages.push(format!(
+                "工作中心 {}：峰值負荷率 {}% → {}%，負荷平方差 {} → {}",
+                center.id,
+                before_peak.round_dp(1).normalize(),
+                after_peak.round_dp(1).normalize(),
+                before_deviation.round_dp(2).normalize(),
+                after_deviation.round_dp(2).normalize()
+            ));
+            for (bucket, &(bucket_start, bucket_end)) in bounds.iter().enumerate() {
+                let load = WorkCenterLoad {
+                    work_center_id: center.id.clone(),
+                    bucket_start,
+                    bucket_end,
+                    required_hours: loads[center_index][bucket],
+                    available_hours: available[center_index][bucket],
+                };
+                if before[center_index][bucket] != load.required_hours {
+                    messages.push(format!(
+                        "  {} {}：{} → {} 小時（可用 {}）",
+                        center.id,
+                        bucket_start,
+                        before[center_index][bucket].round_dp(2).normalize(),
+                        load.required_hours.round_dp(2).normalize(),
+                        load.available_hours.normalize()
+                    ));
+                }
+                if load.is_overloaded() {
+                    violations.push(load);
+                }
+            }
+        }
+
+        Ok(OptimizationResult {
+            feasible: violations.is_empty(),
+            optimized_orders: optimized,
+            messages,
+            shifts,
+            violations,
+        })
+    }
+
+    /// 整體目標值（越小越好）
+    fn score(
+        loads: &[Vec<Decimal>],
+        available: &[Vec<Decimal>],
+        objective: LevelingObjective,
+    ) -> (Decimal, Decimal) {
+        let (peak, deviation) = loads.iter().zip(available).fold(
+            (Decimal::ZERO, Decimal::ZERO),
+            |(peak, deviation), (loads, available)| {
+                let (center_peak, center_deviation) = Self::center_score(loads, available);
+                (peak.max(center_peak), deviation + center_deviation)
+            },
+        );
+        match objective {
+            LevelingObjective::PeakLoad => (peak, deviation),
+            LevelingObjective::SquaredDeviation => (deviation, peak),
+        }
+    }
+
+    /// 單一工作中心的（最高負荷率 %, 負荷與平均負荷的平方差總和）
+    ///
+    /// 沒有可用工時卻有負荷的時間桶，負荷率以每小時 100% 計。
+    fn center_score(loads: &[Decimal], available: &[Decimal]) -> (Decimal, Decimal) {
+        let peak = loads
+            .iter()
+            .zip(available)
+            .map(|(&required, &available)| {
+                if available > Decimal::ZERO {
+                    required / available * Decimal::ONE_HUNDRED
+                } else {
+                    required * Decimal::ONE_HUNDRED
+                }
+            })
+            .max()
+            .unwrap_or(Decimal::ZERO);
+        if loads.is_empty() {
+            return (peak, Decimal::ZERO);
+        }
+        let mean = loads.iter().copied().sum::<Decimal>() / Decimal::from(loads.len());
+        let deviation = loads
+            .iter()
+            .map(|&load| (load - mean) * (load - mean))
+            .sum();
+        (peak, deviation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::{Operation, PlannedOrderType, Routing};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_level_spreads_peak_week() {
+        let mut calendars = CalendarRegistry::new();
+        calendars.register(WorkCalendar::new("PLANT".to_string()));
+        let work_centers = vec![WorkCenter::new(
+            "PAINT".to_string(),
+            "PLANT".to_string(),
+            Decimal::from(8),
+        )];
+        let routings = HashMap::from([(
+            "PANEL".to_string(),
+            Routing::new("PANEL".to_string()).with_operation(Operation::new(
+                10,
+                "PAINT".to_string(),
+                Decimal::ZERO,
+                Decimal::ONE,
+            )),
+        )]);
+
+        // 三張 20 小時的訂單都在 11/17 那週（各一個工作日），11/10 那週空著
+        let orders: Vec<PlannedOrder> = [17, 18, 19]
+            .into_iter()
+            .map(|day| {
+                PlannedOrder::new(
+                    "PANEL".to_string(),
+                    Decimal::from(20),
+                    date(day + 1),
+                    date(day),
+                    PlannedOrderType::Production,
+                )
+            })
+            .collect();
+
+        let result = CapacityLeveler::level(
+            &orders,
+            &ResourceBill::from_routings(routings.values()),
+            &work_centers,
+            &calendars,
+            date(10),
+            BucketingStrategy::Weekly,
+            LevelingObjective::PeakLoad,
+        )
+        .unwrap();
+
+        // 只能提前：一張移到最近的工作日 11/14（11/10 週），兩週負荷 20 / 40 小時
+        assert_eq!(result.shifts.len(), 1);
+        let moved = &result.optimized_orders[result
+            .optimized_orders
+            .iter()
+            .position(|o| o.id == result.shifts[0].order_id)
+            .unwrap()];
+        assert_eq!(moved.order_date, date(14));
+        assert!(!moved.is_late());
+        assert!(result.feasible);
+        assert!(result
+            .messages
+            .iter()
+            .any(|m| m.starts_with("工作中心 PAINT：峰值負荷率 150% → 100%")));
+    }
+}
@@ -4,14 +4,27 @@
 
 pub mod capacity;
 pub mod constraint;
+pub mod leveling;
+#[cfg(feature = "milp")]
+pub mod milp;
+pub mod objective;
+pub mod pipeline;
+pub mod rccp;
 pub mod scheduling;
 
 // Re-export 主要類型
-pub use capacity::CapacityPlanner;
-pub use scheduling::Scheduler;
+pub use capacity::{CapacityPlanner, LoadProfile, OrderShift, WorkCenterLoad};
+pub use leveling::{CapacityLeveler, LevelingObjective};
+#[cfg(feature = "milp")]
+pub use milp::MilpLotSizer;
+pub use objective::{ObjectiveScores, ObjectiveWeights, PolicyOptimizer, PolicyScore};
+pub use pipeline::{OptimizedCalculation, OptimizedPlan, ProductionResources};
+pub use rccp::{RoughCutPlanner, RoughCutResult};
+pub use scheduling::{ScheduledOperation, Scheduler, SetupMatrix, SetupSequence};
 
 /// 優化結果
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct OptimizationResult {
     /// 是否可行
     pub feasible: bool,
@@ -21,6 +34,12 @@ pub struct OptimizationResult {
 
     /// 優化信息
     pub messages: Vec<String>,
+
+    /// 被移動的訂單
+    pub shifts: Vec<OrderShift>,
+
+    /// 優化後仍超載的工作中心負荷
+    pub violations: Vec<WorkCenterLoad>,
 }
 
 impl OptimizationResult {
@@ -30,6 +49,8 @@ impl OptimizationResult {
             feasible: true,
             optimized_orders,
             messages: Vec::new(),
+            shifts: Vec::new(),
+            violations: Vec::new(),
         }
     }
 
@@ -39,6 +60,8 @@ impl OptimizationResult {
             feasible: false,
             optimized_orders: Vec::new(),
             messages: vec![message],
+            shifts: Vec::new(),
+            violations: Vec::new(),
         }
     }
 }
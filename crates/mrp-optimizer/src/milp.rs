@@ -0,0 +1,496 @@
+//! 成本最小化批量規劃：以混合整數規劃（MILP）決定整個計劃期的訂購時點與數量
+//!
+//! 每個物料的淨需求（批對批計劃訂單的需要日期與數量）視為需求時間桶，目標為訂購成本、
+//! 持有成本與延遲成本總和最小：
+//!
+//! - `x_t`：時間桶 t 收貨量；`y_t`：是否下單（0/1，訂購成本）
+//! - `n_t`：有訂購倍數或取整單位時的整數批數，`x_t = 倍數·n_t`
+//! - `I_t` / `B_t`：時間桶 t 後的庫存／欠交量（持有／延遲成本按到下一時間桶的天數計）
+//! - 平衡：`I_{t-1} - B_{t-1} + x_t - d_t = I_t - B_t`，最後一個時間桶不得欠交
+//! - `x_t ≤ M·y_t`、`x_t ≥ 最小訂購量·y_t`（M 為最大訂購量或總需求）
+//!
+//! 物料按計算順序（父件先於子件）逐一求解，父件的新訂單以確認計劃訂單回饋重算，
+//! 子件的淨需求因此由新訂單展開。
+//!
+//! 需啟用 `milp` feature。
+
+use chrono::NaiveDate;
+use microlp::{ComparisonOp, OptimizationDirection, Problem};
+use mrp_calc::{MrpCalculator, NetRequirement};
+use mrp_core::{
+    Demand, Inventory, MrpConfig, MrpError, PlannedOrder, PlannedOrderType, PlanningMethod,
+    ProcurementType, RoundingPolicy, Supply,
+};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::OptimizationResult;
+
+/// 單一物料的成本參數
+#[derive(Debug, Clone, Copy)]
+struct CostRates {
+    /// 每次訂購成本
+    ordering: Decimal,
+    /// 每單位每日持有成本
+    holding: Decimal,
+    /// 每單位每日延遲成本
+    lateness: Decimal,
+}
+
+impl CostRates {
+    /// 收貨與需求按日期累計的總成本（最後一個日期後的庫存以一天計）
+    fn plan_cost(
+        &self,
+        receipts: &[(NaiveDate, Decimal)],
+        demands: &[(NaiveDate, Decimal)],
+    ) -> Decimal {
+        let mut balance: BTreeMap<NaiveDate, Decimal> = BTreeMap::new();
+        for &(date, quantity) in receipts {
+            *balance.entry(date).or_default() += quantity;
+        }
+        for &(date, quantity) in demands {
+            *balance.entry(date).or_default() -= quantity;
+        }
+        let dates: Vec<NaiveDate> = balance.keys().copied().collect();
+        let mut position = Decimal::ZERO;
+        let mut cost = self.ordering * Decimal::from(receipts.len());
+        for (index, date) in dates.iter().enumerate() {
+            position += balance[date];
+            let days = dates
+                .get(index + 1)
+                .map_or(1, |next| (*next - *date).num_days());
+            let rate = if position >= Decimal::ZERO {
+                self.holding
+            } else {
+                self.lateness
+            };
+            cost += rate * position.abs() * Decimal::from(days);
+        }
+        cost
+    }
+}
+
+/// MILP 批量規劃器
+pub struct MilpLotSizer;
+
+impl MilpLotSizer {
+    /// 重新決定各物料的計劃訂單，使訂購、持有與延遲成本總和最小
+    ///
+    /// 訂購成本與持有成本取自物料配置的 `ordering_cost`、`unit_cost × annual_holding_cost_rate / 365`，
+    /// 延遲成本為每單位每日 `lateness_cost_per_unit_day`；缺少成本參數的物料保留計算器的計劃訂單。
+    ///
+    /// 物料按計算順序逐一求解：尚未求解的物料以人工計劃方式計算，只取庫存預測，
+    /// 逐期淨需求為預計庫存（不含計劃訂單）低於安全庫存的新增缺口；求解後的訂單以確認計劃訂單
+    /// 加入供應重新計算，該物料不再產生計劃訂單，生產訂單的子件需求由新訂單展開。
+    /// 新訂單的下單日按提前期自收貨日倒推，晚於淨需求日期收貨的訂單記錄需要日期。
+    pub fn optimize(
+        calculator: &MrpCalculator,
+        demands: &[Demand],
+        supplies: &[Supply],
+        inventories: &[Inventory],
+        lateness_cost_per_unit_day: Decimal,
+    ) -> mrp_core::Result<OptimizationResult> {
+        let rates_for = |config: &MrpConfig| {
+            Some(CostRates {
+                ordering: config.ordering_cost?,
+                holding: config.unit_cost? * config.annual_holding_cost_rate? / Decimal::from(365),
+                lateness: lateness_cost_per_unit_day,
+            })
+        };
+
+        let mut solved: Vec<PlannedOrder> = Vec::new();
+        let mut solved_ids: HashSet<String> = HashSet::new();
+        let mut costed_ids: HashSet<String> = HashSet::new();
+        let mut messages = Vec::new();
+        loop {
+            let overrides: Vec<MrpConfig> = costed_ids
+                .iter()
+                .filter_map(|id| calculator.config(id))
+                .map(|config| {
+                    let mut config = config.clone();
+                    if solved_ids.contains(&config.component_id) {
+                        config.mrp_enabled = false;
+                    } else {
+                        config.planning_method = PlanningMethod::Manual;
+                    }
+                    config
+                })
+                .collect();
+            let mut run_supplies = supplies.to_vec();
+            run_supplies.extend(solved.iter().map(PlannedOrder::to_firm_supply));
+            let mut result = calculator.with_config_overrides(overrides).calculate(
+                demands.to_vec(),
+                run_supplies,
+                inventories.to_vec(),
+            )?;
+
+            // 新出現的有成本物料先以人工計劃方式重算，取得庫存預測
+            let discovered: Vec<String> = result
+                .metrics
+                .items
+                .iter()
+                .map(|item| item.component_id.clone())
+                .filter(|id| !costed_ids.contains(id))
+                .filter(|id| calculator.config(id).and_then(rates_for).is_some())
+                .collect();
+            if !discovered.is_empty() {
+                costed_ids.extend(discovered);
+                continue;
+            }
+
+            // 計算順序中第一個尚未求解的有成本物料：其父件都已定案
+            let next = result
+                .metrics
+                .items
+                .iter()
+                .map(|item| item.component_id.as_str())
+                .find(|id| costed_ids.contains(*id) && !solved_ids.contains(*id))
+                .map(str::to_string);
+            let Some(component_id) = next else {
+                let mut uncosted: Vec<&str> = result
+                    .planned_orders
+                    .iter()
+                    .map(|o| o.component_id.as_str())
+                    .collect();
+                uncosted.sort_unstable();
+                uncosted.dedup();
+                for component_id in uncosted {
+                    messages.push(format!(
+                        "物料 {} 缺少訂購成本、單位成本或持有成本率，保留原計劃訂單",
+                        component_id
+                    ));
+                }
+                solved.extend(result.planned_orders);
+                break;
+            };
+            solved_ids.insert(component_id.clone());
+
+            let Some((config, rates)) = calculator
+                .config(&component_id)
+                .and_then(|config| Some((config, rates_for(config)?)))
+            else {
+                continue;
+            };
+            let projection = result.projections.remove(&component_id).unwrap_or_default();
+            let periods = Self::net_requirements(&projection, config.safety_stock);
+            if periods.is_empty() {
+                continue;
+            }
+            let quantities = Self::solve(&component_id, &periods, config, &rates)?;
+
+            let calendar = calculator.calendar();
+            let order_type = match config.procurement_type {
+                ProcurementType::Make => PlannedOrderType::Production,
+                ProcurementType::Transfer => PlannedOrderType::Transfer,
+                _ => PlannedOrderType::Purchase,
+            };
+            let mut receipts: Vec<PlannedOrder> = periods
+                .iter()
+                .zip(quantities)
+                .filter(|(_, quantity)| *quantity > Decimal::ZERO)
+                .map(|(&(date, _), quantity)| {
+                    let mut order = PlannedOrder::new(
+                        component_id.clone(),
+                        quantity,
+                        date,
+                        calendar.subtract_working_days(date, config.lead_time_for(quantity)),
+                        order_type,
+                    );
+                    order.source_id = config.supplier_id.clone();
+                    order
+                })
+                .collect();
+            Self::mark_need_dates(&mut receipts, &periods);
+
+            let before = rates.plan_cost(&periods, &periods);
+            let after = rates.plan_cost(
+                &receipts
+                    .iter()
+                    .map(|o| (o.required_date, o.quantity))
+                    .collect::<Vec<_>>(),
+                &periods,
+            );
+            messages.push(format!(
+                "物料 {}：淨需求 {} 期 → 計劃訂單 {} 張，總成本 {} → {}",
+                component_id,
+                periods.len(),
+                receipts.len(),
+                before.round_dp(2).normalize(),
+                after.round_dp(2).normalize()
+            ));
+            solved.extend(receipts);
+        }
+
+        Ok(OptimizationResult {
+            messages,
+            ..OptimizationResult::feasible(solved)
+        })
+    }
+
+    /// 庫存預測的逐期淨需求：預計庫存低於安全庫存的缺口中，超過先前各期最大缺口的部分
+    fn net_requirements(
+        projection: &[NetRequirement],
+        safety_stock: Decimal,
+    ) -> Vec<(NaiveDate, Decimal)> {
+        let mut covered = Decimal::ZERO;
+        let mut periods = Vec::new();
+        for row in projection {
+            let shortage = safety_stock - row.projected_on_hand;
+            if shortage > covered {
+                periods.push((row.date, shortage - covered));
+                covered = shortage;
+            }
+        }
+        periods
+    }
+
+    /// 求解單一物料的 MILP，回傳各時間桶的收貨量
+    fn solve(
+        component_id: &str,
+        periods: &[(NaiveDate, Decimal)],
+        config: &MrpConfig,
+        rates: &CostRates,
+    ) -> mrp_core::Result<Vec<Decimal>> {
+        let float = |value: Decimal| value.to_f64().unwrap_or(0.0);
+        // 訂購倍數優先，其次取整方式的倍數；收貨量為其整數倍
+        let unit = config
+            .order_multiple
+            .or(match config.rounding_policy {
+                RoundingPolicy::RoundUpToUnit => Some(Decimal::ONE),
+                RoundingPolicy::RoundToMultiple(multiple) => Some(multiple),
+                _ => None,
+            })
+            .filter(|unit| *unit > Decimal::ZERO);
+        let total: Decimal = periods.iter().map(|(_, quantity)| *quantity).sum();
+        let total = unit.map_or(total, |unit| (total / unit).ceil() * unit);
+        let maximum = config.maximum_order_qty.unwrap_or(total).max(Decimal::ZERO);
+        let maximum = unit.map_or(maximum, |unit| (maximum / unit).floor() * unit);
+        let big_m = float(maximum);
+        let minimum = float(config.minimum_order_qty.unwrap_or(Decimal::ZERO));
+
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let mut receipts = Vec::with_capacity(periods.len());
+        let mut previous = None;
+        for (index, &(date, demand)) in periods.iter().enumerate() {
+            let days = periods
+                .get(index + 1)
+                .map_or(1, |(next, _)| (*next - date).num_days()) as f64;
+            let last = index + 1 == periods.len();
+
+            let receipt = problem.add_var(0.0, (0.0, big_m));
+            let ordered = problem.add_binary_var(float(rates.ordering));
+            let inventory = problem.add_var(float(rates.holding) * days, (0.0, f64::INFINITY));
+            let backlog_max = if last { 0.0 } else { f64::INFINITY };
+            let backlog = problem.add_var(float(rates.lateness) * days, (0.0, backlog_max));
+
+            let mut balance = vec![(receipt, 1.0), (inventory, -1.0), (backlog, 1.0)];
+            if let Some((prev_inventory, prev_backlog)) = previous {
+                balance.push((prev_inventory, 1.0));
+                balance.push((prev_backlog, -1.0));
+            }
+            problem.add_constraint(balance, ComparisonOp::Eq, float(demand));
+            problem.add_constraint([(receipt, 1.0), (ordered, -big_m)], ComparisonOp::Le, 0.0);
+            if minimum > 0.0 {
+                problem.add_constraint(
+                    [(receipt, 1.0), (ordered, -minimum)],
+                    ComparisonOp::Ge,
+                    0.0,
+                );
+            }
+
+            if let Some(unit) = unit {
+                let batches = problem
+                    .add_integer_var(0.0, (0, (maximum / unit).to_i32().unwrap_or(i32::MAX)));
+                problem.add_constraint(
+                    [(receipt, 1.0), (batches, -float(unit))],
+                    ComparisonOp::Eq,
+                    0.0,
+                );
+            }
+
+            receipts.push(receipt);
+            previous = Some((inventory, backlog));
+        }
+
+        let solution = problem.solve().map_err(|e| {
+            MrpError::CalculationError(format!(
+                "物料 {} 的 MILP 批量規劃求解失敗: {}",
+                component_id, e
+            ))
+        })?;
+        Ok(receipts
+            .into_iter()
+            .map(|var| {
+                let quantity = Decimal::try_from(*solution.var_value(var))
+                    .unwrap_or_default()
+                    .round_dp(6)
+                    .normalize();
+                match unit {
+                    Some(unit) if quantity > Decimal::ZERO => (quantity / unit).round() * unit,
+                    _ if quantity > Decimal::ZERO => config.round_quantity(quantity),
+                    _ => Decimal::ZERO,
+                }
+            })
+            .collect())
+    }
+
+    /// 按先進先出將各期淨需求分配到涵蓋它的新訂單，晚收貨時記錄最早的需要日期
+    fn mark_need_dates(receipts: &mut [PlannedOrder], periods: &[(NaiveDate, Decimal)]) {
+        let mut covered = Decimal::ZERO;
+        let mut receipt_index = 0;
+        let mut receipt_end = receipts.first().map_or(Decimal::ZERO, |r| r.quantity);
+        for &(need_date, quantity) in periods {
+            while covered >= receipt_end && receipt_index + 1 < receipts.len() {
+                receipt_index += 1;
+                receipt_end += receipts[receipt_index].quantity;
+            }
+            let Some(receipt) = receipts.get_mut(receipt_index) else {
+                return;
+            };
+            if need_date < receipt.required_date
+                && receipt.need_date.map_or(true, |date| need_date < date)
+            {
+                receipt.need_date = Some(need_date);
+            }
+            covered += quantity;
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bom_graph::BomGraph;
+    use mrp_core::{DemandType, ProcurementType, WorkCalendar};
+    use std::collections::HashMap;
+
+    fn date(day: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, 3).unwrap() + chrono::Duration::days(day)
+    }
+
+    /// 每單位每日持有成本 0.01
+    fn costed(id: &str, lead_time: u32, procurement: ProcurementType, ordering: i64) -> MrpConfig {
+        MrpConfig::new(id.to_string(), lead_time, procurement)
+            .with_unit_cost(Decimal::TEN)
+            .with_eoq_costs(Decimal::from(ordering), Decimal::new(365, 3))
+    }
+
+    fn demand(id: &str, quantity: i64, day: i64) -> Demand {
+        Demand::new(
+            id.to_string(),
+            Decimal::from(quantity),
+            date(day),
+            DemandType::SalesOrder,
+        )
+    }
+
+    #[test]
+    fn test_milp_combines_nearby_requirements() {
+        // 訂購成本 5；現有庫存 5 抵掉第一天的一半需求
+        let configs = HashMap::from([(
+            "GEAR".to_string(),
+            costed("GEAR", 2, ProcurementType::Buy, 5),
+        )]);
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new("PLANT".to_string()),
+        );
+        let demands: Vec<Demand> = [0, 1, 2, 59]
+            .into_iter()
+            .map(|day| demand("GEAR", 10, day))
+            .collect();
+        let inventories = vec![Inventory::new(
+            "GEAR".to_string(),
+            Decimal::from(5),
+            Decimal::ZERO,
+        )];
+
+        let result = MilpLotSizer::optimize(
+            &calculator,
+            &demands,
+            &[],
+            &inventories,
+            Decimal::from(1000),
+        )
+        .unwrap();
+
+        // 淨需求 5/10/10/10：前三天合併（持有 0.3 < 訂購 10）；59 天後的需求持有 5.9 > 訂購 5，單獨下單
+        let receipts: Vec<(NaiveDate, Decimal)> = result
+            .optimized_orders
+            .iter()
+            .map(|o| (o.required_date, o.quantity))
+            .collect();
+        assert_eq!(
+            receipts,
+            vec![(date(0), Decimal::from(25)), (date(59), Decimal::TEN)]
+        );
+        assert_eq!(result.optimized_orders[0].order_date, date(-4));
+        assert!(result.feasible);
+        assert_eq!(
+            result.messages,
+            vec!["物料 GEAR：淨需求 4 期 → 計劃訂單 2 張，總成本 20 → 10.3"]
+        );
+    }
+
+    #[test]
+    fn test_milp_re_explodes_children_with_order_multiple() {
+        // ASSY 每件用 1 個 GEAR；GEAR 以 25 個為一批
+        let mut bom_graph = BomGraph::new();
+        bom_graph
+            .add_bom_item(bom_core::BomItem {
+                id: uuid::Uuid::new_v4(),
+                parent_id: bom_core::ComponentId::new("ASSY"),
+                child_id: bom_core::ComponentId::new("GEAR"),
+                quantity: Decimal::ONE,
+                scrap_factor: Decimal::ZERO,
+                sequence: 10,
+                operation_sequence: None,
+                is_phantom: false,
+                effective_from: None,
+                effective_to: None,
+                alternative_group: None,
+                alternative_priority: None,
+                reference_designator: None,
+                position: None,
+                notes: None,
+                version: 1,
+            })
+            .unwrap();
+        let configs = HashMap::from([
+            (
+                "ASSY".to_string(),
+                costed("ASSY", 1, ProcurementType::Make, 100),
+            ),
+            (
+                "GEAR".to_string(),
+                costed("GEAR", 2, ProcurementType::Buy, 5).with_order_multiple(Decimal::from(25)),
+            ),
+        ]);
+        let calculator =
+            MrpCalculator::new(bom_graph, configs, WorkCalendar::new("PLANT".to_string()));
+        let demands: Vec<Demand> = [0, 1, 2]
+            .into_iter()
+            .map(|day| demand("ASSY", 10, day))
+            .collect();
+
+        let result =
+            MilpLotSizer::optimize(&calculator, &demands, &[], &[], Decimal::from(1000)).unwrap();
+
+        // ASSY 合併為一張 30 件的訂單，GEAR 依新訂單的下單日展開 30 個需求，取整為兩批 50 個
+        let orders = |id: &str| -> Vec<&PlannedOrder> {
+            result
+                .optimized_orders
+                .iter()
+                .filter(|o| o.component_id == id)
+                .collect()
+        };
+        let assy = orders("ASSY");
+        assert_eq!(assy.len(), 1);
+        assert_eq!(assy[0].quantity, Decimal::from(30));
+        let gear = orders("GEAR");
+        assert_eq!(gear.len(), 1);
+        assert_eq!(gear[0].quantity, Decimal::from(50));
+        assert_eq!(gear[0].required_date, assy[0].order_date);
+    }
+}
@@ -0,0 +1,224 @@
+//! 多目標評分：以權重權衡服務水準、庫存金額與訂單數，比較不同計劃政策
+//!
+//! 三個目標單位不同，權重即各目標的單位成本：加權分數 = 服務水準缺口（百分點）× 服務水準權重
+//! + 平均庫存金額 × 庫存金額權重 + 計劃訂單數 × 訂單數權重，分數越低越好。
+
+use mrp_calc::{MrpCalculator, MrpResult, PlanningPolicy, TimePhasedRecord};
+use mrp_core::{Demand, Inventory, Supply};
+use rust_decimal::Decimal;
+
+/// 目標權重
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ObjectiveWeights {
+    /// 服務水準每缺 1 個百分點的成本
+    pub service_level: Decimal,
+
+    /// 每單位平均庫存金額的成本
+    pub inventory_value: Decimal,
+
+    /// 每張計劃訂單的成本（整備／訂購次數）
+    pub order_count: Decimal,
+}
+
+impl ObjectiveWeights {
+    /// 創建目標權重
+    pub fn new(service_level: Decimal, inventory_value: Decimal, order_count: Decimal) -> Self {
+        Self {
+            service_level,
+            inventory_value,
+            order_count,
+        }
+    }
+}
+
+impl Default for ObjectiveWeights {
+    fn default() -> Self {
+        Self::new(Decimal::ONE, Decimal::ONE, Decimal::ONE)
+    }
+}
+
+/// 計劃結果的各目標得分
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ObjectiveScores {
+    /// 服務水準：輸入需求的準時滿足率（0–1；無需求時為 1）
+    pub service_level: Decimal,
+
+    /// 平均庫存金額（逐日平均預計在庫量 × 單位成本；未設單位成本的物料不計）
+    pub inventory_value: Decimal,
+
+    /// 計劃訂單數
+    pub order_count: usize,
+
+    /// 加權分數（越低越好）
+    pub weighted: Decimal,
+}
+
+impl ObjectiveScores {
+    /// 依計算器的物料配置（單位成本）評分計劃結果
+    pub fn evaluate(
+        result: &MrpResult,
+        calculator: &MrpCalculator,
+        weights: &ObjectiveWeights,
+    ) -> Self {
+        let (demand, on_time) = result.tier_fill_rates.iter().fold(
+            (Decimal::ZERO, Decimal::ZERO),
+            |(demand, on_time), rate| {
+                (
+                    demand + rate.demand_quantity,
+                    on_time + rate.on_time_quantity,
+                )
+            },
+        );
+        let service_level = if demand > Decimal::ZERO {
+            on_time / demand
+        } else {
+            Decimal::ONE
+        };
+        let inventory_value: Decimal = result
+            .time_phased
+            .iter()
+            .filter_map(|(id, records)| {
+                let unit_cost = calculator.config(id)?.unit_cost?;
+                Some(Self::average_on_hand(records) * unit_cost)
+            })
+            .sum();
+        let order_count = result.planned_orders.len();
+
+        let weighted =
+            weights.service_level * (Decimal::ONE - service_level) * Decimal::ONE_HUNDRED
+                + weights.inventory_value * inventory_value
+                + weights.order_count * Decimal::from(order_count);
+        Self {
+            service_level,
+            inventory_value,
+            order_count,
+            weighted,
+        }
+    }
+
+    /// 物料的逐日平均預計在庫量
+    ///
+    /// 每個異動日期的在庫量維持到下一個異動日期，最後一個異動日期計一天（負庫存視為零）
+    fn average_on_hand(records: &[TimePhasedRecord]) -> Decimal {
+        let mut total = Decimal::ZERO;
+        let mut days = 0;
+        for (index, record) in records.iter().enumerate() {
+            let held = records
+                .get(index + 1)
+                .map_or(1, |next| (next.date - record.date).num_days());
+            total += record.projected_on_hand.max(Decimal::ZERO) * Decimal::from(held);
+            days += held;
+        }
+        if days == 0 {
+            return Decimal::ZERO;
+        }
+        total / Decimal::from(days)
+    }
+}
+
+/// 單一政策的計算結果與得分
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PolicyScore {
+    /// 政策名稱
+    pub name: String,
+
+    /// 各目標得分
+    pub scores: ObjectiveScores,
+
+    /// 計算結果
+    pub result: MrpResult,
+}
+
+/// 政策比較器
+pub struct PolicyOptimizer;
+
+impl PolicyOptimizer {
+    /// 以各候選政策計算同一組輸入，按加權分數由低到高排列（同分保持候選順序）
+    pub fn compare(
+        calculator: &MrpCalculator,
+        demands: &[Demand],
+        supplies: &[Supply],
+        inventories: &[Inventory],
+        candidates: &[(String, PlanningPolicy)],
+        weights: &ObjectiveWeights,
+    ) -> mrp_core::Result<Vec<PolicyScore>> {
+        let mut scored = candidates
+            .iter()
+            .map(|(name, policy)| {
+                let result = calculator.calculate_with_policy(
+                    demands.to_vec(),
+                    supplies.to_vec(),
+                    inventories.to_vec(),
+                    policy,
+                )?;
+                Ok(PolicyScore {
+                    name: name.clone(),
+                    scores: ObjectiveScores::evaluate(&result, calculator, weights),
+                    result,
+                })
+            })
+            .collect::<mrp_core::Result<Vec<PolicyScore>>>()?;
+        scored.sort_by_key(|score| score.scores.weighted);
+        Ok(scored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bom_graph::BomGraph;
+    use chrono::NaiveDate;
+    use mrp_core::{DemandType, MrpConfig, ProcurementType, RoundingPolicy, WorkCalendar};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_compare_policies_by_weighted_score() {
+        let configs = HashMap::from([(
+            "BOLT".to_string(),
+            MrpConfig::new("BOLT".to_string(), 2, ProcurementType::Buy)
+                .with_unit_cost(Decimal::TWO),
+        )]);
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+        let demands = vec![Demand::new(
+            "BOLT".to_string(),
+            Decimal::from(30),
+            NaiveDate::from_ymd_opt(2025, 11, 20).unwrap(),
+            DemandType::SalesOrder,
+        )];
+        let candidates = vec![
+            (
+                "整箱".to_string(),
+                PlanningPolicy::new()
+                    .with_rounding(RoundingPolicy::RoundToMultiple(Decimal::from(50))),
+            ),
+            ("逐批".to_string(), PlanningPolicy::new()),
+        ];
+
+        let ranked = PolicyOptimizer::compare(
+            &calculator,
+            &demands,
+            &[],
+            &[],
+            &candidates,
+            &ObjectiveWeights::default(),
+        )
+        .unwrap();
+
+        // 服務水準與訂單數相同，整箱多買的 20 件留在庫存（40 元），逐批勝出
+        assert_eq!(ranked[0].name, "逐批");
+        assert_eq!(ranked[0].scores.inventory_value, Decimal::ZERO);
+        assert_eq!(ranked[0].scores.service_level, Decimal::ONE);
+        assert_eq!(ranked[0].scores.order_count, 1);
+        assert_eq!(
+            ranked[1].scores.inventory_value - ranked[0].scores.inventory_value,
+            Decimal::from(40)
+        );
+    }
+}
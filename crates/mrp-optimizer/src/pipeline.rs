@@ -0,0 +1,244 @@
+//! 計算後優化：無限產能 MRP 完成後，自動將計劃訂單送入有限產能排程與工序排程
+//!
+//! 優化器依賴計算引擎（而非相反），因此以擴充 trait 為 [`MrpCalculator`] 加上
+//! `calculate_with_optimization`，引入 [`OptimizedCalculation`] 即可直接呼叫。
+
+use chrono::NaiveTime;
+use mrp_calc::{MrpCalculator, MrpResult};
+use mrp_core::{
+    CalendarRegistry, Demand, Inventory, PlannedOrder, ResourceBill, Routing, Supply, WorkCenter,
+};
+use std::collections::HashMap;
+
+use crate::capacity::CapacityPlanner;
+use crate::scheduling::{ScheduledOperation, Scheduler, SetupMatrix};
+use crate::OptimizationResult;
+
+/// 優化階段使用的生產資源主檔
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ProductionResources {
+    /// 工藝路線（物料ID → 工藝路線）
+    pub routings: HashMap<String, Routing>,
+
+    /// 工作中心
+    pub work_centers: Vec<WorkCenter>,
+
+    /// 工作中心日曆
+    pub calendars: CalendarRegistry,
+
+    /// 工序排程的班次開始時間（None 時不做工序排程）
+    pub shift_start: Option<NaiveTime>,
+
+    /// 換線矩陣（工序排程前按換線時間排定共用工作中心的訂單順序）
+    pub setup_matrices: Vec<SetupMatrix>,
+}
+
+impl ProductionResources {
+    /// 創建生產資源主檔
+    pub fn new(
+        routings: HashMap<String, Routing>,
+        work_centers: Vec<WorkCenter>,
+        calendars: CalendarRegistry,
+    ) -> Self {
+        Self {
+            routings,
+            work_centers,
+            calendars,
+            shift_start: None,
+            setup_matrices: Vec::new(),
+        }
+    }
+
+    /// 建構器模式：有限產能排程後再以班次開始時間做工序排程
+    pub fn with_operation_scheduling(mut self, shift_start: NaiveTime) -> Self {
+        self.shift_start = Some(shift_start);
+        self
+    }
+
+    /// 建構器模式：工序排程前以換線矩陣排定訂單順序
+    pub fn with_setup_matrices(mut self, matrices: Vec<SetupMatrix>) -> Self {
+        self.setup_matrices = matrices;
+        self
+    }
+}
+
+/// 原始計劃與優化後計劃
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct OptimizedPlan {
+    /// 無限產能的 MRP 結果
+    pub raw: MrpResult,
+
+    /// 有限產能排程結果（`optimized_orders` 為優化後的計劃訂單）
+    pub optimization: OptimizationResult,
+
+    /// 優化後訂單的工序排程（未設置班次開始時間時為空）
+    pub operations: Vec<ScheduledOperation>,
+}
+
+impl OptimizedPlan {
+    /// 優化後的計劃訂單
+    pub fn optimized_orders(&self) -> &[PlannedOrder] {
+        &self.optimization.optimized_orders
+    }
+
+    /// 優化後所有工作中心都不超載
+    pub fn is_feasible(&self) -> bool {
+        self.optimization.feasible
+    }
+}
+
+/// 計算後優化（[`MrpCalculator`] 的擴充）
+pub trait OptimizedCalculation {
+    /// 執行 MRP 後依序做有限產能排程與（可選的）工序排程，同時回傳原始與優化後計劃
+    ///
+    /// 訂單最早可提前到計劃起始日（計劃政策的起始日，未設定時為最早的需求或供應日期）；移動規則見
+    /// [`CapacityPlanner::balance_capacity`]。
+    fn calculate_with_optimization(
+        &self,
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+        resources: &ProductionResources,
+    ) -> mrp_core::Result<OptimizedPlan>;
+}
+
+impl OptimizedCalculation for MrpCalculator {
+    fn calculate_with_optimization(
+        &self,
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+        resources: &ProductionResources,
+    ) -> mrp_core::Result<OptimizedPlan> {
+        let plan_start = self.policy().plan_start_date.or_else(|| {
+            demands
+                .iter()
+                .map(|d| d.required_date)
+                .chain(supplies.iter().map(|s| s.available_date))
+                .min()
+        });
+        let raw = self.calculate(demands, supplies, inventories)?;
+        let Some(plan_start) = plan_start else {
+            return Ok(OptimizedPlan {
+                optimization: OptimizationResult::feasible(raw.planned_orders.clone()),
+                raw,
+                operations: Vec::new(),
+            });
+        };
+
+        let optimization = CapacityPlanner::balance_capacity(
+            &raw.planned_orders,
+            &ResourceBill::from_routings(resources.routings.values()),
+            &resources.work_centers,
+            &resources.calendars,
+            plan_start,
+        )?;
+        let operations = match resources.shift_start {
+            Some(shift_start) => Scheduler::schedule_operations(
+                &optimization.optimized_orders,
+                &Scheduler::sequence_setups(
+                    &optimization.optimized_orders,
+                    &resources.routings,
+                    &resources.setup_matrices,
+                ),
+                &resources.routings,
+                &resources.work_centers,
+                &resources.calendars,
+                shift_start,
+            )?,
+            None => Vec::new(),
+        };
+
+        Ok(OptimizedPlan {
+            raw,
+            optimization,
+            operations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bom_graph::BomGraph;
+    use chrono::NaiveDate;
+    use mrp_core::{DemandType, MrpConfig, Operation, ProcurementType, WorkCalendar};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_calculate_with_optimization_keeps_raw_plan() {
+        let date = |day: u32| NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+        let configs: HashMap<String, MrpConfig> = ["DOOR", "PANEL"]
+            .into_iter()
+            .map(|id| {
+                (
+                    id.to_string(),
+                    MrpConfig::new(id.to_string(), 2, ProcurementType::Make),
+                )
+            })
+            .collect();
+        let calculator = MrpCalculator::new(
+            BomGraph::new(),
+            configs,
+            WorkCalendar::new_24_7("24/7".to_string()),
+        );
+        let mut calendars = CalendarRegistry::new();
+        calendars.register(WorkCalendar::new_24_7("PLANT".to_string()));
+        let routing = |id: &str| {
+            (
+                id.to_string(),
+                Routing::new(id.to_string()).with_operation(Operation::new(
+                    10,
+                    "PRESS".to_string(),
+                    Decimal::ZERO,
+                    Decimal::ONE,
+                )),
+            )
+        };
+        let resources = ProductionResources::new(
+            HashMap::from([routing("DOOR"), routing("PANEL")]),
+            vec![WorkCenter::new(
+                "PRESS".to_string(),
+                "PLANT".to_string(),
+                Decimal::from(8),
+            )],
+            calendars,
+        )
+        .with_operation_scheduling(NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+
+        // 兩張 10 件的訂單都在 11/18–19 生產，每天 10 小時超過 8 小時產能
+        let demand = |id: &str, quantity: i64, day: u32| {
+            Demand::new(
+                id.to_string(),
+                Decimal::from(quantity),
+                date(day),
+                DemandType::SalesOrder,
+            )
+        };
+        let demands = vec![
+            demand("DOOR", 1, 10),
+            demand("DOOR", 10, 20),
+            demand("PANEL", 10, 20),
+        ];
+
+        let plan = calculator
+            .calculate_with_optimization(demands, vec![], vec![], &resources)
+            .unwrap();
+
+        assert_eq!(plan.raw.planned_orders.len(), 3);
+        assert!(plan.is_feasible());
+        assert_eq!(plan.optimization.shifts.len(), 1);
+        let shift = &plan.optimization.shifts[0];
+        let raw_order = plan
+            .raw
+            .planned_orders
+            .iter()
+            .find(|o| o.id == shift.order_id)
+            .unwrap();
+        // 原始計劃保持不變
+        assert_eq!(raw_order.order_date, shift.original_order_date);
+        assert_eq!(plan.operations.len(), 3);
+    }
+}
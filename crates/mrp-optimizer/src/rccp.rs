@@ -0,0 +1,167 @@
+//! 粗略產能規劃（RCCP）：在 MRP 展開前以資源清單檢查主排程的關鍵資源負荷
+//!
+//! 只看最終品項的需求與少數關鍵工作中心，不展開 BOM，成本低，適合在正式計算前
+//! 排除明顯不可行的主排程。
+
+use chrono::{Duration, NaiveDate};
+use mrp_calc::bucketing::BucketingStrategy;
+use mrp_core::{CalendarRegistry, Demand, ResourceBill, WorkCalendar, WorkCenter};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::capacity::{CapacityPlanner, WorkCenterLoad};
+
+/// 粗略產能檢查結果
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct RoughCutResult {
+    /// 各關鍵資源逐期負荷（按工作中心、起日排序）
+    pub loads: Vec<WorkCenterLoad>,
+
+    /// 沒有資源清單的需求物料
+    pub unprofiled_items: Vec<String>,
+
+    /// 資源清單引用但未定義的工作中心
+    pub unknown_work_centers: Vec<String>,
+}
+
+impl RoughCutResult {
+    /// 主排程在關鍵資源上是否可行（沒有超載的時間桶）
+    pub fn is_feasible(&self) -> bool {
+        self.overloads().next().is_none()
+    }
+
+    /// 超載的時間桶
+    pub fn overloads(&self) -> impl Iterator<Item = &WorkCenterLoad> {
+        self.loads.iter().filter(|load| load.is_overloaded())
+    }
+}
+
+/// 粗略產能規劃器
+pub struct RoughCutPlanner;
+
+impl RoughCutPlanner {
+    /// 以資源清單檢查最終品項需求（主排程）的關鍵資源負荷
+    ///
+    /// 每筆需求的負荷 = 數量 × 每單位工時（整備工時不計），計入需求日期減偏移天數所在的時間桶；
+    /// 時間桶自最早負荷日起按 `strategy` 切分。
+    pub fn check(
+        demands: &[Demand],
+        bills: &HashMap<String, ResourceBill>,
+        work_centers: &[WorkCenter],
+        calendars: &CalendarRegistry,
+        strategy: BucketingStrategy,
+    ) -> mrp_core::Result<RoughCutResult> {
+        let mut result = RoughCutResult::default();
+        let centers: HashMap<&str, &WorkCenter> =
+            work_centers.iter().map(|wc| (wc.id.as_str(), wc)).collect();
+
+        // 工作中心 → 日期 → 需求工時
+        let mut daily: BTreeMap<&str, BTreeMap<NaiveDate, Decimal>> = BTreeMap::new();
+        for demand in demands {
+            let Some(bill) = bills.get(&demand.component_id) else {
+                if !result.unprofiled_items.contains(&demand.component_id) {
+                    result.unprofiled_items.push(demand.component_id.clone());
+                }
+                continue;
+            };
+            for load in &bill.loads {
+                let Some((&id, _)) = centers.get_key_value(load.work_center_id.as_str()) else {
+                    if !result.unknown_work_centers.contains(&load.work_center_id) {
+                        result
+                            .unknown_work_centers
+                            .push(load.work_center_id.clone());
+                    }
+                    continue;
+                };
+                let date = demand.required_date - Duration::days(i64::from(load.offset_days));
+                *daily.entry(id).or_default().entry(date).or_default() +=
+                    demand.quantity * load.hours_per_unit;
+            }
+        }
+
+        let dates = daily.values().flat_map(|by_date| by_date.keys());
+        let (Some(&start), Some(&end)) = (dates.clone().min(), dates.max()) else {
+            return Ok(result);
+        };
+        let bounds = CapacityPlanner::bucket_bounds(start, end, strategy);
+
+        for (id, by_date) in daily {
+            let center = centers[id];
+            let calendar: WorkCalendar = calendars.resolve(&center.calendar_id)?;
+            for &(bucket_start, bucket_end) in &bounds {
+                result.loads.push(WorkCenterLoad {
+                    work_center_id: id.to_string(),
+                    bucket_start,
+                    bucket_end,
+                    required_hours: by_date
+                        .range(bucket_start..bucket_end)
+                        .map(|(_, hours)| *hours)
+                        .sum(),
+                    available_hours: center.available_hours_between(
+                        &calendar,
+                        bucket_start,
+                        bucket_end,
+                    ),
+                });
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mrp_core::DemandType;
+
+    #[test]
+    fn test_rough_cut_flags_infeasible_week() {
+        let mut calendars = CalendarRegistry::new();
+        calendars.register(WorkCalendar::new("PLANT".to_string()));
+        let work_centers = vec![WorkCenter::new(
+            "ASSEMBLY".to_string(),
+            "PLANT".to_string(),
+            Decimal::from(8),
+        )];
+        let bills = HashMap::from([(
+            "BIKE".to_string(),
+            ResourceBill::new("BIKE".to_string())
+                .with_load("ASSEMBLY".to_string(), Decimal::ONE, 0)
+                .with_load("PAINT".to_string(), Decimal::new(5, 1), 3),
+        )]);
+        let demand = |component_id: &str, day: u32, quantity: i64| {
+            Demand::new(
+                component_id.to_string(),
+                Decimal::from(quantity),
+                NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+                DemandType::SalesOrder,
+            )
+        };
+        // 時間桶自 11/4 起：第一週 30 小時（可用 40），第二週 50 小時
+        let demands = vec![
+            demand("BIKE", 4, 30),
+            demand("BIKE", 11, 20),
+            demand("BIKE", 13, 30),
+            demand("HELMET", 5, 1),
+        ];
+
+        let result = RoughCutPlanner::check(
+            &demands,
+            &bills,
+            &work_centers,
+            &calendars,
+            BucketingStrategy::Weekly,
+        )
+        .unwrap();
+
+        assert!(!result.is_feasible());
+        assert_eq!(result.unprofiled_items, vec!["HELMET".to_string()]);
+        assert_eq!(result.unknown_work_centers, vec!["PAINT".to_string()]);
+        let overloads: Vec<&WorkCenterLoad> = result.overloads().collect();
+        assert_eq!(overloads.len(), 1);
+        assert_eq!(overloads[0].required_hours, Decimal::from(50));
+        assert_eq!(overloads[0].overload_hours(), Decimal::from(10));
+    }
+}
@@ -1,4 +1,172 @@
-//! 排程優化
+//! 排程優化與工序層級的細部排程
+
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use mrp_core::{CalendarRegistry, MrpError, PlannedOrder, Routing, WorkCalendar, WorkCenter};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 排定的工序
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ScheduledOperation {
+    /// 計劃訂單ID
+    pub order_id: Uuid,
+
+    /// 物料ID
+    pub component_id: String,
+
+    /// 工序序號
+    pub sequence: u32,
+
+    /// 工作中心ID
+    pub work_center_id: String,
+
+    /// 分派的設備（0 起算，小於工作中心的並行設備數）
+    pub unit: u32,
+
+    /// 到達工作中心、開始排隊的時間
+    pub queue_start: NaiveDateTime,
+
+    /// 開始整備的時間
+    pub start: NaiveDateTime,
+
+    /// 加工完成的時間
+    pub end: NaiveDateTime,
+
+    /// 搬運完成的時間（下一工序最早可到達的時間）
+    pub move_end: NaiveDateTime,
+}
+
+/// 換線順序局部搜尋最大輪數
+const MAX_SETUP_PASSES: usize = 50;
+
+/// 工作中心順序相依的換線整備時間矩陣
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SetupMatrix {
+    /// 工作中心ID
+    pub work_center_id: String,
+
+    /// 未列出的兩個不同物料之間的換線時間（小時）
+    pub default_hours: Decimal,
+
+    /// (前一物料, 後一物料) → 換線時間（小時）
+    pub changeovers: HashMap<(String, String), Decimal>,
+}
+
+impl SetupMatrix {
+    /// 創建換線矩陣
+    pub fn new(work_center_id: String, default_hours: Decimal) -> Self {
+        Self {
+            work_center_id,
+            default_hours,
+            changeovers: HashMap::new(),
+        }
+    }
+
+    /// 設定由 `from` 換線到 `to` 的時間
+    pub fn with_changeover(mut self, from: String, to: String, hours: Decimal) -> Self {
+        self.changeovers.insert((from, to), hours);
+        self
+    }
+
+    /// 由 `from` 換線到 `to` 的時間：有設定用設定值，同物料為零，其餘用預設值
+    pub fn hours(&self, from: &str, to: &str) -> Decimal {
+        match self.changeovers.get(&(from.to_string(), to.to_string())) {
+            Some(&hours) => hours,
+            None if from == to => Decimal::ZERO,
+            None => self.default_hours,
+        }
+    }
+
+    /// 依序生產各物料的換線時間總和（第一張訂單不計）
+    fn total(&self, items: &[&str]) -> Decimal {
+        items
+            .windows(2)
+            .map(|pair| self.hours(pair[0], pair[1]))
+            .sum()
+    }
+}
+
+/// 工作中心的生產順序
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SetupSequence {
+    /// 工作中心ID
+    pub work_center_id: String,
+
+    /// 建議的訂單生產順序
+    pub order_ids: Vec<Uuid>,
+
+    /// 建議順序的換線時間總和（小時）
+    pub setup_hours: Decimal,
+
+    /// 原派工順序（下單日、完工日）的換線時間總和（小時）
+    pub original_setup_hours: Decimal,
+}
+
+impl SetupSequence {
+    /// 節省的換線時間（小時）
+    pub fn saved_hours(&self) -> Decimal {
+        self.original_setup_hours - self.setup_hours
+    }
+}
+
+/// 工作中心的工作時段：工作日自班次開始時間起連續 `hours_per_day` 小時
+struct WorkingTime<'a> {
+    calendar: &'a WorkCalendar,
+    shift_start: NaiveTime,
+    minutes_per_day: i64,
+}
+
+impl WorkingTime<'_> {
+    /// 包含 `time` 的工作時段結束時間（時段可跨午夜，故也檢查前一天開始的時段）
+    fn close_of(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        [time.date().pred_opt(), Some(time.date())]
+            .into_iter()
+            .flatten()
+            .filter(|date| self.calendar.is_working_day(*date))
+            .map(|date| date.and_time(self.shift_start))
+            .map(|open| (open, open + Duration::minutes(self.minutes_per_day)))
+            .find(|&(open, close)| open <= time && time < close)
+            .map(|(_, close)| close)
+    }
+
+    /// 工作時段內的時間不變；時段外移到下一個時段開始
+    fn normalize(&self, time: NaiveDateTime) -> NaiveDateTime {
+        if self.close_of(time).is_some() {
+            return time;
+        }
+        let open = time.date().and_time(self.shift_start);
+        if self.calendar.is_working_day(time.date()) && time < open {
+            return open;
+        }
+        let next = time.date().succ_opt().unwrap_or(time.date());
+        let date = if self.calendar.is_working_day(next) {
+            next
+        } else {
+            self.calendar.next_working_day(next)
+        };
+        date.and_time(self.shift_start)
+    }
+
+    /// 自 `time` 起消耗指定分鐘數的工作時間
+    fn advance(&self, time: NaiveDateTime, minutes: i64) -> NaiveDateTime {
+        let mut time = self.normalize(time);
+        let mut remaining = minutes;
+        loop {
+            let close = self.close_of(time).unwrap_or(time);
+            let available = (close - time).num_minutes();
+            if remaining <= available {
+                return time + Duration::minutes(remaining);
+            }
+            remaining -= available;
+            time = self.normalize(close);
+        }
+    }
+}
 
 /// 排程器
 pub struct Scheduler;
@@ -8,4 +176,398 @@ impl Scheduler {
     pub fn optimize() {
         // TODO: 實現排程優化
     }
+
+    /// 將生產計劃訂單拆成工序，在工作中心上順排開始／結束時間
+    ///
+    /// 訂單按下單日（同日再按完工日）依序派工；提供 [`Self::sequence_setups`] 的換線順序時，
+    /// 順序中的訂單改按建議順序佔用它們原本的派工位置。每道工序自下單日的班次開始時間起，
+    /// 依序經過排隊、整備與加工、搬運；排隊與搬運也以工作中心的工作時段計算。
+    /// 同一工作中心的工序不重疊，並行設備各自排程，工序派到最早可開工的設備；
+    /// 加工時間按工作中心效率換算。沒有工藝路線的訂單與採購訂單不排程。
+    /// 結果按派工順序（訂單、工序序號）排列。
+    pub fn schedule_operations(
+        orders: &[PlannedOrder],
+        sequences: &[SetupSequence],
+        routings: &HashMap<String, Routing>,
+        work_centers: &[WorkCenter],
+        calendars: &CalendarRegistry,
+        shift_start: NaiveTime,
+    ) -> mrp_core::Result<Vec<ScheduledOperation>> {
+        let mut centers: HashMap<&str, (&WorkCenter, WorkCalendar)> = HashMap::new();
+        for center in work_centers {
+            if center.hours_per_day <= Decimal::ZERO || center.efficiency <= Decimal::ZERO {
+                return Err(MrpError::InvalidConfig(format!(
+                    "工作中心 {} 的每日工時與效率須大於零",
+                    center.id
+                )));
+            }
+            centers.insert(
+                center.id.as_str(),
+                (center, calendars.resolve(&center.calendar_id)?),
+            );
+        }
+
+        let mut sequence: Vec<&PlannedOrder> = orders
+            .iter()
+            .filter(|o| o.is_production() && routings.contains_key(&o.component_id))
+            .collect();
+        sequence.sort_by_key(|o| (o.order_date, o.required_date));
+        for setup in sequences {
+            let rank: HashMap<Uuid, usize> = setup
+                .order_ids
+                .iter()
+                .enumerate()
+                .map(|(rank, id)| (*id, rank))
+                .collect();
+            let positions: Vec<usize> = (0..sequence.len())
+                .filter(|&i| rank.contains_key(&sequence[i].id))
+                .collect();
+            let mut reordered: Vec<&PlannedOrder> =
+                positions.iter().map(|&i| sequence[i]).collect();
+            reordered.sort_by_key(|o| rank[&o.id]);
+            for (position, order) in positions.into_iter().zip(reordered) {
+                sequence[position] = order;
+            }
+        }
+
+        // 工作中心 → 各設備可開始下一工序的時間
+        let mut free: HashMap<&str, Vec<NaiveDateTime>> = HashMap::new();
+        let mut scheduled = Vec::new();
+        for order in sequence {
+            let mut ready = order.order_date.and_time(shift_start);
+            for op in &routings[&order.component_id].operations {
+                let Some((center, calendar)) = centers.get(op.work_center_id.as_str()) else {
+                    return Err(MrpError::InvalidConfig(format!(
+                        "{} 的工序 {} 引用未定義的工作中心 {}",
+                        order.component_id, op.sequence, op.work_center_id
+                    )));
+                };
+                let time = WorkingTime {
+                    calendar,
+                    shift_start,
+                    minutes_per_day: Self::minutes(center.hours_per_day).clamp(1, 24 * 60),
+                };
+
+                let queue_start = time.normalize(ready);
+                let queued = time.advance(queue_start, Self::minutes(op.queue_hours));
+                let units = free.entry(center.id.as_str()).or_insert_with(|| {
+                    vec![NaiveDateTime::MIN; center.parallel_units.max(1) as usize]
+                });
+                let (unit, start) = units
+                    .iter()
+                    .enumerate()
+                    .map(|(unit, &available)| (unit, time.normalize(available.max(queued))))
+                    .min_by_key(|&(unit, start)| (start, unit))
+                    .expect("工作中心至少有一台設備");
+                let end = time.advance(
+                    start,
+                    Self::minutes(op.required_hours(order.quantity) / center.efficiency),
+                );
+                units[unit] = end;
+                let move_end = time.advance(end, Self::minutes(op.move_hours));
+
+                scheduled.push(ScheduledOperation {
+                    order_id: order.id,
+                    component_id: order.component_id.clone(),
+                    sequence: op.sequence,
+                    work_center_id: op.work_center_id.clone(),
+                    unit: unit as u32,
+                    queue_start,
+                    start,
+                    end,
+                    move_end,
+                });
+                ready = move_end;
+            }
+        }
+
+        Ok(scheduled)
+    }
+
+    /// 排定共用工作中心的生產訂單順序，使順序相依的換線時間總和最小
+    ///
+    /// 每個換線矩陣對應一個工作中心，工藝路線經過該工作中心的生產訂單參與排序。
+    /// 先以最近鄰法（逐一嘗試每張訂單作為起點）建立順序，將相似物料排在一起，
+    /// 再逐張訂單嘗試移到其他位置，直到沒有移動能再減少換線時間；結果不會比原派工順序差。
+    /// 排序只考慮換線時間，不檢查完工日，排序後應再以 [`Self::schedule_operations`] 確認交期。
+    pub fn sequence_setups(
+        orders: &[PlannedOrder],
+        routings: &HashMap<String, Routing>,
+        matrices: &[SetupMatrix],
+    ) -> Vec<SetupSequence> {
+        matrices
+            .iter()
+            .map(|matrix| {
+                let mut jobs: Vec<&PlannedOrder> = orders
+                    .iter()
+                    .filter(|o| o.is_production())
+                    .filter(|o| {
+                        routings.get(&o.component_id).is_some_and(|routing| {
+                            routing
+                                .operations
+                                .iter()
+                                .any(|op| op.work_center_id == matrix.work_center_id)
+                        })
+                    })
+                    .collect();
+                jobs.sort_by_key(|o| (o.order_date, o.required_date));
+
+                let items: Vec<&str> = jobs.iter().map(|o| o.component_id.as_str()).collect();
+                let cost = |sequence: &[usize]| {
+                    matrix.total(&sequence.iter().map(|&i| items[i]).collect::<Vec<_>>())
+                };
+
+                let original: Vec<usize> = (0..jobs.len()).collect();
+                let mut best = original.clone();
+                let mut best_cost = cost(&best);
+                for start in 0..jobs.len() {
+                    let candidate = Self::nearest_neighbour(start, &items, matrix);
+                    let candidate_cost = cost(&candidate);
+                    if candidate_cost < best_cost {
+                        best = candidate;
+                        best_cost = candidate_cost;
+                    }
+                }
+
+                for _ in 0..MAX_SETUP_PASSES {
+                    let mut improved = false;
+                    for from in 0..best.len() {
+                        for to in 0..best.len() {
+                            if from == to {
+                                continue;
+                            }
+                            let mut candidate = best.clone();
+                            let job = candidate.remove(from);
+                            candidate.insert(to, job);
+                            let candidate_cost = cost(&candidate);
+                            if candidate_cost < best_cost {
+                                best = candidate;
+                                best_cost = candidate_cost;
+                                improved = true;
+                            }
+                        }
+                    }
+                    if !improved {
+                        break;
+                    }
+                }
+
+                SetupSequence {
+                    work_center_id: matrix.work_center_id.clone(),
+                    order_ids: best.iter().map(|&i| jobs[i].id).collect(),
+                    setup_hours: best_cost,
+                    original_setup_hours: cost(&original),
+                }
+            })
+            .collect()
+    }
+
+    /// 自 `start` 起每次接換線時間最短的訂單（同換線時間取原順序較前者）
+    fn nearest_neighbour(start: usize, items: &[&str], matrix: &SetupMatrix) -> Vec<usize> {
+        let mut sequence = vec![start];
+        let mut remaining: Vec<usize> = (0..items.len()).filter(|&i| i != start).collect();
+        while !remaining.is_empty() {
+            let last = items[sequence[sequence.len() - 1]];
+            let (position, _) = remaining
+                .iter()
+                .enumerate()
+                .min_by_key(|&(_, &i)| (matrix.hours(last, items[i]), i))
+                .expect("尚有未排序的訂單");
+            sequence.push(remaining.remove(position));
+        }
+        sequence
+    }
+
+    /// 工時換算為分鐘（四捨五入，負值視為零）
+    fn minutes(hours: Decimal) -> i64 {
+        (hours * Decimal::from(60))
+            .round()
+            .to_i64()
+            .unwrap_or(0)
+            .max(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::{Operation, PlannedOrderType};
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 11, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_schedule_operations_across_weekend() {
+        let mut calendars = CalendarRegistry::new();
+        calendars.register(WorkCalendar::new("PLANT".to_string()));
+        let work_centers = vec![
+            WorkCenter::new("CUT".to_string(), "PLANT".to_string(), Decimal::from(8)),
+            WorkCenter::new("WELD".to_string(), "PLANT".to_string(), Decimal::from(8)),
+        ];
+        let routings = HashMap::from([(
+            "FRAME".to_string(),
+            Routing::new("FRAME".to_string())
+                .with_operation(
+                    Operation::new(10, "CUT".to_string(), Decimal::ONE, Decimal::new(5, 1))
+                        .with_queue_and_move(Decimal::from(2), Decimal::ONE),
+                )
+                .with_operation(Operation::new(
+                    20,
+                    "WELD".to_string(),
+                    Decimal::ZERO,
+                    Decimal::ONE,
+                )),
+        )]);
+        // 兩張訂單都在 11/7（週五）下單
+        let order = |quantity: i64, need_day: u32| {
+            PlannedOrder::new(
+                "FRAME".to_string(),
+                Decimal::from(quantity),
+                NaiveDate::from_ymd_opt(2025, 11, need_day).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 11, 7).unwrap(),
+                PlannedOrderType::Production,
+            )
+        };
+        let orders = vec![order(2, 14), order(10, 12)];
+
+        let operations = Scheduler::schedule_operations(
+            &orders,
+            &[],
+            &routings,
+            &work_centers,
+            &calendars,
+            NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+        )
+        .unwrap();
+
+        // 較早完工的 10 件先派工：排隊 2 小時，10:00–16:00 切割，搬運跨週末到週一 09:00
+        assert_eq!(operations.len(), 4);
+        let cut = &operations[0];
+        assert_eq!(cut.order_id, orders[1].id);
+        assert_eq!((cut.start, cut.end), (at(7, 10), at(7, 16)));
+        assert_eq!(cut.move_end, at(10, 9));
+        // 焊接 10 小時：週一剩 7 小時，週二 11:00 完成
+        assert_eq!(
+            (operations[1].start, operations[1].end),
+            (at(10, 9), at(11, 11))
+        );
+        // 2 件的訂單等切割機空出，週一 08:00 開工
+        assert_eq!(operations[2].order_id, orders[0].id);
+        assert_eq!(
+            (operations[2].start, operations[2].end),
+            (at(10, 8), at(10, 10))
+        );
+        // 焊接須等第一張訂單完成
+        assert_eq!(operations[3].start, at(11, 11));
+    }
+
+    #[test]
+    fn test_sequence_setups_groups_similar_items() {
+        let routing = |component: &str| {
+            (
+                component.to_string(),
+                Routing::new(component.to_string()).with_operation(Operation::new(
+                    10,
+                    "PRESS".to_string(),
+                    Decimal::ZERO,
+                    Decimal::ONE,
+                )),
+            )
+        };
+        let routings = HashMap::from([routing("RED"), routing("BLUE"), routing("BLACK")]);
+        // 淺色換深色 1 小時，深色換淺色需清洗 3 小時
+        let matrix = SetupMatrix::new("PRESS".to_string(), Decimal::from(3))
+            .with_changeover("RED".to_string(), "BLUE".to_string(), Decimal::ONE)
+            .with_changeover("BLUE".to_string(), "BLACK".to_string(), Decimal::ONE)
+            .with_changeover("RED".to_string(), "BLACK".to_string(), Decimal::ONE);
+        let orders: Vec<PlannedOrder> = [("BLACK", 3), ("RED", 4), ("BLUE", 5), ("RED", 6)]
+            .into_iter()
+            .map(|(component, day)| {
+                PlannedOrder::new(
+                    component.to_string(),
+                    Decimal::TEN,
+                    NaiveDate::from_ymd_opt(2025, 11, day + 7).unwrap(),
+                    NaiveDate::from_ymd_opt(2025, 11, day).unwrap(),
+                    PlannedOrderType::Production,
+                )
+            })
+            .collect();
+
+        let sequences = Scheduler::sequence_setups(&orders, &routings, &[matrix]);
+
+        // 原順序 BLACK→RED→BLUE→RED：3 + 1 + 3 = 7 小時；建議 RED→RED→BLUE→BLACK：2 小時
+        assert_eq!(sequences.len(), 1);
+        let sequence = &sequences[0];
+        assert_eq!(
+            sequence.order_ids,
+            vec![orders[1].id, orders[3].id, orders[2].id, orders[0].id]
+        );
+        assert_eq!(sequence.setup_hours, Decimal::from(2));
+        assert_eq!(sequence.saved_hours(), Decimal::from(5));
+    }
+
+    #[test]
+    fn test_schedule_operations_follows_setup_sequence() {
+        let mut calendars = CalendarRegistry::new();
+        calendars.register(WorkCalendar::new_24_7("PLANT".to_string()));
+        let work_centers = vec![WorkCenter::new(
+            "PRESS".to_string(),
+            "PLANT".to_string(),
+            Decimal::from(8),
+        )];
+        let routing = |component: &str| {
+            (
+                component.to_string(),
+                Routing::new(component.to_string()).with_operation(Operation::new(
+                    10,
+                    "PRESS".to_string(),
+                    Decimal::ZERO,
+                    Decimal::new(1, 1),
+                )),
+            )
+        };
+        let routings = HashMap::from([routing("RED"), routing("BLACK")]);
+        let matrix = SetupMatrix::new("PRESS".to_string(), Decimal::from(3)).with_changeover(
+            "RED".to_string(),
+            "BLACK".to_string(),
+            Decimal::ONE,
+        );
+        // 同日下單：BLACK 較早完工，原派工順序為 BLACK→RED
+        let order = |component: &str, need_day: u32| {
+            PlannedOrder::new(
+                component.to_string(),
+                Decimal::TEN,
+                NaiveDate::from_ymd_opt(2025, 11, need_day).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 11, 3).unwrap(),
+                PlannedOrderType::Production,
+            )
+        };
+        let orders = vec![order("BLACK", 5), order("RED", 6)];
+        let sequences = Scheduler::sequence_setups(&orders, &routings, &[matrix]);
+        assert_eq!(sequences[0].order_ids, vec![orders[1].id, orders[0].id]);
+
+        let operations = Scheduler::schedule_operations(
+            &orders,
+            &sequences,
+            &routings,
+            &work_centers,
+            &calendars,
+            NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+        )
+        .unwrap();
+
+        // 按換線順序先壓 RED，BLACK 接在其後
+        assert_eq!(operations[0].order_id, orders[1].id);
+        assert_eq!(
+            (operations[0].start, operations[0].end),
+            (at(3, 8), at(3, 9))
+        );
+        assert_eq!(operations[1].order_id, orders[0].id);
+        assert_eq!(operations[1].start, at(3, 9));
+    }
 }
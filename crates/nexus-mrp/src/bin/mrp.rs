@@ -0,0 +1,310 @@
+//! `mrp` 命令列入口
+//!
+//! - `mrp rpc`：以標準輸入／輸出執行逐行 JSON-RPC 協定（見 [`nexus_mrp::protocol`]）
+//! - `mrp watch <模型檔> <需求目錄> <供應目錄> <輸出目錄> [輪詢秒數]`：常駐監看輸入目錄的
+//!   CSV 檔，有變更時重新計劃並將結果寫入輸出目錄（見 [`nexus_mrp::cache::WatchDaemon`]）
+//! - `mrp shell <模型檔> <需求目錄> <供應目錄> [計劃檔]`：以輸入目錄的 CSV 檔重新計算
+//!   （或載入 `plan.json` 計劃檔）後進入查詢殼層（見 [`nexus_mrp::calc::PlanShell`]）
+//! - `mrp init --sample <目錄>`：以固定種子產生範例資料（見 [`mrp_examples::SampleDataset`]），
+//!   在目錄下寫出模型檔 `model.jsonl` 與 `demands`、`supplies` 輸入目錄，可直接交給 `watch` 與 `shell`
+//!
+//! 模型檔為逐行 JSON-RPC 請求（通常是 `configure` 與 `load_bom`），啟動時依序執行；
+//! 任一請求回應錯誤即停止啟動。庫存以 `OnHand` 類型的供應列放在供應目錄。
+
+use mrp_examples::{SampleDataset, SampleSpec};
+use nexus_mrp::cache::{AutoReplanner, WatchDaemon};
+use nexus_mrp::calc::{BomProvider, PlanShell, PlanSnapshot};
+use nexus_mrp::{BomGraph, ProtocolServer, WorkCalendar};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+const USAGE: &str = "\
+用法: mrp rpc
+      mrp watch <模型檔> <需求目錄> <供應目錄> <輸出目錄> [輪詢秒數]
+      mrp shell <模型檔> <需求目錄> <供應目錄> [計劃檔]
+      mrp init --sample <目錄>";
+
+/// 預設輪詢間隔（秒）
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let outcome = match args.as_slice() {
+        ["rpc", ..] => rpc(),
+        ["watch", model, demand_dir, supply_dir, output_dir] => watch(
+            model,
+            demand_dir,
+            supply_dir,
+            output_dir,
+            DEFAULT_INTERVAL_SECS,
+        ),
+        ["watch", model, demand_dir, supply_dir, output_dir, seconds] => match seconds.parse() {
+            Ok(seconds) if seconds > 0 => watch(model, demand_dir, supply_dir, output_dir, seconds),
+            _ => return usage(),
+        },
+        ["shell", model, demand_dir, supply_dir] => shell(model, demand_dir, supply_dir, None),
+        ["shell", model, demand_dir, supply_dir, plan] => {
+            shell(model, demand_dir, supply_dir, Some(plan))
+        }
+        ["init", "--sample", dir] => init_sample(Path::new(dir)),
+        _ => return usage(),
+    };
+    match outcome {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("mrp: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("{}", USAGE);
+    ExitCode::from(2)
+}
+
+/// 以空 BOM 與預設日曆啟動協定伺服器，BOM 與物料配置由宿主程序以請求載入
+fn rpc() -> io::Result<()> {
+    let mut server =
+        ProtocolServer::new(Arc::new(BomGraph::new()), WorkCalendar::fallback_calendar());
+    server.serve(io::stdin().lock(), io::stdout().lock())
+}
+
+/// 依模型檔建立引擎後常駐監看，直到程序被終止
+fn watch(
+    model: &str,
+    demand_dir: &str,
+    supply_dir: &str,
+    output_dir: &str,
+    interval_secs: u64,
+) -> io::Result<()> {
+    let server = load_model(model)?;
+    let replanner = AutoReplanner::new(demand_dir.into(), supply_dir.into());
+    let mut daemon = WatchDaemon::new(replanner, output_dir.into());
+    eprintln!(
+        "監看 {} 與 {}，每 {} 秒掃描，結果寫入 {}",
+        demand_dir, supply_dir, interval_secs, output_dir
+    );
+    daemon.run(
+        server.engine().calculator(),
+        Duration::from_secs(interval_secs),
+        |outcome| match outcome {
+            Ok(cycle) => {
+                for (path, e) in &cycle.failures {
+                    eprintln!("mrp: {}: {}（沿用上次匯入的資料）", path.display(), e);
+                }
+                if let Some(dirty) = cycle.replanned {
+                    eprintln!("已重新計劃（受影響物料 {} 項）", dirty.len());
+                }
+            }
+            Err(e) => eprintln!("mrp: {}", e),
+        },
+    )
+}
+
+/// 匯入輸入目錄後進入查詢殼層，直到 `quit` 或輸入結束
+///
+/// 未指定計劃檔時以輸入重新計算；指定時載入計劃檔，輸入只用於 MRP 表格與 what-if。
+fn shell(model: &str, demand_dir: &str, supply_dir: &str, plan: Option<&str>) -> io::Result<()> {
+    let calculator = load_model(model)?.into_engine().into_calculator();
+    let mut replanner = AutoReplanner::new(demand_dir.into(), supply_dir.into());
+    let report = replanner.poll().map_err(io::Error::other)?;
+    if let Some((_, e)) = report.failures.into_iter().next() {
+        return Err(io::Error::other(e));
+    }
+    let (demands, supplies) = (replanner.demands(), replanner.supplies());
+
+    let shell = match plan {
+        Some(plan) => PlanShell::load(&fs::read_to_string(plan)?),
+        None => calculator
+            .calculate(demands.clone(), supplies.clone(), vec![])
+            .map(|result| PlanShell::new(PlanSnapshot::new(result))),
+    }
+    .map_err(io::Error::other)?;
+    shell
+        .with_what_if(calculator, demands, supplies, vec![])
+        .run(io::stdin().lock(), io::stdout().lock())
+        .map_err(io::Error::other)
+}
+
+/// 在目錄下寫出範例資料：模型檔（`configure` 與 `load_bom` 請求）、需求檔與供應檔
+///
+/// 庫存寫成供應檔中計劃起始日的 `OnHand` 列。不覆寫既有檔案。
+fn init_sample(dir: &Path) -> io::Result<()> {
+    let spec = SampleSpec::default();
+    let dataset = SampleDataset::generate(&spec);
+
+    let mut items: Vec<&String> = dataset.configs.keys().collect();
+    items.sort();
+    let configs: Vec<_> = items.iter().map(|id| &dataset.configs[*id]).collect();
+    let lines: Vec<Value> = items
+        .iter()
+        .flat_map(|id| dataset.bom_graph.children(id))
+        .map(|item| {
+            json!({
+                "parent_id": item.parent_id.as_str(),
+                "child_id": item.child_id.as_str(),
+                "quantity": item.quantity,
+                "scrap_factor": item.scrap_factor,
+            })
+        })
+        .collect();
+    let model = format!(
+        "{}\n{}\n",
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "configure",
+            "params": {"configs": configs, "calendar": dataset.calendar},
+        }),
+        json!({"jsonrpc": "2.0", "id": 2, "method": "load_bom", "params": {"items": lines}}),
+    );
+
+    let mut demands = String::from("component_id,quantity,required_date,demand_type\n");
+    for demand in &dataset.demands {
+        let _ = writeln!(
+            demands,
+            "{},{},{},{:?}",
+            demand.component_id, demand.quantity, demand.required_date, demand.demand_type
+        );
+    }
+    let mut supplies = String::from("component_id,quantity,available_date,supply_type\n");
+    for inventory in dataset
+        .inventories
+        .iter()
+        .filter(|i| !i.on_hand_qty.is_zero())
+    {
+        let _ = writeln!(
+            supplies,
+            "{},{},{},OnHand",
+            inventory.component_id, inventory.on_hand_qty, spec.start_date
+        );
+    }
+    for supply in &dataset.supplies {
+        let _ = writeln!(
+            supplies,
+            "{},{},{},{:?}",
+            supply.component_id, supply.quantity, supply.available_date, supply.supply_type
+        );
+    }
+
+    fs::create_dir_all(dir.join("demands"))?;
+    fs::create_dir_all(dir.join("supplies"))?;
+    create_new(&dir.join("model.jsonl"), &model)?;
+    create_new(&dir.join("demands").join("sample.csv"), &demands)?;
+    create_new(&dir.join("supplies").join("sample.csv"), &supplies)?;
+    eprintln!(
+        "已寫出範例資料（{} 項物料、{} 筆需求）到 {}，可執行：\n  mrp shell {} {} {}",
+        dataset.item_count(),
+        dataset.demands.len(),
+        dir.display(),
+        dir.join("model.jsonl").display(),
+        dir.join("demands").display(),
+        dir.join("supplies").display()
+    );
+    Ok(())
+}
+
+/// 寫出新檔案（檔案已存在時回傳錯誤）
+fn create_new(path: &Path, contents: &str) -> io::Result<()> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+/// 以空 BOM 與預設日曆建立協定伺服器，依序執行模型檔的請求
+fn load_model(model: &str) -> io::Result<ProtocolServer> {
+    let mut server =
+        ProtocolServer::new(Arc::new(BomGraph::new()), WorkCalendar::fallback_calendar());
+    for line in fs::read_to_string(model)?.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(response) = server.handle_line(line) else {
+            continue;
+        };
+        let response: Value = serde_json::from_str(&response)?;
+        if let Some(error) = response.get("error") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", model, error["message"]),
+            ));
+        }
+    }
+    Ok(server)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_mrp::calc::CsvImporter;
+    use nexus_mrp::SupplyType;
+
+    #[test]
+    fn test_init_sample_writes_loadable_inputs() {
+        let dir = std::env::temp_dir().join(format!("mrp-init-{}", uuid::Uuid::new_v4()));
+        init_sample(&dir).unwrap();
+        let dataset = SampleDataset::generate(&SampleSpec::default());
+
+        // 模型檔：物料配置與 BOM 行數與資料集一致，且可由 load_model 重播
+        let model = fs::read_to_string(dir.join("model.jsonl")).unwrap();
+        let requests: Vec<Value> = model
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            requests[0]["params"]["configs"].as_array().unwrap().len(),
+            dataset.item_count()
+        );
+        assert_eq!(
+            requests[1]["params"]["items"].as_array().unwrap().len(),
+            dataset.bom_graph.arena().edge_count()
+        );
+        let calculator = load_model(dir.join("model.jsonl").to_str().unwrap())
+            .unwrap()
+            .into_engine()
+            .into_calculator();
+
+        // 需求與供應檔可匯入，庫存成為 OnHand 供應
+        let importer = CsvImporter::new();
+        let read = |path: &str| fs::read(dir.join(path)).unwrap();
+        let demands = importer
+            .import_demands(&read("demands/sample.csv"))
+            .unwrap();
+        let supplies = importer
+            .import_supplies(&read("supplies/sample.csv"))
+            .unwrap();
+        assert_eq!(demands.len(), dataset.demands.len());
+        assert_eq!(
+            supplies
+                .iter()
+                .filter(|s| s.supply_type == SupplyType::PurchaseOrder)
+                .count(),
+            dataset.supplies.len()
+        );
+        assert!(supplies.iter().any(|s| s.supply_type == SupplyType::OnHand));
+
+        let result = calculator.calculate(demands, supplies, vec![]).unwrap();
+        assert!(result
+            .planned_orders
+            .iter()
+            .any(|o| o.component_id.starts_with("RM-")));
+
+        // 不覆寫既有檔案
+        assert_eq!(
+            init_sample(&dir).unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
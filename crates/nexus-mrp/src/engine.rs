@@ -0,0 +1,142 @@
+//! 一站式 MRP 引擎
+
+use bom_graph::BomGraph;
+use mrp_calc::{MrpCalculator, MrpResult};
+use mrp_core::{Demand, Inventory, MrpConfig, Supply, WorkCalendar};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// MRP 引擎（封裝 BOM 圖、物料配置與工作日曆）
+pub struct Engine {
+    calculator: MrpCalculator,
+}
+
+impl Engine {
+    /// 創建引擎建構器
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::new()
+    }
+
+    /// 執行 MRP 計算
+    pub fn run(
+        &self,
+        demands: Vec<Demand>,
+        supplies: Vec<Supply>,
+        inventories: Vec<Inventory>,
+    ) -> mrp_core::Result<MrpResult> {
+        self.calculator.calculate(demands, supplies, inventories)
+    }
+
+    /// 獲取底層計算器引用
+    pub fn calculator(&self) -> &MrpCalculator {
+        &self.calculator
+    }
+
+    /// 取出底層計算器
+    pub fn into_calculator(self) -> MrpCalculator {
+        self.calculator
+    }
+}
+
+/// 引擎建構器
+pub struct EngineBuilder {
+    bom_graph: Option<Arc<BomGraph>>,
+    configs: HashMap<String, MrpConfig>,
+    calendar: Option<WorkCalendar>,
+}
+
+impl EngineBuilder {
+    /// 創建新的建構器
+    pub fn new() -> Self {
+        Self {
+            bom_graph: None,
+            configs: HashMap::new(),
+            calendar: None,
+        }
+    }
+
+    /// 建構器模式：設置 BOM 圖（未設置時使用空圖，即單層計算）
+    pub fn with_bom_graph(mut self, bom_graph: BomGraph) -> Self {
+        self.bom_graph = Some(Arc::new(bom_graph));
+        self
+    }
+
+    /// 建構器模式：設置共享的 BOM 圖（多個引擎共用同一份 BOM，不複製）
+    pub fn with_shared_bom_graph(mut self, bom_graph: Arc<BomGraph>) -> Self {
+        self.bom_graph = Some(bom_graph);
+        self
+    }
+
+    /// 建構器模式：添加單一物料配置
+    pub fn with_config(mut self, config: MrpConfig) -> Self {
+        self.configs.insert(config.component_id.clone(), config);
+        self
+    }
+
+    /// 建構器模式：批量添加物料配置
+    pub fn with_configs(mut self, configs: impl IntoIterator<Item = MrpConfig>) -> Self {
+        for config in configs {
+            self.configs.insert(config.component_id.clone(), config);
+        }
+        self
+    }
+
+    /// 建構器模式：設置工作日曆（未設置時使用預設週一到週五日曆）
+    pub fn with_calendar(mut self, calendar: WorkCalendar) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
+    /// 建立引擎
+    pub fn build(self) -> Engine {
+        Engine {
+            calculator: MrpCalculator::with_shared_bom(
+                self.bom_graph.unwrap_or_else(|| Arc::new(BomGraph::new())),
+                self.configs,
+                self.calendar.unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+impl Default for EngineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::{DemandType, ProcurementType};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_engine_single_item() {
+        let engine = Engine::builder()
+            .with_config(MrpConfig::new(
+                "BIKE-001".to_string(),
+                5,
+                ProcurementType::Make,
+            ))
+            .with_calendar(WorkCalendar::fallback_calendar())
+            .build();
+
+        let demands = vec![Demand::new(
+            "BIKE-001".to_string(),
+            Decimal::from(100),
+            NaiveDate::from_ymd_opt(2025, 11, 20).unwrap(),
+            DemandType::SalesOrder,
+        )];
+
+        let result = engine.run(demands, vec![], vec![]).unwrap();
+
+        assert_eq!(result.planned_orders.len(), 1);
+        assert_eq!(result.planned_orders[0].quantity, Decimal::from(100));
+        assert_eq!(
+            result.planned_orders[0].order_date,
+            NaiveDate::from_ymd_opt(2025, 11, 15).unwrap()
+        );
+    }
+}
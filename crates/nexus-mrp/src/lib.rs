@@ -0,0 +1,55 @@
+//! # NexusMRP
+//!
+//! 門面 crate：匯出各子 crate 的精選 API，並提供 `Engine::builder()` 一站式入口
+//!
+//! ## API 穩定性
+//!
+//! 本 crate 根層與 [`prelude`] 匯出的項目屬於受 semver 保護的公開 API：
+//! - 列舉與資料結構標記為 `#[non_exhaustive]`，新增變體或欄位不算破壞性變更，
+//!   下游 `match` 需保留萬用分支，結構體請使用建構函式與 `with_*` 建構器方法
+//! - 透過 [`model`]、[`calc`] 等子 crate 別名存取的其他項目屬於進階 API，可能隨版本調整
+//!
+//! 詳見 `docs/API_STABILITY.md`。
+//!
+//! ```no_run
+//! use chrono::NaiveDate;
+//! use nexus_mrp::prelude::*;
+//! use rust_decimal::Decimal;
+//!
+//! let engine = Engine::builder()
+//!     .with_bom_graph(BomGraph::new())
+//!     .with_config(MrpConfig::new("BIKE-001".to_string(), 5, ProcurementType::Make))
+//!     .build();
+//!
+//! let demand = Demand::new(
+//!     "BIKE-001".to_string(),
+//!     Decimal::from(100),
+//!     NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+//!     DemandType::SalesOrder,
+//! );
+//!
+//! let result = engine.run(vec![demand], vec![], vec![]).unwrap();
+//! println!("計劃訂單: {}", result.planned_orders.len());
+//! ```
+
+pub mod engine;
+pub mod prelude;
+pub mod protocol;
+
+// 子 crate 完整匯出（進階使用）
+pub use mrp_cache as cache;
+pub use mrp_calc as calc;
+pub use mrp_core as model;
+pub use mrp_optimizer as optimizer;
+
+// 精選 API
+pub use bom_graph::BomGraph;
+pub use engine::{Engine, EngineBuilder};
+pub use mrp_calc::{
+    CoverageSourceType, DemandCoverage, MrpCalculator, MrpResult, MrpWarning, WarningSeverity,
+};
+pub use mrp_core::{
+    Demand, DemandType, Inventory, LotSizingRule, MrpConfig, MrpError, PeggingRecord, PlannedOrder,
+    PlannedOrderType, ProcurementType, Result, Supply, SupplyType, WorkCalendar,
+};
+pub use protocol::ProtocolServer;
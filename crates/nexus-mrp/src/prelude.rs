@@ -0,0 +1,63 @@
+//! 常用類型預匯入
+//!
+//! ```
+//! use nexus_mrp::prelude::*;
+//! ```
+//!
+//! 不包含 `Result` 別名，避免遮蔽標準庫的 `Result`。
+
+pub use crate::engine::{Engine, EngineBuilder};
+pub use bom_graph::BomGraph;
+pub use mrp_calc::{DemandCoverage, MrpResult, MrpWarning, WarningSeverity};
+pub use mrp_core::{
+    Demand, DemandType, Inventory, LotSizingRule, MrpConfig, MrpError, PlannedOrder,
+    PlannedOrderType, ProcurementType, Supply, SupplyType, WorkCalendar,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    fn describe(rule: LotSizingRule) -> &'static str {
+        // 下游 crate 對 #[non_exhaustive] 列舉必須保留萬用分支
+        match rule {
+            LotSizingRule::LotForLot => "批對批",
+            LotSizingRule::FixedOrderQuantity => "固定批量",
+            _ => "其他",
+        }
+    }
+
+    #[test]
+    fn test_prelude_runs_engine_with_builders() {
+        let engine = Engine::builder()
+            .with_bom_graph(BomGraph::new())
+            .with_config(MrpConfig::new(
+                "BIKE-001".to_string(),
+                5,
+                ProcurementType::Make,
+            ))
+            .with_calendar(WorkCalendar::fallback_calendar())
+            .build();
+        let demands = vec![Demand::new(
+            "BIKE-001".to_string(),
+            Decimal::from(100),
+            NaiveDate::from_ymd_opt(2025, 11, 20).unwrap(),
+            DemandType::SalesOrder,
+        )];
+
+        let result: MrpResult = engine.run(demands, vec![], vec![]).unwrap();
+
+        let orders: &[PlannedOrder] = &result.planned_orders;
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].quantity, Decimal::from(100));
+        assert!(result
+            .warnings
+            .iter()
+            .all(|w: &MrpWarning| w.severity != WarningSeverity::Error));
+
+        let config = MrpConfig::new("WHEEL-001".to_string(), 1, ProcurementType::Buy);
+        assert_eq!(describe(config.lot_sizing_rule), "批對批");
+    }
+}
@@ -0,0 +1,354 @@
+//! 逐行 JSON-RPC 協定：以子程序方式嵌入引擎（桌面程式、Electron 前端不需連結 FFI）
+//!
+//! 每行一個 JSON-RPC 2.0 請求，每個請求回應一行；沒有 `id` 的通知照常執行但不回應。支援的方法：
+//! - `configure`：`{"configs": [MrpConfig...], "calendar": WorkCalendar?}`，重建引擎
+//! - `load_bom`：`{"items": [{"parent_id", "child_id", "quantity", "scrap_factor"?}...]}`，取代 BOM 圖
+//! - `calculate`：`{"demands": [...], "supplies": [...], "inventories": [...], "policy": PlanningPolicy?}`
+//! - `shutdown`：回應後結束 `serve`
+//!
+//! 建立伺服器時的 BOM 圖為初始 BOM；`mrp rpc` 以空 BOM 啟動，由 `load_bom` 載入。
+
+use bom_core::{BomItem, ComponentId};
+use bom_graph::BomGraph;
+use mrp_calc::{MrpWarning, PlanningPolicy};
+use mrp_core::{Demand, Inventory, MrpConfig, PlannedOrder, Supply, WorkCalendar};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+
+use crate::engine::Engine;
+
+/// JSON 解析錯誤
+pub const PARSE_ERROR: i64 = -32700;
+/// 未知方法
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// 參數錯誤
+pub const INVALID_PARAMS: i64 = -32602;
+/// MRP 計算錯誤
+pub const CALCULATION_ERROR: i64 = -32000;
+
+#[derive(Deserialize)]
+struct Request {
+    /// 請求ID（缺少時為通知；`null` 仍是請求）
+    #[serde(default, deserialize_with = "present")]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct ConfigureParams {
+    configs: Vec<MrpConfig>,
+    #[serde(default)]
+    calendar: Option<WorkCalendar>,
+}
+
+#[derive(Deserialize)]
+struct LoadBomParams {
+    items: Vec<BomLine>,
+}
+
+#[derive(Deserialize)]
+struct BomLine {
+    parent_id: String,
+    child_id: String,
+    quantity: Decimal,
+    #[serde(default)]
+    scrap_factor: Decimal,
+}
+
+#[derive(Deserialize)]
+struct CalculateParams {
+    demands: Vec<Demand>,
+    #[serde(default)]
+    supplies: Vec<Supply>,
+    #[serde(default)]
+    inventories: Vec<Inventory>,
+    #[serde(default)]
+    policy: Option<PlanningPolicy>,
+}
+
+#[derive(Serialize)]
+struct CalculateResult<'a> {
+    planned_orders: &'a [PlannedOrder],
+    warnings: &'a [MrpWarning],
+    unprocessed_components: &'a [String],
+    calculation_time_ms: Option<u128>,
+}
+
+/// 協定伺服器
+pub struct ProtocolServer {
+    bom_graph: Arc<BomGraph>,
+    configs: Vec<MrpConfig>,
+    calendar: WorkCalendar,
+    engine: Engine,
+    shutdown: bool,
+}
+
+impl ProtocolServer {
+    /// 創建伺服器（尚未配置物料時，所有物料使用預設配置）
+    pub fn new(bom_graph: Arc<BomGraph>, calendar: WorkCalendar) -> Self {
+        let engine = Engine::builder()
+            .with_shared_bom_graph(Arc::clone(&bom_graph))
+            .with_calendar(calendar.clone())
+            .build();
+        Self {
+            bom_graph,
+            configs: Vec::new(),
+            calendar,
+            engine,
+            shutdown: false,
+        }
+    }
+
+    /// 逐行讀取請求並寫出回應，直到輸入結束或收到 `shutdown`
+    pub fn serve<R: BufRead, W: Write>(&mut self, reader: R, mut writer: W) -> io::Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(writer, "{}", response)?;
+                writer.flush()?;
+            }
+            if self.shutdown {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// 處理單行請求，回傳單行回應（通知不回應，回傳 None）
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        match serde_json::from_str::<Request>(line) {
+            Ok(request) => {
+                let outcome = self.dispatch(&request.method, request.params);
+                request.id.map(|id| Self::response(id, outcome).to_string())
+            }
+            Err(e) => {
+                Some(Self::response(Value::Null, Err((PARSE_ERROR, e.to_string()))).to_string())
+            }
+        }
+    }
+
+    /// 目前的引擎（反映已執行的 `configure` 與 `load_bom`）
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// 結束伺服器並取出引擎
+    pub fn into_engine(self) -> Engine {
+        self.engine
+    }
+
+    fn dispatch(&mut self, method: &str, params: Value) -> Result<Value, (i64, String)> {
+        match method {
+            "configure" => {
+                let params: ConfigureParams = Self::params(params)?;
+                if let Some(calendar) = params.calendar {
+                    self.calendar = calendar;
+                }
+                let configured = params.configs.len();
+                self.configs = params.configs;
+                self.rebuild();
+                Ok(json!({ "configured": configured }))
+            }
+            "load_bom" => {
+                let params: LoadBomParams = Self::params(params)?;
+                let loaded = params.items.len();
+                let mut bom_graph = BomGraph::new();
+                for line in params.items {
+                    bom_graph
+                        .add_bom_item(Self::bom_item(line))
+                        .map_err(|e| (INVALID_PARAMS, e.to_string()))?;
+                }
+                self.bom_graph = Arc::new(bom_graph);
+                self.rebuild();
+                Ok(json!({ "loaded": loaded }))
+            }
+            "calculate" => {
+                let params: CalculateParams = Self::params(params)?;
+                let calculator = self.engine.calculator();
+                let result = match &params.policy {
+                    Some(policy) => calculator.calculate_with_policy(
+                        params.demands,
+                        params.supplies,
+                        params.inventories,
+                        policy,
+                    ),
+                    None => {
+                        calculator.calculate(params.demands, params.supplies, params.inventories)
+                    }
+                }
+                .map_err(|e| (CALCULATION_ERROR, e.to_string()))?;
+
+                serde_json::to_value(CalculateResult {
+                    planned_orders: &result.planned_orders,
+                    warnings: &result.warnings,
+                    unprocessed_components: &result.unprocessed_components,
+                    calculation_time_ms: result.calculation_time_ms,
+                })
+                .map_err(|e| (CALCULATION_ERROR, e.to_string()))
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("未知方法: {}", method))),
+        }
+    }
+
+    /// 以目前的 BOM 圖、物料配置與日曆重建引擎
+    fn rebuild(&mut self) {
+        self.engine = Engine::builder()
+            .with_shared_bom_graph(Arc::clone(&self.bom_graph))
+            .with_configs(self.configs.clone())
+            .with_calendar(self.calendar.clone())
+            .build();
+    }
+
+    fn bom_item(line: BomLine) -> BomItem {
+        BomItem {
+            id: uuid::Uuid::new_v4(),
+            parent_id: ComponentId::new(line.parent_id),
+            child_id: ComponentId::new(line.child_id),
+            quantity: line.quantity,
+            scrap_factor: line.scrap_factor,
+            sequence: 10,
+            operation_sequence: None,
+            is_phantom: false,
+            effective_from: None,
+            effective_to: None,
+            alternative_group: None,
+            alternative_priority: None,
+            reference_designator: None,
+            position: None,
+            notes: None,
+            version: 1,
+        }
+    }
+
+    fn params<T: DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+        serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))
+    }
+
+    fn response(id: Value, outcome: Result<Value, (i64, String)>) -> Value {
+        match outcome {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        }
+    }
+}
+
+/// 欄位存在即為 Some（包含 `null`），缺少時由 `#[serde(default)]` 給 None
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use mrp_core::{DemandType, ProcurementType};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_serve_line_delimited_requests() {
+        let config = MrpConfig::new("BIKE-001".to_string(), 5, ProcurementType::Make);
+        let demand = Demand::new(
+            "BIKE-001".to_string(),
+            Decimal::from(100),
+            NaiveDate::from_ymd_opt(2025, 11, 20).unwrap(),
+            DemandType::SalesOrder,
+        );
+        let input = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "configure", "params": {"configs": [config]}})
+                .to_string(),
+            "not json".to_string(),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "explode"}).to_string(),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "calculate", "params": {"demands": [demand]}})
+                .to_string(),
+            json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown"}).to_string(),
+            json!({"jsonrpc": "2.0", "id": 5, "method": "calculate", "params": {"demands": []}})
+                .to_string(),
+        ]
+        .join("\n");
+
+        let mut server =
+            ProtocolServer::new(Arc::new(BomGraph::new()), WorkCalendar::fallback_calendar());
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        // shutdown 之後的請求不處理
+        assert_eq!(responses.len(), 5);
+        assert_eq!(responses[0]["result"]["configured"], 1);
+        assert_eq!(responses[1]["error"]["code"], PARSE_ERROR);
+        assert_eq!(responses[2]["error"]["code"], METHOD_NOT_FOUND);
+        let orders = responses[3]["result"]["planned_orders"].as_array().unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0]["order_date"], "2025-11-15");
+        assert_eq!(responses[4]["id"], 4);
+        assert!(responses[4]["result"].is_null());
+    }
+
+    #[test]
+    fn test_load_bom_and_skip_notification_replies() {
+        let configs = vec![
+            MrpConfig::new("BIKE-001".to_string(), 5, ProcurementType::Make),
+            MrpConfig::new("WHEEL-001".to_string(), 3, ProcurementType::Buy),
+        ];
+        let demand = Demand::new(
+            "BIKE-001".to_string(),
+            Decimal::from(10),
+            NaiveDate::from_ymd_opt(2025, 11, 20).unwrap(),
+            DemandType::SalesOrder,
+        );
+        let input = [
+            // 通知：執行但不回應
+            json!({"jsonrpc": "2.0", "method": "configure", "params": {"configs": configs}})
+                .to_string(),
+            json!({"jsonrpc": "2.0", "id": 1, "method": "load_bom", "params": {"items": [
+                {"parent_id": "BIKE-001", "child_id": "WHEEL-001", "quantity": "2"}
+            ]}})
+            .to_string(),
+            json!({"jsonrpc": "2.0", "id": null, "method": "calculate", "params": {"demands": [demand]}})
+                .to_string(),
+        ]
+        .join("\n");
+
+        let mut server =
+            ProtocolServer::new(Arc::new(BomGraph::new()), WorkCalendar::fallback_calendar());
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"]["loaded"], 1);
+        // id 為 null 的請求仍回應；子件按 BOM 展開
+        assert!(responses[1]["id"].is_null());
+        let orders = responses[1]["result"]["planned_orders"].as_array().unwrap();
+        assert!(orders
+            .iter()
+            .any(|o| o["component_id"] == "WHEEL-001" && o["quantity"] == "20"));
+    }
+}
@@ -1,44 +1,41 @@
 //! 簡單 MRP 計算示例
+//!
+//! 以 `mrp-examples` 產生小型範例資料集（2 個成品、2 階 BOM），執行計算並列出結果
 
-use chrono::NaiveDate;
-use mrp_core::{Demand, DemandType, MrpConfig, ProcurementType, WorkCalendar};
-use rust_decimal::Decimal;
-use std::collections::HashMap;
+use mrp_examples::{SampleDataset, SampleSpec};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("=== 簡單 MRP 計算示例 ===\n");
 
-    // 創建工作日曆
-    let calendar = WorkCalendar::new("DEFAULT".to_string());
-
-    // 創建 MRP 配置
-    let mut configs = HashMap::new();
-    configs.insert(
-        "BIKE-001".to_string(),
-        MrpConfig::new("BIKE-001".to_string(), 5, ProcurementType::Make)
-            .with_safety_stock(Decimal::from(10)),
+    // 產生範例資料集（固定亂數種子，每次執行結果相同）
+    let dataset = SampleDataset::generate(&SampleSpec::small());
+    println!(
+        "範例資料: {} 個物料、{} 筆需求、{} 筆在途供應",
+        dataset.item_count(),
+        dataset.demands.len(),
+        dataset.supplies.len()
     );
 
-    // 創建需求
-    let demands = vec![Demand::new(
-        "BIKE-001".to_string(),
-        Decimal::from(100),
-        NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
-        DemandType::SalesOrder,
-    )
-    .with_source_ref("SO-001".to_string())
-    .with_priority(5)];
-
-    println!("需求清單:");
-    for demand in &demands {
+    println!("\n需求清單（前 5 筆）:");
+    for demand in dataset.demands.iter().take(5) {
         println!(
             "  - 物料: {}, 數量: {}, 需求日期: {}",
             demand.component_id, demand.quantity, demand.required_date
         );
     }
 
-    // TODO: 執行 MRP 計算（需要 BOM 圖）
-    println!("\n註：完整的 MRP 計算需要 BOM 圖數據");
+    // 執行 MRP 計算
+    let result = dataset.run()?;
+    println!(
+        "\n計劃訂單（前 10 筆，共 {} 筆）:",
+        result.planned_orders.len()
+    );
+    for order in result.planned_orders.iter().take(10) {
+        println!(
+            "  - 物料: {}, 數量: {}, 下單日: {}, 到貨日: {}",
+            order.component_id, order.quantity, order.order_date, order.required_date
+        );
+    }
 
     Ok(())
 }